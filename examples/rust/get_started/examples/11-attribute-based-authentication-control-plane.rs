@@ -1,7 +1,10 @@
 use hello_ockam::{create_token, import_project};
 use ockam::identity::authenticated_storage::AuthenticatedAttributeStorage;
 use ockam::identity::credential::OneTimeCode;
-use ockam::identity::{Identity, SecureChannelTrustOptions, TrustEveryonePolicy, TrustMultiIdentifiersPolicy};
+use ockam::identity::{
+    Identity, SecureChannelTrustOptions, TrustMultiIdentifiersPolicy, TrustOnFirstUsePolicy,
+    DEFAULT_SECURE_CHANNEL_TIMEOUT,
+};
 
 use ockam::abac::AbacAccessControl;
 use ockam::remote::RemoteForwarder;
@@ -10,7 +13,6 @@ use ockam_api::authenticator::direct::{CredentialIssuerClient, RpcClient, TokenA
 use ockam_api::{create_tcp_session, DefaultAddress};
 use ockam_core::AllowAll;
 use std::sync::Arc;
-use std::time::Duration;
 
 /// This node supports a "control" server on which several "edge" devices can connect
 ///
@@ -71,7 +73,7 @@ async fn start_node(ctx: Context, project_information_path: &str, token: OneTime
     // create a secure channel to the authority
     // when creating the channel we check that the opposite side is indeed presenting the authority identity
     let secure_channel = control_plane
-        .create_secure_channel_extended(tcp_session.route, trust_options, Duration::from_secs(120))
+        .create_secure_channel_extended(tcp_session.route, trust_options, DEFAULT_SECURE_CHANNEL_TIMEOUT)
         .await?;
 
     let token_acceptor = TokenAcceptorClient::new(
@@ -128,7 +130,7 @@ async fn start_node(ctx: Context, project_information_path: &str, token: OneTime
         .create_secure_channel_extended(
             tcp_project_session.route,
             project_trust_options,
-            Duration::from_secs(120),
+            DEFAULT_SECURE_CHANNEL_TIMEOUT,
         )
         .await?;
     println!("secure channel to project: {secure_channel_address:?}");
@@ -146,9 +148,12 @@ async fn start_node(ctx: Context, project_information_path: &str, token: OneTime
     println!("forwarder is {forwarder:?}");
 
     // 6. create a secure channel listener which will allow the edge node to
-    //    start a secure channel when it is ready
+    //    start a secure channel when it is ready.
+    //    The identity of the edge node is pinned on first contact, a different identity
+    //    connecting later on is rejected
+    let edge_plane_policy = TrustOnFirstUsePolicy::new("edge_plane", control_plane.authenticated_storage().clone());
     let _ = control_plane
-        .create_secure_channel_listener("untrusted", TrustEveryonePolicy)
+        .create_secure_channel_listener("untrusted", edge_plane_policy)
         .await?;
     println!("created a secure channel listener");
 
@@ -1,11 +1,13 @@
 use std::sync::Arc;
-use std::time::Duration;
 
 use hello_ockam::{create_token, import_project};
 use ockam::abac::AbacAccessControl;
 use ockam::identity::authenticated_storage::AuthenticatedAttributeStorage;
 use ockam::identity::credential::OneTimeCode;
-use ockam::identity::{Identity, SecureChannelTrustOptions, TrustEveryonePolicy, TrustMultiIdentifiersPolicy};
+use ockam::identity::{
+    Identity, SecureChannelTrustOptions, TrustEveryonePolicy, TrustMultiIdentifiersPolicy,
+    DEFAULT_SECURE_CHANNEL_TIMEOUT,
+};
 use ockam::{route, vault::Vault, Context, Result, TcpTransport};
 use ockam_api::authenticator::direct::{CredentialIssuerClient, RpcClient, TokenAcceptorClient};
 use ockam_api::{create_tcp_session, DefaultAddress};
@@ -70,7 +72,11 @@ async fn start_node(ctx: Context, project_information_path: &str, token: OneTime
     // create a secure channel to the authority
     // when creating the channel we check that the opposite side is indeed presenting the authority identity
     let secure_channel = edge_plane
-        .create_secure_channel_extended(tcp_authority_session.route, trust_options, Duration::from_secs(120))
+        .create_secure_channel_extended(
+            tcp_authority_session.route,
+            trust_options,
+            DEFAULT_SECURE_CHANNEL_TIMEOUT,
+        )
         .await?;
 
     let token_acceptor = TokenAcceptorClient::new(
@@ -121,7 +127,7 @@ async fn start_node(ctx: Context, project_information_path: &str, token: OneTime
         .create_secure_channel_extended(
             tcp_project_session.route,
             project_trust_options,
-            Duration::from_secs(120),
+            DEFAULT_SECURE_CHANNEL_TIMEOUT,
         )
         .await?;
     println!("secure channel address to the project: {secure_channel_address:?}");
@@ -140,7 +146,7 @@ async fn start_node(ctx: Context, project_information_path: &str, token: OneTime
         .create_secure_channel_extended(
             secure_channel_listener_route.clone(),
             TrustEveryonePolicy,
-            Duration::from_secs(120),
+            DEFAULT_SECURE_CHANNEL_TIMEOUT,
         )
         .await?;
 
@@ -49,7 +49,16 @@ impl Project {
 /// Import a project identity into a Vault from a project.json path
 /// and return a Project struct
 pub async fn import_project(path: &str, vault: &Vault) -> Result<Project> {
-    match read_json(path)? {
+    let json = read_json(path)?;
+    let errors = validate_project_json(&json, vault).await;
+    if !errors.is_empty() {
+        let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        return Err(error(
+            format!("invalid project file {path}: {}", errors.join(", ")).as_str(),
+        ));
+    }
+
+    match json {
         Value::Object(values) => {
             let project_identifier = IdentityIdentifier::from_str(get_field_as_str(&values, "identity")?.as_str())?;
 
@@ -76,6 +85,92 @@ pub async fn import_project(path: &str, vault: &Vault) -> Result<Project> {
     }
 }
 
+/// Error found in a field of a project.json file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectFieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl ProjectFieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        ProjectFieldError {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ProjectFieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Check a project.json file before importing it with `import_project`
+/// and return all the errors found, per field
+pub async fn validate_project(path: &str, vault: &Vault) -> Result<Vec<ProjectFieldError>> {
+    Ok(validate_project_json(&read_json(path)?, vault).await)
+}
+
+/// Check the structure of the json data exported when running `ockam project information`,
+/// the authority identity and the routes, and return all the errors found, per field
+pub async fn validate_project_json(json: &Value, vault: &Vault) -> Vec<ProjectFieldError> {
+    let values = match json {
+        Value::Object(values) => values,
+        _ => return vec![ProjectFieldError::new("project", "expected a json object")],
+    };
+
+    let mut errors = vec![];
+
+    match get_field_as_str(values, "identity") {
+        Ok(identity) => {
+            if let Err(e) = IdentityIdentifier::from_str(identity.as_str()) {
+                errors.push(ProjectFieldError::new("identity", format!("invalid identifier: {e}")));
+            }
+        }
+        Err(_) => errors.push(missing_field("identity")),
+    }
+
+    match get_field_as_str(values, "authority_identity") {
+        Ok(authority_identity) => match hex::decode(authority_identity) {
+            Ok(authority_identity) => {
+                if let Err(e) = PublicIdentity::import(&authority_identity, vault).await {
+                    errors.push(ProjectFieldError::new(
+                        "authority_identity",
+                        format!("invalid identity: {e}"),
+                    ));
+                }
+            }
+            Err(e) => errors.push(ProjectFieldError::new(
+                "authority_identity",
+                format!("invalid hex: {e}"),
+            )),
+        },
+        Err(_) => errors.push(missing_field("authority_identity")),
+    }
+
+    for field_name in ["authority_access_route", "access_route"] {
+        match get_field_as_str(values, field_name) {
+            Ok(route) => {
+                if let Err(e) = MultiAddr::from_str(route.as_str()) {
+                    errors.push(ProjectFieldError::new(
+                        field_name,
+                        format!("incorrect multi address: {e}"),
+                    ));
+                }
+            }
+            Err(_) => errors.push(missing_field(field_name)),
+        }
+    }
+
+    errors
+}
+
+fn missing_field(field_name: &str) -> ProjectFieldError {
+    ProjectFieldError::new(field_name, "missing field or not a string")
+}
+
 /// Read the contents of a file as JSON
 fn read_json(path: &str) -> Result<Value> {
     let mut file = File::open(path).map_err(|_| error("Unable to open the file at {path}"))?;
@@ -99,3 +194,87 @@ fn get_field_as_str(values: &Map<String, Value>, field_name: &str) -> Result<Str
 fn error(message: &str) -> Error {
     Error::new(Origin::Application, Kind::Invalid, anyhow!(message.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::identity::Identity;
+    use ockam::Context;
+    use serde_json::json;
+
+    async fn valid_project(ctx: &Context, vault: &Vault) -> Result<Value> {
+        let project = Identity::create(ctx, vault).await?;
+        let authority = Identity::create(ctx, vault).await?;
+        Ok(json!({
+            "identity": project.identifier().to_string(),
+            "authority_identity": hex::encode(authority.export().await?),
+            "authority_access_route": "/dnsaddr/localhost/tcp/4000/service/api",
+            "access_route": "/dnsaddr/localhost/tcp/4000/service/api",
+        }))
+    }
+
+    fn fields(errors: Vec<ProjectFieldError>) -> Vec<String> {
+        errors.into_iter().map(|e| e.field).collect()
+    }
+
+    #[ockam::test]
+    async fn valid_project_has_no_errors(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let project = valid_project(ctx, &vault).await?;
+        assert!(validate_project_json(&project, &vault).await.is_empty());
+        ctx.stop().await
+    }
+
+    #[ockam::test]
+    async fn malformed_projects_report_each_field(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let not_an_object = json!(["identity"]);
+        assert_eq!(
+            fields(validate_project_json(&not_an_object, &vault).await),
+            vec!["project"]
+        );
+
+        let empty = json!({});
+        assert_eq!(
+            fields(validate_project_json(&empty, &vault).await),
+            vec![
+                "identity",
+                "authority_identity",
+                "authority_access_route",
+                "access_route"
+            ]
+        );
+
+        let mut bad_identifier = valid_project(ctx, &vault).await?;
+        bad_identifier["identity"] = json!("not an identifier");
+        assert_eq!(
+            fields(validate_project_json(&bad_identifier, &vault).await),
+            vec!["identity"]
+        );
+
+        let mut bad_hex = valid_project(ctx, &vault).await?;
+        bad_hex["authority_identity"] = json!("zz");
+        assert_eq!(
+            fields(validate_project_json(&bad_hex, &vault).await),
+            vec!["authority_identity"]
+        );
+
+        let mut bad_authority = valid_project(ctx, &vault).await?;
+        bad_authority["authority_identity"] = json!("0102030405");
+        assert_eq!(
+            fields(validate_project_json(&bad_authority, &vault).await),
+            vec!["authority_identity"]
+        );
+
+        let mut bad_routes = valid_project(ctx, &vault).await?;
+        bad_routes["authority_access_route"] = json!("/not/a/route");
+        bad_routes["access_route"] = json!(4000);
+        assert_eq!(
+            fields(validate_project_json(&bad_routes, &vault).await),
+            vec!["authority_access_route", "access_route"]
+        );
+
+        ctx.stop().await
+    }
+}
@@ -26,6 +26,8 @@ pub use ockam_macros::{node, test};
 // ---
 
 // Export node implementation
+#[cfg(feature = "std")]
+pub use ockam_node::logging;
 pub use ockam_node::{debugger, Context, DelayedEvent, Executor, NodeBuilder, WorkerBuilder};
 // ---
 
@@ -8,12 +8,14 @@ use ockam_core::Result;
 use ockam_core::{IncomingAccessControl, RelayMessage};
 use tracing as log;
 
-use crate::expr::str;
+use crate::expr::{and, str};
+use crate::statistics::PolicyStatistics;
 use crate::Expr::*;
-use crate::{eval, Env, Expr};
+use crate::{eval_with_missing_attributes, AbacStatistics, Env, Expr, MissingAttributes};
 use ockam_core::compat::boxed::Box;
 use ockam_core::compat::format;
-use ockam_core::compat::string::ToString;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::Vec;
 use ockam_identity::authenticated_storage::{
     AuthenticatedAttributeStorage, AuthenticatedStorage, IdentityAttributeStorage,
 };
@@ -27,6 +29,8 @@ pub struct AbacAccessControl<S> {
     attributes: S,
     expression: Expr,
     environment: Env,
+    statistics: Option<PolicyStatistics>,
+    missing_attributes: MissingAttributes,
 }
 
 /// Debug implementation printing out the policy expression only
@@ -44,9 +48,31 @@ impl<S> AbacAccessControl<S> {
             attributes,
             expression,
             environment,
+            statistics: None,
+            missing_attributes: MissingAttributes::default(),
         }
     }
 
+    /// Count how often each condition of the policy expression is met in `statistics`
+    pub fn with_statistics(self, statistics: AbacStatistics) -> Self {
+        let statistics = statistics.policy(&self.expression);
+        self.with_policy_statistics(statistics)
+    }
+
+    /// Count how often each condition of the policy expression is met in counters
+    /// looked up beforehand, see [`AbacStatistics::policy`]
+    pub(crate) fn with_policy_statistics(mut self, statistics: PolicyStatistics) -> Self {
+        self.statistics = Some(statistics);
+        self
+    }
+
+    /// Set how the conditions referencing an attribute the sender lacks are evaluated.
+    /// By default, the whole policy denies access.
+    pub fn with_missing_attributes(mut self, missing_attributes: MissingAttributes) -> Self {
+        self.missing_attributes = missing_attributes;
+        self
+    }
+
     /// Create an AccessControl which will verify that the sender of
     /// a message has an authenticated attribute with the correct name and value
     pub fn create(
@@ -68,6 +94,41 @@ impl<S> AbacAccessControl<S> {
             Env::new(),
         )
     }
+
+    /// Create an AccessControl which will verify that the sender of
+    /// a message has an authenticated attribute with the correct name,
+    /// whose value is any of `attribute_values`
+    pub fn create_with_values(
+        storage: &S,
+        attribute_name: &str,
+        attribute_values: Vec<String>,
+    ) -> AbacAccessControl<AuthenticatedAttributeStorage<S>>
+    where
+        S: AuthenticatedStorage + Clone,
+    {
+        let expression = List(vec![
+            Ident("member?".into()),
+            Ident(format!("subject.{attribute_name}")),
+            Seq(attribute_values.into_iter().map(Str).collect()),
+        ]);
+        AbacAccessControl::new(
+            AuthenticatedAttributeStorage::new(storage.clone()),
+            expression,
+            Env::new(),
+        )
+    }
+
+    /// Combine this AccessControl with `other`, so that access is only granted
+    /// when both policy expressions are valid.
+    ///
+    /// The attributes storage, statistics and handling of missing attributes of
+    /// this AccessControl are kept. When both environments bind the same name,
+    /// the binding of this AccessControl is kept.
+    pub fn and(mut self, other: AbacAccessControl<S>) -> Self {
+        self.expression = and([self.expression, other.expression]);
+        self.environment.merge_left(other.environment);
+        self
+    }
 }
 
 #[async_trait]
@@ -130,8 +191,13 @@ where
         // add the identifier itself as a subject parameter
         environment.put("subject.identifier", str(id.to_string()));
 
+        if let Some(statistics) = &self.statistics {
+            statistics.record(&environment);
+        }
+
         // Finally, evaluate the expression and return the result:
-        match eval(&self.expression, &environment) {
+        match eval_with_missing_attributes(&self.expression, &environment, self.missing_attributes)
+        {
             Ok(Expr::Bool(b)) => {
                 log::debug! {
                     policy        = %self.expression,
@@ -162,3 +228,78 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::AbacAccessControl;
+    use crate::expr::str;
+    use crate::{eval, Env, Expr};
+    use ockam_identity::authenticated_storage::mem::InMemoryStorage;
+
+    fn is_authorized<S>(
+        access_control: &AbacAccessControl<S>,
+        attributes: &[(&str, &str)],
+    ) -> bool {
+        let mut env = access_control.environment.clone();
+        for (key, value) in attributes {
+            env.put(format!("subject.{key}"), str(*value));
+        }
+        matches!(eval(&access_control.expression, &env), Ok(Expr::Bool(true)))
+    }
+
+    fn values(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn any_listed_value_matches() {
+        let storage = InMemoryStorage::new();
+        let access_control = AbacAccessControl::create_with_values(
+            &storage,
+            "component",
+            values(&["edge", "control"]),
+        );
+        assert!(is_authorized(&access_control, &[("component", "edge")]));
+        assert!(is_authorized(&access_control, &[("component", "control")]));
+    }
+
+    #[test]
+    fn unlisted_value_does_not_match() {
+        let storage = InMemoryStorage::new();
+        let access_control = AbacAccessControl::create_with_values(
+            &storage,
+            "component",
+            values(&["edge", "control"]),
+        );
+        assert!(!is_authorized(&access_control, &[("component", "cloud")]));
+        assert!(!is_authorized(&access_control, &[("region", "edge")]));
+    }
+
+    #[test]
+    fn combined_checks_must_all_match() {
+        let storage = InMemoryStorage::new();
+        let access_control = AbacAccessControl::create(&storage, "component", "edge").and(
+            AbacAccessControl::create_with_values(&storage, "region", values(&["us", "eu"])),
+        );
+        assert!(is_authorized(
+            &access_control,
+            &[("component", "edge"), ("region", "eu")]
+        ));
+        assert!(!is_authorized(
+            &access_control,
+            &[("component", "edge"), ("region", "asia")]
+        ));
+        assert!(!is_authorized(
+            &access_control,
+            &[("component", "control"), ("region", "us")]
+        ));
+    }
+
+    #[test]
+    fn single_value_constructor_still_matches() {
+        let storage = InMemoryStorage::new();
+        let access_control = AbacAccessControl::create(&storage, "component", "edge");
+        assert!(is_authorized(&access_control, &[("component", "edge")]));
+        assert!(!is_authorized(&access_control, &[("component", "control")]));
+    }
+}
@@ -6,8 +6,52 @@ use crate::expr::{unit, Expr};
 use ockam_core::compat::string::ToString;
 use ockam_core::compat::vec::Vec;
 
-#[rustfmt::skip]
+/// How the conditions of a policy referencing an attribute missing from the
+/// environment are evaluated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingAttributes {
+    /// The evaluation fails, so the whole policy denies access, even if another
+    /// alternative of an `or` would have granted it
+    #[default]
+    Deny,
+    /// An alternative of an `or` which is a comparison (`=`, `!=`, `<`, `>` or
+    /// `member?`) with a missing attribute as argument evaluates to false, so the
+    /// other alternatives still apply. A missing attribute anywhere else, e.g. under
+    /// a `not`, denies access like [`MissingAttributes::Deny`]
+    NoMatch,
+}
+
+/// Value of the alternatives of an `or` which don't match, see [`MissingAttributes::NoMatch`]
+static NO_MATCH: Expr = Expr::Bool(false);
+
+/// Whether `x` is a comparison with an attribute missing from `env` as argument
+fn is_comparison_with_missing_attribute(x: &Expr, env: &Env) -> bool {
+    match x {
+        Expr::List(xs) => match &xs[..] {
+            [Expr::Ident(id), args @ ..] => {
+                matches!(id.as_str(), "=" | "!=" | "<" | ">" | "member?")
+                    && args
+                        .iter()
+                        .any(|a| matches!(a, Expr::Ident(a) if !env.contains(a)))
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
+    eval_with_missing_attributes(expr, env, MissingAttributes::Deny)
+}
+
+/// Evaluate `expr` like [`eval`], with `missing` deciding how the conditions
+/// referencing attributes missing from `env` are evaluated
+#[rustfmt::skip]
+pub fn eval_with_missing_attributes(
+    expr: &Expr,
+    env: &Env,
+    missing: MissingAttributes
+) -> Result<Expr, EvalError> {
     /// A stack operation.
     ///
     /// Each operation uses the arguments stack as input. The number of
@@ -26,6 +70,15 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
         Seq(usize),
     }
 
+    /// Operation evaluating the alternative `x` of an `or`
+    fn alternative<'a>(x: &'a Expr, env: &Env, missing: MissingAttributes) -> Op<'a> {
+        if missing == MissingAttributes::NoMatch && is_comparison_with_missing_attribute(x, env) {
+            Op::Eval(&NO_MATCH)
+        } else {
+            Op::Eval(x)
+        }
+    }
+
     // Control stack.
     let mut ctrl: Vec<Op> = Vec::new();
     // Arguments stack.
@@ -71,14 +124,18 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                             // itself behind each successive argument, stopping
                             // evaluation as soon as an argument evaluates to
                             // true.
+                            //
+                            // With `MissingAttributes::NoMatch`, the alternatives
+                            // which can't be evaluated for lack of an attribute
+                            // are evaluated as false instead.
                             if nargs == 0 {
                                 args.push(Expr::Bool(false))
                             } else {
                                 for x in xs[2 ..].iter().rev() {
-                                    ctrl.push(Op::Eval(x))
+                                    ctrl.push(alternative(x, env, missing))
                                 }
                                 ctrl.push(Op::Or(nargs - 1));
-                                ctrl.push(Op::Eval(&xs[1]))
+                                ctrl.push(alternative(&xs[1], env, missing))
                             }
                             continue
                         }
@@ -292,3 +349,66 @@ where
     args.push(Expr::Bool(b));
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::{eval, eval_with_missing_attributes, MissingAttributes};
+    use crate::expr::{eq, ident, or, str};
+    use crate::{Env, Expr};
+
+    #[test]
+    fn or_policy_with_a_missing_attribute() {
+        let policy = or([
+            eq([ident("subject.role"), str("admin")]),
+            eq([ident("subject.team"), str("ops")]),
+        ]);
+        let mut env = Env::new();
+        env.put("subject.team", str("ops"));
+
+        // The first alternative references the missing role
+        assert!(eval(&policy, &env).unwrap_err().is_unbound());
+        assert!(
+            eval_with_missing_attributes(&policy, &env, MissingAttributes::Deny)
+                .unwrap_err()
+                .is_unbound()
+        );
+        assert!(matches!(
+            eval_with_missing_attributes(&policy, &env, MissingAttributes::NoMatch),
+            Ok(Expr::Bool(true))
+        ));
+
+        // No alternative matches
+        env.put("subject.team", str("dev"));
+        assert!(matches!(
+            eval_with_missing_attributes(&policy, &env, MissingAttributes::NoMatch),
+            Ok(Expr::Bool(false))
+        ));
+    }
+
+    #[test]
+    fn negated_comparison_with_a_missing_attribute_is_denied() {
+        let policy = eq([ident("subject.role"), str("admin")]);
+        let policy = Expr::List(vec![ident("not"), policy]);
+        let inequality = Expr::List(vec![ident("!="), ident("subject.role"), str("admin")]);
+        let env = Env::new();
+
+        // Outside of an `or`, a comparison with a missing attribute denies access
+        assert!(
+            eval_with_missing_attributes(&inequality, &env, MissingAttributes::NoMatch)
+                .unwrap_err()
+                .is_unbound()
+        );
+        // Negating it must not grant access either, including as an alternative
+        assert!(
+            eval_with_missing_attributes(&policy, &env, MissingAttributes::NoMatch)
+                .unwrap_err()
+                .is_unbound()
+        );
+        let policy = or([policy, eq([ident("subject.team"), str("ops")])]);
+        assert!(
+            eval_with_missing_attributes(&policy, &env, MissingAttributes::NoMatch)
+                .unwrap_err()
+                .is_unbound()
+        );
+    }
+}
@@ -7,6 +7,7 @@ mod env;
 mod error;
 mod eval;
 mod policy;
+mod statistics;
 mod traits;
 mod types;
 
@@ -20,9 +21,10 @@ pub mod mem;
 pub use attribute_access_control::AbacAccessControl;
 pub use env::Env;
 pub use error::{EvalError, ParseError};
-pub use eval::eval;
+pub use eval::{eval, eval_with_missing_attributes, MissingAttributes};
 pub use expr::Expr;
 pub use policy::PolicyAccessControl;
+pub use statistics::{AbacStatistics, ConditionStatistics};
 pub use traits::PolicyStorage;
 pub use types::{Action, Resource, Subject};
 
@@ -1,13 +1,15 @@
 use core::fmt;
 use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::RwLock;
 use ockam_core::{async_trait, RelayMessage};
 use ockam_core::{IncomingAccessControl, Result};
 use ockam_identity::authenticated_storage::IdentityAttributeStorage;
 use tracing as log;
 
+use crate::statistics::PolicyStatistics;
 use crate::traits::PolicyStorage;
 use crate::types::{Action, Resource};
-use crate::AbacAccessControl;
+use crate::{AbacAccessControl, AbacStatistics, MissingAttributes};
 use crate::{Env, Expr};
 
 /// Evaluates a policy expression against an environment of attributes.
@@ -21,6 +23,11 @@ pub struct PolicyAccessControl<P, S> {
     policies: P,
     attributes: S,
     environment: Env,
+    statistics: Option<AbacStatistics>,
+    /// Counters of the conditions of the last policy evaluated, looked up again only
+    /// when the policy changes
+    policy_statistics: RwLock<Option<(Expr, PolicyStatistics)>>,
+    missing_attributes: MissingAttributes,
 }
 
 impl<P, S> PolicyAccessControl<P, S> {
@@ -36,8 +43,41 @@ impl<P, S> PolicyAccessControl<P, S> {
             policies,
             attributes: store,
             environment: env,
+            statistics: None,
+            policy_statistics: RwLock::new(None),
+            missing_attributes: MissingAttributes::default(),
         }
     }
+
+    /// Count how often each condition of the evaluated policies is met in `statistics`
+    pub fn with_statistics(mut self, statistics: AbacStatistics) -> Self {
+        self.statistics = Some(statistics);
+        self
+    }
+
+    /// Set how the conditions referencing an attribute the sender lacks are evaluated,
+    /// see [`AbacAccessControl::with_missing_attributes`]
+    pub fn with_missing_attributes(mut self, missing_attributes: MissingAttributes) -> Self {
+        self.missing_attributes = missing_attributes;
+        self
+    }
+
+    /// Return the counters of the conditions of `expr`, if statistics are kept
+    fn policy_statistics(&self, expr: &Expr) -> Option<PolicyStatistics> {
+        let statistics = self.statistics.as_ref()?;
+        if let Ok(cached) = self.policy_statistics.read() {
+            if let Some((cached, policy_statistics)) = cached.as_ref() {
+                if cached.equals(expr).unwrap_or(false) {
+                    return Some(policy_statistics.clone());
+                }
+            }
+        }
+        let policy_statistics = statistics.policy(expr);
+        if let Ok(mut cached) = self.policy_statistics.write() {
+            *cached = Some((expr.clone(), policy_statistics.clone()));
+        }
+        Some(policy_statistics)
+    }
 }
 
 #[async_trait]
@@ -70,12 +110,16 @@ where
             return Ok(false);
         };
 
-        AbacAccessControl::new(
+        let statistics = self.policy_statistics(&expr);
+        let mut access_control = AbacAccessControl::new(
             self.attributes.async_try_clone().await?,
             expr,
             self.environment.clone(),
         )
-        .is_authorized(msg)
-        .await
+        .with_missing_attributes(self.missing_attributes);
+        if let Some(statistics) = statistics {
+            access_control = access_control.with_policy_statistics(statistics);
+        }
+        access_control.is_authorized(msg).await
     }
 }
@@ -0,0 +1,159 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+
+use crate::{eval, Env, Expr};
+
+/// Number of times a policy condition was met or not
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConditionStatistics {
+    /// Number of evaluations where the condition was met
+    pub matched: u64,
+    /// Number of evaluations where the condition was not met
+    pub denied: u64,
+}
+
+/// Statistics of the conditions of the policies evaluated by the ABAC access
+/// controls sharing them, e.g. `(= subject.component "control")`
+///
+/// The conditions are the expressions combined by `and`, `or`, `not` and `if`.
+/// Each of them is evaluated on its own, so a condition is counted even if the
+/// evaluation of the whole policy doesn't need it. This makes the evaluation of
+/// a policy more expensive, so statistics are only kept by the access controls
+/// they are given to.
+#[derive(Clone, Debug, Default)]
+pub struct AbacStatistics {
+    conditions: Arc<RwLock<BTreeMap<String, Arc<ConditionCounters>>>>,
+}
+
+impl AbacStatistics {
+    /// Create empty statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the statistics of every condition evaluated so far, keyed by condition
+    pub fn conditions(&self) -> BTreeMap<String, ConditionStatistics> {
+        match self.conditions.read() {
+            Ok(conditions) => conditions
+                .iter()
+                .map(|(condition, counters)| (condition.clone(), counters.snapshot()))
+                .collect(),
+            Err(_) => BTreeMap::new(),
+        }
+    }
+
+    /// Return the counters of the conditions of `expr`, registering the new ones.
+    /// This is done once per policy, evaluating it only updates the counters.
+    pub(crate) fn policy(&self, expr: &Expr) -> PolicyStatistics {
+        let mut conditions = Vec::new();
+        collect_conditions(expr, &mut conditions);
+
+        let mut counters: Vec<(Expr, Arc<ConditionCounters>)> = Vec::new();
+        if let Ok(mut registered) = self.conditions.write() {
+            for condition in conditions {
+                let counter = registered.entry(condition.to_string()).or_default().clone();
+                // A condition appearing several times in a policy is counted once
+                if !counters.iter().any(|(_, c)| Arc::ptr_eq(c, &counter)) {
+                    counters.push((condition.clone(), counter))
+                }
+            }
+        }
+        PolicyStatistics {
+            conditions: counters.into(),
+        }
+    }
+}
+
+/// Counters of a condition, shared by the policies it appears in
+#[derive(Debug, Default)]
+struct ConditionCounters {
+    matched: AtomicUsize,
+    denied: AtomicUsize,
+}
+
+impl ConditionCounters {
+    fn snapshot(&self) -> ConditionStatistics {
+        ConditionStatistics {
+            matched: self.matched.load(Ordering::Relaxed) as u64,
+            denied: self.denied.load(Ordering::Relaxed) as u64,
+        }
+    }
+}
+
+/// Counters of the conditions of a policy, see [`AbacStatistics::policy`]
+#[derive(Clone, Debug)]
+pub(crate) struct PolicyStatistics {
+    conditions: Arc<[(Expr, Arc<ConditionCounters>)]>,
+}
+
+impl PolicyStatistics {
+    /// Count whether each condition of the policy is met in `env`
+    pub(crate) fn record(&self, env: &Env) {
+        for (condition, counters) in self.conditions.iter() {
+            if matches!(eval(condition, env), Ok(Expr::Bool(true))) {
+                counters.matched.fetch_add(1, Ordering::Relaxed);
+            } else {
+                counters.denied.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+fn collect_conditions<'a>(expr: &'a Expr, conditions: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::List(xs) => match &xs[..] {
+            [Expr::Ident(op), args @ ..] if matches!(op.as_str(), "and" | "or" | "not" | "if") => {
+                for arg in args {
+                    collect_conditions(arg, conditions)
+                }
+            }
+            [] => {}
+            _ => conditions.push(expr),
+        },
+        Expr::Ident(_) => conditions.push(expr),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AbacStatistics, ConditionStatistics};
+    use crate::expr::{and, eq, ident, str};
+    use crate::Env;
+
+    #[test]
+    fn conditions_are_counted_separately() {
+        let statistics = AbacStatistics::new();
+        let policy = and([
+            eq([ident("subject.component"), str("control")]),
+            eq([ident("subject.project"), str("p1")]),
+        ]);
+
+        let mut env = Env::new();
+        env.put("subject.component", str("control"));
+        env.put("subject.project", str("p1"));
+        let policy_statistics = statistics.policy(&policy);
+        policy_statistics.record(&env);
+        env.put("subject.project", str("p2"));
+        policy_statistics.record(&env);
+
+        let conditions = statistics.conditions();
+        assert_eq!(
+            conditions[r#"(= subject.component "control")"#],
+            ConditionStatistics {
+                matched: 2,
+                denied: 0
+            }
+        );
+        assert_eq!(
+            conditions[r#"(= subject.project "p1")"#],
+            ConditionStatistics {
+                matched: 1,
+                denied: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Path of the crate in the ockam repository
+const CRATE_PATH: &str = "implementations/rust/ockam/ockam_api";
+
+/// Run git in `dir` and return its trimmed output, if it succeeds
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+/// Run the build script again when `path` changes. Missing files are skipped, as cargo
+/// would otherwise run the build script on every build.
+fn rerun_if_changed(path: &Path) {
+    if path.exists() {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+}
+
+/// Embed the commit the crate is built from, reported by the node version endpoint.
+///
+/// The commit is only read from the crate's own repository: when the crate is vendored
+/// in another repository, the commit of that repository would be wrong, and "unknown"
+/// is embedded instead. The build script runs again when `HEAD` or the branch it
+/// points to moves, so the embedded commit doesn't go stale.
+fn hash() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+
+    let git_hash = git(&manifest_dir, &["rev-parse", "--show-toplevel"])
+        .filter(|top| {
+            let expected = Path::new(top).join(CRATE_PATH);
+            match (expected.canonicalize(), manifest_dir.canonicalize()) {
+                (Ok(expected), Ok(actual)) => expected == actual,
+                _ => false,
+            }
+        })
+        .and_then(|_| {
+            let git_dir = PathBuf::from(git(&manifest_dir, &["rev-parse", "--absolute-git-dir"])?);
+            let head = git_dir.join("HEAD");
+            rerun_if_changed(&head);
+            if let Ok(head) = fs::read_to_string(&head) {
+                if let Some(reference) = head.trim().strip_prefix("ref: ") {
+                    rerun_if_changed(&git_dir.join(reference));
+                    rerun_if_changed(&git_dir.join("packed-refs"));
+                }
+            }
+            git(&manifest_dir, &["rev-parse", "HEAD"])
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=GIT_HASH={git_hash}");
+}
+
+fn main() {
+    hash();
+}
@@ -14,14 +14,17 @@ use ockam_core::api::{self, Error, Method, Request, RequestBuilder, Response, St
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{self, Address, CowStr, DenyAll, Result, Route, Routed, Worker};
 use ockam_node::Context;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
 use std::num::NonZeroUsize;
 use std::sync::{Arc, RwLock};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tracing::{trace, warn};
 use types::AddMember;
 
-use crate::authenticator::direct::types::CreateToken;
+use crate::authenticator::direct::types::{
+    CreateToken, OutstandingToken, OutstandingTokenList, RequestCredential, RotateTokens,
+};
 
 const MAX_TOKEN_DURATION: Duration = Duration::from_secs(600);
 const DEFAULT_CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
@@ -339,6 +342,7 @@ where
 pub struct EnrollmentTokenAuthenticator {
     project: Vec<u8>,
     tokens: Arc<RwLock<LruCache<[u8; 32], Token>>>,
+    generations: Arc<RwLock<TokenGenerations>>,
 }
 
 pub struct EnrollmentTokenIssuer(EnrollmentTokenAuthenticator);
@@ -354,6 +358,7 @@ impl EnrollmentTokenAuthenticator {
             tokens: Arc::new(RwLock::new(LruCache::new(
                 NonZeroUsize::new(128).expect("0 < 128"),
             ))),
+            generations: Default::default(),
         };
         (
             EnrollmentTokenIssuer(base.clone()),
@@ -369,10 +374,19 @@ impl EnrollmentTokenIssuer {
         attrs: HashMap<String, String>,
     ) -> Result<OneTimeCode> {
         let otc = OneTimeCode::new();
+        let generation = self.0.generations.read().map(|g| g.current).map_err(|_| {
+            ockam_core::Error::new(
+                Origin::Other,
+                Kind::Internal,
+                "failed to get read lock on token generations",
+            )
+        })?;
         let tkn = Token {
+            id: hex::encode(rand::random::<[u8; 8]>()),
             attrs,
             generated_by: enroller.clone(),
             time: Instant::now(),
+            generation,
         };
         self.0
             .tokens
@@ -389,6 +403,76 @@ impl EnrollmentTokenIssuer {
                 )
             })
     }
+
+    /// Issue the next tokens under a new generation. The tokens issued so far keep
+    /// being accepted for `grace_period`, so that enrollments in progress aren't broken.
+    fn rotate_tokens(&self, grace_period: Duration) -> Result<()> {
+        let mut generations = self.0.generations.write().map_err(|_| {
+            ockam_core::Error::new(
+                Origin::Other,
+                Kind::Internal,
+                "failed to get write lock on token generations",
+            )
+        })?;
+        generations.rotate(grace_period);
+        info!(
+            generation = generations.current,
+            grace_period_secs = grace_period.as_secs(),
+            "Rotated enrollment tokens"
+        );
+        Ok(())
+    }
+
+    /// Return the tokens which can still be redeemed, the first to expire first.
+    fn outstanding_tokens(&self) -> Result<Vec<OutstandingToken>> {
+        let tokens = self.0.tokens.read().map_err(|_| {
+            ockam_core::Error::new(
+                Origin::Other,
+                Kind::Internal,
+                "failed to get read lock on tokens table",
+            )
+        })?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut outstanding: Vec<OutstandingToken> = tokens
+            .iter()
+            .filter(|(_, tkn)| {
+                tkn.time.elapsed() <= MAX_TOKEN_DURATION
+                    && self.0.accepts_generation(tkn.generation)
+            })
+            .map(|(_, tkn)| {
+                let expires_at = now + MAX_TOKEN_DURATION.saturating_sub(tkn.time.elapsed());
+                OutstandingToken::new(tkn.id.clone(), tkn.attrs.clone(), expires_at.as_secs())
+            })
+            .collect();
+        outstanding.sort_by_key(|tkn| tkn.expires_at());
+        Ok(outstanding)
+    }
+
+    /// Revoke the token with the given id, so that it can't be redeemed anymore.
+    /// Return `false` if there is no such token.
+    fn revoke_token(&self, id: &str) -> Result<bool> {
+        let mut tokens = self.0.tokens.write().map_err(|_| {
+            ockam_core::Error::new(
+                Origin::Other,
+                Kind::Internal,
+                "failed to get write lock on tokens table",
+            )
+        })?;
+        let code = tokens
+            .iter()
+            .find(|(_, tkn)| tkn.id == id)
+            .map(|(code, _)| *code);
+        match code {
+            Some(code) => {
+                tokens.pop(&code);
+                info!(id, "Revoked enrollment token");
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
 }
 
 #[ockam_core::worker]
@@ -418,6 +502,30 @@ impl Worker for EnrollmentTokenIssuer {
                         Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
                     }
                 }
+                (Some(Method::Post), "/tokens/rotate") => {
+                    let grace_period = if req.has_body() {
+                        dec.decode::<RotateTokens>()?.grace_period()
+                    } else {
+                        None
+                    };
+                    match self.rotate_tokens(grace_period.unwrap_or(MAX_TOKEN_DURATION)) {
+                        Ok(()) => Response::ok(req.id()).to_vec()?,
+                        Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
+                    }
+                }
+                (Some(Method::Get), "/tokens") => match self.outstanding_tokens() {
+                    Ok(tokens) => Response::ok(req.id())
+                        .body(OutstandingTokenList::new(tokens))
+                        .to_vec()?,
+                    Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
+                },
+                (Some(Method::Delete), path) if path.starts_with("/tokens/") => {
+                    match self.revoke_token(&path["/tokens/".len()..]) {
+                        Ok(true) => Response::ok(req.id()).to_vec()?,
+                        Ok(false) => Response::not_found(req.id()).to_vec()?,
+                        Err(error) => api::internal_error(&req, &error.to_string()).to_vec()?,
+                    }
+                }
                 _ => api::unknown_path(&req).to_vec()?,
             };
             c.send(m.return_route(), res).await
@@ -458,6 +566,8 @@ where
                             if let Some(tkn) = r.pop(otc.code()) {
                                 if tkn.time.elapsed() > MAX_TOKEN_DURATION {
                                     Err(api::forbidden(&req, "expired token"))
+                                } else if !self.0.accepts_generation(tkn.generation) {
+                                    Err(api::forbidden(&req, "rotated token"))
                                 } else {
                                     Ok(tkn)
                                 }
@@ -503,9 +613,51 @@ where
 }
 
 struct Token {
+    /// Identifies the token when listing or revoking it, since its code is secret
+    id: String,
     attrs: HashMap<String, String>,
     generated_by: IdentityIdentifier,
     time: Instant,
+    generation: u64,
+}
+
+/// Generations enrollment tokens are issued under.
+///
+/// Tokens are issued under the current generation. When the tokens are rotated, the
+/// tokens of the current generation are only accepted until the end of the grace
+/// period. Rotating again doesn't shorten the grace period of the earlier generations.
+#[derive(Default)]
+struct TokenGenerations {
+    current: u64,
+    /// Time until which the tokens of each earlier generation are accepted
+    previous: BTreeMap<u64, Instant>,
+}
+
+impl TokenGenerations {
+    fn rotate(&mut self, grace_period: Duration) {
+        let now = Instant::now();
+        self.previous.retain(|_, until| now < *until);
+        self.previous.insert(self.current, now + grace_period);
+        self.current += 1;
+    }
+
+    fn accepts(&self, generation: u64) -> bool {
+        generation == self.current
+            || self
+                .previous
+                .get(&generation)
+                .map(|until| Instant::now() < *until)
+                .unwrap_or(false)
+    }
+}
+
+impl EnrollmentTokenAuthenticator {
+    fn accepts_generation(&self, generation: u64) -> bool {
+        self.generations
+            .read()
+            .map(|g| g.accepts(generation))
+            .unwrap_or(false)
+    }
 }
 
 /// Decode, log and map response error to ockam_core error.
@@ -530,6 +682,12 @@ fn error(label: &str, res: &Response, dec: &mut Decoder<'_>) -> ockam_core::Erro
     }
 }
 
+/// Request sent to a credential issuer to get a credential for the identity
+/// at the other end of the secure channel
+pub fn credential_request() -> RequestBuilder<'static> {
+    Request::post("/")
+}
+
 pub struct CredentialIssuerClient(RpcClient);
 impl CredentialIssuerClient {
     pub fn new(client: RpcClient) -> Self {
@@ -537,7 +695,18 @@ impl CredentialIssuerClient {
     }
 
     pub async fn credential(&self) -> Result<Credential> {
-        self.0.request(&Request::post("/")).await
+        self.0.request(&credential_request()).await
+    }
+
+    /// Get a credential including the `requested` attributes. The issuer decides,
+    /// according to its policy, which of them the credential includes.
+    pub async fn credential_with_attributes(
+        &self,
+        requested: BTreeMap<String, String>,
+    ) -> Result<Credential> {
+        self.0
+            .request(&credential_request().body(RequestCredential::new(requested)))
+            .await
     }
 }
 
@@ -571,6 +740,109 @@ impl TokenIssuerClient {
             .request(&Request::post("/").body(CreateToken::new().with_attributes(attributes)))
             .await
     }
+
+    /// Issue the next tokens under a new generation, and keep accepting the tokens
+    /// issued so far for `grace_period`
+    pub async fn rotate_tokens(&self, grace_period: Duration) -> Result<()> {
+        self.0
+            .request_no_resp_body(
+                &Request::post("/tokens/rotate")
+                    .body(RotateTokens::new().with_grace_period(grace_period)),
+            )
+            .await
+    }
+
+    /// List the tokens which were issued and can still be redeemed
+    pub async fn list_tokens(&self) -> Result<Vec<OutstandingToken>> {
+        let list: OutstandingTokenList = self.0.request(&Request::get("/tokens")).await?;
+        Ok(list.into_tokens())
+    }
+
+    /// Revoke the outstanding token with the given id, so that it can't be redeemed
+    pub async fn revoke_token(&self, id: &str) -> Result<()> {
+        self.0
+            .request_no_resp_body(&Request::delete(format!("/tokens/{id}")))
+            .await
+    }
+
+    /// Issue an enrollment token for every device descriptor line read from `devices`.
+    ///
+    /// Blank lines and lines starting with `#` are skipped. The outcome for each
+    /// device is passed to `on_device`, together with its line number, as soon as it
+    /// is known; a device that can't be parsed or enrolled doesn't stop the batch.
+    /// Only an error reading from `devices` aborts it.
+    pub async fn enroll_devices<R, F>(&self, devices: R, mut on_device: F) -> Result<()>
+    where
+        R: BufRead,
+        F: FnMut(usize, Result<(DeviceDescriptor, OneTimeCode)>),
+    {
+        for (n, line) in devices.lines().enumerate() {
+            let line =
+                line.map_err(|e| ockam_core::Error::new(Origin::Application, Kind::Io, e))?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let outcome = match line.parse::<DeviceDescriptor>() {
+                Ok(device) => self
+                    .create_token(device.attributes())
+                    .await
+                    .map(|token| (device, token)),
+                Err(e) => Err(e),
+            };
+            on_device(n + 1, outcome);
+        }
+        Ok(())
+    }
+}
+
+/// A device to enroll, described by a line `<name> [<key>=<value> ...]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceDescriptor {
+    name: String,
+    attributes: HashMap<String, String>,
+}
+
+impl DeviceDescriptor {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn attributes(&self) -> HashMap<&str, &str> {
+        self.attributes
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect()
+    }
+}
+
+impl str::FromStr for DeviceDescriptor {
+    type Err = ockam_core::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |msg: String| ockam_core::Error::new(Origin::Application, Kind::Invalid, msg);
+        let mut parts = s.split_whitespace();
+        let name = parts
+            .next()
+            .ok_or_else(|| invalid("missing device name".to_string()))?;
+        let mut attributes = HashMap::new();
+        for attr in parts {
+            let (key, value) = attr.split_once('=').ok_or_else(|| {
+                invalid(format!("attribute `{attr}` is not in `key=value` format"))
+            })?;
+            if key.is_empty() {
+                return Err(invalid(format!("attribute `{attr}` has an empty key")));
+            }
+            if key == PROJECT_ID {
+                return Err(invalid(format!("attribute `{PROJECT_ID}` is reserved")));
+            }
+            attributes.insert(key.to_string(), value.to_string());
+        }
+        Ok(DeviceDescriptor {
+            name: name.to_string(),
+            attributes,
+        })
+    }
 }
 
 pub struct TokenAcceptorClient(RpcClient);
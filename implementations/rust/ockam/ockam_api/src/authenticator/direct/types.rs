@@ -1,7 +1,8 @@
 use minicbor::{Decode, Encode};
 use ockam_core::CowStr;
 use ockam_identity::IdentityIdentifier;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -77,3 +78,125 @@ impl<'a> CreateToken<'a> {
             .collect()
     }
 }
+
+/// Request body asking a credential issuer to include attributes in the credential
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RequestCredential {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2694318>,
+    #[n(1)] attributes: BTreeMap<String, String>,
+}
+
+impl RequestCredential {
+    pub fn new(attributes: BTreeMap<String, String>) -> Self {
+        RequestCredential {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            attributes,
+        }
+    }
+
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.attributes
+    }
+
+    pub fn into_attributes(self) -> BTreeMap<String, String> {
+        self.attributes
+    }
+}
+
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RotateTokens {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3817402>,
+    #[n(1)] grace_period_secs: Option<u64>,
+}
+
+impl RotateTokens {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        RotateTokens {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            grace_period_secs: None,
+        }
+    }
+
+    /// Keep accepting the tokens issued before the rotation for `grace_period`
+    pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period_secs = Some(grace_period.as_secs());
+        self
+    }
+
+    pub fn grace_period(&self) -> Option<Duration> {
+        self.grace_period_secs.map(Duration::from_secs)
+    }
+}
+
+/// An enrollment token which can still be redeemed, described without its code
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutstandingToken {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5938264>,
+    #[n(1)] id: String,
+    #[n(2)] attributes: HashMap<String, String>,
+    /// Expiration of the token, in seconds since the Unix epoch
+    #[n(3)] expires_at: u64,
+}
+
+impl OutstandingToken {
+    pub fn new(id: String, attributes: HashMap<String, String>, expires_at: u64) -> Self {
+        OutstandingToken {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            id,
+            attributes,
+            expires_at,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn attributes(&self) -> &HashMap<String, String> {
+        &self.attributes
+    }
+
+    pub fn expires_at(&self) -> u64 {
+        self.expires_at
+    }
+}
+
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct OutstandingTokenList {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4172093>,
+    #[n(1)] tokens: Vec<OutstandingToken>,
+}
+
+impl OutstandingTokenList {
+    pub fn new(tokens: Vec<OutstandingToken>) -> Self {
+        OutstandingTokenList {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            tokens,
+        }
+    }
+
+    pub fn tokens(&self) -> &[OutstandingToken] {
+        &self.tokens
+    }
+
+    pub fn into_tokens(self) -> Vec<OutstandingToken> {
+        self.tokens
+    }
+}
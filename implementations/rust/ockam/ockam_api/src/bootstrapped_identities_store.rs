@@ -30,6 +30,11 @@ impl<B: IdentityAttributeStorageReader, S: IdentityAttributeStorage>
             storage,
         }
     }
+
+    /// The storage of the learned attributes, without the pre-trusted identities
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
 }
 
 #[async_trait]
@@ -90,6 +95,16 @@ impl<B: IdentityAttributeStorageReader, S: IdentityAttributeStorage> IdentityAtt
                 */
         }
     }
+
+    async fn delete(&self, identity: &IdentityIdentifier) -> Result<()> {
+        trace! {
+            target: "ockam_api::bootstrapped_identities_store",
+            id     = %identity,
+            "delete"
+        }
+        // Pre-trusted identities can't be removed, only the learned attributes are deleted
+        self.storage.delete(identity).await
+    }
 }
 
 impl<B: IdentityAttributeStorageReader, S: IdentityAttributeStorage> IdentityAttributeStorage
@@ -818,6 +818,31 @@ impl NodeState {
         Ok(LmdbStorage::new(self.path.join("policies_storage.lmdb")).await?)
     }
 
+    /// Returns the credential last fetched by the node, if any
+    pub fn credential(&self) -> Result<Option<CredentialConfig>> {
+        let path = self.path.join("credential.json");
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    pub fn set_credential(&self, credential: &CredentialConfig) -> Result<()> {
+        let contents = serde_json::to_string(credential)?;
+        std::fs::write(self.path.join("credential.json"), contents)?;
+        Ok(())
+    }
+
+    /// Forget the credential last fetched by the node, if any
+    pub fn clear_credential(&self) -> Result<()> {
+        let path = self.path.join("credential.json");
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
     pub fn kill_process(&self, sigkill: bool) -> Result<()> {
         if let Some(pid) = self.pid()? {
             nix::sys::signal::kill(
@@ -1029,6 +1054,19 @@ impl NodeSetupConfig {
             .ok_or_else(|| CliStateError::NotFound("default tcp transport".to_string()))
     }
 
+    /// Replace the default TCP listener, e.g. once the node API listener is moved
+    pub fn set_default_tcp_listener(mut self, transport: CreateTransportJson) -> Self {
+        match self
+            .transports
+            .iter_mut()
+            .find(|t| t.tt == TransportType::Tcp && t.tm == TransportMode::Listen)
+        {
+            Some(listener) => *listener = transport,
+            None => self.transports.push(transport),
+        }
+        self
+    }
+
     pub fn add_transport(mut self, transport: CreateTransportJson) -> Self {
         self.transports.push(transport);
         self
@@ -7,7 +7,7 @@ use ockam_core::api::{Error, Id, Method, Request, Response, Status};
 use ockam_core::vault::Signature;
 use ockam_core::{Address, DenyAll, Result, Routed, Worker};
 use ockam_identity::change_history::IdentityHistoryComparison;
-use ockam_identity::{Identity, PublicIdentity};
+use ockam_identity::{Identity, IdentityStateConst, PublicIdentity};
 use ockam_node::Context;
 use ockam_vault::Vault;
 use tracing::trace;
@@ -123,7 +123,14 @@ impl IdentityService {
             },
             Post => match req.path_segments::<2>().as_slice() {
                 [""] => {
-                    let identity = Identity::create(&self.ctx, &self.vault).await?;
+                    // The key type is optional, for the clients which don't set it
+                    let key_type = if req.has_body() {
+                        dec.decode::<CreateRequest>()?.key_type()
+                    } else {
+                        IdentityStateConst::DEFAULT_KEY_TYPE
+                    };
+                    let identity =
+                        Identity::create_with_key_type(&self.ctx, &self.vault, key_type).await?;
                     let identifier = identity.identifier();
 
                     let body =
@@ -1,5 +1,6 @@
 #![allow(missing_docs)]
 
+use ockam_core::vault::SecretType;
 use ockam_core::{CowBytes, CowStr};
 
 use minicbor::{Decode, Encode};
@@ -7,6 +8,28 @@ use minicbor::{Decode, Encode};
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateRequest {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5170294>,
+    #[n(1)] key_type: SecretType,
+}
+
+impl CreateRequest {
+    pub fn new(key_type: SecretType) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            key_type,
+        }
+    }
+    pub fn key_type(&self) -> SecretType {
+        self.key_type
+    }
+}
+
 #[derive(Debug, Clone, Encode, Decode)]
 #[rustfmt::skip]
 #[cbor(map)]
@@ -3,8 +3,11 @@
 // TODO: split up this file into sub modules
 
 use minicbor::{Decode, Encode};
+use ockam_core::api::Method;
 use ockam_core::CowStr;
 
+use crate::nodes::models::portal::PortalLimits;
+
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
 
@@ -45,3 +48,124 @@ impl<'a> NodeStatus<'a> {
         }
     }
 }
+
+/// Response body for the version and build information of a node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct NodeVersion<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4082163>,
+    #[b(1)] pub version: CowStr<'a>,
+    #[b(2)] pub git_hash: Option<CowStr<'a>>,
+    #[b(3)] pub features: Vec<CowStr<'a>>,
+}
+
+impl<'a> NodeVersion<'a> {
+    pub fn new(
+        version: impl Into<CowStr<'a>>,
+        git_hash: Option<impl Into<CowStr<'a>>>,
+        features: Vec<CowStr<'a>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            version: version.into(),
+            git_hash: git_hash.map(|h| h.into()),
+            features,
+        }
+    }
+}
+
+/// Response body for the configuration a node is running with, including the changes
+/// made since it started, e.g. to its portal limits
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct NodeConfig<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2760493>,
+    #[b(1)] pub node_name: CowStr<'a>,
+    #[b(2)] pub project_id: Option<CowStr<'a>>,
+    #[n(3)] pub skip_defaults: bool,
+    /// Whether credentials are checked for the inlets and outlets of the node
+    #[n(4)] pub credential_checks: bool,
+    /// Maximum size, in bytes, of a credential accepted from an authority
+    #[n(5)] pub max_credential_size: u64,
+    #[n(6)] pub portal_limits: PortalLimits,
+    /// Number of recent TCP connection errors kept
+    #[n(7)] pub max_connection_errors: u64,
+    #[n(8)] pub list_routes_on_unknown_path: bool,
+    #[b(9)] pub fallback_identity_name: Option<CowStr<'a>>,
+    #[b(10)] pub snapshot_path: Option<CowStr<'a>>,
+    /// Cargo features the node was compiled with
+    #[b(11)] pub features: Vec<CowStr<'a>>,
+    #[n(12)] pub authority_connect_timeout_ms: u64,
+    /// Fraction of its lifetime after which the node credential is refreshed,
+    /// `None` if it is never refreshed
+    #[n(13)] pub credential_refresh_lifetime_fraction: Option<f64>,
+    #[n(14)] pub credential_refresh_jitter_ms: Option<u64>,
+    /// Maximum lifetime of a credential accepted from an authority, `None` meaning unlimited
+    #[n(15)] pub max_credential_lifetime_secs: Option<u64>,
+    #[n(16)] pub max_concurrent_credential_presentations: u64,
+    #[n(17)] pub max_concurrent_credential_fetches: u64,
+    #[n(18)] pub credential_verification_ttl_ms: u64,
+}
+
+impl<'a> NodeConfig<'a> {
+    /// Configuration of the node named `node_name`, with the defaults of the other settings
+    pub fn new(node_name: impl Into<CowStr<'a>>, features: Vec<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            node_name: node_name.into(),
+            project_id: None,
+            skip_defaults: false,
+            credential_checks: false,
+            max_credential_size: 0,
+            portal_limits: PortalLimits::default(),
+            max_connection_errors: 0,
+            list_routes_on_unknown_path: true,
+            fallback_identity_name: None,
+            snapshot_path: None,
+            features,
+            authority_connect_timeout_ms: 0,
+            credential_refresh_lifetime_fraction: None,
+            credential_refresh_jitter_ms: None,
+            max_credential_lifetime_secs: None,
+            max_concurrent_credential_presentations: 0,
+            max_concurrent_credential_fetches: 0,
+            credential_verification_ttl_ms: 0,
+        }
+    }
+}
+
+/// Response body for a request whose method and path match no route of the node
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct UnknownRoute<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3381945>,
+    #[b(1)] pub path: CowStr<'a>,
+    #[n(2)] pub method: Option<Method>,
+    /// Supported routes, as `METHOD /path` with `{placeholders}` for variable segments.
+    /// Empty if the node is configured not to list its routes.
+    #[b(3)] pub routes: Vec<CowStr<'a>>,
+}
+
+impl<'a> UnknownRoute<'a> {
+    pub fn new(
+        path: impl Into<CowStr<'a>>,
+        method: Option<Method>,
+        routes: Vec<CowStr<'a>>,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            path: path.into(),
+            method,
+            routes,
+        }
+    }
+}
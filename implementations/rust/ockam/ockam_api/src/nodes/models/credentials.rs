@@ -1,11 +1,15 @@
 //! Credential request/response types
 
 use minicbor::{Decode, Encode};
+use ockam_core::api::Method;
 use ockam_core::compat::borrow::Cow;
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
+use ockam_identity::credential::{Attributes, Credential, Timestamp};
+use ockam_identity::IdentityIdentifier;
 use ockam_multiaddr::MultiAddr;
+use std::time::Duration;
 
 #[derive(Clone, Debug, Decode, Encode)]
 #[rustfmt::skip]
@@ -15,6 +19,7 @@ pub struct GetCredentialRequest {
     #[n(0)] tag: TypeTag<8479533>,
     #[n(1)] overwrite: bool,
     #[n(2)] pub identity_name: Option<String>,
+    #[n(3)] pub authority: Option<IdentityIdentifier>,
 }
 
 impl GetCredentialRequest {
@@ -24,14 +29,63 @@ impl GetCredentialRequest {
             tag: TypeTag,
             overwrite,
             identity_name,
+            authority: None,
         }
     }
 
+    /// Get the credential from the authority identified by `authority`, instead
+    /// of the first authority of the node
+    pub fn with_authority(mut self, authority: IdentityIdentifier) -> Self {
+        self.authority = Some(authority);
+        self
+    }
+
     pub fn is_overwrite(&self) -> bool {
         self.overwrite
     }
 }
 
+/// Response body of a [`GetCredentialRequest`]: the credential, and when it expires
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CredentialResponse {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5820193>,
+    #[n(1)] pub credential: Credential,
+    /// Unix time the credential expires at, if it expires
+    #[n(2)] pub expires_at: Option<u64>,
+    /// Seconds until the credential expires, 0 once it has expired
+    #[n(3)] pub seconds_remaining: Option<u64>,
+    /// Name of the identity the credential was requested for, when the requested
+    /// identity doesn't exist and the node's fallback identity was used instead
+    #[n(4)] pub fallback_identity: Option<String>,
+}
+
+impl CredentialResponse {
+    /// Respond with `credential`, expiring at `expires_at`, as seen at `now`
+    pub fn new(credential: Credential, expires_at: Option<Timestamp>, now: Timestamp) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            credential,
+            expires_at: expires_at.map(u64::from),
+            seconds_remaining: expires_at
+                .map(|expires_at| expires_at.elapsed(now).unwrap_or_default().as_secs()),
+            fallback_identity: None,
+        }
+    }
+
+    pub fn with_fallback_identity(mut self, name: impl Into<String>) -> Self {
+        self.fallback_identity = Some(name.into());
+        self
+    }
+
+    pub fn seconds_remaining(&self) -> Option<Duration> {
+        self.seconds_remaining.map(Duration::from_secs)
+    }
+}
+
 #[derive(Clone, Debug, Decode, Encode)]
 #[rustfmt::skip]
 #[cbor(map)]
@@ -40,6 +94,9 @@ pub struct PresentCredentialRequest<'a> {
     #[n(0)] tag: TypeTag<3698687>,
     #[b(1)] pub route: Cow<'a, str>,
     #[n(2)] pub oneway: bool,
+    #[n(3)] pub timeout_ms: Option<u64>,
+    /// Routes to present the credential to, one after the other, instead of `route`
+    #[b(4)] pub routes: Option<Vec<Cow<'a, str>>>,
 }
 
 impl<'a> PresentCredentialRequest<'a> {
@@ -49,6 +106,404 @@ impl<'a> PresentCredentialRequest<'a> {
             tag: TypeTag,
             route: route.to_string().into(),
             oneway,
+            timeout_ms: None,
+            routes: None,
+        }
+    }
+
+    /// Present the credential to each of `routes` instead of the request route. The
+    /// outcome of each presentation is returned in a [`CredentialPresentationList`]
+    pub fn with_routes(mut self, routes: &[MultiAddr]) -> Self {
+        self.routes = Some(routes.iter().map(|r| r.to_string().into()).collect());
+        self
+    }
+
+    /// Fail the presentation if it doesn't complete within `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+}
+
+/// Outcome of the presentation of a credential to one route
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CredentialPresentationResult<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4418023>,
+    #[b(1)] pub route: Cow<'a, str>,
+    /// Why the presentation failed, if it did
+    #[b(2)] pub error: Option<Cow<'a, str>>,
+}
+
+impl<'a> CredentialPresentationResult<'a> {
+    pub fn new(route: impl Into<Cow<'a, str>>, error: Option<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            route: route.into(),
+            error: error.map(|e| e.into()),
+        }
+    }
+
+    pub fn is_presented(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Response body with the outcome of the presentation to each route of a
+/// [`PresentCredentialRequest`], in the order of the routes
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CredentialPresentationList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7750214>,
+    #[b(1)] pub list: Vec<CredentialPresentationResult<'a>>,
+}
+
+impl<'a> CredentialPresentationList<'a> {
+    pub fn new(list: Vec<CredentialPresentationResult<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
+
+/// Authority and route a credential was fetched from
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CredentialSource<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5264390>,
+    #[b(1)] pub authority: Cow<'a, str>,
+    #[b(2)] pub route: Cow<'a, str>,
+}
+
+impl<'a> CredentialSource<'a> {
+    pub fn new(authority: impl Into<Cow<'a, str>>, route: &MultiAddr) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            authority: authority.into(),
+            route: route.to_string().into(),
+        }
+    }
+}
+
+/// Credential request a node would send to its authority, returned without sending it
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CredentialRequestPreview<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<1849273>,
+    /// Identity the credential would be requested for
+    #[b(1)] pub subject: Cow<'a, str>,
+    #[b(2)] pub authority: Cow<'a, str>,
+    #[b(3)] pub route: Cow<'a, str>,
+    /// Address of the credential issuer service of the authority
+    #[b(4)] pub issuer: Cow<'a, str>,
+    #[n(5)] pub method: Option<Method>,
+    #[b(6)] pub path: Cow<'a, str>,
+    #[n(7)] pub has_body: bool,
+    /// The request wouldn't be sent, since the identity already has a credential
+    /// and overwriting it wasn't asked for
+    #[n(8)] pub skipped: bool,
+}
+
+impl<'a> CredentialRequestPreview<'a> {
+    pub fn new(
+        subject: impl Into<Cow<'a, str>>,
+        authority: impl Into<Cow<'a, str>>,
+        route: &MultiAddr,
+        issuer: impl Into<Cow<'a, str>>,
+        request: &ockam_core::api::Request<'_>,
+        skipped: bool,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            subject: subject.into(),
+            authority: authority.into(),
+            route: route.to_string().into(),
+            issuer: issuer.into(),
+            method: request.method(),
+            path: request.path().to_string().into(),
+            has_body: request.has_body(),
+            skipped,
+        }
+    }
+}
+
+/// Portals which would be affected if the node's current credential was cleared or rotated
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CredentialDependents<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7145023>,
+    /// Aliases of the inlets depending on the credential
+    #[b(1)] pub inlets: Vec<Cow<'a, str>>,
+}
+
+impl<'a> CredentialDependents<'a> {
+    pub fn new(inlets: Vec<Cow<'a, str>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            inlets,
+        }
+    }
+}
+
+/// Attributes of the node's current credential, which the node presents to its peers
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CredentialAttributes<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6619402>,
+    #[b(1)] pub issuer: Cow<'a, str>,
+    /// Expiration of the credential, in seconds since the Unix epoch
+    #[n(2)] pub expires_at: u64,
+    #[b(3)] pub attributes: Attributes,
+}
+
+impl<'a> CredentialAttributes<'a> {
+    pub fn new(issuer: impl Into<Cow<'a, str>>, expires_at: u64, attributes: Attributes) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            issuer: issuer.into(),
+            expires_at,
+            attributes,
+        }
+    }
+}
+
+/// Request body to verify a credential against the node's authorities
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct VerifyCredentialRequest {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2648317>,
+    #[n(1)] pub credential: Credential,
+}
+
+impl VerifyCredentialRequest {
+    pub fn new(credential: Credential) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            credential,
+        }
+    }
+}
+
+/// Outcome of the verification of a credential against the node's authorities
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CredentialVerification<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7720594>,
+    #[n(1)] pub valid: bool,
+    /// Why the credential isn't valid
+    #[b(2)] pub reason: Option<Cow<'a, str>>,
+    /// Issuer and subject of the credential, if it can be decoded
+    #[b(3)] pub issuer: Option<Cow<'a, str>>,
+    #[b(4)] pub subject: Option<Cow<'a, str>>,
+    /// Expiration of the credential, in seconds since the Unix epoch
+    #[n(5)] pub expires_at: Option<u64>,
+    /// Attributes of the credential, if it is valid
+    #[b(6)] pub attributes: Option<Attributes>,
+}
+
+impl<'a> CredentialVerification<'a> {
+    pub fn valid(
+        issuer: impl Into<Cow<'a, str>>,
+        subject: impl Into<Cow<'a, str>>,
+        expires_at: u64,
+        attributes: Attributes,
+    ) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            valid: true,
+            reason: None,
+            issuer: Some(issuer.into()),
+            subject: Some(subject.into()),
+            expires_at: Some(expires_at),
+            attributes: Some(attributes),
+        }
+    }
+
+    pub fn invalid(reason: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            valid: false,
+            reason: Some(reason.into()),
+            issuer: None,
+            subject: None,
+            expires_at: None,
+            attributes: None,
+        }
+    }
+
+    /// Set the issuer, subject and expiration read from a credential which isn't valid
+    pub fn with_unverified_data(
+        mut self,
+        issuer: impl Into<Cow<'a, str>>,
+        subject: impl Into<Cow<'a, str>>,
+        expires_at: u64,
+    ) -> Self {
+        self.issuer = Some(issuer.into());
+        self.subject = Some(subject.into());
+        self.expires_at = Some(expires_at);
+        self
+    }
+}
+
+/// Reason a credential couldn't be got, sent as the `code` of the error body.
+///
+/// Codes are stable: a new reason gets a new code, and codes are never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialErrorCode {
+    /// The node has no authority
+    NoAuthority = 1,
+    /// The requested authority isn't one of the node's authorities
+    UnknownAuthority = 2,
+    /// The identity has a credential already, and overwriting it wasn't requested
+    AlreadyExists = 3,
+    /// The authority can't be reached
+    Unreachable = 4,
+    /// No secure channel can be created to the authority
+    SecureChannel = 5,
+    /// The authority doesn't return a credential
+    Fetch = 6,
+    /// The credential returned by the authority can't be verified
+    Verification = 7,
+}
+
+impl CredentialErrorCode {
+    pub fn code(self) -> u16 {
+        self as u16
+    }
+
+    pub fn from_code(code: u16) -> Option<Self> {
+        match code {
+            1 => Some(Self::NoAuthority),
+            2 => Some(Self::UnknownAuthority),
+            3 => Some(Self::AlreadyExists),
+            4 => Some(Self::Unreachable),
+            5 => Some(Self::SecureChannel),
+            6 => Some(Self::Fetch),
+            7 => Some(Self::Verification),
+            _ => None,
+        }
+    }
+}
+
+/// Failure injected in a credential fetch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum CredentialFault {
+    /// The authority doesn't answer in time
+    #[n(0)] Timeout,
+    /// The authority can't be reached
+    #[n(1)] Refused,
+    /// The credential returned by the authority can't be verified
+    #[n(2)] VerificationFailure,
+}
+
+/// Request body to make the next `count` credential fetches fail with `fault`
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct InjectCredentialFaults {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2915446>,
+    #[n(1)] pub fault: CredentialFault,
+    #[n(2)] pub count: u32,
+}
+
+impl InjectCredentialFaults {
+    pub fn new(fault: CredentialFault, count: u32) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            fault,
+            count,
+        }
+    }
+}
+
+/// Route used to reach an authority and whether it can currently be reached
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AuthorityRoute<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4470182>,
+    #[b(1)] pub authority: Cow<'a, str>,
+    /// Configured address of the authority
+    #[b(2)] pub route: Cow<'a, str>,
+    /// Route to the authority once its address is dialed, if it could be
+    #[b(3)] pub resolved_route: Option<Cow<'a, str>>,
+    #[n(4)] pub reachable: bool,
+    #[b(5)] pub error: Option<Cow<'a, str>>,
+}
+
+impl<'a> AuthorityRoute<'a> {
+    pub fn new(
+        authority: impl Into<Cow<'a, str>>,
+        route: &MultiAddr,
+        resolved_route: Result<MultiAddr, String>,
+    ) -> Self {
+        let (resolved_route, error) = match resolved_route {
+            Ok(resolved_route) => (Some(resolved_route.to_string().into()), None),
+            Err(error) => (None, Some(error.into())),
+        };
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            authority: authority.into(),
+            route: route.to_string().into(),
+            reachable: resolved_route.is_some(),
+            resolved_route,
+            error,
+        }
+    }
+}
+
+/// Response body listing the routes to the node's authorities
+#[derive(Clone, Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AuthorityRouteList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<9031746>,
+    #[b(1)] pub list: Vec<AuthorityRoute<'a>>,
+}
+
+impl<'a> AuthorityRouteList<'a> {
+    pub fn new(list: Vec<AuthorityRoute<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
         }
     }
 }
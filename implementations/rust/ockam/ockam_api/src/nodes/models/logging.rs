@@ -0,0 +1,72 @@
+use minicbor::{Decode, Encode};
+use ockam_core::CowStr;
+
+#[cfg(feature = "tag")]
+use ockam_core::TypeTag;
+
+/// Request body to change the log level of a target at runtime
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SetLogLevel<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4168250>,
+    /// Target of the events, e.g. `ockam_transport_tcp::workers::receiver`
+    #[b(1)] pub target: CowStr<'a>,
+    /// One of `off`, `error`, `warn`, `info`, `debug`, `trace`.
+    /// `None` removes the level previously set for the target
+    #[b(2)] pub level: Option<CowStr<'a>>,
+}
+
+impl<'a> SetLogLevel<'a> {
+    pub fn new(target: impl Into<CowStr<'a>>, level: Option<impl Into<CowStr<'a>>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            target: target.into(),
+            level: level.map(Into::into),
+        }
+    }
+}
+
+/// Log level set at runtime for a target
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct LogLevelStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<1502684>,
+    #[b(1)] pub target: CowStr<'a>,
+    #[b(2)] pub level: CowStr<'a>,
+}
+
+impl<'a> LogLevelStatus<'a> {
+    pub fn new(target: impl Into<CowStr<'a>>, level: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            target: target.into(),
+            level: level.into(),
+        }
+    }
+}
+
+/// Response body for listing the log levels set at runtime
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct LogLevelList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8830971>,
+    #[b(1)] pub list: Vec<LogLevelStatus<'a>>
+}
+
+impl<'a> LogLevelList<'a> {
+    pub fn new(list: Vec<LogLevelStatus<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
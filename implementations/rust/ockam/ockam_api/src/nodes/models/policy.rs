@@ -1,5 +1,6 @@
 use minicbor::{Decode, Encode};
 use ockam_abac::{Action, Expr};
+use ockam_core::compat::borrow::Cow;
 
 #[cfg(feature = "tag")]
 use ockam_core::TypeTag;
@@ -49,3 +50,47 @@ impl PolicyList {
         &self.expressions
     }
 }
+
+/// Number of times a condition of the node's policies was met or not
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyConditionStatistics<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6608213>,
+    #[b(1)] pub condition: Cow<'a, str>,
+    #[n(2)] pub matched: u64,
+    #[n(3)] pub denied: u64,
+}
+
+impl<'a> PolicyConditionStatistics<'a> {
+    pub fn new(condition: impl Into<Cow<'a, str>>, matched: u64, denied: u64) -> Self {
+        PolicyConditionStatistics {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            condition: condition.into(),
+            matched,
+            denied,
+        }
+    }
+}
+
+/// Response body listing the statistics of the conditions of the node's policies
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyConditionStatisticsList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<1843570>,
+    #[b(1)] pub list: Vec<PolicyConditionStatistics<'a>>,
+}
+
+impl<'a> PolicyConditionStatisticsList<'a> {
+    pub fn new(list: Vec<PolicyConditionStatistics<'a>>) -> Self {
+        PolicyConditionStatisticsList {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
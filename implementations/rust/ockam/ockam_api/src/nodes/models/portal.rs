@@ -29,7 +29,15 @@ pub struct CreateInlet<'a> {
     /// An authorised identity for secure channels.
     /// Only set for non-project addresses as for projects the project's
     /// authorised identity will be used.
-    #[n(4)] authorized: Option<IdentityIdentifier>
+    #[n(4)] authorized: Option<IdentityIdentifier>,
+    /// Only check that the outlet route can be reached, without
+    /// binding the inlet listener.
+    #[n(5)] dry_run: Option<bool>,
+    /// A free-text description of this portal endpoint
+    #[b(6)] description: Option<CowStr<'a>>,
+    /// Whether this inlet depends on the node's credential, even if the node
+    /// has no credential yet when the inlet is created
+    #[n(7)] depends_on_credential: Option<bool>,
 }
 
 impl<'a> CreateInlet<'a> {
@@ -41,6 +49,9 @@ impl<'a> CreateInlet<'a> {
             outlet_addr: to,
             alias: None,
             authorized: None,
+            dry_run: None,
+            description: None,
+            depends_on_credential: None,
         }
     }
 
@@ -52,6 +63,9 @@ impl<'a> CreateInlet<'a> {
             outlet_addr: to,
             alias: None,
             authorized: auth,
+            dry_run: None,
+            description: None,
+            depends_on_credential: None,
         }
     }
 
@@ -59,6 +73,18 @@ impl<'a> CreateInlet<'a> {
         self.alias = Some(CowStr(a.into()))
     }
 
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = Some(dry_run)
+    }
+
+    pub fn set_description(&mut self, d: impl Into<Cow<'a, str>>) {
+        self.description = Some(CowStr(d.into()))
+    }
+
+    pub fn set_depends_on_credential(&mut self, depends_on_credential: bool) {
+        self.depends_on_credential = Some(depends_on_credential)
+    }
+
     pub fn listen_addr(&self) -> SocketAddr {
         self.listen_addr
     }
@@ -74,6 +100,18 @@ impl<'a> CreateInlet<'a> {
     pub fn alias(&self) -> Option<&str> {
         self.alias.as_deref()
     }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.unwrap_or(false)
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn depends_on_credential(&self) -> bool {
+        self.depends_on_credential.unwrap_or(false)
+    }
 }
 
 /// Request body to create an inlet or outlet
@@ -89,6 +127,11 @@ pub struct CreateOutlet<'a> {
     #[b(2)] pub worker_addr: Cow<'a, str>,
     /// A human-friendly alias for this portal endpoint
     #[b(3)] pub alias: Option<CowStr<'a>>,
+    /// A free-text description of this portal endpoint
+    #[b(4)] pub description: Option<CowStr<'a>>,
+    /// Attributes, as `(key, value)` pairs, the senders of messages to the outlet
+    /// must have. Several values for the same key allow any of them.
+    #[b(5)] pub allow: Option<Vec<(CowStr<'a>, CowStr<'a>)>>,
 }
 
 impl<'a> CreateOutlet<'a> {
@@ -103,8 +146,24 @@ impl<'a> CreateOutlet<'a> {
             tcp_addr: tcp_addr.into(),
             worker_addr: worker_addr.into(),
             alias: alias.into(),
+            description: None,
+            allow: None,
         }
     }
+
+    pub fn set_description(&mut self, d: impl Into<Cow<'a, str>>) {
+        self.description = Some(CowStr(d.into()))
+    }
+
+    pub fn add_allowed_attribute(
+        &mut self,
+        key: impl Into<Cow<'a, str>>,
+        value: impl Into<Cow<'a, str>>,
+    ) {
+        self.allow
+            .get_or_insert_with(Vec::new)
+            .push((CowStr(key.into()), CowStr(value.into())))
+    }
 }
 
 /// Response body when interacting with a portal endpoint
@@ -120,6 +179,8 @@ pub struct InletStatus<'a> {
     /// An optional status payload
     #[b(4)] pub payload: Option<CowStr<'a>>,
     #[b(5)] pub outlet_route: CowStr<'a>,
+    /// A free-text description of the inlet
+    #[b(6)] pub description: Option<CowStr<'a>>,
 }
 
 impl<'a> InletStatus<'a> {
@@ -132,6 +193,7 @@ impl<'a> InletStatus<'a> {
             alias: "".into(),
             payload: Some(reason.into()),
             outlet_route: "".into(),
+            description: None,
         }
     }
 
@@ -150,8 +212,14 @@ impl<'a> InletStatus<'a> {
             alias: alias.into(),
             payload: payload.into(),
             outlet_route: outlet_route.into(),
+            description: None,
         }
     }
+
+    pub fn with_description(mut self, description: Option<impl Into<CowStr<'a>>>) -> Self {
+        self.description = description.map(Into::into);
+        self
+    }
 }
 
 /// Response body when interacting with a portal endpoint
@@ -166,6 +234,12 @@ pub struct OutletStatus<'a> {
     #[b(3)] pub alias: CowStr<'a>,
     /// An optional status payload
     #[b(4)] pub payload: Option<CowStr<'a>>,
+    /// A free-text description of the outlet
+    #[b(5)] pub description: Option<CowStr<'a>>,
+    /// When the outlet was created, in milliseconds since the Unix epoch
+    #[n(6)] pub created_at: Option<u64>,
+    /// Number of open connections through the outlet
+    #[n(7)] pub active_connections: Option<u32>,
 }
 
 impl<'a> OutletStatus<'a> {
@@ -177,6 +251,9 @@ impl<'a> OutletStatus<'a> {
             worker_addr: "".into(),
             alias: "".into(),
             payload: Some(reason.into()),
+            description: None,
+            created_at: None,
+            active_connections: None,
         }
     }
 
@@ -193,8 +270,26 @@ impl<'a> OutletStatus<'a> {
             worker_addr: worker_addr.into(),
             alias: alias.into(),
             payload: payload.into(),
+            description: None,
+            created_at: None,
+            active_connections: None,
         }
     }
+
+    pub fn with_description(mut self, description: Option<impl Into<CowStr<'a>>>) -> Self {
+        self.description = description.map(Into::into);
+        self
+    }
+
+    pub fn with_created_at(mut self, created_at: u64) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    pub fn with_active_connections(mut self, active_connections: u32) -> Self {
+        self.active_connections = Some(active_connections);
+        self
+    }
 }
 
 /// Response body when returning a list of Inlets
@@ -236,3 +331,26 @@ impl<'a> OutletList<'a> {
         }
     }
 }
+
+/// Maximum number of inlets and outlets of a node, `None` meaning unlimited.
+/// Used both as request body to change the limits and response body to read them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PortalLimits {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6211847>,
+    #[n(1)] pub max_inlets: Option<u32>,
+    #[n(2)] pub max_outlets: Option<u32>,
+}
+
+impl PortalLimits {
+    pub fn new(max_inlets: Option<u32>, max_outlets: Option<u32>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            max_inlets,
+            max_outlets,
+        }
+    }
+}
@@ -58,6 +58,13 @@ impl<'a> CreateSecureChannelRequest<'a> {
             credential_name: credential_name.map(|x| x.into()),
         }
     }
+
+    /// Fail the creation of the secure channel if it doesn't complete within `timeout`,
+    /// instead of the default timeout of the node
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 /// Response body when instructing a node to create a Secure Channel
@@ -182,6 +189,76 @@ impl<'a> ShowSecureChannelRequest<'a> {
     }
 }
 
+/// A secure channel of a [`SecureChannelGroup`]
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct GroupedSecureChannel<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3304417>,
+    #[b(1)] pub channel: CowStr<'a>,
+    /// Whether this node initiated the channel
+    #[n(2)] pub initiator: bool,
+    /// Time since the channel was established, in seconds
+    #[n(3)] pub age_secs: Option<u64>,
+}
+
+impl<'a> GroupedSecureChannel<'a> {
+    pub fn new(channel: &Address, initiator: bool, age: Option<Duration>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            channel: channel.to_string().into(),
+            initiator,
+            age_secs: age.map(|age| age.as_secs()),
+        }
+    }
+}
+
+/// The secure channels established with the same remote identity, oldest first
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SecureChannelGroup<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<8015526>,
+    #[b(1)] pub identity: CowStr<'a>,
+    #[n(2)] pub count: u64,
+    #[b(3)] pub channels: Vec<GroupedSecureChannel<'a>>,
+}
+
+impl<'a> SecureChannelGroup<'a> {
+    pub fn new(identity: &IdentityIdentifier, channels: Vec<GroupedSecureChannel<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            identity: identity.to_string().into(),
+            count: channels.len() as u64,
+            channels,
+        }
+    }
+}
+
+/// Response body listing the secure channels of a node grouped by remote identity
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SecureChannelGroupList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5731092>,
+    #[b(1)] pub groups: Vec<SecureChannelGroup<'a>>,
+}
+
+impl<'a> SecureChannelGroupList<'a> {
+    pub fn new(groups: Vec<SecureChannelGroup<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            groups,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Decode, Encode, Serialize)]
 #[rustfmt::skip]
 #[cbor(map)]
@@ -1,6 +1,7 @@
 use minicbor::{Decode, Encode};
 use ockam_core::{CowStr, Result};
 use std::fmt::{self, Display};
+use std::time::Duration;
 
 use crate::cli_state::CliStateError;
 use crate::config::lookup::InternetAddress;
@@ -8,6 +9,7 @@ use crate::config::lookup::InternetAddress;
 use ockam_core::TypeTag;
 use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Tcp};
 use ockam_multiaddr::MultiAddr;
+use ockam_transport_tcp::{ConnectionStats, TcpConnectionInfo, TcpEvent, TransportStats};
 
 ///////////////////-!  REQUEST BODIES
 
@@ -134,6 +136,72 @@ impl Display for TransportMode {
     }
 }
 
+/// Request to check whether a route can be reached
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ProbeReachability<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5528391>,
+    #[b(1)] pub route: CowStr<'a>,
+    /// Whether to also create a secure channel to the end of the route
+    #[n(2)] pub secure_channel: bool,
+}
+
+impl<'a> ProbeReachability<'a> {
+    pub fn new(route: &MultiAddr, secure_channel: bool) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            route: route.to_string().into(),
+            secure_channel,
+        }
+    }
+}
+
+/// Request body when subscribing to the events of the node's TCP connections,
+/// which are sent to `route` as [`TcpEventMessage`]s
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SubscribeTcpEvents<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2948173>,
+    #[b(1)] pub route: CowStr<'a>,
+}
+
+impl<'a> SubscribeTcpEvents<'a> {
+    pub fn new(route: &MultiAddr) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            route: route.to_string().into(),
+        }
+    }
+}
+
+/// Request body when moving a listener to a new address, while the connections it
+/// accepted are drained
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct MigrateListener<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<4620917>,
+    /// The address the new listener is bound to
+    #[b(1)] pub addr: CowStr<'a>,
+}
+
+impl<'a> MigrateListener<'a> {
+    pub fn new(addr: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            addr: addr.into(),
+        }
+    }
+}
+
 ///////////////////-!  RESPONSE BODIES
 
 /// Response body when interacting with a transport
@@ -178,6 +246,95 @@ impl<'a> TransportStatus<'a> {
     }
 }
 
+/// Response body telling how many connections accepted by the previous listeners
+/// of a migrated listener are still open, see [`MigrateListener`]
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ListenerDrainStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<5093368>,
+    #[b(1)] pub tid: CowStr<'a>,
+    #[n(2)] pub connections: u64,
+    /// Whether all the connections of the previous listeners are closed
+    #[n(3)] pub drained: bool,
+}
+
+impl<'a> ListenerDrainStatus<'a> {
+    pub fn new(tid: impl Into<CowStr<'a>>, connections: usize) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            tid: tid.into(),
+            connections: connections as u64,
+            drained: connections == 0,
+        }
+    }
+}
+
+/// Response body with the traffic of a TCP connection since it was opened
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpConnectionStats<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6402781>,
+    #[b(1)] pub tid: CowStr<'a>,
+    #[n(2)] pub bytes_in: u64,
+    #[n(3)] pub bytes_out: u64,
+    #[n(4)] pub messages_in: u64,
+    #[n(5)] pub messages_out: u64,
+}
+
+impl<'a> TcpConnectionStats<'a> {
+    pub fn new(tid: impl Into<CowStr<'a>>, stats: ConnectionStats) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            tid: tid.into(),
+            bytes_in: stats.bytes_in,
+            bytes_out: stats.bytes_out,
+            messages_in: stats.messages_in,
+            messages_out: stats.messages_out,
+        }
+    }
+}
+
+/// Response body with the traffic of all the open TCP connections of the node, and
+/// the number of frames dropped by each policy
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpTransportStats {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7314029>,
+    #[n(1)] pub connections: u64,
+    #[n(2)] pub bytes_in: u64,
+    #[n(3)] pub bytes_out: u64,
+    #[n(4)] pub messages_in: u64,
+    #[n(5)] pub messages_out: u64,
+    #[n(6)] pub dropped_messages: u64,
+    #[n(7)] pub corrupt_frames: u64,
+    #[n(8)] pub duplicate_sessions: u64,
+}
+
+impl TcpTransportStats {
+    pub fn new(stats: TransportStats) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            connections: stats.connections,
+            bytes_in: stats.bytes_in,
+            bytes_out: stats.bytes_out,
+            messages_in: stats.messages_in,
+            messages_out: stats.messages_out,
+            dropped_messages: stats.dropped_messages,
+            corrupt_frames: stats.corrupt_frames,
+            duplicate_sessions: stats.duplicate_sessions,
+        }
+    }
+}
+
 /// Response body when interacting with a transport
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -185,7 +342,10 @@ impl<'a> TransportStatus<'a> {
 pub struct TransportList<'a> {
     #[cfg(feature = "tag")]
     #[n(0)] tag: TypeTag<5212817>,
-    #[b(1)] pub list: Vec<TransportStatus<'a>>
+    #[b(1)] pub list: Vec<TransportStatus<'a>>,
+    /// Open connections registered by the TCP transport, only set when listing
+    /// the TCP connections
+    #[b(2)] pub connections: Option<Vec<TcpConnectionStatus<'a>>>,
 }
 
 impl<'a> TransportList<'a> {
@@ -194,6 +354,187 @@ impl<'a> TransportList<'a> {
             #[cfg(feature = "tag")]
             tag: TypeTag,
             list,
+            connections: None,
+        }
+    }
+
+    pub fn with_connections(mut self, connections: Vec<TcpConnectionStatus<'a>>) -> Self {
+        self.connections = Some(connections);
+        self
+    }
+}
+
+/// An open TCP connection registered by the TCP transport
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpConnectionStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<3864105>,
+    /// Address of the receiver processor of the connection
+    #[b(1)] pub receiver_addr: CowStr<'a>,
+    /// Peer the connection was established with
+    #[b(2)] pub peer: CowStr<'a>,
+    /// Session claimed by the connection, if any
+    #[b(3)] pub session_id: Option<CowStr<'a>>,
+}
+
+impl<'a> TcpConnectionStatus<'a> {
+    pub fn new(connection: &TcpConnectionInfo) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            receiver_addr: connection.receiver_address().to_string().into(),
+            peer: connection.peer().to_string().into(),
+            session_id: connection.session_id().map(|id| id.to_string().into()),
+        }
+    }
+}
+
+/// A recent failure to establish a TCP connection
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ConnectionError<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<2290417>,
+    /// Peer the connection was established with
+    #[b(1)] pub peer: CowStr<'a>,
+    /// Time of the failure, in seconds since the Unix epoch
+    #[n(2)] pub time: u64,
+    /// Reason of the failure
+    #[b(3)] pub reason: CowStr<'a>,
+}
+
+impl<'a> ConnectionError<'a> {
+    pub fn new(peer: impl Into<CowStr<'a>>, time: u64, reason: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            peer: peer.into(),
+            time,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Response body listing the recent connection failures, oldest first
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ConnectionErrorList<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6810352>,
+    #[b(1)] pub list: Vec<ConnectionError<'a>>
+}
+
+impl<'a> ConnectionErrorList<'a> {
+    pub fn new(list: Vec<ConnectionError<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            list,
+        }
+    }
+}
+
+/// Response body telling whether a route could be reached, and how long it took
+/// to find out
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct ReachabilityStatus<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7140263>,
+    #[b(1)] pub route: CowStr<'a>,
+    #[n(2)] pub reachable: bool,
+    #[n(3)] pub elapsed_ms: u64,
+    #[b(4)] pub error: Option<CowStr<'a>>,
+}
+
+impl<'a> ReachabilityStatus<'a> {
+    pub fn new(route: &MultiAddr, elapsed: Duration, error: Option<String>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            route: route.to_string().into(),
+            reachable: error.is_none(),
+            elapsed_ms: elapsed.as_millis() as u64,
+            error: error.map(|e| e.into()),
+        }
+    }
+}
+
+/// Response body identifying a subscription to the TCP events
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpEventsSubscription<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<6350816>,
+    #[b(1)] pub id: CowStr<'a>,
+}
+
+impl<'a> TcpEventsSubscription<'a> {
+    pub fn new(id: impl Into<CowStr<'a>>) -> Self {
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            id: id.into(),
+        }
+    }
+}
+
+/// Kind of a [`TcpEventMessage`]
+#[derive(Copy, Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(index_only)]
+pub enum TcpEventKind {
+    #[n(0)] ConnectionOpened,
+    #[n(1)] ConnectionClosed,
+    #[n(2)] FrameReceived,
+    #[n(3)] Heartbeat,
+    #[n(4)] ForwardError,
+}
+
+/// Message sent to the subscribers of the TCP events, see [`SubscribeTcpEvents`]
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TcpEventMessage<'a> {
+    #[cfg(feature = "tag")]
+    #[n(0)] tag: TypeTag<7725049>,
+    #[n(1)] pub kind: TcpEventKind,
+    /// Peer of the connection the event happened on
+    #[b(2)] pub peer: CowStr<'a>,
+    /// Size of the received frame, in bytes
+    #[n(3)] pub size: Option<u64>,
+    /// Reason of a forward error
+    #[b(4)] pub reason: Option<CowStr<'a>>,
+}
+
+impl<'a> From<&TcpEvent> for TcpEventMessage<'a> {
+    fn from(event: &TcpEvent) -> Self {
+        let (kind, size, reason) = match event {
+            TcpEvent::ConnectionOpened { .. } => (TcpEventKind::ConnectionOpened, None, None),
+            TcpEvent::ConnectionClosed { .. } => (TcpEventKind::ConnectionClosed, None, None),
+            TcpEvent::FrameReceived { size, .. } => {
+                (TcpEventKind::FrameReceived, Some(*size as u64), None)
+            }
+            TcpEvent::Heartbeat { .. } => (TcpEventKind::Heartbeat, None, None),
+            TcpEvent::ForwardError { reason, .. } => (
+                TcpEventKind::ForwardError,
+                None,
+                Some(reason.clone().into()),
+            ),
+        };
+        Self {
+            #[cfg(feature = "tag")]
+            tag: TypeTag,
+            kind,
+            peer: event.peer().to_string().into(),
+            size,
+            reason,
         }
     }
 }
@@ -2,6 +2,8 @@ use crate::nodes::service::Alias;
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::{Address, Route};
 use ockam_identity::IdentityIdentifier;
+use ockam_multiaddr::MultiAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Default)]
 pub(crate) struct SecureChannelRegistry {
@@ -127,6 +129,12 @@ pub(crate) struct InletInfo {
     pub(crate) bind_addr: String,
     pub(crate) worker_addr: Address,
     pub(crate) outlet_route: Route,
+    pub(crate) description: Option<String>,
+    /// Whether the inlet stops working if the node's credential is cleared
+    pub(crate) depends_on_credential: bool,
+    /// Whether the outlet route doesn't go through connections or secure channels
+    /// created for the inlet, so that it is still valid after the node restarts
+    pub(crate) persistent_route: bool,
 }
 
 impl InletInfo {
@@ -134,6 +142,9 @@ impl InletInfo {
         bind_addr: &str,
         worker_addr: Option<&Address>,
         outlet_route: &Route,
+        description: Option<&str>,
+        depends_on_credential: bool,
+        persistent_route: bool,
     ) -> Self {
         let worker_addr = match worker_addr {
             Some(addr) => addr.clone(),
@@ -143,6 +154,9 @@ impl InletInfo {
             bind_addr: bind_addr.to_owned(),
             worker_addr,
             outlet_route: outlet_route.to_owned(),
+            description: description.map(str::to_owned),
+            depends_on_credential,
+            persistent_route,
         }
     }
 }
@@ -150,10 +164,20 @@ impl InletInfo {
 pub(crate) struct OutletInfo {
     pub(crate) tcp_addr: String,
     pub(crate) worker_addr: Address,
+    pub(crate) description: Option<String>,
+    /// Attributes, as `(key, value)` pairs, the senders to the outlet must have
+    pub(crate) allow: Vec<(String, String)>,
+    /// When the outlet was created, in milliseconds since the Unix epoch
+    pub(crate) created_at: u64,
 }
 
 impl OutletInfo {
-    pub(crate) fn new(tcp_addr: &str, worker_addr: Option<&Address>) -> Self {
+    pub(crate) fn new(
+        tcp_addr: &str,
+        worker_addr: Option<&Address>,
+        description: Option<&str>,
+        allow: &[(String, String)],
+    ) -> Self {
         let worker_addr = match worker_addr {
             Some(addr) => addr.clone(),
             None => Address::from_string(""),
@@ -161,10 +185,28 @@ impl OutletInfo {
         Self {
             tcp_addr: tcp_addr.to_owned(),
             worker_addr,
+            description: description.map(str::to_owned),
+            allow: allow.to_vec(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
         }
     }
 }
 
+/// Authority a credential was fetched from
+pub(crate) struct CredentialSourceInfo {
+    pub(crate) authority: IdentityIdentifier,
+    pub(crate) route: MultiAddr,
+}
+
+impl CredentialSourceInfo {
+    pub(crate) fn new(authority: IdentityIdentifier, route: MultiAddr) -> Self {
+        Self { authority, route }
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct Registry {
     pub(crate) secure_channels: SecureChannelRegistry,
@@ -185,4 +227,5 @@ pub(crate) struct Registry {
     // FIXME: wow this is a terrible way to store data
     pub(crate) inlets: BTreeMap<Alias, InletInfo>,
     pub(crate) outlets: BTreeMap<Alias, OutletInfo>,
+    pub(crate) credential_sources: BTreeMap<IdentityIdentifier, CredentialSourceInfo>,
 }
@@ -5,6 +5,7 @@ use minicbor::Decoder;
 use ockam::compat::asynchronous::RwLock;
 use ockam::identity::{Identity, IdentityIdentifier, PublicIdentity};
 use ockam::{Address, Context, ForwardingService, Result, Routed, TcpTransport, Worker};
+use ockam_abac::AbacStatistics;
 use ockam_core::api::{Error, Method, Request, Response, ResponseBuilder, Status};
 use ockam_core::compat::{
     boxed::Box,
@@ -14,27 +15,36 @@ use ockam_core::compat::{
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{route, AllowAll, AsyncTryClone};
 use ockam_identity::authenticated_storage::AuthenticatedAttributeStorage;
-use ockam_identity::credential::Credential;
+use ockam_identity::credential::refresh::CredentialRefreshSchedule;
+use ockam_identity::credential::{Credential, CredentialVerificationCache};
 use ockam_multiaddr::proto::{Project, Secure};
 use ockam_multiaddr::{MultiAddr, Protocol};
 use ockam_node::tokio;
+use ockam_node::tokio::sync::Semaphore;
 use ockam_node::tokio::task::JoinHandle;
+use ockam_transport_tcp::DEFAULT_MAX_CONNECTION_ERRORS;
 use ockam_vault::Vault;
 use std::collections::BTreeMap;
+#[cfg(feature = "fault-injection")]
+use std::collections::VecDeque;
 use std::error::Error as _;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use super::models::secure_channel::CredentialExchangeMode;
 use super::registry::Registry;
 use crate::bootstrapped_identities_store::BootstrapedIdentityStore;
 use crate::bootstrapped_identities_store::PreTrustedIdentities;
 use crate::cli_state::CliState;
-use crate::config::cli::AuthoritiesConfig;
+use crate::config::cli::{AuthoritiesConfig, Authority};
 use crate::config::lookup::ProjectLookup;
 use crate::error::ApiError;
 use crate::lmdb::LmdbStorage;
 use crate::nodes::connection::Connection;
 use crate::nodes::models::base::NodeStatus;
+#[cfg(feature = "fault-injection")]
+use crate::nodes::models::credentials::CredentialFault;
+use crate::nodes::models::portal::PortalLimits;
 use crate::nodes::models::transport::{TransportMode, TransportType};
 use crate::nodes::models::workers::{WorkerList, WorkerStatus};
 use crate::session::util::{starts_with_host_tcp, starts_with_secure};
@@ -46,13 +56,26 @@ use crate::{
 
 pub mod message;
 
+mod attributes;
+mod config;
 mod credentials;
+#[cfg(feature = "fault-injection")]
+mod fault_injection;
 mod forwarder;
+mod identity;
+mod logging;
 mod policy;
 mod portals;
+mod routes;
 mod secure_channel;
 mod services;
+mod snapshot;
 mod transport;
+mod version;
+
+pub use credentials::CredentialFlowStage;
+use credentials::{CredentialFetches, CredentialVerifications};
+pub use snapshot::NodeManagerSnapshot;
 
 const TARGET: &str = "ockam_api::nodemanager::service";
 
@@ -74,6 +97,7 @@ pub(crate) fn map_multiaddr_err(_err: ockam_multiaddr::Error) -> ockam_core::Err
     invalid_multiaddr_error()
 }
 
+#[derive(Clone)]
 pub(crate) struct Authorities(Vec<AuthorityInfo>);
 
 impl Authorities {
@@ -84,6 +108,25 @@ impl Authorities {
     pub fn public_identities(&self) -> Vec<PublicIdentity> {
         self.0.iter().map(|x| x.identity.clone()).collect()
     }
+
+    /// Return the authority identified by `identifier`, or the first authority
+    pub fn select(&self, identifier: Option<&IdentityIdentifier>) -> Result<&AuthorityInfo> {
+        match identifier {
+            Some(identifier) => self
+                .0
+                .iter()
+                .find(|a| a.identity.identifier() == identifier)
+                .ok_or_else(|| {
+                    ApiError::message(format!(
+                        "no authority with identifier {identifier} is known"
+                    ))
+                }),
+            None => self
+                .0
+                .first()
+                .ok_or_else(|| ApiError::generic("No known Authority")),
+        }
+    }
 }
 
 impl AsRef<[AuthorityInfo]> for Authorities {
@@ -105,10 +148,28 @@ pub struct NodeManager {
     pub(crate) cli_state: CliState,
     node_name: String,
     transports: Transports,
+    /// Id of the listener serving the node API, under which it is in `transports`
+    api_transport_id: Alias,
     pub(crate) tcp_transport: TcpTransport,
     pub(crate) controller_identity_id: IdentityIdentifier,
     skip_defaults: bool,
     enable_credential_checks: bool,
+    max_credential_size: usize,
+    max_credential_lifetime: Option<Duration>,
+    authority_connect_timeout: Duration,
+    credential_refresh: Option<CredentialRefreshSchedule>,
+    credential_refresher: Option<JoinHandle<()>>,
+    portal_limits: PortalLimits,
+    credential_presentations: Arc<Semaphore>,
+    max_concurrent_credential_presentations: usize,
+    credential_fetches: Arc<CredentialFetches>,
+    max_concurrent_credential_fetches: usize,
+    credential_verifications: Arc<CredentialVerifications>,
+    /// Verifications of the credentials presented by peers to the node identity
+    peer_credential_verifications: CredentialVerificationCache,
+    list_routes_on_unknown_path: bool,
+    fallback_identity_name: Option<String>,
+    snapshot_path: Option<PathBuf>,
     vault: Vault,
     identity: Identity<Vault, LmdbStorage>,
     project_id: Option<String>,
@@ -117,9 +178,16 @@ pub struct NodeManager {
     pub(crate) registry: Registry,
     sessions: Arc<Mutex<Sessions>>,
     medic: JoinHandle<Result<(), ockam_core::Error>>,
+    tcp_event_subscriptions: BTreeMap<String, JoinHandle<()>>,
+    draining_listeners: BTreeMap<Alias, Vec<Address>>,
     policies: LmdbStorage,
+    abac_statistics: Option<AbacStatistics>,
     attributes_storage:
         BootstrapedIdentityStore<PreTrustedIdentities, AuthenticatedAttributeStorage<LmdbStorage>>,
+    #[cfg(feature = "fault-injection")]
+    /// Faults to inject in the next credential fetches, with the number of fetches left
+    /// to fail with each of them
+    credential_faults: VecDeque<(CredentialFault, u32)>,
 }
 
 pub struct NodeManagerWorker {
@@ -171,6 +239,19 @@ pub struct NodeManagerGeneralOptions {
     node_name: String,
     skip_defaults: bool,
     pre_trusted_identities: Option<PreTrustedIdentities>,
+    max_credential_size: usize,
+    max_credential_lifetime: Option<Duration>,
+    authority_connect_timeout: Duration,
+    credential_refresh: Option<CredentialRefreshSchedule>,
+    portal_limits: PortalLimits,
+    max_concurrent_credential_presentations: usize,
+    max_concurrent_credential_fetches: usize,
+    credential_verification_ttl: Duration,
+    max_connection_errors: usize,
+    list_routes_on_unknown_path: bool,
+    fallback_identity_name: Option<String>,
+    snapshot_path: Option<PathBuf>,
+    abac_statistics: bool,
 }
 
 impl NodeManagerGeneralOptions {
@@ -185,8 +266,118 @@ impl NodeManagerGeneralOptions {
             node_name,
             skip_defaults,
             pre_trusted_identities,
+            max_credential_size: credentials::DEFAULT_MAX_CREDENTIAL_SIZE,
+            max_credential_lifetime: None,
+            authority_connect_timeout: credentials::DEFAULT_AUTHORITY_CONNECT_TIMEOUT,
+            credential_refresh: Some(CredentialRefreshSchedule::default()),
+            portal_limits: PortalLimits::default(),
+            max_concurrent_credential_presentations:
+                credentials::DEFAULT_MAX_CONCURRENT_CREDENTIAL_PRESENTATIONS,
+            max_concurrent_credential_fetches:
+                credentials::DEFAULT_MAX_CONCURRENT_CREDENTIAL_FETCHES,
+            credential_verification_ttl: credentials::DEFAULT_CREDENTIAL_VERIFICATION_TTL,
+            max_connection_errors: DEFAULT_MAX_CONNECTION_ERRORS,
+            list_routes_on_unknown_path: true,
+            fallback_identity_name: None,
+            snapshot_path: None,
+            abac_statistics: false,
         }
     }
+
+    /// Set the maximum size, in bytes, of a credential accepted from an authority
+    pub fn with_max_credential_size(mut self, max_credential_size: usize) -> Self {
+        self.max_credential_size = max_credential_size;
+        self
+    }
+
+    /// Set the maximum lifetime of a credential accepted from an authority.
+    /// Credentials valid for longer are rejected instead of being stored.
+    pub fn with_max_credential_lifetime(mut self, max_credential_lifetime: Duration) -> Self {
+        self.max_credential_lifetime = Some(max_credential_lifetime);
+        self
+    }
+
+    /// Set how long to wait for an authority to accept a TCP connection, before
+    /// the secure channel to it is created. Unreachable authorities fail after that.
+    pub fn with_authority_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.authority_connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Set when the credential of the node identity is refreshed before it expires
+    pub fn with_credential_refresh(mut self, schedule: CredentialRefreshSchedule) -> Self {
+        self.credential_refresh = Some(schedule);
+        self
+    }
+
+    /// Never refresh the credential of the node identity
+    pub fn without_credential_refresh(mut self) -> Self {
+        self.credential_refresh = None;
+        self
+    }
+
+    /// Set the maximum number of inlets and outlets of the node
+    pub fn with_portal_limits(mut self, portal_limits: PortalLimits) -> Self {
+        self.portal_limits = portal_limits;
+        self
+    }
+
+    /// Set the maximum number of credential presentations running at the same time.
+    /// Presentations past this limit wait for a running one to complete.
+    pub fn with_max_concurrent_credential_presentations(mut self, max: usize) -> Self {
+        self.max_concurrent_credential_presentations = max.max(1);
+        self
+    }
+
+    /// Set the maximum number of credentials fetched from authorities at the same time.
+    /// Fetches past this limit wait for a running one to complete.
+    pub fn with_max_concurrent_credential_fetches(mut self, max: usize) -> Self {
+        self.max_concurrent_credential_fetches = max.max(1);
+        self
+    }
+
+    /// Set how long a verified credential isn't verified again when it's fetched
+    /// again unchanged, at most until the credential expires. A zero TTL verifies
+    /// every fetched credential.
+    pub fn with_credential_verification_ttl(mut self, ttl: Duration) -> Self {
+        self.credential_verification_ttl = ttl;
+        self
+    }
+
+    /// Set the number of recent TCP connection failures kept by the node
+    pub fn with_max_connection_errors(mut self, max: usize) -> Self {
+        self.max_connection_errors = max;
+        self
+    }
+
+    /// Set whether the response to a request matching no route lists the
+    /// supported routes, which it does by default
+    pub fn with_routes_listed_on_unknown_path(mut self, list_routes: bool) -> Self {
+        self.list_routes_on_unknown_path = list_routes;
+        self
+    }
+
+    /// Set the name of the identity used to get a credential when the identity
+    /// named in the request can't be loaded
+    pub fn with_fallback_identity(mut self, name: impl Into<String>) -> Self {
+        self.fallback_identity_name = Some(name.into());
+        self
+    }
+
+    /// Set the file the node manager state is saved to by `POST /node/snapshot`.
+    /// If the file exists when the node starts, the state is restored from it.
+    pub fn with_snapshot_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.snapshot_path = Some(path.into());
+        self
+    }
+
+    /// Count how often each condition of the policies evaluated by the node is met,
+    /// see `GET /node/abac/statistics`. This makes every access decision more
+    /// expensive, so it's disabled by default.
+    pub fn with_abac_statistics(mut self) -> Self {
+        self.abac_statistics = true;
+        self
+    }
 }
 
 pub struct NodeManagerProjectsOptions<'a> {
@@ -194,6 +385,8 @@ pub struct NodeManagerProjectsOptions<'a> {
     project_id: Option<String>,
     projects: BTreeMap<String, ProjectLookup>,
     credential: Option<Credential>,
+    authorities_dir: Option<PathBuf>,
+    credential_self_test: bool,
 }
 
 impl<'a> NodeManagerProjectsOptions<'a> {
@@ -208,8 +401,27 @@ impl<'a> NodeManagerProjectsOptions<'a> {
             project_id,
             projects,
             credential,
+            authorities_dir: None,
+            credential_self_test: false,
         }
     }
+
+    /// Load the node's authorities from the files of `dir` when the node starts, next to
+    /// the authorities of the project. Each file is named after the identifier of the
+    /// authority it describes, e.g. `P6c20e8....json`, and contains that authority's
+    /// identity and access route in JSON. The node doesn't start if any of them is invalid.
+    pub fn with_authorities_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.authorities_dir = Some(dir.into());
+        self
+    }
+
+    /// Check that each authority of the node can be reached when the node starts, and
+    /// that a secure channel can be created to it, without requesting a credential. The
+    /// node doesn't start if either stage fails.
+    pub fn with_credential_self_test(mut self) -> Self {
+        self.credential_self_test = true;
+        self
+    }
 }
 
 pub struct NodeManagerTransportOptions {
@@ -266,6 +478,15 @@ impl NodeManager {
         if let Some(cred) = projects_options.credential {
             identity.set_credential(cred.to_owned()).await;
         }
+        let peer_credential_verifications = CredentialVerificationCache::new();
+        identity
+            .set_credential_verification_cache(peer_credential_verifications.clone())
+            .await;
+
+        transport_options
+            .tcp_transport
+            .registry()
+            .set_max_connection_errors(general_options.max_connection_errors);
 
         let medic = Medic::new();
         let sessions = medic.sessions();
@@ -274,11 +495,34 @@ impl NodeManager {
             cli_state,
             node_name: general_options.node_name,
             transports,
+            api_transport_id,
             tcp_transport: transport_options.tcp_transport,
             controller_identity_id: Self::load_controller_identity_id()?,
             skip_defaults: general_options.skip_defaults,
             enable_credential_checks: projects_options.ac.is_some()
                 && projects_options.project_id.is_some(),
+            max_credential_size: general_options.max_credential_size,
+            max_credential_lifetime: general_options.max_credential_lifetime,
+            authority_connect_timeout: general_options.authority_connect_timeout,
+            credential_refresh: general_options.credential_refresh,
+            credential_refresher: None,
+            portal_limits: general_options.portal_limits,
+            credential_presentations: Arc::new(Semaphore::new(
+                general_options.max_concurrent_credential_presentations,
+            )),
+            max_concurrent_credential_presentations: general_options
+                .max_concurrent_credential_presentations,
+            credential_fetches: Arc::new(CredentialFetches::new(
+                general_options.max_concurrent_credential_fetches,
+            )),
+            max_concurrent_credential_fetches: general_options.max_concurrent_credential_fetches,
+            credential_verifications: Arc::new(CredentialVerifications::new(
+                general_options.credential_verification_ttl,
+            )),
+            peer_credential_verifications,
+            list_routes_on_unknown_path: general_options.list_routes_on_unknown_path,
+            fallback_identity_name: general_options.fallback_identity_name,
+            snapshot_path: general_options.snapshot_path,
             vault,
             identity,
             projects: Arc::new(projects_options.projects),
@@ -289,9 +533,14 @@ impl NodeManager {
                 let ctx = ctx.async_try_clone().await?;
                 tokio::spawn(medic.start(ctx))
             },
+            tcp_event_subscriptions: BTreeMap::new(),
+            draining_listeners: BTreeMap::new(),
             sessions,
             policies: policies_storage,
+            abac_statistics: general_options.abac_statistics.then(AbacStatistics::new),
             attributes_storage,
+            #[cfg(feature = "fault-injection")]
+            credential_faults: VecDeque::new(),
         };
 
         if !general_options.skip_defaults {
@@ -299,6 +548,27 @@ impl NodeManager {
                 s.configure_authorities(ac).await?;
             }
         }
+        if let Some(dir) = projects_options.authorities_dir {
+            s.preload_authorities(&dir).await?;
+        }
+        // A snapshot which can't be restored doesn't prevent the node from starting
+        if let Some(path) = s.snapshot_path.clone().filter(|path| path.exists()) {
+            let res = match NodeManagerSnapshot::read_from(&path) {
+                Ok(snapshot) => s.restore(snapshot).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = res {
+                warn!(%err, path = %path.display(), "cannot restore the node snapshot");
+            }
+        }
+        if s.authorities.is_some() && s.identity.credential().await.is_none() {
+            if let Err(err) = s.restore_node_credential().await {
+                warn!(%err, "cannot restore the persisted node credential");
+            }
+        }
+        if projects_options.credential_self_test {
+            s.credential_self_test().await?;
+        }
         // Always start the echoer service as ockam_api::Medic assumes it will be
         // started unconditionally on every node. It's used for liveness checks.
         s.start_echoer_service_impl(ctx, DefaultAddress::ECHO_SERVICE.into())
@@ -320,10 +590,73 @@ impl NodeManager {
         }
 
         self.authorities = Some(Authorities::new(v));
+        self.credential_verifications.clear();
 
         Ok(())
     }
 
+    /// Add the authorities described by the files of `dir`, in the order of their names.
+    /// An authority is only accepted if its identity is valid and its identifier is the
+    /// name of its file, so that a file can't silently pin a different authority.
+    async fn preload_authorities(&mut self, dir: &Path) -> Result<()> {
+        let read_dir = |e| ApiError::message(format!("cannot read {}: {e}", dir.display()));
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir).map_err(read_dir)? {
+            let path = entry.map_err(read_dir)?.path();
+            if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let vault = self.vault()?;
+        let mut preloaded = Vec::with_capacity(paths.len());
+        for path in paths {
+            let invalid = |e: &dyn std::fmt::Display| {
+                ApiError::message(format!("invalid authority {}: {e}", path.display()))
+            };
+            let contents = std::fs::read_to_string(&path).map_err(|e| invalid(&e))?;
+            let authority: Authority = serde_json::from_str(&contents).map_err(|e| invalid(&e))?;
+            let identity = PublicIdentity::import(authority.identity(), vault)
+                .await
+                .map_err(|e| invalid(&e))?;
+            let expected = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("");
+            if identity.identifier().to_string() != expected {
+                return Err(invalid(&format!(
+                    "its identifier is {}",
+                    identity.identifier()
+                )));
+            }
+            preloaded.push(AuthorityInfo {
+                identity,
+                addr: authority.access_route().clone(),
+            });
+        }
+        self.add_authorities(preloaded);
+
+        Ok(())
+    }
+
+    /// Add authorities to the known ones, skipping those already known
+    fn add_authorities(&mut self, added: Vec<AuthorityInfo>) {
+        let mut authorities = self.authorities.take().map(|a| a.0).unwrap_or_default();
+        for authority in added {
+            if !authorities
+                .iter()
+                .any(|a| a.identity.identifier() == authority.identity.identifier())
+            {
+                authorities.push(authority);
+            }
+        }
+        if !authorities.is_empty() {
+            self.authorities = Some(Authorities::new(authorities));
+        }
+        self.credential_verifications.clear();
+    }
+
     async fn initialize_defaults(&mut self, ctx: &Context) -> Result<()> {
         // Start services
         self.start_vault_service_impl(ctx, DefaultAddress::VAULT_SERVICE.into())
@@ -537,19 +870,48 @@ impl NodeManagerWorker {
                     .to_vec()?
             }
 
-            // ==*== Tcp Connection ==*==
-            // TODO: Get all tcp connections
-            (Get, ["node", "tcp", "connection"]) => {
-                let node_manager = self.node_manager.read().await;
-                self.get_tcp_con_or_list(req, &node_manager.transports, TransportMode::Connect)
-                    .to_vec()?
+            (Get, ["node", "version"]) => self.get_node_version(req).to_vec()?,
+            (Get, ["node", "config"]) => self.get_node_config(req).await.to_vec()?,
+
+            (Post, ["node", "snapshot"]) => self
+                .save_snapshot(req)
+                .await?
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
+
+            (Get, ["node", "identity", "public"]) => {
+                self.get_public_identity(req).await?.to_vec()?
             }
+
+            // ==*== Logging ==*==
+            (Get, ["node", "logging"]) => self.get_log_levels(req).to_vec()?,
+            (Put, ["node", "logging"]) => self.set_log_level(req, dec)?.to_vec()?,
+
+            // ==*== Tcp Connection ==*==
+            (Get, ["node", "tcp", "connection"]) => self.get_tcp_connections(req).await.to_vec()?,
             (Post, ["node", "tcp", "connection"]) => {
                 self.add_transport(req, dec).await?.to_vec()?
             }
             (Delete, ["node", "tcp", "connection"]) => {
                 self.delete_transport(req, dec).await?.to_vec()?
             }
+            (Get, ["node", "tcp", "connection", tid, "stats"]) => self
+                .get_tcp_connection_stats(req, tid)
+                .await
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
+            (Get, ["node", "tcp", "stats"]) => self.get_tcp_transport_stats(req).await.to_vec()?,
+
+            (Get, ["node", "tcp", "errors"]) => {
+                self.get_tcp_connection_errors(req).await.to_vec()?
+            }
+            (Post, ["node", "tcp", "events"]) => {
+                self.subscribe_tcp_events(ctx, req, dec).await?.to_vec()?
+            }
+            (Delete, ["node", "tcp", "events", id]) => {
+                self.unsubscribe_tcp_events(req, id).await.to_vec()?
+            }
+            (Post, ["node", "reachability"]) => {
+                self.probe_reachability(req, dec).await?.to_vec()?
+            }
 
             // ==*== Tcp Listeners ==*==
             (Get, ["node", "tcp", "listener"]) => {
@@ -565,14 +927,68 @@ impl NodeManagerWorker {
             (Delete, ["node", "tcp", "listener"]) => {
                 self.delete_transport(req, dec).await?.to_vec()?
             }
+            (Post, ["node", "tcp", "listener", tid, "migrate"]) => self
+                .migrate_listener(req, dec, tid)
+                .await?
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
+            (Get, ["node", "tcp", "listener", tid, "drain"]) => self
+                .get_listener_drain_status(req, tid)
+                .await
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
 
             // ==*== Credential ==*==
             (Post, ["node", "credentials", "actions", "get"]) => self
                 .get_credential(req, dec, ctx)
                 .await?
                 .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
+            (Post, ["node", "credentials", "actions", "get", "preview"]) => self
+                .preview_credential_request(req, dec, ctx)
+                .await?
+                .to_vec()?,
+            (Get, ["node", "credentials", "source", id]) => self
+                .get_credential_source(req, id)
+                .await?
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
+            (Get, ["node", "credentials", "attributes"]) => self
+                .get_credential_attributes(req)
+                .await?
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
+            (Post, ["node", "credentials", "refresh"]) => self
+                .refresh_credential(req)
+                .await?
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
+            (Post, ["node", "credentials", "verify"]) => {
+                self.verify_credential(req, dec).await?.to_vec()?
+            }
+            (Get, ["node", "credentials", "dependents"]) => {
+                self.get_credential_dependents(req).await.to_vec()?
+            }
+            (Get, ["node", "credentials", "authorities"]) => {
+                self.get_authority_routes(req).await?.to_vec()?
+            }
+            (Delete, ["node", "credentials", "authorities", id]) => {
+                self.delete_authority(req, id).await?.to_vec()?
+            }
+            (Delete, ["node", "credentials", "verifications", id]) => {
+                self.invalidate_peer_credential(req, id).await?.to_vec()?
+            }
             (Post, ["node", "credentials", "actions", "present"]) => {
-                self.present_credential(req, dec).await?.to_vec()?
+                self.present_credential(req, dec).await?
+            }
+            #[cfg(feature = "fault-injection")]
+            (Post, ["node", "faults", "credentials"]) => {
+                self.inject_credential_faults(req, dec).await?.to_vec()?
+            }
+
+            // ==*== Peer attributes ==*==
+            (Get, ["node", "attributes"]) => self.export_attributes(req).await?.to_vec()?,
+            (Post, ["node", "attributes"]) => self.import_attributes(req, dec).await?.to_vec()?,
+            (Get, ["node", "attributes", id]) => self
+                .get_peer_attributes(req, id)
+                .await?
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
+            (Delete, ["node", "attributes", id]) => {
+                self.delete_peer_attributes(req, id).await?.to_vec()?
             }
 
             // ==*== Secure channels ==*==
@@ -582,6 +998,9 @@ impl NodeManagerWorker {
                 self.list_secure_channels(req, &node_manager.registry)
                     .to_vec()?
             }
+            (Get, ["node", "secure_channel", "by_identity"]) => {
+                self.list_secure_channels_by_identity(req).await?.to_vec()?
+            }
             (Get, ["node", "secure_channel_listener"]) => {
                 let node_manager = self.node_manager.read().await;
                 self.list_secure_channel_listener(req, &node_manager.registry)
@@ -593,6 +1012,9 @@ impl NodeManagerWorker {
             (Delete, ["node", "secure_channel"]) => {
                 self.delete_secure_channel(req, dec).await?.to_vec()?
             }
+            (Delete, ["node", "connections", id]) => {
+                self.close_peer_connections(req, id).await?.to_vec()?
+            }
             (Get, ["node", "show_secure_channel"]) => {
                 self.show_secure_channel(req, dec).await?.to_vec()?
             }
@@ -658,7 +1080,30 @@ impl NodeManagerWorker {
             }
             (Get, ["node", "outlet"]) => {
                 let node_manager = self.node_manager.read().await;
-                self.get_outlets(req, &node_manager.registry).to_vec()?
+                self.get_outlets(
+                    req,
+                    &node_manager.registry,
+                    node_manager.tcp_transport.registry(),
+                )
+                .to_vec()?
+            }
+            (Get, ["node", "outlet", alias]) => {
+                let node_manager = self.node_manager.read().await;
+                self.get_outlet(
+                    req,
+                    &node_manager.registry,
+                    node_manager.tcp_transport.registry(),
+                    alias,
+                )
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?
+            }
+            (Delete, ["node", "outlet", alias]) => self
+                .delete_outlet(req, alias)
+                .await
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
+            (Get, ["node", "portals", "limits"]) => self.get_portal_limits(req).await.to_vec()?,
+            (Put, ["node", "portals", "limits"]) => {
+                self.set_portal_limits(req, dec).await?.to_vec()?
             }
             (Post, ["node", "inlet"]) => self.create_inlet(req, dec, ctx).await?.to_vec()?,
             (Post, ["node", "outlet"]) => self.create_outlet(req, dec).await?.to_vec()?,
@@ -678,6 +1123,12 @@ impl NodeManagerWorker {
                     .to_vec()?
             }
 
+            (Get, ["node", "abac", "statistics"]) => self
+                .node_manager
+                .read()
+                .await
+                .get_abac_statistics(req)
+                .either(ResponseBuilder::to_vec, ResponseBuilder::to_vec)?,
             (Post, ["policy", resource, action]) => self
                 .node_manager
                 .read()
@@ -755,9 +1206,7 @@ impl NodeManagerWorker {
             // ==*== Catch-all for Unimplemented APIs ==*==
             _ => {
                 warn!(%method, %path, "Called invalid endpoint");
-                Response::bad_request(req.id())
-                    .body(format!("Invalid endpoint: {path}"))
-                    .to_vec()?
+                self.unknown_route(req).await.to_vec()?
             }
         };
         Ok(r)
@@ -775,12 +1224,23 @@ impl Worker for NodeManagerWorker {
             node_manager.initialize_defaults(ctx).await?;
         }
 
+        if let Some(schedule) = node_manager.credential_refresh {
+            node_manager.credential_refresher =
+                Some(tokio::spawn(credentials::refresh_credential_periodically(
+                    Arc::downgrade(&self.node_manager),
+                    schedule,
+                )));
+        }
+
         Ok(())
     }
 
     async fn shutdown(&mut self, _: &mut Self::Context) -> Result<()> {
         let node_manager = self.node_manager.read().await;
         node_manager.medic.abort();
+        if let Some(refresher) = &node_manager.credential_refresher {
+            refresher.abort();
+        }
         Ok(())
     }
 
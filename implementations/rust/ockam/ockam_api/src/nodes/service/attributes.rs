@@ -0,0 +1,192 @@
+use either::Either;
+use minicbor::Decoder;
+use ockam::Result;
+use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_identity::authenticated_storage::{
+    AttributesEntry, AttributesSnapshot, IdentityAttributeStorageReader,
+    IdentityAttributeStorageWriter,
+};
+use ockam_identity::IdentityIdentifier;
+
+use super::NodeManagerWorker;
+
+impl NodeManagerWorker {
+    /// Return the attributes learned for the given peer
+    pub(super) async fn get_peer_attributes(
+        &self,
+        req: &Request<'_>,
+        id: &str,
+    ) -> Result<Either<ResponseBuilder, ResponseBuilder<AttributesEntry>>> {
+        let node_manager = self.node_manager.read().await;
+        let identifier = IdentityIdentifier::try_from(id)?;
+        match node_manager
+            .attributes_storage
+            .get_attributes(&identifier)
+            .await?
+        {
+            Some(entry) => Ok(Either::Right(Response::ok(req.id()).body(entry))),
+            None => Ok(Either::Left(Response::not_found(req.id()))),
+        }
+    }
+
+    /// Remove the attributes learned for the given peer, e.g. when it is decommissioned
+    pub(super) async fn delete_peer_attributes(
+        &self,
+        req: &Request<'_>,
+        id: &str,
+    ) -> Result<ResponseBuilder> {
+        let node_manager = self.node_manager.read().await;
+        let identifier = IdentityIdentifier::try_from(id)?;
+        node_manager.attributes_storage.delete(&identifier).await?;
+        info!(%identifier, "Deleted peer attributes");
+        Ok(Response::ok(req.id()))
+    }
+
+    /// Export all the learned attributes, e.g. to back them up or migrate them to another node.
+    /// The attributes of pre-trusted identities are not part of the export.
+    pub(super) async fn export_attributes(
+        &self,
+        req: &Request<'_>,
+    ) -> Result<ResponseBuilder<AttributesSnapshot>> {
+        let node_manager = self.node_manager.read().await;
+        let snapshot =
+            AttributesSnapshot::export(node_manager.attributes_storage.storage()).await?;
+        Ok(Response::ok(req.id()).body(snapshot))
+    }
+
+    /// Import attributes previously exported from this node or another one
+    pub(super) async fn import_attributes(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let node_manager = self.node_manager.read().await;
+        let snapshot: AttributesSnapshot = dec.decode()?;
+        let imported = snapshot.import(&node_manager.attributes_storage).await?;
+        info!(%imported, "Imported peer attributes");
+        Ok(Response::ok(req.id()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::nodes::NODEMANAGER_ADDR;
+    use minicbor::Decoder;
+    use ockam::Result;
+    use ockam_core::api::{Request, Response, Status};
+    use ockam_core::compat::collections::BTreeMap;
+    use ockam_core::route;
+    use ockam_identity::authenticated_storage::{
+        mem::InMemoryStorage, AttributesEntry, AttributesSnapshot, AuthenticatedAttributeStorage,
+        IdentityAttributeStorageReader, IdentityAttributeStorageWriter,
+    };
+    use ockam_identity::credential::Timestamp;
+    use ockam_identity::IdentityIdentifier;
+    use ockam_node::Context;
+
+    const PEER1: &str = "P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357a";
+    const PEER2: &str = "P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357b";
+
+    #[ockam_macros::test]
+    async fn delete_only_removes_the_targeted_peer(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let peer1 = IdentityIdentifier::try_from(PEER1)?;
+        let peer2 = IdentityIdentifier::try_from(PEER2)?;
+        {
+            let node_manager = handle.node_manager.read().await;
+            for peer in [&peer1, &peer2] {
+                let entry = AttributesEntry::new(
+                    BTreeMap::from([("role".to_string(), b"device".to_vec())]),
+                    Timestamp::now().unwrap(),
+                    None,
+                    None,
+                );
+                node_manager
+                    .attributes_storage
+                    .put_attributes(peer, entry)
+                    .await?;
+            }
+        }
+
+        let req = Request::get(format!("/node/attributes/{PEER1}")).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let entry: AttributesEntry = dec.decode()?;
+        assert_eq!(entry.attrs().get("role"), Some(&b"device".to_vec()));
+
+        let req = Request::delete(format!("/node/attributes/{PEER1}")).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let req = Request::get(format!("/node/attributes/{PEER1}")).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        let node_manager = handle.node_manager.read().await;
+        let storage = &node_manager.attributes_storage;
+        assert!(storage.get_attributes(&peer1).await?.is_none());
+        assert!(storage.get_attributes(&peer2).await?.is_some());
+        drop(node_manager);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn exported_attributes_can_be_imported(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let peer1 = IdentityIdentifier::try_from(PEER1)?;
+        let peer2 = IdentityIdentifier::try_from(PEER2)?;
+        let entry = AttributesEntry::new(
+            BTreeMap::from([("role".to_string(), b"device".to_vec())]),
+            Timestamp::now().unwrap(),
+            None,
+            Some(peer2.clone()),
+        );
+        {
+            let node_manager = handle.node_manager.read().await;
+            node_manager
+                .attributes_storage
+                .put_attributes(&peer1, entry.clone())
+                .await?;
+        }
+
+        let req = Request::get("/node/attributes").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let snapshot: AttributesSnapshot = dec.decode()?;
+        assert_eq!(snapshot.entries(), &[(peer1.clone(), entry.clone())]);
+
+        // Restore the attributes on another storage
+        let other = AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+        assert_eq!(snapshot.import(&other).await?, 1);
+        assert_eq!(other.get_attributes(&peer1).await?, Some(entry.clone()));
+
+        // Restore the attributes on the node, after they were lost
+        {
+            let node_manager = handle.node_manager.read().await;
+            node_manager.attributes_storage.delete(&peer1).await?;
+        }
+        let req = Request::post("/node/attributes").body(snapshot).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let node_manager = handle.node_manager.read().await;
+        let restored = node_manager
+            .attributes_storage
+            .get_attributes(&peer1)
+            .await?;
+        assert_eq!(restored, Some(entry));
+        drop(node_manager);
+
+        ctx.stop().await
+    }
+}
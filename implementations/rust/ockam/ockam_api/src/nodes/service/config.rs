@@ -0,0 +1,141 @@
+use crate::nodes::models::base::NodeConfig;
+use ockam_core::api::{Request, Response, ResponseBuilder};
+
+use super::version::enabled_features;
+use super::NodeManagerWorker;
+
+impl NodeManagerWorker {
+    /// Return the configuration the node is running with, as it is now rather than
+    /// as it was when the node started
+    pub(super) async fn get_node_config(
+        &self,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<NodeConfig<'static>> {
+        let node_manager = self.node_manager.read().await;
+        let mut config = NodeConfig::new(node_manager.node_name.clone(), enabled_features());
+        config.project_id = node_manager.project_id.clone().map(|id| id.into());
+        config.skip_defaults = node_manager.skip_defaults;
+        config.credential_checks = node_manager.enable_credential_checks;
+        config.max_credential_size = node_manager.max_credential_size as u64;
+        config.portal_limits = node_manager.portal_limits.clone();
+        config.max_connection_errors = node_manager
+            .tcp_transport
+            .registry()
+            .get_max_connection_errors() as u64;
+        config.list_routes_on_unknown_path = node_manager.list_routes_on_unknown_path;
+        config.fallback_identity_name = node_manager
+            .fallback_identity_name
+            .clone()
+            .map(|name| name.into());
+        config.snapshot_path = node_manager
+            .snapshot_path
+            .as_ref()
+            .map(|path| path.display().to_string().into());
+        config.authority_connect_timeout_ms =
+            node_manager.authority_connect_timeout.as_millis() as u64;
+        if let Some(schedule) = &node_manager.credential_refresh {
+            config.credential_refresh_lifetime_fraction = Some(schedule.lifetime_fraction());
+            config.credential_refresh_jitter_ms = Some(schedule.jitter().as_millis() as u64);
+        }
+        config.max_credential_lifetime_secs = node_manager
+            .max_credential_lifetime
+            .map(|lifetime| lifetime.as_secs());
+        config.max_concurrent_credential_presentations =
+            node_manager.max_concurrent_credential_presentations as u64;
+        config.max_concurrent_credential_fetches =
+            node_manager.max_concurrent_credential_fetches as u64;
+        config.credential_verification_ttl_ms =
+            node_manager.credential_verifications.ttl().as_millis() as u64;
+        Response::ok(req.id()).body(config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::nodes::models::base::NodeConfig;
+    use crate::nodes::models::portal::PortalLimits;
+    use crate::nodes::service::credentials::{
+        DEFAULT_AUTHORITY_CONNECT_TIMEOUT, DEFAULT_CREDENTIAL_VERIFICATION_TTL,
+        DEFAULT_MAX_CONCURRENT_CREDENTIAL_FETCHES, DEFAULT_MAX_CONCURRENT_CREDENTIAL_PRESENTATIONS,
+    };
+    use crate::nodes::NODEMANAGER_ADDR;
+    use minicbor::Decoder;
+    use ockam::Result;
+    use ockam_core::api::{Request, Response, Status};
+    use ockam_core::route;
+    use ockam_identity::credential::refresh::CredentialRefreshSchedule;
+    use ockam_node::Context;
+
+    /// Return the portal limits and the maximum credential size of the node config
+    async fn get_config(ctx: &mut Context) -> Result<(PortalLimits, u64)> {
+        let req = Request::get("/node/config").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let config: NodeConfig = dec.decode()?;
+        assert!(config.features.iter().any(|f| f == "lmdb"));
+        Ok((config.portal_limits, config.max_credential_size))
+    }
+
+    #[ockam_macros::test]
+    async fn runtime_changes_are_reflected_in_the_config(ctx: &mut Context) -> Result<()> {
+        let _handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let (portal_limits, max_credential_size) = get_config(ctx).await?;
+        assert_eq!(portal_limits, PortalLimits::default());
+        assert!(max_credential_size > 0);
+
+        let req = Request::put("/node/portals/limits")
+            .body(PortalLimits::new(Some(3), None))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let (portal_limits, _) = get_config(ctx).await?;
+        assert_eq!(portal_limits, PortalLimits::new(Some(3), None));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn credential_settings_are_in_the_config(ctx: &mut Context) -> Result<()> {
+        let _handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let req = Request::get("/node/config").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let config: NodeConfig = dec.decode()?;
+
+        assert_eq!(
+            config.authority_connect_timeout_ms,
+            DEFAULT_AUTHORITY_CONNECT_TIMEOUT.as_millis() as u64
+        );
+        assert_eq!(
+            config.credential_refresh_lifetime_fraction,
+            Some(CredentialRefreshSchedule::DEFAULT_LIFETIME_FRACTION)
+        );
+        assert_eq!(
+            config.credential_refresh_jitter_ms,
+            Some(CredentialRefreshSchedule::DEFAULT_JITTER.as_millis() as u64)
+        );
+        assert_eq!(config.max_credential_lifetime_secs, None);
+        assert_eq!(
+            config.max_concurrent_credential_presentations,
+            DEFAULT_MAX_CONCURRENT_CREDENTIAL_PRESENTATIONS as u64
+        );
+        assert_eq!(
+            config.max_concurrent_credential_fetches,
+            DEFAULT_MAX_CONCURRENT_CREDENTIAL_FETCHES as u64
+        );
+        assert_eq!(
+            config.credential_verification_ttl_ms,
+            DEFAULT_CREDENTIAL_VERIFICATION_TTL.as_millis() as u64
+        );
+
+        ctx.stop().await
+    }
+}
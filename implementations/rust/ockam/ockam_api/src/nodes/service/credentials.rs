@@ -1,160 +1,3104 @@
-use crate::authenticator::direct::{CredentialIssuerClient, RpcClient};
+use crate::authenticator::direct::{credential_request, CredentialIssuerClient, RpcClient};
+use crate::cli_state::{CliStateError, CredentialConfig};
 use crate::error::ApiError;
+use crate::lmdb::LmdbStorage;
 use crate::local_multiaddr_to_route;
-use crate::nodes::models::credentials::{GetCredentialRequest, PresentCredentialRequest};
-use crate::nodes::service::map_multiaddr_err;
+use crate::nodes::models::credentials::{
+    AuthorityRoute, AuthorityRouteList, CredentialAttributes, CredentialDependents,
+    CredentialErrorCode, CredentialPresentationList, CredentialPresentationResult,
+    CredentialRequestPreview, CredentialResponse, CredentialSource, CredentialVerification,
+    GetCredentialRequest, PresentCredentialRequest, VerifyCredentialRequest,
+};
+use crate::nodes::registry::CredentialSourceInfo;
+use crate::nodes::service::{map_multiaddr_err, Authorities, AuthorityInfo};
 use crate::nodes::NodeManager;
-use crate::{create_tcp_session, DefaultAddress};
+use crate::{create_tcp_session_with_options, route_to_multiaddr, DefaultAddress, TcpSession};
 use either::Either;
+use lru::LruCache;
 use minicbor::Decoder;
 use ockam::Result;
-use ockam_core::api::{Error, Request, Response, ResponseBuilder};
-use ockam_core::{route, AsyncTryClone};
+use ockam_core::api::{Error, Request, Response, ResponseBuilder, Status};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::future::join_all;
+use ockam_core::compat::rand::{thread_rng, Rng};
+use ockam_core::compat::sync::Mutex;
+use ockam_core::vault::Hasher;
+use ockam_core::{route, Address, AsyncTryClone};
 use ockam_identity::authenticated_storage::AuthenticatedStorage;
-use ockam_identity::credential::Credential;
-use ockam_identity::{Identity, IdentityVault};
+use ockam_identity::credential::refresh::CredentialRefreshSchedule;
+use ockam_identity::credential::{Credential, CredentialData, Timestamp};
+use ockam_identity::{
+    Identity, IdentityIdentifier, IdentityVault, SecureChannelTrustOptions, TrustIdentifierPolicy,
+    DEFAULT_SECURE_CHANNEL_TIMEOUT,
+};
 use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio;
+use ockam_node::tokio::sync::{oneshot, OwnedSemaphorePermit, RwLock, Semaphore};
 use ockam_node::Context;
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpTransport};
+use ockam_vault::Vault;
+use std::fmt;
+use std::future::Future;
+use std::num::NonZeroUsize;
 use std::str::FromStr;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 
+use super::transport::probe_route;
 use super::NodeManagerWorker;
 
+#[cfg(feature = "fault-injection")]
+use super::fault_injection::credential_fault_error;
+#[cfg(feature = "fault-injection")]
+use crate::nodes::models::credentials::CredentialFault;
+
+/// Default maximum size, in bytes, of a credential accepted from an authority
+pub(crate) const DEFAULT_MAX_CREDENTIAL_SIZE: usize = 64 * 1024;
+
+/// Default maximum number of credential presentations running at the same time
+pub(crate) const DEFAULT_MAX_CONCURRENT_CREDENTIAL_PRESENTATIONS: usize = 16;
+
+/// Default maximum number of credentials fetched from authorities at the same time
+pub(crate) const DEFAULT_MAX_CONCURRENT_CREDENTIAL_FETCHES: usize = 4;
+
+/// Default time during which a verified credential isn't verified again
+pub(crate) const DEFAULT_CREDENTIAL_VERIFICATION_TTL: Duration = Duration::from_secs(60);
+
+/// Number of verified credentials remembered by a node
+const CREDENTIAL_VERIFICATION_CACHE_SIZE: usize = 32;
+
+/// Default time given to a credential presentation to complete
+pub(crate) const DEFAULT_CREDENTIAL_PRESENTATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time given to an authority to accept a TCP connection
+pub(crate) const DEFAULT_AUTHORITY_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time between two checks for a credential to refresh, while the node identity has none
+const CREDENTIAL_REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Delay before retrying the first failed credential refresh, doubled after each failure
+const CREDENTIAL_REFRESH_MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Upper bound of the delay before retrying a failed credential refresh
+const CREDENTIAL_REFRESH_MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// Reject credentials whose encoded size exceeds `max_size`
+fn check_credential_size(credential: &Credential, max_size: usize) -> Result<()> {
+    let size = minicbor::to_vec(credential)?.len();
+    if size > max_size {
+        return Err(ApiError::message(format!(
+            "credential size of {size} bytes exceeds the maximum of {max_size} bytes"
+        )));
+    }
+    Ok(())
+}
+
+/// Reject credentials valid for longer than `max_lifetime`
+fn check_credential_lifetime(credential: &Credential, max_lifetime: Duration) -> Result<()> {
+    let data = CredentialData::try_from(credential)?;
+    let lifetime = data
+        .unverified_expires_at()
+        .elapsed(data.unverified_created_at())
+        .unwrap_or_default();
+    if lifetime > max_lifetime {
+        return Err(ApiError::message(format!(
+            "credential lifetime of {}s exceeds the maximum of {}s",
+            lifetime.as_secs(),
+            max_lifetime.as_secs()
+        )));
+    }
+    Ok(())
+}
+
+/// Refresh the credential of the node identity whenever `schedule` says it's due, until
+/// the node manager is dropped. Failed refreshes are retried after a jittered delay,
+/// doubled after each failure.
+pub(super) async fn refresh_credential_periodically(
+    node_manager: Weak<RwLock<NodeManager>>,
+    schedule: CredentialRefreshSchedule,
+) {
+    let mut retry_delay = CREDENTIAL_REFRESH_MIN_RETRY_DELAY;
+    loop {
+        // The node manager isn't kept alive while waiting
+        let node_manager = match node_manager.upgrade() {
+            Some(node_manager) => node_manager,
+            None => return,
+        };
+        let delay = node_manager
+            .read()
+            .await
+            .credential_refresh_delay(&schedule)
+            .await;
+        let delay = match delay {
+            None => CREDENTIAL_REFRESH_CHECK_INTERVAL,
+            Some(delay) if !delay.is_zero() => delay,
+            Some(_) => match refresh_node_credential(&node_manager).await {
+                Ok(()) => {
+                    debug!("Refreshed the credential of the node identity");
+                    retry_delay = CREDENTIAL_REFRESH_MIN_RETRY_DELAY;
+                    // The schedule of a short-lived credential may already be due again
+                    CREDENTIAL_REFRESH_MIN_RETRY_DELAY
+                }
+                Err(err) => {
+                    warn!(%err, "Failed to refresh the credential of the node identity");
+                    let delay =
+                        retry_delay / 2 + retry_delay.mul_f64(thread_rng().gen_range(0.0..0.5));
+                    retry_delay = (retry_delay * 2).min(CREDENTIAL_REFRESH_MAX_RETRY_DELAY);
+                    delay
+                }
+            },
+        };
+        drop(node_manager);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Replace the credential of the node identity with a new one, from the authority
+/// which issued the current one. The node manager isn't locked while the credential
+/// is fetched, see [`get_credential_unlocked`].
+async fn refresh_node_credential(node_manager: &RwLock<NodeManager>) -> Result<()> {
+    let (identity, authority) = {
+        let node_manager = node_manager.read().await;
+        let identity = node_manager.identity.async_try_clone().await?;
+        let authority = node_manager
+            .registry
+            .credential_sources
+            .get(identity.identifier())
+            .map(|source| source.authority.clone());
+        (identity, authority)
+    };
+    get_credential_unlocked(node_manager, &identity, true, authority.as_ref()).await?;
+    Ok(())
+}
+
 impl NodeManager {
-    pub(super) async fn get_credential_impl<V: IdentityVault, S: AuthenticatedStorage>(
+    /// Return how long to wait before refreshing the credential of the node identity,
+    /// or `None` if it has none
+    async fn credential_refresh_delay(
+        &self,
+        schedule: &CredentialRefreshSchedule,
+    ) -> Option<Duration> {
+        let credential = self.identity.credential().await?;
+        let data = CredentialData::try_from(&credential).ok()?;
+        Some(schedule.refresh_delay(
+            data.unverified_created_at(),
+            data.unverified_expires_at(),
+            Timestamp::now()?,
+        ))
+    }
+
+    /// Load the identity named `name` with the node's vault, or with the default
+    /// vault if the node's vault doesn't hold its keys
+    async fn load_named_identity(
+        &self,
+        ctx: &Context,
+        name: &str,
+    ) -> Result<Identity<Vault, LmdbStorage>> {
+        let idt_state = self.cli_state.identities.get(name)?;
+        match idt_state.get(ctx, self.vault()?).await {
+            Ok(idt) => Ok(idt),
+            Err(_) => {
+                let default_vault = &self.cli_state.vaults.default()?.get().await?;
+                Ok(idt_state.get(ctx, default_vault).await?)
+            }
+        }
+    }
+
+    /// Return the identity a credential is requested for: the identity named `name`,
+    /// or the fallback identity if there is no such identity, or the node identity.
+    /// The name of the fallback identity is returned when it is used.
+    async fn credential_identity(
+        &self,
+        ctx: &Context,
+        name: Option<&str>,
+    ) -> Result<(Identity<Vault, LmdbStorage>, Option<String>)> {
+        let identity = match name {
+            Some(identity) => identity,
+            None => return Ok((self.identity()?.async_try_clone().await?, None)),
+        };
+        // An identity which exists but fails to load is an error, not a reason to
+        // request a credential for another identity
+        let fallback = match (
+            &self.fallback_identity_name,
+            self.cli_state.identities.get(identity),
+        ) {
+            (Some(fallback), Err(CliStateError::NotFound(_))) => fallback,
+            _ => return Ok((self.load_named_identity(ctx, identity).await?, None)),
+        };
+        warn!(%identity, %fallback, "identity not found, using the fallback identity instead");
+        let idt = self.load_named_identity(ctx, fallback).await?;
+        Ok((idt, Some(fallback.clone())))
+    }
+
+    /// Wait until a credential presentation can start without exceeding the
+    /// node's limit. The presentation slot is released when the permit is dropped.
+    pub(super) async fn credential_presentation_permit(&self) -> Result<OwnedSemaphorePermit> {
+        self.credential_presentations
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| ApiError::generic("credential presentations are closed"))
+    }
+
+    /// Present the node's credential to `route`, and its authorities' credential in
+    /// return unless `oneway` is set. Return false if the presentation didn't complete
+    /// within `timeout`
+    async fn present_credential_impl(
+        &self,
+        route: &str,
+        oneway: bool,
+        timeout: Duration,
+    ) -> Result<bool> {
+        // TODO: Replace with self.connect?
+        let route = MultiAddr::from_str(route).map_err(map_multiaddr_err)?;
+        let route = match local_multiaddr_to_route(&route) {
+            Some(route) => route,
+            None => return Err(ApiError::generic("invalid credentials service route")),
+        };
+
+        let identity = self.identity()?;
+
+        let presented = if oneway {
+            tokio::time::timeout(timeout, identity.present_credential(route, None)).await
+        } else {
+            let authorities = self.authorities()?.public_identities();
+            // Waiting for a presentation slot counts towards the timeout
+            tokio::time::timeout(timeout, async {
+                let _permit = self.credential_presentation_permit().await?;
+                identity
+                    .present_credential_mutual(route, &authorities, &self.attributes_storage, None)
+                    .await
+            })
+            .await
+        };
+
+        match presented {
+            Ok(result) => result.map(|_| true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Check that a credential can be fetched for `identity`, and select the authority
+    /// it is fetched from
+    pub(super) async fn prepare_credential_fetch<V: IdentityVault, S: AuthenticatedStorage>(
         &mut self,
         identity: &Identity<V, S>,
         overwrite: bool,
-    ) -> Result<()> {
+        authority: Option<&IdentityIdentifier>,
+    ) -> std::result::Result<CredentialFetch, CredentialError> {
         debug!("Credential check: looking for identity");
 
         if identity.credential().await.is_some() && !overwrite {
-            return Err(ApiError::generic("credential already exists"));
+            return Err(CredentialError::new(
+                CredentialErrorCode::AlreadyExists,
+                ApiError::generic("credential already exists"),
+            ));
+        }
+
+        #[cfg(feature = "fault-injection")]
+        let fault = self.take_credential_fault();
+        #[cfg(feature = "fault-injection")]
+        if let Some(fault @ (CredentialFault::Timeout | CredentialFault::Refused)) = fault {
+            return Err(CredentialError::new(
+                CredentialErrorCode::Unreachable,
+                credential_fault_error(fault),
+            ));
         }
 
         debug!("Credential check: looking for authorities...");
-        let authorities = self.authorities()?;
+        let authority = self.credential_authority(authority)?.clone();
+        let source = CredentialSourceInfo::new(
+            authority.identity.identifier().clone(),
+            authority.addr.clone(),
+        );
+        let flow = self
+            .credential_flow()
+            .await
+            .map_err(|e| CredentialError::new(CredentialErrorCode::Fetch, e))?;
+        Ok(CredentialFetch {
+            authority,
+            source,
+            flow,
+            fetches: self.credential_fetches.clone(),
+            #[cfg(feature = "fault-injection")]
+            fault,
+        })
+    }
+
+    /// Store a credential fetched for `identity`
+    pub(super) async fn store_credential<V: IdentityVault, S: AuthenticatedStorage>(
+        &mut self,
+        identity: &Identity<V, S>,
+        fetch: CredentialFetch,
+        credential: Credential,
+    ) -> std::result::Result<(), CredentialError> {
+        #[cfg(feature = "fault-injection")]
+        if let Some(fault @ CredentialFault::VerificationFailure) = fetch.fault {
+            return Err(CredentialError::new(
+                CredentialErrorCode::Verification,
+                credential_fault_error(fault),
+            ));
+        }
+
+        // Keep the node credential across restarts of the node
+        if identity.identifier() == self.identity.identifier() {
+            if let Err(err) = self.persist_node_credential(&credential, &fetch.source.authority) {
+                warn!(%err, "cannot persist the node credential");
+            }
+        }
+
+        identity.set_credential(credential).await;
+
+        // Keep track of where the credential came from, for auditing and refreshing
+        self.registry
+            .credential_sources
+            .insert(identity.identifier().clone(), fetch.source);
+
+        Ok(())
+    }
+
+    /// Store the credential of the node identity, and the identifier of the authority
+    /// which issued it, in the node state
+    pub(super) fn persist_node_credential(
+        &self,
+        credential: &Credential,
+        authority: &IdentityIdentifier,
+    ) -> Result<()> {
+        let encoded = hex::encode(minicbor::to_vec(credential)?);
+        let config = CredentialConfig::new(authority.to_string(), encoded)?;
+        self.cli_state
+            .nodes
+            .get(&self.node_name)?
+            .set_credential(&config)?;
+        Ok(())
+    }
+
+    /// Set the credential persisted by a previous run of the node on the node identity,
+    /// if it was issued by one of the node's authorities and hasn't expired. Return
+    /// whether a credential was restored.
+    pub(super) async fn restore_node_credential(&mut self) -> Result<bool> {
+        let config = match self.cli_state.nodes.get(&self.node_name)?.credential()? {
+            Some(config) => config,
+            None => return Ok(false),
+        };
+        let credential = config.credential()?;
+        let issuer = IdentityIdentifier::from_str(&config.issuer)?;
+        let authority = self.authorities()?.select(Some(&issuer))?;
+        let source = CredentialSourceInfo::new(issuer.clone(), authority.addr.clone());
+
+        // An expired credential fails the verification, and is fetched again when needed
+        if let Err(err) = self
+            .identity
+            .verify_self_credential(&credential, [&authority.identity])
+            .await
+        {
+            info!(%err, authority = %issuer, "The persisted node credential is not valid anymore");
+            return Ok(false);
+        }
+
+        self.identity.set_credential(credential).await;
+        self.registry
+            .credential_sources
+            .insert(self.identity.identifier().clone(), source);
+        info!(authority = %issuer, "Restored the persisted node credential");
+        Ok(true)
+    }
+
+    /// Return the credential request [`get_credential_unlocked`] would send to the
+    /// authority for `identity`, without sending it
+    async fn credential_request_preview<V: IdentityVault, S: AuthenticatedStorage>(
+        &self,
+        identity: &Identity<V, S>,
+        overwrite: bool,
+        authority: Option<&IdentityIdentifier>,
+    ) -> Result<CredentialRequestPreview<'static>> {
+        let skipped = identity.credential().await.is_some() && !overwrite;
+        let authority = self.authorities()?.select(authority)?;
+        Ok(CredentialRequestPreview::new(
+            identity.identifier().to_string(),
+            authority.identity.identifier().to_string(),
+            &authority.addr,
+            DefaultAddress::CREDENTIAL_ISSUER,
+            credential_request().header(),
+            skipped,
+        ))
+    }
+
+    /// Verify the credential of the node identity the way peers verify it, and return
+    /// its attributes
+    async fn credential_attributes<'a>(
+        &self,
+        credential: &Credential,
+    ) -> Result<CredentialAttributes<'a>> {
+        let issuer = CredentialData::try_from(credential)
+            .map_err(|e| ApiError::message(format!("cannot decode the credential: {e}")))?
+            .unverified_issuer()
+            .clone();
+        let authority = self
+            .authorities()?
+            .public_identities()
+            .into_iter()
+            .find(|authority| authority.identifier() == &issuer)
+            .ok_or_else(|| {
+                ApiError::message(format!("credential issuer {issuer} is not an authority"))
+            })?;
+        let data = authority
+            .verify_credential(credential, self.identity.identifier(), &self.vault)
+            .await?;
+        Ok(CredentialAttributes::new(
+            issuer.to_string(),
+            data.expires_at().unix_time(),
+            data.into_attributes(),
+        ))
+    }
+
+    /// Verify a credential of any subject against the node's authorities, the way the
+    /// credentials presented by peers are verified
+    async fn verify_credential(&self, credential: &Credential) -> CredentialVerification<'static> {
+        let data = match CredentialData::try_from(credential) {
+            Ok(data) => data,
+            Err(e) => {
+                return CredentialVerification::invalid(format!(
+                    "cannot decode the credential: {e}"
+                ))
+            }
+        };
+        let issuer = data.unverified_issuer().clone();
+        let subject = data.unverified_subject().clone();
+        let expires_at = data.unverified_expires_at().unix_time();
+        let invalid = |reason: String| {
+            CredentialVerification::invalid(reason).with_unverified_data(
+                issuer.to_string(),
+                subject.to_string(),
+                expires_at,
+            )
+        };
+
+        let authorities = match self.authorities() {
+            Ok(authorities) => authorities.public_identities(),
+            Err(_) => return invalid("the node has no authority".to_string()),
+        };
+        let authority = match authorities
+            .iter()
+            .find(|authority| authority.identifier() == &issuer)
+        {
+            Some(authority) => authority,
+            None => return invalid(format!("credential issuer {issuer} is not an authority")),
+        };
+        match authority
+            .verify_credential(credential, &subject, &self.vault)
+            .await
+        {
+            Ok(data) => CredentialVerification::valid(
+                issuer.to_string(),
+                subject.to_string(),
+                expires_at,
+                data.into_attributes(),
+            ),
+            Err(e) => invalid(e.to_string()),
+        }
+    }
 
-        // Take first authority
-        let authority = authorities
+    /// Stop trusting the authority with the given identifier, and clear the
+    /// credentials it issued. Return whether the authority was known.
+    pub(super) async fn remove_authority(&mut self, identifier: &IdentityIdentifier) -> bool {
+        let removed = match self.authorities.as_mut() {
+            Some(authorities) => {
+                let known = authorities.0.len();
+                authorities
+                    .0
+                    .retain(|authority| authority.identity.identifier() != identifier);
+                authorities.0.len() != known
+            }
+            None => false,
+        };
+        if self
+            .authorities
             .as_ref()
-            .first()
-            .ok_or_else(|| ApiError::generic("No known Authority"))?;
+            .map(|authorities| authorities.0.is_empty())
+            .unwrap_or(false)
+        {
+            self.authorities = None;
+        }
+        if removed {
+            self.credential_verifications.clear();
+            self.invalidate_untrusted_credentials().await;
+        }
+        removed
+    }
 
-        debug!("Getting credential from : {}", authority.addr);
+    /// Clear the credentials held by the node, and forget where credentials were fetched
+    /// from, when they were issued by an authority the node doesn't trust anymore. The
+    /// node holds the credential of its identity, in memory and in the node state. The
+    /// other identities are loaded for each request, and don't keep their credential.
+    async fn invalidate_untrusted_credentials(&mut self) {
+        let authorities: Vec<IdentityIdentifier> = match &self.authorities {
+            Some(authorities) => authorities
+                .as_ref()
+                .iter()
+                .map(|authority| authority.identity.identifier().clone())
+                .collect(),
+            None => Vec::new(),
+        };
+
+        if let Some(credential) = self.identity.credential().await {
+            let issuer = CredentialData::try_from(&credential)
+                .map(|data| data.unverified_issuer().clone())
+                .ok();
+            if !issuer.map(|i| authorities.contains(&i)).unwrap_or(false) {
+                self.identity.clear_credential().await;
+                info!(
+                    identity = %self.identity.identifier(),
+                    "Cleared the node credential, its issuer isn't trusted anymore"
+                );
+            }
+        }
 
-        let allowed = vec![authority.identity.identifier().clone()];
+        if let Err(err) = self.invalidate_persisted_credential(&authorities) {
+            warn!(%err, "cannot clear the persisted node credential");
+        }
 
-        let authority_tcp_session =
-            match create_tcp_session(&authority.addr, &self.tcp_transport).await {
-                Some(authority_tcp_session) => authority_tcp_session,
-                None => {
-                    error!("INVALID ROUTE");
-                    return Err(ApiError::generic("invalid authority route"));
-                }
+        self.registry.credential_sources.retain(|identity, source| {
+            let trusted = authorities.contains(&source.authority);
+            if !trusted {
+                info!(
+                    %identity,
+                    authority = %source.authority,
+                    "Forgot the source of an untrusted credential"
+                );
+            }
+            trusted
+        });
+    }
+
+    /// Remove the node credential persisted in the node state if it was issued by none
+    /// of `authorities`
+    fn invalidate_persisted_credential(&self, authorities: &[IdentityIdentifier]) -> Result<()> {
+        let node_state = self.cli_state.nodes.get(&self.node_name)?;
+        let config = match node_state.credential()? {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+        let trusted = IdentityIdentifier::from_str(&config.issuer)
+            .map(|issuer| authorities.contains(&issuer))
+            .unwrap_or(false);
+        if !trusted {
+            node_state.clear_credential()?;
+            info!(
+                authority = %config.issuer,
+                "Removed the persisted node credential, its issuer isn't trusted anymore"
+            );
+        }
+        Ok(())
+    }
+
+    /// Return the authority identified by `identifier`, or the first authority
+    fn credential_authority(
+        &self,
+        identifier: Option<&IdentityIdentifier>,
+    ) -> std::result::Result<&AuthorityInfo, CredentialError> {
+        let authorities = self
+            .authorities()
+            .map_err(|e| CredentialError::new(CredentialErrorCode::NoAuthority, e))?;
+        authorities.select(identifier).map_err(|e| {
+            let code = match identifier {
+                Some(_) => CredentialErrorCode::UnknownAuthority,
+                None => CredentialErrorCode::NoAuthority,
             };
+            CredentialError::new(code, e)
+        })
+    }
 
-        debug!("Create secure channel to project authority");
+    /// Return what the credential flow needs from the node manager, so that it runs
+    /// without holding its lock
+    async fn credential_flow(&self) -> Result<CredentialFlow> {
+        Ok(CredentialFlow {
+            tcp_transport: self.tcp_transport.async_try_clone().await?,
+            authorities: self.authorities()?.clone(),
+            authority_connect_timeout: self.authority_connect_timeout,
+            max_credential_size: self.max_credential_size,
+            max_credential_lifetime: self.max_credential_lifetime,
+            vault: self.vault.clone(),
+            verifications: self.credential_verifications.clone(),
+        })
+    }
+
+    /// Check that each authority of the node can be reached, and that a secure channel
+    /// can be created to it. No credential is requested, and the connections are closed
+    /// once checked. The error names the stage of the flow that failed, and the
+    /// authority it failed with.
+    pub(super) async fn credential_self_test(&mut self) -> Result<()> {
+        let authorities = self
+            .authorities()
+            .map_err(|e| credential_self_test_failure(CredentialFlowStage::Authority, e))?
+            .as_ref()
+            .to_vec();
+        if authorities.is_empty() {
+            return Err(credential_self_test_failure(
+                CredentialFlowStage::Authority,
+                "No known Authority",
+            ));
+        }
+
+        let identity = self.identity.async_try_clone().await?;
+        let flow = self.credential_flow().await?;
+        for authority in &authorities {
+            let identifier = authority.identity.identifier();
+            if let Err((stage, e)) = flow.dry_run(&identity, authority).await {
+                let e = format!("{e}, with authority {identifier} at {}", authority.addr);
+                return Err(credential_self_test_failure(stage, e));
+            }
+            info!(authority = %identifier, "Credential self-test passed");
+        }
+
+        Ok(())
+    }
+}
+
+/// Get a credential for `identity` from the authority identified by `authority`, or from
+/// the first authority of the node, and store it. The node manager isn't locked while the
+/// credential is fetched, so that fetches running at the same time can share a request
+/// to the authority.
+pub(super) async fn get_credential_unlocked<V: IdentityVault, S: AuthenticatedStorage>(
+    node_manager: &RwLock<NodeManager>,
+    identity: &Identity<V, S>,
+    overwrite: bool,
+    authority: Option<&IdentityIdentifier>,
+) -> std::result::Result<(), CredentialError> {
+    let fetch = node_manager
+        .write()
+        .await
+        .prepare_credential_fetch(identity, overwrite, authority)
+        .await?;
+    let credential = fetch.run(identity).await?;
+    node_manager
+        .write()
+        .await
+        .store_credential(identity, fetch, credential)
+        .await
+}
+
+/// What the credential flow needs from the node manager, see [`NodeManager::credential_flow`]
+pub(super) struct CredentialFlow {
+    tcp_transport: TcpTransport,
+    authorities: Authorities,
+    authority_connect_timeout: Duration,
+    max_credential_size: usize,
+    max_credential_lifetime: Option<Duration>,
+    vault: Vault,
+    verifications: Arc<CredentialVerifications>,
+}
+
+impl CredentialFlow {
+    /// Get a verified credential for `identity` from `authority`. The secure channel and
+    /// TCP connection opened to the authority are closed once the credential is received.
+    /// On failure, the error comes with the stage of the flow that failed.
+    async fn run<V: IdentityVault, S: AuthenticatedStorage>(
+        &self,
+        identity: &Identity<V, S>,
+        authority: &AuthorityInfo,
+    ) -> std::result::Result<Credential, (CredentialFlowStage, ockam_core::Error)> {
+        let session = self.connect_to_authority(authority).await?;
+        let credential = match self
+            .create_authority_secure_channel(identity, authority, &session)
+            .await
+        {
+            Ok(sc) => {
+                let credential = request_credential_over(identity, sc.clone()).await;
+                let _ = identity.stop_secure_channel(&sc).await;
+                credential
+            }
+            Err(e) => Err(e),
+        };
+        self.disconnect(&session).await;
+        let credential = credential?;
+        self.check_fetched_credential(identity, &credential).await?;
+        Ok(credential)
+    }
+
+    /// Run the flow up to the creation of a secure channel to `authority`, without
+    /// requesting a credential, then close the secure channel and TCP connection
+    async fn dry_run<V: IdentityVault, S: AuthenticatedStorage>(
+        &self,
+        identity: &Identity<V, S>,
+        authority: &AuthorityInfo,
+    ) -> std::result::Result<(), (CredentialFlowStage, ockam_core::Error)> {
+        let session = self.connect_to_authority(authority).await?;
         let sc = self
-            .create_secure_channel_internal(
-                identity,
-                authority_tcp_session.route,
-                Some(allowed),
-                None,
-                authority_tcp_session.session,
-            )
-            .await?;
-        debug!("Created secure channel to project authority");
+            .create_authority_secure_channel(identity, authority, &session)
+            .await;
+        if let Ok(sc) = &sc {
+            let _ = identity.stop_secure_channel(sc).await;
+        }
+        self.disconnect(&session).await;
+        sc.map(|_| ())
+    }
 
-        // Borrow checker issues...
-        let authorities = self.authorities()?;
+    /// Open a TCP connection to `authority`
+    async fn connect_to_authority(
+        &self,
+        authority: &AuthorityInfo,
+    ) -> std::result::Result<TcpSession, (CredentialFlowStage, ockam_core::Error)> {
+        debug!("Getting credential from : {}", authority.addr);
 
-        let client = CredentialIssuerClient::new(
-            RpcClient::new(
-                route![sc, DefaultAddress::CREDENTIAL_ISSUER],
-                identity.ctx(),
-            )
-            .await?,
+        let trust_options =
+            TcpConnectionTrustOptions::new().with_connect_timeout(self.authority_connect_timeout);
+        create_tcp_session_with_options(&authority.addr, &self.tcp_transport, trust_options)
+            .await
+            .map_err(|e| {
+                error!(addr = %authority.addr, err = %e, "cannot reach the authority");
+                (CredentialFlowStage::Reach, e)
+            })
+    }
+
+    /// Close a TCP connection opened by [`connect_to_authority`](Self::connect_to_authority)
+    async fn disconnect(&self, session: &TcpSession) {
+        if let Ok(connection) = session.route.next() {
+            let _ = self.tcp_transport.disconnect(connection).await;
+        }
+    }
+
+    /// Create a secure channel to `authority` over a TCP connection to it
+    async fn create_authority_secure_channel<V: IdentityVault, S: AuthenticatedStorage>(
+        &self,
+        identity: &Identity<V, S>,
+        authority: &AuthorityInfo,
+        session: &TcpSession,
+    ) -> std::result::Result<Address, (CredentialFlowStage, ockam_core::Error)> {
+        debug!("Create secure channel to project authority");
+        let trust_options = SecureChannelTrustOptions::new().with_trust_policy(
+            TrustIdentifierPolicy::new(authority.identity.identifier().clone()),
         );
-        let credential = client.credential().await?;
-        debug!("Got credential");
+        let trust_options = match &session.session {
+            Some((sessions, session_id)) => {
+                trust_options.with_ciphertext_session(sessions, session_id)
+            }
+            None => trust_options,
+        };
+        let sc = identity
+            .create_secure_channel_extended(
+                session.route.clone(),
+                trust_options,
+                DEFAULT_SECURE_CHANNEL_TIMEOUT,
+            )
+            .await
+            .map_err(|e| (CredentialFlowStage::SecureChannel, e))?;
+        debug!("Created secure channel to project authority");
+        Ok(sc)
+    }
 
-        identity
-            .verify_self_credential(&credential, authorities.public_identities().iter())
-            .await?;
+    /// Check that a credential fetched for `identity` is accepted by the node
+    async fn check_fetched_credential<V: IdentityVault, S: AuthenticatedStorage>(
+        &self,
+        identity: &Identity<V, S>,
+        credential: &Credential,
+    ) -> std::result::Result<(), (CredentialFlowStage, ockam_core::Error)> {
+        check_credential_size(credential, self.max_credential_size)
+            .map_err(|e| (CredentialFlowStage::Fetch, e))?;
+
+        self.verify_self_credential(identity, credential)
+            .await
+            .map_err(|e| (CredentialFlowStage::Verify, e))?;
         debug!("Verified self credential");
 
-        identity.set_credential(credential.to_owned()).await;
+        if let Some(max_lifetime) = self.max_credential_lifetime {
+            check_credential_lifetime(credential, max_lifetime)
+                .map_err(|e| (CredentialFlowStage::Verify, e))?;
+        }
 
         Ok(())
     }
+
+    /// Verify that `credential` was issued to `identity` by one of the authorities, unless
+    /// an identical credential was verified recently, see [`CredentialVerifications`]
+    async fn verify_self_credential<V: IdentityVault, S: AuthenticatedStorage>(
+        &self,
+        identity: &Identity<V, S>,
+        credential: &Credential,
+    ) -> Result<()> {
+        let authorities = self.authorities.public_identities();
+        let mut data = minicbor::to_vec(credential)?;
+        data.extend_from_slice(identity.identifier().key_id().as_bytes());
+        for authority in &authorities {
+            data.extend_from_slice(authority.identifier().key_id().as_bytes());
+        }
+        let key = self.vault.sha256(&data).await?;
+        let expires_in = CredentialData::try_from(credential)
+            .ok()
+            .zip(Timestamp::now())
+            .and_then(|(data, now)| data.unverified_expires_at().elapsed(now))
+            .unwrap_or_default();
+        self.verifications
+            .verify(key, expires_in, || {
+                identity.verify_self_credential(credential, authorities.iter())
+            })
+            .await
+    }
 }
 
-impl NodeManagerWorker {
-    pub(super) async fn get_credential(
-        &mut self,
-        req: &Request<'_>,
-        dec: &mut Decoder<'_>,
-        ctx: &Context,
-    ) -> Result<Either<ResponseBuilder<Error<'_>>, ResponseBuilder<Credential>>> {
-        let mut node_manager = self.node_manager.write().await;
-        let request: GetCredentialRequest = dec.decode()?;
+/// Request a credential for `identity` from the credential issuer at the other end of
+/// the secure channel `sc`
+async fn request_credential_over<V: IdentityVault, S: AuthenticatedStorage>(
+    identity: &Identity<V, S>,
+    sc: Address,
+) -> std::result::Result<Credential, (CredentialFlowStage, ockam_core::Error)> {
+    let client = CredentialIssuerClient::new(
+        RpcClient::new(
+            route![sc, DefaultAddress::CREDENTIAL_ISSUER],
+            identity.ctx(),
+        )
+        .await
+        .map_err(|e| (CredentialFlowStage::Fetch, e))?,
+    );
+    let credential = client
+        .credential()
+        .await
+        .map_err(|e| (CredentialFlowStage::Fetch, e))?;
+    debug!("Got credential");
+    Ok(credential)
+}
 
-        let identity = if let Some(identity) = &request.identity_name {
-            let idt_state = node_manager.cli_state.identities.get(identity)?;
-            match idt_state.get(ctx, node_manager.vault()?).await {
-                Ok(idt) => idt,
-                Err(_) => {
-                    let default_vault = &node_manager.cli_state.vaults.default()?.get().await?;
-                    idt_state.get(ctx, default_vault).await?
-                }
-            }
-        } else {
-            node_manager.identity()?.async_try_clone().await?
+/// Credential fetch checked by the node manager, and run without using it
+pub(super) struct CredentialFetch {
+    authority: AuthorityInfo,
+    source: CredentialSourceInfo,
+    flow: CredentialFlow,
+    fetches: Arc<CredentialFetches>,
+    #[cfg(feature = "fault-injection")]
+    fault: Option<CredentialFault>,
+}
+
+impl CredentialFetch {
+    /// Identifies the fetches of a credential for `identity` sharing this one's result
+    fn key<V: IdentityVault, S: AuthenticatedStorage>(
+        &self,
+        identity: &Identity<V, S>,
+    ) -> CredentialFetchKey {
+        (identity.identifier().clone(), self.source.authority.clone())
+    }
+
+    /// Get a credential for `identity`, or wait for the result of an identical fetch
+    /// already running, see [`CredentialFetches`]. The node manager isn't used, so the
+    /// fetch doesn't depend on whether the caller holds its lock.
+    pub(super) async fn run<V: IdentityVault, S: AuthenticatedStorage>(
+        &self,
+        identity: &Identity<V, S>,
+    ) -> std::result::Result<Credential, CredentialError> {
+        let request = async {
+            self.flow
+                .run(identity, &self.authority)
+                .await
+                .map_err(|(stage, err)| CredentialError::new(stage.error_code(), err))
         };
+        self.fetches.fetch(self.key(identity), request).await
+    }
+}
 
-        node_manager
-            .get_credential_impl(&identity, request.is_overwrite())
-            .await?;
+/// Error failing the credential self-test at `stage`
+fn credential_self_test_failure(
+    stage: CredentialFlowStage,
+    e: impl fmt::Display,
+) -> ockam_core::Error {
+    ApiError::message(format!(
+        "credential self-test failed at the {stage} stage: {e}"
+    ))
+}
 
-        if let Some(c) = identity.credential().await {
-            Ok(Either::Right(Response::ok(req.id()).body(c)))
-        } else {
-            let err = Error::default().with_message("error getting credential");
-            Ok(Either::Left(Response::internal_error(req.id()).body(err)))
+/// Stage of the flow getting a credential from an authority
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CredentialFlowStage {
+    /// Finding the authorities of the node
+    Authority,
+    /// Connecting to the authority
+    Reach,
+    /// Creating a secure channel to the authority
+    SecureChannel,
+    /// Presenting an enrollment token to the authority. The node doesn't enroll its
+    /// identities itself, this stage is only run by the clients enrolling them.
+    Enrollment,
+    /// Getting a credential from the authority
+    Fetch,
+    /// Verifying the credential
+    Verify,
+}
+
+impl fmt::Display for CredentialFlowStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CredentialFlowStage::Authority => "authority configuration",
+            CredentialFlowStage::Reach => "authority connection",
+            CredentialFlowStage::SecureChannel => "secure channel",
+            CredentialFlowStage::Enrollment => "enrollment token",
+            CredentialFlowStage::Fetch => "credential fetch",
+            CredentialFlowStage::Verify => "credential verification",
+        })
+    }
+}
+
+impl CredentialFlowStage {
+    /// All the stages, in the order they run
+    pub const ALL: [CredentialFlowStage; 6] = [
+        CredentialFlowStage::Authority,
+        CredentialFlowStage::Reach,
+        CredentialFlowStage::SecureChannel,
+        CredentialFlowStage::Enrollment,
+        CredentialFlowStage::Fetch,
+        CredentialFlowStage::Verify,
+    ];
+
+    /// Code of the error returned when the flow fails at this stage
+    fn error_code(self) -> CredentialErrorCode {
+        match self {
+            CredentialFlowStage::Authority => CredentialErrorCode::NoAuthority,
+            CredentialFlowStage::Reach => CredentialErrorCode::Unreachable,
+            CredentialFlowStage::SecureChannel => CredentialErrorCode::SecureChannel,
+            CredentialFlowStage::Enrollment | CredentialFlowStage::Fetch => {
+                CredentialErrorCode::Fetch
+            }
+            CredentialFlowStage::Verify => CredentialErrorCode::Verification,
         }
     }
+}
 
-    pub(super) async fn present_credential(
-        &self,
-        req: &Request<'_>,
-        dec: &mut Decoder<'_>,
-    ) -> Result<ResponseBuilder> {
-        let node_manager = self.node_manager.read().await;
-        let request: PresentCredentialRequest = dec.decode()?;
+/// Failure getting a credential, with the code identifying its reason
+#[derive(Debug)]
+pub(crate) struct CredentialError {
+    code: CredentialErrorCode,
+    error: ockam_core::Error,
+}
 
-        // TODO: Replace with self.connect?
-        let route = MultiAddr::from_str(&request.route).map_err(map_multiaddr_err)?;
-        let route = match local_multiaddr_to_route(&route) {
-            Some(route) => route,
-            None => return Err(ApiError::generic("invalid credentials service route")),
+impl CredentialError {
+    fn new(code: CredentialErrorCode, error: ockam_core::Error) -> Self {
+        Self { code, error }
+    }
+
+    pub(crate) fn code(&self) -> CredentialErrorCode {
+        self.code
+    }
+
+    pub(crate) fn error(&self) -> &ockam_core::Error {
+        &self.error
+    }
+
+    /// Response to the request `req`, failing with this error
+    fn to_response(&self, req: &Request<'_>) -> ResponseBuilder<Error<'static>> {
+        let status = match self.code {
+            CredentialErrorCode::AlreadyExists => Status::Conflict,
+            CredentialErrorCode::NoAuthority | CredentialErrorCode::UnknownAuthority => {
+                Status::BadRequest
+            }
+            _ => Status::InternalServerError,
         };
+        let body = Error::new(req.path().to_string())
+            .with_code(self.code.code())
+            .with_message(self.error.to_string());
+        Response::builder(req.id(), status).body(body)
+    }
+}
 
-        let identity = node_manager.identity()?;
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
 
-        if request.oneway {
-            identity.present_credential(route, None).await?;
-        } else {
-            identity
-                .present_credential_mutual(
-                    route,
-                    &node_manager.authorities()?.public_identities(),
-                    &node_manager.attributes_storage,
-                    None,
-                )
-                .await?;
+impl From<CredentialError> for ockam_core::Error {
+    fn from(e: CredentialError) -> Self {
+        e.error
+    }
+}
+
+/// Identity a credential is fetched for, and authority it is fetched from
+type CredentialFetchKey = (IdentityIdentifier, IdentityIdentifier);
+
+type CredentialFetchWaiters =
+    Vec<oneshot::Sender<std::result::Result<Credential, (CredentialErrorCode, String)>>>;
+
+/// Credential fetches running on a node.
+///
+/// The number of fetches running at the same time is bounded, and a fetch started
+/// while an identical one runs waits for its result instead of contacting the
+/// authority again.
+pub(crate) struct CredentialFetches {
+    permits: Semaphore,
+    in_flight: Mutex<BTreeMap<CredentialFetchKey, CredentialFetchWaiters>>,
+}
+
+impl CredentialFetches {
+    pub(crate) fn new(max_concurrent_fetches: usize) -> Self {
+        Self {
+            permits: Semaphore::new(max_concurrent_fetches),
+            in_flight: Default::default(),
         }
+    }
+
+    async fn fetch(
+        &self,
+        key: CredentialFetchKey,
+        fetch: impl Future<Output = std::result::Result<Credential, CredentialError>>,
+    ) -> std::result::Result<Credential, CredentialError> {
+        let waiter = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get_mut(&key) {
+                Some(waiters) => {
+                    let (tx, rx) = oneshot::channel();
+                    waiters.push(tx);
+                    Some(rx)
+                }
+                None => {
+                    in_flight.insert(key.clone(), Vec::new());
+                    None
+                }
+            }
+        };
+        if let Some(rx) = waiter {
+            debug!("Credential check: waiting for the credential being fetched");
+            return match rx.await {
+                Ok(Ok(credential)) => Ok(credential),
+                Ok(Err((code, err))) => Err(CredentialError::new(code, ApiError::message(err))),
+                Err(_) => Err(CredentialError::new(
+                    CredentialErrorCode::Fetch,
+                    ApiError::generic("credential fetch was cancelled"),
+                )),
+            };
+        }
+
+        // Waiters are released, with an error, if this fetch is cancelled
+        let mut guard = InFlightFetch {
+            fetches: self,
+            key: Some(key),
+        };
+        let result = match self.permits.acquire().await {
+            Ok(_permit) => fetch.await,
+            Err(_) => Err(CredentialError::new(
+                CredentialErrorCode::Fetch,
+                ApiError::generic("credential fetches are closed"),
+            )),
+        };
+        let waiters = guard.complete();
+        for waiter in waiters {
+            let shared = match &result {
+                Ok(credential) => Ok(credential.clone()),
+                Err(err) => Err((err.code, err.to_string())),
+            };
+            let _ = waiter.send(shared);
+        }
+        result
+    }
+}
+
+/// Removes a fetch from the fetches in flight when it completes or is cancelled
+struct InFlightFetch<'a> {
+    fetches: &'a CredentialFetches,
+    key: Option<CredentialFetchKey>,
+}
+
+impl InFlightFetch<'_> {
+    fn complete(&mut self) -> CredentialFetchWaiters {
+        match self.key.take() {
+            Some(key) => self
+                .fetches
+                .in_flight
+                .lock()
+                .unwrap()
+                .remove(&key)
+                .unwrap_or_default(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Drop for InFlightFetch<'_> {
+    fn drop(&mut self) {
+        self.complete();
+    }
+}
+
+/// Credentials recently verified by a node.
+///
+/// A credential is identified by a hash of its bytes, of the identity it was verified
+/// for, and of the authorities it was verified with. A credential identical to one
+/// verified within the TTL isn't verified again, unless it has expired since.
+pub(crate) struct CredentialVerifications {
+    /// Time until which each verification is reused
+    verified: Mutex<LruCache<[u8; 32], Instant>>,
+    ttl: Duration,
+}
+
+impl CredentialVerifications {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            verified: Mutex::new(LruCache::new(
+                NonZeroUsize::new(CREDENTIAL_VERIFICATION_CACHE_SIZE).expect("0 < 32"),
+            )),
+            ttl,
+        }
+    }
+
+    /// Time during which a verified credential isn't verified again
+    pub(crate) fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Run `verify`, unless the credential identified by `key` was verified within the TTL.
+    /// The credential expires in `expires_in`, its verification isn't reused after that.
+    async fn verify<F>(
+        &self,
+        key: [u8; 32],
+        expires_in: Duration,
+        verify: impl FnOnce() -> F,
+    ) -> Result<()>
+    where
+        F: Future<Output = Result<()>>,
+    {
+        if let Some(valid_until) = self.verified.lock().unwrap().get(&key) {
+            if Instant::now() < *valid_until {
+                debug!("Credential check: skipping the verification of a verified credential");
+                return Ok(());
+            }
+        }
+        verify().await?;
+        let ttl = self.ttl.min(expires_in);
+        if !ttl.is_zero() {
+            self.verified.lock().unwrap().put(key, Instant::now() + ttl);
+        }
+        Ok(())
+    }
+
+    /// Forget the verified credentials, e.g. when the authorities change
+    pub(crate) fn clear(&self) {
+        self.verified.lock().unwrap().clear();
+    }
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn get_credential(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        ctx: &Context,
+    ) -> Result<Either<ResponseBuilder<Error<'_>>, ResponseBuilder<CredentialResponse>>> {
+        let request: GetCredentialRequest = dec.decode()?;
+
+        let (identity, fallback_identity) = self
+            .node_manager
+            .read()
+            .await
+            .credential_identity(ctx, request.identity_name.as_deref())
+            .await?;
+
+        // The node manager isn't locked while the credential is fetched
+        if let Err(err) = get_credential_unlocked(
+            &self.node_manager,
+            &identity,
+            request.is_overwrite(),
+            request.authority.as_ref(),
+        )
+        .await
+        {
+            return Ok(Either::Left(err.to_response(req)));
+        }
+
+        if let (Some(c), Some(now)) = (identity.credential().await, Timestamp::now()) {
+            let expires_at = CredentialData::try_from(&c)?.unverified_expires_at();
+            let mut body = CredentialResponse::new(c, Some(expires_at), now);
+            if let Some(name) = fallback_identity {
+                body = body.with_fallback_identity(name);
+            }
+            Ok(Either::Right(Response::ok(req.id()).body(body)))
+        } else {
+            let err = Error::default().with_message("error getting credential");
+            Ok(Either::Left(Response::internal_error(req.id()).body(err)))
+        }
+    }
+
+    /// Return the credential request the node would send for the identity of a
+    /// [`GetCredentialRequest`], without sending it
+    pub(super) async fn preview_credential_request(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        ctx: &Context,
+    ) -> Result<ResponseBuilder<CredentialRequestPreview<'static>>> {
+        let node_manager = self.node_manager.read().await;
+        let request: GetCredentialRequest = dec.decode()?;
+
+        let (identity, _) = node_manager
+            .credential_identity(ctx, request.identity_name.as_deref())
+            .await?;
+        let preview = node_manager
+            .credential_request_preview(
+                &identity,
+                request.is_overwrite(),
+                request.authority.as_ref(),
+            )
+            .await?;
+        Ok(Response::ok(req.id()).body(preview))
+    }
+
+    /// Return the authority and route the credential of the given identity was fetched from
+    pub(super) async fn get_credential_source<'a>(
+        &self,
+        req: &Request<'_>,
+        id: &str,
+    ) -> Result<Either<ResponseBuilder, ResponseBuilder<CredentialSource<'a>>>> {
+        let node_manager = self.node_manager.read().await;
+        let identifier = IdentityIdentifier::try_from(id)?;
+        match node_manager.registry.credential_sources.get(&identifier) {
+            Some(source) => Ok(Either::Right(Response::ok(req.id()).body(
+                CredentialSource::new(source.authority.to_string(), &source.route),
+            ))),
+            None => Ok(Either::Left(Response::not_found(req.id()))),
+        }
+    }
+
+    /// List the attributes of the node's current credential, i.e. the attributes
+    /// the node presents to its peers, as opposed to the attributes learned from them.
+    /// The credential is verified the way peers verify it.
+    pub(super) async fn get_credential_attributes<'a>(
+        &self,
+        req: &Request<'_>,
+    ) -> Result<Either<ResponseBuilder, ResponseBuilder<CredentialAttributes<'a>>>> {
+        let node_manager = self.node_manager.read().await;
+        let credential = match node_manager.identity()?.credential().await {
+            Some(credential) => credential,
+            None => return Ok(Either::Left(Response::not_found(req.id()))),
+        };
+        let attributes = node_manager.credential_attributes(&credential).await?;
+        Ok(Either::Right(Response::ok(req.id()).body(attributes)))
+    }
+
+    /// Verify a credential against the node's authorities, e.g. to troubleshoot the
+    /// credential of a peer. The outcome is returned in the response body, with the
+    /// reason a credential isn't valid or the attributes of a valid one.
+    pub(super) async fn verify_credential(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<CredentialVerification<'static>>> {
+        let request: VerifyCredentialRequest = dec.decode()?;
+        let node_manager = self.node_manager.read().await;
+        let verification = node_manager.verify_credential(&request.credential).await;
+        Ok(Response::ok(req.id()).body(verification))
+    }
+
+    /// Get a new credential for the node identity from its authority, replacing the
+    /// current one, e.g. after the authority updated the attributes of the node.
+    /// Return the attributes of the new credential.
+    pub(super) async fn refresh_credential<'a>(
+        &self,
+        req: &Request<'_>,
+    ) -> Result<Either<ResponseBuilder<Error<'static>>, ResponseBuilder<CredentialAttributes<'a>>>>
+    {
+        let identity = {
+            let node_manager = self.node_manager.read().await;
+            node_manager.identity()?.async_try_clone().await?
+        };
+        if let Err(err) = get_credential_unlocked(&self.node_manager, &identity, true, None).await {
+            return Ok(Either::Left(err.to_response(req)));
+        }
+        let node_manager = self.node_manager.read().await;
+        let credential = identity
+            .credential()
+            .await
+            .ok_or_else(|| ApiError::generic("error getting credential"))?;
+        let attributes = node_manager.credential_attributes(&credential).await?;
+        info!(
+            identity = %identity.identifier(),
+            expires_at = attributes.expires_at,
+            "Refreshed the node credential"
+        );
+        Ok(Either::Right(Response::ok(req.id()).body(attributes)))
+    }
+
+    /// List the inlets depending on the node's current credential,
+    /// which would stop working if the credential was cleared or rotated
+    pub(super) async fn get_credential_dependents(
+        &self,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<CredentialDependents<'static>> {
+        let node_manager = self.node_manager.read().await;
+        let inlets = node_manager
+            .registry
+            .inlets
+            .iter()
+            .filter(|(_, info)| info.depends_on_credential)
+            .map(|(alias, _)| alias.clone().into())
+            .collect();
+        Response::ok(req.id()).body(CredentialDependents::new(inlets))
+    }
+
+    /// Dial every authority the way credentials are fetched from them, checking
+    /// their identity over a secure channel, and return the route each of them is
+    /// reached by
+    pub(super) async fn get_authority_routes(
+        &self,
+        req: &Request<'_>,
+    ) -> Result<ResponseBuilder<AuthorityRouteList<'static>>> {
+        let (tcp_transport, identity, authorities) = {
+            let node_manager = self.node_manager.read().await;
+            let authorities: Vec<AuthorityInfo> = match node_manager.authorities() {
+                Ok(authorities) => authorities.as_ref().to_vec(),
+                Err(_) => Vec::new(),
+            };
+            (
+                node_manager.tcp_transport.async_try_clone().await?,
+                node_manager.identity.async_try_clone().await?,
+                authorities,
+            )
+        };
+
+        // The authorities are probed concurrently, without holding the node manager
+        let probes = authorities.iter().map(|authority| async {
+            let identifier = authority.identity.identifier();
+            let trust_options = SecureChannelTrustOptions::new()
+                .with_trust_policy(TrustIdentifierPolicy::new(identifier.clone()));
+            let probe = probe_route(
+                &tcp_transport,
+                &identity,
+                &authority.addr,
+                Some(trust_options),
+            );
+            let resolved_route = match probe.await {
+                Ok(route) => route_to_multiaddr(&route)
+                    .ok_or_else(|| format!("cannot represent route {route}")),
+                Err(err) => Err(err.to_string()),
+            };
+            AuthorityRoute::new(identifier.to_string(), &authority.addr, resolved_route)
+        });
+        let list = join_all(probes).await;
+        Ok(Response::ok(req.id()).body(AuthorityRouteList::new(list)))
+    }
+
+    /// Remove an authority from the node's authorities
+    pub(super) async fn delete_authority(
+        &self,
+        req: &Request<'_>,
+        id: &str,
+    ) -> Result<ResponseBuilder> {
+        let mut node_manager = self.node_manager.write().await;
+        let identifier = IdentityIdentifier::try_from(id)?;
+        if node_manager.remove_authority(&identifier).await {
+            info!(%identifier, "Removed authority");
+            Ok(Response::ok(req.id()))
+        } else {
+            Ok(Response::not_found(req.id()))
+        }
+    }
+
+    /// Forget the verifications of the credentials presented by a peer to the node,
+    /// e.g. once its credential was revoked by its authority, so that its next
+    /// presentation is verified again
+    pub(super) async fn invalidate_peer_credential(
+        &self,
+        req: &Request<'_>,
+        id: &str,
+    ) -> Result<ResponseBuilder> {
+        let node_manager = self.node_manager.read().await;
+        let identifier = IdentityIdentifier::try_from(id)?;
+        let invalidated = node_manager
+            .peer_credential_verifications
+            .invalidate(&identifier);
+        if invalidated > 0 {
+            info!(%identifier, %invalidated, "Invalidated peer credential verifications");
+            Ok(Response::ok(req.id()))
+        } else {
+            Ok(Response::not_found(req.id()))
+        }
+    }
+
+    pub(super) async fn present_credential(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<Vec<u8>> {
+        let node_manager = self.node_manager.read().await;
+        let request: PresentCredentialRequest = dec.decode()?;
+        let timeout = request
+            .timeout()
+            .unwrap_or(DEFAULT_CREDENTIAL_PRESENTATION_TIMEOUT);
+
+        // Presentations to several routes run one after another, in the order of the
+        // routes, and don't abort on the first failure: the outcome of each one is
+        // returned instead
+        if let Some(routes) = &request.routes {
+            let mut list = Vec::with_capacity(routes.len());
+            for route in routes {
+                let error = match node_manager
+                    .present_credential_impl(route, request.oneway, timeout)
+                    .await
+                {
+                    Ok(true) => None,
+                    Ok(false) => {
+                        warn!(%route, ?timeout, "Credential presentation timed out");
+                        Some(format!(
+                            "credential presentation timed out after {}ms",
+                            timeout.as_millis()
+                        ))
+                    }
+                    Err(e) => {
+                        warn!(%route, %e, "Credential presentation failed");
+                        Some(e.to_string())
+                    }
+                };
+                list.push(CredentialPresentationResult::new(route.to_string(), error));
+            }
+            let response = Response::ok(req.id()).body(CredentialPresentationList::new(list));
+            return Ok(response.to_vec()?);
+        }
+
+        if !node_manager
+            .present_credential_impl(&request.route, request.oneway, timeout)
+            .await?
+        {
+            warn!(route = %request.route, ?timeout, "Credential presentation timed out");
+            let err = Error::new(req.path().to_string()).with_message(format!(
+                "credential presentation timed out after {}ms",
+                timeout.as_millis()
+            ));
+            return Ok(Response::request_timeout(req.id()).body(err).to_vec()?);
+        }
+
+        Ok(Response::ok(req.id()).to_vec()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        check_credential_lifetime, check_credential_size, get_credential_unlocked,
+        refresh_credential_periodically, CredentialFlowStage, CredentialVerifications,
+    };
+    use crate::authenticator::direct::CredentialIssuer;
+    use crate::cli_state::IdentityConfig;
+    use crate::config::cli::Authority;
+    use crate::error::ApiError;
+    use crate::lmdb::LmdbStorage;
+    use crate::nodes::models::credentials::{
+        AuthorityRouteList, CredentialAttributes, CredentialErrorCode, CredentialPresentationList,
+        CredentialRequestPreview, CredentialResponse, CredentialSource, CredentialVerification,
+        GetCredentialRequest, PresentCredentialRequest, VerifyCredentialRequest,
+    };
+    #[cfg(feature = "fault-injection")]
+    use crate::nodes::models::credentials::{CredentialFault, InjectCredentialFaults};
+    use crate::nodes::service::{Authorities, AuthorityInfo, NodeManagerProjectsOptions};
+    use crate::nodes::NODEMANAGER_ADDR;
+    use crate::util::test::NodeManagerHandle;
+    use crate::DefaultAddress;
+    use minicbor::Decoder;
+    use ockam::identity::TrustEveryonePolicy;
+    use ockam::Result;
+    use ockam_core::api::{Error, Request, Response, Status};
+    use ockam_core::compat::collections::BTreeMap;
+    use ockam_core::compat::future::join_all;
+    #[cfg(feature = "fault-injection")]
+    use ockam_core::errcode::Kind;
+    use ockam_core::{route, Address, AllowAll, Any, AsyncTryClone, Routed, Worker};
+    use ockam_identity::authenticated_storage::mem::InMemoryStorage;
+    use ockam_identity::authenticated_storage::{
+        AttributesEntry, AuthenticatedAttributeStorage, IdentityAttributeStorageWriter,
+    };
+    use ockam_identity::credential::refresh::CredentialRefreshSchedule;
+    use ockam_identity::credential::{
+        Credential, CredentialData, Timestamp, MAX_CREDENTIAL_VALIDITY,
+    };
+    use ockam_identity::{Identity, IdentityIdentifier, IdentitySecureChannelLocalInfo};
+    use ockam_multiaddr::MultiAddr;
+    use ockam_node::tokio::sync::Semaphore;
+    use ockam_node::tokio::time::timeout;
+    use ockam_node::{tokio, Context};
+    use ockam_transport_tcp::TcpListenerTrustOptions;
+    use ockam_vault::Vault;
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[ockam_macros::test]
+    async fn oversized_credential_is_rejected(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let authority = Identity::create(ctx, &vault).await?;
+        let subject = Identity::create(ctx, &vault).await?;
+
+        let builder =
+            Credential::builder(subject.identifier().clone()).with_attribute("blob", &[0u8; 4096]);
+        let credential = authority.issue_credential(builder).await?;
+
+        assert!(check_credential_size(&credential, 1024).is_err());
+        assert!(check_credential_size(&credential, 64 * 1024).is_ok());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn long_lived_credential_is_rejected(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let authority = Identity::create(ctx, &vault).await?;
+        let subject = Identity::create(ctx, &vault).await?;
+
+        let builder =
+            Credential::builder(subject.identifier().clone()).valid_for(Duration::from_secs(3600));
+        let credential = authority.issue_credential(builder).await?;
+
+        assert!(check_credential_lifetime(&credential, Duration::from_secs(1800)).is_err());
+        assert!(check_credential_lifetime(&credential, Duration::from_secs(3600)).is_ok());
+        assert!(check_credential_lifetime(&credential, Duration::from_secs(7200)).is_ok());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn credential_response_reports_the_remaining_lifetime(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+        let authority = Identity::create(ctx, &vault).await?;
+        let subject = Identity::create(ctx, &vault).await?;
+
+        let builder =
+            Credential::builder(subject.identifier().clone()).valid_for(Duration::from_secs(600));
+        let credential = authority.issue_credential(builder).await?;
+        let data = CredentialData::try_from(&credential)?;
+        let created_at = data.unverified_created_at();
+        let expires_at = data.unverified_expires_at();
+
+        let response = CredentialResponse::new(credential.clone(), Some(expires_at), created_at);
+        assert_eq!(response.expires_at, Some(u64::from(expires_at)));
+        assert_eq!(response.seconds_remaining, Some(600));
+
+        // A credential seen after it expired has no time left
+        let response = CredentialResponse::new(credential.clone(), Some(created_at), expires_at);
+        assert_eq!(response.seconds_remaining, Some(0));
+
+        let response = CredentialResponse::new(credential, None, created_at);
+        assert_eq!(response.expires_at, None);
+        assert_eq!(response.seconds_remaining, None);
+
+        // The node reports the lifetime of the credential it gets from its authority
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        start_authority(ctx, &handle).await?;
+        let (status, buf) = get_credential_from(ctx, None).await?;
+        assert_eq!(status, Status::Ok);
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let response: CredentialResponse = dec.decode()?;
+        let remaining = response.seconds_remaining().unwrap();
+        assert!(remaining <= MAX_CREDENTIAL_VALIDITY);
+        assert!(remaining > MAX_CREDENTIAL_VALIDITY - Duration::from_secs(60));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn presentations_past_the_limit_are_queued(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        start_authority(ctx, &handle).await?;
+        let identity = node_identity(&handle).await?;
+        get_credential_unlocked(&handle.node_manager, &identity, false, None).await?;
+        {
+            let mut node_manager = handle.node_manager.write().await;
+            node_manager.credential_presentations = Arc::new(Semaphore::new(2));
+        }
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let mut routes = Vec::new();
+        for i in 0..4 {
+            let address = format!("slow_credentials_{i}");
+            let service = SlowCredentialService {
+                running: running.clone(),
+                max_running: max_running.clone(),
+            };
+            ctx.start_worker(address.as_str(), service, AllowAll, AllowAll)
+                .await?;
+            routes.push(format!("/service/{address}"));
+        }
+
+        // Four mutual presentations start at once, at most two run at a time
+        let node_manager = handle.node_manager.read().await;
+        let presentations = routes.iter().map(|route| {
+            node_manager.present_credential_impl(route, false, Duration::from_secs(5))
+        });
+        let outcomes = join_all(presentations).await;
+        drop(node_manager);
+        assert_eq!(outcomes.len(), 4);
+        // The slow services don't answer like credential exchange workers, but none of
+        // the presentations timed out waiting for a slot
+        assert!(outcomes.iter().all(|outcome| !matches!(outcome, Ok(false))));
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+
+        ctx.stop().await
+    }
+
+    /// Credential service taking a while to answer presentations, and recording
+    /// how many presentations it handles at the same time across its instances
+    struct SlowCredentialService {
+        running: Arc<AtomicUsize>,
+        max_running: Arc<AtomicUsize>,
+    }
+
+    #[ockam::worker]
+    impl Worker for SlowCredentialService {
+        type Context = Context;
+        type Message = Vec<u8>;
+
+        async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Vec<u8>>) -> Result<()> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            ctx.sleep(Duration::from_millis(300)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+
+            let req: Request = Decoder::new(msg.as_body()).decode()?;
+            let res = Response::ok(req.id()).to_vec()?;
+            ctx.send(msg.return_route(), res).await
+        }
+    }
+
+    /// Return a copy of the node identity, to get its credential without holding the
+    /// lock of the node manager
+    async fn node_identity(handle: &NodeManagerHandle) -> Result<Identity<Vault, LmdbStorage>> {
+        let node_manager = handle.node_manager.read().await;
+        node_manager.identity()?.async_try_clone().await
+    }
+
+    /// Start an authority issuing credentials to the node identity, and configure
+    /// it as the node's authority. Return the authority and its route.
+    async fn start_authority(
+        ctx: &Context,
+        handle: &NodeManagerHandle,
+    ) -> Result<(Identity<Vault, InMemoryStorage>, MultiAddr)> {
+        start_authority_with_issuer(ctx, handle, DefaultAddress::CREDENTIAL_ISSUER).await
+    }
+
+    /// Same as [`start_authority`], with the credential issuer started at `issuer_address`
+    async fn start_authority_with_issuer(
+        ctx: &Context,
+        handle: &NodeManagerHandle,
+        issuer_address: &str,
+    ) -> Result<(Identity<Vault, InMemoryStorage>, MultiAddr)> {
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        authority
+            .create_secure_channel_listener("authority_api", TrustEveryonePolicy)
+            .await?;
+        let members = AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+        let entry = AttributesEntry::new(
+            BTreeMap::from([("role".to_string(), b"member".to_vec())]),
+            Timestamp::now().unwrap(),
+            None,
+            None,
+        );
+        members
+            .put_attributes(handle.identity.identifier(), entry)
+            .await?;
+        let issuer = CredentialIssuer::new(
+            b"project".to_vec(),
+            members,
+            authority.async_try_clone().await?,
+        )
+        .await?;
+        ctx.start_worker(issuer_address, issuer, AllowAll, AllowAll)
+            .await?;
+
+        let (listener, _) = handle
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+            .await?;
+        let authority_route = MultiAddr::from_str(&format!(
+            "/ip4/127.0.0.1/tcp/{}/service/authority_api",
+            listener.port()
+        ))
+        .unwrap();
+
+        let mut node_manager = handle.node_manager.write().await;
+        node_manager.authorities = Some(Authorities::new(vec![AuthorityInfo {
+            identity: authority.to_public().await?,
+            addr: authority_route.clone(),
+        }]));
+
+        Ok((authority, authority_route))
+    }
+
+    /// Count the requests to the credential issuer and relay them to it, after `delay`
+    struct CountingIssuer {
+        issuer_address: Address,
+        requests: Arc<AtomicUsize>,
+        delay: Duration,
+    }
+
+    #[ockam::worker]
+    impl Worker for CountingIssuer {
+        type Context = Context;
+        type Message = Any;
+
+        async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            let mut message = msg.into_local_message();
+            message
+                .transport_mut()
+                .onward_route
+                .modify()
+                .pop_front()
+                .prepend(self.issuer_address.clone());
+            ctx.forward(message).await
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn concurrent_fetches_share_one_authority_round_trip(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        start_authority_with_issuer(ctx, &handle, "issuer").await?;
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counting_issuer = CountingIssuer {
+            issuer_address: "issuer".into(),
+            requests: requests.clone(),
+            delay: Duration::from_millis(500),
+        };
+        ctx.start_worker(
+            DefaultAddress::CREDENTIAL_ISSUER,
+            counting_issuer,
+            AllowAll,
+            AllowAll,
+        )
+        .await?;
+
+        // A fetch started by the node itself, e.g. for a secure channel, is in flight
+        let identity = handle
+            .node_manager
+            .read()
+            .await
+            .identity()?
+            .async_try_clone()
+            .await?;
+        let node_manager = handle.node_manager.clone();
+        let background = tokio::spawn(async move {
+            get_credential_unlocked(&node_manager, &identity, true, None)
+                .await
+                .map_err(ockam_core::Error::from)
+        });
+        for _ in 0..100 {
+            if requests.load(Ordering::SeqCst) == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // A request for the same credential shares its result
+        let (status, buf) = get_credential_from(ctx, None).await?;
+        assert_eq!(status, Status::Ok);
+        background.await.unwrap()?;
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let response: CredentialResponse = dec.decode()?;
+        let node_manager = handle.node_manager.read().await;
+        assert_eq!(
+            Some(response.credential),
+            node_manager.identity()?.credential().await
+        );
+        drop(node_manager);
+
+        // A later fetch contacts the authority again
+        let (status, _) = get_credential_from(ctx, None).await?;
+        assert_eq!(status, Status::Ok);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn persisted_credential_is_restored_without_contacting_the_authority(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (authority, _) = start_authority_with_issuer(ctx, &handle, "issuer").await?;
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counting_issuer = CountingIssuer {
+            issuer_address: "issuer".into(),
+            requests: requests.clone(),
+            delay: Duration::ZERO,
+        };
+        ctx.start_worker(
+            DefaultAddress::CREDENTIAL_ISSUER,
+            counting_issuer,
+            AllowAll,
+            AllowAll,
+        )
+        .await?;
+
+        let identity = node_identity(&handle).await?;
+        get_credential_unlocked(&handle.node_manager, &identity, false, None).await?;
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        let mut node_manager = handle.node_manager.write().await;
+        let credential = node_manager.identity.credential().await;
+        assert!(credential.is_some());
+
+        // A restarted node has no credential in memory, only the persisted one
+        node_manager.identity.clear_credential().await;
+        node_manager.registry.credential_sources.clear();
+        assert!(node_manager.restore_node_credential().await?);
+        assert_eq!(node_manager.identity.credential().await, credential);
+        let source = node_manager
+            .registry
+            .credential_sources
+            .get(identity.identifier())
+            .unwrap();
+        assert_eq!(&source.authority, authority.identifier());
+
+        // The authority was only contacted by the first fetch
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        drop(node_manager);
+
+        ctx.stop().await
+    }
+
+    /// Request received by a credential issuer
+    #[derive(Debug, PartialEq, Eq)]
+    struct SentRequest {
+        subject: String,
+        method: Option<String>,
+        path: String,
+        has_body: bool,
+    }
+
+    /// Record the requests to the credential issuer and relay them to it
+    struct RecordingIssuer {
+        issuer_address: Address,
+        sent: Arc<Mutex<Vec<SentRequest>>>,
+    }
+
+    #[ockam::worker]
+    impl Worker for RecordingIssuer {
+        type Context = Context;
+        type Message = Any;
+
+        async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+            let req: Request = Decoder::new(msg.payload()).decode()?;
+            let info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+            self.sent.lock().unwrap().push(SentRequest {
+                subject: info.their_identity_id().to_string(),
+                method: req.method().map(|m| m.to_string()),
+                path: req.path().to_string(),
+                has_body: req.has_body(),
+            });
+            let mut message = msg.into_local_message();
+            message
+                .transport_mut()
+                .onward_route
+                .modify()
+                .pop_front()
+                .prepend(self.issuer_address.clone());
+            ctx.forward(message).await
+        }
+    }
+
+    /// Return the response to a credential request preview
+    async fn preview_credential_request(ctx: &mut Context, overwrite: bool) -> Result<Vec<u8>> {
+        let req = Request::post("/node/credentials/actions/get/preview")
+            .body(GetCredentialRequest::new(overwrite, None))
+            .to_vec()?;
+        ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await
+    }
+
+    fn decode_preview(buf: &[u8]) -> Result<CredentialRequestPreview<'_>> {
+        let mut dec = Decoder::new(buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        Ok(dec.decode()?)
+    }
+
+    #[ockam_macros::test]
+    async fn credential_request_preview_matches_the_sent_request(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (authority, authority_route) =
+            start_authority_with_issuer(ctx, &handle, "issuer").await?;
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let recording_issuer = RecordingIssuer {
+            issuer_address: "issuer".into(),
+            sent: sent.clone(),
+        };
+        ctx.start_worker(
+            DefaultAddress::CREDENTIAL_ISSUER,
+            recording_issuer,
+            AllowAll,
+            AllowAll,
+        )
+        .await?;
+
+        let buf = preview_credential_request(ctx, false).await?;
+        let preview = decode_preview(&buf)?;
+        assert!(!preview.skipped);
+        assert_eq!(preview.authority, authority.identifier().to_string());
+        assert_eq!(preview.route, authority_route.to_string());
+        assert_eq!(preview.issuer, DefaultAddress::CREDENTIAL_ISSUER);
+        // Nothing is sent to the authority
+        assert!(sent.lock().unwrap().is_empty());
+
+        let identity = node_identity(&handle).await?;
+        get_credential_unlocked(&handle.node_manager, &identity, false, None).await?;
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![SentRequest {
+                subject: preview.subject.to_string(),
+                method: preview.method.map(|m| m.to_string()),
+                path: preview.path.to_string(),
+                has_body: preview.has_body,
+            }]
+        );
+
+        // Once the identity has a credential, no request is sent unless overwriting it
+        let buf = preview_credential_request(ctx, false).await?;
+        assert!(decode_preview(&buf)?.skipped);
+        let buf = preview_credential_request(ctx, true).await?;
+        assert!(!decode_preview(&buf)?.skipped);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn credential_source_is_recorded(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (authority, authority_route) = start_authority(ctx, &handle).await?;
+
+        let identity = node_identity(&handle).await?;
+        get_credential_unlocked(&handle.node_manager, &identity, false, None).await?;
+
+        let id = handle.identity.identifier();
+        let req = Request::get(format!("/node/credentials/source/{id}")).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let source: CredentialSource = dec.decode()?;
+        assert_eq!(source.authority, authority.identifier().to_string());
+        assert_eq!(source.route, authority_route.to_string());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn fallback_identity_is_used_when_the_named_one_is_missing(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (authority, _) = start_authority(ctx, &handle).await?;
+
+        let config = IdentityConfig::new(&handle.identity).await;
+        handle.cli_state.identities.create("fallback", config)?;
+        handle.node_manager.write().await.fallback_identity_name = Some("fallback".into());
+
+        let req = Request::post("/node/credentials/actions/get")
+            .body(GetCredentialRequest::new(false, Some("missing".into())))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let response: CredentialResponse = dec.decode()?;
+        assert_eq!(response.fallback_identity.as_deref(), Some("fallback"));
+        let data = CredentialData::try_from(&response.credential)?;
+        assert_eq!(data.unverified_subject(), handle.identity.identifier());
+        assert_eq!(data.unverified_issuer(), authority.identifier());
+
+        // An identity which exists but can't be loaded isn't replaced by the fallback
+        let path = handle.cli_state.dir.join("identities").join("broken.json");
+        std::fs::write(path, "{}").unwrap();
+        let req = Request::post("/node/credentials/actions/get")
+            .body(GetCredentialRequest::new(false, Some("broken".into())))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::InternalServerError));
+
+        ctx.stop().await
+    }
+
+    /// Return an authority nobody listens for
+    async fn unreachable_authority(ctx: &Context) -> Result<AuthorityInfo> {
+        let identity = Identity::create(ctx, &Vault::create()).await?;
+        // Nothing listens on the port once the listener is dropped
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let addr = MultiAddr::from_str(&format!("/ip4/127.0.0.1/tcp/{port}/service/api")).unwrap();
+        Ok(AuthorityInfo {
+            identity: identity.to_public().await?,
+            addr,
+        })
+    }
+
+    async fn get_credential_from(
+        ctx: &mut Context,
+        authority: Option<&IdentityIdentifier>,
+    ) -> Result<(Status, Vec<u8>)> {
+        let mut request = GetCredentialRequest::new(true, None);
+        if let Some(authority) = authority {
+            request = request.with_authority(authority.clone());
+        }
+        let req = Request::post("/node/credentials/actions/get")
+            .body(request)
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        Ok((res.status().unwrap(), buf))
+    }
+
+    #[ockam_macros::test]
+    async fn credential_is_requested_from_the_named_authority(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (authority, authority_route) = start_authority(ctx, &handle).await?;
+
+        // The first authority of the node is one nobody listens for
+        let unreachable = unreachable_authority(ctx).await?;
+        if let Some(authorities) = handle.node_manager.write().await.authorities.as_mut() {
+            authorities.0.insert(0, unreachable);
+        }
+
+        // By default, the first authority is contacted
+        let (status, _) = get_credential_from(ctx, None).await?;
+        assert_ne!(status, Status::Ok);
+
+        let (status, buf) = get_credential_from(ctx, Some(authority.identifier())).await?;
+        assert_eq!(status, Status::Ok);
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let credential = dec.decode::<CredentialResponse>()?.credential;
+        let data = CredentialData::try_from(&credential)?;
+        assert_eq!(data.unverified_issuer(), authority.identifier());
+        let id = handle.identity.identifier();
+        let req = Request::get(format!("/node/credentials/source/{id}")).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let source: CredentialSource = dec.decode()?;
+        assert_eq!(source.authority, authority.identifier().to_string());
+        assert_eq!(source.route, authority_route.to_string());
+
+        // An authority the node doesn't know of is reported
+        let unknown = Identity::create(ctx, &Vault::create()).await?;
+        let (status, buf) = get_credential_from(ctx, Some(unknown.identifier())).await?;
+        assert_ne!(status, Status::Ok);
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let err: Error = dec.decode()?;
+        assert!(err
+            .message()
+            .unwrap()
+            .contains(&unknown.identifier().to_string()));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn credential_is_refreshed_before_it_expires(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (authority, _) = start_authority(ctx, &handle).await?;
+
+        // Inject a short-lived credential for the node identity
+        let short_lived = {
+            let node_manager = handle.node_manager.read().await;
+            let identity = node_manager.identity()?;
+            let builder = Credential::builder(identity.identifier().clone())
+                .valid_for(Duration::from_secs(6));
+            let credential = authority.issue_credential(builder).await?;
+            identity.set_credential(credential.clone()).await;
+            credential
+        };
+        let expires_at = CredentialData::try_from(&short_lived)?.unverified_expires_at();
+
+        let schedule = CredentialRefreshSchedule::default()
+            .with_lifetime_fraction(0.5)?
+            .with_jitter(Duration::ZERO);
+        let refresher = tokio::spawn(refresh_credential_periodically(
+            Arc::downgrade(&handle.node_manager),
+            schedule,
+        ));
+
+        let refreshed = timeout(Duration::from_secs(6), async {
+            loop {
+                let credential = handle.node_manager.read().await.identity.credential().await;
+                match credential {
+                    Some(credential) if credential != short_lived => return credential,
+                    _ => tokio::time::sleep(Duration::from_millis(100)).await,
+                }
+            }
+        })
+        .await
+        .expect("the credential wasn't refreshed");
+        assert!(Timestamp::now().unwrap().unix_time() <= expires_at.unix_time());
+
+        let data = CredentialData::try_from(&refreshed)?;
+        assert_eq!(data.unverified_issuer(), authority.identifier());
+        assert!(data.unverified_expires_at().unix_time() > expires_at.unix_time());
+
+        refresher.abort();
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn credential_refresh_does_not_lock_the_node_manager(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (authority, _) = start_authority_with_issuer(ctx, &handle, "issuer").await?;
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counting_issuer = CountingIssuer {
+            issuer_address: "issuer".into(),
+            requests: requests.clone(),
+            delay: Duration::from_secs(1),
+        };
+        ctx.start_worker(
+            DefaultAddress::CREDENTIAL_ISSUER,
+            counting_issuer,
+            AllowAll,
+            AllowAll,
+        )
+        .await?;
+
+        // The credential of the node identity is due for a refresh right away
+        {
+            let node_manager = handle.node_manager.read().await;
+            let identity = node_manager.identity()?;
+            let builder = Credential::builder(identity.identifier().clone())
+                .valid_for(Duration::from_secs(60));
+            identity
+                .set_credential(authority.issue_credential(builder).await?)
+                .await;
+        }
+        let schedule = CredentialRefreshSchedule::default()
+            .with_lifetime_fraction(f64::EPSILON)?
+            .with_jitter(Duration::ZERO);
+        let refresher = tokio::spawn(refresh_credential_periodically(
+            Arc::downgrade(&handle.node_manager),
+            schedule,
+        ));
+
+        for _ in 0..100 {
+            if requests.load(Ordering::SeqCst) == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // While the authority handles the request, the node manager can be updated
+        let locked = timeout(Duration::from_millis(200), handle.node_manager.write()).await;
+        assert!(locked.is_ok());
+        drop(locked);
+
+        refresher.abort();
+        ctx.stop().await
+    }
+
+    async fn delete_authority(
+        ctx: &mut Context,
+        identifier: &IdentityIdentifier,
+    ) -> Result<Status> {
+        let req =
+            Request::delete(format!("/node/credentials/authorities/{identifier}")).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        Ok(res.status().unwrap())
+    }
+
+    #[ockam_macros::test]
+    async fn removing_an_authority_clears_its_credential(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (issuer, _) = start_authority(ctx, &handle).await?;
+        let other = Identity::create(ctx, &Vault::create()).await?;
+        {
+            let mut node_manager = handle.node_manager.write().await;
+            if let Some(authorities) = node_manager.authorities.as_mut() {
+                authorities.0.push(AuthorityInfo {
+                    identity: other.to_public().await?,
+                    addr: MultiAddr::from_str("/ip4/127.0.0.1/tcp/4000/service/api").unwrap(),
+                });
+            }
+        }
+        let identity = node_identity(&handle).await?;
+        get_credential_unlocked(&handle.node_manager, &identity, false, None).await?;
+
+        let node_state = handle
+            .cli_state
+            .nodes
+            .get(&handle.node_manager.read().await.node_name)?;
+        assert!(node_state.credential()?.is_some());
+
+        // The credential wasn't issued by the removed authority
+        assert_eq!(delete_authority(ctx, other.identifier()).await?, Status::Ok);
+        {
+            let node_manager = handle.node_manager.read().await;
+            assert!(node_manager.identity()?.credential().await.is_some());
+            assert_eq!(node_manager.authorities()?.as_ref().len(), 1);
+        }
+        assert!(node_state.credential()?.is_some());
+
+        assert_eq!(
+            delete_authority(ctx, issuer.identifier()).await?,
+            Status::Ok
+        );
+        {
+            let node_manager = handle.node_manager.read().await;
+            assert!(node_manager.identity()?.credential().await.is_none());
+            assert!(node_manager.registry.credential_sources.is_empty());
+            assert!(node_manager.authorities().is_err());
+        }
+        // The next run of the node doesn't restore it either
+        assert!(node_state.credential()?.is_none());
+
+        assert_eq!(
+            delete_authority(ctx, issuer.identifier()).await?,
+            Status::NotFound
+        );
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn authority_routes_match_the_configured_addresses(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (authority, authority_route) = start_authority(ctx, &handle).await?;
+
+        // A second authority nobody listens for
+        let unreachable = Identity::create(ctx, &Vault::create()).await?;
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let unreachable_route =
+            MultiAddr::from_str(&format!("/ip4/127.0.0.1/tcp/{port}/service/api")).unwrap();
+        // And another one, impersonated by the listener of the first one
+        let impostor = Identity::create(ctx, &Vault::create()).await?;
+        if let Some(authorities) = handle.node_manager.write().await.authorities.as_mut() {
+            authorities.0.push(AuthorityInfo {
+                identity: unreachable.to_public().await?,
+                addr: unreachable_route.clone(),
+            });
+            authorities.0.push(AuthorityInfo {
+                identity: impostor.to_public().await?,
+                addr: authority_route.clone(),
+            });
+        }
+
+        let req = Request::get("/node/credentials/authorities").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let routes: AuthorityRouteList = dec.decode()?;
+        assert_eq!(routes.list.len(), 3);
+
+        let reachable = &routes.list[0];
+        assert_eq!(reachable.authority, authority.identifier().to_string());
+        assert_eq!(reachable.route, authority_route.to_string());
+        assert!(reachable.reachable);
+        let resolved_route = reachable.resolved_route.as_ref().unwrap();
+        assert!(resolved_route.ends_with("/service/authority_api"));
+
+        let unreachable_authority = &routes.list[1];
+        assert_eq!(
+            unreachable_authority.authority,
+            unreachable.identifier().to_string()
+        );
+        assert_eq!(unreachable_authority.route, unreachable_route.to_string());
+        assert!(!unreachable_authority.reachable);
+        assert!(unreachable_authority.resolved_route.is_none());
+        assert!(unreachable_authority.error.is_some());
+
+        let impostor_authority = &routes.list[2];
+        assert_eq!(
+            impostor_authority.authority,
+            impostor.identifier().to_string()
+        );
+        assert!(!impostor_authority.reachable);
+        assert!(impostor_authority.error.is_some());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn preloaded_authorities_are_available_after_startup(ctx: &mut Context) -> Result<()> {
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        let authority_route = MultiAddr::from_str("/ip4/127.0.0.1/tcp/4000/service/api").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(format!("{}.json", authority.identifier()));
+        let contents = Authority::new(authority.export().await?, authority_route.clone());
+        std::fs::write(file, serde_json::to_string(&contents).unwrap()).unwrap();
+
+        let handle = crate::util::test::start_manager_for_tests_with_projects_options(
+            ctx,
+            NodeManagerProjectsOptions::new(None, None, Default::default(), None)
+                .with_authorities_dir(dir.path()),
+        )
+        .await?;
+
+        {
+            let node_manager = handle.node_manager.read().await;
+            let authorities = node_manager.authorities()?.as_ref();
+            assert_eq!(authorities.len(), 1);
+            assert_eq!(authorities[0].identity.identifier(), authority.identifier());
+            assert_eq!(authorities[0].addr, authority_route);
+        }
+
+        ctx.stop().await
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[ockam_macros::test]
+    async fn injected_credential_fault_fails_fetch_until_exhausted(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        start_authority(ctx, &handle).await?;
+
+        let req = Request::post("/node/faults/credentials")
+            .body(InjectCredentialFaults::new(CredentialFault::Refused, 2))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let identity = node_identity(&handle).await?;
+
+        // The injected failures are returned as if the authority refused the connection,
+        // and leave the node without credential, so that the next fetch retries
+        for _ in 0..2 {
+            let err = get_credential_unlocked(&handle.node_manager, &identity, false, None)
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), CredentialErrorCode::Unreachable);
+            assert_eq!(err.error().code().kind, Kind::Io);
+            assert!(identity.credential().await.is_none());
+        }
+
+        // Once the faults are exhausted the fetch succeeds again
+        get_credential_unlocked(&handle.node_manager, &identity, false, None).await?;
+        assert!(identity.credential().await.is_some());
+
+        ctx.stop().await
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[ockam_macros::test]
+    async fn injected_credential_fault_makes_the_refresher_retry(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (authority, _) = start_authority_with_issuer(ctx, &handle, "issuer").await?;
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counting_issuer = CountingIssuer {
+            issuer_address: "issuer".into(),
+            requests: requests.clone(),
+            delay: Duration::ZERO,
+        };
+        ctx.start_worker(
+            DefaultAddress::CREDENTIAL_ISSUER,
+            counting_issuer,
+            AllowAll,
+            AllowAll,
+        )
+        .await?;
+
+        // The credential of the node identity is due for a refresh right away
+        let due = {
+            let node_manager = handle.node_manager.read().await;
+            let identity = node_manager.identity()?;
+            let builder = Credential::builder(identity.identifier().clone())
+                .valid_for(Duration::from_secs(60));
+            let credential = authority.issue_credential(builder).await?;
+            identity.set_credential(credential.clone()).await;
+            credential
+        };
+
+        let req = Request::post("/node/faults/credentials")
+            .body(InjectCredentialFaults::new(CredentialFault::Timeout, 1))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let schedule = CredentialRefreshSchedule::default()
+            .with_lifetime_fraction(f64::EPSILON)?
+            .with_jitter(Duration::ZERO);
+        let refresher = tokio::spawn(refresh_credential_periodically(
+            Arc::downgrade(&handle.node_manager),
+            schedule,
+        ));
+
+        // The first refresh fails without reaching the authority, and the refresher
+        // retries after its retry delay instead of giving up
+        timeout(Duration::from_secs(5), async {
+            loop {
+                let credential = handle.node_manager.read().await.identity.credential().await;
+                match credential {
+                    Some(credential) if credential != due => return,
+                    _ => tokio::time::sleep(Duration::from_millis(50)).await,
+                }
+            }
+        })
+        .await
+        .expect("the refresher didn't retry after the injected failure");
+        assert!(handle
+            .node_manager
+            .read()
+            .await
+            .credential_faults
+            .is_empty());
+        // Only the retry reached the authority
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        refresher.abort();
+        ctx.stop().await
+    }
+
+    /// Send `request` to the node manager, and return the status of the response and
+    /// the code of its error, if any
+    async fn get_credential_error_code(
+        ctx: &mut Context,
+        request: GetCredentialRequest,
+    ) -> Result<(Status, Option<CredentialErrorCode>)> {
+        let req = Request::post("/node/credentials/actions/get")
+            .body(request)
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        let status = res.status().unwrap();
+        if status == Status::Ok {
+            return Ok((status, None));
+        }
+        let err: Error = dec.decode()?;
+        Ok((status, err.code().and_then(CredentialErrorCode::from_code)))
+    }
+
+    #[ockam_macros::test]
+    async fn identical_credential_is_verified_once(ctx: &mut Context) -> Result<()> {
+        let verifications = CredentialVerifications::new(Duration::from_secs(60));
+        let calls = AtomicUsize::new(0);
+        let verify = |valid: bool| {
+            let calls = &calls;
+            move || async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                if valid {
+                    Ok(())
+                } else {
+                    Err(ApiError::generic("invalid credential"))
+                }
+            }
+        };
+
+        let valid_for = Duration::from_secs(3600);
+        verifications
+            .verify([1; 32], valid_for, verify(true))
+            .await?;
+        verifications
+            .verify([1; 32], valid_for, verify(true))
+            .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Other credentials, and failed verifications, aren't cached
+        assert!(verifications
+            .verify([2; 32], valid_for, verify(false))
+            .await
+            .is_err());
+        assert!(verifications
+            .verify([2; 32], valid_for, verify(false))
+            .await
+            .is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Changing the authorities clears the cache
+        verifications.clear();
+        verifications
+            .verify([1; 32], valid_for, verify(true))
+            .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        // A credential expiring before the end of the TTL is verified again once expired
+        let expires_in = Duration::from_millis(100);
+        verifications
+            .verify([3; 32], expires_in, verify(true))
+            .await?;
+        verifications
+            .verify([3; 32], expires_in, verify(true))
+            .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        verifications
+            .verify([3; 32], Duration::ZERO, verify(true))
+            .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+
+        // Without a TTL, every credential is verified
+        let verifications = CredentialVerifications::new(Duration::ZERO);
+        verifications
+            .verify([1; 32], valid_for, verify(true))
+            .await?;
+        verifications
+            .verify([1; 32], valid_for, verify(true))
+            .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 8);
+
+        ctx.stop().await
+    }
+
+    #[test]
+    fn credential_error_codes_are_distinct_and_stable() {
+        let codes = [
+            (CredentialErrorCode::NoAuthority, 1),
+            (CredentialErrorCode::UnknownAuthority, 2),
+            (CredentialErrorCode::AlreadyExists, 3),
+            (CredentialErrorCode::Unreachable, 4),
+            (CredentialErrorCode::SecureChannel, 5),
+            (CredentialErrorCode::Fetch, 6),
+            (CredentialErrorCode::Verification, 7),
+        ];
+        for (code, value) in codes {
+            assert_eq!(code.code(), value);
+            assert_eq!(CredentialErrorCode::from_code(value), Some(code));
+        }
+        assert_eq!(CredentialErrorCode::from_code(0), None);
+
+        // Each stage of the credential flow fails with its own code
+        let stages = [
+            (
+                CredentialFlowStage::Authority,
+                CredentialErrorCode::NoAuthority,
+            ),
+            (CredentialFlowStage::Reach, CredentialErrorCode::Unreachable),
+            (
+                CredentialFlowStage::SecureChannel,
+                CredentialErrorCode::SecureChannel,
+            ),
+            (CredentialFlowStage::Fetch, CredentialErrorCode::Fetch),
+            (
+                CredentialFlowStage::Verify,
+                CredentialErrorCode::Verification,
+            ),
+        ];
+        for (stage, code) in stages {
+            assert_eq!(stage.error_code(), code);
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn authority_and_overwrite_errors_have_their_code(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (status, code) =
+            get_credential_error_code(ctx, GetCredentialRequest::new(true, None)).await?;
+        assert_eq!(status, Status::BadRequest);
+        assert_eq!(code, Some(CredentialErrorCode::NoAuthority));
+
+        start_authority(ctx, &handle).await?;
+        let unknown = Identity::create(ctx, &Vault::create()).await?;
+        let request =
+            GetCredentialRequest::new(true, None).with_authority(unknown.identifier().clone());
+        let (status, code) = get_credential_error_code(ctx, request).await?;
+        assert_eq!(status, Status::BadRequest);
+        assert_eq!(code, Some(CredentialErrorCode::UnknownAuthority));
+
+        let (status, _) =
+            get_credential_error_code(ctx, GetCredentialRequest::new(false, None)).await?;
+        assert_eq!(status, Status::Ok);
+        let (status, code) =
+            get_credential_error_code(ctx, GetCredentialRequest::new(false, None)).await?;
+        assert_eq!(status, Status::Conflict);
+        assert_eq!(code, Some(CredentialErrorCode::AlreadyExists));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn unreachable_authority_error_has_its_code(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let unreachable = unreachable_authority(ctx).await?;
+        handle.node_manager.write().await.authorities = Some(Authorities::new(vec![unreachable]));
+
+        let (status, code) =
+            get_credential_error_code(ctx, GetCredentialRequest::new(true, None)).await?;
+        assert_eq!(status, Status::InternalServerError);
+        assert_eq!(code, Some(CredentialErrorCode::Unreachable));
+
+        ctx.stop().await
+    }
+
+    /// Credential issuer refusing every request
+    struct RefusingIssuer;
+
+    #[ockam::worker]
+    impl Worker for RefusingIssuer {
+        type Context = Context;
+        type Message = Vec<u8>;
+
+        async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Vec<u8>>) -> Result<()> {
+            let req: Request = Decoder::new(msg.as_body()).decode()?;
+            let res = Response::forbidden(req.id()).to_vec()?;
+            ctx.send(msg.return_route(), res).await
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn refused_credential_error_has_its_code(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        start_authority_with_issuer(ctx, &handle, "issuer").await?;
+        ctx.start_worker(
+            DefaultAddress::CREDENTIAL_ISSUER,
+            RefusingIssuer,
+            AllowAll,
+            AllowAll,
+        )
+        .await?;
+
+        let (status, code) =
+            get_credential_error_code(ctx, GetCredentialRequest::new(true, None)).await?;
+        assert_eq!(status, Status::InternalServerError);
+        assert_eq!(code, Some(CredentialErrorCode::Fetch));
+
+        ctx.stop().await
+    }
+
+    #[cfg(feature = "fault-injection")]
+    #[ockam_macros::test]
+    async fn unverified_credential_error_has_its_code(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        start_authority(ctx, &handle).await?;
+        handle
+            .node_manager
+            .write()
+            .await
+            .credential_faults
+            .push_back((CredentialFault::VerificationFailure, 1));
+
+        let (status, code) =
+            get_credential_error_code(ctx, GetCredentialRequest::new(true, None)).await?;
+        assert_eq!(status, Status::InternalServerError);
+        assert_eq!(code, Some(CredentialErrorCode::Verification));
+
+        ctx.stop().await
+    }
+
+    /// Credential service which never answers
+    struct SilentCredentialService;
+
+    #[ockam::worker]
+    impl Worker for SilentCredentialService {
+        type Context = Context;
+        type Message = Any;
+
+        async fn handle_message(&mut self, _ctx: &mut Context, _msg: Routed<Any>) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn stalled_credential_presentation_times_out(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        start_authority(ctx, &handle).await?;
+        let identity = node_identity(&handle).await?;
+        get_credential_unlocked(&handle.node_manager, &identity, false, None).await?;
+        ctx.start_worker(
+            "silent_credentials",
+            SilentCredentialService,
+            AllowAll,
+            AllowAll,
+        )
+        .await?;
+
+        let to = MultiAddr::from_str("/service/silent_credentials").unwrap();
+        let request =
+            PresentCredentialRequest::new(&to, true).with_timeout(Duration::from_millis(200));
+        let req = Request::post("/node/credentials/actions/present")
+            .body(request)
+            .to_vec()?;
+        let buf: Vec<u8> = timeout(
+            Duration::from_secs(5),
+            ctx.send_and_receive(route![NODEMANAGER_ADDR], req),
+        )
+        .await
+        .expect("the presentation should time out instead of hanging")?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::RequestTimeout));
+        let err: Error = dec.decode()?;
+        assert!(err.message().unwrap().contains("timed out"));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn presentation_waiting_for_a_slot_times_out(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        start_authority(ctx, &handle).await?;
+        let identity = node_identity(&handle).await?;
+        get_credential_unlocked(&handle.node_manager, &identity, false, None).await?;
+        let permit = {
+            let mut node_manager = handle.node_manager.write().await;
+            node_manager.credential_presentations = Arc::new(Semaphore::new(1));
+            node_manager.credential_presentation_permit().await?
+        };
+        ctx.start_worker(
+            "accepting_credentials",
+            AcceptingCredentialService,
+            AllowAll,
+            AllowAll,
+        )
+        .await?;
+
+        // The only presentation slot is taken, the mutual presentation never starts
+        let to = MultiAddr::from_str("/service/accepting_credentials").unwrap();
+        let request =
+            PresentCredentialRequest::new(&to, false).with_timeout(Duration::from_millis(200));
+        let req = Request::post("/node/credentials/actions/present")
+            .body(request)
+            .to_vec()?;
+        let buf: Vec<u8> = timeout(
+            Duration::from_secs(5),
+            ctx.send_and_receive(route![NODEMANAGER_ADDR], req),
+        )
+        .await
+        .expect("the presentation should time out instead of waiting for a slot")?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::RequestTimeout));
+        drop(permit);
+
+        ctx.stop().await
+    }
+
+    /// Credential service accepting any presentation
+    struct AcceptingCredentialService;
+
+    #[ockam::worker]
+    impl Worker for AcceptingCredentialService {
+        type Context = Context;
+        type Message = Vec<u8>;
+
+        async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Vec<u8>>) -> Result<()> {
+            let req: Request = Decoder::new(msg.as_body()).decode()?;
+            let res = Response::ok(req.id()).to_vec()?;
+            ctx.send(msg.return_route(), res).await
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn credential_presentation_to_several_routes_reports_each_outcome(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        start_authority(ctx, &handle).await?;
+        let identity = node_identity(&handle).await?;
+        get_credential_unlocked(&handle.node_manager, &identity, false, None).await?;
+        ctx.start_worker(
+            "accepting_credentials",
+            AcceptingCredentialService,
+            AllowAll,
+            AllowAll,
+        )
+        .await?;
+        ctx.start_worker("refusing_credentials", RefusingIssuer, AllowAll, AllowAll)
+            .await?;
+
+        // The first presentation fails, without aborting the second one
+        let refusing = MultiAddr::from_str("/service/refusing_credentials").unwrap();
+        let accepting = MultiAddr::from_str("/service/accepting_credentials").unwrap();
+        let request = PresentCredentialRequest::new(&refusing, true)
+            .with_routes(&[refusing.clone(), accepting.clone()])
+            .with_timeout(Duration::from_secs(5));
+        let req = Request::post("/node/credentials/actions/present")
+            .body(request)
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let presentations: CredentialPresentationList = dec.decode()?;
+        assert_eq!(presentations.list.len(), 2);
+        assert_eq!(presentations.list[0].route, refusing.to_string());
+        assert!(!presentations.list[0].is_presented());
+        assert_eq!(presentations.list[1].route, accepting.to_string());
+        assert!(presentations.list[1].is_presented());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn own_credential_attributes_are_listed(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        // The node doesn't hold a credential yet
+        let req = Request::get("/node/credentials/attributes").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        let builder = Credential::builder(handle.identity.identifier().clone())
+            .with_attribute("role", b"member")
+            .with_attribute("zone", b"eu-west");
+        let credential = authority.issue_credential(builder).await?;
+        {
+            let mut node_manager = handle.node_manager.write().await;
+            node_manager.authorities = Some(Authorities::new(vec![AuthorityInfo {
+                identity: authority.to_public().await?,
+                addr: MultiAddr::from_str("/service/authority_api").unwrap(),
+            }]));
+            node_manager.identity()?.set_credential(credential).await;
+        }
+
+        let req = Request::get("/node/credentials/attributes").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let listed: CredentialAttributes = dec.decode()?;
+        assert_eq!(listed.issuer, authority.identifier().to_string());
+        assert_eq!(listed.attributes.len(), 2);
+        assert_eq!(listed.attributes.get("role"), Some(&b"member"[..]));
+        assert_eq!(listed.attributes.get("zone"), Some(&b"eu-west"[..]));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn peer_credential_is_verified_once_until_invalidated(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        set_authority(&handle, &authority).await?;
+        handle
+            .node_manager
+            .write()
+            .await
+            .start_credentials_service_impl("peer_credentials".into(), true)
+            .await?;
+        node_identity(&handle)
+            .await?
+            .create_secure_channel_listener("peer_listener", TrustEveryonePolicy)
+            .await?;
+
+        let peer = Identity::create(ctx, &Vault::create()).await?;
+        let credential = authority
+            .issue_credential(Credential::builder(peer.identifier().clone()))
+            .await?;
+        peer.set_credential(credential).await;
+        let channel = peer
+            .create_secure_channel(route!["peer_listener"], TrustEveryonePolicy)
+            .await?;
+
+        // The second presentation of the same credential isn't verified again
+        for _ in 0..2 {
+            peer.present_credential(route![channel.clone(), "peer_credentials"], None)
+                .await?;
+        }
+        {
+            let node_manager = handle.node_manager.read().await;
+            assert_eq!(node_manager.peer_credential_verifications.hits(), 1);
+        }
+
+        // Once invalidated, the next presentation is verified again
+        let id = peer.identifier();
+        let req = Request::delete(format!("/node/credentials/verifications/{id}")).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        peer.present_credential(route![channel, "peer_credentials"], None)
+            .await?;
+        {
+            let node_manager = handle.node_manager.read().await;
+            assert_eq!(node_manager.peer_credential_verifications.hits(), 1);
+            assert_eq!(node_manager.peer_credential_verifications.len(), 1);
+        }
+
+        ctx.stop().await
+    }
+
+    /// Trust `authority` as the only authority of the node
+    async fn set_authority(
+        handle: &NodeManagerHandle,
+        authority: &Identity<Vault, InMemoryStorage>,
+    ) -> Result<()> {
+        let mut node_manager = handle.node_manager.write().await;
+        node_manager.authorities = Some(Authorities::new(vec![AuthorityInfo {
+            identity: authority.to_public().await?,
+            addr: MultiAddr::from_str("/service/authority_api").unwrap(),
+        }]));
+        Ok(())
+    }
+
+    /// Ask the node to verify `credential` and return the encoded response
+    async fn verify_credential(ctx: &Context, credential: Credential) -> Result<Vec<u8>> {
+        let req = Request::post("/node/credentials/verify")
+            .body(VerifyCredentialRequest::new(credential))
+            .to_vec()?;
+        ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await
+    }
+
+    #[ockam_macros::test]
+    async fn credential_issued_by_an_authority_is_valid(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        set_authority(&handle, &authority).await?;
+
+        // The credential of a peer, not the node's own
+        let peer = Identity::create(ctx, &Vault::create()).await?;
+        let builder =
+            Credential::builder(peer.identifier().clone()).with_attribute("role", b"member");
+        let credential = authority.issue_credential(builder).await?;
+
+        let buf = verify_credential(ctx, credential).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let verification: CredentialVerification = dec.decode()?;
+        assert!(verification.valid);
+        assert!(verification.reason.is_none());
+        assert_eq!(
+            verification.issuer.as_deref(),
+            Some(authority.identifier().to_string().as_str())
+        );
+        assert_eq!(
+            verification.subject.as_deref(),
+            Some(peer.identifier().to_string().as_str())
+        );
+        let attributes = verification.attributes.unwrap();
+        assert_eq!(attributes.get("role"), Some(&b"member"[..]));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn credential_issued_by_another_identity_is_invalid(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        set_authority(&handle, &authority).await?;
+
+        let other = Identity::create(ctx, &Vault::create()).await?;
+        let peer = Identity::create(ctx, &Vault::create()).await?;
+        let builder =
+            Credential::builder(peer.identifier().clone()).with_attribute("role", b"member");
+        let credential = other.issue_credential(builder).await?;
+
+        let buf = verify_credential(ctx, credential).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let verification: CredentialVerification = dec.decode()?;
+        assert!(!verification.valid);
+        assert!(verification.reason.unwrap().contains("is not an authority"));
+        assert_eq!(
+            verification.issuer.as_deref(),
+            Some(other.identifier().to_string().as_str())
+        );
+        assert!(verification.attributes.is_none());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn expired_credential_is_invalid(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        set_authority(&handle, &authority).await?;
+
+        let peer = Identity::create(ctx, &Vault::create()).await?;
+        let builder = Credential::builder(peer.identifier().clone())
+            .with_attribute("role", b"member")
+            .valid_for(Duration::ZERO);
+        let credential = authority.issue_credential(builder).await?;
+
+        let buf = verify_credential(ctx, credential).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let verification: CredentialVerification = dec.decode()?;
+        assert!(!verification.valid);
+        assert!(verification.reason.unwrap().contains("expired"));
+        assert!(verification.expires_at.is_some());
+        assert!(verification.attributes.is_none());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn refresh_replaces_the_stored_credential(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (authority, _) = start_authority_with_issuer(ctx, &handle, "issuer").await?;
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counting_issuer = CountingIssuer {
+            issuer_address: "issuer".into(),
+            requests: requests.clone(),
+            delay: Duration::ZERO,
+        };
+        ctx.start_worker(
+            DefaultAddress::CREDENTIAL_ISSUER,
+            counting_issuer,
+            AllowAll,
+            AllowAll,
+        )
+        .await?;
+
+        // The node holds a credential issued before the authority updated its attributes
+        let builder = Credential::builder(handle.identity.identifier().clone())
+            .with_attribute("role", b"guest");
+        let stale = authority.issue_credential(builder).await?;
+        {
+            let node_manager = handle.node_manager.read().await;
+            node_manager.identity()?.set_credential(stale.clone()).await;
+        }
+
+        let req = Request::post("/node/credentials/refresh").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let refreshed: CredentialAttributes = dec.decode()?;
+        assert_eq!(refreshed.issuer, authority.identifier().to_string());
+        assert_eq!(refreshed.attributes.get("role"), Some(&b"member"[..]));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let node_manager = handle.node_manager.read().await;
+        let stored = node_manager.identity()?.credential().await.unwrap();
+        assert_ne!(stored, stale);
+        let data = authority
+            .to_public()
+            .await?
+            .verify_credential(&stored, handle.identity.identifier(), &Vault::create())
+            .await?;
+        assert_eq!(data.expires_at().unix_time(), refreshed.expires_at);
+        assert_eq!(data.attributes().get("role"), Some(&b"member"[..]));
+        drop(node_manager);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn credential_self_test_reports_an_unreachable_authority(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        // An authority listening on a port nobody listens on anymore
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        let authority_route =
+            MultiAddr::from_str(&format!("/ip4/127.0.0.1/tcp/{port}/service/api")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(format!("{}.json", authority.identifier()));
+        let contents = Authority::new(authority.export().await?, authority_route);
+        std::fs::write(file, serde_json::to_string(&contents).unwrap()).unwrap();
+
+        let err = crate::util::test::start_manager_for_tests_with_projects_options(
+            ctx,
+            NodeManagerProjectsOptions::new(None, None, Default::default(), None)
+                .with_authorities_dir(dir.path())
+                .with_credential_self_test(),
+        )
+        .await
+        .err()
+        .expect("the node should not start")
+        .to_string();
+        let stage = format!("at the {} stage", CredentialFlowStage::Reach);
+        assert!(err.contains(&stage), "{err}");
+        assert!(err.contains(&authority.identifier().to_string()), "{err}");
 
-        let response = Response::ok(req.id());
-        Ok(response)
+        ctx.stop().await
     }
 }
@@ -0,0 +1,63 @@
+//! Fault injection for chaos testing, only compiled with the `fault-injection` feature
+
+use crate::nodes::models::credentials::{CredentialFault, InjectCredentialFaults};
+use crate::nodes::NodeManager;
+use minicbor::Decoder;
+use ockam::Result;
+use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_core::errcode::{Kind, Origin};
+
+use super::NodeManagerWorker;
+
+/// Error returned by a credential fetch failing with the given fault
+pub(super) fn credential_fault_error(fault: CredentialFault) -> ockam_core::Error {
+    match fault {
+        CredentialFault::Timeout => ockam_core::Error::new(
+            Origin::Application,
+            Kind::Timeout,
+            "injected fault: timeout while fetching credential",
+        ),
+        CredentialFault::Refused => ockam_core::Error::new(
+            Origin::Application,
+            Kind::Io,
+            "injected fault: authority refused the connection",
+        ),
+        CredentialFault::VerificationFailure => ockam_core::Error::new(
+            Origin::Identity,
+            Kind::Invalid,
+            "injected fault: credential verification failed",
+        ),
+    }
+}
+
+impl NodeManager {
+    /// Return the fault to inject in the current credential fetch, if any
+    pub(super) fn take_credential_fault(&mut self) -> Option<CredentialFault> {
+        let (fault, remaining) = self.credential_faults.front_mut()?;
+        let fault = *fault;
+        *remaining -= 1;
+        if *remaining == 0 {
+            self.credential_faults.pop_front();
+        }
+        Some(fault)
+    }
+}
+
+impl NodeManagerWorker {
+    /// Make the next credential fetches fail
+    pub(super) async fn inject_credential_faults(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let mut node_manager = self.node_manager.write().await;
+        let request: InjectCredentialFaults = dec.decode()?;
+        warn!(fault = ?request.fault, count = %request.count, "Injecting credential faults");
+        if request.count > 0 {
+            node_manager
+                .credential_faults
+                .push_back((request.fault, request.count));
+        }
+        Ok(Response::ok(req.id()))
+    }
+}
@@ -0,0 +1,50 @@
+use crate::nodes::models::identity::LongIdentityResponse;
+use ockam::Result;
+use ockam_core::api::{Request, Response, ResponseBuilder};
+
+use super::NodeManagerWorker;
+
+impl NodeManagerWorker {
+    /// Return the exported change history of the node's identity, which can be
+    /// imported on another node, e.g. to configure a trust policy.
+    pub(super) async fn get_public_identity(
+        &self,
+        req: &Request<'_>,
+    ) -> Result<ResponseBuilder<LongIdentityResponse<'_>>> {
+        let node_manager = self.node_manager.read().await;
+        let identity = node_manager.identity()?.export().await?;
+        Ok(Response::ok(req.id()).body(LongIdentityResponse::new(identity)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::nodes::models::identity::LongIdentityResponse;
+    use crate::nodes::NODEMANAGER_ADDR;
+    use minicbor::Decoder;
+    use ockam::identity::PublicIdentity;
+    use ockam::Result;
+    use ockam_core::api::{Request, Response, Status};
+    use ockam_core::route;
+    use ockam_node::Context;
+    use ockam_vault::Vault;
+
+    #[ockam_macros::test]
+    async fn exported_public_identity_can_be_imported(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let req = Request::get("/node/identity/public").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let body: LongIdentityResponse = dec.decode()?;
+
+        // Import it with a different vault, as another node would do
+        let other_vault = Vault::create();
+        let imported = PublicIdentity::import(&body.identity, &other_vault).await?;
+        assert_eq!(imported.identifier(), handle.identity.identifier());
+
+        ctx.stop().await
+    }
+}
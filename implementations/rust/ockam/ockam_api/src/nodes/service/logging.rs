@@ -0,0 +1,41 @@
+use crate::nodes::models::logging::{LogLevelList, LogLevelStatus, SetLogLevel};
+use minicbor::Decoder;
+use ockam::Result;
+use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_node::logging;
+use std::str::FromStr;
+use tracing::level_filters::LevelFilter;
+use tracing::warn;
+
+use super::NodeManagerWorker;
+use crate::error::ApiError;
+
+impl NodeManagerWorker {
+    /// Change the log level of a target, taking effect immediately
+    pub(super) fn set_log_level(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let request: SetLogLevel = dec.decode()?;
+        let level = match request.level.as_deref() {
+            Some(level) => Some(
+                LevelFilter::from_str(level)
+                    .map_err(|_| ApiError::generic(&format!("invalid log level `{level}`")))?,
+            ),
+            None => None,
+        };
+        warn!(target = %request.target, level = ?level, "Changing log level");
+        logging::set_log_level(&request.target, level)?;
+        Ok(Response::ok(req.id()))
+    }
+
+    /// Return the log levels set at runtime
+    pub(super) fn get_log_levels(&self, req: &Request<'_>) -> ResponseBuilder<LogLevelList<'_>> {
+        let list = logging::log_levels()
+            .into_iter()
+            .map(|(target, level)| LogLevelStatus::new(target, level.to_string()))
+            .collect();
+        Response::ok(req.id()).body(LogLevelList::new(list))
+    }
+}
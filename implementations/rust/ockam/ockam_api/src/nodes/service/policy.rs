@@ -1,4 +1,6 @@
-use crate::nodes::models::policy::{Policy, PolicyList};
+use crate::nodes::models::policy::{
+    Policy, PolicyConditionStatistics, PolicyConditionStatisticsList, PolicyList,
+};
 use either::Either;
 use minicbor::Decoder;
 use ockam_abac::{Action, PolicyStorage, Resource};
@@ -41,6 +43,32 @@ impl NodeManager {
         }
     }
 
+    /// Return how often each condition of the policies evaluated by the node was met,
+    /// if the node keeps statistics
+    pub(super) fn get_abac_statistics<'a>(
+        &self,
+        req: &'a Request<'_>,
+    ) -> Either<ResponseBuilder<Error<'a>>, ResponseBuilder<PolicyConditionStatisticsList<'static>>>
+    {
+        let statistics = match &self.abac_statistics {
+            Some(statistics) => statistics,
+            None => {
+                let mut err = Error::new(req.path())
+                    .with_message("ABAC statistics are disabled on this node");
+                if let Some(m) = req.method() {
+                    err.set_method(m)
+                }
+                return Either::Left(Response::not_found(req.id()).body(err));
+            }
+        };
+        let list = statistics
+            .conditions()
+            .into_iter()
+            .map(|(condition, s)| PolicyConditionStatistics::new(condition, s.matched, s.denied))
+            .collect();
+        Either::Right(Response::ok(req.id()).body(PolicyConditionStatisticsList::new(list)))
+    }
+
     pub(super) async fn list_policies(
         &self,
         req: &Request<'_>,
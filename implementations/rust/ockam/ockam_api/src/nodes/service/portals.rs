@@ -1,34 +1,38 @@
 use crate::error::ApiError;
 use crate::nodes::connection::Connection;
 use crate::nodes::models::portal::{
-    CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus,
+    CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus, PortalLimits,
 };
 use crate::nodes::registry::{InletInfo, OutletInfo, Registry};
 use crate::nodes::service::random_alias;
 use crate::session::{util, Data, Replacer, Session};
 use crate::{actions, resources};
 use crate::{local_multiaddr_to_route, try_multiaddr_to_addr};
+use either::Either;
 use minicbor::Decoder;
 use ockam::compat::asynchronous::RwLock;
 use ockam::compat::tokio::time::timeout;
-use ockam::{Address, AsyncTryClone, Result};
+use ockam::{Address, AsyncTryClone, Result, Route};
 use ockam_abac::expr::{eq, ident, str};
-use ockam_abac::{Action, Env, PolicyAccessControl, PolicyStorage, Resource};
-use ockam_core::api::{Request, Response, ResponseBuilder};
-use ockam_core::{AllowAll, IncomingAccessControl};
+use ockam_abac::{AbacAccessControl, Action, Env, PolicyAccessControl, PolicyStorage, Resource};
+use ockam_core::api::{Error, Request, Response, ResponseBuilder};
+use ockam_core::{AllIncomingAccessControl, AllowAll, IncomingAccessControl};
 use ockam_identity::IdentityIdentifier;
 use ockam_multiaddr::proto::{Project, Secure, Service};
 use ockam_multiaddr::{MultiAddr, Protocol};
 use ockam_node::Context;
+use ockam_transport_tcp::TcpRegistry;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use super::{NodeManager, NodeManagerWorker};
+use super::transport::PROBE_TIMEOUT;
+use super::{Alias, NodeManager, NodeManagerWorker};
 
 const INLET_WORKER: &str = "inlet-worker";
 const OUTER_CHAN: &str = "outer-chan";
 
 impl NodeManager {
-    async fn access_control(
+    pub(super) async fn access_control(
         &self,
         r: &Resource,
         a: &Action,
@@ -48,21 +52,163 @@ impl NodeManager {
             }
             let store = self.attributes_storage.async_try_clone().await?;
             let policies = self.policies.clone();
-            Ok(Arc::new(PolicyAccessControl::new(
-                policies,
-                store,
-                r.clone(),
-                a.clone(),
-                env,
-            )))
+            let mut access_control =
+                PolicyAccessControl::new(policies, store, r.clone(), a.clone(), env);
+            if let Some(statistics) = &self.abac_statistics {
+                access_control = access_control.with_statistics(statistics.clone())
+            }
+            Ok(Arc::new(access_control))
         } else {
             // TODO: @ac allow passing this as a cli argument
             Ok(Arc::new(AllowAll))
         }
     }
+
+    /// Access control of an outlet: on top of the policy of its resource, the
+    /// senders must have all the attributes of `allow`
+    pub(super) async fn outlet_access_control(
+        &self,
+        r: &Resource,
+        project_id: Option<String>,
+        allow: &[(String, String)],
+    ) -> Result<Arc<dyn IncomingAccessControl>> {
+        let access_control = self
+            .access_control(r, &actions::HANDLE_MESSAGE, project_id)
+            .await?;
+
+        let mut values: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (key, value) in allow {
+            values.entry(key.as_str()).or_default().push(value.clone());
+        }
+        let storage = self.identity()?.authenticated_storage();
+        let mut abac = match values
+            .into_iter()
+            .map(|(key, values)| AbacAccessControl::create_with_values(storage, key, values))
+            .reduce(AbacAccessControl::and)
+        {
+            Some(abac) => abac,
+            None => return Ok(access_control),
+        };
+        if let Some(statistics) = &self.abac_statistics {
+            abac = abac.with_statistics(statistics.clone())
+        }
+        Ok(Arc::new(AllIncomingAccessControl::new(vec![
+            access_control,
+            Arc::new(abac),
+        ])))
+    }
+}
+
+impl NodeManager {
+    /// Resolve the outlet route of an inlet and establish the secure channels it
+    /// requires, then tear them down again, along with the TCP connections opened
+    /// for them. Nothing is bound locally.
+    async fn check_outlet_route(&mut self, ctx: &Context, req: &CreateInlet<'_>) -> Result<Route> {
+        let existing = self.tcp_transport.registry().get_all_sender_workers();
+        let mut channels = Vec::new();
+        // Bounded like the other probes, as the node manager stays locked meanwhile
+        let route = timeout(
+            PROBE_TIMEOUT,
+            self.resolve_outlet_route(ctx, req, &mut channels),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(ApiError::message(format!(
+                "timed out reaching the outlet at {}",
+                req.outlet_addr()
+            )))
+        });
+
+        // The first hop of the outlet route, or of the channels, is the TCP
+        // connection they were created over
+        let mut hops: Vec<Address> = route
+            .as_ref()
+            .ok()
+            .and_then(|r| r.iter().next().cloned())
+            .into_iter()
+            .collect();
+        for channel in channels.iter().rev().filter(|c| !c.is_empty()) {
+            if let Ok(addr) = try_multiaddr_to_addr(channel) {
+                if let Some(hop) = self
+                    .registry
+                    .secure_channels
+                    .get_by_addr(&addr)
+                    .and_then(|info| info.route().iter().next().cloned())
+                {
+                    hops.push(hop);
+                }
+                let _ = self.delete_secure_channel(&addr).await;
+            }
+        }
+
+        // Connections which already existed may be used by other channels or portals
+        let connections = self.tcp_transport.registry().get_all_sender_workers();
+        for hop in hops {
+            if connections.contains(&hop) && !existing.contains(&hop) {
+                debug!(addr = %hop, "closing the tcp connection of the inlet dry run");
+                let _ = self.tcp_transport.disconnect(&hop).await;
+            }
+        }
+        route
+    }
+
+    async fn resolve_outlet_route(
+        &mut self,
+        ctx: &Context,
+        req: &CreateInlet<'_>,
+        channels: &mut Vec<MultiAddr>,
+    ) -> Result<Route> {
+        let connection =
+            Connection::new(ctx, req.outlet_addr()).with_authorized_identity(req.authorized());
+        let (sec1, rest) = self.connect(connection).await?;
+        channels.push(sec1.clone());
+        let rest =
+            if !sec1.is_empty() && rest.matches(0, &[Service::CODE.into(), Secure::CODE.into()]) {
+                let addr = sec1.clone().try_with(rest.iter().take(2))?;
+                let connection = Connection::new(ctx, &addr);
+                let (sec2, _) = self.connect(connection).await?;
+                channels.push(sec2.clone());
+                sec2.try_with(rest.iter().skip(2))?
+            } else {
+                sec1.try_with(&rest)?
+            };
+        local_multiaddr_to_route(&rest)
+            .ok_or_else(|| ApiError::message(format!("invalid outlet route: {rest}")))
+    }
+}
+
+/// Return the limit that registering a portal under `alias` would exceed, if any
+fn exceeded_limit<V>(portals: &BTreeMap<Alias, V>, alias: &str, max: Option<u32>) -> Option<u32> {
+    max.filter(|max| !portals.contains_key(alias) && portals.len() >= *max as usize)
 }
 
 impl NodeManagerWorker {
+    pub(super) async fn get_portal_limits(
+        &self,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<PortalLimits> {
+        let node_manager = self.node_manager.read().await;
+        Response::ok(req.id()).body(node_manager.portal_limits.clone())
+    }
+
+    /// Change the maximum number of inlets and outlets. Portals already created
+    /// are kept when the new limits are lower than their number.
+    pub(super) async fn set_portal_limits(
+        &mut self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder> {
+        let mut node_manager = self.node_manager.write().await;
+        let limits: PortalLimits = dec.decode()?;
+        info!(
+            max_inlets = ?limits.max_inlets,
+            max_outlets = ?limits.max_outlets,
+            "Changing portal limits"
+        );
+        node_manager.portal_limits = limits;
+        Ok(Response::ok(req.id()))
+    }
+
     pub(super) fn get_inlets<'a>(
         &self,
         req: &Request<'a>,
@@ -80,6 +226,7 @@ impl NodeManagerWorker {
                         None,
                         info.outlet_route.to_string(),
                     )
+                    .with_description(info.description.as_deref())
                 })
                 .collect(),
         ))
@@ -89,18 +236,72 @@ impl NodeManagerWorker {
         &self,
         req: &Request<'a>,
         registry: &'a Registry,
+        tcp_registry: &TcpRegistry,
     ) -> ResponseBuilder<OutletList<'a>> {
         Response::ok(req.id()).body(OutletList::new(
             registry
                 .outlets
                 .iter()
-                .map(|(alias, info)| {
-                    OutletStatus::new(&info.tcp_addr, info.worker_addr.to_string(), alias, None)
-                })
+                .map(|(alias, info)| outlet_status(alias, info, tcp_registry))
                 .collect(),
         ))
     }
 
+    /// Return the outlet with the given alias
+    pub(super) fn get_outlet<'a>(
+        &self,
+        req: &'a Request<'_>,
+        registry: &'a Registry,
+        tcp_registry: &TcpRegistry,
+        alias: &str,
+    ) -> Either<ResponseBuilder<Error<'a>>, ResponseBuilder<OutletStatus<'a>>> {
+        match registry.outlets.get_key_value(alias) {
+            Some((alias, info)) => {
+                let outlet = outlet_status(alias, info, tcp_registry);
+                Either::Right(Response::ok(req.id()).body(outlet))
+            }
+            None => {
+                let mut err = Error::new(req.path())
+                    .with_message(format!("outlet with alias {alias} not found"));
+                if let Some(m) = req.method() {
+                    err.set_method(m)
+                }
+                Either::Left(Response::not_found(req.id()).body(err))
+            }
+        }
+    }
+
+    /// Delete the outlet with the given alias and close its connections
+    pub(super) async fn delete_outlet<'a>(
+        &mut self,
+        req: &'a Request<'_>,
+        alias: &str,
+    ) -> Either<ResponseBuilder<Error<'a>>, ResponseBuilder> {
+        let mut node_manager = self.node_manager.write().await;
+        match node_manager.registry.outlets.remove(alias) {
+            Some(info) => {
+                // The outlet worker is missing if the outlet failed to be created
+                if let Err(e) = node_manager
+                    .tcp_transport
+                    .stop_outlet_with_connections(info.worker_addr)
+                    .await
+                {
+                    warn!(%alias, %e, "failed to stop the outlet worker");
+                }
+                info!(%alias, "Deleted outlet");
+                Either::Right(Response::ok(req.id()))
+            }
+            None => {
+                let mut err = Error::new(req.path())
+                    .with_message(format!("outlet with alias {alias} not found"));
+                if let Some(m) = req.method() {
+                    err.set_method(m)
+                }
+                Either::Left(Response::not_found(req.id()).body(err))
+            }
+        }
+    }
+
     pub(super) async fn create_inlet<'a>(
         &mut self,
         req: &Request<'_>,
@@ -127,12 +328,50 @@ impl NodeManagerWorker {
             "Creating inlet portal"
         }
 
+        if req.is_dry_run() {
+            return Ok(match node_manager.check_outlet_route(ctx, &req).await {
+                Ok(outlet_route) => Response::ok(rid).body(InletStatus::new(
+                    listen_addr,
+                    "",
+                    alias,
+                    Some("dry run succeeded".into()),
+                    outlet_route.to_string(),
+                )),
+                Err(e) => {
+                    warn!(to = %req.outlet_addr(), err = %e, "tcp inlet dry run failed");
+                    Response::bad_request(rid).body(InletStatus::new(
+                        listen_addr,
+                        "",
+                        alias,
+                        Some(e.to_string().into()),
+                        "",
+                    ))
+                }
+            });
+        }
+
+        if let Some(max) = exceeded_limit(
+            &node_manager.registry.inlets,
+            &alias,
+            node_manager.portal_limits.max_inlets,
+        ) {
+            warn!(%alias, %max, "tcp inlet limit reached");
+            return Ok(Response::bad_request(rid).body(InletStatus::new(
+                listen_addr,
+                "",
+                alias,
+                Some(format!("the node already has the maximum number of inlets ({max})").into()),
+                "",
+            )));
+        }
+
         // The addressing scheme is very flexible. Typically the node connects to
         // the cloud via secure channel and the with another secure channel via
         // forwarder to the actual outlet on the target node. However it is also
         // possible that there is just a single secure channel used to go directly
-        // to another node.
-        let (outer, rest) = {
+        // to another node. The route only outlives the node if no connection was
+        // created for it.
+        let (outer, rest, persistent_route) = {
             let connection =
                 Connection::new(ctx, req.outlet_addr()).with_authorized_identity(req.authorized());
             let (sec1, rest) = node_manager.connect(connection).await?;
@@ -140,9 +379,14 @@ impl NodeManagerWorker {
                 let addr = sec1.clone().try_with(rest.iter().take(2))?;
                 let connection = Connection::new(ctx, &addr);
                 let (sec2, _) = node_manager.connect(connection).await?;
-                (sec1, sec2.try_with(rest.iter().skip(2))?)
+                (sec1, sec2.try_with(rest.iter().skip(2))?, false)
             } else {
-                (MultiAddr::default(), sec1.try_with(&rest)?)
+                let persistent_route = sec1.is_empty();
+                (
+                    MultiAddr::default(),
+                    sec1.try_with(&rest)?,
+                    persistent_route,
+                )
             }
         };
 
@@ -184,6 +428,13 @@ impl NodeManagerWorker {
             .access_control(&resource, &actions::HANDLE_MESSAGE, project_id)
             .await?;
 
+        // The inlet relies on the node's credential if it was explicitly tied to it,
+        // if its access control checks credentials, or if a credential is presented
+        // on the secure channels it is created with
+        let depends_on_credential = req.depends_on_credential()
+            || check_credential
+            || node_manager.identity()?.credential().await.is_some();
+
         let res = node_manager
             .tcp_transport
             .create_inlet_impl(
@@ -198,7 +449,14 @@ impl NodeManagerWorker {
                 // TODO: Use better way to store inlets?
                 node_manager.registry.inlets.insert(
                     alias.clone(),
-                    InletInfo::new(&listen_addr, Some(&worker_addr), &outlet_route),
+                    InletInfo::new(
+                        &listen_addr,
+                        Some(&worker_addr),
+                        &outlet_route,
+                        req.description(),
+                        depends_on_credential,
+                        persistent_route,
+                    ),
                 );
                 if !outer.is_empty() {
                     let mut s = Session::new(without_outlet_address(rest));
@@ -218,20 +476,30 @@ impl NodeManagerWorker {
                     node_manager.sessions.lock().unwrap().add(s);
                 }
 
-                Response::ok(rid).body(InletStatus::new(
-                    listen_addr,
-                    worker_addr.to_string(),
-                    alias,
-                    None,
-                    outlet_route.to_string(),
-                ))
+                Response::ok(rid).body(
+                    InletStatus::new(
+                        listen_addr,
+                        worker_addr.to_string(),
+                        alias,
+                        None,
+                        outlet_route.to_string(),
+                    )
+                    .with_description(req.description().map(str::to_string)),
+                )
             }
             Err(e) => {
                 warn!(to = %req.outlet_addr(), err = %e, "failed to create tcp inlet");
                 // TODO: Use better way to store inlets?
                 node_manager.registry.inlets.insert(
                     alias.clone(),
-                    InletInfo::new(&listen_addr, None, &outlet_route),
+                    InletInfo::new(
+                        &listen_addr,
+                        None,
+                        &outlet_route,
+                        req.description(),
+                        depends_on_credential,
+                        persistent_route,
+                    ),
                 );
 
                 Response::bad_request(rid).body(InletStatus::new(
@@ -255,9 +523,16 @@ impl NodeManagerWorker {
             tcp_addr,
             worker_addr,
             alias,
+            description,
+            allow,
             ..
         } = dec.decode()?;
         let tcp_addr = tcp_addr.to_string();
+        let allow: Vec<(String, String)> = allow
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
         let resource = alias
             .as_deref()
             .map(Resource::new)
@@ -265,6 +540,21 @@ impl NodeManagerWorker {
         let alias = alias.map(|a| a.0.into()).unwrap_or_else(random_alias);
 
         info!("Handling request to create outlet portal");
+
+        if let Some(max) = exceeded_limit(
+            &node_manager.registry.outlets,
+            &alias,
+            node_manager.portal_limits.max_outlets,
+        ) {
+            warn!(%alias, %max, "tcp outlet limit reached");
+            return Ok(Response::bad_request(req.id()).body(OutletStatus::new(
+                tcp_addr,
+                worker_addr.to_string(),
+                alias,
+                Some(format!("the node already has the maximum number of outlets ({max})").into()),
+            )));
+        }
+
         let worker_addr = Address::from(worker_addr.as_ref());
 
         let check_credential = node_manager.enable_credential_checks;
@@ -275,7 +565,7 @@ impl NodeManagerWorker {
         };
 
         let access_control = node_manager
-            .access_control(&resource, &actions::HANDLE_MESSAGE, project_id)
+            .outlet_access_control(&resource, project_id, &allow)
             .await?;
 
         let res = node_manager
@@ -285,25 +575,29 @@ impl NodeManagerWorker {
 
         Ok(match res {
             Ok(_) => {
-                // TODO: Use better way to store outlets?
-                node_manager.registry.outlets.insert(
-                    alias.clone(),
-                    OutletInfo::new(&tcp_addr, Some(&worker_addr)),
+                let info = OutletInfo::new(
+                    &tcp_addr,
+                    Some(&worker_addr),
+                    description.as_deref(),
+                    &allow,
                 );
+                let created_at = info.created_at;
+                // TODO: Use better way to store outlets?
+                node_manager.registry.outlets.insert(alias.clone(), info);
 
-                Response::ok(req.id()).body(OutletStatus::new(
-                    tcp_addr,
-                    worker_addr.to_string(),
-                    alias,
-                    None,
-                ))
+                Response::ok(req.id()).body(
+                    OutletStatus::new(tcp_addr, worker_addr.to_string(), alias, None)
+                        .with_description(description.map(|d| d.to_string()))
+                        .with_created_at(created_at)
+                        .with_active_connections(0),
+                )
             }
             Err(e) => {
                 // TODO: Use better way to store outlets?
-                node_manager
-                    .registry
-                    .outlets
-                    .insert(alias.clone(), OutletInfo::new(&tcp_addr, None));
+                node_manager.registry.outlets.insert(
+                    alias.clone(),
+                    OutletInfo::new(&tcp_addr, None, description.as_deref(), &allow),
+                );
 
                 Response::bad_request(req.id()).body(OutletStatus::new(
                     tcp_addr,
@@ -316,6 +610,19 @@ impl NodeManagerWorker {
     }
 }
 
+/// Status of a registered outlet, with the number of connections it currently has open
+fn outlet_status<'a>(
+    alias: &'a str,
+    info: &'a OutletInfo,
+    tcp_registry: &TcpRegistry,
+) -> OutletStatus<'a> {
+    let active_connections = tcp_registry.get_outlet_connections(&info.worker_addr).len();
+    OutletStatus::new(&info.tcp_addr, info.worker_addr.to_string(), alias, None)
+        .with_description(info.description.as_deref())
+        .with_created_at(info.created_at)
+        .with_active_connections(active_connections as u32)
+}
+
 /// Create a session replacer.
 ///
 /// This returns a function that accepts the previous ping address (e.g.
@@ -424,3 +731,534 @@ fn without_outlet_address(mut addr: MultiAddr) -> MultiAddr {
     }
     addr
 }
+
+#[cfg(test)]
+mod test {
+    use crate::echoer::Echoer;
+    use crate::nodes::models::credentials::CredentialDependents;
+    use crate::nodes::models::policy::PolicyConditionStatisticsList;
+    use crate::nodes::models::portal::{
+        CreateInlet, CreateOutlet, InletStatus, OutletList, OutletStatus, PortalLimits,
+    };
+    use crate::nodes::NODEMANAGER_ADDR;
+    use minicbor::Decoder;
+    use ockam::identity::TrustEveryonePolicy;
+    use ockam::Result;
+    use ockam_abac::{AbacStatistics, Action, Resource};
+    use ockam_core::api::{Error, Request, Response, Status};
+    use ockam_core::compat::collections::BTreeMap;
+    use ockam_core::compat::sync::Arc;
+    use ockam_core::{route, Address, AllowAll, CowStr};
+    use ockam_identity::authenticated_storage::{AttributesEntry, IdentityAttributeStorageWriter};
+    use ockam_identity::credential::{Credential, Timestamp};
+    use ockam_identity::Identity;
+    use ockam_multiaddr::MultiAddr;
+    use ockam_node::{Context, WorkerBuilder};
+    use ockam_transport_tcp::TcpListenerTrustOptions;
+    use ockam_vault::Vault;
+    use std::net::{SocketAddr, TcpListener};
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    fn unused_addr() -> SocketAddr {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+    }
+
+    #[ockam_macros::test]
+    async fn dry_run_with_unreachable_outlet_does_not_bind(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let listen_addr = unused_addr();
+        let outlet_addr = unused_addr();
+        let to = MultiAddr::from_str(&format!(
+            "/ip4/127.0.0.1/tcp/{}/secure/api/service/outlet",
+            outlet_addr.port()
+        ))
+        .unwrap();
+        let mut payload = CreateInlet::to_node(listen_addr, to, None);
+        payload.set_dry_run(true);
+
+        let req = Request::post("/node/inlet").body(payload).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::BadRequest));
+        let status: InletStatus = dec.decode()?;
+        assert!(status.payload.is_some());
+
+        // The inlet listener was never bound nor registered
+        assert!(TcpListener::bind(listen_addr).is_ok());
+        assert!(handle.node_manager.read().await.registry.inlets.is_empty());
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn dry_run_closes_the_connections_it_opened(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (peer, _) = handle
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+            .await?;
+
+        let to = MultiAddr::from_str(&format!(
+            "/ip4/127.0.0.1/tcp/{}/service/outlet",
+            peer.port()
+        ))
+        .unwrap();
+        let mut payload = CreateInlet::to_node(unused_addr(), to, None);
+        payload.set_dry_run(true);
+
+        let req = Request::post("/node/inlet").body(payload).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        // Both ends of the connection used for the check are closed
+        let registry = handle.tcp.registry().clone();
+        let closed = ockam_node::tokio::time::timeout(Duration::from_secs(5), async {
+            while !registry.get_all_sender_workers().is_empty() {
+                ockam_node::tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        assert!(closed.is_ok());
+
+        ctx.stop().await
+    }
+
+    /// Create an outlet and return the response status, along with its error, if any
+    async fn create_outlet(ctx: &Context, alias: &str) -> Result<(Status, Option<String>)> {
+        let payload = CreateOutlet::new(
+            unused_addr().to_string(),
+            format!("outlet-{alias}"),
+            Some(CowStr::from(alias.to_string())),
+        );
+        let req = Request::post("/node/outlet").body(payload).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        let status: OutletStatus = dec.decode()?;
+        Ok((res.status().unwrap(), status.payload.map(|p| p.to_string())))
+    }
+
+    #[ockam_macros::test]
+    async fn outlet_creation_is_rejected_past_the_limit(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let req = Request::put("/node/portals/limits")
+            .body(PortalLimits::new(None, Some(2)))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let req = Request::get("/node/portals/limits").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let limits: PortalLimits = dec.decode()?;
+        assert_eq!(limits, PortalLimits::new(None, Some(2)));
+
+        assert_eq!(create_outlet(ctx, "first").await?.0, Status::Ok);
+        assert_eq!(create_outlet(ctx, "second").await?.0, Status::Ok);
+
+        let (status, error) = create_outlet(ctx, "third").await?;
+        assert_eq!(status, Status::BadRequest);
+        assert!(error.unwrap().contains("maximum number of outlets"));
+        assert_eq!(handle.node_manager.read().await.registry.outlets.len(), 2);
+
+        // Once an outlet is deleted, a new one can be created
+        {
+            let mut node_manager = handle.node_manager.write().await;
+            let info = node_manager.registry.outlets.remove("first").unwrap();
+            node_manager
+                .tcp_transport
+                .stop_outlet(info.worker_addr)
+                .await?;
+        }
+        assert_eq!(create_outlet(ctx, "third").await?.0, Status::Ok);
+
+        ctx.stop().await
+    }
+
+    /// Create an inlet to a local outlet service and return the response status,
+    /// along with its error, if any
+    async fn create_inlet(ctx: &Context, alias: &str) -> Result<(Status, Option<String>)> {
+        let to = MultiAddr::from_str("/service/outlet").unwrap();
+        let mut payload = CreateInlet::to_node(unused_addr(), to, None);
+        payload.set_alias(alias.to_string());
+        let req = Request::post("/node/inlet").body(payload).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        let status: InletStatus = dec.decode()?;
+        Ok((res.status().unwrap(), status.payload.map(|p| p.to_string())))
+    }
+
+    #[ockam_macros::test]
+    async fn inlet_creation_is_rejected_past_the_limit(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let req = Request::put("/node/portals/limits")
+            .body(PortalLimits::new(Some(1), None))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        assert_eq!(create_inlet(ctx, "first").await?.0, Status::Ok);
+
+        let (status, error) = create_inlet(ctx, "second").await?;
+        assert_eq!(status, Status::BadRequest);
+        assert!(error.unwrap().contains("maximum number of inlets"));
+        assert_eq!(handle.node_manager.read().await.registry.inlets.len(), 1);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn outlet_description_is_listed(ctx: &mut Context) -> Result<()> {
+        let _handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let mut payload = CreateOutlet::new(
+            unused_addr().to_string(),
+            "outlet-described",
+            Some(CowStr::from("described")),
+        );
+        payload.set_description("prod postgres reader");
+        let req = Request::post("/node/outlet").body(payload).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let status: OutletStatus = dec.decode()?;
+        assert_eq!(status.description.as_deref(), Some("prod postgres reader"));
+
+        let req = Request::get("/node/outlet").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let list: OutletList = dec.decode()?;
+        let outlet = list.list.iter().find(|o| o.alias == "described").unwrap();
+        assert_eq!(outlet.description.as_deref(), Some("prod postgres reader"));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn outlet_is_shown_by_alias(ctx: &mut Context) -> Result<()> {
+        let _handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        assert_eq!(create_outlet(ctx, "shown").await?.0, Status::Ok);
+        assert_eq!(create_outlet(ctx, "other").await?.0, Status::Ok);
+
+        let req = Request::get("/node/outlet/shown").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let outlet: OutletStatus = dec.decode()?;
+        assert_eq!(outlet.alias, "shown");
+        assert!(outlet.worker_addr.ends_with("outlet-shown"));
+
+        let req = Request::get("/node/outlet/unknown").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+        let err: Error = dec.decode()?;
+        assert!(err.message().unwrap().contains("unknown"));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn outlet_is_deleted_by_alias(ctx: &mut Context) -> Result<()> {
+        let _handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        assert_eq!(create_outlet(ctx, "deleted").await?.0, Status::Ok);
+        assert_eq!(create_outlet(ctx, "kept").await?.0, Status::Ok);
+
+        let req = Request::delete("/node/outlet/deleted").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let req = Request::get("/node/outlet").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let _: Response = dec.decode()?;
+        let list: OutletList = dec.decode()?;
+        let aliases: Vec<_> = list.list.iter().map(|o| o.alias.to_string()).collect();
+        assert_eq!(aliases, vec!["kept".to_string()]);
+
+        let outlet_worker = Address::from_string("outlet-deleted");
+        while ctx.list_workers().await?.contains(&outlet_worker) {
+            ockam_node::tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // The outlet is gone
+        let req = Request::delete("/node/outlet/deleted").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+        let err: Error = dec.decode()?;
+        assert!(err.message().unwrap().contains("deleted"));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn outlet_status_counts_active_connections(ctx: &mut Context) -> Result<()> {
+        let _handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        // The backend only needs to accept the connections
+        let backend = TcpListener::bind("127.0.0.1:0").unwrap();
+        let payload = CreateOutlet::new(
+            backend.local_addr().unwrap().to_string(),
+            "outlet-counted",
+            Some(CowStr::from("counted")),
+        );
+        let req = Request::post("/node/outlet").body(payload).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let outlet: OutletStatus = dec.decode()?;
+        assert!(outlet.created_at.is_some());
+        assert_eq!(outlet.active_connections, Some(0));
+
+        let inlet_addr = unused_addr();
+        let to = MultiAddr::from_str("/service/outlet-counted").unwrap();
+        let req = Request::post("/node/inlet")
+            .body(CreateInlet::to_node(inlet_addr, to, None))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let _first = std::net::TcpStream::connect(inlet_addr).unwrap();
+        let _second = std::net::TcpStream::connect(inlet_addr).unwrap();
+
+        let mut active_connections = None;
+        for _ in 0..50 {
+            let req = Request::get("/node/outlet/counted").to_vec()?;
+            let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+            let mut dec = Decoder::new(&buf);
+            let _: Response = dec.decode()?;
+            let outlet: OutletStatus = dec.decode()?;
+            active_connections = outlet.active_connections;
+            if active_connections == Some(2) {
+                break;
+            }
+            ockam_node::tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(active_connections, Some(2));
+
+        ctx.stop().await
+    }
+
+    /// Create an inlet to the given local outlet and return the response status
+    async fn create_inlet(
+        ctx: &Context,
+        alias: &str,
+        outlet_alias: &str,
+        depends_on_credential: bool,
+    ) -> Result<Status> {
+        let to = MultiAddr::from_str(&format!("/service/outlet-{outlet_alias}")).unwrap();
+        let mut payload = CreateInlet::to_node(unused_addr(), to, None);
+        payload.set_alias(alias.to_string());
+        payload.set_depends_on_credential(depends_on_credential);
+        let req = Request::post("/node/inlet").body(payload).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        Ok(res.status().unwrap())
+    }
+
+    #[ockam_macros::test]
+    async fn inlets_depending_on_the_credential_are_listed(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        assert_eq!(create_outlet(ctx, "backend").await?.0, Status::Ok);
+
+        // Without credential, only the inlets explicitly tied to it depend on it
+        assert_eq!(
+            create_inlet(ctx, "plain", "backend", false).await?,
+            Status::Ok
+        );
+        assert_eq!(
+            create_inlet(ctx, "tied", "backend", true).await?,
+            Status::Ok
+        );
+
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        let credential = authority
+            .issue_credential(Credential::builder(handle.identity.identifier().clone()))
+            .await?;
+        handle
+            .node_manager
+            .read()
+            .await
+            .identity()?
+            .set_credential(credential)
+            .await;
+
+        // Inlets created while the node has a credential depend on it
+        assert_eq!(
+            create_inlet(ctx, "after", "backend", false).await?,
+            Status::Ok
+        );
+
+        let req = Request::get("/node/credentials/dependents").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let dependents: CredentialDependents = dec.decode()?;
+        assert_eq!(dependents.inlets, vec!["after", "tied"]);
+
+        ctx.stop().await
+    }
+
+    async fn get_abac_statistics(ctx: &mut Context) -> Result<PolicyConditionStatisticsList> {
+        let req = Request::get("/node/abac/statistics").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        Ok(dec.decode()?)
+    }
+
+    #[ockam_macros::test]
+    async fn abac_statistics_follow_access_decisions(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let vault = Vault::create();
+        let server = Identity::create(ctx, &vault).await?;
+        server
+            .create_secure_channel_listener("listener", TrustEveryonePolicy)
+            .await?;
+        let member = Identity::create(ctx, &vault).await?;
+        let outsider = Identity::create(ctx, &vault).await?;
+        handle.node_manager.write().await.abac_statistics = Some(AbacStatistics::new());
+
+        // A worker protected by the default portal policy of the "project" project
+        let access_control = {
+            let node_manager = handle.node_manager.read().await;
+            let entry = AttributesEntry::new(
+                BTreeMap::from([("project_id".to_string(), b"project".to_vec())]),
+                Timestamp::now().unwrap(),
+                None,
+                None,
+            );
+            node_manager
+                .attributes_storage
+                .put_attributes(member.identifier(), entry)
+                .await?;
+            node_manager
+                .access_control(
+                    &Resource::new("echoer"),
+                    &Action::new("handle_message"),
+                    Some("project".to_string()),
+                )
+                .await?
+        };
+        WorkerBuilder::with_access_control(access_control, Arc::new(AllowAll), "echoer", Echoer)
+            .start(ctx)
+            .await?;
+
+        // The member is allowed
+        let channel = member
+            .create_secure_channel(route!["listener"], TrustEveryonePolicy)
+            .await?;
+        let reply: String = ctx
+            .send_and_receive(route![channel, "echoer"], "hello".to_string())
+            .await?;
+        assert_eq!(reply, "hello");
+
+        // The outsider isn't
+        let channel = outsider
+            .create_secure_channel(route!["listener"], TrustEveryonePolicy)
+            .await?;
+        ctx.send(route![channel, "echoer"], "hello".to_string())
+            .await?;
+
+        let condition = "(= resource.project_id subject.project_id)";
+        let mut counts = (0, 0);
+        for _ in 0..50 {
+            let statistics = get_abac_statistics(ctx).await?;
+            if let Some(s) = statistics.list.iter().find(|s| s.condition == condition) {
+                counts = (s.matched, s.denied);
+            }
+            if counts.1 > 0 {
+                break;
+            }
+            ockam_node::tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(counts, (1, 1));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn outlet_denies_senders_without_the_allowed_attribute(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let vault = Vault::create();
+        let server = Identity::create(ctx, &vault).await?;
+        server
+            .create_secure_channel_listener("listener", TrustEveryonePolicy)
+            .await?;
+        let member = Identity::create(ctx, &vault).await?;
+        let outsider = Identity::create(ctx, &vault).await?;
+        {
+            let node_manager = handle.node_manager.read().await;
+            let entry = AttributesEntry::new(
+                BTreeMap::from([("role".to_string(), b"db".to_vec())]),
+                Timestamp::now().unwrap(),
+                None,
+                None,
+            );
+            node_manager
+                .attributes_storage
+                .put_attributes(member.identifier(), entry)
+                .await?;
+        }
+
+        let mut payload = CreateOutlet::new(
+            unused_addr().to_string(),
+            "outlet-guarded",
+            Some(CowStr::from("guarded")),
+        );
+        payload.add_allowed_attribute("role", "db");
+        let req = Request::post("/node/outlet").body(payload).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        // Only the member gets past the outlet access control
+        for identity in [&member, &outsider] {
+            let channel = identity
+                .create_secure_channel(route!["listener"], TrustEveryonePolicy)
+                .await?;
+            ctx.send(route![channel, "outlet-guarded"], "hello".to_string())
+                .await?;
+        }
+
+        let condition = "(= subject.role \"db\")";
+        let mut counts = (0, 0);
+        for _ in 0..50 {
+            let statistics = get_abac_statistics(ctx).await?;
+            if let Some(s) = statistics.list.iter().find(|s| s.condition == condition) {
+                counts = (s.matched, s.denied);
+            }
+            if counts == (1, 1) {
+                break;
+            }
+            ockam_node::tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(counts, (1, 1));
+
+        ctx.stop().await
+    }
+}
@@ -0,0 +1,224 @@
+use crate::nodes::models::base::UnknownRoute;
+use ockam_core::api::{Request, Response, ResponseBuilder};
+
+use super::NodeManagerWorker;
+
+/// Routes handled by the node manager, listed in the response to requests matching none
+/// of them. Keep it in sync with `NodeManagerWorker::handle_request`: the test below checks
+/// that each of them is handled.
+const ROUTES: &[&str] = &[
+    "GET /node",
+    "GET /node/version",
+    "GET /node/config",
+    "POST /node/snapshot",
+    "GET /node/identity/public",
+    "GET /node/logging",
+    "PUT /node/logging",
+    "GET /node/tcp/connection",
+    "POST /node/tcp/connection",
+    "DELETE /node/tcp/connection",
+    "GET /node/tcp/connection/{id}/stats",
+    "GET /node/tcp/stats",
+    "GET /node/tcp/errors",
+    "POST /node/tcp/events",
+    "DELETE /node/tcp/events/{id}",
+    "POST /node/reachability",
+    "GET /node/tcp/listener",
+    "POST /node/tcp/listener",
+    "DELETE /node/tcp/listener",
+    "POST /node/tcp/listener/{id}/migrate",
+    "GET /node/tcp/listener/{id}/drain",
+    "POST /node/credentials/actions/get",
+    "POST /node/credentials/actions/get/preview",
+    "GET /node/credentials/source/{identifier}",
+    "GET /node/credentials/attributes",
+    "POST /node/credentials/refresh",
+    "POST /node/credentials/verify",
+    "GET /node/credentials/dependents",
+    "GET /node/credentials/authorities",
+    "DELETE /node/credentials/authorities/{identifier}",
+    "DELETE /node/credentials/verifications/{identifier}",
+    "POST /node/credentials/actions/present",
+    "GET /node/attributes",
+    "POST /node/attributes",
+    "GET /node/attributes/{identifier}",
+    "DELETE /node/attributes/{identifier}",
+    "GET /node/secure_channel",
+    "GET /node/secure_channel/by_identity",
+    "GET /node/secure_channel_listener",
+    "POST /node/secure_channel",
+    "DELETE /node/secure_channel",
+    "DELETE /node/connections/{identifier}",
+    "GET /node/show_secure_channel",
+    "POST /node/secure_channel_listener",
+    "POST /node/services/vault_service",
+    "POST /node/services/identity_service",
+    "POST /node/services/authenticated",
+    "POST /node/services/uppercase",
+    "POST /node/services/echo",
+    "POST /node/services/hop",
+    "POST /node/services/direct_authenticator",
+    "POST /node/services/verifier",
+    "POST /node/services/credentials",
+    "POST /node/services/okta",
+    "POST /node/services/kafka_consumer",
+    "POST /node/services/kafka_producer",
+    "GET /node/services",
+    "POST /node/forwarder",
+    "GET /node/inlet",
+    "GET /node/outlet",
+    "GET /node/outlet/{alias}",
+    "DELETE /node/outlet/{alias}",
+    "GET /node/portals/limits",
+    "PUT /node/portals/limits",
+    "POST /node/inlet",
+    "POST /node/outlet",
+    "GET /node/workers",
+    "GET /node/abac/statistics",
+    "POST /policy/{resource}/{action}",
+    "GET /policy/{resource}",
+    "GET /policy/{resource}/{action}",
+    "DELETE /policy/{resource}/{action}",
+    "POST /v0/spaces",
+    "GET /v0/spaces",
+    "GET /v0/spaces/{space_id}",
+    "DELETE /v0/spaces/{space_id}",
+    "POST /v0/projects/{space_id}",
+    "GET /v0/projects",
+    "GET /v0/projects/{project_id}",
+    "DELETE /v0/projects/{space_id}/{project_id}",
+    "POST /v0/enroll/auth0",
+    "GET /v0/enroll/token",
+    "PUT /v0/enroll/token",
+    "POST /subscription",
+    "GET /subscription/{id}",
+    "GET /subscription",
+    "PUT /subscription/{id}/contact_info",
+    "PUT /subscription/{id}/space_id",
+    "PUT /subscription/{id}/unsubscribe",
+    "GET /{project_id}/addons",
+    "PUT /{project_id}/addons/{addon_id}",
+    "DELETE /{project_id}/addons/{addon_id}",
+    "POST /v0/message",
+];
+
+/// Return the routes starting with the first segment of `path`,
+/// or all of them if no route does
+fn routes_near(path: &str) -> Vec<&'static str> {
+    let first = path.trim_start_matches('/').split('/').next().unwrap_or("");
+    let prefix = format!("/{first}");
+    let near: Vec<&'static str> = ROUTES
+        .iter()
+        .copied()
+        .filter(|route| {
+            let route_path = route.split_once(' ').map(|(_, p)| p).unwrap_or(route);
+            route_path == prefix || route_path.starts_with(&format!("{prefix}/"))
+        })
+        .collect();
+    if near.is_empty() {
+        ROUTES.to_vec()
+    } else {
+        near
+    }
+}
+
+impl NodeManagerWorker {
+    /// Return a not found response naming the requested path and, if the node is
+    /// configured to, the supported routes closest to it
+    pub(super) async fn unknown_route<'a>(
+        &self,
+        req: &'a Request<'_>,
+    ) -> ResponseBuilder<UnknownRoute<'a>> {
+        let list_routes = self.node_manager.read().await.list_routes_on_unknown_path;
+        let routes = if list_routes {
+            routes_near(req.path())
+                .into_iter()
+                .map(|route| route.into())
+                .collect()
+        } else {
+            vec![]
+        };
+        Response::not_found(req.id()).body(UnknownRoute::new(req.path(), req.method(), routes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ROUTES;
+    use crate::nodes::models::base::UnknownRoute;
+    use crate::nodes::NODEMANAGER_ADDR;
+    use minicbor::Decoder;
+    use ockam::Result;
+    use ockam_core::api::{Method, Request, Response, Status};
+    use ockam_core::route;
+    use ockam_node::tokio::time::timeout;
+    use ockam_node::Context;
+    use std::time::Duration;
+
+    #[ockam_macros::test]
+    async fn unknown_route_is_described(ctx: &mut Context) -> Result<()> {
+        let _handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let req = Request::get("/node/unknown").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+        let body: UnknownRoute = dec.decode()?;
+
+        assert_eq!(body.path, "/node/unknown");
+        assert!(matches!(body.method, Some(Method::Get)));
+        assert!(body.routes.iter().any(|r| r == "GET /node/version"));
+        assert!(body.routes.iter().all(|r| r.contains(" /node")));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn listed_routes_are_handled(ctx: &mut Context) -> Result<()> {
+        let _handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        for route in ROUTES {
+            let (method, path) = route.split_once(' ').unwrap();
+            let path: Vec<&str> = path
+                .split('/')
+                .map(|segment| {
+                    if segment.starts_with('{') {
+                        "x"
+                    } else {
+                        segment
+                    }
+                })
+                .collect();
+            let method = match method {
+                "GET" => Method::Get,
+                "POST" => Method::Post,
+                "PUT" => Method::Put,
+                "DELETE" => Method::Delete,
+                _ => panic!("unexpected method in {route}"),
+            };
+            let req = Request::builder(method, path.join("/")).to_vec()?;
+
+            // Requests without a body fail in most handlers, and some of them wait for
+            // other nodes, but none of them is answered as an unknown route
+            let buf: Vec<u8> = match timeout(
+                Duration::from_secs(5),
+                ctx.send_and_receive(route![NODEMANAGER_ADDR], req),
+            )
+            .await
+            {
+                Ok(buf) => buf?,
+                Err(_) => continue,
+            };
+            let mut dec = Decoder::new(&buf);
+            let res: Response = dec.decode()?;
+            if res.status() == Some(Status::NotFound) {
+                if let Ok(body) = dec.decode::<UnknownRoute>() {
+                    assert!(body.routes.is_empty(), "{route} is not handled");
+                }
+            }
+        }
+
+        ctx.stop().await
+    }
+}
@@ -6,7 +6,8 @@ use crate::error::ApiError;
 use crate::nodes::models::secure_channel::{
     CreateSecureChannelListenerRequest, CreateSecureChannelRequest, CreateSecureChannelResponse,
     CredentialExchangeMode, DeleteSecureChannelRequest, DeleteSecureChannelResponse,
-    ShowSecureChannelRequest, ShowSecureChannelResponse,
+    GroupedSecureChannel, SecureChannelGroup, SecureChannelGroupList, ShowSecureChannelRequest,
+    ShowSecureChannelResponse,
 };
 use crate::nodes::registry::Registry;
 use crate::nodes::NodeManager;
@@ -18,13 +19,14 @@ use ockam_core::api::{Request, Response, ResponseBuilder};
 use ockam_core::sessions::{SessionId, Sessions};
 use ockam_core::{route, AsyncTryClone, CowStr};
 use ockam_identity::authenticated_storage::AuthenticatedStorage;
-
+use ockam_identity::credential::Timestamp;
 use ockam_identity::{
     Identity, IdentityIdentifier, IdentityVault, SecureChannelListenerTrustOptions,
-    SecureChannelTrustOptions, TrustMultiIdentifiersPolicy,
+    SecureChannelTrustOptions, TrustMultiIdentifiersPolicy, DEFAULT_SECURE_CHANNEL_TIMEOUT,
 };
 use ockam_multiaddr::MultiAddr;
 use ockam_node::Context;
+use std::collections::BTreeMap;
 
 impl NodeManager {
     async fn get_credential_if_needed<V: IdentityVault, S: AuthenticatedStorage>(
@@ -37,7 +39,11 @@ impl NodeManager {
         }
 
         debug!("Credential check: requesting...");
-        self.get_credential_impl(identity, false).await?;
+        // The fetch doesn't use the node manager, so it is shared with the fetches
+        // started without holding its lock, see `get_credential_unlocked`
+        let fetch = self.prepare_credential_fetch(identity, false, None).await?;
+        let credential = fetch.run(identity).await?;
+        self.store_credential(identity, fetch, credential).await?;
         debug!("Credential check: got new credential...");
 
         Ok(())
@@ -65,7 +71,7 @@ impl NodeManager {
         // Else, create it.
 
         debug!(%sc_route, "Creating secure channel");
-        let timeout = timeout.unwrap_or(Duration::from_secs(120));
+        let timeout = timeout.unwrap_or(DEFAULT_SECURE_CHANNEL_TIMEOUT);
         let trust_options = SecureChannelTrustOptions::new();
 
         let trust_options = match session {
@@ -175,6 +181,7 @@ impl NodeManager {
                 }
 
                 let authorities = self.authorities()?;
+                let _permit = self.credential_presentation_permit().await?;
                 identity
                     .present_credential_mutual(
                         route![sc_addr.clone(), DefaultAddress::CREDENTIALS_SERVICE],
@@ -243,6 +250,79 @@ impl NodeManager {
         self.registry.secure_channels.remove_by_addr(addr);
         Ok(())
     }
+
+    /// Close every secure channel established with the given peer, initiated or
+    /// accepted, along with the TCP connections they run over. Connections also used
+    /// by secure channels with other peers, or by inlets, are left open.
+    ///
+    /// Returns the number of closed secure channels and TCP connections. Failing to
+    /// close one of them doesn't prevent closing the others: the errors are reported
+    /// once everything else was closed.
+    pub(super) async fn close_peer_connections(
+        &mut self,
+        peer: &IdentityIdentifier,
+    ) -> Result<(usize, usize)> {
+        let (channels, others): (Vec<_>, Vec<_>) = self
+            .identity()?
+            .secure_channel_registry()
+            .get_channel_list()
+            .into_iter()
+            .partition(|entry| entry.their_id() == peer);
+
+        let senders = self.tcp_transport.registry().get_all_sender_workers();
+        let shared: Vec<&Address> = others
+            .iter()
+            .map(|entry| entry.next_hop())
+            .chain(
+                self.registry
+                    .inlets
+                    .values()
+                    .filter_map(|info| info.outlet_route.iter().next()),
+            )
+            .collect();
+        let mut connections = Vec::new();
+        for entry in &channels {
+            let hop = entry.next_hop();
+            if senders.contains(hop) && !shared.contains(&hop) && !connections.contains(hop) {
+                connections.push(hop.clone());
+            }
+        }
+
+        let mut errors = Vec::new();
+        let mut closed_channels = 0;
+        for entry in &channels {
+            let addr = entry.encryptor_messaging_address();
+            match self.delete_secure_channel(addr).await {
+                Ok(()) => closed_channels += 1,
+                Err(err) => {
+                    warn!(%addr, %peer, %err, "cannot close secure channel");
+                    errors.push(format!("secure channel {addr}: {err}"));
+                }
+            }
+        }
+
+        let mut closed_connections = 0;
+        for addr in &connections {
+            debug!(%addr, %peer, "closing tcp connection");
+            match self.tcp_transport.disconnect(addr).await {
+                Ok(()) => closed_connections += 1,
+                Err(err) => {
+                    warn!(%addr, %peer, %err, "cannot close tcp connection");
+                    errors.push(format!("tcp connection {addr}: {err}"));
+                }
+            }
+            self.transports
+                .retain(|_, (_, _, worker_addr, _)| worker_addr != addr);
+        }
+
+        if !errors.is_empty() {
+            return Err(ApiError::message(format!(
+                "cannot close all the connections of {peer}: {}",
+                errors.join(", ")
+            )));
+        }
+        Ok((closed_channels, closed_connections))
+    }
 }
 
 impl NodeManagerWorker {
@@ -261,6 +341,45 @@ impl NodeManagerWorker {
         )
     }
 
+    /// List the secure channels of the node, initiated or accepted, grouped by the
+    /// identity verified at the other end
+    pub(super) async fn list_secure_channels_by_identity(
+        &self,
+        req: &Request<'_>,
+    ) -> Result<ResponseBuilder<SecureChannelGroupList<'static>>> {
+        let node_manager = self.node_manager.read().await;
+        let now = Timestamp::now();
+        let mut groups: BTreeMap<IdentityIdentifier, Vec<_>> = BTreeMap::new();
+        for entry in node_manager
+            .identity()?
+            .secure_channel_registry()
+            .get_channel_list()
+        {
+            let age = match (now, entry.established()) {
+                (Some(now), Some(established)) => now.elapsed(established),
+                _ => None,
+            };
+            groups
+                .entry(entry.their_id().clone())
+                .or_default()
+                .push(GroupedSecureChannel::new(
+                    entry.encryptor_messaging_address(),
+                    entry.is_initiator(),
+                    age,
+                ));
+        }
+
+        Ok(Response::ok(req.id()).body(SecureChannelGroupList::new(
+            groups
+                .into_iter()
+                .map(|(identity, mut channels)| {
+                    channels.sort_by(|a, b| b.age_secs.cmp(&a.age_secs));
+                    SecureChannelGroup::new(&identity, channels)
+                })
+                .collect(),
+        )))
+    }
+
     pub(super) fn list_secure_channel_listener(
         &self,
         req: &Request<'_>,
@@ -413,4 +532,263 @@ impl NodeManagerWorker {
 
         Ok(response)
     }
+
+    pub(super) async fn close_peer_connections(
+        &mut self,
+        req: &Request<'_>,
+        id: &str,
+    ) -> Result<ResponseBuilder> {
+        let peer = IdentityIdentifier::try_from(id)?;
+        let mut node_manager = self.node_manager.write().await;
+        let (channels, connections) = node_manager.close_peer_connections(&peer).await?;
+        info!(%peer, %channels, %connections, "Closed peer connections");
+        Ok(Response::ok(req.id()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::nodes::models::secure_channel::{
+        CreateSecureChannelRequest, CredentialExchangeMode, SecureChannelGroupList,
+    };
+    use crate::nodes::NODEMANAGER_ADDR;
+    use minicbor::Decoder;
+    use ockam::identity::{Identity, TrustEveryonePolicy};
+    use ockam::Result;
+    use ockam_core::api::{Request, Response, Status};
+    use ockam_core::errcode::Kind;
+    use ockam_core::{route, Address, AllowAll, Any, AsyncTryClone, Routed, Worker};
+    use ockam_multiaddr::MultiAddr;
+    use ockam_node::{tokio, Context};
+    use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions};
+    use ockam_vault::Vault;
+    use std::str::FromStr;
+    use std::time::{Duration, Instant};
+
+    /// Never answer, like a secure channel listener on a stalled network
+    struct Unresponsive;
+
+    #[ockam::worker]
+    impl Worker for Unresponsive {
+        type Context = Context;
+        type Message = Any;
+
+        async fn handle_message(&mut self, _ctx: &mut Context, _msg: Routed<Any>) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn secure_channel_creation_times_out_as_requested(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        ctx.start_worker("unresponsive", Unresponsive, AllowAll, AllowAll)
+            .await?;
+
+        let mut node_manager = handle.node_manager.write().await;
+        let identity = node_manager.identity()?.async_try_clone().await?;
+        let started = Instant::now();
+        let err = node_manager
+            .create_secure_channel_internal(
+                &identity,
+                route!["unresponsive"],
+                None,
+                Some(Duration::from_millis(200)),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.code().kind, Kind::Timeout);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        drop(node_manager);
+
+        // The timeout of a request is used to create the secure channel
+        let addr = MultiAddr::from_str("/service/unresponsive").unwrap();
+        let request =
+            CreateSecureChannelRequest::new(&addr, None, CredentialExchangeMode::None, None, None)
+                .with_timeout(Duration::from_millis(200));
+        let req = Request::post("/node/secure_channel")
+            .body(request)
+            .to_vec()?;
+        let started = Instant::now();
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_ne!(res.status(), Some(Status::Ok));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn close_all_connections_of_a_peer(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let (listener, listener_address) = handle
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+            .await?;
+        let identity = handle
+            .node_manager
+            .read()
+            .await
+            .identity()?
+            .async_try_clone()
+            .await?;
+
+        let peer1 = Identity::create(ctx, &Vault::create()).await?;
+        peer1
+            .create_secure_channel_listener("peer1", TrustEveryonePolicy)
+            .await?;
+        let peer2 = Identity::create(ctx, &Vault::create()).await?;
+        peer2
+            .create_secure_channel_listener("peer2", TrustEveryonePolicy)
+            .await?;
+
+        // Two channels to peer1 and one to peer2, each over its own TCP connection,
+        // and one more to each peer over a shared connection
+        let shared = handle
+            .tcp
+            .connect(listener.to_string(), TcpConnectionTrustOptions::new())
+            .await?;
+        let mut channels: Vec<(&str, Address, Address)> = vec![];
+        for (peer, own_connection) in [
+            ("peer1", true),
+            ("peer1", true),
+            ("peer2", true),
+            ("peer1", false),
+            ("peer2", false),
+        ] {
+            let connection = if own_connection {
+                handle
+                    .tcp
+                    .connect(listener.to_string(), TcpConnectionTrustOptions::new())
+                    .await?
+            } else {
+                shared.clone()
+            };
+            let mut node_manager = handle.node_manager.write().await;
+            let channel = node_manager
+                .create_secure_channel_internal(
+                    &identity,
+                    route![connection.clone(), peer],
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            channels.push((peer, channel, connection));
+        }
+
+        // peer1 also opened a channel to the node, which accepted its connection
+        identity
+            .create_secure_channel_listener("api", TrustEveryonePolicy)
+            .await?;
+        let accepted_before = handle
+            .tcp
+            .registry()
+            .get_listener_connections(&listener_address);
+        let inbound = handle
+            .tcp
+            .connect(listener.to_string(), TcpConnectionTrustOptions::new())
+            .await?;
+        peer1
+            .create_secure_channel(route![inbound, "api"], TrustEveryonePolicy)
+            .await?;
+        let accepted: Vec<Address> = handle
+            .tcp
+            .registry()
+            .get_listener_connections(&listener_address)
+            .into_iter()
+            .filter(|a| !accepted_before.contains(a))
+            .collect();
+        assert_eq!(accepted.len(), 1);
+        let has_channel_from_peer1 = || {
+            identity
+                .secure_channel_registry()
+                .get_channel_list()
+                .iter()
+                .any(|e| !e.is_initiator() && e.their_id() == peer1.identifier())
+        };
+        while !has_channel_from_peer1() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let req = Request::delete(format!("/node/connections/{}", peer1.identifier())).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let node_manager = handle.node_manager.read().await;
+        let senders = handle.tcp.registry().get_all_sender_workers();
+        for (peer, channel, connection) in channels {
+            let is_open = peer == "peer2";
+            assert_eq!(
+                node_manager
+                    .registry
+                    .secure_channels
+                    .get_by_addr(&channel)
+                    .is_some(),
+                is_open
+            );
+            // The shared connection is still used by the channel to peer2
+            assert_eq!(
+                senders.contains(&connection),
+                is_open || connection == shared
+            );
+        }
+        assert!(!has_channel_from_peer1());
+        assert!(!senders.contains(&accepted[0]));
+        drop(node_manager);
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn secure_channels_are_grouped_by_remote_identity(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let peer1 = Identity::create(ctx, &Vault::create()).await?;
+        peer1
+            .create_secure_channel_listener("peer1", TrustEveryonePolicy)
+            .await?;
+        let peer2 = Identity::create(ctx, &Vault::create()).await?;
+        peer2
+            .create_secure_channel_listener("peer2", TrustEveryonePolicy)
+            .await?;
+
+        let identity = handle
+            .node_manager
+            .read()
+            .await
+            .identity()?
+            .async_try_clone()
+            .await?;
+        for peer in ["peer1", "peer1", "peer2"] {
+            identity
+                .create_secure_channel(route![peer], TrustEveryonePolicy)
+                .await?;
+        }
+
+        let req = Request::get("/node/secure_channel/by_identity").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let list: SecureChannelGroupList = dec.decode()?;
+
+        assert_eq!(list.groups.len(), 2);
+        for (peer, count) in [(&peer1, 2), (&peer2, 1)] {
+            let group = list
+                .groups
+                .iter()
+                .find(|g| g.identity == peer.identifier().to_string())
+                .expect("the peer should have a group");
+            assert_eq!(group.count, count);
+            assert_eq!(group.channels.len(), count as usize);
+            assert!(group
+                .channels
+                .iter()
+                .all(|c| c.initiator && c.age_secs.is_some()));
+        }
+
+        ctx.stop().await
+    }
 }
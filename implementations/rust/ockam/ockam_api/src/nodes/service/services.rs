@@ -242,13 +242,17 @@ impl NodeManager {
         if self.policies.get_policy(r, a).await?.is_none() {
             self.policies.set_policy(r, a, default).await?
         }
-        Ok(Arc::new(PolicyAccessControl::new(
+        let mut access_control = PolicyAccessControl::new(
             self.policies.clone(),
             self.attributes_storage.async_try_clone().await?,
             r.clone(),
             a.clone(),
             env,
-        )))
+        );
+        if let Some(statistics) = &self.abac_statistics {
+            access_control = access_control.with_statistics(statistics.clone())
+        }
+        Ok(Arc::new(access_control))
     }
 
     pub(super) async fn start_credential_issuer_service_impl(
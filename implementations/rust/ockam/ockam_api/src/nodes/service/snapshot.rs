@@ -0,0 +1,418 @@
+use crate::actions;
+use crate::config::cli::Authority;
+use crate::error::ApiError;
+use crate::nodes::registry::{InletInfo, OutletInfo};
+use crate::nodes::service::AuthorityInfo;
+use crate::nodes::NodeManager;
+use crate::{local_multiaddr_to_route, route_to_multiaddr};
+use either::Either;
+use ockam::identity::PublicIdentity;
+use ockam::{Address, Result};
+use ockam_abac::Resource;
+use ockam_core::api::{Error, Request, Response, ResponseBuilder};
+use ockam_multiaddr::MultiAddr;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::NodeManagerWorker;
+
+/// State of a node manager saved to disk, to restart the node without setting
+/// up its authorities and portals again. The node credential isn't part of it,
+/// it is kept in the node state when it is fetched.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeManagerSnapshot {
+    /// Identifier of the node identity the snapshot was taken with
+    identifier: String,
+    authorities: Vec<Authority>,
+    outlets: Vec<OutletSnapshot>,
+    inlets: Vec<InletSnapshot>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OutletSnapshot {
+    alias: String,
+    tcp_addr: String,
+    worker_addr: String,
+    description: Option<String>,
+    #[serde(default)]
+    allow: Vec<(String, String)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InletSnapshot {
+    alias: String,
+    bind_addr: String,
+    outlet_addr: MultiAddr,
+    description: Option<String>,
+    depends_on_credential: bool,
+}
+
+impl NodeManagerSnapshot {
+    /// Read a snapshot saved with [`NodeManagerSnapshot::write_to`]
+    pub fn read_from(path: &Path) -> Result<Self> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            ApiError::message(format!("invalid snapshot {}: {e}", path.display()))
+        };
+        let contents = std::fs::read_to_string(path).map_err(|e| invalid(&e))?;
+        serde_json::from_str(&contents).map_err(|e| invalid(&e))
+    }
+
+    /// Save the snapshot to `path`, replacing the previous one only once it is
+    /// completely written
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        let write = |e: &dyn std::fmt::Display| {
+            ApiError::message(format!("cannot write snapshot {}: {e}", path.display()))
+        };
+        let contents = serde_json::to_vec_pretty(self).map_err(|e| write(&e))?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, contents).map_err(|e| write(&e))?;
+        std::fs::rename(&tmp, path).map_err(|e| write(&e))
+    }
+}
+
+impl NodeManager {
+    /// Take a snapshot of the node authorities and portals. Portals which failed
+    /// to start aren't part of it, nor inlets reaching their outlet through
+    /// connections or secure channels, which don't outlive the node.
+    pub async fn snapshot(&self) -> Result<NodeManagerSnapshot> {
+        let mut authorities = Vec::new();
+        if let Some(infos) = &self.authorities {
+            for info in infos.as_ref() {
+                authorities.push(Authority::new(info.identity.export()?, info.addr.clone()));
+            }
+        }
+
+        let outlets = self
+            .registry
+            .outlets
+            .iter()
+            .filter(|(_, info)| !info.worker_addr.address().is_empty())
+            .map(|(alias, info)| OutletSnapshot {
+                alias: alias.clone(),
+                tcp_addr: info.tcp_addr.clone(),
+                worker_addr: info.worker_addr.address().to_string(),
+                description: info.description.clone(),
+                allow: info.allow.clone(),
+            })
+            .collect();
+
+        let mut inlets = Vec::new();
+        for (alias, info) in &self.registry.inlets {
+            if info.worker_addr.address().is_empty() {
+                continue;
+            }
+            if !info.persistent_route {
+                warn!(%alias, route = %info.outlet_route, "the route of the inlet doesn't outlive the node, it isn't saved");
+                continue;
+            }
+            let outlet_addr = match route_to_multiaddr(&info.outlet_route) {
+                Some(addr) => addr,
+                None => {
+                    warn!(%alias, route = %info.outlet_route, "cannot save the route of the inlet");
+                    continue;
+                }
+            };
+            inlets.push(InletSnapshot {
+                alias: alias.clone(),
+                bind_addr: info.bind_addr.clone(),
+                outlet_addr,
+                description: info.description.clone(),
+                depends_on_credential: info.depends_on_credential,
+            });
+        }
+
+        Ok(NodeManagerSnapshot {
+            identifier: self.identity.identifier().to_string(),
+            authorities,
+            outlets,
+            inlets,
+        })
+    }
+
+    /// Restore the authorities and portals of a snapshot.
+    ///
+    /// Only a snapshot taken with another identity is rejected. An authority or a
+    /// portal which can't be restored is skipped, so that the rest of the node
+    /// still starts.
+    pub async fn restore(&mut self, snapshot: NodeManagerSnapshot) -> Result<()> {
+        if snapshot.identifier != self.identity.identifier().to_string() {
+            return Err(ApiError::message(format!(
+                "the snapshot was taken by the identity {}",
+                snapshot.identifier
+            )));
+        }
+
+        let vault = self.vault()?;
+        let mut authorities = Vec::with_capacity(snapshot.authorities.len());
+        for authority in &snapshot.authorities {
+            let addr = authority.access_route();
+            match PublicIdentity::import(authority.identity(), vault).await {
+                Ok(identity) => authorities.push(AuthorityInfo {
+                    identity,
+                    addr: addr.clone(),
+                }),
+                Err(err) => warn!(%addr, %err, "failed to restore authority"),
+            }
+        }
+        self.add_authorities(authorities);
+
+        let project_id = if self.enable_credential_checks {
+            match self.project_id() {
+                Ok(project_id) => Some(project_id.to_string()),
+                Err(err) => {
+                    warn!(%err, "the portals can't check credentials, they aren't restored");
+                    return Ok(());
+                }
+            }
+        } else {
+            None
+        };
+
+        for outlet in snapshot.outlets {
+            if self.registry.outlets.contains_key(&outlet.alias) {
+                continue;
+            }
+            let worker_addr = Address::from(outlet.worker_addr.as_str());
+            let res = self
+                .outlet_access_control(
+                    &Resource::new(&outlet.alias),
+                    project_id.clone(),
+                    &outlet.allow,
+                )
+                .await;
+            let access_control = match res {
+                Ok(access_control) => access_control,
+                Err(err) => {
+                    warn!(alias = %outlet.alias, %err, "failed to restore tcp outlet");
+                    continue;
+                }
+            };
+            let res = self
+                .tcp_transport
+                .create_outlet_impl(worker_addr.clone(), outlet.tcp_addr.clone(), access_control)
+                .await;
+            let worker_addr = match res {
+                Ok(()) => Some(&worker_addr),
+                Err(err) => {
+                    warn!(alias = %outlet.alias, %err, "failed to restore tcp outlet");
+                    None
+                }
+            };
+            self.registry.outlets.insert(
+                outlet.alias,
+                OutletInfo::new(
+                    &outlet.tcp_addr,
+                    worker_addr,
+                    outlet.description.as_deref(),
+                    &outlet.allow,
+                ),
+            );
+        }
+
+        for inlet in snapshot.inlets {
+            if self.registry.inlets.contains_key(&inlet.alias) {
+                continue;
+            }
+            let outlet_route = match local_multiaddr_to_route(&inlet.outlet_addr) {
+                Some(route) => route,
+                None => {
+                    warn!(alias = %inlet.alias, addr = %inlet.outlet_addr, "invalid inlet outlet route");
+                    continue;
+                }
+            };
+            let res = self
+                .access_control(
+                    &Resource::new(&inlet.alias),
+                    &actions::HANDLE_MESSAGE,
+                    project_id.clone(),
+                )
+                .await;
+            let access_control = match res {
+                Ok(access_control) => access_control,
+                Err(err) => {
+                    warn!(alias = %inlet.alias, %err, "failed to restore tcp inlet");
+                    continue;
+                }
+            };
+            let res = self
+                .tcp_transport
+                .create_inlet_impl(
+                    inlet.bind_addr.clone(),
+                    outlet_route.clone(),
+                    access_control,
+                )
+                .await;
+            let worker_addr = match res {
+                Ok((worker_addr, _)) => Some(worker_addr),
+                Err(err) => {
+                    warn!(alias = %inlet.alias, %err, "failed to restore tcp inlet");
+                    None
+                }
+            };
+            self.registry.inlets.insert(
+                inlet.alias,
+                InletInfo::new(
+                    &inlet.bind_addr,
+                    worker_addr.as_ref(),
+                    &outlet_route,
+                    inlet.description.as_deref(),
+                    inlet.depends_on_credential,
+                    true,
+                ),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl NodeManagerWorker {
+    /// Save a snapshot of the node manager to the node's snapshot path
+    pub(super) async fn save_snapshot(
+        &self,
+        req: &Request<'_>,
+    ) -> Result<Either<ResponseBuilder<Error<'_>>, ResponseBuilder>> {
+        let node_manager = self.node_manager.read().await;
+        let path = match &node_manager.snapshot_path {
+            Some(path) => path,
+            None => {
+                let err = Error::new(req.path()).with_message("the node has no snapshot path");
+                return Ok(Either::Left(Response::bad_request(req.id()).body(err)));
+            }
+        };
+        node_manager.snapshot().await?.write_to(path)?;
+        Ok(Either::Right(Response::ok(req.id())))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NodeManagerSnapshot;
+    use crate::nodes::models::portal::{CreateInlet, CreateOutlet};
+    use crate::nodes::service::{Authorities, AuthorityInfo};
+    use crate::nodes::NODEMANAGER_ADDR;
+    use minicbor::Decoder;
+    use ockam::Result;
+    use ockam_core::api::{Request, Response, Status};
+    use ockam_core::{route, Address, CowStr};
+    use ockam_identity::credential::Credential;
+    use ockam_identity::Identity;
+    use ockam_multiaddr::MultiAddr;
+    use ockam_node::{tokio, Context};
+    use ockam_vault::Vault;
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Start a TCP server echoing what it receives and return its address
+    async fn start_echo_server() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let (mut reader, mut writer) = stream.split();
+                    let _ = tokio::io::copy(&mut reader, &mut writer).await;
+                });
+            }
+        });
+        addr
+    }
+
+    async fn post(ctx: &Context, req: Vec<u8>) -> Result<Option<Status>> {
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        Ok(res.status())
+    }
+
+    #[ockam_macros::test]
+    async fn restored_node_has_its_portals_and_credential(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.json");
+
+        // Nobody listens for the authority, the credential can't be fetched again
+        let authority = Identity::create(ctx, &Vault::create()).await?;
+        let credential = authority
+            .issue_credential(Credential::builder(handle.identity.identifier().clone()))
+            .await?;
+        {
+            let mut node_manager = handle.node_manager.write().await;
+            node_manager.authorities = Some(Authorities::new(vec![AuthorityInfo {
+                identity: authority.to_public().await?,
+                addr: MultiAddr::from_str("/ip4/127.0.0.1/tcp/1/service/api").unwrap(),
+            }]));
+            node_manager.persist_node_credential(&credential, authority.identifier())?;
+            node_manager.snapshot_path = Some(path.clone());
+        }
+
+        let echo_addr = start_echo_server().await;
+        let outlet = CreateOutlet::new(
+            echo_addr.to_string(),
+            "outlet-echo",
+            Some(CowStr::from("echo")),
+        );
+        let req = Request::post("/node/outlet").body(outlet).to_vec()?;
+        assert_eq!(post(ctx, req).await?, Some(Status::Ok));
+
+        let listen_addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let to = MultiAddr::from_str("/service/outlet-echo").unwrap();
+        let mut inlet = CreateInlet::to_node(listen_addr, to, None);
+        inlet.set_alias("echo-inlet".to_string());
+        let req = Request::post("/node/inlet").body(inlet).to_vec()?;
+        assert_eq!(post(ctx, req).await?, Some(Status::Ok));
+
+        let req = Request::post("/node/snapshot").to_vec()?;
+        assert_eq!(post(ctx, req).await?, Some(Status::Ok));
+        let snapshot = NodeManagerSnapshot::read_from(&path)?;
+
+        // Start over with a node without authorities, portals nor credential
+        {
+            let mut node_manager = handle.node_manager.write().await;
+            node_manager.authorities = None;
+            let inlet = node_manager.registry.inlets.remove("echo-inlet").unwrap();
+            node_manager
+                .tcp_transport
+                .stop_inlet(inlet.worker_addr)
+                .await?;
+            let outlet = node_manager.registry.outlets.remove("echo").unwrap();
+            node_manager
+                .tcp_transport
+                .stop_outlet(outlet.worker_addr)
+                .await?;
+            let node_state = handle.cli_state.nodes.get(&node_manager.node_name)?;
+            node_manager.identity = node_state.config.identity(ctx).await?;
+        }
+        let outlet_worker = Address::from_string("outlet-echo");
+        while ctx.list_workers().await?.contains(&outlet_worker)
+            || std::net::TcpListener::bind(listen_addr).is_err()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        {
+            let mut node_manager = handle.node_manager.write().await;
+            assert!(node_manager.identity.credential().await.is_none());
+            node_manager.restore(snapshot).await?;
+            // The persisted credential is accepted by the restored authority
+            assert!(node_manager.restore_node_credential().await?);
+            assert_eq!(node_manager.identity.credential().await, Some(credential));
+            assert!(node_manager.registry.inlets.contains_key("echo-inlet"));
+            assert!(node_manager.registry.outlets.contains_key("echo"));
+        }
+
+        // The restored inlet reaches the restored outlet
+        let mut stream = TcpStream::connect(listen_addr).await.unwrap();
+        stream.write_all(b"hello").await.unwrap();
+        let mut buf = [0u8; 5];
+        stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+
+        ctx.stop().await
+    }
+}
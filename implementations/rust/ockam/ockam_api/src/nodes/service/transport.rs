@@ -1,14 +1,138 @@
+use crate::error::ApiError;
+use crate::lmdb::LmdbStorage;
+use crate::nodes::connection::Connection;
 use crate::nodes::models::transport::{
-    CreateTransport, DeleteTransport, TransportList, TransportMode, TransportStatus,
+    ConnectionError, ConnectionErrorList, CreateTransport, CreateTransportJson, DeleteTransport,
+    ListenerDrainStatus, MigrateListener, ProbeReachability, ReachabilityStatus,
+    SubscribeTcpEvents, TcpConnectionStats, TcpConnectionStatus, TcpEventMessage,
+    TcpEventsSubscription, TcpTransportStats, TransportList, TransportMode, TransportStatus,
 };
-use crate::nodes::service::{random_alias, Alias, Transports};
+use crate::nodes::service::{map_multiaddr_err, random_alias, Alias, Transports};
+use crate::nodes::NodeManager;
+use crate::{create_tcp_session_with_options, local_multiaddr_to_route};
+use either::Either;
 use minicbor::Decoder;
-use ockam::Result;
+use ockam::identity::TrustEveryonePolicy;
+use ockam::{Context, Result};
 use ockam_core::api::{Request, Response, ResponseBuilder};
-use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions};
+use ockam_core::{AsyncTryClone, Route};
+use ockam_identity::{Identity, SecureChannelTrustOptions};
+use ockam_multiaddr::MultiAddr;
+use ockam_node::tokio;
+use ockam_node::tokio::sync::broadcast::error::RecvError;
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpTransport};
+use ockam_vault::Vault;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use super::NodeManagerWorker;
 
+/// Time given to a probe to connect to a route, and then to establish its secure channel
+pub(super) const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum number of concurrent subscriptions to the TCP events
+const MAX_TCP_EVENT_SUBSCRIPTIONS: usize = 16;
+
+/// A subscription to the TCP events ends once no event was sent for this long
+const TCP_EVENTS_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Connect to `route`, and create a secure channel to its end with `identity` if
+/// `secure_channel` is given, then close whatever was opened to reach it. Return the
+/// route of the TCP connection the probe went through.
+///
+/// The probe doesn't use the node manager: callers release its lock first, so that an
+/// unresponsive host doesn't hold up the node for the length of the probe.
+pub(super) async fn probe_route(
+    tcp: &TcpTransport,
+    identity: &Identity<Vault, LmdbStorage>,
+    route: &MultiAddr,
+    secure_channel: Option<SecureChannelTrustOptions>,
+) -> Result<Route> {
+    let tcp_options = TcpConnectionTrustOptions::new().with_connect_timeout(PROBE_TIMEOUT);
+    let tcp_session = create_tcp_session_with_options(route, tcp, tcp_options)
+        .await
+        .map_err(|err| ApiError::message(format!("cannot connect to {route}: {err}")))?;
+
+    let res = match secure_channel {
+        Some(trust_options) => {
+            let trust_options = match &tcp_session.session {
+                Some((sessions, session_id)) => {
+                    trust_options.with_ciphertext_session(sessions, session_id)
+                }
+                None => trust_options,
+            };
+            match identity
+                .create_secure_channel_extended(
+                    tcp_session.route.clone(),
+                    trust_options,
+                    PROBE_TIMEOUT,
+                )
+                .await
+            {
+                Ok(channel) => identity.stop_secure_channel(&channel).await,
+                Err(err) => Err(err),
+            }
+        }
+        None => Ok(()),
+    };
+
+    if let Ok(connection) = tcp_session.route.next() {
+        let _ = tcp.disconnect(connection).await;
+    }
+    res.map(|_| tcp_session.route)
+}
+
+impl NodeManager {
+    /// Send the events of the TCP connections to `route` until the subscription is
+    /// cancelled, stays idle for [`TCP_EVENTS_IDLE_TIMEOUT`] or the subscriber can't be
+    /// reached anymore, and return the subscription id
+    async fn subscribe_tcp_events_impl(&mut self, ctx: &Context, route: Route) -> Result<String> {
+        // Forget the subscriptions which ended on their own
+        self.tcp_event_subscriptions
+            .retain(|_, subscription| !subscription.is_finished());
+        if self.tcp_event_subscriptions.len() >= MAX_TCP_EVENT_SUBSCRIPTIONS {
+            return Err(ApiError::message(format!(
+                "the tcp events can't have more than {MAX_TCP_EVENT_SUBSCRIPTIONS} subscriptions"
+            )));
+        }
+
+        let id = random_alias();
+        let ctx = ctx.async_try_clone().await?;
+        let mut events = self.tcp_transport.registry().subscribe_events();
+        let subscription = tokio::spawn(async move {
+            loop {
+                let event = match tokio::time::timeout(TCP_EVENTS_IDLE_TIMEOUT, events.recv()).await
+                {
+                    Ok(Ok(event)) => event,
+                    Err(_) => {
+                        debug!(%route, "tcp events subscription is idle");
+                        break;
+                    }
+                    Ok(Err(RecvError::Lagged(missed))) => {
+                        warn!(%route, %missed, "tcp events subscriber is lagging behind");
+                        continue;
+                    }
+                    Ok(Err(RecvError::Closed)) => break,
+                };
+                let msg = match minicbor::to_vec(TcpEventMessage::from(&event)) {
+                    Ok(msg) => msg,
+                    Err(err) => {
+                        warn!(%err, "cannot encode a tcp event");
+                        continue;
+                    }
+                };
+                if let Err(err) = ctx.send(route.clone(), msg).await {
+                    debug!(%route, %err, "tcp events subscriber is unreachable");
+                    break;
+                }
+            }
+        });
+        self.tcp_event_subscriptions
+            .insert(id.clone(), subscription);
+        Ok(id)
+    }
+}
+
 impl NodeManagerWorker {
     pub(super) fn get_tcp_con_or_list<'a>(
         &self,
@@ -33,6 +157,126 @@ impl NodeManagerWorker {
         ))
     }
 
+    /// List the TCP connections created through the node manager, and all the
+    /// open connections registered by the TCP transport
+    pub(super) async fn get_tcp_connections(
+        &self,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<TransportList<'static>> {
+        let node_manager = self.node_manager.read().await;
+        let connections = node_manager
+            .tcp_transport
+            .registry()
+            .get_connections()
+            .iter()
+            .map(TcpConnectionStatus::new)
+            .collect();
+        let list = node_manager
+            .transports
+            .iter()
+            .filter(|(_, (_, tm, _, _))| *tm == TransportMode::Connect)
+            .map(|(tid, (tt, tm, worker_addr, socket_addr))| {
+                TransportStatus::new(
+                    *tt,
+                    *tm,
+                    socket_addr.clone(),
+                    worker_addr.address().to_string(),
+                    tid.to_string(),
+                )
+            })
+            .collect();
+        Response::ok(req.id()).body(TransportList::new(list).with_connections(connections))
+    }
+
+    pub(super) async fn get_tcp_connection_errors(
+        &self,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<ConnectionErrorList<'static>> {
+        let node_manager = self.node_manager.read().await;
+        let errors = node_manager
+            .tcp_transport
+            .registry()
+            .get_connection_errors();
+        Response::ok(req.id()).body(ConnectionErrorList::new(
+            errors
+                .iter()
+                .map(|e| {
+                    ConnectionError::new(e.peer().to_string(), e.time(), e.reason().to_string())
+                })
+                .collect(),
+        ))
+    }
+
+    /// Subscribe a route to the TCP events, see [`SubscribeTcpEvents`]
+    pub(super) async fn subscribe_tcp_events<'a>(
+        &self,
+        ctx: &Context,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<TcpEventsSubscription<'a>>> {
+        let mut node_manager = self.node_manager.write().await;
+        let request: SubscribeTcpEvents = dec.decode()?;
+        let addr = MultiAddr::from_str(&request.route).map_err(map_multiaddr_err)?;
+
+        let (sec, suffix) = node_manager.connect(Connection::new(ctx, &addr)).await?;
+        let route = local_multiaddr_to_route(&sec.try_with(&suffix)?)
+            .ok_or_else(|| ApiError::generic("invalid tcp events subscriber route"))?;
+        let id = node_manager.subscribe_tcp_events_impl(ctx, route).await?;
+        info!(%addr, %id, "subscribed to the tcp events");
+
+        Ok(Response::ok(req.id()).body(TcpEventsSubscription::new(id)))
+    }
+
+    /// Cancel a subscription to the TCP events
+    pub(super) async fn unsubscribe_tcp_events(
+        &self,
+        req: &Request<'_>,
+        id: &str,
+    ) -> ResponseBuilder {
+        let mut node_manager = self.node_manager.write().await;
+        match node_manager.tcp_event_subscriptions.remove(id) {
+            Some(subscription) => {
+                subscription.abort();
+                info!(%id, "unsubscribed from the tcp events");
+                Response::ok(req.id())
+            }
+            None => Response::not_found(req.id()),
+        }
+    }
+
+    /// Check whether a route can be reached, see [`ProbeReachability`]
+    pub(super) async fn probe_reachability<'a>(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+    ) -> Result<ResponseBuilder<ReachabilityStatus<'a>>> {
+        let request: ProbeReachability = dec.decode()?;
+        let route = MultiAddr::from_str(&request.route).map_err(map_multiaddr_err)?;
+        let (tcp, identity) = {
+            let node_manager = self.node_manager.read().await;
+            (
+                node_manager.tcp_transport.async_try_clone().await?,
+                node_manager.identity.async_try_clone().await?,
+            )
+        };
+
+        let started = Instant::now();
+        let secure_channel = request
+            .secure_channel
+            .then(|| SecureChannelTrustOptions::new().with_trust_policy(TrustEveryonePolicy));
+        let res = probe_route(&tcp, &identity, &route, secure_channel).await;
+        let elapsed = started.elapsed();
+        if let Err(err) = &res {
+            debug!(%route, %err, "route is unreachable");
+        }
+
+        Ok(Response::ok(req.id()).body(ReachabilityStatus::new(
+            &route,
+            elapsed,
+            res.err().map(|err| err.to_string()),
+        )))
+    }
+
     pub(super) async fn add_transport<'a>(
         &self,
         req: &Request<'_>,
@@ -94,6 +338,126 @@ impl NodeManagerWorker {
         Ok(response)
     }
 
+    /// Move the listener `tid` to a new address, see [`MigrateListener`]. The listener
+    /// keeps its id, and the connections accepted at its previous address are drained.
+    pub(super) async fn migrate_listener<'a>(
+        &self,
+        req: &Request<'_>,
+        dec: &mut Decoder<'_>,
+        tid: &str,
+    ) -> Result<Either<ResponseBuilder, ResponseBuilder<TransportStatus<'a>>>> {
+        let mut node_manager = self.node_manager.write().await;
+        let MigrateListener { addr, .. } = dec.decode()?;
+
+        let (tt, old_listener) = match node_manager.transports.get(tid) {
+            Some((tt, TransportMode::Listen, worker_address, _)) => (*tt, worker_address.clone()),
+            _ => return Ok(Either::Left(Response::not_found(req.id()))),
+        };
+        info!(%tid, %addr, "Handling request to migrate a listener");
+
+        let (socket_address, worker_address) = node_manager
+            .tcp_transport
+            .migrate_listener(&old_listener, &addr)
+            .await?;
+        let socket_address = socket_address.to_string();
+
+        // The CLI reaches the node API at the address of the node setup
+        if tid == node_manager.api_transport_id {
+            let node_state = node_manager.cli_state.nodes.get(&node_manager.node_name)?;
+            let listener = CreateTransportJson::new(tt, TransportMode::Listen, &socket_address)?;
+            node_state.set_setup(&node_state.setup()?.set_default_tcp_listener(listener))?;
+        }
+        node_manager.transports.insert(
+            tid.to_string(),
+            (
+                tt,
+                TransportMode::Listen,
+                worker_address.clone(),
+                socket_address.clone(),
+            ),
+        );
+        node_manager
+            .draining_listeners
+            .entry(tid.to_string())
+            .or_default()
+            .push(old_listener);
+
+        Ok(Either::Right(Response::ok(req.id()).body(
+            TransportStatus::new(
+                tt,
+                TransportMode::Listen,
+                socket_address,
+                worker_address.address().to_string(),
+                tid.to_string(),
+            ),
+        )))
+    }
+
+    /// Report the connections accepted at the previous addresses of the listener `tid`
+    /// which are still open, and forget the previous addresses which are drained
+    pub(super) async fn get_listener_drain_status<'a>(
+        &self,
+        req: &Request<'_>,
+        tid: &'a str,
+    ) -> Either<ResponseBuilder, ResponseBuilder<ListenerDrainStatus<'a>>> {
+        let mut node_manager = self.node_manager.write().await;
+        if !matches!(
+            node_manager.transports.get(tid),
+            Some((_, TransportMode::Listen, _, _))
+        ) {
+            return Either::Left(Response::not_found(req.id()));
+        }
+
+        let registry = node_manager.tcp_transport.registry().clone();
+        let mut connections = 0;
+        if let Some(listeners) = node_manager.draining_listeners.get_mut(tid) {
+            listeners.retain(|listener| {
+                let open = registry.get_listener_connections(listener).len();
+                connections += open;
+                open > 0
+            });
+            if listeners.is_empty() {
+                info!(%tid, "migrated listener is drained");
+                node_manager.draining_listeners.remove(tid);
+            }
+        }
+
+        Either::Right(Response::ok(req.id()).body(ListenerDrainStatus::new(tid, connections)))
+    }
+
+    /// Report the traffic of the outgoing connection `tid` since it was opened
+    pub(super) async fn get_tcp_connection_stats<'a>(
+        &self,
+        req: &Request<'_>,
+        tid: &'a str,
+    ) -> Either<ResponseBuilder, ResponseBuilder<TcpConnectionStats<'a>>> {
+        let node_manager = self.node_manager.read().await;
+        let stats = match node_manager.transports.get(tid) {
+            Some((_, TransportMode::Connect, worker_address, _)) => node_manager
+                .tcp_transport
+                .registry()
+                .connection_stats(worker_address),
+            _ => None,
+        };
+        match stats {
+            Some(stats) => {
+                Either::Right(Response::ok(req.id()).body(TcpConnectionStats::new(tid, stats)))
+            }
+            None => Either::Left(Response::not_found(req.id())),
+        }
+    }
+
+    /// Report the traffic of all the open connections of the node, and the frames
+    /// dropped by the transport policies
+    pub(super) async fn get_tcp_transport_stats(
+        &self,
+        req: &Request<'_>,
+    ) -> ResponseBuilder<TcpTransportStats> {
+        let node_manager = self.node_manager.read().await;
+        let stats = node_manager.tcp_transport.registry().transport_stats();
+        Response::ok(req.id()).body(TcpTransportStats::new(stats))
+    }
+
     pub(super) async fn delete_transport(
         &self,
         req: &Request<'_>,
@@ -119,3 +483,418 @@ impl NodeManagerWorker {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::nodes::models::transport::{
+        CreateTransport, ListenerDrainStatus, MigrateListener, ProbeReachability,
+        ReachabilityStatus, SubscribeTcpEvents, TcpConnectionStats, TcpEventKind, TcpEventMessage,
+        TcpEventsSubscription, TcpTransportStats, TransportList, TransportMode, TransportStatus,
+        TransportType,
+    };
+    use crate::nodes::NODEMANAGER_ADDR;
+    use minicbor::Decoder;
+    use ockam::identity::TrustEveryonePolicy;
+    use ockam::Result;
+    use ockam_core::api::{Request, Response, Status};
+    use ockam_core::{route, Address, AllowAll};
+    use ockam_identity::Identity;
+    use ockam_multiaddr::MultiAddr;
+    use ockam_node::{tokio, Context};
+    use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions};
+    use ockam_vault::Vault;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    /// Probe `route` and return whether it is reachable, along with the error if it isn't
+    async fn probe(
+        ctx: &mut Context,
+        route: &MultiAddr,
+        secure_channel: bool,
+    ) -> Result<(bool, Option<String>)> {
+        let req = Request::post("/node/reachability")
+            .body(ProbeReachability::new(route, secure_channel))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let status: ReachabilityStatus = dec.decode()?;
+        assert_eq!(status.route, route.to_string());
+        Ok((status.reachable, status.error.map(|e| e.to_string())))
+    }
+
+    #[ockam_macros::test]
+    async fn reachable_and_unreachable_routes_are_reported(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let peer = Identity::create(ctx, &Vault::create()).await?;
+        peer.create_secure_channel_listener("probed_api", TrustEveryonePolicy)
+            .await?;
+        let (listener, _) = handle
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+            .await?;
+        let reachable = MultiAddr::from_str(&format!(
+            "/ip4/127.0.0.1/tcp/{}/service/probed_api",
+            listener.port()
+        ))
+        .unwrap();
+
+        assert_eq!(probe(ctx, &reachable, false).await?, (true, None));
+        assert_eq!(probe(ctx, &reachable, true).await?, (true, None));
+
+        // Nobody listens on a port which was just released
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let unreachable =
+            MultiAddr::from_str(&format!("/ip4/127.0.0.1/tcp/{port}/service/probed_api")).unwrap();
+        let (reachable, error) = probe(ctx, &unreachable, false).await?;
+        assert!(!reachable);
+        assert!(error.is_some());
+
+        ctx.stop().await
+    }
+
+    /// Receive the tcp events sent to `subscriber` until one of `kind` from `peer` arrives
+    async fn wait_for_tcp_event(
+        subscriber: &mut Context,
+        kind: TcpEventKind,
+        peer: &str,
+    ) -> Result<()> {
+        loop {
+            let msg = subscriber
+                .receive_duration_timeout::<Vec<u8>>(Duration::from_secs(5))
+                .await?
+                .take()
+                .body();
+            let event: TcpEventMessage = Decoder::new(&msg).decode()?;
+            if event.kind == kind && event.peer == peer {
+                return Ok(());
+            }
+        }
+    }
+
+    #[ockam_macros::test]
+    async fn opened_and_closed_connections_are_streamed(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let mut subscriber = ctx
+            .new_detached("tcp_events_tail", AllowAll, AllowAll)
+            .await?;
+
+        let route = MultiAddr::from_str("/service/tcp_events_tail").unwrap();
+        let req = Request::post("/node/tcp/events")
+            .body(SubscribeTcpEvents::new(&route))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let subscription: TcpEventsSubscription = dec.decode()?;
+
+        let (listener, _) = handle
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+            .await?;
+        let connection = handle
+            .tcp
+            .connect(listener.to_string(), TcpConnectionTrustOptions::new())
+            .await?;
+        let peer = listener.to_string();
+        wait_for_tcp_event(&mut subscriber, TcpEventKind::ConnectionOpened, &peer).await?;
+        handle.tcp.disconnect(&connection).await?;
+        wait_for_tcp_event(&mut subscriber, TcpEventKind::ConnectionClosed, &peer).await?;
+
+        // Once unsubscribed, no more events are sent
+        let req = Request::delete(format!("/node/tcp/events/{}", subscription.id)).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        while subscriber
+            .receive_duration_timeout::<Vec<u8>>(Duration::from_millis(200))
+            .await
+            .is_ok()
+        {}
+        handle
+            .tcp
+            .connect(listener.to_string(), TcpConnectionTrustOptions::new())
+            .await?;
+        assert!(subscriber
+            .receive_duration_timeout::<Vec<u8>>(Duration::from_millis(500))
+            .await
+            .is_err());
+
+        ctx.stop().await
+    }
+
+    /// Return the drain status of the listener `tid`
+    async fn drain_status(ctx: &mut Context, tid: &str) -> Result<(u64, bool)> {
+        let req = Request::get(format!("/node/tcp/listener/{tid}/drain")).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let status: ListenerDrainStatus = dec.decode()?;
+        Ok((status.connections, status.drained))
+    }
+
+    #[ockam_macros::test]
+    async fn migrated_listener_is_drained_once_its_connections_are_closed(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let req = Request::post("/node/tcp/listener")
+            .body(CreateTransport::new(
+                TransportType::Tcp,
+                TransportMode::Listen,
+                "127.0.0.1:0",
+            ))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let old: TransportStatus = dec.decode()?;
+        let tid = old.tid.to_string();
+
+        let connection = handle
+            .tcp
+            .connect(
+                old.socket_addr.to_string(),
+                TcpConnectionTrustOptions::new(),
+            )
+            .await?;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let req = Request::post(format!("/node/tcp/listener/{tid}/migrate"))
+            .body(MigrateListener::new("127.0.0.1:0"))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let new: TransportStatus = dec.decode()?;
+        assert_eq!(new.tid.to_string(), tid);
+        assert_ne!(new.socket_addr, old.socket_addr);
+        assert_eq!(drain_status(ctx, &tid).await?, (1, false));
+
+        handle.tcp.disconnect(&connection).await?;
+        let mut drained = false;
+        for _ in 0..100 {
+            if drain_status(ctx, &tid).await? == (0, true) {
+                drained = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(drained);
+
+        // Unknown listeners have no drain status
+        let req = Request::get("/node/tcp/listener/unknown/drain").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn migrating_the_api_listener_updates_the_node_setup(ctx: &mut Context) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        // Serve the node API with an actual listener
+        let (socket_addr, listener) = handle
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+            .await?;
+        let (tid, node_name) = {
+            let mut node_manager = handle.node_manager.write().await;
+            let tid = node_manager.api_transport_id.clone();
+            node_manager.transports.insert(
+                tid.clone(),
+                (
+                    TransportType::Tcp,
+                    TransportMode::Listen,
+                    listener,
+                    socket_addr.to_string(),
+                ),
+            );
+            (tid, node_manager.node_name.clone())
+        };
+
+        let req = Request::post(format!("/node/tcp/listener/{tid}/migrate"))
+            .body(MigrateListener::new("127.0.0.1:0"))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let new: TransportStatus = dec.decode()?;
+
+        let setup = handle.cli_state.nodes.get(&node_name)?.setup()?;
+        let port = setup.default_tcp_listener()?.addr.port();
+        assert_ne!(port, socket_addr.port());
+        assert!(new.socket_addr.ends_with(&format!(":{port}")));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn tcp_connection_stats_count_the_sent_messages(ctx: &mut Context) -> Result<()> {
+        const MESSAGES: u64 = 3;
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let mut collector = ctx.new_detached("collector", AllowAll, AllowAll).await?;
+
+        let (listener, _) = handle
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+            .await?;
+        let req = Request::post("/node/tcp/connection")
+            .body(CreateTransport::new(
+                TransportType::Tcp,
+                TransportMode::Connect,
+                listener.to_string(),
+            ))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let connection: TransportStatus = dec.decode()?;
+
+        let sender = Address::from_string(connection.worker_addr.to_string());
+        for i in 0..MESSAGES {
+            ctx.send(route![sender.clone(), "collector"], i.to_string())
+                .await?;
+            collector.receive::<String>().await?;
+        }
+
+        let req =
+            Request::get(format!("/node/tcp/connection/{}/stats", connection.tid)).to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let stats: TcpConnectionStats = dec.decode()?;
+        assert_eq!(stats.messages_out, MESSAGES);
+        assert_eq!(stats.messages_in, 0);
+        assert!(stats.bytes_out > 0);
+
+        // Unknown connections have no stats
+        let req = Request::get("/node/tcp/connection/unknown/stats").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::NotFound));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn tcp_connection_list_includes_the_registered_connections(
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let (listener, _) = handle
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+            .await?;
+        let req = Request::post("/node/tcp/connection")
+            .body(CreateTransport::new(
+                TransportType::Tcp,
+                TransportMode::Connect,
+                listener.to_string(),
+            ))
+            .to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let res: Response = Decoder::new(&buf).decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+
+        let req = Request::get("/node/tcp/connection").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let list: TransportList = dec.decode()?;
+        assert_eq!(list.list.len(), 1);
+
+        // Both ends of the connection belong to the node
+        let connections = list.connections.unwrap();
+        assert_eq!(connections.len(), 2);
+        assert!(connections
+            .iter()
+            .any(|c| c.peer.to_string() == listener.to_string()));
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn tcp_transport_stats_sum_the_connection_stats(ctx: &mut Context) -> Result<()> {
+        const MESSAGES: u64 = 3;
+        let handle = crate::util::test::start_manager_for_tests(ctx).await?;
+        let mut collector = ctx.new_detached("collector", AllowAll, AllowAll).await?;
+
+        // Both ends of the connections belong to the node
+        let (listener, _) = handle
+            .tcp
+            .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+            .await?;
+        for _ in 0..2 {
+            let sender = handle
+                .tcp
+                .connect(listener.to_string(), TcpConnectionTrustOptions::new())
+                .await?;
+            for i in 0..MESSAGES {
+                ctx.send(route![sender.clone(), "collector"], i.to_string())
+                    .await?;
+                collector.receive::<String>().await?;
+            }
+        }
+
+        let req = Request::get("/node/tcp/stats").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let stats: TcpTransportStats = dec.decode()?;
+
+        let registry = handle.tcp.registry();
+        let connections: Vec<_> = registry
+            .get_all_sender_workers()
+            .iter()
+            .filter_map(|sender| registry.connection_stats(sender))
+            .collect();
+        assert_eq!(connections.len(), 4);
+        assert_eq!(stats.connections, connections.len() as u64);
+        assert_eq!(
+            stats.bytes_in,
+            connections.iter().map(|c| c.bytes_in).sum::<u64>()
+        );
+        assert_eq!(
+            stats.bytes_out,
+            connections.iter().map(|c| c.bytes_out).sum::<u64>()
+        );
+        assert_eq!(
+            stats.messages_in,
+            connections.iter().map(|c| c.messages_in).sum::<u64>()
+        );
+        assert_eq!(
+            stats.messages_out,
+            connections.iter().map(|c| c.messages_out).sum::<u64>()
+        );
+
+        // Every message sent by one end was received by the other one
+        assert_eq!(stats.messages_out, 2 * MESSAGES);
+        assert_eq!(stats.messages_in, 2 * MESSAGES);
+        assert_eq!(stats.bytes_in, stats.bytes_out);
+        assert_eq!(stats.dropped_messages, 0);
+        assert_eq!(stats.corrupt_frames, 0);
+        assert_eq!(stats.duplicate_sessions, 0);
+
+        ctx.stop().await
+    }
+}
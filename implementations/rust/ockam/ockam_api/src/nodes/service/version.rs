@@ -0,0 +1,71 @@
+use crate::nodes::models::base::NodeVersion;
+use ockam_core::api::{Request, Response, ResponseBuilder};
+use ockam_core::CowStr;
+
+use super::NodeManagerWorker;
+
+/// Cargo features of this crate the node was compiled with
+const FEATURES: &[(&str, bool)] = &[
+    ("std", cfg!(feature = "std")),
+    ("tag", cfg!(feature = "tag")),
+    ("vault-storage", cfg!(feature = "vault-storage")),
+    ("lmdb", cfg!(feature = "lmdb")),
+    (
+        "direct-authenticator",
+        cfg!(feature = "direct-authenticator"),
+    ),
+    ("fault-injection", cfg!(feature = "fault-injection")),
+];
+
+impl NodeManagerWorker {
+    /// Return the version of the node, the git commit it was built from
+    /// (embedded by the build script, "unknown" outside of the ockam repository)
+    /// and its enabled features
+    pub(super) fn get_node_version(&self, req: &Request<'_>) -> ResponseBuilder<NodeVersion<'_>> {
+        Response::ok(req.id()).body(NodeVersion::new(
+            env!("CARGO_PKG_VERSION"),
+            option_env!("GIT_HASH").map(str::trim),
+            enabled_features(),
+        ))
+    }
+}
+
+/// Names of the enabled [`FEATURES`]
+pub(super) fn enabled_features<'a>() -> Vec<CowStr<'a>> {
+    FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| (*name).into())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::nodes::models::base::NodeVersion;
+    use crate::nodes::NODEMANAGER_ADDR;
+    use minicbor::Decoder;
+    use ockam::Result;
+    use ockam_core::api::{Request, Response, Status};
+    use ockam_core::route;
+    use ockam_node::Context;
+
+    #[ockam_macros::test]
+    async fn node_version_is_returned(ctx: &mut Context) -> Result<()> {
+        let _handle = crate::util::test::start_manager_for_tests(ctx).await?;
+
+        let req = Request::get("/node/version").to_vec()?;
+        let buf: Vec<u8> = ctx.send_and_receive(route![NODEMANAGER_ADDR], req).await?;
+        let mut dec = Decoder::new(&buf);
+        let res: Response = dec.decode()?;
+        assert_eq!(res.status(), Some(Status::Ok));
+        let body: NodeVersion = dec.decode()?;
+
+        assert_eq!(body.version, env!("CARGO_PKG_VERSION"));
+        let parts: Vec<&str> = body.version.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|p| p.parse::<u32>().is_ok()));
+        assert!(body.features.iter().any(|f| f == "lmdb"));
+
+        ctx.stop().await
+    }
+}
@@ -82,63 +82,68 @@ pub struct TcpSession {
 }
 
 pub async fn create_tcp_session(ma: &MultiAddr, tcp: &TcpTransport) -> Option<TcpSession> {
+    create_tcp_session_with_options(ma, tcp, TcpConnectionTrustOptions::new())
+        .await
+        .ok()
+}
+
+/// Same as [`create_tcp_session`], with the TCP connection created with `trust_options`.
+/// The error tells why the session couldn't be created, e.g. a connect timeout.
+pub async fn create_tcp_session_with_options(
+    ma: &MultiAddr,
+    tcp: &TcpTransport,
+    trust_options: TcpConnectionTrustOptions,
+) -> Result<TcpSession> {
+    let invalid = || ApiError::generic("invalid multiaddr");
     let mut rb = Route::new();
     let mut it = ma.iter().peekable();
     let sessions = Sessions::default();
     let session_id = sessions.generate_session_id();
 
-    let mut trust_options =
-        Some(TcpConnectionTrustOptions::new().with_session(&sessions, &session_id));
+    let mut trust_options = Some(trust_options.with_session(&sessions, &session_id));
 
     while let Some(p) = it.next() {
         match p.code() {
             Ip4::CODE => {
-                let ip4 = p.cast::<Ip4>()?;
-                let port = it.next()?.cast::<Tcp>()?;
+                let ip4 = p.cast::<Ip4>().ok_or_else(invalid)?;
+                let port = it
+                    .next()
+                    .and_then(|p| p.cast::<Tcp>())
+                    .ok_or_else(invalid)?;
                 let socket_addr = SocketAddrV4::new(*ip4, *port);
 
-                let trust_options = match trust_options.take() {
-                    Some(trust_options) => trust_options,
-                    None => return None, // Only 1 TCP hop is allowed
-                };
+                // Only 1 TCP hop is allowed
+                let trust_options = trust_options.take().ok_or_else(invalid)?;
 
-                let addr = tcp
-                    .connect(socket_addr.to_string(), trust_options)
-                    .await
-                    .ok()?;
+                let addr = tcp.connect(socket_addr.to_string(), trust_options).await?;
                 rb = rb.append(addr)
             }
             Ip6::CODE => {
-                let ip6 = p.cast::<Ip6>()?;
-                let port = it.next()?.cast::<Tcp>()?;
+                let ip6 = p.cast::<Ip6>().ok_or_else(invalid)?;
+                let port = it
+                    .next()
+                    .and_then(|p| p.cast::<Tcp>())
+                    .ok_or_else(invalid)?;
                 let socket_addr = SocketAddrV6::new(*ip6, *port, 0, 0);
 
-                let trust_options = match trust_options.take() {
-                    Some(trust_options) => trust_options,
-                    None => return None, // Only 1 TCP hop is allowed
-                };
+                // Only 1 TCP hop is allowed
+                let trust_options = trust_options.take().ok_or_else(invalid)?;
 
-                let addr = tcp
-                    .connect(socket_addr.to_string(), trust_options)
-                    .await
-                    .ok()?;
+                let addr = tcp.connect(socket_addr.to_string(), trust_options).await?;
                 rb = rb.append(addr)
             }
             DnsAddr::CODE => {
-                let host = p.cast::<DnsAddr>()?;
+                let host = p.cast::<DnsAddr>().ok_or_else(invalid)?;
                 if let Some(p) = it.peek() {
                     if p.code() == Tcp::CODE {
-                        let port = p.cast::<Tcp>()?;
+                        let port = p.cast::<Tcp>().ok_or_else(invalid)?;
 
-                        let trust_options = match trust_options.take() {
-                            Some(trust_options) => trust_options,
-                            None => return None, // Only 1 TCP hop is allowed
-                        };
+                        // Only 1 TCP hop is allowed
+                        let trust_options = trust_options.take().ok_or_else(invalid)?;
 
                         let addr = tcp
                             .connect(format!("{}:{}", &*host, *port), trust_options)
-                            .await
-                            .ok()?;
+                            .await?;
                         rb = rb.append(addr);
                         let _ = it.next();
                         continue;
@@ -146,30 +151,30 @@ pub async fn create_tcp_session(ma: &MultiAddr, tcp: &TcpTransport) -> Option<Tc
                 }
             }
             Worker::CODE => {
-                let local = p.cast::<Worker>()?;
+                let local = p.cast::<Worker>().ok_or_else(invalid)?;
                 rb = rb.append(Address::new(LOCAL, &*local))
             }
             Service::CODE => {
-                let local = p.cast::<Service>()?;
+                let local = p.cast::<Service>().ok_or_else(invalid)?;
                 rb = rb.append(Address::new(LOCAL, &*local))
             }
             Secure::CODE => {
-                let local = p.cast::<Secure>()?;
+                let local = p.cast::<Secure>().ok_or_else(invalid)?;
                 rb = rb.append(Address::new(LOCAL, &*local))
             }
             other => {
                 error!(target: "ockam_api", code = %other, "unsupported protocol");
-                return None;
+                return Err(invalid());
             }
         }
     }
 
     match trust_options {
-        Some(_) => Some(TcpSession {
+        Some(_) => Ok(TcpSession {
             session: None,
             route: rb.into(),
         }),
-        None => Some(TcpSession {
+        None => Ok(TcpSession {
             session: Some((sessions, session_id)),
             route: rb.into(),
         }),
@@ -382,6 +387,18 @@ pub mod test {
     /// things *will* break.
     // #[must_use] make sense to enable only on rust 1.67+
     pub async fn start_manager_for_tests(context: &mut Context) -> Result<NodeManagerHandle> {
+        start_manager_for_tests_with_projects_options(
+            context,
+            NodeManagerProjectsOptions::new(None, None, Default::default(), None),
+        )
+        .await
+    }
+
+    /// Starts a local node manager configured with `projects_options`, see [`start_manager_for_tests`]
+    pub async fn start_manager_for_tests_with_projects_options(
+        context: &mut Context,
+        projects_options: NodeManagerProjectsOptions<'_>,
+    ) -> Result<NodeManagerHandle> {
         let tcp = TcpTransport::create(context).await?;
         let cli_state = CliState::test()?;
 
@@ -411,7 +428,7 @@ pub mod test {
         let node_manager = NodeManager::create(
             context,
             NodeManagerGeneralOptions::new(cli_state.clone(), node_name, true, None),
-            NodeManagerProjectsOptions::new(None, None, Default::default(), None),
+            projects_options,
             NodeManagerTransportOptions::new(
                 (
                     crate::nodes::models::transport::TransportType::Tcp,
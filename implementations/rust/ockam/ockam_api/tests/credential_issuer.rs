@@ -1,16 +1,22 @@
 use ockam_core::compat::collections::{BTreeMap, HashMap};
 
+use minicbor::Decoder;
 use ockam::authenticated_storage::AttributesEntry;
 use ockam::identity::Identity;
 use ockam::route;
 use ockam::vault::Vault;
 use ockam_api::authenticator::direct;
+use ockam_api::authenticator::direct::types::RequestCredential;
 use ockam_api::bootstrapped_identities_store::PreTrustedIdentities;
+use ockam_core::api::{Request, Response};
 use ockam_core::compat::rand::random_string;
-use ockam_core::{AllowAll, AsyncTryClone, Result};
-use ockam_identity::credential::Timestamp;
-use ockam_identity::{PublicIdentity, TrustEveryonePolicy};
+use ockam_core::{AllowAll, AsyncTryClone, Result, Routed, Worker};
+use ockam_identity::authenticated_storage::mem::InMemoryStorage;
+use ockam_identity::authenticated_storage::AuthenticatedAttributeStorage;
+use ockam_identity::credential::{Credential, Timestamp};
+use ockam_identity::{IdentitySecureChannelLocalInfo, PublicIdentity, TrustEveryonePolicy};
 use ockam_node::Context;
+use std::time::Duration;
 
 #[ockam_macros::test]
 async fn credential(ctx: &mut Context) -> Result<()> {
@@ -73,3 +79,257 @@ async fn credential(ctx: &mut Context) -> Result<()> {
     assert_eq!(Some(b"value".as_slice()), data.attributes().get("attr"));
     ctx.stop().await
 }
+
+/// Credential issuer including the requested attributes in the credentials it issues
+struct EchoIssuer {
+    identity: Identity<Vault, InMemoryStorage>,
+}
+
+#[ockam::worker]
+impl Worker for EchoIssuer {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Vec<u8>>) -> Result<()> {
+        let info = IdentitySecureChannelLocalInfo::find_info(msg.local_message())?;
+        let mut dec = Decoder::new(msg.as_body());
+        let req: Request = dec.decode()?;
+        let requested: RequestCredential = dec.decode()?;
+        let builder = requested.attributes().iter().fold(
+            Credential::builder(info.their_identity_id().clone()),
+            |builder, (name, value)| builder.with_attribute(name, value.as_bytes()),
+        );
+        let credential = self.identity.issue_credential(builder).await?;
+        let res = Response::ok(req.id()).body(credential).to_vec()?;
+        ctx.send(msg.return_route(), res).await
+    }
+}
+
+#[ockam_macros::test]
+async fn credential_with_requested_attributes(ctx: &mut Context) -> Result<()> {
+    let api_worker_addr = random_string();
+    let auth_worker_addr = random_string();
+
+    let auth_identity = Identity::create(ctx, &Vault::create()).await?;
+    let member_identity = Identity::create(ctx, &Vault::create()).await?;
+    auth_identity
+        .create_secure_channel_listener(&api_worker_addr, TrustEveryonePolicy)
+        .await?;
+    let issuer = EchoIssuer {
+        identity: auth_identity.async_try_clone().await?,
+    };
+    ctx.start_worker(&auth_worker_addr, issuer, AllowAll, AllowAll)
+        .await?;
+
+    let e2a = member_identity
+        .create_secure_channel(&api_worker_addr, TrustEveryonePolicy)
+        .await?;
+    let c = direct::CredentialIssuerClient::new(
+        direct::RpcClient::new(route![e2a.address(), &auth_worker_addr], ctx).await?,
+    );
+    let requested = BTreeMap::from([
+        ("role".to_string(), "developer".to_string()),
+        ("team".to_string(), "edge".to_string()),
+    ]);
+    let cred = c.credential_with_attributes(requested).await?;
+
+    let exported = auth_identity.export().await?;
+    let pkey = PublicIdentity::import(&exported, &Vault::create())
+        .await
+        .unwrap();
+    let data = pkey
+        .verify_credential(&cred, member_identity.identifier(), &Vault::create())
+        .await?;
+    assert_eq!(Some(b"developer".as_slice()), data.attributes().get("role"));
+    assert_eq!(Some(b"edge".as_slice()), data.attributes().get("team"));
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn enroll_devices(ctx: &mut Context) -> Result<()> {
+    let api_worker_addr = random_string();
+    let issuer_worker_addr = random_string();
+
+    let auth_identity = Identity::create(ctx, &Vault::create()).await?;
+    let enroller_identity = Identity::create(ctx, &Vault::create()).await?;
+    let store = AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+
+    // Create the EnrollmentTokenIssuer:
+    auth_identity
+        .create_secure_channel_listener(&api_worker_addr, TrustEveryonePolicy)
+        .await?;
+    let (issuer, _acceptor) =
+        direct::EnrollmentTokenAuthenticator::new_worker_pair(b"project42".to_vec(), store);
+    ctx.start_worker(&issuer_worker_addr, issuer, AllowAll, AllowAll)
+        .await?;
+
+    // Connect to the API channel from the enroller:
+    let e2a = enroller_identity
+        .create_secure_channel(&api_worker_addr, TrustEveryonePolicy)
+        .await?;
+    let c = direct::TokenIssuerClient::new(
+        direct::RpcClient::new(route![e2a.address(), &issuer_worker_addr], ctx).await?,
+    );
+
+    // The second device has an invalid attribute, which must not stop the batch
+    let devices = "\
+        # device queue\n\
+        sensor-1 role=sensor floor=1\n\
+        sensor-2 role\n\
+        \n\
+        gateway-1 role=gateway\n";
+    let mut outcomes = Vec::new();
+    c.enroll_devices(devices.as_bytes(), |line, outcome| {
+        outcomes.push((line, outcome))
+    })
+    .await?;
+
+    assert_eq!(outcomes.len(), 3);
+    let (line, outcome) = &outcomes[0];
+    assert_eq!(*line, 2);
+    let (device, _token) = outcome.as_ref().unwrap();
+    assert_eq!(device.name(), "sensor-1");
+    assert_eq!(
+        device.attributes(),
+        HashMap::from([("role", "sensor"), ("floor", "1")])
+    );
+
+    let (line, outcome) = &outcomes[1];
+    assert_eq!(*line, 3);
+    assert!(outcome.is_err());
+
+    let (line, outcome) = &outcomes[2];
+    assert_eq!(*line, 5);
+    let (device, _token) = outcome.as_ref().unwrap();
+    assert_eq!(device.name(), "gateway-1");
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn tokens_issued_before_a_rotation_are_accepted_during_the_grace_period(
+    ctx: &mut Context,
+) -> Result<()> {
+    let api_worker_addr = random_string();
+    let issuer_worker_addr = random_string();
+    let acceptor_worker_addr = random_string();
+
+    let auth_identity = Identity::create(ctx, &Vault::create()).await?;
+    let enroller_identity = Identity::create(ctx, &Vault::create()).await?;
+    let member_identity = Identity::create(ctx, &Vault::create()).await?;
+    let store = AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+
+    // Create the EnrollmentTokenIssuer and EnrollmentTokenAcceptor:
+    auth_identity
+        .create_secure_channel_listener(&api_worker_addr, TrustEveryonePolicy)
+        .await?;
+    let (issuer, acceptor) =
+        direct::EnrollmentTokenAuthenticator::new_worker_pair(b"project42".to_vec(), store);
+    ctx.start_worker(&issuer_worker_addr, issuer, AllowAll, AllowAll)
+        .await?;
+    ctx.start_worker(&acceptor_worker_addr, acceptor, AllowAll, AllowAll)
+        .await?;
+
+    let e2a = enroller_identity
+        .create_secure_channel(&api_worker_addr, TrustEveryonePolicy)
+        .await?;
+    let issuer_client = direct::TokenIssuerClient::new(
+        direct::RpcClient::new(route![e2a.address(), &issuer_worker_addr], ctx).await?,
+    );
+    let m2a = member_identity
+        .create_secure_channel(&api_worker_addr, TrustEveryonePolicy)
+        .await?;
+    let acceptor_client = direct::TokenAcceptorClient::new(
+        direct::RpcClient::new(route![m2a.address(), &acceptor_worker_addr], ctx).await?,
+    );
+
+    // Tokens issued on both sides of a rotation are accepted during the grace period
+    let old_token = issuer_client.create_token(HashMap::new()).await?;
+    issuer_client.rotate_tokens(Duration::from_secs(60)).await?;
+    let new_token = issuer_client.create_token(HashMap::new()).await?;
+    acceptor_client.present_token(&old_token).await?;
+    acceptor_client.present_token(&new_token).await?;
+
+    // Rotating again doesn't end the grace period of the earlier rotation
+    let oldest_token = issuer_client.create_token(HashMap::new()).await?;
+    issuer_client.rotate_tokens(Duration::from_secs(60)).await?;
+    let old_token = issuer_client.create_token(HashMap::new()).await?;
+    issuer_client.rotate_tokens(Duration::from_secs(60)).await?;
+    acceptor_client.present_token(&oldest_token).await?;
+    acceptor_client.present_token(&old_token).await?;
+
+    // Once the grace period is over, the tokens issued before the rotation are rejected
+    let old_token = issuer_client.create_token(HashMap::new()).await?;
+    issuer_client.rotate_tokens(Duration::ZERO).await?;
+    assert!(acceptor_client.present_token(&old_token).await.is_err());
+    let new_token = issuer_client.create_token(HashMap::new()).await?;
+    acceptor_client.present_token(&new_token).await?;
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn revoked_tokens_are_rejected(ctx: &mut Context) -> Result<()> {
+    let api_worker_addr = random_string();
+    let issuer_worker_addr = random_string();
+    let acceptor_worker_addr = random_string();
+
+    let auth_identity = Identity::create(ctx, &Vault::create()).await?;
+    let enroller_identity = Identity::create(ctx, &Vault::create()).await?;
+    let member_identity = Identity::create(ctx, &Vault::create()).await?;
+    let store = AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+
+    // Create the EnrollmentTokenIssuer and EnrollmentTokenAcceptor:
+    auth_identity
+        .create_secure_channel_listener(&api_worker_addr, TrustEveryonePolicy)
+        .await?;
+    let (issuer, acceptor) =
+        direct::EnrollmentTokenAuthenticator::new_worker_pair(b"project42".to_vec(), store);
+    ctx.start_worker(&issuer_worker_addr, issuer, AllowAll, AllowAll)
+        .await?;
+    ctx.start_worker(&acceptor_worker_addr, acceptor, AllowAll, AllowAll)
+        .await?;
+
+    let e2a = enroller_identity
+        .create_secure_channel(&api_worker_addr, TrustEveryonePolicy)
+        .await?;
+    let issuer_client = direct::TokenIssuerClient::new(
+        direct::RpcClient::new(route![e2a.address(), &issuer_worker_addr], ctx).await?,
+    );
+    let m2a = member_identity
+        .create_secure_channel(&api_worker_addr, TrustEveryonePolicy)
+        .await?;
+    let acceptor_client = direct::TokenAcceptorClient::new(
+        direct::RpcClient::new(route![m2a.address(), &acceptor_worker_addr], ctx).await?,
+    );
+
+    // Outstanding tokens are listed with their attributes
+    let revoked_token = issuer_client
+        .create_token(HashMap::from([("role", "revoked")]))
+        .await?;
+    let kept_token = issuer_client
+        .create_token(HashMap::from([("role", "kept")]))
+        .await?;
+    let tokens = issuer_client.list_tokens().await?;
+    assert_eq!(tokens.len(), 2);
+    let revoked_id = tokens
+        .iter()
+        .find(|t| t.attributes().get("role").map(String::as_str) == Some("revoked"))
+        .unwrap()
+        .id()
+        .to_string();
+
+    // A revoked token is rejected and not listed anymore, while the others still work
+    issuer_client.revoke_token(&revoked_id).await?;
+    assert!(issuer_client.revoke_token(&revoked_id).await.is_err());
+    let tokens = issuer_client.list_tokens().await?;
+    assert_eq!(tokens.len(), 1);
+    assert_ne!(tokens[0].id(), revoked_id);
+    assert!(acceptor_client.present_token(&revoked_token).await.is_err());
+    acceptor_client.present_token(&kept_token).await?;
+
+    // Redeemed tokens aren't outstanding anymore
+    assert!(issuer_client.list_tokens().await?.is_empty());
+
+    ctx.stop().await
+}
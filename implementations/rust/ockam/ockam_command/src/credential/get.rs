@@ -1,6 +1,10 @@
 use clap::Args;
+use std::str::FromStr;
+use std::time::Duration;
 
+use ockam::identity::IdentityIdentifier;
 use ockam::Context;
+use ockam_api::nodes::models::credentials::CredentialResponse;
 
 use crate::node::NodeOpts;
 use crate::util::{api, node_rpc, Rpc};
@@ -16,6 +20,11 @@ pub struct GetCommand {
 
     #[arg(long = "identity", value_name = "IDENTITY")]
     identity: Option<String>,
+
+    /// Identifier of the authority to get the credential from, instead of the first
+    /// authority of the node
+    #[arg(long = "authority", value_name = "IDENTIFIER", value_parser = IdentityIdentifier::from_str)]
+    authority: Option<IdentityIdentifier>,
 }
 
 impl GetCommand {
@@ -37,7 +46,28 @@ async fn run_impl(
     rpc.request(api::credentials::get_credential(
         cmd.overwrite,
         cmd.identity,
+        cmd.authority,
     ))
     .await?;
+    let response = rpc.parse_response::<CredentialResponse>()?;
+    if let Some(name) = &response.fallback_identity {
+        println!("Identity not found, got a credential for the fallback identity {name}");
+    }
+    match response.seconds_remaining() {
+        Some(remaining) if remaining.is_zero() => println!("Credential expired"),
+        Some(remaining) => println!("Credential expires in {}", format_remaining(remaining)),
+        None => println!("Credential expires never"),
+    }
     Ok(())
 }
+
+/// Format the time left before a credential expires in its largest unit, e.g. "42m"
+fn format_remaining(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
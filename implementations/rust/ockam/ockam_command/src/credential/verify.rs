@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::{util::node_rpc, vault::default_vault_name, CommandGlobalOpts};
 use anyhow::anyhow;
@@ -7,16 +7,23 @@ use clap::Args;
 use colorful::Colorful;
 use ockam::Context;
 
-use ockam_identity::IdentityIdentifier;
-
-use super::validate_encoded_cred;
+use ockam_api::authenticator::direct::PROJECT_MEMBER_SCHEMA;
+use ockam_identity::credential::{Credential, CredentialData, Unverified, Verified};
+use ockam_identity::{IdentityError, IdentityIdentifier, IdentityVault, PublicIdentity};
 
 #[derive(Clone, Debug, Args)]
 pub struct VerifyCommand {
-    #[arg(long = "issuer")]
-    pub issuer: IdentityIdentifier,
+    /// Identifier of an identity of this machine which may have issued the credential
+    #[arg(long = "issuer", required_unless_present = "authorities")]
+    pub issuer: Option<IdentityIdentifier>,
+
+    /// Exported identity, hex encoded, of an authority which may have issued the credential
+    #[arg(long = "authority", value_name = "IDENTITY", value_parser = parse_identity)]
+    pub authorities: Vec<Vec<u8>>,
 
-    #[arg(group = "credential_value", value_name = "CREDENTIAL_STRING", long)]
+    /// The credential, or the path of a file holding it, encoded with CBOR, hex encoded,
+    /// or as displayed by `ockam credential show`
+    #[arg(group = "credential_value", value_name = "CREDENTIAL", long)]
     pub credential: Option<String>,
 
     #[arg(group = "credential_value", value_name = "CREDENTIAL_FILE", long)]
@@ -36,20 +43,88 @@ async fn run_impl(
     ctx: Context,
     (opts, cmd): (CommandGlobalOpts, VerifyCommand),
 ) -> crate::Result<()> {
-    let cred_as_str = match (cmd.credential, cmd.credential_path) {
-        (_, Some(credential_path)) => tokio::fs::read_to_string(credential_path).await?,
-        (Some(credential), _) => credential,
+    let encoded = match (cmd.credential, cmd.credential_path) {
+        (_, Some(credential_path)) => tokio::fs::read(credential_path).await?,
+        (Some(credential), _) if Path::new(&credential).is_file() => {
+            tokio::fs::read(credential).await?
+        }
+        (Some(credential), _) => credential.into_bytes(),
         _ => return Err(anyhow!("Credential or Credential Path argument must be provided").into()),
     };
+    let credentials = decode_credentials(&encoded)?;
 
-    match validate_encoded_cred(&cred_as_str, &cmd.issuer, &cmd.vault, &opts, &ctx).await {
-        Ok(_) => {
+    let vault = opts.state.vaults.get(&cmd.vault)?.get().await?;
+    let mut authorities = Vec::new();
+    for identity in &cmd.authorities {
+        authorities.push(PublicIdentity::import(identity, &vault).await?);
+    }
+    if let Some(issuer) = &cmd.issuer {
+        let identity = opts
+            .state
+            .identities
+            .get_by_identifier(issuer)?
+            .get(&ctx, &vault)
+            .await?;
+        authorities.push(identity.to_public().await?);
+    }
+
+    match verify_credentials(&credentials, &authorities, &vault).await {
+        Ok(data) => {
             println!("{} Verified Credential", "✔︎".light_green());
+            println!("  Subject: {}", data.subject());
+            println!("  Issuer: {}", data.issuer());
+            println!("  Expires: {}", u64::from(data.expires_at()));
+            println!("  Attributes:");
+            for (name, value) in data.attributes().iter() {
+                let value = std::str::from_utf8(value).unwrap_or("**binary**");
+                println!("    {name}: {value}");
+            }
+            Ok(())
         }
-        Err(e) => {
-            println!("{} Credential is not valid!\n\n{e}", "✕".light_red());
+        Err(e) => Err(anyhow!("{} Credential is not valid!\n\n{e}", "✕".light_red()).into()),
+    }
+}
+
+fn parse_identity(identity: &str) -> crate::Result<Vec<u8>> {
+    hex::decode(identity).map_err(|e| anyhow!(e).into())
+}
+
+/// Decode a credential encoded with CBOR, hex encoded or not, or as displayed by
+/// [`Credential`]. A credential is returned for each schema it may have in the latter
+/// case, see [`Credential::from_display`]
+fn decode_credentials(encoded: &[u8]) -> crate::Result<Vec<Credential>> {
+    if let Ok(text) = std::str::from_utf8(encoded) {
+        let text = text.trim();
+        if text.lines().any(|line| line.trim() == "---") {
+            return Ok(Credential::from_display(
+                text,
+                &[None, Some(PROJECT_MEMBER_SCHEMA)],
+            )?);
         }
-    };
+        if let Ok(bytes) = hex::decode(text) {
+            return Ok(vec![minicbor::decode(&bytes)?]);
+        }
+    }
+    Ok(vec![minicbor::decode(encoded)?])
+}
 
-    Ok(())
+/// Verify the credentials the way a node verifies the credential it gets from an
+/// authority, and return the data of the first valid one
+async fn verify_credentials(
+    credentials: &[Credential],
+    authorities: &[PublicIdentity],
+    vault: &impl IdentityVault,
+) -> ockam_core::Result<CredentialData<Verified>> {
+    let mut result = Err(IdentityError::InvalidCredentialFormat.into());
+    for credential in credentials {
+        let data: CredentialData<Unverified> = CredentialData::try_from(credential)?;
+        let issuer = PublicIdentity::find_issuer(credential, authorities)?;
+        result = issuer
+            .verify_credential(credential, data.unverified_subject(), vault)
+            .await;
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
 }
@@ -25,17 +25,20 @@ impl Error {
     pub fn code(&self) -> ExitCode {
         self.code
     }
+
+    /// Description of the error and of its cause, without the version header
+    pub fn message(&self) -> String {
+        match &self.cause {
+            Some(cause) => format!("{}. Caused by: {}", self.description, cause),
+            None => self.description.clone(),
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", Version::short())?;
-        if let Some(cause) = &self.cause {
-            writeln!(f, "{}. Caused by: {}", self.description, cause)?;
-        } else {
-            writeln!(f, "{}", self.description)?;
-        }
-        Ok(())
+        writeln!(f, "{}", self.message())
     }
 }
 
@@ -1,8 +1,10 @@
 use crate::util::node_rpc;
-use crate::CommandGlobalOpts;
+use crate::{CommandGlobalOpts, Result};
+use anyhow::anyhow;
 use clap::Args;
 use ockam::Context;
 use ockam_api::cli_state::{self, VaultConfig};
+use ockam_core::vault::SecretType;
 use ockam_identity::Identity;
 use rand::prelude::random;
 
@@ -14,6 +16,10 @@ pub struct CreateCommand {
     /// Vault name to store the identity key
     #[arg(long)]
     vault: Option<String>,
+
+    /// Type of the identity key: ed25519 or nist-p256
+    #[arg(long, default_value = "ed25519", value_parser = parse_key_type)]
+    key_type: SecretType,
 }
 
 impl CreateCommand {
@@ -41,10 +47,11 @@ async fn run_impl(
         options.state.vaults.default()?
     };
     let vault = vault_state.get().await?;
-    let identity = Identity::create_ext(
+    let identity = Identity::create_ext_with_key_type(
         &ctx,
         &options.state.identities.authenticated_storage().await?,
         &vault,
+        cmd.key_type,
     )
     .await?;
     let identity_config = cli_state::IdentityConfig::new(&identity).await;
@@ -55,3 +62,11 @@ async fn run_impl(
     println!("Identity created: {}", identity.identifier());
     Ok(())
 }
+
+pub(crate) fn parse_key_type(key_type: &str) -> Result<SecretType> {
+    match key_type {
+        "ed25519" => Ok(SecretType::Ed25519),
+        "nist-p256" => Ok(SecretType::NistP256),
+        _ => Err(anyhow!("unsupported identity key type '{key_type}'").into()),
+    }
+}
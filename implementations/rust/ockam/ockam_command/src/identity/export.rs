@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::identity::LongIdentityResponse;
+
+use crate::node::NodeOpts;
+use crate::util::{api, extract_address_value, node_rpc, Rpc};
+use crate::CommandGlobalOpts;
+
+/// Export the public identity of a node
+#[derive(Clone, Debug, Args)]
+pub struct ExportCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// Write the hex encoded public identity to this file instead of stdout
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+impl ExportCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ExportCommand),
+) -> crate::Result<()> {
+    let node_name = extract_address_value(&cmd.node_opts.api_node)?;
+    let mut rpc = Rpc::background(&ctx, &opts, &node_name)?;
+    rpc.request(api::get_public_identity()).await?;
+    let res = rpc.parse_response::<LongIdentityResponse>()?;
+    let encoded = hex::encode(res.identity.as_ref());
+    match cmd.output {
+        Some(path) => {
+            tokio::fs::write(&path, encoded).await?;
+            println!("Public identity written to {}", path.display());
+        }
+        None => println!("{encoded}"),
+    }
+    Ok(())
+}
@@ -1,11 +1,13 @@
 mod create;
 mod default;
 mod delete;
+mod export;
 mod list;
 mod show;
 
-pub(crate) use create::CreateCommand;
+pub(crate) use create::{parse_key_type, CreateCommand};
 pub(crate) use delete::DeleteCommand;
+pub(crate) use export::ExportCommand;
 pub(crate) use list::ListCommand;
 use ockam_api::cli_state::CliState;
 pub(crate) use show::ShowCommand;
@@ -33,6 +35,8 @@ pub enum IdentitySubcommand {
     Default(DefaultCommand),
     /// Delete an identity
     Delete(DeleteCommand),
+    /// Export the public identity of a node
+    Export(ExportCommand),
 }
 
 impl IdentityCommand {
@@ -43,6 +47,7 @@ impl IdentityCommand {
             IdentitySubcommand::List(c) => c.run(options),
             IdentitySubcommand::Delete(c) => c.run(options),
             IdentitySubcommand::Default(c) => c.run(options),
+            IdentitySubcommand::Export(c) => c.run(options),
         }
     }
 }
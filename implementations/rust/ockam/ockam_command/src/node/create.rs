@@ -14,6 +14,7 @@ use std::{
 };
 use tracing::error;
 
+use crate::identity::parse_key_type;
 use crate::project::ProjectInfo;
 use crate::secure_channel::listener::create as secure_channel_listener;
 use crate::service::config::Config;
@@ -34,6 +35,7 @@ use ockam::{Context, TcpTransport};
 use ockam_api::{
     bootstrapped_identities_store::PreTrustedIdentities,
     config::cli::Authority,
+    nodes::models::portal::PortalLimits,
     nodes::models::transport::{TransportMode, TransportType},
     nodes::{
         service::{
@@ -44,6 +46,7 @@ use ockam_api::{
 };
 use ockam_api::{config::cli, nodes::models::transport::CreateTransportJson};
 
+use ockam_core::vault::SecretType;
 use ockam_core::{AllowAll, LOCAL};
 
 /// Create a node
@@ -100,11 +103,48 @@ pub struct CreateCommand {
     #[arg(long = "identity", value_name = "IDENTITY")]
     identity: Option<String>,
 
+    /// Type of the key of the identity created for the node when no identity is given:
+    /// ed25519 or nist-p256
+    #[arg(long, default_value = "ed25519", value_parser = parse_key_type)]
+    pub key_type: SecretType,
+
     #[arg(long = "authority-identity", value_parser = parse_identity_authority)]
     pub authority_identities: Option<Vec<Authority>>,
 
     #[arg(long = "credential", value_name = "CREDENTIAL_NAME")]
     pub credential: Option<String>,
+
+    /// Count how often each condition of the node's access control policies is met
+    #[arg(long)]
+    pub abac_statistics: bool,
+
+    /// Directory of authority identity files loaded when the node starts
+    #[arg(long, value_name = "DIR")]
+    pub authorities_dir: Option<PathBuf>,
+
+    /// Identity used to get a credential when the requested identity doesn't exist
+    #[arg(long, value_name = "IDENTITY")]
+    pub fallback_identity: Option<String>,
+
+    /// File the node state is saved to, and restored from when the node starts
+    #[arg(long, value_name = "PATH")]
+    pub snapshot_path: Option<PathBuf>,
+
+    /// Check that the node's authorities can be reached when the node starts
+    #[arg(long)]
+    pub credential_self_test: bool,
+
+    /// Maximum number of inlets of the node
+    #[arg(long)]
+    pub max_inlets: Option<u32>,
+
+    /// Maximum number of outlets of the node
+    #[arg(long)]
+    pub max_outlets: Option<u32>,
+
+    /// Number of recent TCP connection errors kept by the node
+    #[arg(long)]
+    pub max_connection_errors: Option<usize>,
 }
 
 impl Default for CreateCommand {
@@ -119,11 +159,20 @@ impl Default for CreateCommand {
             project: None,
             vault: None,
             identity: None,
+            key_type: SecretType::Ed25519,
             trusted_identities: None,
             trusted_identities_file: None,
             reload_from_trusted_identities_file: None,
             authority_identities: None,
             credential: None,
+            abac_statistics: false,
+            authorities_dir: None,
+            fallback_identity: None,
+            snapshot_path: None,
+            credential_self_test: false,
+            max_inlets: None,
+            max_outlets: None,
+            max_connection_errors: None,
         }
     }
 }
@@ -222,6 +271,7 @@ async fn run_foreground_node(
             &node_name,
             cmd.vault.as_ref(),
             cmd.identity.as_ref(),
+            cmd.key_type,
         )
         .await?;
     }
@@ -291,20 +341,43 @@ async fn run_foreground_node(
         None => None,
     };
 
+    let mut general_options = NodeManagerGeneralOptions::new(
+        opts.state.clone(),
+        cmd.node_name.clone(),
+        cmd.launch_config.is_some(),
+        pre_trusted_identities,
+    );
+    if cmd.abac_statistics {
+        general_options = general_options.with_abac_statistics();
+    }
+    if let Some(name) = cmd.fallback_identity {
+        general_options = general_options.with_fallback_identity(name);
+    }
+    if let Some(path) = cmd.snapshot_path {
+        general_options = general_options.with_snapshot_path(path);
+    }
+    if cmd.max_inlets.is_some() || cmd.max_outlets.is_some() {
+        general_options =
+            general_options.with_portal_limits(PortalLimits::new(cmd.max_inlets, cmd.max_outlets));
+    }
+    if let Some(max) = cmd.max_connection_errors {
+        general_options = general_options.with_max_connection_errors(max);
+    }
+
+    let authorities = cfg.authorities(&node_name)?.snapshot();
+    let mut projects_options =
+        NodeManagerProjectsOptions::new(Some(&authorities), project_id, projects, credential);
+    if let Some(dir) = cmd.authorities_dir {
+        projects_options = projects_options.with_authorities_dir(dir);
+    }
+    if cmd.credential_self_test {
+        projects_options = projects_options.with_credential_self_test();
+    }
+
     let node_man = NodeManager::create(
         &ctx,
-        NodeManagerGeneralOptions::new(
-            opts.state.clone(),
-            cmd.node_name.clone(),
-            cmd.launch_config.is_some(),
-            pre_trusted_identities,
-        ),
-        NodeManagerProjectsOptions::new(
-            Some(&cfg.authorities(&node_name)?.snapshot()),
-            project_id,
-            projects,
-            credential,
-        ),
+        general_options,
+        projects_options,
         NodeManagerTransportOptions::new(
             (
                 TransportType::Tcp,
@@ -478,6 +551,7 @@ async fn spawn_background_node(
         &node_name,
         cmd.vault.as_ref(),
         cmd.identity.as_ref(),
+        cmd.key_type,
     )
     .await?;
 
@@ -497,6 +571,14 @@ async fn spawn_background_node(
             .map(|config| serde_json::to_string(config).unwrap()),
         cmd.authority_identities.as_ref(),
         cmd.credential.as_ref(),
+        cmd.abac_statistics,
+        cmd.authorities_dir.as_ref(),
+        cmd.fallback_identity.as_ref(),
+        cmd.snapshot_path.as_ref(),
+        cmd.credential_self_test,
+        cmd.max_inlets,
+        cmd.max_outlets,
+        cmd.max_connection_errors,
     )?;
 
     Ok(())
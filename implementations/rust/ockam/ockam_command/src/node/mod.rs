@@ -9,6 +9,7 @@ use ockam_api::cli_state::CliState;
 use show::ShowCommand;
 use start::StartCommand;
 use stop::StopCommand;
+use version::VersionCommand;
 
 use crate::{help, CommandGlobalOpts};
 
@@ -21,6 +22,7 @@ mod show;
 mod start;
 mod stop;
 pub mod util;
+mod version;
 
 const HELP_DETAIL: &str = include_str!("../constants/node/help_detail.txt");
 
@@ -53,6 +55,8 @@ pub enum NodeSubcommand {
     Stop(StopCommand),
     #[command(display_order = 800)]
     Default(DefaultCommand),
+    #[command(display_order = 800)]
+    Version(VersionCommand),
 }
 
 impl NodeCommand {
@@ -66,6 +70,7 @@ impl NodeCommand {
             NodeSubcommand::Stop(c) => c.run(options),
             NodeSubcommand::Logs(c) => c.run(options),
             NodeSubcommand::Default(c) => c.run(options),
+            NodeSubcommand::Version(c) => c.run(options),
         }
     }
 }
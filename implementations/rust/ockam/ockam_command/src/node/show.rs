@@ -60,6 +60,7 @@ fn print_node_info(
     default_id: Option<&str>,
     services: Option<&ServiceList>,
     tcp_listeners: Option<&TransportList>,
+    tcp_connections: Option<&TransportList>,
     secure_channel_listeners: Option<&Vec<String>>,
     inlets_outlets: Option<(&InletList, &OutletList)>,
 ) {
@@ -106,6 +107,18 @@ fn print_node_info(
         }
     }
 
+    if let Some(connections) = tcp_connections.and_then(|list| list.connections.as_ref()) {
+        println!("  TCP Connections:");
+        for e in connections {
+            println!("    Connection:");
+            println!("      Receiver: {}", e.receiver_addr);
+            println!("      Peer: {}", e.peer);
+            if let Some(session_id) = &e.session_id {
+                println!("      Session: {session_id}");
+            }
+        }
+    }
+
     if let Some(list) = secure_channel_listeners {
         println!("  Secure Channel Listeners:");
         for e in list {
@@ -166,7 +179,7 @@ pub async fn print_query_status(
                 .map(|listener| listener.addr.port())
         });
         print_node_info(
-            node_port, node_name, is_default, false, None, None, None, None, None,
+            node_port, node_name, is_default, false, None, None, None, None, None, None,
         );
     } else {
         // Get short id for the node
@@ -185,6 +198,11 @@ pub async fn print_query_status(
         rpc.request(api::list_tcp_listeners()).await?;
         let tcp_listeners = rpc.parse_response::<TransportList>()?;
 
+        // Get list of TCP connections for node
+        let mut rpc = rpc.clone();
+        rpc.request(api::list_tcp_connections()).await?;
+        let tcp_connections = rpc.parse_response::<TransportList>()?;
+
         // Get list of Secure Channel Listeners
         let mut rpc = rpc.clone();
         rpc.request(api::list_secure_channel_listener()).await?;
@@ -216,6 +234,7 @@ pub async fn print_query_status(
             Some(&default_id),
             Some(&services),
             Some(&tcp_listeners),
+            Some(&tcp_connections),
             Some(&secure_channel_listeners),
             Some((&inlets, &outlets)),
         );
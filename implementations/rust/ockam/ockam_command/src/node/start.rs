@@ -62,6 +62,14 @@ async fn run_impl(
         None,
         None, // No launch config available
         None,
+        false, // No ABAC statistics
+        None,  // No authorities directory
+        None,  // No fallback identity
+        None,  // No snapshot path
+        false, // No credential self-test
+        None,  // No inlet limit
+        None,  // No outlet limit
+        None,  // Default number of connection errors kept
     )?;
 
     // Print node status
@@ -11,11 +11,13 @@ use ockam::identity::{Identity, PublicIdentity};
 use ockam::{Context, TcpListenerTrustOptions, TcpTransport};
 use ockam_api::cli_state;
 use ockam_api::config::cli::{self, Authority};
+use ockam_api::nodes::models::portal::PortalLimits;
 use ockam_api::nodes::models::transport::{TransportMode, TransportType};
 use ockam_api::nodes::service::{
     NodeManagerGeneralOptions, NodeManagerProjectsOptions, NodeManagerTransportOptions,
 };
 use ockam_api::nodes::{NodeManager, NodeManagerWorker, NODEMANAGER_ADDR};
+use ockam_core::vault::SecretType;
 use ockam_core::AllowAll;
 use ockam_multiaddr::MultiAddr;
 use ockam_vault::Vault;
@@ -45,7 +47,7 @@ pub async fn start_embedded_node_with_vault_and_identity(
 
     // This node was initially created as a foreground node
     if !cmd.child_process {
-        init_node_state(ctx, opts, &cmd.node_name, vault, identity).await?;
+        init_node_state(ctx, opts, &cmd.node_name, vault, identity, cmd.key_type).await?;
     }
 
     let project_id = if let Some(p) = project_opts {
@@ -74,20 +76,43 @@ pub async fn start_embedded_node_with_vault_and_identity(
 
     let projects = cfg.inner().lookup().projects().collect();
 
+    let mut general_options = NodeManagerGeneralOptions::new(
+        opts.state.clone(),
+        cmd.node_name.clone(),
+        cmd.launch_config.is_some(),
+        None,
+    );
+    if cmd.abac_statistics {
+        general_options = general_options.with_abac_statistics();
+    }
+    if let Some(name) = cmd.fallback_identity {
+        general_options = general_options.with_fallback_identity(name);
+    }
+    if let Some(path) = cmd.snapshot_path {
+        general_options = general_options.with_snapshot_path(path);
+    }
+    if cmd.max_inlets.is_some() || cmd.max_outlets.is_some() {
+        general_options =
+            general_options.with_portal_limits(PortalLimits::new(cmd.max_inlets, cmd.max_outlets));
+    }
+    if let Some(max) = cmd.max_connection_errors {
+        general_options = general_options.with_max_connection_errors(max);
+    }
+
+    let authorities = cfg.authorities(&cmd.node_name)?.snapshot();
+    let mut projects_options =
+        NodeManagerProjectsOptions::new(Some(&authorities), project_id, projects, None);
+    if let Some(dir) = cmd.authorities_dir {
+        projects_options = projects_options.with_authorities_dir(dir);
+    }
+    if cmd.credential_self_test {
+        projects_options = projects_options.with_credential_self_test();
+    }
+
     let node_man = NodeManager::create(
         ctx,
-        NodeManagerGeneralOptions::new(
-            opts.state.clone(),
-            cmd.node_name.clone(),
-            cmd.launch_config.is_some(),
-            None,
-        ),
-        NodeManagerProjectsOptions::new(
-            Some(&cfg.authorities(&cmd.node_name)?.snapshot()),
-            project_id,
-            projects,
-            None,
-        ),
+        general_options,
+        projects_options,
         NodeManagerTransportOptions::new(
             (
                 TransportType::Tcp,
@@ -153,6 +178,7 @@ pub(super) async fn init_node_state(
     node_name: &str,
     vault: Option<&String>,
     identity: Option<&String>,
+    key_type: SecretType,
 ) -> Result<()> {
     // Get vault specified in the argument, or get the default
     let vault_state = if let Some(v) = vault {
@@ -177,10 +203,11 @@ pub(super) async fn init_node_state(
     } else {
         let vault = vault_state.get().await?;
         let identity_name = hex::encode(random::<[u8; 4]>());
-        let identity = Identity::create_ext(
+        let identity = Identity::create_ext_with_key_type(
             ctx,
             &opts.state.identities.authenticated_storage().await?,
             &vault,
+            key_type,
         )
         .await?;
         let identity_config = cli_state::IdentityConfig::new(&identity).await;
@@ -281,6 +308,14 @@ pub fn spawn_node(
     launch_config: Option<String>,
     authority_identities: Option<&Vec<Authority>>,
     credential: Option<&String>,
+    abac_statistics: bool,
+    authorities_dir: Option<&PathBuf>,
+    fallback_identity: Option<&String>,
+    snapshot_path: Option<&PathBuf>,
+    credential_self_test: bool,
+    max_inlets: Option<u32>,
+    max_outlets: Option<u32>,
+    max_connection_errors: Option<usize>,
 ) -> crate::Result<()> {
     // On systems with non-obvious path setups (or during
     // development) re-executing the current binary is a more
@@ -361,6 +396,52 @@ pub fn spawn_node(
         args.push(credential.to_string());
     }
 
+    if abac_statistics {
+        args.push("--abac-statistics".to_string());
+    }
+
+    if let Some(dir) = authorities_dir {
+        args.push("--authorities-dir".to_string());
+        args.push(
+            dir.to_str()
+                .unwrap_or_else(|| panic!("unsupported path {dir:?}"))
+                .to_string(),
+        );
+    }
+
+    if let Some(fallback_identity) = fallback_identity {
+        args.push("--fallback-identity".to_string());
+        args.push(fallback_identity.to_string());
+    }
+
+    if let Some(path) = snapshot_path {
+        args.push("--snapshot-path".to_string());
+        args.push(
+            path.to_str()
+                .unwrap_or_else(|| panic!("unsupported path {path:?}"))
+                .to_string(),
+        );
+    }
+
+    if credential_self_test {
+        args.push("--credential-self-test".to_string());
+    }
+
+    if let Some(max) = max_inlets {
+        args.push("--max-inlets".to_string());
+        args.push(max.to_string());
+    }
+
+    if let Some(max) = max_outlets {
+        args.push("--max-outlets".to_string());
+        args.push(max.to_string());
+    }
+
+    if let Some(max) = max_connection_errors {
+        args.push("--max-connection-errors".to_string());
+        args.push(max.to_string());
+    }
+
     args.push(name.to_owned());
 
     let child = Command::new(ockam_exe)
@@ -0,0 +1,37 @@
+use crate::node::default_node_name;
+use crate::util::{api, node_rpc, Rpc};
+use crate::{help, node::HELP_DETAIL, CommandGlobalOpts};
+use clap::Args;
+use ockam::Context;
+use ockam_api::nodes::models::base::NodeVersion;
+
+/// Show the version and build information of a node
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(HELP_DETAIL))]
+pub struct VersionCommand {
+    /// Name of the node.
+    #[arg(default_value_t = default_node_name())]
+    node_name: String,
+}
+
+impl VersionCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, VersionCommand),
+) -> crate::Result<()> {
+    let mut rpc = Rpc::background(&ctx, &opts, &cmd.node_name)?;
+    rpc.request(api::query_version()).await?;
+    let res = rpc.parse_response::<NodeVersion>()?;
+
+    println!("Node:     {}", cmd.node_name);
+    println!("Version:  {}", res.version);
+    println!("Git hash: {}", res.git_hash.as_deref().unwrap_or("unknown"));
+    let features: Vec<&str> = res.features.iter().map(|f| f.as_ref()).collect();
+    println!("Features: {}", features.join(", "));
+    Ok(())
+}
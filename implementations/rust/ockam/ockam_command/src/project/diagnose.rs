@@ -0,0 +1,359 @@
+use clap::Args;
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context as _};
+use ockam::identity::credential::{Credential, OneTimeCode};
+use ockam::identity::{IdentityIdentifier, PublicIdentity};
+use ockam::{Context, Route};
+use ockam_api::authenticator::direct::{CredentialIssuerClient, RpcClient, TokenAcceptorClient};
+use ockam_api::config::lookup::ProjectAuthority;
+use ockam_api::nodes::models::transport::{ProbeReachability, ReachabilityStatus};
+use ockam_api::nodes::service::CredentialFlowStage as Stage;
+use ockam_api::DefaultAddress;
+use ockam_core::api::Request;
+use ockam_multiaddr::MultiAddr;
+use ockam_vault::Vault;
+
+use crate::node::util::{delete_embedded_node, start_embedded_node};
+use crate::project::util::create_secure_channel_to_authority;
+use crate::project::ProjectInfo;
+use crate::util::api::{CloudOpts, ProjectOpts};
+use crate::util::{node_rpc, RpcBuilder};
+use crate::CommandGlobalOpts;
+
+/// Diagnose a failing enrollment with a project, stage by stage
+#[derive(Clone, Debug, Args)]
+pub struct DiagnoseCommand {
+    /// Enrollment token to present to the project authority. Without it, the token
+    /// is not presented and the credential is fetched for an already enrolled identity.
+    #[arg(long = "token", value_name = "ENROLLMENT TOKEN", value_parser = OneTimeCode::from_str)]
+    token: Option<OneTimeCode>,
+
+    #[command(flatten)]
+    cloud_opts: CloudOpts,
+
+    #[command(flatten)]
+    project_opts: ProjectOpts,
+}
+
+impl DiagnoseCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self));
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, DiagnoseCommand),
+) -> crate::Result<()> {
+    // The node isn't started with the project, so that a broken project.json is
+    // reported as a failed stage
+    let node_name = start_embedded_node(&ctx, &opts, None).await?;
+    let diagnosis = diagnose(&ctx, &opts, &node_name, &cmd).await;
+    delete_embedded_node(&opts, &node_name).await;
+
+    print!("{diagnosis}");
+    match diagnosis.failure() {
+        Some((stage, _)) => Err(anyhow!("Enrollment failed at stage: {stage}").into()),
+        None => Ok(()),
+    }
+}
+
+/// Walk the enrollment stages, stopping at the first one which fails
+async fn diagnose(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    cmd: &DiagnoseCommand,
+) -> Diagnosis {
+    let mut diagnosis = Diagnosis::default();
+
+    let authority = match diagnosis
+        .run(Stage::Authority, load_authority(opts, &cmd.project_opts))
+        .await
+    {
+        Some(authority) => authority,
+        None => return diagnosis,
+    };
+
+    if diagnosis
+        .run(
+            Stage::Reach,
+            reach_authority(ctx, opts, node_name, authority.address()),
+        )
+        .await
+        .is_none()
+    {
+        return diagnosis;
+    }
+
+    let secure_channel_addr = match diagnosis
+        .run(
+            Stage::SecureChannel,
+            create_secure_channel_to_authority(
+                ctx,
+                opts,
+                node_name,
+                &authority,
+                authority.address(),
+                cmd.cloud_opts.identity.clone(),
+            ),
+        )
+        .await
+    {
+        Some(addr) => addr,
+        None => return diagnosis,
+    };
+
+    match &cmd.token {
+        Some(token) => {
+            if diagnosis
+                .run(
+                    Stage::Enrollment,
+                    present_token(ctx, &secure_channel_addr, token),
+                )
+                .await
+                .is_none()
+            {
+                return diagnosis;
+            }
+        }
+        None => diagnosis.skip(Stage::Enrollment, "no enrollment token given"),
+    }
+
+    let credential = match diagnosis
+        .run(Stage::Fetch, fetch_credential(ctx, &secure_channel_addr))
+        .await
+    {
+        Some(credential) => credential,
+        None => return diagnosis,
+    };
+
+    diagnosis
+        .run(
+            Stage::Verify,
+            verify_credential(ctx, opts, node_name, cmd, &authority, &credential),
+        )
+        .await;
+
+    diagnosis
+}
+
+/// Load the project authority from the project.json file
+async fn load_authority(
+    opts: &CommandGlobalOpts,
+    project_opts: &ProjectOpts,
+) -> crate::Result<ProjectAuthority> {
+    let path = match &project_opts.project_path {
+        Some(p) => p.clone(),
+        None => {
+            opts.state
+                .projects
+                .default()
+                .context("A default project or project parameter is required.")?
+                .path
+        }
+    };
+    let s = tokio::fs::read_to_string(&path)
+        .await
+        .with_context(|| format!("Cannot read {}", path.display()))?;
+    let proj: ProjectInfo = serde_json::from_str(&s)?;
+    let authority =
+        ProjectAuthority::from_raw(&proj.authority_access_route, &proj.authority_identity)
+            .await?
+            .ok_or_else(|| anyhow!("Authority details not configured"))?;
+    Ok(authority)
+}
+
+/// Have the node open a TCP connection to the project authority
+async fn reach_authority(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    addr: &MultiAddr,
+) -> crate::Result<()> {
+    let mut rpc = RpcBuilder::new(ctx, opts, node_name).build();
+    let req = Request::post("/node/reachability").body(ProbeReachability::new(addr, false));
+    rpc.request(req).await?;
+    let status = rpc.parse_response::<ReachabilityStatus>()?;
+    if !status.reachable {
+        let error = status.error.as_deref().unwrap_or("unknown error");
+        return Err(anyhow!("Cannot reach {addr}: {error}").into());
+    }
+    Ok(())
+}
+
+/// Present the enrollment token to the project authority
+async fn present_token(
+    ctx: &Context,
+    secure_channel_addr: &MultiAddr,
+    token: &OneTimeCode,
+) -> crate::Result<()> {
+    let route = service_route(
+        secure_channel_addr,
+        DefaultAddress::ENROLLMENT_TOKEN_ACCEPTOR,
+    )?;
+    let client = TokenAcceptorClient::new(RpcClient::new(route, ctx).await?);
+    client.present_token(token).await?;
+    Ok(())
+}
+
+/// Get a credential from the project authority
+async fn fetch_credential(
+    ctx: &Context,
+    secure_channel_addr: &MultiAddr,
+) -> crate::Result<Credential> {
+    let route = service_route(secure_channel_addr, DefaultAddress::CREDENTIAL_ISSUER)?;
+    let client = CredentialIssuerClient::new(RpcClient::new(route, ctx).await?);
+    Ok(client.credential().await?)
+}
+
+/// Check that the credential was issued to the enrolling identity by the project
+/// authority, and is still valid
+async fn verify_credential(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    cmd: &DiagnoseCommand,
+    authority: &ProjectAuthority,
+    credential: &Credential,
+) -> crate::Result<()> {
+    let subject = enrolling_identifier(ctx, opts, node_name, cmd).await?;
+    let vault = Vault::default();
+    let authority = PublicIdentity::import(authority.identity(), &vault).await?;
+    authority
+        .verify_credential(credential, &subject, &vault)
+        .await?;
+    Ok(())
+}
+
+/// Identifier of the identity the secure channel to the authority is created with:
+/// the identity given on the command line, or else the identity of the node
+async fn enrolling_identifier(
+    ctx: &Context,
+    opts: &CommandGlobalOpts,
+    node_name: &str,
+    cmd: &DiagnoseCommand,
+) -> crate::Result<IdentityIdentifier> {
+    let identifier = match &cmd.cloud_opts.identity {
+        Some(name) => opts.state.identities.get(name)?.config.identifier,
+        None => opts
+            .state
+            .nodes
+            .get(node_name)?
+            .config
+            .identity(ctx)
+            .await?
+            .identifier()
+            .clone(),
+    };
+    Ok(identifier)
+}
+
+/// Route to the `service` of the project authority, through its secure channel
+fn service_route(secure_channel_addr: &MultiAddr, service: &str) -> crate::Result<Route> {
+    let service = MultiAddr::try_from(format!("/service/{service}").as_str())?;
+    let mut addr = secure_channel_addr.clone();
+    for proto in service.iter() {
+        addr.push_back_value(&proto)?;
+    }
+    let route =
+        ockam_api::local_multiaddr_to_route(&addr).context(format!("Invalid MultiAddr {addr}"))?;
+    Ok(route)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Pass,
+    Fail(String),
+    Skip(String),
+}
+
+/// Outcome of each enrollment stage which ran
+#[derive(Debug, Default)]
+struct Diagnosis {
+    outcomes: Vec<(Stage, Outcome)>,
+}
+
+impl Diagnosis {
+    /// Run `stage`, recording whether it passed or the error it failed with
+    async fn run<T>(
+        &mut self,
+        stage: Stage,
+        f: impl Future<Output = crate::Result<T>>,
+    ) -> Option<T> {
+        match f.await {
+            Ok(t) => {
+                self.outcomes.push((stage, Outcome::Pass));
+                Some(t)
+            }
+            Err(e) => {
+                self.outcomes.push((stage, Outcome::Fail(e.message())));
+                None
+            }
+        }
+    }
+
+    /// Record that `stage` was not run, and why
+    fn skip(&mut self, stage: Stage, reason: &str) {
+        self.outcomes
+            .push((stage, Outcome::Skip(reason.to_string())));
+    }
+
+    /// The stage which failed, with its error
+    fn failure(&self) -> Option<(Stage, &str)> {
+        self.outcomes
+            .iter()
+            .find_map(|(stage, outcome)| match outcome {
+                Outcome::Fail(e) => Some((*stage, e.as_str())),
+                _ => None,
+            })
+    }
+}
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for stage in Stage::ALL {
+            match self.outcomes.iter().find(|(s, _)| *s == stage) {
+                Some((_, Outcome::Pass)) => writeln!(f, "PASS  {stage}")?,
+                Some((_, Outcome::Fail(e))) => writeln!(f, "FAIL  {stage}: {e}")?,
+                Some((_, Outcome::Skip(reason))) => writeln!(f, "SKIP  {stage}: {reason}")?,
+                None => writeln!(f, "SKIP  {stage}: an earlier stage failed")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn broken_stage_is_reported_with_its_error() {
+        let mut diagnosis = Diagnosis::default();
+        diagnosis
+            .run(Stage::Authority, async { crate::Result::Ok(()) })
+            .await
+            .unwrap();
+        assert!(diagnosis
+            .run(Stage::Reach, async {
+                crate::Result::<()>::Err(anyhow!("Cannot reach /ip4/127.0.0.1/tcp/1").into())
+            })
+            .await
+            .is_none());
+
+        let (stage, error) = diagnosis.failure().unwrap();
+        assert_eq!(stage, Stage::Reach);
+        assert!(error.contains("Cannot reach /ip4/127.0.0.1/tcp/1"));
+
+        let report = diagnosis.to_string();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), Stage::ALL.len());
+        assert_eq!(lines[0], "PASS  authority configuration");
+        assert_eq!(lines[1], format!("FAIL  authority connection: {error}"));
+        assert!(lines[2..].iter().all(|l| l.starts_with("SKIP")));
+    }
+}
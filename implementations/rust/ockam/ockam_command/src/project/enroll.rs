@@ -1,6 +1,9 @@
 use clap::Args;
 use ockam_api::cloud::ORCHESTRATOR_RESTART_TIMEOUT;
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
 use std::time::Duration;
 
 use anyhow::{anyhow, Context as _};
@@ -31,9 +34,14 @@ pub struct EnrollCommand {
     #[command(flatten)]
     node_opts: NodeOpts,
 
-    #[arg(long, short)]
+    #[arg(long, short, conflicts_with = "devices")]
     member: Option<IdentityIdentifier>,
 
+    /// Issue an enrollment token per device read from this file, or from stdin if `-`.
+    /// Each line describes a device as `<name> [<key>=<value> ...]`
+    #[arg(long, value_name = "FILE", conflicts_with = "attributes")]
+    devices: Option<PathBuf>,
+
     #[arg(long, short, default_value = "/project/default")]
     to: MultiAddr,
 
@@ -60,6 +68,20 @@ impl EnrollCommand {
         }
         Ok(attributes)
     }
+
+    fn devices(&self) -> Result<Option<Box<dyn BufRead + Send>>> {
+        match &self.devices {
+            None => Ok(None),
+            Some(path) if path.as_os_str() == "-" => {
+                Ok(Some(Box::new(BufReader::new(io::stdin()))))
+            }
+            Some(path) => {
+                let file = File::open(path)
+                    .context(format!("failed to open devices file {}", path.display()))?;
+                Ok(Some(Box::new(BufReader::new(file))))
+            }
+        }
+    }
 }
 
 struct Runner {
@@ -131,8 +153,26 @@ impl Runner {
                     .await?
                     .with_timeout(Duration::from_secs(ORCHESTRATOR_RESTART_TIMEOUT)),
             );
-            let token = client.create_token(self.cmd.attributes()?).await?;
-            println!("{}", token.to_string())
+            if let Some(devices) = self.cmd.devices()? {
+                // Emit one `<name> <token>` line per enrolled device, and keep going
+                // when a device fails so that a single bad entry doesn't stall the queue
+                let mut failures = 0;
+                client
+                    .enroll_devices(devices, |line, outcome| match outcome {
+                        Ok((device, token)) => println!("{} {}", device.name(), token.to_string()),
+                        Err(e) => {
+                            failures += 1;
+                            eprintln!("line {line}: failed to enroll device: {e}")
+                        }
+                    })
+                    .await?;
+                if failures > 0 {
+                    eprintln!("{failures} device(s) could not be enrolled");
+                }
+            } else {
+                let token = client.create_token(self.cmd.attributes()?).await?;
+                println!("{}", token.to_string())
+            }
         }
 
         delete_embedded_node(&self.opts, &node_name).await;
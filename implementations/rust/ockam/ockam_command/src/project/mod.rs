@@ -2,6 +2,7 @@ mod addon;
 mod auth;
 mod create;
 mod delete;
+mod diagnose;
 mod enroll;
 mod info;
 mod list;
@@ -17,6 +18,7 @@ pub use crate::credential::get::GetCommand;
 pub use addon::AddonCommand;
 pub use create::CreateCommand;
 pub use delete::DeleteCommand;
+pub use diagnose::DiagnoseCommand;
 pub use enroll::EnrollCommand;
 pub use info::InfoCommand;
 pub use list::ListCommand;
@@ -43,6 +45,7 @@ pub enum ProjectSubcommand {
     Enroll(EnrollCommand),
     Addon(AddonCommand),
     Authenticate(AuthCommand),
+    Diagnose(DiagnoseCommand),
 }
 
 impl ProjectCommand {
@@ -56,6 +59,7 @@ impl ProjectCommand {
             ProjectSubcommand::Information(c) => c.run(options),
             ProjectSubcommand::Addon(c) => c.run(options),
             ProjectSubcommand::Authenticate(c) => c.run(options),
+            ProjectSubcommand::Diagnose(c) => c.run(options),
         }
     }
 }
@@ -9,6 +9,7 @@ use clap::Args;
 use colorful::Colorful;
 use ockam_core::api::Request;
 use serde_json::json;
+use std::time::Duration;
 
 use crate::secure_channel::HELP_DETAIL;
 use crate::util::api::CloudOpts;
@@ -42,6 +43,10 @@ pub struct CreateCommand {
     /// Name of a stored Credential to use within this Secure Channel
     #[arg(short, long)]
     pub credential: Option<String>,
+
+    /// Seconds to wait for the Secure Channel to be created, instead of the node default
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
 }
 
 impl CreateCommand {
@@ -165,16 +170,26 @@ async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, CreateCommand)) -> R
     // Delegate the request to create a secure channel to the from node.
     let mut rpc = RpcBuilder::new(&ctx, &opts, from).tcp(&tcp)?.build();
 
-    let payload = models::secure_channel::CreateSecureChannelRequest::new(
+    let mut payload = models::secure_channel::CreateSecureChannelRequest::new(
         to,
         authorized_identifiers,
         CredentialExchangeMode::Mutual,
         cmd.cloud_opts.identity.clone(),
         cmd.credential.clone(),
     );
+    if let Some(timeout) = cmd.timeout {
+        payload = payload.with_timeout(Duration::from_secs(timeout));
+    }
     let request = Request::post("/node/secure_channel").body(payload);
 
-    rpc.request(request).await?;
+    match cmd.timeout {
+        // Leave the node the time to report that the secure channel creation timed out
+        Some(timeout) => {
+            rpc.request_with_timeout(request, Duration::from_secs(timeout + 5))
+                .await?
+        }
+        None => rpc.request(request).await?,
+    }
     let response = rpc.parse_response::<CreateSecureChannelResponse>()?;
 
     cmd.print_output(from, to, &opts, response);
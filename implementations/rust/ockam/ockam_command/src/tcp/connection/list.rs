@@ -64,5 +64,25 @@ async fn run_impl(
         ]);
 
     print_stdout(table).context("failed to print node status")?;
+
+    if let Some(connections) = &response.connections {
+        let table = connections
+            .iter()
+            .map(|c| {
+                vec![
+                    c.receiver_addr.cell(),
+                    c.peer.cell(),
+                    c.session_id.as_deref().unwrap_or("-").cell(),
+                ]
+            })
+            .collect::<Vec<_>>()
+            .table()
+            .title(vec![
+                "Receiver address".cell().bold(true),
+                "Peer".cell().bold(true),
+                "Session ID".cell().bold(true),
+            ]);
+        print_stdout(table).context("failed to print tcp connections")?;
+    }
     Ok(())
 }
@@ -40,6 +40,14 @@ pub struct CreateCommand {
     /// Assign a name to this inlet.
     #[arg(long, display_order = 900, id = "ALIAS", value_parser = alias_parser)]
     alias: Option<String>,
+
+    /// Attach a free-text description to this inlet.
+    #[arg(long, display_order = 900, id = "DESCRIPTION")]
+    description: Option<String>,
+
+    /// Only check that the outlet route is reachable, without creating the inlet.
+    #[arg(long, display_order = 900)]
+    dry_run: bool,
 }
 
 impl CreateCommand {
@@ -74,12 +82,19 @@ async fn rpc(ctx: Context, (opts, mut cmd): (CommandGlobalOpts, CreateCommand))
         if let Some(a) = cmd.alias {
             payload.set_alias(a)
         }
+        if let Some(d) = cmd.description {
+            payload.set_description(d)
+        }
+        payload.set_dry_run(cmd.dry_run);
         Request::post("/node/inlet").body(payload)
     };
 
     let mut rpc = RpcBuilder::new(&ctx, &opts, &node).tcp(&tcp)?.build();
     rpc.request(req).await?;
-    rpc.parse_response::<InletStatus>()?;
+    let status = rpc.parse_response::<InletStatus>()?;
+    if cmd.dry_run {
+        println!("Outlet route {} is reachable", status.outlet_route);
+    }
 
     Ok(())
 }
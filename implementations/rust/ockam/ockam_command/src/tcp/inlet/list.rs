@@ -40,6 +40,9 @@ async fn run_impl(
                 println!("  To Outlet Address: {ma}");
             }
         }
+        if let Some(description) = &inlet_infor.description {
+            println!("  Description: {description}");
+        }
     }
     Ok(())
 }
@@ -33,6 +33,15 @@ pub struct CreateCommand {
     /// Assign a name to this outlet.
     #[arg(long, display_order = 900, id = "ALIAS", value_parser = alias_parser)]
     alias: Option<String>,
+
+    /// Attach a free-text description to this outlet.
+    #[arg(long, display_order = 903, id = "DESCRIPTION")]
+    description: Option<String>,
+
+    /// Only allow senders having this attribute, in `key=value` format.
+    /// Repeat to require several attributes, or to allow several values of the same attribute.
+    #[arg(long, display_order = 904, value_name = "ATTRIBUTE", value_parser = allow_parser)]
+    allow: Vec<(String, String)>,
 }
 
 impl CreateCommand {
@@ -68,14 +77,33 @@ fn make_api_request<'a>(cmd: CreateCommand) -> crate::Result<RequestBuilder<'a,
     let tcp_addr = cmd.to.to_string();
     let worker_addr = cmd.from;
     let alias = cmd.alias.map(|a| a.into());
-    let payload = CreateOutlet::new(tcp_addr, worker_addr, alias);
+    let mut payload = CreateOutlet::new(tcp_addr, worker_addr, alias);
+    if let Some(d) = cmd.description {
+        payload.set_description(d)
+    }
+    for (key, value) in cmd.allow {
+        payload.add_allowed_attribute(key, value)
+    }
     let request = Request::post("/node/outlet").body(payload);
     Ok(request)
 }
 
-fn alias_parser(arg: &str) -> Result<String> {
+fn allow_parser(arg: &str) -> Result<(String, String)> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(anyhow!("an allowed attribute must be in `key=value` format").into()),
+    }
+}
+
+/// An outlet alias is part of the path of the requests about the outlet, so it
+/// must not contain '/' characters or whitespace
+pub(crate) fn alias_parser(arg: &str) -> Result<String> {
     if arg.contains(':') {
-        Err(anyhow!("an inlet alias must not contain ':' characters").into())
+        Err(anyhow!("an outlet alias must not contain ':' characters").into())
+    } else if arg.contains('/') {
+        Err(anyhow!("an outlet alias must not contain '/' characters").into())
+    } else if arg.contains(char::is_whitespace) {
+        Err(anyhow!("an outlet alias must not contain whitespace").into())
     } else {
         Ok(arg.to_string())
     }
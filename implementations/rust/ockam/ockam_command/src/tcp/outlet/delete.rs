@@ -0,0 +1,39 @@
+use crate::node::NodeOpts;
+use crate::tcp::outlet::create::alias_parser;
+use crate::util::{extract_address_value, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts};
+use clap::Args;
+use ockam_core::api::Request;
+const HELP_DETAIL: &str = include_str!("../../constants/tcp/outlet/help_detail.txt");
+
+/// Delete a TCP Outlet
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(HELP_DETAIL))]
+pub struct DeleteCommand {
+    /// Alias of the outlet
+    #[arg(display_order = 900, id = "ALIAS", value_parser = alias_parser)]
+    alias: String,
+
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl DeleteCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: ockam::Context,
+    (options, command): (CommandGlobalOpts, DeleteCommand),
+) -> crate::Result<()> {
+    let node_name = extract_address_value(&command.node_opts.api_node)?;
+    let mut rpc = Rpc::background(&ctx, &options, &node_name)?;
+    rpc.request(Request::delete(format!("/node/outlet/{}", command.alias)))
+        .await?;
+    rpc.is_ok()?;
+
+    println!("Tcp outlet `{}` successfully deleted", command.alias);
+    Ok(())
+}
@@ -2,10 +2,11 @@ use crate::node::NodeOpts;
 use crate::util::{extract_address_value, node_rpc, Rpc};
 use crate::{help, CommandGlobalOpts};
 use clap::Args;
-use ockam_api::nodes::models::portal::OutletList;
+use ockam_api::nodes::models::portal::{OutletList, OutletStatus};
 use ockam_api::{error::ApiError, route_to_multiaddr};
 use ockam_core::api::Request;
 use ockam_core::route;
+use serde::Serialize;
 const HELP_DETAIL: &str = include_str!("../../constants/tcp/outlet/help_detail.txt");
 
 /// List TCP Outlets
@@ -15,6 +16,10 @@ const HELP_DETAIL: &str = include_str!("../../constants/tcp/outlet/help_detail.t
 pub struct ListCommand {
     #[command(flatten)]
     node_opts: NodeOpts,
+
+    /// Only list the outlets whose alias starts with this prefix
+    #[arg(long, display_order = 900, value_name = "STR")]
+    alias_prefix: Option<String>,
 }
 
 impl ListCommand {
@@ -23,6 +28,39 @@ impl ListCommand {
     }
 }
 
+/// TCP Outlet, as listed by the command
+#[derive(Debug, Clone, Serialize)]
+pub struct OutletInfo {
+    pub alias: String,
+    /// Address of the outlet worker, as a multiaddr
+    pub worker_address: String,
+    pub tcp_addr: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// When the outlet was created, in milliseconds since the Unix epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_connections: Option<u32>,
+}
+
+impl TryFrom<&OutletStatus<'_>> for OutletInfo {
+    type Error = ApiError;
+
+    fn try_from(outlet: &OutletStatus<'_>) -> Result<Self, Self::Error> {
+        let worker_address = route_to_multiaddr(&route![outlet.worker_addr.to_string()])
+            .ok_or_else(|| ApiError::generic("Invalid Outlet Address"))?;
+        Ok(Self {
+            alias: outlet.alias.to_string(),
+            worker_address: worker_address.to_string(),
+            tcp_addr: outlet.tcp_addr.to_string(),
+            description: outlet.description.as_ref().map(|d| d.to_string()),
+            created_at: outlet.created_at,
+            active_connections: outlet.active_connections,
+        })
+    }
+}
+
 async fn run_impl(
     ctx: ockam::Context,
     (options, command): (CommandGlobalOpts, ListCommand),
@@ -32,14 +70,13 @@ async fn run_impl(
     rpc.request(Request::get("/node/outlet")).await?;
     let response = rpc.parse_response::<OutletList>()?;
 
-    println!("Outlet:");
-    for outlet in &response.list {
-        println!("    Alias: {}", outlet.alias);
-        let addr = route_to_multiaddr(&route![outlet.worker_addr.to_string()])
-            .ok_or_else(|| ApiError::generic("Invalid Outlet Address"))?;
-        println!("    From Outlet: {addr}");
-
-        println!("    To TCP: {}", outlet.tcp_addr);
-    }
+    let prefix = command.alias_prefix.as_deref().unwrap_or_default();
+    let outlets = response
+        .list
+        .iter()
+        .filter(|outlet| outlet.alias.starts_with(prefix))
+        .map(OutletInfo::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    rpc.print_response(outlets)?;
     Ok(())
 }
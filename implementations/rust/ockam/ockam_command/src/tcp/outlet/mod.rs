@@ -1,10 +1,14 @@
 mod create;
-mod list;
+mod delete;
+pub(crate) mod list;
+mod show;
 
 use crate::CommandGlobalOpts;
 use clap::{Args, Subcommand};
 use create::CreateCommand;
+use delete::DeleteCommand;
 use list::ListCommand;
+use show::ShowCommand;
 
 /// Manage TCP Outlets
 #[derive(Clone, Debug, Args)]
@@ -16,14 +20,18 @@ pub struct TcpOutletCommand {
 #[derive(Clone, Debug, Subcommand)]
 pub enum TcpOutletSubCommand {
     Create(CreateCommand),
+    Delete(DeleteCommand),
     List(ListCommand),
+    Show(ShowCommand),
 }
 
 impl TcpOutletCommand {
     pub fn run(self, options: CommandGlobalOpts) {
         match self.subcommand {
             TcpOutletSubCommand::Create(c) => c.run(options),
+            TcpOutletSubCommand::Delete(c) => c.run(options),
             TcpOutletSubCommand::List(c) => c.run(options),
+            TcpOutletSubCommand::Show(c) => c.run(options),
         }
     }
 }
@@ -0,0 +1,41 @@
+use crate::node::NodeOpts;
+use crate::tcp::outlet::create::alias_parser;
+use crate::tcp::outlet::list::OutletInfo;
+use crate::util::{extract_address_value, node_rpc, Rpc};
+use crate::{help, CommandGlobalOpts};
+use clap::Args;
+use ockam_api::nodes::models::portal::OutletStatus;
+use ockam_core::api::Request;
+const HELP_DETAIL: &str = include_str!("../../constants/tcp/outlet/help_detail.txt");
+
+/// Show a TCP Outlet
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = help::template(HELP_DETAIL))]
+pub struct ShowCommand {
+    /// Alias of the outlet
+    #[arg(display_order = 900, id = "ALIAS", value_parser = alias_parser)]
+    alias: String,
+
+    #[command(flatten)]
+    node_opts: NodeOpts,
+}
+
+impl ShowCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self))
+    }
+}
+
+async fn run_impl(
+    ctx: ockam::Context,
+    (options, command): (CommandGlobalOpts, ShowCommand),
+) -> crate::Result<()> {
+    let node_name = extract_address_value(&command.node_opts.api_node)?;
+    let mut rpc = Rpc::background(&ctx, &options, &node_name)?;
+    rpc.request(Request::get(format!("/node/outlet/{}", command.alias)))
+        .await?;
+    let response = rpc.parse_response::<OutletStatus>()?;
+
+    rpc.print_response(OutletInfo::try_from(&response)?)?;
+    Ok(())
+}
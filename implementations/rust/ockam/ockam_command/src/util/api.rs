@@ -33,11 +33,26 @@ pub(crate) fn query_status() -> RequestBuilder<'static, ()> {
     Request::get("/node")
 }
 
+/// Construct a request to query the node version and build information
+pub(crate) fn query_version() -> RequestBuilder<'static, ()> {
+    Request::get("/node/version")
+}
+
+/// Construct a request to export the node's public identity
+pub(crate) fn get_public_identity() -> RequestBuilder<'static, ()> {
+    Request::get("/node/identity/public")
+}
+
 /// Construct a request to query node tcp listeners
 pub(crate) fn list_tcp_listeners() -> RequestBuilder<'static, ()> {
     Request::get("/node/tcp/listener")
 }
 
+/// Construct a request to query node tcp connections
+pub(crate) fn list_tcp_connections() -> RequestBuilder<'static, ()> {
+    Request::get("/node/tcp/connection")
+}
+
 /// Construct a request to create node tcp connection
 pub(crate) fn create_tcp_connection(
     cmd: &crate::tcp::connection::CreateCommand,
@@ -178,8 +193,12 @@ pub(crate) mod credentials {
     pub(crate) fn get_credential<'r>(
         overwrite: bool,
         identity_name: Option<String>,
+        authority: Option<IdentityIdentifier>,
     ) -> RequestBuilder<'r, GetCredentialRequest> {
-        let b = GetCredentialRequest::new(overwrite, identity_name);
+        let mut b = GetCredentialRequest::new(overwrite, identity_name);
+        if let Some(authority) = authority {
+            b = b.with_authority(authority);
+        }
         Request::post("/node/credentials/actions/get").body(b)
     }
 }
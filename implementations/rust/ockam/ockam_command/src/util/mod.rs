@@ -9,8 +9,8 @@ use std::{
 use anyhow::{anyhow, Context as _};
 use minicbor::{data::Type, Decode, Decoder, Encode};
 use tracing::{debug, error, trace};
+use tracing_subscriber::fmt;
 use tracing_subscriber::prelude::*;
-use tracing_subscriber::{filter::LevelFilter, fmt, EnvFilter};
 
 pub use config::*;
 use ockam::{Address, Context, NodeBuilder, Route, TcpConnectionTrustOptions, TcpTransport};
@@ -453,25 +453,29 @@ pub fn setup_logging(verbose: u8, no_color: bool) {
         "ockam_vault",
         "ockam_vault_sync_core",
     ];
-    let builder = EnvFilter::builder();
     // If `verbose` is not set, try to read the log level from the OCKAM_LOG env variable.
     // If both `verbose` and OCKAM_LOG are not set, logging will not be enabled.
     // Otherwise, use `verbose` to define the log level.
-    let filter = match verbose {
+    let directives = match verbose {
         0 => match env::var("OCKAM_LOG") {
-            Ok(s) if !s.is_empty() => builder.with_env_var("OCKAM_LOG").from_env_lossy(),
+            Ok(s) if !s.is_empty() => s,
             _ => return,
         },
-        1 => builder
-            .with_default_directive(LevelFilter::INFO.into())
-            .parse_lossy(ockam_crates.map(|c| format!("{c}=info")).join(",")),
-        2 => builder
-            .with_default_directive(LevelFilter::DEBUG.into())
-            .parse_lossy(ockam_crates.map(|c| format!("{c}=debug")).join(",")),
-        _ => builder
-            .with_default_directive(LevelFilter::TRACE.into())
-            .parse_lossy(ockam_crates.map(|c| format!("{c}=trace")).join(",")),
+        1 => format!(
+            "info,{}",
+            ockam_crates.map(|c| format!("{c}=info")).join(",")
+        ),
+        2 => format!(
+            "debug,{}",
+            ockam_crates.map(|c| format!("{c}=debug")).join(",")
+        ),
+        _ => format!(
+            "trace,{}",
+            ockam_crates.map(|c| format!("{c}=trace")).join(",")
+        ),
     };
+    // The filter can be changed at runtime, see `ockam::logging::set_log_level`
+    let filter = ockam::logging::reloadable_filter(directives);
     let fmt = fmt::Layer::default().with_ansi(!no_color);
     let result = tracing_subscriber::registry()
         .with(filter)
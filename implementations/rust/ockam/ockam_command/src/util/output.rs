@@ -5,6 +5,7 @@ use ockam::identity::credential::Credential;
 use ockam_api::cloud::project::Project;
 
 use crate::project::ProjectInfo;
+use crate::tcp::outlet::list::OutletInfo;
 use crate::util::comma_separated;
 use crate::Result;
 use colorful::Colorful;
@@ -122,6 +123,40 @@ impl Output for ProjectInfo<'_> {
     }
 }
 
+impl Output for OutletInfo {
+    fn output(&self) -> Result<String> {
+        let mut w = String::from("Outlet:");
+        write_outlet(&mut w, self)?;
+        Ok(w)
+    }
+}
+
+impl Output for Vec<OutletInfo> {
+    fn output(&self) -> Result<String> {
+        let mut w = String::from("Outlet:");
+        for outlet in self {
+            write_outlet(&mut w, outlet)?;
+        }
+        Ok(w)
+    }
+}
+
+fn write_outlet(w: &mut String, outlet: &OutletInfo) -> Result<()> {
+    write!(w, "\n    Alias: {}", outlet.alias)?;
+    write!(w, "\n    From Outlet: {}", outlet.worker_address)?;
+    write!(w, "\n    To TCP: {}", outlet.tcp_addr)?;
+    if let Some(description) = &outlet.description {
+        write!(w, "\n    Description: {description}")?;
+    }
+    if let Some(created_at) = outlet.created_at {
+        write!(w, "\n    Created At: {created_at} (unix ms)")?;
+    }
+    if let Some(active_connections) = outlet.active_connections {
+        write!(w, "\n    Active Connections: {active_connections}")?;
+    }
+    Ok(())
+}
+
 impl Output for Vec<Project<'_>> {
     fn output(&self) -> Result<String> {
         if self.is_empty() {
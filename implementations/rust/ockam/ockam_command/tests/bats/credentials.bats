@@ -44,3 +44,25 @@ teardown() {
   assert_output --partial "Credential: smart_nyc_cred"
   assert_output --partial "Attributes: {\"application\": \"Smart Factory\", \"city\": \"New York\"}"
 }
+
+@test "credentials - verify against an exported authority identity" {
+  run "$OCKAM" identity create i1
+  assert_success
+  authority=$($OCKAM identity show i1 --full --encoding hex)
+
+  run "$OCKAM" identity create i2
+  assert_success
+  idt2=$($OCKAM identity show i2)
+
+  "$OCKAM" credential issue --as i1 --for "$idt2" --attribute city="New York" --encoding hex >"$OCKAM_HOME/credential"
+
+  run "$OCKAM" credential verify --authority "$authority" --credential "$OCKAM_HOME/credential"
+  assert_success
+  assert_output --partial "Verified Credential"
+  assert_output --partial "city: New York"
+
+  # Change the last character of the signature
+  sed -E 's/0$/1/; t; s/.$/0/' "$OCKAM_HOME/credential" >"$OCKAM_HOME/tampered"
+  run "$OCKAM" credential verify --authority "$authority" --credential "$OCKAM_HOME/tampered"
+  assert_failure
+}
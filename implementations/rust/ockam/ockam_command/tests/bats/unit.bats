@@ -330,6 +330,98 @@ teardown() {
 
 # ===== PORTALS (INLET/OUTLET)
 
+@test "portals - show an outlet by alias" {
+  run --separate-stderr "$OCKAM" node create n1
+  assert_success
+  run --separate-stderr "$OCKAM" tcp-outlet create --at /node/n1 --from /service/outlet --to 127.0.0.1:5000 --alias outlet-1
+  assert_success
+
+  run --separate-stderr "$OCKAM" tcp-outlet show outlet-1 --node n1
+  assert_success
+  assert_output --partial "Alias: outlet-1"
+  assert_output --regexp "From Outlet: .*/service/outlet"
+  assert_output --partial "To TCP: 127.0.0.1:5000"
+  assert_output --regexp "Created At: [0-9]+"
+  assert_output --partial "Active Connections: 0"
+
+  run "$OCKAM" tcp-outlet show unknown --node n1
+  assert_failure
+  assert_output --partial "outlet with alias unknown not found"
+
+  run "$OCKAM" tcp-outlet show "outlet 1" --node n1
+  assert_failure
+  assert_output --partial "must not contain whitespace"
+}
+
+@test "portals - delete an outlet by alias" {
+  run --separate-stderr "$OCKAM" node create n1
+  assert_success
+  run --separate-stderr "$OCKAM" tcp-outlet create --at /node/n1 --from /service/outlet --to 127.0.0.1:5000 --alias outlet-1
+  assert_success
+
+  run --separate-stderr "$OCKAM" tcp-outlet delete outlet-1 --node n1
+  assert_success
+
+  run --separate-stderr "$OCKAM" tcp-outlet list --node n1
+  assert_success
+  refute_output --partial "outlet-1"
+
+  run "$OCKAM" tcp-outlet delete outlet-1 --node n1
+  assert_failure
+  assert_output --partial "outlet with alias outlet-1 not found"
+
+  run "$OCKAM" tcp-outlet delete outlet/1 --node n1
+  assert_failure
+  assert_output --partial "must not contain '/' characters"
+}
+
+@test "portals - list outlets as json" {
+  run --separate-stderr "$OCKAM" node create n1
+  assert_success
+  run --separate-stderr "$OCKAM" tcp-outlet create --at /node/n1 --from /service/outlet --to 127.0.0.1:5000 --alias outlet-1
+  assert_success
+
+  run --separate-stderr "$OCKAM" tcp-outlet list --node n1 --output json
+  assert_success
+  refute_output --partial "Outlet:"
+  run jq -e '.[] | select(.alias == "outlet-1") | .tcp_addr == "127.0.0.1:5000" and (.worker_address | endswith("/service/outlet"))' <<<"$output"
+  assert_success
+}
+
+@test "portals - create an outlet allowing senders by attribute" {
+  run --separate-stderr "$OCKAM" node create n1
+  assert_success
+
+  run --separate-stderr "$OCKAM" tcp-outlet create --at /node/n1 --from /service/outlet --to 127.0.0.1:5000 --allow role=db --allow role=admin
+  assert_success
+  assert_output --regexp "/service/outlet"
+
+  run "$OCKAM" tcp-outlet create --at /node/n1 --from /service/outlet-2 --to 127.0.0.1:5000 --allow role
+  assert_failure
+  assert_output --partial "key=value"
+}
+
+@test "portals - list outlets filtered by alias prefix" {
+  run --separate-stderr "$OCKAM" node create n1
+  assert_success
+  run --separate-stderr "$OCKAM" tcp-outlet create --at /node/n1 --from /service/db-1 --to 127.0.0.1:5000 --alias db-1
+  assert_success
+  run --separate-stderr "$OCKAM" tcp-outlet create --at /node/n1 --from /service/db-2 --to 127.0.0.1:5001 --alias db-2
+  assert_success
+  run --separate-stderr "$OCKAM" tcp-outlet create --at /node/n1 --from /service/web --to 127.0.0.1:5002 --alias web
+  assert_success
+
+  run --separate-stderr "$OCKAM" tcp-outlet list --node n1 --alias-prefix db-
+  assert_success
+  assert_output --partial "Alias: db-1"
+  assert_output --partial "Alias: db-2"
+  refute_output --partial "Alias: web"
+
+  run --separate-stderr "$OCKAM" tcp-outlet list --node n1 --alias-prefix unknown
+  assert_success
+  assert_output "Outlet:"
+}
+
 @test "portals - create an inlet/outlet pair and move tcp traffic through it" {
   port=6000
   run --separate-stderr "$OCKAM" node create n1
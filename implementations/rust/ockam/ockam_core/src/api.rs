@@ -210,6 +210,7 @@ pub enum Status {
     #[n(404)] NotFound,
     #[n(409)] Conflict,
     #[n(405)] MethodNotAllowed,
+    #[n(408)] RequestTimeout,
     #[n(500)] InternalServerError,
     #[n(501)] NotImplemented
 }
@@ -224,6 +225,7 @@ impl Display for Status {
             Status::NotFound => "404 NotFound",
             Status::Conflict => "409 Conflict",
             Status::MethodNotAllowed => "405 MethodNotAllowed",
+            Status::RequestTimeout => "408 RequestTimeout",
             Status::InternalServerError => "500 InternalServerError",
             Status::NotImplemented => "501 NotImplemented",
         })
@@ -352,6 +354,10 @@ impl Response {
         Response::builder(re, Status::Forbidden)
     }
 
+    pub fn request_timeout(re: Id) -> ResponseBuilder {
+        Response::builder(re, Status::RequestTimeout)
+    }
+
     pub fn internal_error(re: Id) -> ResponseBuilder {
         Response::builder(re, Status::InternalServerError)
     }
@@ -392,6 +398,8 @@ pub struct Error<'a> {
     #[n(2)] method: Option<Method>,
     /// The actual error message.
     #[b(3)] message: Option<Cow<'a, str>>,
+    /// Code identifying the error, if the service defines one.
+    #[n(4)] code: Option<u16>,
 }
 
 impl<'a> Error<'a> {
@@ -402,6 +410,7 @@ impl<'a> Error<'a> {
             method: None,
             path: Some(path.into()),
             message: None,
+            code: None,
         }
     }
 
@@ -419,6 +428,11 @@ impl<'a> Error<'a> {
         self
     }
 
+    pub fn with_code(mut self, code: u16) -> Self {
+        self.code = Some(code);
+        self
+    }
+
     pub fn path(&self) -> Option<&str> {
         self.path.as_deref()
     }
@@ -430,6 +444,10 @@ impl<'a> Error<'a> {
     pub fn message(&self) -> Option<&str> {
         self.message.as_deref()
     }
+
+    pub fn code(&self) -> Option<u16> {
+        self.code
+    }
 }
 
 /// Path segments, i.e. '/'-separated string slices.
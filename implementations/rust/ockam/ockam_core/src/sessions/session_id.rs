@@ -1,6 +1,7 @@
 use crate::compat::rand::distributions::{Distribution, Standard};
 use crate::compat::rand::Rng;
 use crate::compat::string::String;
+use core::fmt;
 use serde::{Deserialize, Serialize};
 
 /// Unique random identifier of a session
@@ -13,3 +14,9 @@ impl Distribution<SessionId> for Standard {
         SessionId(hex::encode(address))
     }
 }
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
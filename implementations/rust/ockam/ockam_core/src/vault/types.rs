@@ -31,6 +31,11 @@ pub const AES128_SECRET_LENGTH_U32: u32 = 16;
 /// AES128 private key length.
 pub const AES128_SECRET_LENGTH_USIZE: usize = 16;
 
+/// NIST P-256 private key length.
+pub const NIST_P256_SECRET_LENGTH_U32: u32 = 32;
+/// NIST P-256 private key length.
+pub const NIST_P256_SECRET_LENGTH_USIZE: usize = 32;
+
 cfg_if! {
     if #[cfg(not(feature = "alloc"))] {
         /// Secret Key Vector. The maximum size is 32 bytes.
@@ -1,12 +1,13 @@
 use crate::alloc::borrow::ToOwned;
 use crate::alloc::string::ToString;
 use crate::credential::Timestamp;
-use crate::{IdentityIdentifier, IdentityStateConst};
+use crate::{IdentityError, IdentityIdentifier, IdentityStateConst};
 use minicbor::{Decode, Encode};
 use ockam_core::async_trait;
 use ockam_core::compat::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{AsyncTryClone, Result};
+use tracing::{info, warn};
 
 /// Storage for Authenticated data
 #[async_trait]
@@ -99,6 +100,9 @@ pub trait IdentityAttributeStorageWriter: AsyncTryClone + Send + Sync + 'static
         identity: &IdentityIdentifier,
         entry: AttributesEntry,
     ) -> Result<()>;
+
+    /// Remove all the attributes associated with the given identity identifier
+    async fn delete(&self, identity: &IdentityIdentifier) -> Result<()>;
 }
 
 /// Trait implementing read/write access to an AuthenticatedIdentities table
@@ -108,6 +112,24 @@ pub trait IdentityAttributeStorage:
 {
 }
 
+/// What to do when attributes are stored for an identity which already has a
+/// different value for one of them, e.g. when a peer presents an updated role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeConflictPolicy {
+    /// The new entry replaces the stored one
+    LastWriteWins,
+    /// The stored entry is kept and the new one is dropped
+    KeepExisting,
+    /// The new entry is rejected with [`IdentityError::AttributeConflict`]
+    RejectOnChange,
+}
+
+impl Default for AttributeConflictPolicy {
+    fn default() -> Self {
+        Self::LastWriteWins
+    }
+}
+
 /// Implementation of `IdentityAttributeStorage` trait based on an underling
 /// `AuthenticatedStorage` store.
 #[derive(AsyncTryClone)]
@@ -115,16 +137,36 @@ pub trait IdentityAttributeStorage:
 #[derive(Debug)]
 pub struct AuthenticatedAttributeStorage<S: AuthenticatedStorage> {
     storage: S,
+    conflict_policy: AttributeConflictPolicy,
 }
 
 impl<S: AuthenticatedStorage> AuthenticatedAttributeStorage<S> {
     /// Constructor. `AttributesEntry` entries are serialized and stored on the underling
     /// storage given.
     pub fn new(storage: S) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            conflict_policy: AttributeConflictPolicy::default(),
+        }
+    }
+
+    /// Set what to do when an entry changes the value of a stored attribute
+    pub fn with_conflict_policy(mut self, conflict_policy: AttributeConflictPolicy) -> Self {
+        self.conflict_policy = conflict_policy;
+        self
     }
 }
 
+/// Names of the attributes of `stored` which `entry` sets to a different value
+fn conflicting_attributes(stored: &AttributesEntry, entry: &AttributesEntry) -> Vec<String> {
+    entry
+        .attrs()
+        .iter()
+        .filter(|(k, v)| matches!(stored.attrs().get(*k), Some(s) if s != *v))
+        .map(|(k, _)| k.clone())
+        .collect()
+}
+
 impl<S: AuthenticatedStorage> IdentityAttributeStorage for AuthenticatedAttributeStorage<S> {}
 
 #[async_trait]
@@ -182,6 +224,25 @@ impl<S: AuthenticatedStorage> IdentityAttributeStorageWriter for AuthenticatedAt
         sender: &IdentityIdentifier,
         entry: AttributesEntry,
     ) -> Result<()> {
+        if let Some(stored) = self.get_attributes(sender).await? {
+            let conflicts = conflicting_attributes(&stored, &entry);
+            if !conflicts.is_empty() {
+                match self.conflict_policy {
+                    AttributeConflictPolicy::LastWriteWins => {
+                        info!(%sender, ?conflicts, "replacing the stored attributes");
+                    }
+                    AttributeConflictPolicy::KeepExisting => {
+                        warn!(%sender, ?conflicts, "keeping the stored attributes");
+                        return Ok(());
+                    }
+                    AttributeConflictPolicy::RejectOnChange => {
+                        warn!(%sender, ?conflicts, "rejecting attributes changing stored values");
+                        return Err(IdentityError::AttributeConflict.into());
+                    }
+                }
+            }
+        }
+
         // TODO: Implement expiration mechanism in Storage
         let entry = minicbor::to_vec(&entry)?;
 
@@ -195,6 +256,55 @@ impl<S: AuthenticatedStorage> IdentityAttributeStorageWriter for AuthenticatedAt
 
         Ok(())
     }
+
+    async fn delete(&self, identity: &IdentityIdentifier) -> Result<()> {
+        self.storage
+            .del(&identity.to_string(), IdentityStateConst::ATTRIBUTES_KEY)
+            .await
+    }
+}
+
+/// Serializable copy of the entries of an `IdentityAttributeStorage`, used to back up
+/// the learned attributes of a node or to migrate them to another node.
+///
+/// Entries keep their creation date, expiration date and attesting identity.
+#[derive(Debug, Clone, Encode, Decode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct AttributesSnapshot {
+    #[n(1)] entries: Vec<(IdentityIdentifier, AttributesEntry)>,
+}
+
+impl AttributesSnapshot {
+    /// Copy all the (non-expired) entries of the given storage
+    pub async fn export(storage: &impl IdentityAttributeStorageReader) -> Result<Self> {
+        Ok(Self {
+            entries: storage.list().await?,
+        })
+    }
+
+    /// Write the entries into the given storage, overriding existing entries of the same
+    /// identities. Entries which expired since the export are skipped.
+    /// Return the number of imported entries.
+    pub async fn import(&self, storage: &impl IdentityAttributeStorageWriter) -> Result<usize> {
+        let now = Timestamp::now().ok_or_else(|| {
+            ockam_core::Error::new(Origin::Core, Kind::Internal, "invalid system time")
+        })?;
+        let mut imported = 0;
+        for (identifier, entry) in &self.entries {
+            if matches!(entry.expires(), Some(exp) if exp <= now) {
+                continue;
+            }
+            storage.put_attributes(identifier, entry.clone()).await?;
+            imported += 1;
+        }
+        Ok(imported)
+    }
+
+    /// The exported entries
+    pub fn entries(&self) -> &[(IdentityIdentifier, AttributesEntry)] {
+        &self.entries
+    }
 }
 
 /// In-memory impl
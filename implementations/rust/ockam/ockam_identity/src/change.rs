@@ -1,4 +1,4 @@
-use crate::ChangeIdentifier;
+use crate::{ChangeIdentifier, KeyAttributes};
 use core::fmt;
 use ockam_core::compat::vec::Vec;
 use ockam_core::vault::PublicKey;
@@ -37,9 +37,13 @@ impl IdentityChange {
     }
 
     pub(crate) fn label(&self) -> &str {
+        self.key_attributes().label()
+    }
+
+    pub(crate) fn key_attributes(&self) -> &KeyAttributes {
         match self {
-            IdentityChange::CreateKey(data) => data.key_attributes().label(),
-            IdentityChange::RotateKey(data) => data.key_attributes().label(),
+            IdentityChange::CreateKey(data) => data.key_attributes(),
+            IdentityChange::RotateKey(data) => data.key_attributes(),
         }
     }
 
@@ -58,19 +58,14 @@ impl fmt::Display for RotateKeyChangeData {
 }
 
 impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
-    /// Rotate key change
-    pub(crate) async fn make_rotate_key_change(
-        &self,
-        key_attributes: KeyAttributes,
-    ) -> Result<IdentitySignedChange> {
+    /// Rotate key change. The new key has the same attributes as the key it replaces.
+    pub(crate) async fn make_rotate_key_change(&self, label: &str) -> Result<IdentitySignedChange> {
         let change_history = self.change_history.read().await;
         let prev_change_id = change_history.get_last_change_id()?;
 
-        let last_change_in_chain = IdentityChangeHistory::find_last_key_change(
-            change_history.as_ref(),
-            key_attributes.label(),
-        )?
-        .clone();
+        let last_change_in_chain =
+            IdentityChangeHistory::find_last_key_change(change_history.as_ref(), label)?.clone();
+        let key_attributes = last_change_in_chain.change().key_attributes().clone();
 
         let last_key_in_chain =
             Self::get_secret_key_from_change(&last_change_in_chain, &self.vault).await?;
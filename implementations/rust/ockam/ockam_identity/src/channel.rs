@@ -32,6 +32,9 @@ use crate::{Identity, IdentityVault};
 use core::time::Duration;
 use ockam_core::{Address, AsyncTryClone, Result, Route};
 
+/// How long the creation of a SecureChannel may take, unless told otherwise
+pub const DEFAULT_SECURE_CHANNEL_TIMEOUT: Duration = Duration::from_secs(120);
+
 impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
     /// Spawns a SecureChannel listener at given `Address` with given [`SecureChannelListenerTrustOptions`]
     pub async fn create_secure_channel_listener(
@@ -65,7 +68,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
             route.into(),
             identity_clone,
             trust_options.into(),
-            Duration::from_secs(120),
+            DEFAULT_SECURE_CHANNEL_TIMEOUT,
         )
         .await
     }
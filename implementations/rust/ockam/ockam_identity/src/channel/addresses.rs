@@ -22,6 +22,8 @@ pub(crate) struct Addresses {
     pub(crate) encryptor: Address,
     // Used to decrypt messages that were received though some channel other than Ockam Routing from the other end of the channel
     pub(crate) encryptor_api: Address,
+    // Used to receive the rekey timer events
+    pub(crate) encryptor_rekey: Address,
 
     // Address where we send notification that secure channel creation is completed
     // Only for initiator
@@ -44,6 +46,8 @@ impl Addresses {
         let encryptor = Address::random_tagged(&format!("SecureChannel.{}.encryptor", role_str));
         let encryptor_api =
             Address::random_tagged(&format!("SecureChannel.{}.encryptor.api", role_str));
+        let encryptor_rekey =
+            Address::random_tagged(&format!("SecureChannel.{}.encryptor.rekey", role_str));
 
         let completion_callback = Address::random_tagged(&format!(
             "SecureChannel.{}.decryptor.completion_callback_address",
@@ -57,6 +61,7 @@ impl Addresses {
             decryptor_backwards_compatibility,
             encryptor,
             encryptor_api,
+            encryptor_rekey,
             completion_callback,
         }
     }
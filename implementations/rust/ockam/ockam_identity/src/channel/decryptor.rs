@@ -1,38 +1,105 @@
-use crate::channel::encryptor::Encryptor;
+use crate::channel::common::SecureChannelVault;
+use crate::channel::encryptor::{Encryptor, NONCES_PER_EPOCH};
 use crate::error::IdentityError;
 use ockam_core::compat::vec::Vec;
-use ockam_core::vault::{KeyId, SymmetricVault};
+use ockam_core::vault::KeyId;
 use ockam_core::Result;
 
-#[derive(Clone)]
-pub(crate) struct Decryptor<V: SymmetricVault> {
+/// Maximum number of epochs the other side can move ahead between two received messages,
+/// which bounds the number of keys derived for a single message
+const MAX_SKIPPED_EPOCHS: u64 = 16;
+
+pub(crate) struct Decryptor<V: SecureChannelVault> {
     key: KeyId,
+    // Epoch of the current key
+    epoch: u64,
+    // Key of the previous epoch, kept for the messages still in flight during a rekey
+    previous_key: Option<KeyId>,
     vault: V,
 }
 
-impl<V: SymmetricVault> Decryptor<V> {
+impl<V: SecureChannelVault> Decryptor<V> {
     /// Restore 12-byte nonce needed for AES GCM from 8 byte that we use for noise
-    fn convert_nonce_from_small(b: &[u8]) -> Result<[u8; 12]> {
+    fn convert_nonce_from_small(b: &[u8]) -> Result<(u64, [u8; 12])> {
         let bytes: [u8; 8] = b.try_into().map_err(|_| IdentityError::InvalidNonce)?;
 
         let nonce = u64::from_be_bytes(bytes);
 
-        Ok(Encryptor::<V>::convert_nonce_from_u64(nonce).1)
+        Ok((nonce, Encryptor::<V>::convert_nonce_from_u64(nonce).1))
     }
 
-    pub async fn decrypt(&self, payload: &[u8]) -> Result<Vec<u8>> {
+    pub async fn decrypt(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
         if payload.len() < 8 {
             return Err(IdentityError::InvalidNonce.into());
         }
 
-        let nonce = Self::convert_nonce_from_small(&payload[..8])?;
+        let (nonce, nonce_bytes) = Self::convert_nonce_from_small(&payload[..8])?;
+        let epoch = nonce / NONCES_PER_EPOCH;
+
+        if epoch == self.epoch {
+            return self
+                .vault
+                .aead_aes_gcm_decrypt(&self.key, &payload[8..], &nonce_bytes, &[])
+                .await;
+        }
+
+        if epoch + 1 == self.epoch {
+            let previous_key = self
+                .previous_key
+                .as_ref()
+                .ok_or(IdentityError::InvalidNonce)?;
+            return self
+                .vault
+                .aead_aes_gcm_decrypt(previous_key, &payload[8..], &nonce_bytes, &[])
+                .await;
+        }
+
+        if epoch < self.epoch || epoch - self.epoch > MAX_SKIPPED_EPOCHS {
+            return Err(IdentityError::InvalidNonce.into());
+        }
+
+        // The other side rekeyed: derive the keys up to its epoch, and only switch to them
+        // once the message is authenticated
+        let mut derived_keys = Vec::new();
+        let mut key = self.key.clone();
+        for _ in self.epoch..epoch {
+            key = Encryptor::<V>::derive_next_key(&self.vault, &key).await?;
+            derived_keys.push(key.clone());
+        }
+
+        let decrypted = self
+            .vault
+            .aead_aes_gcm_decrypt(&key, &payload[8..], &nonce_bytes, &[])
+            .await;
+
+        if decrypted.is_err() {
+            for key in derived_keys {
+                self.vault.secret_destroy(key).await?;
+            }
+            return decrypted;
+        }
+
+        // Switch to the new key, keep the key of the epoch just before it for the messages
+        // still in flight and destroy the others
+        let mut keys: Vec<KeyId> = self.previous_key.take().into_iter().collect();
+        keys.push(core::mem::replace(&mut self.key, key));
+        keys.extend(derived_keys);
+        let _current_key = keys.pop();
+        self.previous_key = keys.pop();
+        for key in keys {
+            self.vault.secret_destroy(key).await?;
+        }
+        self.epoch = epoch;
 
-        self.vault
-            .aead_aes_gcm_decrypt(&self.key, &payload[8..], &nonce, &[])
-            .await
+        decrypted
     }
 
     pub fn new(key: KeyId, vault: V) -> Self {
-        Self { key, vault }
+        Self {
+            key,
+            epoch: 0,
+            previous_key: None,
+            vault,
+        }
     }
 }
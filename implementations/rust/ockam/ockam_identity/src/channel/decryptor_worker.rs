@@ -29,7 +29,7 @@ use ockam_core::{
 use ockam_key_exchange_xx::Initiator as XXInitiator;
 use ockam_key_exchange_xx::Responder as XXResponder;
 use ockam_key_exchange_xx::XXNewKeyExchanger;
-use ockam_node::{Context, WorkerBuilder};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
 use tracing::{debug, info, warn};
 
 pub(crate) struct DecryptorWorker<
@@ -45,6 +45,7 @@ pub(crate) struct DecryptorWorker<
     init_payload: Option<Vec<u8>>,
     identity: Identity<V, S>,
     trust_policy: Arc<dyn TrustPolicy>,
+    rekey_interval: Option<Duration>,
     state_key_exchange: Option<KeyExchange<K>>,
     state_exchange_identity: Option<ExchangeIdentity<V>>,
     state_initialized: Option<Initialized<V>>,
@@ -88,6 +89,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, XXInitiator<V
             init_payload: None,
             identity,
             trust_policy: trust_options.trust_policy,
+            rekey_interval: trust_options.rekey_interval,
             state_key_exchange: Some(KeyExchange { key_exchanger }),
             state_exchange_identity: None,
             state_initialized: None,
@@ -103,7 +105,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, XXInitiator<V
         );
 
         completion_callback_ctx
-            .receive_timeout::<AuthenticationConfirmation>(timeout.as_secs())
+            .receive_duration_timeout::<AuthenticationConfirmation>(timeout)
             .await?;
 
         Ok(addresses.encryptor)
@@ -149,6 +151,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> DecryptorWorker<V, XXResponder<V
             init_payload: Some(body.payload().to_vec()),
             identity,
             trust_policy: trust_options.trust_policy,
+            rekey_interval: trust_options.rekey_interval,
             state_key_exchange: Some(KeyExchange { key_exchanger }),
             state_exchange_identity: None,
             state_initialized: None,
@@ -477,7 +480,7 @@ impl<V: IdentityVault, K: SecureChannelKeyExchanger, S: AuthenticatedStorage>
             });
 
             let next_hop = self.remote_route.next()?.clone();
-            let encryptor = EncryptorWorker::new(
+            let mut encryptor = EncryptorWorker::new(
                 self.role,
                 self.addresses.clone(),
                 self.remote_route.clone(),
@@ -490,7 +493,7 @@ impl<V: IdentityVault, K: SecureChannelKeyExchanger, S: AuthenticatedStorage>
             let main_mailbox = Mailbox::new(
                 self.addresses.encryptor.clone(),
                 Arc::new(LocalSourceOnly),
-                Arc::new(AllowOnwardAddress(next_hop)),
+                Arc::new(AllowOnwardAddress(next_hop.clone())),
             );
             let api_mailbox = Mailbox::new(
                 self.addresses.encryptor_api.clone(),
@@ -498,12 +501,26 @@ impl<V: IdentityVault, K: SecureChannelKeyExchanger, S: AuthenticatedStorage>
                 Arc::new(LocalOnwardOnly),
             );
 
-            WorkerBuilder::with_mailboxes(
-                Mailboxes::new(main_mailbox, vec![api_mailbox]),
-                encryptor,
-            )
-            .start(ctx)
-            .await?;
+            let mut mailboxes = vec![api_mailbox];
+
+            if let Some(rekey_interval) = self.rekey_interval {
+                let rekey_timer = DelayedEvent::create(
+                    ctx,
+                    self.addresses.encryptor_rekey.clone(),
+                    Vec::<u8>::new(),
+                )
+                .await?;
+                mailboxes.push(Mailbox::new(
+                    self.addresses.encryptor_rekey.clone(),
+                    Arc::new(AllowSourceAddress(rekey_timer.address())),
+                    Arc::new(DenyAll),
+                ));
+                encryptor = encryptor.with_rekey_timer(rekey_timer, rekey_interval);
+            }
+
+            WorkerBuilder::with_mailboxes(Mailboxes::new(main_mailbox, mailboxes), encryptor)
+                .start(ctx)
+                .await?;
 
             info!(
                 "Initialized SecureChannel {} at local: {}, remote: {}",
@@ -520,6 +537,7 @@ impl<V: IdentityVault, K: SecureChannelKeyExchanger, S: AuthenticatedStorage>
                 self.role.is_initiator(),
                 self.identity.identifier().clone(),
                 their_identity_id.clone(),
+                next_hop,
             );
             self.identity
                 .secure_channel_registry
@@ -1,15 +1,22 @@
+use crate::channel::common::SecureChannelVault;
 use crate::error::IdentityError;
 use ockam_core::compat::vec::Vec;
-use ockam_core::vault::{KeyId, SymmetricVault};
+use ockam_core::vault::{KeyId, Secret, SecretKey};
 use ockam_core::Result;
 
-pub(crate) struct Encryptor<V: SymmetricVault> {
+/// Number of nonces in a key epoch. The epoch of a nonce is given by its 32 high bits
+/// and tells which key (the initial key, or one of the keys derived from it) was used.
+pub(crate) const NONCES_PER_EPOCH: u64 = 1 << 32;
+
+pub(crate) struct Encryptor<V: SecureChannelVault> {
     key: KeyId,
+    // Epoch of the current key
+    epoch: u64,
     nonce: u64,
     vault: V,
 }
 
-impl<V: SymmetricVault> Encryptor<V> {
+impl<V: SecureChannelVault> Encryptor<V> {
     /// We use u64 nonce since it's convenient to work with it (e.g. increment)
     /// But we use 8-byte be format to send it over to the other side (according to noise spec)
     /// And we use 12-byte be format for encryption, since AES-GCM wants 12 bytes
@@ -22,12 +29,44 @@ impl<V: SymmetricVault> Encryptor<V> {
         (b, n)
     }
 
+    /// Derive the key of the next epoch from the given key, as in the noise REKEY function:
+    /// the new key is the encryption of zeros with the maximum nonce (which is never used
+    /// to encrypt messages)
+    pub(crate) async fn derive_next_key(vault: &V, key: &KeyId) -> Result<KeyId> {
+        let attributes = vault.secret_attributes_get(key).await?;
+        let (_, nonce) = Self::convert_nonce_from_u64(u64::MAX);
+        let new_key = vault
+            .aead_aes_gcm_encrypt(key, &[0u8; 32], &nonce, &[])
+            .await?;
+        let new_key = new_key[..attributes.length() as usize].to_vec();
+
+        vault
+            .secret_import(Secret::Key(SecretKey::new(new_key)), attributes)
+            .await
+    }
+
+    /// Switch to the key of the next epoch, starting with the next message.
+    /// The other side of the channel notices the switch with the nonce of that message
+    /// and derives the same key.
+    pub(crate) fn rekey(&mut self) {
+        self.nonce = (self.epoch + 1).saturating_mul(NONCES_PER_EPOCH);
+    }
+
     pub async fn encrypt(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
         let old_nonce = self.nonce;
         if old_nonce == u64::MAX {
             return Err(IdentityError::NonceOverflow.into());
         }
 
+        // The nonce entered a new epoch (after a rekey or when all the nonces of the
+        // current epoch were used), switch to the next key
+        if old_nonce / NONCES_PER_EPOCH > self.epoch {
+            let key = Self::derive_next_key(&self.vault, &self.key).await?;
+            let old_key = core::mem::replace(&mut self.key, key);
+            self.vault.secret_destroy(old_key).await?;
+            self.epoch += 1;
+        }
+
         self.nonce += 1;
 
         let (small_nonce, nonce) = Self::convert_nonce_from_u64(old_nonce);
@@ -45,6 +84,11 @@ impl<V: SymmetricVault> Encryptor<V> {
     }
 
     pub fn new(key: KeyId, nonce: u64, vault: V) -> Self {
-        Self { key, nonce, vault }
+        Self {
+            key,
+            epoch: nonce / NONCES_PER_EPOCH,
+            nonce,
+            vault,
+        }
     }
 }
@@ -4,10 +4,11 @@ use crate::channel::common::SecureChannelVault;
 use crate::channel::encryptor::Encryptor;
 use crate::channel::Role;
 use crate::error::IdentityError;
-use ockam_core::compat::boxed::Box;
+use core::time::Duration;
+use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::{async_trait, Address, Decodable, Encodable, Route};
 use ockam_core::{Any, Result, Routed, TransportMessage, Worker};
-use ockam_node::Context;
+use ockam_node::{Context, DelayedEvent};
 use tracing::debug;
 
 pub(crate) struct EncryptorWorker<V: SecureChannelVault> {
@@ -16,6 +17,8 @@ pub(crate) struct EncryptorWorker<V: SecureChannelVault> {
     remote_route: Route,
     remote_backwards_compatibility_address: Address,
     encryptor: Encryptor<V>,
+    rekey_timer: Option<(DelayedEvent<Vec<u8>>, Duration)>,
+    rekey_due: bool,
 }
 
 impl<V: SecureChannelVault> EncryptorWorker<V> {
@@ -32,9 +35,46 @@ impl<V: SecureChannelVault> EncryptorWorker<V> {
             remote_route,
             remote_backwards_compatibility_address,
             encryptor,
+            rekey_timer: None,
+            rekey_due: false,
         }
     }
 
+    /// Rekey periodically, the timer sends an event to the rekey address every `rekey_interval`
+    pub fn with_rekey_timer(
+        mut self,
+        rekey_timer: DelayedEvent<Vec<u8>>,
+        rekey_interval: Duration,
+    ) -> Self {
+        self.rekey_timer = Some((rekey_timer, rekey_interval));
+        self
+    }
+
+    async fn schedule_rekey(&mut self) -> Result<()> {
+        if let Some((rekey_timer, rekey_interval)) = &mut self.rekey_timer {
+            rekey_timer.schedule(*rekey_interval).await?;
+        }
+
+        Ok(())
+    }
+
+    /// The key is only switched when the next message is encrypted, so that an idle
+    /// channel doesn't go through epochs the other side never sees
+    async fn rekey_if_due(&mut self) -> Result<()> {
+        if self.rekey_due {
+            debug!(
+                "SecureChannel {} rekeying {}",
+                self.role.str(),
+                &self.addresses.encryptor
+            );
+            self.encryptor.rekey();
+            self.rekey_due = false;
+            self.schedule_rekey().await?;
+        }
+
+        Ok(())
+    }
+
     async fn handle_encrypt_api(
         &mut self,
         ctx: &mut <Self as Worker>::Context,
@@ -51,6 +91,8 @@ impl<V: SecureChannelVault> EncryptorWorker<V> {
         // Decode raw payload binary
         let request = EncryptionRequest::decode(&msg.into_transport_message().payload)?;
 
+        self.rekey_if_due().await?;
+
         // Encrypt the message
         let encrypted_payload = self.encryptor.encrypt(&request.0).await;
 
@@ -95,6 +137,8 @@ impl<V: SecureChannelVault> EncryptorWorker<V> {
             msg.into_transport_message().payload,
         );
 
+        self.rekey_if_due().await?;
+
         // Encrypt the message
         let encrypted_payload = self.encryptor.encrypt(&msg.encode()?).await?;
 
@@ -115,6 +159,10 @@ impl<V: SecureChannelVault> Worker for EncryptorWorker<V> {
     type Message = Any;
     type Context = Context;
 
+    async fn initialize(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        self.schedule_rekey().await
+    }
+
     async fn handle_message(
         &mut self,
         ctx: &mut Self::Context,
@@ -126,6 +174,8 @@ impl<V: SecureChannelVault> Worker for EncryptorWorker<V> {
             self.handle_encrypt(ctx, msg).await?;
         } else if msg_addr == self.addresses.encryptor_api {
             self.handle_encrypt_api(ctx, msg).await?;
+        } else if msg_addr == self.addresses.encryptor_rekey {
+            self.rekey_due = true;
         } else {
             return Err(IdentityError::UnknownChannelMsgDestination.into());
         }
@@ -1,3 +1,4 @@
+use crate::credential::Timestamp;
 use crate::error::IdentityError;
 use crate::IdentityIdentifier;
 use ockam_core::compat::collections::BTreeMap;
@@ -15,10 +16,12 @@ pub struct SecureChannelRegistryEntry {
     is_initiator: bool,
     my_id: IdentityIdentifier,
     their_id: IdentityIdentifier,
+    next_hop: Address,
+    established: Option<Timestamp>,
 }
 
 impl SecureChannelRegistryEntry {
-    /// Create new registry entry
+    /// Create new registry entry, for a channel established now
     pub fn new(
         encryptor_messaging_address: Address,
         encryptor_api_address: Address,
@@ -27,6 +30,7 @@ impl SecureChannelRegistryEntry {
         is_initiator: bool,
         my_id: IdentityIdentifier,
         their_id: IdentityIdentifier,
+        next_hop: Address,
     ) -> Self {
         Self {
             encryptor_messaging_address,
@@ -36,6 +40,8 @@ impl SecureChannelRegistryEntry {
             is_initiator,
             my_id,
             their_id,
+            next_hop,
+            established: Timestamp::now(),
         }
     }
 
@@ -72,6 +78,17 @@ impl SecureChannelRegistryEntry {
     pub fn their_id(&self) -> &IdentityIdentifier {
         &self.their_id
     }
+
+    /// First hop of the route to the other end of the channel, e.g. the
+    /// transport connection the channel runs over
+    pub fn next_hop(&self) -> &Address {
+        &self.next_hop
+    }
+
+    /// When the channel was established, if the current time is available
+    pub fn established(&self) -> Option<Timestamp> {
+        self.established
+    }
 }
 
 /// Registry of all known Secure Channels
@@ -1,4 +1,5 @@
 use crate::{TrustEveryonePolicy, TrustPolicy};
+use core::time::Duration;
 use ockam_core::compat::sync::Arc;
 use ockam_core::sessions::{SessionId, Sessions};
 
@@ -7,6 +8,7 @@ pub struct SecureChannelTrustOptions {
     pub(crate) ciphertext_session: Option<(Sessions, SessionId)>,
     pub(crate) _plaintext_session: Option<(Sessions, SessionId)>,
     pub(crate) trust_policy: Arc<dyn TrustPolicy>,
+    pub(crate) rekey_interval: Option<Duration>,
 }
 
 impl Default for SecureChannelTrustOptions {
@@ -22,6 +24,7 @@ impl SecureChannelTrustOptions {
             ciphertext_session: None,
             _plaintext_session: None,
             trust_policy: Arc::new(TrustEveryonePolicy),
+            rekey_interval: None,
         }
     }
 
@@ -36,12 +39,20 @@ impl SecureChannelTrustOptions {
         self.trust_policy = Arc::new(trust_policy);
         self
     }
+
+    /// Periodically switch to a new encryption key, derived from the current one, without
+    /// interrupting the data flow. A compromised key only exposes the messages of its period.
+    pub fn with_rekey_interval(mut self, rekey_interval: Duration) -> Self {
+        self.rekey_interval = Some(rekey_interval);
+        self
+    }
 }
 
 /// Trust options for a Secure Channel Listener
 pub struct SecureChannelListenerTrustOptions {
     pub(crate) session: Option<(Sessions, SessionId)>,
     pub(crate) trust_policy: Arc<dyn TrustPolicy>,
+    pub(crate) rekey_interval: Option<Duration>,
 }
 
 impl Default for SecureChannelListenerTrustOptions {
@@ -56,6 +67,7 @@ impl SecureChannelListenerTrustOptions {
         Self {
             session: None,
             trust_policy: Arc::new(TrustEveryonePolicy),
+            rekey_interval: None,
         }
     }
 
@@ -71,12 +83,20 @@ impl SecureChannelListenerTrustOptions {
         self
     }
 
+    /// Set the rekey interval of the channels accepted by this listener,
+    /// see [`SecureChannelTrustOptions::with_rekey_interval`]
+    pub fn with_rekey_interval(mut self, rekey_interval: Duration) -> Self {
+        self.rekey_interval = Some(rekey_interval);
+        self
+    }
+
     pub(crate) fn secure_channel_trust_options(
         &self,
         session_id: Option<SessionId>,
     ) -> SecureChannelTrustOptions {
-        let trust_options =
+        let mut trust_options =
             SecureChannelTrustOptions::new().with_trust_policy(self.trust_policy.clone());
+        trust_options.rekey_interval = self.rekey_interval;
 
         match (&self.session, session_id) {
             // Ignore listener_session_id, since we're spawning dedicated decryptor after
@@ -18,6 +18,8 @@ mod trust_everyone_policy;
 pub use trust_everyone_policy::*;
 mod trust_public_key_policy;
 pub use trust_public_key_policy::*;
+mod trust_on_first_use_policy;
+pub use trust_on_first_use_policy::*;
 
 /// Authenticated data of the newly created SecureChannel to perform `TrustPolicy` check
 #[derive(Clone, Serialize, Deserialize)]
@@ -4,6 +4,11 @@ use ockam_core::{
     compat::{boxed::Box, vec::Vec},
     Result,
 };
+#[cfg(feature = "std")]
+use ockam_core::{
+    errcode::{Kind, Origin},
+    Error,
+};
 
 /// `TrustPolicy` based on list of pre-known `IdentityIdentifier`s of the possible participants
 #[derive(Clone)]
@@ -17,6 +22,59 @@ impl TrustMultiIdentifiersPolicy {
         Self { identity_ids }
     }
 
+    /// Trust the identifiers listed in the file at `path`, either one per line or as a
+    /// JSON array of strings. Nobody is trusted if the file is empty.
+    #[cfg(feature = "std")]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            Error::new(
+                Origin::Identity,
+                Kind::Io,
+                format!(
+                    "cannot read the trusted identifiers from {}: {e}",
+                    path.display()
+                ),
+            )
+        })?;
+        let invalid = |message: String| {
+            Error::new(
+                Origin::Identity,
+                Kind::Invalid,
+                format!(
+                    "invalid trusted identifiers in {}: {message}",
+                    path.display()
+                ),
+            )
+        };
+
+        let contents = contents.trim();
+        let entries: Vec<(usize, String)> = if contents.starts_with('[') {
+            serde_json::from_str::<Vec<String>>(contents)
+                .map_err(|e| invalid(e.to_string()))?
+                .into_iter()
+                .enumerate()
+                .map(|(i, entry)| (i + 1, entry))
+                .collect()
+        } else {
+            contents
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| (i + 1, line.trim().trim_matches('"').to_string()))
+                .collect()
+        };
+
+        let identity_ids = entries
+            .into_iter()
+            .map(|(n, entry)| {
+                IdentityIdentifier::try_from(entry.as_str())
+                    .map_err(|_| invalid(format!("entry {n} is not an identifier: {entry:?}")))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::new(identity_ids))
+    }
+
     fn contains(&self, their_id: &IdentityIdentifier) -> bool {
         let mut found = subtle::Choice::from(0);
         for trusted_id in &*self.identity_ids {
@@ -32,3 +90,62 @@ impl TrustPolicy for TrustMultiIdentifiersPolicy {
         Ok(self.contains(trust_info.their_identity_id()))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::TrustMultiIdentifiersPolicy;
+    use crate::{IdentityIdentifier, SecureChannelTrustInfo, TrustPolicy};
+    use ockam_core::errcode::Kind;
+    use std::path::PathBuf;
+
+    /// Write `contents` to the file `name` in the temporary directory
+    fn write_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    async fn is_trusted(policy: &TrustMultiIdentifiersPolicy, id: &IdentityIdentifier) -> bool {
+        policy
+            .check(&SecureChannelTrustInfo::new(id.clone()))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn trusted_identifiers_are_loaded_from_a_file() {
+        let id1 = IdentityIdentifier::from_key_id("a1b2");
+        let id2 = IdentityIdentifier::from_key_id("c3d4");
+        let other = IdentityIdentifier::from_key_id("e5f6");
+
+        let lines = write_file("trusted-lines", &format!("{id1}\n\n  {id2}  \n"));
+        let json = write_file("trusted-json", &format!("[\"{id1}\", \"{id2}\"]"));
+        for path in [lines, json] {
+            let policy = TrustMultiIdentifiersPolicy::from_file(&path).unwrap();
+            std::fs::remove_file(path).unwrap();
+            assert!(is_trusted(&policy, &id1).await);
+            assert!(is_trusted(&policy, &id2).await);
+            assert!(!is_trusted(&policy, &other).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn an_empty_file_trusts_nobody() {
+        let path = write_file("trusted-empty", "\n");
+        let policy = TrustMultiIdentifiersPolicy::from_file(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let id = IdentityIdentifier::from_key_id("a1b2");
+        assert!(!is_trusted(&policy, &id).await);
+    }
+
+    #[test]
+    fn a_malformed_entry_is_reported() {
+        let id = IdentityIdentifier::from_key_id("a1b2");
+        let path = write_file("trusted-malformed", &format!("{id}\nnot-an-identifier\n"));
+        let err = TrustMultiIdentifiersPolicy::from_file(&path).err().unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(err.code().kind, Kind::Invalid);
+        assert!(err.to_string().contains("entry 2"));
+        assert!(err.to_string().contains("not-an-identifier"));
+    }
+}
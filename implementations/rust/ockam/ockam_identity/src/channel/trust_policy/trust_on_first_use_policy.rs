@@ -0,0 +1,98 @@
+use crate::authenticated_storage::AuthenticatedStorage;
+use crate::{
+    IdentityError, IdentityIdentifier, IdentityStateConst, SecureChannelTrustInfo, TrustPolicy,
+};
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::{async_trait, compat::boxed::Box};
+use ockam_core::{AsyncTryClone, Result};
+use ockam_node::compat::asynchronous::Mutex;
+use tracing::warn;
+
+/// Trust on first use `TrustPolicy`: the first `IdentityIdentifier` presented by the logical
+/// peer `peer_name` is accepted and pinned in the given storage, any other `IdentityIdentifier`
+/// presented afterwards for that peer is rejected
+///
+/// The checks of a policy and of its clones are serialized, so that concurrent channels
+/// to the same peer can't pin different identifiers. Clone the policy rather than
+/// creating a new one for each channel to the peer.
+#[derive(AsyncTryClone)]
+#[async_try_clone(crate = "ockam_core")]
+pub struct TrustOnFirstUsePolicy<S: AuthenticatedStorage> {
+    peer_name: String,
+    pin_store: S,
+    pin_lock: Arc<Mutex<()>>,
+}
+
+impl<S: AuthenticatedStorage> TrustOnFirstUsePolicy<S> {
+    /// Constructor
+    pub fn new(peer_name: impl Into<String>, pin_store: S) -> Self {
+        Self {
+            peer_name: peer_name.into(),
+            pin_store,
+            pin_lock: Default::default(),
+        }
+    }
+
+    /// `IdentityIdentifier` pinned for the peer, if it was already contacted
+    pub async fn pinned_identifier(&self) -> Result<Option<IdentityIdentifier>> {
+        match self
+            .pin_store
+            .get(&self.peer_name, IdentityStateConst::TOFU_PIN_KEY)
+            .await?
+        {
+            Some(pin) => {
+                let pin =
+                    core::str::from_utf8(&pin).map_err(|_| IdentityError::InvalidIdentityId)?;
+                Ok(Some(IdentityIdentifier::try_from(pin)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Forget the pinned `IdentityIdentifier`, e.g. after the peer legitimately rotated
+    /// its identity. The next identifier presented for the peer will be pinned.
+    pub async fn unpin(&self) -> Result<()> {
+        self.pin_store
+            .del(&self.peer_name, IdentityStateConst::TOFU_PIN_KEY)
+            .await
+    }
+}
+
+#[async_trait]
+impl<S: AuthenticatedStorage> TrustPolicy for TrustOnFirstUsePolicy<S> {
+    async fn check(&self, trust_info: &SecureChannelTrustInfo) -> Result<bool> {
+        let their_identity_id = trust_info.their_identity_id();
+        let _pinning = self.pin_lock.lock().await;
+        let pinned = match self.pinned_identifier().await? {
+            Some(pinned) => pinned,
+            None => {
+                let pin: Vec<u8> = their_identity_id.to_string().into_bytes();
+                self.pin_store
+                    .set(
+                        &self.peer_name,
+                        IdentityStateConst::TOFU_PIN_KEY.to_string(),
+                        pin,
+                    )
+                    .await?;
+                // A policy which isn't a clone of this one may have pinned another
+                // identifier in the same storage meanwhile, the stored one prevails
+                match self.pinned_identifier().await? {
+                    Some(pinned) => pinned,
+                    None => return Ok(false),
+                }
+            }
+        };
+
+        if &pinned == their_identity_id {
+            Ok(true)
+        } else {
+            warn!(
+                "Peer {} presented {} instead of the pinned {}",
+                self.peer_name, their_identity_id, pinned
+            );
+            Ok(false)
+        }
+    }
+}
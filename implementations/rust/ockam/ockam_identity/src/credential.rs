@@ -2,15 +2,21 @@
 
 mod identity;
 mod public_identity;
+mod vault_retry;
+mod verification_cache;
 mod worker;
 
 pub mod access_control;
 pub mod one_time_code;
+pub mod reconnect;
+pub mod refresh;
 
 use ockam_core::compat::collections::HashMap;
 pub use one_time_code::*;
+pub use vault_retry::*;
+pub use verification_cache::*;
 
-use crate::IdentityIdentifier;
+use crate::{IdentityError, IdentityIdentifier};
 use core::fmt;
 use core::marker::PhantomData;
 use core::time::Duration;
@@ -152,6 +158,110 @@ impl Credential {
     }
 }
 
+#[cfg(feature = "std")]
+impl Credential {
+    /// Rebuild a credential from its display format, see the [`Display`](fmt::Display)
+    /// implementation.
+    ///
+    /// The display format doesn't show the schema of the credential, so a credential is
+    /// rebuilt for each of `schemas`, the signature of at most one of them being valid.
+    /// Attributes which aren't UTF-8 are displayed as `**binary**`, and can't be rebuilt.
+    pub fn from_display(s: &str, schemas: &[Option<SchemaId>]) -> Result<Vec<Credential>> {
+        let invalid = || ockam_core::Error::from(IdentityError::InvalidCredentialFormat);
+        let field = |name: &str| {
+            s.lines()
+                .find_map(|line| line.trim().strip_prefix(name))
+                .map(str::trim)
+                .ok_or_else(invalid)
+        };
+
+        let subject = IdentityIdentifier::try_from(field("Subject:")?)?;
+        let (issuer, issuer_key_label) = field("Issuer:")?.split_once(' ').ok_or_else(invalid)?;
+        let issuer = IdentityIdentifier::try_from(issuer)?;
+        let issuer_key_label = issuer_key_label
+            .strip_prefix('(')
+            .and_then(|label| label.strip_suffix(')'))
+            .ok_or_else(invalid)?;
+        let created = Timestamp(field("Created:")?.parse().map_err(|_| invalid())?);
+        let expires = Timestamp(field("Expires:")?.parse().map_err(|_| invalid())?);
+        let attributes = parse_displayed_attributes(field("Attributes:")?).ok_or_else(invalid)?;
+        let signature = hex::decode(field("Signature:")?).map_err(|_| invalid())?;
+
+        schemas
+            .iter()
+            .map(|schema| {
+                let data = CredentialData {
+                    schema: *schema,
+                    attributes: attributes.clone(),
+                    subject: subject.clone(),
+                    issuer: issuer.clone(),
+                    issuer_key_label: issuer_key_label.into(),
+                    created,
+                    expires,
+                    status: None::<PhantomData<Verified>>,
+                };
+                Ok(Credential::new(minicbor::to_vec(&data)?, signature.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Parse attributes displayed as a debug map of strings, e.g. `{"role": "member"}`
+#[cfg(feature = "std")]
+fn parse_displayed_attributes(s: &str) -> Option<Attributes> {
+    let mut chars = s
+        .strip_prefix('{')?
+        .strip_suffix('}')?
+        .trim()
+        .chars()
+        .peekable();
+    let mut attributes = Attributes::new();
+    while chars.peek().is_some() {
+        let name = parse_debug_str(&mut chars)?;
+        if chars.next()? != ':' {
+            return None;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let value = parse_debug_str(&mut chars)?;
+        attributes.put(&name, value.as_bytes());
+        match chars.next() {
+            Some(',') => while chars.next_if(|c| c.is_whitespace()).is_some() {},
+            Some(_) => return None,
+            None => (),
+        }
+    }
+    Some(attributes)
+}
+
+/// Parse a string displayed with its `Debug` implementation, e.g. `"a \"quoted\" word"`
+#[cfg(feature = "std")]
+fn parse_debug_str(chars: &mut core::iter::Peekable<core::str::Chars>) -> Option<String> {
+    if chars.next()? != '"' {
+        return None;
+    }
+    let mut s = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(s),
+            '\\' => s.push(match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                'u' => {
+                    if chars.next()? != '{' {
+                        return None;
+                    }
+                    let code: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                    char::from_u32(u32::from_str_radix(&code, 16).ok()?)?
+                }
+                c => c,
+            }),
+            c => s.push(c),
+        }
+    }
+}
+
 impl CredentialData<Verified> {
     pub fn schema(&self) -> Option<SchemaId> {
         self.schema
@@ -196,6 +306,12 @@ impl CredentialData<Unverified> {
     pub fn unverified_subject(&self) -> &IdentityIdentifier {
         &self.subject
     }
+    pub fn unverified_created_at(&self) -> Timestamp {
+        self.created
+    }
+    pub fn unverified_expires_at(&self) -> Timestamp {
+        self.expires
+    }
 }
 
 impl TryFrom<&Credential> for CredentialData<Unverified> {
@@ -2,26 +2,68 @@ use crate::alloc::string::ToString;
 use crate::authenticated_storage::{
     AttributesEntry, AuthenticatedStorage, IdentityAttributeStorage,
 };
+use crate::credential::reconnect::{
+    CredentialExchangeReconnect, CredentialExchangeReconnectProcessor,
+};
 use crate::credential::worker::CredentialExchangeWorker;
 use crate::credential::{
-    Credential, CredentialBuilder, CredentialData, Timestamp, Unverified, Verified,
+    is_vault_unavailable, Credential, CredentialBuilder, CredentialData,
+    CredentialVerificationCache, Timestamp, VaultRetry, Verified,
 };
 use crate::{
     Identity, IdentityError, IdentityIdentifier, IdentitySecureChannelLocalInfo,
     IdentityStateConst, IdentityVault, PublicIdentity,
 };
 use core::marker::PhantomData;
-use minicbor::Decoder;
+use minicbor::encode::{self, Write};
+use minicbor::{Decoder, Encode, Encoder};
 use ockam_core::api::{Request, Response, Status};
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::vault::SignatureVec;
-use ockam_core::{Address, AllowAll, AsyncTryClone, Error, Mailboxes, Result, Route};
+use ockam_core::{Address, AllowAll, AsyncTryClone, DenyAll, Error, Mailboxes, Result, Route};
 use ockam_node::api::{request, request_with_local_info};
 
 use ockam_node::WorkerBuilder;
 
+/// Body of a credential presentation: the credential, followed by the names of the
+/// attributes the other party may record when only some of them are disclosed.
+///
+/// Parties unaware of the disclosed attributes decode the credential and ignore them.
+struct CredentialPresentation<'a> {
+    credential: &'a Credential,
+    disclosed: Option<&'a [&'a str]>,
+}
+
+impl<'a, C> Encode<C> for CredentialPresentation<'a> {
+    fn encode<W: Write>(
+        &self,
+        e: &mut Encoder<W>,
+        ctx: &mut C,
+    ) -> Result<(), encode::Error<W::Error>> {
+        self.credential.encode(e, ctx)?;
+        if let Some(disclosed) = self.disclosed {
+            e.encode(disclosed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode the body of a credential presentation, see [`CredentialPresentation`]
+pub(crate) fn decode_presentation(
+    dec: &mut Decoder<'_>,
+) -> Result<(Credential, Option<Vec<String>>)> {
+    let credential = dec.decode()?;
+    let disclosed = if dec.position() < dec.input().len() {
+        Some(dec.decode()?)
+    } else {
+        None
+    };
+    Ok((credential, disclosed))
+}
+
 impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
     pub async fn set_credential(&self, credential: Credential) {
         // TODO: May also verify received credential calling self.verify_self_credential
@@ -32,6 +74,23 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
         self.credential.read().await.clone()
     }
 
+    /// Remove the credential of this identity and return it, if any
+    pub async fn clear_credential(&self) -> Option<Credential> {
+        self.credential.write().await.take()
+    }
+
+    /// Keep the verifications of the credentials presented to this identity in `cache`,
+    /// so that a peer presenting the same credential again isn't verified again
+    pub async fn set_credential_verification_cache(&self, cache: CredentialVerificationCache) {
+        *self.credential_verification_cache.write().await = Some(cache);
+    }
+
+    /// Set how the credential operations of this identity retry the operations of its
+    /// vault when it is temporarily unavailable, see [`VaultRetry`]
+    pub async fn set_vault_retry(&self, retry: VaultRetry) {
+        *self.vault_retry.write().await = retry;
+    }
+
     /// Create a signed credential based on the given values.
     pub async fn issue_credential(&self, builder: CredentialBuilder) -> Result<Credential> {
         let key_label = IdentityStateConst::ROOT_LABEL;
@@ -50,7 +109,10 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
         };
         let bytes = minicbor::to_vec(&dat)?;
 
-        let sig = self.create_signature(&bytes, None).await?;
+        let retry = self.vault_retry.read().await.clone();
+        let sig = retry
+            .run(&self.ctx, || self.create_signature(&bytes, None))
+            .await?;
         Ok(Credential::new(bytes, SignatureVec::from(sig)))
     }
 
@@ -81,20 +143,86 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
         Ok(())
     }
 
+    /// Start a credential exchange worker, like [`Identity::start_credential_exchange_worker`],
+    /// together with a processor re-establishing the given secure channel when it is lost.
+    ///
+    /// Return the address of the processor. It stops with the worker, or when the
+    /// reconnection attempts are exhausted.
+    pub async fn start_credential_exchange_worker_with_reconnect(
+        &self,
+        authorities: Vec<PublicIdentity>,
+        address: impl Into<Address>,
+        present_back: bool,
+        attributes_storage: impl IdentityAttributeStorage,
+        reconnect: CredentialExchangeReconnect,
+    ) -> Result<Address> {
+        let address = address.into();
+        self.start_credential_exchange_worker(
+            authorities,
+            address.clone(),
+            present_back,
+            attributes_storage,
+        )
+        .await?;
+
+        let processor_address = Address::random_tagged("CredentialExchangeReconnectProcessor");
+        let identity = self.async_try_clone().await?;
+        self.ctx
+            .start_processor(
+                processor_address.clone(),
+                CredentialExchangeReconnectProcessor::new(reconnect, identity, address),
+                DenyAll,
+                DenyAll,
+            )
+            .await?;
+
+        Ok(processor_address)
+    }
+
     /// Present credential to other party, route shall use secure channel
     pub async fn present_credential(
         &self,
         route: impl Into<Route>,
         provided_credential: Option<&Credential>,
+    ) -> Result<()> {
+        self.present_credential_impl(route, provided_credential, None)
+            .await
+    }
+
+    /// Present credential to other party, like [`Identity::present_credential`], letting it
+    /// record only the `disclosed` attributes.
+    ///
+    /// The credential is signed as a whole by its authority, so it is still sent complete:
+    /// this limits the attributes the other party stores and uses for access control,
+    /// not the attributes a malicious party can read.
+    pub async fn present_credential_with_attributes(
+        &self,
+        route: impl Into<Route>,
+        provided_credential: Option<&Credential>,
+        disclosed: &[&str],
+    ) -> Result<()> {
+        self.present_credential_impl(route, provided_credential, Some(disclosed))
+            .await
+    }
+
+    async fn present_credential_impl(
+        &self,
+        route: impl Into<Route>,
+        provided_credential: Option<&Credential>,
+        disclosed: Option<&[&str]>,
     ) -> Result<()> {
         let credential = self.get_credential_or_provided(provided_credential).await?;
+        let presentation = CredentialPresentation {
+            credential: &credential,
+            disclosed,
+        };
 
         let buf = request(
             &self.ctx,
             "credential",
             None,
             route.into(),
-            Request::post("actions/present").body(credential),
+            Request::post("actions/present").body(presentation),
         )
         .await?;
 
@@ -117,8 +245,51 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
         authorities: impl IntoIterator<Item = &PublicIdentity>,
         attributes_storage: &impl IdentityAttributeStorage,
         provided_credential: Option<&Credential>,
+    ) -> Result<()> {
+        self.present_credential_mutual_impl(
+            route,
+            authorities,
+            attributes_storage,
+            provided_credential,
+            None,
+        )
+        .await
+    }
+
+    /// Present credential to other party, like [`Identity::present_credential_mutual`],
+    /// letting it record only the `disclosed` attributes.
+    /// See [`Identity::present_credential_with_attributes`].
+    pub async fn present_credential_mutual_with_attributes(
+        &self,
+        route: impl Into<Route>,
+        authorities: impl IntoIterator<Item = &PublicIdentity>,
+        attributes_storage: &impl IdentityAttributeStorage,
+        provided_credential: Option<&Credential>,
+        disclosed: &[&str],
+    ) -> Result<()> {
+        self.present_credential_mutual_impl(
+            route,
+            authorities,
+            attributes_storage,
+            provided_credential,
+            Some(disclosed),
+        )
+        .await
+    }
+
+    async fn present_credential_mutual_impl(
+        &self,
+        route: impl Into<Route>,
+        authorities: impl IntoIterator<Item = &PublicIdentity>,
+        attributes_storage: &impl IdentityAttributeStorage,
+        provided_credential: Option<&Credential>,
+        disclosed: Option<&[&str]>,
     ) -> Result<()> {
         let credential = self.get_credential_or_provided(provided_credential).await?;
+        let presentation = CredentialPresentation {
+            credential: &credential,
+            disclosed,
+        };
 
         let path = "actions/present_mutual";
         let (buf, local_info) = request_with_local_info(
@@ -126,7 +297,7 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
             "credential",
             None,
             route.into(),
-            Request::post(path).body(credential),
+            Request::post(path).body(presentation),
         )
         .await?;
 
@@ -156,39 +327,45 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
 
         let credential: Credential = dec.decode()?;
 
-        self.receive_presented_credential(their_id, credential, authorities, attributes_storage)
-            .await?;
+        self.receive_presented_credential(
+            their_id,
+            credential,
+            None,
+            authorities,
+            attributes_storage,
+        )
+        .await?;
 
         Ok(())
     }
 }
 
 impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
+    /// Verify `credential`, retrying while the vault is unavailable. A vault which is
+    /// still unavailable is reported as such, not as a verification failure
     async fn verify_credential(
+        &self,
         sender: &IdentityIdentifier,
         credential: &Credential,
         authorities: impl IntoIterator<Item = &PublicIdentity>,
-        vault: &impl IdentityVault,
     ) -> Result<CredentialData<Verified>> {
-        let credential_data: CredentialData<Unverified> = match minicbor::decode(&credential.data) {
-            Ok(c) => c,
-            Err(_) => return Err(IdentityError::InvalidCredentialFormat.into()),
-        };
-
-        let issuer = authorities
-            .into_iter()
-            .find(|&x| x.identifier() == &credential_data.issuer);
-        let issuer = match issuer {
-            Some(i) => i,
-            None => return Err(IdentityError::UnknownAuthority.into()),
-        };
-
-        let credential_data = match issuer.verify_credential(credential, sender, vault).await {
-            Ok(d) => d,
-            Err(_) => return Err(IdentityError::CredentialVerificationFailed.into()),
-        };
+        let issuer = PublicIdentity::find_issuer(credential, authorities)?;
 
-        Ok(credential_data)
+        let retry = self.vault_retry.read().await.clone();
+        retry
+            .run(&self.ctx, || async move {
+                issuer
+                    .verify_credential(credential, sender, &self.vault)
+                    .await
+                    .map_err(|e| {
+                        if is_vault_unavailable(&e) {
+                            e
+                        } else {
+                            IdentityError::CredentialVerificationFailed.into()
+                        }
+                    })
+            })
+            .await
     }
 
     pub async fn verify_self_credential(
@@ -196,20 +373,25 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
         credential: &Credential,
         authorities: impl IntoIterator<Item = &PublicIdentity>,
     ) -> Result<()> {
-        let _ = Self::verify_credential(self.identifier(), credential, authorities, &self.vault)
+        let _ = self
+            .verify_credential(self.identifier(), credential, authorities)
             .await?;
         Ok(())
     }
 
+    /// Verify a credential presented by `sender` and store its attributes, or only
+    /// the `disclosed` ones if the sender restricted them
     pub(crate) async fn receive_presented_credential(
         &self,
         sender: IdentityIdentifier,
         credential: Credential,
+        disclosed: Option<Vec<String>>,
         authorities: impl IntoIterator<Item = &PublicIdentity>,
         attributes_storage: &impl IdentityAttributeStorage,
     ) -> Result<()> {
-        let credential_data =
-            Self::verify_credential(&sender, &credential, authorities, &self.vault).await?;
+        let credential_data = self
+            .verify_presented_credential(&sender, &credential, authorities)
+            .await?;
 
         //TODO: review the credential' attributes types.   They are references and has lifetimes,
         //etc,  but in reality this is always just deserizalided (either from wire or from
@@ -218,6 +400,11 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
             .attributes
             .attrs
             .iter()
+            .filter(|(k, _)| {
+                disclosed
+                    .as_ref()
+                    .map_or(true, |disclosed| disclosed.iter().any(|d| d == k))
+            })
             .map(|(k, v)| (k.to_string(), v.to_vec()))
             .collect();
         attributes_storage
@@ -235,6 +422,47 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
         Ok(())
     }
 
+    /// Verify a credential presented by `sender`, unless the credential verification
+    /// cache of this identity, if any, has a verification of it
+    async fn verify_presented_credential<'a>(
+        &self,
+        sender: &IdentityIdentifier,
+        credential: &Credential,
+        authorities: impl IntoIterator<Item = &'a PublicIdentity>,
+    ) -> Result<CredentialData<Verified>> {
+        let cache = self.credential_verification_cache.read().await.clone();
+        let cache = match cache {
+            Some(cache) => cache,
+            None => {
+                return self
+                    .verify_credential(sender, credential, authorities)
+                    .await
+            }
+        };
+
+        let authorities: Vec<&PublicIdentity> = authorities.into_iter().collect();
+        let retry = self.vault_retry.read().await.clone();
+        let fingerprint = retry
+            .run(&self.ctx, || {
+                CredentialVerificationCache::fingerprint(credential, &self.vault)
+            })
+            .await?;
+        if let Some(credential_data) = cache.get(
+            sender,
+            &fingerprint,
+            credential,
+            authorities.iter().copied(),
+        ) {
+            return Ok(credential_data);
+        }
+
+        let credential_data = self
+            .verify_credential(sender, credential, authorities)
+            .await?;
+        cache.insert(sender, fingerprint, &credential_data);
+        Ok(credential_data)
+    }
+
     /// Gets a clone of the identities current credential
     /// or uses the provided credential if one exists
     async fn get_credential_or_provided(
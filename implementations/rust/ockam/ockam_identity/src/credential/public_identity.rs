@@ -1,7 +1,7 @@
 use crate::alloc::borrow::ToOwned;
 use crate::authenticated_storage::IdentityAttributeStorage;
-use crate::credential::{Credential, CredentialData, Timestamp, Verified};
-use crate::PublicIdentity;
+use crate::credential::{Credential, CredentialData, Timestamp, Unverified, Verified};
+use crate::{IdentityError, PublicIdentity};
 use crate::{IdentityIdentifier, IdentityStateConst, IdentityVault};
 use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::{string::String, vec::Vec};
@@ -10,6 +10,22 @@ use ockam_core::vault::Signature;
 use ockam_core::{Error, Result};
 
 impl PublicIdentity {
+    /// Return the identity of `authorities` which issued `credential`, without
+    /// verifying the credential
+    pub fn find_issuer<'a>(
+        credential: &Credential,
+        authorities: impl IntoIterator<Item = &'a PublicIdentity>,
+    ) -> Result<&'a PublicIdentity> {
+        let credential_data: CredentialData<Unverified> = match minicbor::decode(&credential.data) {
+            Ok(c) => c,
+            Err(_) => return Err(IdentityError::InvalidCredentialFormat.into()),
+        };
+        authorities
+            .into_iter()
+            .find(|x| x.identifier() == &credential_data.issuer)
+            .ok_or_else(|| IdentityError::UnknownAuthority.into())
+    }
+
     /// Perform a signature check with the given identity.
     ///
     /// If successful, the credential data are returned.
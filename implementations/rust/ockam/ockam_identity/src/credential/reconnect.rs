@@ -0,0 +1,177 @@
+use crate::authenticated_storage::AuthenticatedStorage;
+use crate::{Identity, IdentityVault};
+use core::time::Duration;
+use ockam_core::compat::{boxed::Box, sync::Arc};
+use ockam_core::{async_trait, Address, Processor, Result, Route};
+use ockam_node::Context;
+use tracing::{debug, info, warn};
+
+/// Re-establishes the secure channel a credential exchange worker is reachable through
+#[async_trait]
+pub trait ChannelReconnector: Send + Sync + 'static {
+    /// Create a new secure channel and return its address
+    async fn reconnect(&self, ctx: &Context) -> Result<Address>;
+}
+
+/// Reconnection options for a credential exchange worker
+///
+/// A new channel doesn't carry the attributes recorded over the lost one: unless
+/// [`CredentialExchangeReconnect::with_presentation`] is set, the other party has to
+/// present its credential again over the new channel.
+#[derive(Clone)]
+pub struct CredentialExchangeReconnect {
+    pub(crate) channel: Address,
+    pub(crate) reconnector: Arc<dyn ChannelReconnector>,
+    pub(crate) check_interval: Duration,
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+    pub(crate) max_retries: u32,
+    pub(crate) presentation_route: Option<Route>,
+}
+
+impl CredentialExchangeReconnect {
+    /// Default interval between two channel liveness checks
+    pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+    /// Default delay before retrying a failed reconnection
+    pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+    /// Default upper bound of the delay between reconnection attempts
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+    /// Default number of consecutive failed reconnections before giving up
+    pub const DEFAULT_MAX_RETRIES: u32 = 10;
+
+    /// Constructor. `channel` is the address of the currently used secure channel
+    pub fn new(channel: impl Into<Address>, reconnector: impl ChannelReconnector) -> Self {
+        Self {
+            channel: channel.into(),
+            reconnector: Arc::new(reconnector),
+            check_interval: Self::DEFAULT_CHECK_INTERVAL,
+            initial_backoff: Self::DEFAULT_INITIAL_BACKOFF,
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+            max_retries: Self::DEFAULT_MAX_RETRIES,
+            presentation_route: None,
+        }
+    }
+
+    /// Set the interval between two channel liveness checks
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// Set the initial and maximum delay between reconnection attempts.
+    /// The delay doubles after each failed attempt.
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff.max(initial_backoff);
+        self
+    }
+
+    /// Set the number of consecutive failed reconnections after which the
+    /// channel is not watched anymore
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Present the credential of the identity over each new channel, to the
+    /// credential exchange worker at `route` on the other side of the channel
+    pub fn with_presentation(mut self, route: impl Into<Route>) -> Self {
+        self.presentation_route = Some(route.into());
+        self
+    }
+}
+
+/// Processor watching the secure channel of a credential exchange worker and
+/// re-establishing it with an exponential backoff when it is lost.
+///
+/// The processor stops with the credential exchange worker, or after too many consecutive
+/// failed reconnections, see [`CredentialExchangeReconnect::with_max_retries`].
+pub(crate) struct CredentialExchangeReconnectProcessor<V: IdentityVault, S: AuthenticatedStorage> {
+    options: CredentialExchangeReconnect,
+    identity: Identity<V, S>,
+    worker: Address,
+    backoff: Duration,
+    failures: u32,
+}
+
+impl<V: IdentityVault, S: AuthenticatedStorage> CredentialExchangeReconnectProcessor<V, S> {
+    pub(crate) fn new(
+        options: CredentialExchangeReconnect,
+        identity: Identity<V, S>,
+        worker: Address,
+    ) -> Self {
+        let backoff = options.initial_backoff;
+        Self {
+            options,
+            identity,
+            worker,
+            backoff,
+            failures: 0,
+        }
+    }
+}
+
+#[async_trait]
+impl<V: IdentityVault, S: AuthenticatedStorage> Processor
+    for CredentialExchangeReconnectProcessor<V, S>
+{
+    type Context = Context;
+
+    async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
+        ctx.sleep(self.options.check_interval).await;
+
+        let workers = ctx.list_workers().await?;
+        if !workers.contains(&self.worker) {
+            debug!(
+                "Credential exchange worker {} was stopped, not watching its channel anymore",
+                self.worker
+            );
+            return Ok(false);
+        }
+        if workers.contains(&self.options.channel) {
+            return Ok(true);
+        }
+
+        warn!(
+            "Secure channel {} used for credential exchange was lost, reconnecting",
+            self.options.channel
+        );
+
+        match self.options.reconnector.reconnect(ctx).await {
+            Ok(channel) => {
+                info!("Secure channel for credential exchange re-established: {channel}");
+                self.options.channel = channel;
+                self.backoff = self.options.initial_backoff;
+                self.failures = 0;
+
+                if let Some(mut presentation_route) = self.options.presentation_route.clone() {
+                    let route: Route = presentation_route
+                        .modify()
+                        .prepend(self.options.channel.clone())
+                        .into();
+                    if let Err(err) = self.identity.present_credential(route, None).await {
+                        warn!("Credential presentation over the new channel failed: {err}");
+                    }
+                }
+            }
+            Err(err) => {
+                self.failures += 1;
+                if self.failures >= self.options.max_retries {
+                    warn!(
+                        "Reconnection failed: {err}, giving up after {} attempts",
+                        self.failures
+                    );
+                    return Ok(false);
+                }
+                debug!(
+                    "Reconnection failed: {err}, retrying in {}ms",
+                    self.backoff.as_millis()
+                );
+                ctx.sleep(self.backoff).await;
+                self.backoff = (self.backoff * 2).min(self.options.max_backoff);
+            }
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,156 @@
+use crate::credential::Timestamp;
+use core::time::Duration;
+use ockam_core::compat::rand::{thread_rng, Rng};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+
+/// When to refresh a credential before it expires
+///
+/// A credential is refreshed once a fraction of its lifetime has elapsed. To avoid
+/// a fleet of nodes issued credentials at the same time all refreshing them at the
+/// same time, the refresh is brought forward by a random delay of up to `jitter`.
+#[derive(Clone, Copy, Debug)]
+pub struct CredentialRefreshSchedule {
+    lifetime_fraction: f64,
+    jitter: Duration,
+}
+
+impl Default for CredentialRefreshSchedule {
+    fn default() -> Self {
+        Self {
+            lifetime_fraction: Self::DEFAULT_LIFETIME_FRACTION,
+            jitter: Self::DEFAULT_JITTER,
+        }
+    }
+}
+
+impl CredentialRefreshSchedule {
+    /// Default fraction of the credential lifetime after which it is refreshed
+    pub const DEFAULT_LIFETIME_FRACTION: f64 = 0.8;
+    /// Default upper bound of the random delay a refresh is brought forward by
+    pub const DEFAULT_JITTER: Duration = Duration::from_secs(5 * 60);
+
+    /// Set the fraction of the credential lifetime after which it is refreshed.
+    /// The fraction must be greater than 0 and at most 1.
+    pub fn with_lifetime_fraction(mut self, lifetime_fraction: f64) -> Result<Self> {
+        if !(lifetime_fraction > 0.0 && lifetime_fraction <= 1.0) {
+            return Err(Error::new(
+                Origin::Identity,
+                Kind::Invalid,
+                format!(
+                    "invalid credential refresh lifetime fraction {}, expected a value in (0, 1]",
+                    lifetime_fraction
+                ),
+            ));
+        }
+        self.lifetime_fraction = lifetime_fraction;
+        Ok(self)
+    }
+
+    /// Set the upper bound of the random delay a refresh is brought forward by.
+    /// A zero jitter refreshes all credentials at the exact same fraction of their lifetime.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Fraction of the credential lifetime after which it is refreshed
+    pub fn lifetime_fraction(&self) -> f64 {
+        self.lifetime_fraction
+    }
+
+    /// Upper bound of the random delay a refresh is brought forward by
+    pub fn jitter(&self) -> Duration {
+        self.jitter
+    }
+
+    /// Return how long to wait, from `now`, before refreshing a credential valid
+    /// from `created_at` to `expires_at`. Each call draws a new random jitter.
+    pub fn refresh_delay(
+        &self,
+        created_at: Timestamp,
+        expires_at: Timestamp,
+        now: Timestamp,
+    ) -> Duration {
+        let lifetime = expires_at.elapsed(created_at).unwrap_or_default();
+        let nominal =
+            Duration::from_secs(created_at.unix_time()) + lifetime.mul_f64(self.lifetime_fraction);
+
+        let jitter_millis = self.jitter.as_millis() as u64;
+        let jitter = if jitter_millis == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_millis(thread_rng().gen_range(0..=jitter_millis))
+        };
+
+        nominal
+            .saturating_sub(jitter)
+            .saturating_sub(Duration::from_secs(now.unix_time()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn refresh_delays_are_spread_across_the_jitter_window() {
+        let jitter = Duration::from_secs(600);
+        let schedule = CredentialRefreshSchedule::default()
+            .with_lifetime_fraction(0.5)
+            .unwrap()
+            .with_jitter(jitter);
+        let created_at = Timestamp(1_000_000);
+        let expires_at = Timestamp(1_000_000 + 3600);
+
+        // The refresh is due after half of the lifetime, at the latest
+        let nominal = Duration::from_secs(1800);
+        let delays: Vec<Duration> = (0..200)
+            .map(|_| schedule.refresh_delay(created_at, expires_at, created_at))
+            .collect();
+
+        assert!(delays
+            .iter()
+            .all(|d| *d <= nominal && *d >= nominal - jitter));
+
+        // Delays cover the whole window rather than bunching together
+        let earliest = delays.iter().min().unwrap();
+        let latest = delays.iter().max().unwrap();
+        assert!(*latest - *earliest >= jitter / 2);
+        let mut buckets = [0; 4];
+        for d in &delays {
+            let offset = (nominal - *d).as_millis() * 4 / (jitter.as_millis() + 1);
+            buckets[offset as usize] += 1;
+        }
+        assert!(buckets.iter().all(|b| *b > 0));
+    }
+
+    #[test]
+    fn refresh_without_jitter_is_due_at_the_lifetime_fraction() {
+        let schedule = CredentialRefreshSchedule::default()
+            .with_lifetime_fraction(0.8)
+            .unwrap()
+            .with_jitter(Duration::ZERO);
+        let created_at = Timestamp(1_000);
+        let expires_at = Timestamp(2_000);
+
+        assert_eq!(
+            schedule.refresh_delay(created_at, expires_at, Timestamp(1_500)),
+            Duration::from_secs(300)
+        );
+        // A refresh already due has no delay
+        assert_eq!(
+            schedule.refresh_delay(created_at, expires_at, Timestamp(1_900)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn invalid_lifetime_fractions_are_rejected() {
+        let schedule = CredentialRefreshSchedule::default();
+        for fraction in [f64::NAN, 0.0, -0.5, 1.5, f64::INFINITY] {
+            assert!(schedule.with_lifetime_fraction(fraction).is_err());
+        }
+        assert!(schedule.with_lifetime_fraction(1.0).is_ok());
+    }
+}
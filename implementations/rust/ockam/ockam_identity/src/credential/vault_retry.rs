@@ -0,0 +1,115 @@
+use crate::IdentityError;
+use core::future::Future;
+use core::time::Duration;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use ockam_node::Context;
+use tracing::debug;
+
+/// Return whether `error` was returned by a vault which can't be reached for now,
+/// e.g. a remote or HSM-backed vault, so that the operation may succeed if retried.
+/// Vaults report it with [`Origin::Vault`] and [`Kind::Timeout`].
+pub fn is_vault_unavailable(error: &Error) -> bool {
+    let code = error.code();
+    code.origin == Origin::Vault && code.kind == Kind::Timeout
+}
+
+/// How the credential operations of an [`Identity`](crate::Identity) retry the vault
+/// operations failing because the vault is unavailable, see [`is_vault_unavailable`]
+///
+/// Once the retries are exhausted, the operation fails with
+/// [`IdentityError::VaultUnavailable`]. Other vault errors are never retried.
+#[derive(Clone, Debug)]
+pub struct VaultRetry {
+    pub(crate) max_retries: usize,
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+}
+
+impl Default for VaultRetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VaultRetry {
+    /// Default number of retries of a vault operation
+    pub const DEFAULT_MAX_RETRIES: usize = 3;
+    /// Default delay before the first retry
+    pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+    /// Default upper bound of the delay between two retries
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(2);
+
+    /// Constructor
+    pub fn new() -> Self {
+        Self {
+            max_retries: Self::DEFAULT_MAX_RETRIES,
+            initial_backoff: Self::DEFAULT_INITIAL_BACKOFF,
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+        }
+    }
+
+    /// Fail right away when the vault is unavailable
+    pub fn disabled() -> Self {
+        Self::new().with_max_retries(0)
+    }
+
+    /// Set the number of retries of a vault operation
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the initial and maximum delay between two retries.
+    /// The delay doubles after each failed retry.
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff.max(initial_backoff);
+        self
+    }
+
+    /// Run `operation`, retrying it while it fails because the vault is unavailable
+    pub(crate) async fn run<T, F, Fut>(&self, ctx: &Context, mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut retries = 0;
+        loop {
+            match operation().await {
+                Err(e) if is_vault_unavailable(&e) => {
+                    if retries == self.max_retries {
+                        debug!("Vault still unavailable after {retries} retries: {e}");
+                        return Err(IdentityError::VaultUnavailable.into());
+                    }
+                    debug!(
+                        "Vault unavailable: {e}, retrying in {}ms",
+                        backoff.as_millis()
+                    );
+                    ctx.sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                    retries += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ockam_vault::VaultError;
+
+    #[test]
+    fn vault_unavailable_errors_are_classified() {
+        assert!(is_vault_unavailable(&VaultError::Unavailable.into()));
+        assert!(!is_vault_unavailable(&VaultError::SecretNotFound.into()));
+        assert!(!is_vault_unavailable(&Error::new(
+            Origin::Transport,
+            Kind::Timeout,
+            "timeout"
+        )));
+    }
+}
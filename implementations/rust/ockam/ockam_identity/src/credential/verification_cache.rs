@@ -0,0 +1,137 @@
+use crate::credential::{Credential, CredentialData, Timestamp, Unverified, Verified};
+use crate::{IdentityIdentifier, IdentityVault, PublicIdentity};
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+/// SHA-256 of the data and signature of a credential
+type Fingerprint = [u8; 32];
+
+#[derive(Default)]
+struct Entries {
+    verified: BTreeMap<(IdentityIdentifier, Fingerprint), CachedVerification>,
+    hits: u64,
+}
+
+struct CachedVerification {
+    issuer: IdentityIdentifier,
+    expires: Timestamp,
+}
+
+/// Cache of the credentials presented to an [`Identity`](crate::Identity) and verified
+/// by it, see [`Identity::set_credential_verification_cache`](crate::Identity::set_credential_verification_cache)
+///
+/// Verifications are keyed by the identity presenting the credential and the fingerprint
+/// of the credential, and are kept until the credential expires. When the same peer
+/// presents the same credential again, e.g. after reconnecting, its signature isn't
+/// checked again, as long as its issuer is still one of the trusted authorities.
+/// Clones of the cache share the same verifications.
+#[derive(Clone, Default)]
+pub struct CredentialVerificationCache {
+    entries: Arc<RwLock<Entries>>,
+}
+
+impl CredentialVerificationCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the verifications of the credentials presented by `subject`, so that
+    /// they are verified again the next time they are presented, e.g. once its
+    /// authority is known to have revoked its credential. This doesn't deny the
+    /// credentials: one still verifying against the authorities is accepted again.
+    /// Return the number of forgotten verifications.
+    pub fn invalidate(&self, subject: &IdentityIdentifier) -> usize {
+        match self.entries.write() {
+            Ok(mut entries) => {
+                let cached = entries.verified.len();
+                entries.verified.retain(|(s, _), _| s != subject);
+                cached - entries.verified.len()
+            }
+            Err(_) => 0,
+        }
+    }
+
+    /// Number of verifications served from the cache so far
+    pub fn hits(&self) -> u64 {
+        match self.entries.read() {
+            Ok(entries) => entries.hits,
+            Err(_) => 0,
+        }
+    }
+
+    /// Number of cached verifications, including the expired ones not evicted yet
+    pub fn len(&self) -> usize {
+        match self.entries.read() {
+            Ok(entries) => entries.verified.len(),
+            Err(_) => 0,
+        }
+    }
+
+    /// Return whether no verification is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) async fn fingerprint(
+        credential: &Credential,
+        vault: &impl IdentityVault,
+    ) -> Result<Fingerprint> {
+        let mut bytes = Vec::with_capacity(credential.data.len() + credential.signature.len());
+        bytes.extend_from_slice(&credential.data);
+        bytes.extend_from_slice(&credential.signature);
+        vault.sha256(&bytes).await
+    }
+
+    /// Return the data of `credential` if `sender` presented it before, it isn't
+    /// expired, and its issuer is one of `authorities`
+    pub(crate) fn get<'a>(
+        &self,
+        sender: &IdentityIdentifier,
+        fingerprint: &Fingerprint,
+        credential: &Credential,
+        authorities: impl IntoIterator<Item = &'a PublicIdentity>,
+    ) -> Option<CredentialData<Verified>> {
+        let now = Timestamp::now()?;
+        let mut entries = self.entries.write().ok()?;
+        let key = (sender.clone(), *fingerprint);
+        let cached = entries.verified.get(&key)?;
+        if cached.expires <= now {
+            entries.verified.remove(&key);
+            return None;
+        }
+        if !authorities
+            .into_iter()
+            .any(|a| a.identifier() == &cached.issuer)
+        {
+            return None;
+        }
+        let data = CredentialData::<Unverified>::try_from(credential).ok()?;
+        entries.hits += 1;
+        Some(data.into_verified())
+    }
+
+    /// Remember that `sender` presented a credential with the given fingerprint, whose
+    /// verification returned `data`
+    pub(crate) fn insert(
+        &self,
+        sender: &IdentityIdentifier,
+        fingerprint: Fingerprint,
+        data: &CredentialData<Verified>,
+    ) {
+        if let Ok(mut entries) = self.entries.write() {
+            if let Some(now) = Timestamp::now() {
+                entries.verified.retain(|_, cached| cached.expires > now);
+            }
+            entries.verified.insert(
+                (sender.clone(), fingerprint),
+                CachedVerification {
+                    issuer: data.issuer.clone(),
+                    expires: data.expires,
+                },
+            );
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::authenticated_storage::{AuthenticatedStorage, IdentityAttributeStorage};
-use crate::credential::Credential;
+use crate::credential::identity::decode_presentation;
 use crate::{
     Identity, IdentityIdentifier, IdentitySecureChannelLocalInfo, IdentityVault, PublicIdentity,
 };
@@ -86,13 +86,14 @@ impl<AS: IdentityAttributeStorage, S: AuthenticatedStorage, V: IdentityVault>
                     "Received one-way credential presentation request from {}",
                     sender
                 );
-                let credential: Credential = dec.decode()?;
+                let (credential, disclosed) = decode_presentation(dec)?;
 
                 let res = self
                     .identity
                     .receive_presented_credential(
                         sender.clone(),
                         credential,
+                        disclosed,
                         self.authorities.iter(),
                         &self.attributes_storage,
                     )
@@ -117,13 +118,14 @@ impl<AS: IdentityAttributeStorage, S: AuthenticatedStorage, V: IdentityVault>
                     "Received mutual credential presentation request from {}",
                     sender
                 );
-                let credential: Credential = dec.decode()?;
+                let (credential, disclosed) = decode_presentation(dec)?;
 
                 let res = self
                     .identity
                     .receive_presented_credential(
                         sender.clone(),
                         credential,
+                        disclosed,
                         self.authorities.iter(),
                         &self.attributes_storage,
                     )
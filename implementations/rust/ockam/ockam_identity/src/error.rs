@@ -44,6 +44,12 @@ pub enum IdentityError {
     NonceOverflow,
     /// SecureChannel was not found in the Registry
     SecureChannelNotFound,
+    /// The key type can't be used for the root key of an `Identity`
+    UnsupportedKeyType,
+    /// The vault was still unavailable after retrying, see `VaultRetry`
+    VaultUnavailable,
+    /// Attributes changing stored values were rejected, see `AttributeConflictPolicy`
+    AttributeConflict,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
@@ -56,8 +62,11 @@ impl core::fmt::Display for IdentityError {
 impl From<IdentityError> for Error {
     #[track_caller]
     fn from(err: IdentityError) -> Self {
-        let kind = Kind::Unknown; // FIXME: fill these in with more
-                                  // meaningful error kinds
+        let kind = match err {
+            IdentityError::VaultUnavailable => Kind::Timeout,
+            _ => Kind::Unknown, // FIXME: fill these in with more
+                                // meaningful error kinds
+        };
         Error::new(Origin::Identity, kind, err)
     }
 }
@@ -3,21 +3,19 @@ use crate::authenticated_storage::mem::InMemoryStorage;
 use crate::authenticated_storage::AuthenticatedStorage;
 use crate::change::IdentitySignedChange;
 use crate::change_history::{IdentityChangeHistory, IdentityHistoryComparison};
-use crate::credential::Credential;
+use crate::credential::{Credential, CredentialVerificationCache, VaultRetry};
 use crate::{
     ChangeIdentifier, IdentityError, IdentityIdentifier, IdentityVault, KeyAttributes,
     PublicIdentity, SecureChannelRegistry,
 };
 use ockam_core::compat::{boxed::Box, string::String, sync::Arc, vec::Vec};
 use ockam_core::vault::Secret::Key;
-use ockam_core::vault::{
-    SecretKey, SecretPersistence, SecretType, Signature, CURVE25519_SECRET_LENGTH_U32,
-};
+use ockam_core::vault::{SecretKey, SecretType, Signature};
 use ockam_core::{Address, Result};
 use ockam_core::{AsyncTryClone, DenyAll};
 use ockam_node::compat::asynchronous::RwLock;
 use ockam_node::Context;
-use ockam_vault::{KeyId, SecretAttributes};
+use ockam_vault::KeyId;
 
 /// Identity implementation
 #[derive(AsyncTryClone)]
@@ -25,6 +23,8 @@ use ockam_vault::{KeyId, SecretAttributes};
 pub struct Identity<V: IdentityVault, S: AuthenticatedStorage> {
     id: IdentityIdentifier,
     pub(crate) credential: Arc<RwLock<Option<Credential>>>,
+    pub(crate) credential_verification_cache: Arc<RwLock<Option<CredentialVerificationCache>>>,
+    pub(crate) vault_retry: Arc<RwLock<VaultRetry>>,
     pub(crate) change_history: Arc<RwLock<IdentityChangeHistory>>,
     pub(crate) ctx: Context,
     pub(crate) authenticated_storage: S,
@@ -45,6 +45,10 @@ impl IdentityStateConst {
     pub const CHANGE_HISTORY_KEY: &'static str = "CHANGE_HISTORY";
     /// Attributes key for AuthenticatedStorage
     pub const ATTRIBUTES_KEY: &'static str = "ATTRIBUTES";
+    /// Identifiers pinned by the trust on first use policy key for AuthenticatedStorage
+    pub const TOFU_PIN_KEY: &'static str = "TOFU_PIN";
+    /// Type of the root key of an [`crate::Identity`] created without specifying one
+    pub const DEFAULT_KEY_TYPE: SecretType = SecretType::Ed25519;
 }
 
 impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
@@ -60,6 +64,8 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
         Self {
             id,
             credential: Arc::new(RwLock::new(None)),
+            credential_verification_cache: Arc::new(RwLock::new(None)),
+            vault_retry: Arc::new(RwLock::new(VaultRetry::default())),
             change_history: Arc::new(RwLock::new(change_history)),
             ctx,
             authenticated_storage,
@@ -158,22 +164,41 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
         Ok(identity)
     }
 
+    /// Generate the root key of a new `Identity` of type `key_type` in `vault`
+    ///
+    /// The key type is rejected if it can't sign the identity changes, see
+    /// [`KeyAttributes::root_with_key_type`], or if the vault can't generate it.
+    async fn generate_root_key(vault: &V, key_type: SecretType) -> Result<(KeyId, KeyAttributes)> {
+        let attrs = KeyAttributes::root_with_key_type(key_type)?;
+        let kid = vault.secret_generate(attrs.secret_attributes()).await?;
+        Ok((kid, attrs))
+    }
+
     /// Create an `Identity`. Extended version
     pub async fn create_ext(ctx: &Context, authenticated_storage: &S, vault: &V) -> Result<Self> {
-        let attrs = KeyAttributes::new(
-            IdentityStateConst::ROOT_LABEL.to_string(),
-            SecretAttributes::new(
-                SecretType::Ed25519,
-                SecretPersistence::Persistent,
-                CURVE25519_SECRET_LENGTH_U32,
-            ),
-        );
+        Self::create_ext_with_key_type(
+            ctx,
+            authenticated_storage,
+            vault,
+            IdentityStateConst::DEFAULT_KEY_TYPE,
+        )
+        .await
+    }
+
+    /// Create an `Identity` whose root key is of type `key_type`. Extended version
+    pub async fn create_ext_with_key_type(
+        ctx: &Context,
+        authenticated_storage: &S,
+        vault: &V,
+        key_type: SecretType,
+    ) -> Result<Self> {
+        let (kid, attrs) = Self::generate_root_key(vault, key_type).await?;
         Self::create_impl(
             ctx,
             authenticated_storage.async_try_clone().await?,
             SecureChannelRegistry::new(),
             vault,
-            None,
+            Some(&kid),
             attrs,
         )
         .await
@@ -202,20 +227,22 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
 impl<V: IdentityVault> Identity<V, InMemoryStorage> {
     /// Create an `Identity` with a new secret key and `InMemoryStorage`
     pub async fn create(ctx: &Context, vault: &V) -> Result<Self> {
-        let attrs = KeyAttributes::new(
-            IdentityStateConst::ROOT_LABEL.to_string(),
-            SecretAttributes::new(
-                SecretType::Ed25519,
-                SecretPersistence::Persistent,
-                CURVE25519_SECRET_LENGTH_U32,
-            ),
-        );
+        Self::create_with_key_type(ctx, vault, IdentityStateConst::DEFAULT_KEY_TYPE).await
+    }
+
+    /// Create an `Identity` with a new secret key of type `key_type` and `InMemoryStorage`
+    pub async fn create_with_key_type(
+        ctx: &Context,
+        vault: &V,
+        key_type: SecretType,
+    ) -> Result<Self> {
+        let (kid, attrs) = Self::generate_root_key(vault, key_type).await?;
         Self::create_impl(
             ctx,
             InMemoryStorage::new(),
             SecureChannelRegistry::new(),
             vault,
-            None,
+            Some(&kid),
             attrs,
         )
         .await
@@ -349,21 +376,17 @@ impl<V: IdentityVault, S: AuthenticatedStorage> Identity<V, S> {
         self.add_change(change).await
     }
 
-    /// Rotate an existing key with a given label
+    /// Rotate an existing key with a given label, keeping its type
     pub async fn rotate_key(&self, label: &str) -> Result<()> {
-        let change = self
-            .make_rotate_key_change(KeyAttributes::default_with_label(label.to_string()))
-            .await?;
+        let change = self.make_rotate_key_change(label).await?;
 
         self.add_change(change).await
     }
 
-    /// Rotate this `Identity` root key
+    /// Rotate this `Identity` root key, keeping its type
     pub async fn rotate_root_key(&self) -> Result<()> {
         let change = self
-            .make_rotate_key_change(KeyAttributes::default_with_label(
-                IdentityStateConst::ROOT_LABEL.to_string(),
-            ))
+            .make_rotate_key_change(IdentityStateConst::ROOT_LABEL)
             .await?;
 
         self.add_change(change).await
@@ -551,4 +574,54 @@ mod test {
 
         Ok(())
     }
+
+    #[ockam_macros::test]
+    async fn test_create_with_key_type(ctx: &mut Context) -> Result<()> {
+        let vault = Vault::create();
+
+        let identity = Identity::create_with_key_type(ctx, &vault, SecretType::NistP256).await?;
+        let public1 = identity.get_root_public_key().await?;
+
+        // The change history signed with the NIST P-256 key can be verified by a peer
+        let known_identity =
+            PublicIdentity::import(&identity.export().await?, &Vault::create()).await?;
+        assert_eq!(known_identity.identifier(), identity.identifier());
+        let proof = identity.create_signature(b"data", None).await?;
+        if !known_identity
+            .verify_signature(&proof, b"data", None, &vault)
+            .await?
+        {
+            return test_error("the proof was invalid");
+        }
+
+        // The rotated root key keeps the type of the key it replaces
+
+        identity.rotate_root_key().await?;
+
+        if !identity.verify_changes().await? {
+            return test_error("verify_changes failed");
+        }
+
+        let public2 = identity.get_root_public_key().await?;
+        if public1 == public2 {
+            return test_error("public did not change after rotate_key");
+        }
+        if public2.stype() != SecretType::NistP256 {
+            return test_error("the rotated root key is not a NIST P-256 key");
+        }
+
+        // Keys which can't sign can't be root keys
+        for key_type in [SecretType::X25519, SecretType::Aes, SecretType::Buffer] {
+            if Identity::create_with_key_type(ctx, &vault, key_type)
+                .await
+                .is_ok()
+            {
+                return test_error(format!("{key_type:?} should be rejected"));
+            }
+        }
+
+        ctx.stop().await?;
+
+        Ok(())
+    }
 }
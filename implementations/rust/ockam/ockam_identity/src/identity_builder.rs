@@ -1,5 +1,6 @@
 use crate::authenticated_storage::mem::InMemoryStorage;
-use crate::{Identity, IdentityVault};
+use crate::{Identity, IdentityStateConst, IdentityVault};
+use ockam_core::vault::SecretType;
 use ockam_core::{Address, DenyAll, Result};
 use ockam_node::Context;
 
@@ -7,6 +8,7 @@ use ockam_node::Context;
 pub struct IdentityBuilder<V: IdentityVault> {
     ctx: Context,
     vault: V,
+    key_type: SecretType,
 }
 
 impl<V: IdentityVault> IdentityBuilder<V> {
@@ -23,12 +25,19 @@ impl<V: IdentityVault> IdentityBuilder<V> {
         Ok(Self {
             ctx: child_ctx,
             vault: vault.async_try_clone().await?,
+            key_type: IdentityStateConst::DEFAULT_KEY_TYPE,
         })
     }
 
+    /// Use a root key of type `key_type`, see [`Identity::create_with_key_type`]
+    pub fn with_key_type(mut self, key_type: SecretType) -> Self {
+        self.key_type = key_type;
+        self
+    }
+
     /// Build an `Identity`
     pub async fn build(self) -> Result<Identity<V, InMemoryStorage>> {
-        Identity::create(&self.ctx, &self.vault).await
+        Identity::create_with_key_type(&self.ctx, &self.vault, self.key_type).await
     }
 }
 
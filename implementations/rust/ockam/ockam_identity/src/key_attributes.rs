@@ -1,6 +1,10 @@
+use crate::{IdentityError, IdentityStateConst};
 use core::fmt;
-use ockam_core::compat::string::String;
-use ockam_core::vault::{SecretPersistence, SecretType, CURVE25519_SECRET_LENGTH_U32};
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::vault::{
+    SecretPersistence, SecretType, CURVE25519_SECRET_LENGTH_U32, NIST_P256_SECRET_LENGTH_U32,
+};
+use ockam_core::Result;
 use ockam_vault::SecretAttributes;
 use serde::{Deserialize, Serialize};
 
@@ -35,6 +39,24 @@ impl KeyAttributes {
         )
     }
 
+    /// Root key of a new [`Identity`](crate::Identity) of type `key_type`
+    ///
+    /// Only the key types which can sign the changes of an identity are accepted,
+    /// i.e. Ed25519 and NIST P-256.
+    pub fn root_with_key_type(key_type: SecretType) -> Result<Self> {
+        let length = match key_type {
+            SecretType::Ed25519 => CURVE25519_SECRET_LENGTH_U32,
+            SecretType::NistP256 => NIST_P256_SECRET_LENGTH_U32,
+            SecretType::X25519 | SecretType::Buffer | SecretType::Aes => {
+                return Err(IdentityError::UnsupportedKeyType.into())
+            }
+        };
+        Ok(Self::new(
+            IdentityStateConst::ROOT_LABEL.to_string(),
+            SecretAttributes::new(key_type, SecretPersistence::Persistent, length),
+        ))
+    }
+
     /// Constructor
     pub fn new(label: String, secret_attributes: SecretAttributes) -> Self {
         Self {
@@ -0,0 +1,142 @@
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::{Error, Result};
+use ockam_identity::authenticated_storage::{
+    mem::InMemoryStorage, AttributeConflictPolicy, AttributesEntry, AttributesSnapshot,
+    AuthenticatedAttributeStorage, IdentityAttributeStorageReader, IdentityAttributeStorageWriter,
+};
+use ockam_identity::credential::Timestamp;
+use ockam_identity::{IdentityError, IdentityIdentifier};
+use ockam_node::Context;
+
+const AUTHORITY: &str = "P6474cfdbf547240b6d716bff89c976810859bc3f47be8ea620df12a392ea6cb7";
+const PEER1: &str = "P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357a";
+const PEER2: &str = "P624ed0b2e5a2be82e267ead6b3279f683616b66de9537a23e45343c95cbb357b";
+
+fn timestamp_in(seconds: u64) -> Timestamp {
+    let t = Timestamp::now().unwrap().unix_time() + seconds;
+    minicbor::decode(&minicbor::to_vec(t).unwrap()).unwrap()
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn attributes_snapshot__round_trip__should_preserve_entries(ctx: &mut Context) -> Result<()> {
+    let source = AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+    let authority = IdentityIdentifier::try_from(AUTHORITY)?;
+    let peer1 = IdentityIdentifier::try_from(PEER1)?;
+    let peer2 = IdentityIdentifier::try_from(PEER2)?;
+
+    source
+        .put_attributes(
+            &peer1,
+            AttributesEntry::new(
+                BTreeMap::from([("role".to_string(), b"member".to_vec())]),
+                Timestamp::now().unwrap(),
+                Some(timestamp_in(3600)),
+                Some(authority),
+            ),
+        )
+        .await?;
+    source
+        .put_attributes(
+            &peer2,
+            AttributesEntry::new(
+                BTreeMap::from([("role".to_string(), b"device".to_vec())]),
+                Timestamp::now().unwrap(),
+                None,
+                None,
+            ),
+        )
+        .await?;
+
+    // The snapshot goes through its serialized form, as in a backup
+    let exported = minicbor::to_vec(AttributesSnapshot::export(&source).await?)?;
+    let snapshot: AttributesSnapshot = minicbor::decode(&exported)?;
+    assert_eq!(snapshot.entries().len(), 2);
+
+    let destination = AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+    assert_eq!(snapshot.import(&destination).await?, 2);
+
+    let mut expected = source.list().await?;
+    let mut actual = destination.list().await?;
+    expected.sort_by(|a, b| a.0.cmp(&b.0));
+    actual.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(actual, expected);
+
+    ctx.stop().await
+}
+
+fn role_entry(role: &str) -> AttributesEntry {
+    AttributesEntry::new(
+        BTreeMap::from([("role".to_string(), role.as_bytes().to_vec())]),
+        Timestamp::now().unwrap(),
+        None,
+        None,
+    )
+}
+
+/// Store a `member` role for a peer, then a conflicting `admin` role, and return the
+/// result of the second write and the role stored afterwards
+async fn store_conflicting_role(policy: AttributeConflictPolicy) -> Result<(Result<()>, Vec<u8>)> {
+    let storage =
+        AuthenticatedAttributeStorage::new(InMemoryStorage::new()).with_conflict_policy(policy);
+    let peer = IdentityIdentifier::try_from(PEER1)?;
+
+    storage.put_attributes(&peer, role_entry("member")).await?;
+    let result = storage.put_attributes(&peer, role_entry("admin")).await;
+    let stored = storage.get_attributes(&peer).await?.unwrap();
+    Ok((result, stored.attrs()["role"].clone()))
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn attribute_conflict__last_write_wins__should_replace_the_stored_value(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (result, role) = store_conflicting_role(AttributeConflictPolicy::LastWriteWins).await?;
+    assert!(result.is_ok());
+    assert_eq!(role, b"admin");
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn attribute_conflict__keep_existing__should_keep_the_stored_value(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (result, role) = store_conflicting_role(AttributeConflictPolicy::KeepExisting).await?;
+    assert!(result.is_ok());
+    assert_eq!(role, b"member");
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn attribute_conflict__reject_on_change__should_fail_and_keep_the_stored_value(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (result, role) = store_conflicting_role(AttributeConflictPolicy::RejectOnChange).await?;
+    assert_eq!(
+        result.unwrap_err().code(),
+        Error::from(IdentityError::AttributeConflict).code()
+    );
+    assert_eq!(role, b"member");
+
+    // Storing the same value again, or new attributes, isn't a conflict
+    let storage = AuthenticatedAttributeStorage::new(InMemoryStorage::new())
+        .with_conflict_policy(AttributeConflictPolicy::RejectOnChange);
+    let peer = IdentityIdentifier::try_from(PEER1)?;
+    storage.put_attributes(&peer, role_entry("member")).await?;
+    storage.put_attributes(&peer, role_entry("member")).await?;
+    let mut entry = BTreeMap::from([("role".to_string(), b"member".to_vec())]);
+    entry.insert("zone".to_string(), b"eu".to_vec());
+    storage
+        .put_attributes(
+            &peer,
+            AttributesEntry::new(entry, Timestamp::now().unwrap(), None, None),
+        )
+        .await?;
+
+    ctx.stop().await
+}
@@ -1,11 +1,16 @@
 use core::sync::atomic::{AtomicU8, Ordering};
 use core::time::Duration;
 use ockam_core::compat::sync::Arc;
-use ockam_core::{route, Address, AllowAll, Any, DenyAll, Mailboxes, Result, Routed, Worker};
+use ockam_core::{
+    route, Address, AllowAll, Any, AsyncTryClone, DenyAll, Mailboxes, Result, Routed, Worker,
+};
 use ockam_identity::access_control::IdentityAccessControlBuilder;
 use ockam_identity::api::{DecryptionResponse, EncryptionRequest, EncryptionResponse};
+use ockam_identity::authenticated_storage::mem::InMemoryStorage;
 use ockam_identity::{
-    Identity, IdentitySecureChannelLocalInfo, TrustEveryonePolicy, TrustIdentifierPolicy,
+    Identity, IdentitySecureChannelLocalInfo, SecureChannelListenerTrustOptions,
+    SecureChannelTrustInfo, SecureChannelTrustOptions, TrustEveryonePolicy, TrustIdentifierPolicy,
+    TrustOnFirstUsePolicy, TrustPolicy,
 };
 use ockam_node::{Context, WorkerBuilder};
 use ockam_vault::Vault;
@@ -219,6 +224,74 @@ async fn test_channel_api(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_rekey(ctx: &mut Context) -> Result<()> {
+    let alice_vault = Vault::create();
+    let bob_vault = Vault::create();
+
+    let alice = Identity::create(ctx, &alice_vault).await?;
+    let bob = Identity::create(ctx, &bob_vault).await?;
+
+    let rekey_interval = Duration::from_millis(200);
+
+    bob.create_secure_channel_listener(
+        "bob_listener",
+        SecureChannelListenerTrustOptions::new().with_rekey_interval(rekey_interval),
+    )
+    .await?;
+
+    let alice_channel = alice
+        .create_secure_channel(
+            route!["bob_listener"],
+            SecureChannelTrustOptions::new().with_rekey_interval(rekey_interval),
+        )
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+
+    // Messages keep flowing in both directions while both sides rekey
+    for i in 0..4 {
+        child_ctx
+            .send(route![alice_channel.clone(), "child"], format!("Ping {i}"))
+            .await?;
+        let msg = child_ctx.receive::<String>().await?.take();
+        let return_route = msg.return_route();
+        assert_eq!(format!("Ping {i}"), msg.body());
+
+        child_ctx.send(return_route, format!("Pong {i}")).await?;
+        let msg = child_ctx.receive::<String>().await?.take();
+        assert_eq!(format!("Pong {i}"), msg.body());
+
+        sleep(rekey_interval + Duration::from_millis(100)).await;
+    }
+
+    // The nonce of the messages now belongs to a later key epoch
+    let alice_channel_data = alice
+        .secure_channel_registry()
+        .get_channel_by_encryptor_address(&alice_channel)
+        .unwrap();
+    let encrypted: EncryptionResponse = ctx
+        .send_and_receive(
+            route![alice_channel_data.encryptor_api_address().clone()],
+            EncryptionRequest(b"Ping".to_vec()),
+        )
+        .await?;
+    let encrypted = match encrypted {
+        EncryptionResponse::Ok(p) => p,
+        EncryptionResponse::Err(err) => return Err(err),
+    };
+    let nonce = u64::from_be_bytes(encrypted[..8].try_into().unwrap());
+    assert!(nonce >> 32 >= 3, "Channel should have been rekeyed");
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn test_tunneled_secure_channel_works(ctx: &mut Context) -> Result<()> {
     let vault = Vault::create();
@@ -556,3 +629,87 @@ async fn access_control__no_secure_channel__should_not_pass_messages(
 
     ctx.stop().await
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn trust_on_first_use__first_contact__should_pin_identifier(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+    let alice = Identity::create(ctx, &vault).await?;
+    let bob = Identity::create(ctx, &vault).await?;
+
+    let pin_store = InMemoryStorage::new();
+    bob.create_secure_channel_listener("bob_listener", TrustEveryonePolicy)
+        .await?;
+
+    let alice_channel = alice
+        .create_secure_channel(
+            route!["bob_listener"],
+            TrustOnFirstUsePolicy::new("bob", pin_store.clone()),
+        )
+        .await?;
+
+    let policy = TrustOnFirstUsePolicy::new("bob", pin_store);
+    assert_eq!(
+        policy.pinned_identifier().await?,
+        Some(bob.identifier().clone())
+    );
+
+    // The pinned identifier keeps being accepted
+    alice.stop_secure_channel(&alice_channel).await?;
+    alice
+        .create_secure_channel(route!["bob_listener"], policy)
+        .await?;
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn trust_on_first_use__changed_identity__should_reject(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+    let bob = Identity::create(ctx, &vault).await?;
+    let impostor = Identity::create(ctx, &vault).await?;
+
+    let pin_store = InMemoryStorage::new();
+    let policy = TrustOnFirstUsePolicy::new("bob", pin_store.clone());
+
+    let bob_info = SecureChannelTrustInfo::new(bob.identifier().clone());
+    let impostor_info = SecureChannelTrustInfo::new(impostor.identifier().clone());
+    assert!(policy.check(&bob_info).await?);
+    assert!(!policy.check(&impostor_info).await?);
+    assert!(policy.check(&bob_info).await?);
+
+    // Pins are per logical peer
+    let other_policy = TrustOnFirstUsePolicy::new("carol", pin_store);
+    assert!(other_policy.check(&impostor_info).await?);
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn trust_on_first_use__concurrent_first_uses__should_pin_one_identity(
+    ctx: &mut Context,
+) -> Result<()> {
+    let vault = Vault::create();
+    let bob = Identity::create(ctx, &vault).await?;
+    let impostor = Identity::create(ctx, &vault).await?;
+
+    let pin_store = InMemoryStorage::new();
+    let policy = TrustOnFirstUsePolicy::new("bob", pin_store);
+    let other_policy = policy.async_try_clone().await?;
+
+    // Two channels to the peer are established at the same time
+    let bob_info = SecureChannelTrustInfo::new(bob.identifier().clone());
+    let impostor_info = SecureChannelTrustInfo::new(impostor.identifier().clone());
+    let (bob_trusted, impostor_trusted) =
+        tokio::join!(policy.check(&bob_info), other_policy.check(&impostor_info));
+    let (bob_trusted, impostor_trusted) = (bob_trusted?, impostor_trusted?);
+    assert_ne!(bob_trusted, impostor_trusted);
+
+    let pinned = policy.pinned_identifier().await?.unwrap();
+    let trusted = if bob_trusted { &bob } else { &impostor };
+    assert_eq!(&pinned, trusted.identifier());
+
+    ctx.stop().await
+}
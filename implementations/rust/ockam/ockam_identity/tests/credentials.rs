@@ -1,16 +1,20 @@
 use ockam_core::compat::{boxed::Box, sync::Arc};
 use ockam_core::{async_trait, AllowAll, Any, AsyncTryClone, DenyAll, Mailboxes};
-use ockam_core::{route, Result, Routed, Worker};
+use ockam_core::{route, Address, Result, Routed, Worker};
 use ockam_identity::authenticated_storage::{
     mem::InMemoryStorage, AuthenticatedAttributeStorage, IdentityAttributeStorageReader,
 };
 use ockam_identity::credential::access_control::CredentialAccessControl;
-use ockam_identity::credential::Credential;
-use ockam_identity::{Identity, TrustEveryonePolicy, TrustIdentifierPolicy};
+use ockam_identity::credential::reconnect::{ChannelReconnector, CredentialExchangeReconnect};
+use ockam_identity::credential::{Credential, CredentialVerificationCache};
+use ockam_identity::{
+    Identity, IdentityIdentifier, PublicIdentity, TrustEveryonePolicy, TrustIdentifierPolicy,
+};
 
 use ockam_node::{Context, WorkerBuilder};
 use ockam_vault::Vault;
 use std::sync::atomic::{AtomicI8, Ordering};
+use std::sync::Mutex;
 use std::time::Duration;
 
 #[ockam_macros::test]
@@ -70,6 +74,59 @@ async fn full_flow_oneway(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn oneway_with_attributes_stores_only_disclosed_attributes(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authenticated_attribute_storage =
+        AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+
+    let authority = Identity::create(ctx, &vault).await?;
+
+    let server = Identity::create(ctx, &vault).await?;
+
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy)
+        .await?;
+
+    server
+        .start_credential_exchange_worker(
+            vec![authority.to_public().await?],
+            "credential_exchange",
+            false,
+            authenticated_attribute_storage.async_try_clone().await?,
+        )
+        .await?;
+
+    let client = Identity::create(ctx, &vault).await?;
+    let channel = client
+        .create_secure_channel(
+            route!["listener"],
+            TrustIdentifierPolicy::new(server.identifier().clone()),
+        )
+        .await?;
+
+    let credential = Credential::builder(client.identifier().clone())
+        .with_attribute("role", b"member")
+        .with_attribute("email", b"client@example.com");
+    let credential = authority.issue_credential(credential).await?;
+    client.set_credential(credential).await;
+
+    client
+        .present_credential_with_attributes(route![channel, "credential_exchange"], None, &["role"])
+        .await?;
+
+    let attrs = authenticated_attribute_storage
+        .get_attributes(client.identifier())
+        .await?
+        .unwrap();
+
+    assert_eq!(attrs.attrs().get("role").unwrap().as_slice(), b"member");
+    assert!(attrs.attrs().get("email").is_none());
+
+    ctx.stop().await
+}
+
 #[ockam_macros::test]
 async fn full_flow_twoway(ctx: &mut Context) -> Result<()> {
     let vault = Vault::create();
@@ -249,3 +306,344 @@ async fn access_control(ctx: &mut Context) -> Result<()> {
 
     ctx.stop().await
 }
+
+struct ClientReconnector {
+    client: Identity<Vault, InMemoryStorage>,
+    server: IdentityIdentifier,
+    channel: Arc<Mutex<Option<Address>>>,
+}
+
+#[async_trait]
+impl ChannelReconnector for ClientReconnector {
+    async fn reconnect(&self, _ctx: &Context) -> Result<Address> {
+        let channel = self
+            .client
+            .create_secure_channel(
+                route!["listener"],
+                TrustIdentifierPolicy::new(self.server.clone()),
+            )
+            .await?;
+        *self.channel.lock().unwrap() = Some(channel.clone());
+        Ok(channel)
+    }
+}
+
+#[ockam_macros::test]
+async fn full_flow_oneway_after_channel_loss(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let authenticated_attribute_storage =
+        AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+
+    let authority = Identity::create(ctx, &vault).await?;
+    let server = Identity::create(ctx, &vault).await?;
+    let client = Identity::create(ctx, &vault).await?;
+
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy)
+        .await?;
+
+    let channel = client
+        .create_secure_channel(
+            route!["listener"],
+            TrustIdentifierPolicy::new(server.identifier().clone()),
+        )
+        .await?;
+
+    let new_channel = Arc::new(Mutex::new(None));
+    let reconnector = ClientReconnector {
+        client: client.async_try_clone().await?,
+        server: server.identifier().clone(),
+        channel: new_channel.clone(),
+    };
+    let reconnect = CredentialExchangeReconnect::new(channel.clone(), reconnector)
+        .with_check_interval(Duration::from_millis(50))
+        .with_backoff(Duration::from_millis(50), Duration::from_millis(200));
+
+    server
+        .start_credential_exchange_worker_with_reconnect(
+            vec![authority.to_public().await?],
+            "credential_exchange",
+            false,
+            authenticated_attribute_storage.async_try_clone().await?,
+            reconnect,
+        )
+        .await?;
+
+    // Simulate the loss of the channel
+    client.stop_secure_channel(&channel).await?;
+
+    let mut channel = None;
+    for _ in 0..50 {
+        ctx.sleep(Duration::from_millis(50)).await;
+        channel = new_channel.lock().unwrap().clone();
+        if channel.is_some() {
+            break;
+        }
+    }
+    let channel = channel.expect("the channel should have been re-established");
+
+    let credential =
+        Credential::builder(client.identifier().clone()).with_attribute("is_superuser", b"true");
+    let credential = authority.issue_credential(credential).await?;
+    client.set_credential(credential).await;
+
+    client
+        .present_credential(route![channel, "credential_exchange"], None)
+        .await?;
+
+    let attrs = authenticated_attribute_storage
+        .get_attributes(client.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(
+        attrs.attrs().get("is_superuser").unwrap().as_slice(),
+        b"true"
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn credential_is_presented_again_after_channel_loss(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+
+    let server_storage = AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+
+    let authority = Identity::create(ctx, &vault).await?;
+    let server = Identity::create(ctx, &vault).await?;
+    let client = Identity::create(ctx, &vault).await?;
+
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy)
+        .await?;
+    server
+        .start_credential_exchange_worker(
+            vec![authority.to_public().await?],
+            "credential_exchange",
+            false,
+            server_storage.async_try_clone().await?,
+        )
+        .await?;
+
+    let channel = client
+        .create_secure_channel(
+            route!["listener"],
+            TrustIdentifierPolicy::new(server.identifier().clone()),
+        )
+        .await?;
+
+    let credential =
+        Credential::builder(client.identifier().clone()).with_attribute("is_superuser", b"true");
+    let credential = authority.issue_credential(credential).await?;
+    client.set_credential(credential).await;
+
+    let reconnector = ClientReconnector {
+        client: client.async_try_clone().await?,
+        server: server.identifier().clone(),
+        channel: Arc::new(Mutex::new(None)),
+    };
+    let reconnect = CredentialExchangeReconnect::new(channel.clone(), reconnector)
+        .with_check_interval(Duration::from_millis(50))
+        .with_presentation(route!["credential_exchange"]);
+
+    client
+        .start_credential_exchange_worker_with_reconnect(
+            vec![authority.to_public().await?],
+            "client_credential_exchange",
+            false,
+            AuthenticatedAttributeStorage::new(InMemoryStorage::new()),
+            reconnect,
+        )
+        .await?;
+
+    // The client never presented its credential over the lost channel:
+    // the presentation is only done over the new one
+    client.stop_secure_channel(&channel).await?;
+
+    let mut attrs = None;
+    for _ in 0..50 {
+        ctx.sleep(Duration::from_millis(50)).await;
+        attrs = server_storage.get_attributes(client.identifier()).await?;
+        if attrs.is_some() {
+            break;
+        }
+    }
+    let attrs = attrs.expect("the credential should have been presented again");
+    assert_eq!(
+        attrs.attrs().get("is_superuser").unwrap().as_slice(),
+        b"true"
+    );
+
+    ctx.stop().await
+}
+
+struct FailingReconnector;
+
+#[async_trait]
+impl ChannelReconnector for FailingReconnector {
+    async fn reconnect(&self, _ctx: &Context) -> Result<Address> {
+        Err(ockam_core::Error::new(
+            ockam_core::errcode::Origin::Transport,
+            ockam_core::errcode::Kind::Unknown,
+            "unreachable",
+        ))
+    }
+}
+
+#[ockam_macros::test]
+async fn reconnection_stops_after_max_retries(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+    let server = Identity::create(ctx, &vault).await?;
+
+    let reconnect = CredentialExchangeReconnect::new("lost_channel", FailingReconnector)
+        .with_check_interval(Duration::from_millis(10))
+        .with_backoff(Duration::from_millis(10), Duration::from_millis(10))
+        .with_max_retries(3);
+    let processor = server
+        .start_credential_exchange_worker_with_reconnect(
+            vec![],
+            "credential_exchange",
+            false,
+            AuthenticatedAttributeStorage::new(InMemoryStorage::new()),
+            reconnect,
+        )
+        .await?;
+    assert!(ctx.list_workers().await?.contains(&processor));
+
+    ctx.sleep(Duration::from_millis(500)).await;
+    assert!(!ctx.list_workers().await?.contains(&processor));
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn reconnection_stops_with_the_worker(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+    let server = Identity::create(ctx, &vault).await?;
+
+    let reconnect = CredentialExchangeReconnect::new("lost_channel", FailingReconnector)
+        .with_check_interval(Duration::from_millis(10))
+        .with_backoff(Duration::from_millis(10), Duration::from_millis(10))
+        .with_max_retries(u32::MAX);
+    let processor = server
+        .start_credential_exchange_worker_with_reconnect(
+            vec![],
+            "credential_exchange",
+            false,
+            AuthenticatedAttributeStorage::new(InMemoryStorage::new()),
+            reconnect,
+        )
+        .await?;
+
+    ctx.stop_worker("credential_exchange").await?;
+
+    ctx.sleep(Duration::from_millis(200)).await;
+    assert!(!ctx.list_workers().await?.contains(&processor));
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn repeated_presentation_is_served_from_the_verification_cache(
+    ctx: &mut Context,
+) -> Result<()> {
+    let vault = Vault::create();
+
+    let authenticated_attribute_storage =
+        AuthenticatedAttributeStorage::new(InMemoryStorage::new());
+
+    let authority = Identity::create(ctx, &vault).await?;
+    let server = Identity::create(ctx, &vault).await?;
+    let client = Identity::create(ctx, &vault).await?;
+
+    let cache = CredentialVerificationCache::new();
+    server
+        .set_credential_verification_cache(cache.clone())
+        .await;
+    server
+        .create_secure_channel_listener("listener", TrustEveryonePolicy)
+        .await?;
+    server
+        .start_credential_exchange_worker(
+            vec![authority.to_public().await?],
+            "credential_exchange",
+            false,
+            authenticated_attribute_storage.async_try_clone().await?,
+        )
+        .await?;
+
+    let channel = client
+        .create_secure_channel(
+            route!["listener"],
+            TrustIdentifierPolicy::new(server.identifier().clone()),
+        )
+        .await?;
+    let credential =
+        Credential::builder(client.identifier().clone()).with_attribute("is_superuser", b"true");
+    let credential = authority.issue_credential(credential).await?;
+    client.set_credential(credential).await;
+
+    // The first presentation is verified, the second one is served from the cache
+    client
+        .present_credential(route![channel.clone(), "credential_exchange"], None)
+        .await?;
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.hits(), 0);
+    client
+        .present_credential(route![channel.clone(), "credential_exchange"], None)
+        .await?;
+    assert_eq!(cache.hits(), 1);
+
+    // Once invalidated, the credential is verified again
+    assert_eq!(cache.invalidate(client.identifier()), 1);
+    assert!(cache.is_empty());
+    client
+        .present_credential(route![channel, "credential_exchange"], None)
+        .await?;
+    assert_eq!(cache.hits(), 1);
+    assert_eq!(cache.len(), 1);
+
+    let attrs = authenticated_attribute_storage
+        .get_attributes(client.identifier())
+        .await?
+        .unwrap();
+    assert_eq!(
+        attrs.attrs().get("is_superuser").unwrap().as_slice(),
+        b"true"
+    );
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn displayed_credential_is_verified_unless_tampered(ctx: &mut Context) -> Result<()> {
+    let vault = Vault::create();
+    let authority = Identity::create(ctx, &vault).await?;
+    let subject = Identity::create(ctx, &vault).await?;
+    let authorities = vec![authority.to_public().await?];
+
+    let credential = authority
+        .issue_credential(
+            Credential::builder(subject.identifier().clone()).with_attribute("city", b"London"),
+        )
+        .await?;
+    let displayed = credential.to_string();
+
+    let candidates = Credential::from_display(&displayed, &[None])?;
+    let issuer = PublicIdentity::find_issuer(&candidates[0], &authorities)?;
+    let data = issuer
+        .verify_credential(&candidates[0], subject.identifier(), &vault)
+        .await?;
+    assert_eq!(data.attributes().get("city"), Some(b"London".as_slice()));
+
+    let tampered = displayed.replace("London", "Paris");
+    let candidates = Credential::from_display(&tampered, &[None])?;
+    let issuer = PublicIdentity::find_issuer(&candidates[0], &authorities)?;
+    assert!(issuer
+        .verify_credential(&candidates[0], subject.identifier(), &vault)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
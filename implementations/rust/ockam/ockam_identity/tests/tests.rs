@@ -1,7 +1,7 @@
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::vault::{SecretAttributes, SecretPersistence, SecretType, SecretVault};
 use ockam_core::{Error, Result};
-use ockam_identity::Identity;
+use ockam_identity::{Identity, PublicIdentity};
 use ockam_node::Context;
 use ockam_vault::Vault;
 use rand::{thread_rng, RngCore};
@@ -0,0 +1,198 @@
+use core::time::Duration;
+use ockam_core::vault::{
+    AsymmetricVault, Buffer, Hasher, KeyId, PublicKey, Secret, SecretAttributes, SecretVault,
+    Signature, Signer, SmallBuffer, SymmetricVault, Verifier,
+};
+use ockam_core::{async_trait, compat::boxed::Box, Error};
+use ockam_core::{AsyncTryClone, Result};
+use ockam_identity::credential::{Credential, VaultRetry};
+use ockam_identity::{Identity, IdentityError};
+use ockam_node::Context;
+use ockam_vault::{Vault, VaultError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Vault whose signature verifications fail as unavailable for the next `outages` calls
+#[derive(AsyncTryClone)]
+#[async_try_clone(crate = "ockam_core")]
+struct FlakyVault {
+    outages: Arc<AtomicUsize>,
+    vault: Vault,
+}
+
+impl FlakyVault {
+    fn new(vault: Vault) -> Self {
+        Self {
+            outages: Arc::new(AtomicUsize::new(0)),
+            vault,
+        }
+    }
+
+    fn set_outages(&self, outages: usize) {
+        self.outages.store(outages, Ordering::Relaxed)
+    }
+}
+
+#[async_trait]
+impl SecretVault for FlakyVault {
+    async fn secret_generate(&self, attributes: SecretAttributes) -> Result<KeyId> {
+        self.vault.secret_generate(attributes).await
+    }
+
+    async fn secret_import(&self, secret: Secret, attributes: SecretAttributes) -> Result<KeyId> {
+        self.vault.secret_import(secret, attributes).await
+    }
+
+    async fn secret_export(&self, key_id: &KeyId) -> Result<Secret> {
+        self.vault.secret_export(key_id).await
+    }
+
+    async fn secret_attributes_get(&self, key_id: &KeyId) -> Result<SecretAttributes> {
+        self.vault.secret_attributes_get(key_id).await
+    }
+
+    async fn secret_public_key_get(&self, key_id: &KeyId) -> Result<PublicKey> {
+        self.vault.secret_public_key_get(key_id).await
+    }
+
+    async fn secret_destroy(&self, key_id: KeyId) -> Result<()> {
+        self.vault.secret_destroy(key_id).await
+    }
+}
+
+#[async_trait]
+impl SymmetricVault for FlakyVault {
+    async fn aead_aes_gcm_encrypt(
+        &self,
+        key_id: &KeyId,
+        plaintext: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Buffer<u8>> {
+        self.vault
+            .aead_aes_gcm_encrypt(key_id, plaintext, nonce, aad)
+            .await
+    }
+
+    async fn aead_aes_gcm_decrypt(
+        &self,
+        key_id: &KeyId,
+        cipher_text: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Buffer<u8>> {
+        self.vault
+            .aead_aes_gcm_decrypt(key_id, cipher_text, nonce, aad)
+            .await
+    }
+}
+
+#[async_trait]
+impl Hasher for FlakyVault {
+    async fn sha256(&self, data: &[u8]) -> Result<[u8; 32]> {
+        self.vault.sha256(data).await
+    }
+
+    async fn hkdf_sha256(
+        &self,
+        salt: &KeyId,
+        info: &[u8],
+        ikm: Option<&KeyId>,
+        output_attributes: SmallBuffer<SecretAttributes>,
+    ) -> Result<SmallBuffer<KeyId>> {
+        self.vault
+            .hkdf_sha256(salt, info, ikm, output_attributes)
+            .await
+    }
+}
+
+#[async_trait]
+impl AsymmetricVault for FlakyVault {
+    async fn ec_diffie_hellman(
+        &self,
+        secret: &KeyId,
+        peer_public_key: &PublicKey,
+    ) -> Result<KeyId> {
+        self.vault.ec_diffie_hellman(secret, peer_public_key).await
+    }
+
+    async fn compute_key_id_for_public_key(&self, public_key: &PublicKey) -> Result<KeyId> {
+        self.vault.compute_key_id_for_public_key(public_key).await
+    }
+}
+
+#[async_trait]
+impl Signer for FlakyVault {
+    async fn sign(&self, key_id: &KeyId, data: &[u8]) -> Result<Signature> {
+        self.vault.sign(key_id, data).await
+    }
+}
+
+#[async_trait]
+impl Verifier for FlakyVault {
+    async fn verify(
+        &self,
+        signature: &Signature,
+        public_key: &PublicKey,
+        data: &[u8],
+    ) -> Result<bool> {
+        let unavailable = self
+            .outages
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok();
+        if unavailable {
+            return Err(VaultError::Unavailable.into());
+        }
+        self.vault.verify(signature, public_key, data).await
+    }
+}
+
+#[ockam_macros::test]
+async fn credential_verification_is_retried_while_the_vault_is_unavailable(
+    ctx: &mut Context,
+) -> Result<()> {
+    let authority = Identity::create(ctx, &Vault::create()).await?;
+    let vault = FlakyVault::new(Vault::create());
+    let member = Identity::create(ctx, &vault).await?;
+    member
+        .set_vault_retry(
+            VaultRetry::new()
+                .with_max_retries(3)
+                .with_backoff(Duration::from_millis(1), Duration::from_millis(10)),
+        )
+        .await;
+
+    let credential = Credential::builder(member.identifier().clone());
+    let credential = authority.issue_credential(credential).await?;
+    let authorities = vec![authority.to_public().await?];
+
+    // The vault recovers before the retries are exhausted
+    vault.set_outages(2);
+    member
+        .verify_self_credential(&credential, authorities.iter())
+        .await?;
+
+    // The vault is still unavailable once the retries are exhausted
+    vault.set_outages(10);
+    let err = member
+        .verify_self_credential(&credential, authorities.iter())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.code(),
+        Error::from(IdentityError::VaultUnavailable).code()
+    );
+
+    // Without retries, the first failure is returned
+    member.set_vault_retry(VaultRetry::disabled()).await;
+    vault.set_outages(1);
+    assert!(member
+        .verify_self_credential(&credential, authorities.iter())
+        .await
+        .is_err());
+    member
+        .verify_self_credential(&credential, authorities.iter())
+        .await?;
+
+    ctx.stop().await
+}
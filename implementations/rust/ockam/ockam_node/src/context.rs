@@ -676,6 +676,26 @@ impl Context {
         &self,
         local_msg: LocalMessage,
         sending_address: Address,
+    ) -> Result<()> {
+        self.forward_from_address_impl(local_msg, sending_address, true)
+            .await
+    }
+
+    /// Forward a transport message to its next routing destination, like
+    /// [`Context::forward`], without waiting for room in the mailbox of the destination
+    ///
+    /// If that mailbox is full, the message is not forwarded and an error of kind
+    /// [`Kind::ResourceExhausted`] is returned, see [`NodeError::is_mailbox_full`].
+    pub async fn try_forward(&self, local_msg: LocalMessage) -> Result<()> {
+        self.forward_from_address_impl(local_msg, self.address(), false)
+            .await
+    }
+
+    async fn forward_from_address_impl(
+        &self,
+        local_msg: LocalMessage,
+        sending_address: Address,
+        wait_for_mailbox: bool,
     ) -> Result<()> {
         // Check if the sender address exists
         if !self.mailboxes.contains(&sending_address) {
@@ -723,10 +743,16 @@ impl Context {
         }
 
         // Forward the message
-        sender
-            .send(relay_msg)
-            .await
-            .map_err(NodeError::from_send_err)?;
+        if wait_for_mailbox {
+            sender
+                .send(relay_msg)
+                .await
+                .map_err(NodeError::from_send_err)?;
+        } else {
+            sender
+                .try_send(relay_msg)
+                .map_err(NodeError::from_try_send_err)?;
+        }
 
         Ok(())
     }
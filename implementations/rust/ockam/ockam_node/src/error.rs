@@ -1,4 +1,7 @@
-use crate::tokio::{sync::mpsc::error::SendError, time::error::Elapsed};
+use crate::tokio::{
+    sync::mpsc::error::{SendError, TrySendError},
+    time::error::Elapsed,
+};
 use core::fmt;
 use ockam_core::{
     compat::error::Error as StdError,
@@ -53,6 +56,25 @@ impl NodeError {
         .context("SendError", err)
     }
 
+    /// Create an ockam_core::Error based on a tokio::TrySendError. A full
+    /// channel is reported as a [`WorkerReason::MailboxFull`] worker state
+    pub(crate) fn from_try_send_err<T: fmt::Debug>(err: TrySendError<T>) -> Error {
+        match err {
+            TrySendError::Full(_) => Error::new(
+                Origin::Node,
+                Kind::ResourceExhausted,
+                NodeError::WorkerState(WorkerReason::MailboxFull),
+            ),
+            TrySendError::Closed(msg) => Self::from_send_err(SendError(msg)),
+        }
+    }
+
+    /// Return true if `err` was caused by a full worker mailbox,
+    /// for instance when calling [`Context::try_forward`](crate::Context::try_forward)
+    pub fn is_mailbox_full(err: &Error) -> bool {
+        err.code().origin == Origin::Node && err.code().kind == Kind::ResourceExhausted
+    }
+
     /// Create an ockam_core::Error from a tokio::Elapsed
     pub(crate) fn with_elapsed(self, err: Elapsed) -> Error {
         Error::new(Origin::Node, Kind::Timeout, err).context("Type", self)
@@ -145,6 +167,8 @@ pub enum WorkerReason {
     Faulty,
     /// The worker is otherwise corrupt and can not be recovered
     Corrupt,
+    /// The worker mailbox is full
+    MailboxFull,
 }
 
 impl fmt::Display for WorkerReason {
@@ -156,6 +180,7 @@ impl fmt::Display for WorkerReason {
                 Self::Shutdown => "target worker is shutting down",
                 Self::Faulty => "target worker is faulty and waiting for supervisor",
                 Self::Corrupt => "target worker is corrupt and can not be recovered",
+                Self::MailboxFull => "target worker mailbox is full",
             }
         )
     }
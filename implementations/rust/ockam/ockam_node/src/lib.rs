@@ -41,6 +41,10 @@ pub mod api;
 /// Debugger
 pub mod debugger;
 
+/// Runtime adjustment of the log levels
+#[cfg(feature = "std")]
+pub mod logging;
+
 mod async_drop;
 mod cancel;
 mod context;
@@ -0,0 +1,99 @@
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Filter installed with [`reloadable_filter`], along with the levels set at runtime
+struct ReloadableFilter {
+    directives: String,
+    levels: BTreeMap<String, LevelFilter>,
+    handle: reload::Handle<EnvFilter, Registry>,
+}
+
+impl ReloadableFilter {
+    fn reload(&self) -> Result<()> {
+        let filter = build_filter(&self.directives, &self.levels)?;
+        self.handle
+            .reload(filter)
+            .map_err(|e| Error::new(Origin::Node, Kind::Internal, e))
+    }
+}
+
+static RELOADABLE_FILTER: Mutex<Option<ReloadableFilter>> = Mutex::new(None);
+
+fn build_filter(directives: &str, levels: &BTreeMap<String, LevelFilter>) -> Result<EnvFilter> {
+    let mut filter = EnvFilter::builder().parse_lossy(directives);
+    for (target, level) in levels {
+        let directive = format!("{target}={level}")
+            .parse()
+            .map_err(|e| Error::new(Origin::Node, Kind::Invalid, e))?;
+        filter = filter.add_directive(directive);
+    }
+    Ok(filter)
+}
+
+/// Create a filter layer from `EnvFilter` `directives` (e.g. `info,ockam_node=debug`),
+/// whose levels can later be changed at runtime with [`set_log_level`].
+///
+/// The layer must be the first one added to the [`Registry`]. As long as a filter
+/// created by this function is in use by a subscriber, that filter is the one
+/// changed at runtime.
+pub fn reloadable_filter(directives: impl Into<String>) -> reload::Layer<EnvFilter, Registry> {
+    let directives = directives.into();
+    let (layer, handle) = reload::Layer::new(EnvFilter::builder().parse_lossy(&directives));
+    if let Ok(mut current) = RELOADABLE_FILTER.lock() {
+        let in_use = current
+            .as_ref()
+            .map(|filter| filter.handle.with_current(|_| ()).is_ok())
+            .unwrap_or(false);
+        if !in_use {
+            *current = Some(ReloadableFilter {
+                directives,
+                levels: BTreeMap::new(),
+                handle,
+            });
+        }
+    }
+    layer
+}
+
+/// Set the maximum level of the events emitted by `target` and its submodules,
+/// e.g. `ockam_transport_tcp::workers::receiver`. The change takes effect immediately.
+///
+/// A `None` level removes the level previously set for `target`.
+pub fn set_log_level(target: &str, level: Option<LevelFilter>) -> Result<()> {
+    let mut current = RELOADABLE_FILTER
+        .lock()
+        .map_err(|_| Error::new(Origin::Node, Kind::Internal, "log filter lock poisoned"))?;
+    let filter = current.as_mut().ok_or_else(|| {
+        Error::new(
+            Origin::Node,
+            Kind::NotFound,
+            "logging was not set up with a reloadable filter",
+        )
+    })?;
+
+    let previous = match level {
+        Some(level) => filter.levels.insert(target.to_string(), level),
+        None => filter.levels.remove(target),
+    };
+    if let Err(e) = filter.reload() {
+        match previous {
+            Some(level) => filter.levels.insert(target.to_string(), level),
+            None => filter.levels.remove(target),
+        };
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Return the levels set at runtime with [`set_log_level`], by target
+pub fn log_levels() -> BTreeMap<String, LevelFilter> {
+    RELOADABLE_FILTER
+        .lock()
+        .ok()
+        .and_then(|current| current.as_ref().map(|filter| filter.levels.clone()))
+        .unwrap_or_default()
+}
@@ -77,14 +77,14 @@ impl NodeBuilder {
 fn setup_tracing() {
     #[cfg(feature = "std")]
     {
-        use tracing_subscriber::{filter::LevelFilter, fmt, prelude::*, EnvFilter};
+        use tracing_subscriber::{fmt, prelude::*};
         static ONCE: std::sync::Once = std::sync::Once::new();
         ONCE.call_once(|| {
-            let filter = EnvFilter::try_from_env("OCKAM_LOG").unwrap_or_else(|_| {
-                EnvFilter::default()
-                    .add_directive(LevelFilter::INFO.into())
-                    .add_directive("ockam_node=info".parse().unwrap())
-            });
+            let directives = match std::env::var("OCKAM_LOG") {
+                Ok(s) if !s.is_empty() => s,
+                _ => "info,ockam_node=info".to_string(),
+            };
+            let filter = crate::logging::reloadable_filter(directives);
             // Ignore failure, since we may init externally.
             let _ = tracing_subscriber::registry()
                 .with(filter)
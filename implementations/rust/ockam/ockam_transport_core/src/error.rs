@@ -38,6 +38,10 @@ pub enum TransportError {
     PortalInvalidState,
     /// InvalidRouterResponseType
     InvalidRouterResponseType,
+    /// A received frame does not match its checksum
+    FrameChecksum,
+    /// The peer didn't accept the connection within the connect timeout
+    ConnectTimeout,
 }
 
 impl ockam_core::compat::error::Error for TransportError {}
@@ -59,6 +63,8 @@ impl core::fmt::Display for TransportError {
             Self::GenericIo => write!(f, "generic I/O failure"),
             Self::PortalInvalidState => write!(f, "portal entered invalid state"),
             Self::InvalidRouterResponseType => write!(f, "router responded with invalid type"),
+            Self::FrameChecksum => write!(f, "received frame does not match its checksum"),
+            Self::ConnectTimeout => write!(f, "timed out connecting to the peer"),
         }
     }
 }
@@ -83,6 +89,8 @@ impl From<TransportError> for Error {
             GenericIo => Kind::Io,
             PortalInvalidState => Kind::Invalid,
             InvalidRouterResponseType => Kind::Invalid,
+            FrameChecksum => Kind::Protocol,
+            ConnectTimeout => Kind::Timeout,
         };
 
         Error::new(Origin::Transport, kind, err)
@@ -0,0 +1,54 @@
+/// Length, in bytes, of the checksum appended to frames when frame checksums are enabled
+pub(crate) const FRAME_CHECKSUM_LEN: usize = 4;
+
+/// CRC-32 (IEEE 802.3) of `data`
+///
+/// Frame checksums only detect accidental corruption, integrity against a tampering
+/// peer is provided by secure channels.
+pub(crate) fn frame_checksum(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Split a frame received with a checksum into its payload, if the checksum
+/// matches, or return `None`
+pub(crate) fn verify_frame_checksum(frame: &[u8]) -> Option<&[u8]> {
+    if frame.len() < FRAME_CHECKSUM_LEN {
+        return None;
+    }
+    let (payload, checksum) = frame.split_at(frame.len() - FRAME_CHECKSUM_LEN);
+    let mut bytes = [0u8; FRAME_CHECKSUM_LEN];
+    bytes.copy_from_slice(checksum);
+    if u32::from_be_bytes(bytes) == frame_checksum(payload) {
+        Some(payload)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{frame_checksum, verify_frame_checksum};
+
+    #[test]
+    fn checksum_matches_the_crc32_check_value() {
+        assert_eq!(frame_checksum(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn flipped_bit_is_detected() {
+        let mut frame = b"some payload".to_vec();
+        frame.extend(frame_checksum(&frame).to_be_bytes());
+        assert_eq!(verify_frame_checksum(&frame), Some(&b"some payload"[..]));
+
+        frame[3] ^= 0x10;
+        assert!(verify_frame_checksum(&frame).is_none());
+    }
+}
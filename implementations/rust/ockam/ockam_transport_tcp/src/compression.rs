@@ -0,0 +1,83 @@
+use ockam_core::compat::borrow::Cow;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+
+/// Flag of a message sent as is
+const RAW_MESSAGE: u8 = 0;
+/// Flag of a message compressed with LZ4, prefixed by its length before compression,
+/// encoded as a little-endian 32-bit unsigned integer
+const LZ4_MESSAGE: u8 = 1;
+
+/// Messages shorter than this are sent as is, compressing them doesn't pay off
+const MIN_COMPRESSED_LEN: usize = 128;
+
+/// Prefix the serialized `msg` with a flag byte telling if it is compressed. The
+/// message is compressed with LZ4 when it makes it shorter, and sent as is otherwise
+pub(crate) fn compress_message(msg: Vec<u8>) -> Vec<u8> {
+    if msg.len() >= MIN_COMPRESSED_LEN {
+        let compressed = lz4_flex::block::compress_prepend_size(&msg);
+        if compressed.len() < msg.len() {
+            let mut buf = Vec::with_capacity(compressed.len() + 1);
+            buf.push(LZ4_MESSAGE);
+            buf.extend_from_slice(&compressed);
+            return buf;
+        }
+    }
+
+    let mut buf = Vec::with_capacity(msg.len() + 1);
+    buf.push(RAW_MESSAGE);
+    buf.extend_from_slice(&msg);
+    buf
+}
+
+/// Return the serialized message prefixed by [`compress_message`], decompressing it if
+/// needed. Messages longer than `max_len` once decompressed are rejected
+pub(crate) fn decompress_message(buf: &[u8], max_len: usize) -> Result<Cow<'_, [u8]>> {
+    match buf.split_first() {
+        Some((&RAW_MESSAGE, msg)) => Ok(Cow::Borrowed(msg)),
+        Some((&LZ4_MESSAGE, compressed)) if compressed.len() >= 4 => {
+            let (len, compressed) = compressed.split_at(4);
+            let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+            if len > max_len {
+                return Err(TransportError::RecvBadMessage.into());
+            }
+            let msg = lz4_flex::block::decompress(compressed, len)
+                .map_err(|_| TransportError::RecvBadMessage)?;
+            Ok(Cow::Owned(msg))
+        }
+        _ => Err(TransportError::RecvBadMessage.into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compress_message, decompress_message, LZ4_MESSAGE, RAW_MESSAGE};
+
+    #[test]
+    fn compressible_message_is_compressed() {
+        let msg = br#"{"metric":"cpu","value":42}"#.repeat(100);
+        let buf = compress_message(msg.clone());
+        assert_eq!(buf[0], LZ4_MESSAGE);
+        assert!(buf.len() < msg.len());
+        assert_eq!(decompress_message(&buf, msg.len()).unwrap(), &msg[..]);
+
+        // The length once decompressed is bounded
+        assert!(decompress_message(&buf, msg.len() - 1).is_err());
+    }
+
+    #[test]
+    fn short_message_is_sent_as_is() {
+        let buf = compress_message(b"hello".to_vec());
+        assert_eq!(buf, b"\x00hello");
+        assert_eq!(buf[0], RAW_MESSAGE);
+        assert_eq!(decompress_message(&buf, 5).unwrap(), &b"hello"[..]);
+    }
+
+    #[test]
+    fn unknown_flag_is_rejected() {
+        assert!(decompress_message(b"\x02hello", 5).is_err());
+        assert!(decompress_message(b"", 5).is_err());
+        assert!(decompress_message(b"\x01\x05\x00", 5).is_err());
+    }
+}
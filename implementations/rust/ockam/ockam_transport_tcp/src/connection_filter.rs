@@ -0,0 +1,189 @@
+use core::fmt;
+use ockam_core::compat::net::{IpAddr, SocketAddr};
+use ockam_core::compat::{sync::Arc, vec::Vec};
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+
+/// Decides which peers may open a connection to a TCP listener
+///
+/// Filters are configured with the listener trust options, and are run for every accepted
+/// connection before anything is read from it. A connection which isn't allowed is closed
+/// right away, and no worker is spawned for it.
+pub trait TcpConnectionFilter: Send + Sync + 'static {
+    /// Return true if `peer` may open a connection
+    fn allow(&self, peer: &SocketAddr) -> bool;
+}
+
+/// [`TcpConnectionFilter`]s of a TCP listener, which all must allow a connection
+#[derive(Clone, Default)]
+pub(crate) struct TcpConnectionFilters(Vec<Arc<dyn TcpConnectionFilter>>);
+
+impl TcpConnectionFilters {
+    pub(crate) fn push(&mut self, filter: Arc<dyn TcpConnectionFilter>) {
+        self.0.push(filter)
+    }
+
+    /// Return true if all filters allow `peer`
+    pub(crate) fn allow(&self, peer: &SocketAddr) -> bool {
+        self.0.iter().all(|filter| filter.allow(peer))
+    }
+}
+
+impl fmt::Debug for TcpConnectionFilters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TcpConnectionFilters({})", self.0.len())
+    }
+}
+
+/// [`TcpConnectionFilter`] allowing or denying peers by the network, in CIDR notation,
+/// their IP address belongs to
+///
+/// A peer in a denied network is refused. Otherwise, if allowed networks are set, a peer
+/// must belong to one of them, and any peer is allowed if none is set. IPv4 addresses
+/// mapped to IPv6, e.g. `::ffff:10.0.0.1`, are matched as IPv4 addresses.
+///
+/// ```rust
+/// use ockam_transport_tcp::TcpCidrFilter;
+/// # use ockam_core::Result;
+/// # fn test() -> Result<()> {
+/// let filter = TcpCidrFilter::new()
+///     .with_allowed("10.0.0.0/8")?
+///     .with_denied("10.0.99.0/24")?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TcpCidrFilter {
+    allowed: Vec<Cidr>,
+    denied: Vec<Cidr>,
+}
+
+impl TcpCidrFilter {
+    /// Create a filter allowing any peer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow the peers of the network `cidr`, e.g. `10.0.0.0/8` or `fd00::/8`
+    pub fn with_allowed(mut self, cidr: &str) -> Result<Self> {
+        self.allowed.push(Cidr::parse(cidr)?);
+        Ok(self)
+    }
+
+    /// Deny the peers of the network `cidr`, e.g. `10.0.0.0/8` or `fd00::/8`
+    pub fn with_denied(mut self, cidr: &str) -> Result<Self> {
+        self.denied.push(Cidr::parse(cidr)?);
+        Ok(self)
+    }
+}
+
+impl TcpConnectionFilter for TcpCidrFilter {
+    fn allow(&self, peer: &SocketAddr) -> bool {
+        let ip = match peer.ip() {
+            IpAddr::V6(ip) => ip.to_ipv4_mapped().map_or(IpAddr::V6(ip), IpAddr::V4),
+            ip => ip,
+        };
+        if self.denied.iter().any(|cidr| cidr.contains(&ip)) {
+            return false;
+        }
+        self.allowed.is_empty() || self.allowed.iter().any(|cidr| cidr.contains(&ip))
+    }
+}
+
+/// Network in CIDR notation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Cidr {
+    network: IpAddr,
+    prefix_len: u32,
+}
+
+impl Cidr {
+    /// Parse `cidr`, e.g. `10.0.0.0/8`. A single address, e.g. `10.0.0.1`, is a
+    /// network of one address
+    fn parse(cidr: &str) -> Result<Self> {
+        let (network, prefix_len) = match cidr.split_once('/') {
+            Some((network, prefix_len)) => (network, Some(prefix_len)),
+            None => (cidr, None),
+        };
+        let network: IpAddr = network
+            .parse()
+            .map_err(|_| TransportError::InvalidAddress)?;
+        let max_len = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len
+                .parse()
+                .ok()
+                .filter(|len| *len <= max_len)
+                .ok_or(TransportError::InvalidAddress)?,
+            None => max_len,
+        };
+        Ok(Self {
+            network,
+            prefix_len,
+        })
+    }
+
+    /// Return true if `ip` belongs to that network
+    fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix_len).unwrap_or(0);
+                u32::from(network) & mask == u32::from(*ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix_len).unwrap_or(0);
+                u128::from(network) & mask == u128::from(*ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TcpCidrFilter, TcpConnectionFilter};
+    use core::str::FromStr;
+    use ockam_core::compat::net::SocketAddr;
+
+    fn peer(s: &str) -> SocketAddr {
+        SocketAddr::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn peers_are_matched_by_network() {
+        let filter = TcpCidrFilter::new()
+            .with_allowed("10.0.0.0/8")
+            .unwrap()
+            .with_allowed("fd00::/8")
+            .unwrap()
+            .with_denied("10.0.99.0/24")
+            .unwrap();
+
+        assert!(filter.allow(&peer("10.1.2.3:4000")));
+        assert!(filter.allow(&peer("[fd12::1]:4000")));
+        assert!(!filter.allow(&peer("10.0.99.1:4000")));
+        assert!(!filter.allow(&peer("192.168.1.1:4000")));
+        assert!(!filter.allow(&peer("[fe80::1]:4000")));
+
+        // IPv4 addresses mapped to IPv6 are matched as IPv4 addresses
+        assert!(filter.allow(&peer("[::ffff:10.1.2.3]:4000")));
+        assert!(!filter.allow(&peer("[::ffff:10.0.99.1]:4000")));
+    }
+
+    #[test]
+    fn any_peer_is_allowed_without_allowed_networks() {
+        let filter = TcpCidrFilter::new().with_denied("127.0.0.1").unwrap();
+        assert!(!filter.allow(&peer("127.0.0.1:4000")));
+        assert!(filter.allow(&peer("127.0.0.2:4000")));
+
+        let filter = TcpCidrFilter::new().with_allowed("0.0.0.0/0").unwrap();
+        assert!(filter.allow(&peer("8.8.8.8:53")));
+    }
+
+    #[test]
+    fn invalid_networks_are_rejected() {
+        assert!(TcpCidrFilter::new().with_allowed("10.0.0.0/33").is_err());
+        assert!(TcpCidrFilter::new().with_allowed("::/129").is_err());
+        assert!(TcpCidrFilter::new().with_allowed("not a network").is_err());
+        assert!(TcpCidrFilter::new().with_denied("10.0.0.0/x").is_err());
+    }
+}
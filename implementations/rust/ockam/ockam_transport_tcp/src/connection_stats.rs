@@ -0,0 +1,107 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Snapshot of the traffic of a TCP connection since it was opened,
+/// see [`TcpRegistry::connection_stats`](crate::TcpRegistry::connection_stats)
+///
+/// Byte counts include the length header of each frame, so that the bytes sent by
+/// one end of a connection match the bytes received by the other end.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionStats {
+    /// Number of bytes received
+    pub bytes_in: u64,
+    /// Number of bytes sent
+    pub bytes_out: u64,
+    /// Number of frames received, heartbeats included
+    pub messages_in: u64,
+    /// Number of frames sent, heartbeats included
+    pub messages_out: u64,
+}
+
+/// Traffic of all the open connections of a transport, and frames dropped by the
+/// transport policies since it was created, see
+/// [`TcpRegistry::transport_stats`](crate::TcpRegistry::transport_stats)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransportStats {
+    /// Number of open connections, counting each end of a connection within the transport
+    pub connections: u64,
+    /// Sum of the [`ConnectionStats::bytes_in`] of the open connections
+    pub bytes_in: u64,
+    /// Sum of the [`ConnectionStats::bytes_out`] of the open connections
+    pub bytes_out: u64,
+    /// Sum of the [`ConnectionStats::messages_in`] of the open connections
+    pub messages_in: u64,
+    /// Sum of the [`ConnectionStats::messages_out`] of the open connections
+    pub messages_out: u64,
+    /// Messages dropped by the [`TcpMailboxFullPolicy`](crate::TcpMailboxFullPolicy)
+    pub dropped_messages: u64,
+    /// Frames rejected because they did not match their checksum
+    pub corrupt_frames: u64,
+    /// Connections closed by the [`TcpDuplicateSessionPolicy`](crate::TcpDuplicateSessionPolicy)
+    pub duplicate_sessions: u64,
+}
+
+impl TransportStats {
+    /// Add the traffic of a connection to the totals
+    pub(crate) fn add_connection(&mut self, stats: ConnectionStats) {
+        self.connections += 1;
+        self.bytes_in += stats.bytes_in;
+        self.bytes_out += stats.bytes_out;
+        self.messages_in += stats.messages_in;
+        self.messages_out += stats.messages_out;
+    }
+}
+
+/// Counters shared by the sender worker and the receiver processor of a connection
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionCounters {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    messages_in: AtomicU64,
+    messages_out: AtomicU64,
+}
+
+impl ConnectionCounters {
+    /// Count a frame of `len` bytes, length header included, read from the connection
+    pub(crate) fn record_in(&self, len: usize) {
+        self.bytes_in.fetch_add(len as u64, Ordering::Relaxed);
+        self.messages_in.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a frame of `len` bytes, length header included, written to the connection
+    pub(crate) fn record_out(&self, len: usize) {
+        self.bytes_out.fetch_add(len as u64, Ordering::Relaxed);
+        self.messages_out.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> ConnectionStats {
+        ConnectionStats {
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            messages_in: self.messages_in.load(Ordering::Relaxed),
+            messages_out: self.messages_out.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_are_summed_per_direction() {
+        let counters = ConnectionCounters::default();
+        counters.record_in(10);
+        counters.record_in(5);
+        counters.record_out(7);
+
+        assert_eq!(
+            counters.snapshot(),
+            ConnectionStats {
+                bytes_in: 15,
+                bytes_out: 7,
+                messages_in: 2,
+                messages_out: 1,
+            }
+        );
+    }
+}
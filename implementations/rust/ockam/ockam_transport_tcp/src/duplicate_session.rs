@@ -0,0 +1,15 @@
+/// What a TCP connection does when its [`SessionId`](ockam_core::sessions::SessionId)
+/// is already used by another connection of the same transport, e.g. because of a
+/// misconfiguration
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TcpDuplicateSessionPolicy {
+    /// Accept the connection, as if its session was not in use
+    #[default]
+    Allow,
+    /// Accept the connection, but log a warning and count it in
+    /// [`TcpRegistry::get_duplicate_sessions`](crate::TcpRegistry::get_duplicate_sessions)
+    Flag,
+    /// Close the connection before any received message is forwarded, and count it in
+    /// [`TcpRegistry::get_duplicate_sessions`](crate::TcpRegistry::get_duplicate_sessions)
+    Reject,
+}
@@ -0,0 +1,67 @@
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::string::String;
+use ockam_core::Address;
+
+/// Number of events buffered for each subscriber of a [`TcpRegistry`](crate::TcpRegistry)
+/// before the slowest ones start missing events
+pub const TCP_EVENTS_CAPACITY: usize = 256;
+
+/// An event of the TCP connections of a transport, for live debugging,
+/// see [`TcpRegistry::subscribe_events`](crate::TcpRegistry::subscribe_events)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TcpEvent {
+    /// A connection was opened
+    ConnectionOpened {
+        /// Peer of the connection
+        peer: SocketAddr,
+    },
+    /// A connection was closed
+    ConnectionClosed {
+        /// Peer of the connection
+        peer: SocketAddr,
+    },
+    /// A frame was received
+    FrameReceived {
+        /// Peer of the connection
+        peer: SocketAddr,
+        /// Size of the frame, in bytes, without its length header
+        size: usize,
+    },
+    /// A heartbeat was received
+    Heartbeat {
+        /// Peer of the connection
+        peer: SocketAddr,
+    },
+    /// A received message could not be forwarded to its next hop
+    ForwardError {
+        /// Peer of the connection
+        peer: SocketAddr,
+        /// Reason of the failure
+        reason: String,
+    },
+}
+
+impl TcpEvent {
+    /// Peer of the connection the event happened on
+    pub fn peer(&self) -> SocketAddr {
+        match self {
+            TcpEvent::ConnectionOpened { peer }
+            | TcpEvent::ConnectionClosed { peer }
+            | TcpEvent::FrameReceived { peer, .. }
+            | TcpEvent::Heartbeat { peer }
+            | TcpEvent::ForwardError { peer, .. } => *peer,
+        }
+    }
+}
+
+/// Notified when the connections of a transport are opened and closed, e.g. to export
+/// connection metrics, see [`TcpRegistry::add_connection_listener`](crate::TcpRegistry::add_connection_listener)
+///
+/// Listeners are called by the receiver processors of the connections, and should return
+/// quickly, e.g. by updating a counter or sending the event to a channel.
+pub trait TcpConnectionListener: Send + Sync + 'static {
+    /// A connection to `peer` was opened, with its receiver processor at `address`
+    fn on_connect(&self, address: &Address, peer: SocketAddr);
+    /// The connection to `peer` whose receiver processor was at `address` was closed
+    fn on_disconnect(&self, address: &Address, peer: SocketAddr);
+}
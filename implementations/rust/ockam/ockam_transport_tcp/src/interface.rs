@@ -0,0 +1,81 @@
+use cfg_if::cfg_if;
+use ockam_core::compat::net::{IpAddr, SocketAddr};
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::TcpListener;
+use tracing::{debug, warn};
+
+/// Maximum number of pending connections of a listener bound to an interface, as
+/// used by [`TcpListener::bind`]
+const LISTEN_BACKLOG: i32 = 1024;
+
+/// Return the address of the network interface `name`, preferring an IPv4 address
+/// if `ipv4` is true, or an IPv6 one otherwise
+pub(crate) fn interface_address(name: &str, ipv4: bool) -> Result<IpAddr> {
+    let addresses: Vec<IpAddr> = if_addrs::get_if_addrs()
+        .map_err(TransportError::from)?
+        .into_iter()
+        .filter(|interface| interface.name == name)
+        .map(|interface| interface.ip())
+        .collect();
+
+    addresses
+        .iter()
+        .find(|ip| ip.is_ipv4() == ipv4)
+        .or_else(|| addresses.first())
+        .copied()
+        .ok_or_else(|| TransportError::InvalidAddress.into())
+}
+
+/// Bind a listener to the address of the network interface `name`, on the port of
+/// `addr`. On Linux, the socket is also bound to the interface itself, so that it
+/// only accepts the connections received by that interface
+pub(crate) fn bind_to_interface(addr: SocketAddr, name: &str) -> Result<TcpListener> {
+    let addr = SocketAddr::new(interface_address(name, addr.is_ipv4())?, addr.port());
+    debug!("Binding TcpListener to {} on interface {}", addr, name);
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))
+        .map_err(TransportError::from)?;
+
+    cfg_if! {
+        if #[cfg(unix)] {
+            socket.set_reuse_address(true).map_err(TransportError::from)?;
+        }
+    }
+
+    cfg_if! {
+        if #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))] {
+            // Binding to a device may require privileges, the address alone still
+            // restricts the listener to that interface's address
+            if let Err(e) = socket.bind_device(Some(name.as_bytes())) {
+                warn!("Failed to bind TcpListener to interface {}: {}", name, e);
+            }
+        }
+    }
+
+    socket.bind(&addr.into()).map_err(TransportError::from)?;
+    socket
+        .listen(LISTEN_BACKLOG)
+        .map_err(TransportError::from)?;
+    socket.set_nonblocking(true).map_err(TransportError::from)?;
+
+    Ok(TcpListener::from_std(socket.into()).map_err(TransportError::from)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::interface_address;
+
+    #[test]
+    fn unknown_interface_is_rejected() {
+        assert!(interface_address("not-an-interface", true).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn loopback_interface_is_resolved() {
+        let ip = interface_address("lo", true).unwrap();
+        assert!(ip.is_ipv4() && ip.is_loopback());
+    }
+}
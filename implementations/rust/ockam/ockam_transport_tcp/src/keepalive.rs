@@ -0,0 +1,94 @@
+use cfg_if::cfg_if;
+use core::time::Duration;
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use socket2::SockRef;
+use tokio::net::TcpStream;
+
+/// Keepalive probes sent over an idle TCP connection, so that a connection dropped
+/// on the way, e.g. by a NAT whose idle timeout elapsed, is detected before the next
+/// message is written to it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TcpKeepalive {
+    /// Time a connection stays idle before the first probe is sent
+    pub time: Duration,
+    /// Time between two probes
+    pub interval: Duration,
+    /// Number of unanswered probes after which the connection is closed.
+    /// Only applied on Unix systems
+    pub retries: u32,
+}
+
+impl Default for TcpKeepalive {
+    fn default() -> Self {
+        Self {
+            time: Duration::from_secs(300),
+            interval: Duration::from_secs(75),
+            retries: 2,
+        }
+    }
+}
+
+impl TcpKeepalive {
+    /// Constructor
+    pub fn new(time: Duration, interval: Duration, retries: u32) -> Self {
+        Self {
+            time,
+            interval,
+            retries,
+        }
+    }
+
+    /// Enable keepalive on `stream`, before it is split into halves
+    pub(crate) fn apply(&self, stream: &TcpStream) -> Result<()> {
+        let mut keepalive = socket2::TcpKeepalive::new()
+            .with_time(self.time)
+            .with_interval(self.interval);
+
+        cfg_if! {
+            if #[cfg(unix)] {
+               keepalive = keepalive.with_retries(self.retries);
+            }
+        }
+
+        SockRef::from(stream)
+            .set_tcp_keepalive(&keepalive)
+            .map_err(TransportError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TcpKeepalive;
+    use crate::TcpSendWorker;
+    use core::time::Duration;
+    use socket2::SockRef;
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[tokio::test]
+    async fn keepalive_is_enabled_on_the_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        assert!(!SockRef::from(&server).keepalive().unwrap());
+
+        let keepalive = TcpKeepalive::new(Duration::from_secs(30), Duration::from_secs(10), 3);
+        keepalive.apply(&server).unwrap();
+        assert!(SockRef::from(&server).keepalive().unwrap());
+        drop(client);
+    }
+
+    #[tokio::test]
+    async fn keepalive_is_enabled_on_outgoing_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let keepalive = TcpKeepalive::new(Duration::from_secs(30), Duration::from_secs(10), 3);
+        let (read_half, _write_half) =
+            TcpSendWorker::connect(listener.local_addr().unwrap(), &keepalive)
+                .await
+                .unwrap();
+        assert!(SockRef::from(read_half.as_ref()).keepalive().unwrap());
+    }
+}
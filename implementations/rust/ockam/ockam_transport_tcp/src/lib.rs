@@ -25,15 +25,43 @@ extern crate core;
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+mod checksum;
+mod compression;
+mod connection_filter;
+mod connection_stats;
+mod duplicate_session;
+mod events;
+mod interface;
+mod keepalive;
+mod local_info;
+mod mailbox_full;
+mod ordering;
+mod peer_address;
 mod portal;
+mod reconnect;
 mod registry;
 mod transport;
 mod trust_options;
 
+pub use connection_filter::*;
+pub use connection_stats::{ConnectionStats, TransportStats};
+pub use duplicate_session::*;
+pub use events::*;
+pub use interface::*;
+pub use keepalive::*;
+pub use local_info::*;
+pub use mailbox_full::*;
+pub use ordering::*;
+pub use peer_address::*;
 pub use portal::*;
+pub use reconnect::*;
 pub use registry::*;
 pub use transport::*;
 pub use trust_options::*;
 
+/// The version of `rustls` expected by [`TcpTransport::connect_tls`] and
+/// [`TcpTransport::listen_tls`]
+pub use tokio_rustls::rustls;
+
 mod workers;
 pub(crate) use workers::*;
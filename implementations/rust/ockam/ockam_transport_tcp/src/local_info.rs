@@ -0,0 +1,175 @@
+use core::fmt;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::{string::String, sync::Arc, vec::Vec};
+use ockam_core::sessions::SESSION_ID_IDENTIFIER;
+use ockam_core::{Decodable, Encodable, LocalInfo, Result, TransportMessage};
+use ockam_transport_core::TransportError;
+use tracing::warn;
+
+/// Produces additional [`LocalInfo`] for the messages received by a TCP connection
+///
+/// Producers are configured with the connection and listener trust options, they are run
+/// for every inbound message and their output is attached to the forwarded
+/// [`LocalMessage`](ockam_core::LocalMessage) next to the [`SessionId`](ockam_core::sessions::SessionId)
+/// (if present).
+pub trait TcpLocalInfoProducer: Send + Sync + 'static {
+    /// Return the [`LocalInfo`] to attach to a message received from `peer`, if any
+    fn produce(&self, peer: &SocketAddr, msg: &TransportMessage) -> Result<Option<LocalInfo>>;
+}
+
+/// Ordered list of [`TcpLocalInfoProducer`]s of a TCP connection
+#[derive(Clone, Default)]
+pub(crate) struct LocalInfoProducers(Vec<Arc<dyn TcpLocalInfoProducer>>);
+
+impl LocalInfoProducers {
+    pub(crate) fn push(&mut self, producer: Arc<dyn TcpLocalInfoProducer>) {
+        self.0.push(producer)
+    }
+
+    /// Run all producers and append their output to `local_info`.
+    /// A failing producer is skipped, so that it can't stall the connection
+    pub(crate) fn produce(
+        &self,
+        peer: &SocketAddr,
+        msg: &TransportMessage,
+        local_info: &mut Vec<LocalInfo>,
+    ) {
+        for producer in &self.0 {
+            match producer.produce(peer, msg) {
+                Ok(Some(info)) => local_info.push(info),
+                Ok(None) => {}
+                Err(err) => warn!("Failed to produce LocalInfo for {}: {}", peer, err),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for LocalInfoProducers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LocalInfoProducers({})", self.0.len())
+    }
+}
+
+/// Types of [`LocalInfo`] carried across a TCP connection
+///
+/// The [`LocalInfo`] of an allowed type attached to a message sent by the connection is
+/// serialized in its frame, and reattached to the message by the receiving connection, after
+/// the [`LocalInfo`] it produces itself. Any other [`LocalInfo`] is stripped, on both sides.
+/// Since that changes the frames format, either both sides of the connection allow some
+/// types, or none does.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LocalInfoPassthrough(Vec<String>);
+
+impl LocalInfoPassthrough {
+    pub(crate) fn push(&mut self, type_identifier: String) {
+        self.0.push(type_identifier)
+    }
+
+    /// Return true if the frames carry [`LocalInfo`]
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    /// Return the [`LocalInfo`] of `local_info` whose type is allowed, or `None` if the
+    /// frames don't carry [`LocalInfo`]
+    pub(crate) fn select(&self, local_info: &[LocalInfo]) -> Option<Vec<LocalInfo>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let selected = local_info
+            .iter()
+            .filter(|info| self.0.iter().any(|t| t == info.type_identifier()))
+            .cloned()
+            .collect();
+        Some(selected)
+    }
+}
+
+/// Remove the [`SessionIdLocalInfo`](ockam_core::sessions::SessionIdLocalInfo) from the
+/// [`LocalInfo`] forwarded by a peer, since only the receiving connection attaches the
+/// session of its messages. Return true if there was any
+pub(crate) fn strip_session_id(local_info: &mut Vec<LocalInfo>) -> bool {
+    let len = local_info.len();
+    local_info.retain(|info| info.type_identifier() != SESSION_ID_IDENTIFIER);
+    local_info.len() != len
+}
+
+/// Serialize `local_info`, prefixed by its length encoded as a big-endian 16-bit unsigned integer
+pub(crate) fn encode_frame_local_info(local_info: &[LocalInfo]) -> Result<Vec<u8>> {
+    let encoded = local_info
+        .encode()
+        .map_err(|_| TransportError::SendBadMessage)?;
+    if encoded.len() > u16::MAX as usize {
+        return Err(TransportError::SendBadMessage.into());
+    }
+    let mut buf = (encoded.len() as u16).to_be_bytes().to_vec();
+    buf.extend_from_slice(&encoded);
+    Ok(buf)
+}
+
+/// Split the [`LocalInfo`] serialized by [`encode_frame_local_info`] off the start of `buf`
+pub(crate) fn decode_frame_local_info(buf: &[u8]) -> Result<(Vec<LocalInfo>, &[u8])> {
+    if buf.len() < 2 {
+        return Err(TransportError::RecvBadMessage.into());
+    }
+    let (len, buf) = buf.split_at(2);
+    let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+    if buf.len() < len {
+        return Err(TransportError::RecvBadMessage.into());
+    }
+    let (local_info, buf) = buf.split_at(len);
+    let local_info =
+        Vec::<LocalInfo>::decode(local_info).map_err(|_| TransportError::RecvBadMessage)?;
+    Ok((local_info, buf))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        decode_frame_local_info, encode_frame_local_info, strip_session_id, LocalInfoPassthrough,
+    };
+    use ockam_core::sessions::{SessionIdLocalInfo, Sessions};
+    use ockam_core::LocalInfo;
+
+    #[test]
+    fn only_allowed_types_are_selected() {
+        let trace = LocalInfo::new("TRACE_ID".into(), b"trace-1".to_vec());
+        let tenant = LocalInfo::new("TENANT".into(), b"tenant-1".to_vec());
+
+        let mut passthrough = LocalInfoPassthrough::default();
+        assert_eq!(passthrough.select(&[trace.clone()]), None);
+
+        passthrough.push("TRACE_ID".into());
+        assert_eq!(
+            passthrough.select(&[tenant.clone(), trace.clone()]),
+            Some(vec![trace])
+        );
+        assert_eq!(passthrough.select(&[tenant]), Some(vec![]));
+    }
+
+    #[test]
+    fn forwarded_session_id_is_stripped() {
+        let trace = LocalInfo::new("TRACE_ID".into(), b"trace-1".to_vec());
+        let session = SessionIdLocalInfo::new(Sessions::default().generate_session_id())
+            .to_local_info()
+            .unwrap();
+
+        let mut local_info = vec![session, trace.clone()];
+        assert!(strip_session_id(&mut local_info));
+        assert_eq!(local_info, vec![trace.clone()]);
+        assert!(!strip_session_id(&mut local_info));
+        assert_eq!(local_info, vec![trace]);
+    }
+
+    #[test]
+    fn local_info_is_split_off_the_frame() {
+        let trace = LocalInfo::new("TRACE_ID".into(), b"trace-1".to_vec());
+        let mut frame = encode_frame_local_info(&[trace.clone()]).unwrap();
+        frame.extend_from_slice(b"message");
+
+        let (local_info, rest) = decode_frame_local_info(&frame).unwrap();
+        assert_eq!(local_info, vec![trace]);
+        assert_eq!(rest, b"message");
+        assert!(decode_frame_local_info(&frame[..4]).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+use core::time::Duration;
+
+/// What a TCP connection does with a received message when the mailbox of the
+/// worker it is forwarded to is full
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TcpMailboxFullPolicy {
+    /// Wait until the mailbox has room for the message. No more messages are read
+    /// from the connection in the meantime, which applies backpressure to the peer
+    #[default]
+    Block,
+    /// Wait at most the given duration for the mailbox to have room for the message,
+    /// then drop it. Dropped messages are counted by
+    /// [`TcpRegistry::get_dropped_messages`](crate::TcpRegistry::get_dropped_messages)
+    BlockWithTimeout(Duration),
+    /// Drop the message right away. Dropped messages are counted by
+    /// [`TcpRegistry::get_dropped_messages`](crate::TcpRegistry::get_dropped_messages)
+    Drop,
+    /// Drop the message and close the connection
+    Close,
+}
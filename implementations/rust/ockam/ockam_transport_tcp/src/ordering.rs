@@ -0,0 +1,418 @@
+use core::fmt;
+use core::future::Future;
+use core::time::Duration;
+use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::rand::random;
+use ockam_core::compat::sync::{Arc, Mutex, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{LocalMessage, Result};
+use ockam_transport_core::TransportError;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::time::Instant;
+use tracing::warn;
+
+/// Sequence number used for frames that are not part of the ordered stream (heartbeats)
+pub(crate) const UNORDERED_SEQUENCE_NUMBER: u64 = 0;
+
+/// Time given to the peer of a connection in strict ordering mode to complete
+/// the handshake telling which ordered stream the connection belongs to
+pub(crate) const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum number of ordered streams a listener keeps track of. Streams which are not
+/// used by any connection anymore are forgotten first to make room for new ones
+const MAX_ORDERED_STREAMS: usize = 1024;
+
+/// Time after which the frames missing in front of buffered frames are given up on
+const GAP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Ordering guarantees of the messages received by TCP connections
+#[derive(Clone, Default)]
+pub enum TcpOrdering {
+    /// Messages are delivered in the order they are received on a given connection,
+    /// no ordering is guaranteed across connections
+    #[default]
+    BestEffort,
+    /// Every frame carries a sequence number and messages are delivered in order,
+    /// including across the connections created with the same options (e.g. after
+    /// a reconnection). Both sides of the connection must use that mode.
+    ///
+    /// When a connection is established, its initiator sends the identifier of its
+    /// ordered stream, so that a listener keeps the messages of each peer in order
+    /// independently of the other peers. If the listener doesn't know the stream
+    /// anymore, e.g. because it was restarted, both sides start its sequence over.
+    Strict(StrictOrdering),
+}
+
+impl TcpOrdering {
+    /// Strict ordering, buffering at most `max_buffered` out-of-order messages
+    pub fn strict(max_buffered: usize) -> Self {
+        Self::Strict(StrictOrdering::new(max_buffered))
+    }
+
+    /// Ordering of a connection initiated with these options. In strict mode, the
+    /// ordered stream of the connection is agreed on with the peer before any frame
+    /// is exchanged:
+    ///  - the initiator sends the identifier of the stream, and whether it already
+    ///    sent frames on it, as a big-endian 64-bit unsigned integer and a byte
+    ///  - the listener replies with a byte telling if it knew the stream
+    pub(crate) async fn initiate<R, W>(
+        &self,
+        read_half: &mut R,
+        write_half: &mut W,
+    ) -> Result<ConnectionOrdering>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let ordering = match self {
+            TcpOrdering::BestEffort => return Ok(ConnectionOrdering::BestEffort),
+            TcpOrdering::Strict(ordering) => ordering,
+        };
+        let (stream, _) = ordering.stream(ordering.stream_id)?;
+
+        let mut hello = stream.id.to_be_bytes().to_vec();
+        hello.push(stream.has_sent() as u8);
+        write_half
+            .write_all(&hello)
+            .await
+            .map_err(TransportError::from)?;
+        write_half.flush().await.map_err(TransportError::from)?;
+
+        let known = with_handshake_timeout(read_half.read_u8()).await?;
+        if known == 0 {
+            // The peer numbers the frames it sends from the start again
+            stream.restart_received();
+        }
+
+        Ok(ConnectionOrdering::Strict(stream))
+    }
+
+    /// Ordering of a connection accepted with these options, see [`TcpOrdering::initiate`]
+    pub(crate) async fn accept<R, W>(
+        &self,
+        read_half: &mut R,
+        write_half: &mut W,
+    ) -> Result<ConnectionOrdering>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let ordering = match self {
+            TcpOrdering::BestEffort => return Ok(ConnectionOrdering::BestEffort),
+            TcpOrdering::Strict(ordering) => ordering,
+        };
+
+        let stream_id = with_handshake_timeout(read_half.read_u64()).await?;
+        let resumed = with_handshake_timeout(read_half.read_u8()).await? != 0;
+        let (stream, created) = ordering.stream(stream_id)?;
+        if created && resumed {
+            // The frames the peer sent before this listener knew the stream are lost,
+            // the sequence resumes at the first frame received
+            stream.resume_received();
+        }
+
+        write_half
+            .write_all(&[!created as u8])
+            .await
+            .map_err(TransportError::from)?;
+        write_half.flush().await.map_err(TransportError::from)?;
+
+        Ok(ConnectionOrdering::Strict(stream))
+    }
+}
+
+/// Run a read of the ordering handshake, giving up after [`HANDSHAKE_TIMEOUT`]
+async fn with_handshake_timeout<T>(read: impl Future<Output = std::io::Result<T>>) -> Result<T> {
+    match tokio::time::timeout(HANDSHAKE_TIMEOUT, read).await {
+        Ok(res) => Ok(res.map_err(TransportError::from)?),
+        Err(_) => Err(TransportError::RecvBadMessage.into()),
+    }
+}
+
+impl fmt::Debug for TcpOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TcpOrdering::BestEffort => write!(f, "BestEffort"),
+            TcpOrdering::Strict(_) => write!(f, "Strict"),
+        }
+    }
+}
+
+/// State of the strict ordering mode, shared by all the connections using it
+///
+/// The connections initiated with the same options share one ordered stream. A
+/// listener keeps one ordered stream per peer, identified by the initiator of the
+/// connections, for as long as the listener options are used.
+#[derive(Clone)]
+pub struct StrictOrdering {
+    max_buffered: usize,
+    /// Identifier of the ordered stream of the connections initiated with these options
+    stream_id: u64,
+    streams: Arc<RwLock<BTreeMap<u64, OrderedStream>>>,
+}
+
+impl StrictOrdering {
+    fn new(max_buffered: usize) -> Self {
+        Self {
+            max_buffered,
+            stream_id: random(),
+            streams: Default::default(),
+        }
+    }
+
+    /// Return the ordered stream with the given identifier, and whether it was just created
+    ///
+    /// At most [`MAX_ORDERED_STREAMS`] streams are tracked, an error is returned when they
+    /// are all used by a connection
+    fn stream(&self, stream_id: u64) -> Result<(OrderedStream, bool)> {
+        let mut streams = self.streams.write().unwrap();
+        if let Some(stream) = streams.get(&stream_id) {
+            return Ok((stream.clone(), false));
+        }
+        if streams.len() >= MAX_ORDERED_STREAMS {
+            streams.retain(|_, stream| stream.is_used());
+            if streams.len() >= MAX_ORDERED_STREAMS {
+                warn!("Too many ordered streams, rejecting stream {}", stream_id);
+                return Err(TransportError::Capacity.into());
+            }
+        }
+        let stream = OrderedStream::new(stream_id, self.max_buffered);
+        streams.insert(stream_id, stream.clone());
+        Ok((stream, true))
+    }
+}
+
+/// Ordering of the messages exchanged over a single connection
+#[derive(Clone)]
+pub(crate) enum ConnectionOrdering {
+    BestEffort,
+    Strict(OrderedStream),
+}
+
+/// Sequence numbers of the messages exchanged with one peer, possibly over
+/// several connections
+#[derive(Clone)]
+pub(crate) struct OrderedStream {
+    id: u64,
+    state: Arc<Mutex<OrderedStreamState>>,
+}
+
+struct OrderedStreamState {
+    next_to_send: u64,
+    reorder_buffer: ReorderBuffer<LocalMessage>,
+}
+
+impl OrderedStream {
+    fn new(id: u64, max_buffered: usize) -> Self {
+        Self {
+            id,
+            state: Arc::new(Mutex::new(OrderedStreamState {
+                next_to_send: UNORDERED_SEQUENCE_NUMBER + 1,
+                reorder_buffer: ReorderBuffer::new(UNORDERED_SEQUENCE_NUMBER + 1, max_buffered),
+            })),
+        }
+    }
+
+    /// Return the sequence number of the next frame to send
+    pub(crate) fn next_sequence_number(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let sequence_number = state.next_to_send;
+        state.next_to_send += 1;
+        sequence_number
+    }
+
+    /// Return true if a connection still uses this stream
+    fn is_used(&self) -> bool {
+        // One reference is held by the streams of the ordering options
+        Arc::strong_count(&self.state) > 1
+    }
+
+    /// Return true if frames were already sent on this stream
+    fn has_sent(&self) -> bool {
+        self.state.lock().unwrap().next_to_send > UNORDERED_SEQUENCE_NUMBER + 1
+    }
+
+    /// Expect the received frames to be numbered from the start again, the buffered
+    /// frames are dropped
+    fn restart_received(&self) {
+        let mut state = self.state.lock().unwrap();
+        let max_buffered = state.reorder_buffer.max_buffered;
+        state.reorder_buffer = ReorderBuffer::new(UNORDERED_SEQUENCE_NUMBER + 1, max_buffered);
+    }
+
+    /// Deliver the received frames from the next one, whatever its sequence number
+    fn resume_received(&self) {
+        let mut state = self.state.lock().unwrap();
+        let max_buffered = state.reorder_buffer.max_buffered;
+        state.reorder_buffer = ReorderBuffer::resumed(max_buffered);
+    }
+
+    /// Buffer a received message and return the messages that can now be delivered, in order
+    pub(crate) fn reorder(&self, sequence_number: u64, msg: LocalMessage) -> Vec<LocalMessage> {
+        self.state
+            .lock()
+            .unwrap()
+            .reorder_buffer
+            .push(sequence_number, msg)
+    }
+}
+
+/// Bounded buffer delivering items in the order of their sequence numbers
+pub(crate) struct ReorderBuffer<T> {
+    next: u64,
+    /// False until the first item is pushed when the sequence number of the
+    /// next item is unknown
+    synchronized: bool,
+    buffered: BTreeMap<u64, T>,
+    max_buffered: usize,
+    /// Time since which the next item is missing while later items are buffered
+    gap_since: Option<Instant>,
+}
+
+impl<T> ReorderBuffer<T> {
+    pub(crate) fn new(first: u64, max_buffered: usize) -> Self {
+        Self {
+            next: first,
+            synchronized: true,
+            buffered: BTreeMap::new(),
+            max_buffered,
+            gap_since: None,
+        }
+    }
+
+    /// Buffer starting at the sequence number of the first item pushed
+    pub(crate) fn resumed(max_buffered: usize) -> Self {
+        Self {
+            next: 0,
+            synchronized: false,
+            buffered: BTreeMap::new(),
+            max_buffered,
+            gap_since: None,
+        }
+    }
+
+    /// Add an item and return all the items that can be delivered in order.
+    ///
+    /// Items older than the last delivered one are dropped. When the buffer is full,
+    /// or the missing items are still missing after [`GAP_TIMEOUT`], they are given up
+    /// on and the buffered ones are delivered.
+    pub(crate) fn push(&mut self, sequence_number: u64, item: T) -> Vec<T> {
+        self.push_at(sequence_number, item, Instant::now())
+    }
+
+    fn push_at(&mut self, sequence_number: u64, item: T, now: Instant) -> Vec<T> {
+        if !self.synchronized {
+            self.next = sequence_number;
+            self.synchronized = true;
+        }
+
+        if sequence_number < self.next {
+            warn!(
+                "Dropping frame {} which was already delivered or skipped",
+                sequence_number
+            );
+            return Vec::new();
+        }
+
+        self.buffered.insert(sequence_number, item);
+
+        let gap_timed_out = self
+            .gap_since
+            .map_or(false, |since| now.duration_since(since) >= GAP_TIMEOUT);
+        if self.buffered.len() > self.max_buffered || gap_timed_out {
+            if let Some(first) = self.buffered.keys().next() {
+                if *first > self.next {
+                    warn!("Skipping missing frames {} to {}", self.next, first - 1);
+                    self.next = *first;
+                }
+            }
+        }
+
+        let mut ready = Vec::new();
+        while let Some(item) = self.buffered.remove(&self.next) {
+            ready.push(item);
+            self.next += 1;
+        }
+
+        // The gap timer starts over each time the delivery makes progress
+        if self.buffered.is_empty() {
+            self.gap_since = None;
+        } else if self.gap_since.is_none() || !ready.is_empty() {
+            self.gap_since = Some(now);
+        }
+        ready
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ReorderBuffer, StrictOrdering, GAP_TIMEOUT, MAX_ORDERED_STREAMS};
+    use core::time::Duration;
+    use tokio::time::Instant;
+
+    #[test]
+    fn items_are_delivered_in_order() {
+        let mut buffer = ReorderBuffer::new(1, 10);
+        assert!(buffer.push(2, "b").is_empty());
+        assert!(buffer.push(3, "c").is_empty());
+        assert_eq!(buffer.push(1, "a"), vec!["a", "b", "c"]);
+        assert_eq!(buffer.push(4, "d"), vec!["d"]);
+    }
+
+    #[test]
+    fn old_items_are_dropped() {
+        let mut buffer = ReorderBuffer::new(1, 10);
+        assert_eq!(buffer.push(1, "a"), vec!["a"]);
+        assert!(buffer.push(1, "a").is_empty());
+    }
+
+    #[test]
+    fn resumed_buffer_starts_at_the_first_item() {
+        let mut buffer = ReorderBuffer::resumed(10);
+        assert_eq!(buffer.push(5, "e"), vec!["e"]);
+        assert!(buffer.push(7, "g").is_empty());
+        assert_eq!(buffer.push(6, "f"), vec!["f", "g"]);
+        assert!(buffer.push(4, "d").is_empty());
+    }
+
+    #[test]
+    fn gap_is_skipped_when_buffer_is_full() {
+        let mut buffer = ReorderBuffer::new(1, 2);
+        assert!(buffer.push(2, "b").is_empty());
+        assert!(buffer.push(3, "c").is_empty());
+        assert_eq!(buffer.push(4, "d"), vec!["b", "c", "d"]);
+        assert!(buffer.push(1, "a").is_empty());
+    }
+
+    #[test]
+    fn gap_is_skipped_after_the_gap_timeout() {
+        let start = Instant::now();
+        let mut buffer = ReorderBuffer::new(1, 10);
+        assert!(buffer.push_at(2, "b", start).is_empty());
+        assert!(buffer
+            .push_at(3, "c", start + GAP_TIMEOUT - Duration::from_millis(1))
+            .is_empty());
+        assert_eq!(
+            buffer.push_at(4, "d", start + GAP_TIMEOUT),
+            vec!["b", "c", "d"]
+        );
+        assert!(buffer.push_at(1, "a", start + GAP_TIMEOUT).is_empty());
+    }
+
+    #[test]
+    fn unused_streams_are_forgotten_past_the_limit() {
+        let ordering = StrictOrdering::new(10);
+        let mut used = Vec::new();
+        for stream_id in 0..MAX_ORDERED_STREAMS as u64 {
+            used.push(ordering.stream(stream_id).unwrap().0);
+        }
+        assert!(ordering.stream(u64::MAX).is_err());
+
+        // Streams which are still used are kept
+        used.pop();
+        let (_, created) = ordering.stream(u64::MAX).unwrap();
+        assert!(created);
+        let (_, created) = ordering.stream(0).unwrap();
+        assert!(!created);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::TcpLocalInfoProducer;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::{Decodable, Encodable, LocalInfo, LocalMessage, Result, TransportMessage};
+use ockam_transport_core::TransportError;
+
+/// PeerAddress LocalInfo unique Identifier
+pub const PEER_ADDRESS_IDENTIFIER: &str = "PEER_ADDRESS_IDENTIFIER";
+
+/// LocalInfo holding the address of the TCP peer a message was received from
+///
+/// It is attached to the messages received by the connections configured with
+/// [`TcpConnectionTrustOptions::with_peer_address_local_info`](crate::TcpConnectionTrustOptions::with_peer_address_local_info)
+/// or [`TcpListenerTrustOptions::with_peer_address_local_info`](crate::TcpListenerTrustOptions::with_peer_address_local_info)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeerAddressLocalInfo {
+    peer: SocketAddr,
+}
+
+impl PeerAddressLocalInfo {
+    /// Constructor
+    pub fn new(peer: SocketAddr) -> Self {
+        Self { peer }
+    }
+
+    /// Try to decode `PeerAddressLocalInfo` from general `LocalInfo`
+    pub fn from_local_info(value: &LocalInfo) -> Result<Self> {
+        if value.type_identifier() != PEER_ADDRESS_IDENTIFIER {
+            return Err(TransportError::InvalidAddress.into());
+        }
+
+        let peer = String::decode(value.data()).map_err(|_| TransportError::InvalidAddress)?;
+        let peer = peer.parse().map_err(|_| TransportError::InvalidAddress)?;
+        Ok(Self { peer })
+    }
+
+    /// Encode `PeerAddressLocalInfo` to general `LocalInfo`
+    pub fn to_local_info(&self) -> Result<LocalInfo> {
+        Ok(LocalInfo::new(
+            PEER_ADDRESS_IDENTIFIER.into(),
+            self.peer.to_string().encode()?,
+        ))
+    }
+
+    /// Find `PeerAddressLocalInfo` in a list of general `LocalInfo` of that `LocalMessage`
+    pub fn find_info(local_msg: &LocalMessage) -> Result<Self> {
+        Self::find_info_from_list(local_msg.local_info())
+    }
+
+    /// Find `PeerAddressLocalInfo` in a list of general `LocalInfo`
+    pub fn find_info_from_list(local_info: &[LocalInfo]) -> Result<Self> {
+        if let Some(local_info) = local_info
+            .iter()
+            .find(|x| x.type_identifier() == PEER_ADDRESS_IDENTIFIER)
+        {
+            Self::from_local_info(local_info)
+        } else {
+            Err(TransportError::InvalidAddress.into())
+        }
+    }
+}
+
+impl PeerAddressLocalInfo {
+    /// Address of the TCP peer
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+}
+
+/// Attaches a [`PeerAddressLocalInfo`] to every received message
+pub(crate) struct PeerAddressProducer;
+
+impl TcpLocalInfoProducer for PeerAddressProducer {
+    fn produce(&self, peer: &SocketAddr, _msg: &TransportMessage) -> Result<Option<LocalInfo>> {
+        Ok(Some(PeerAddressLocalInfo::new(*peer).to_local_info()?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PeerAddressLocalInfo, PEER_ADDRESS_IDENTIFIER};
+    use ockam_core::{Encodable, LocalInfo};
+
+    #[test]
+    fn peer_address_round_trips() {
+        let info = PeerAddressLocalInfo::new("192.168.1.10:41000".parse().unwrap());
+        let local_info = vec![
+            LocalInfo::new("TRACE_ID".into(), b"trace-1".to_vec()),
+            info.to_local_info().unwrap(),
+        ];
+        assert_eq!(
+            PeerAddressLocalInfo::find_info_from_list(&local_info).unwrap(),
+            info
+        );
+
+        let malformed = LocalInfo::new(
+            PEER_ADDRESS_IDENTIFIER.into(),
+            "not an address".to_string().encode().unwrap(),
+        );
+        assert!(PeerAddressLocalInfo::from_local_info(&malformed).is_err());
+    }
+}
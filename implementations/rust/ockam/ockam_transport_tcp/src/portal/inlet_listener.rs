@@ -1,11 +1,12 @@
-use crate::{TcpPortalWorker, TcpRegistry};
+use crate::portal::AcceptRateLimiter;
+use crate::{TcpInletRateLimit, TcpInletRouteGroup, TcpPortalWorker, TcpRegistry};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::{
     async_trait,
     compat::{boxed::Box, sync::Arc},
     DenyAll,
 };
-use ockam_core::{Address, IncomingAccessControl, Mailboxes, Processor, Result, Route};
+use ockam_core::{Address, IncomingAccessControl, Mailboxes, Processor, Result};
 use ockam_node::{Context, ProcessorBuilder};
 use ockam_transport_core::TransportError;
 use tokio::net::TcpListener;
@@ -19,8 +20,9 @@ use tracing::{debug, error};
 pub(crate) struct TcpInletListenProcessor {
     registry: TcpRegistry,
     inner: TcpListener,
-    outlet_listener_route: Route,
+    outlet_routes: Arc<TcpInletRouteGroup>,
     access_control: Arc<dyn IncomingAccessControl>,
+    rate_limiter: Option<AcceptRateLimiter>,
 }
 
 impl TcpInletListenProcessor {
@@ -28,9 +30,10 @@ impl TcpInletListenProcessor {
     pub(crate) async fn start(
         ctx: &Context,
         registry: TcpRegistry,
-        outlet_listener_route: Route,
+        outlet_routes: Arc<TcpInletRouteGroup>,
         addr: SocketAddr,
         access_control: Arc<dyn IncomingAccessControl>,
+        rate_limit: Option<TcpInletRateLimit>,
     ) -> Result<(Address, SocketAddr)> {
         let waddr = Address::random_tagged("TcpInletListenProcessor");
 
@@ -46,8 +49,9 @@ impl TcpInletListenProcessor {
         let processor = Self {
             registry,
             inner,
-            outlet_listener_route,
+            outlet_routes,
             access_control: access_control.clone(),
+            rate_limiter: rate_limit.map(AcceptRateLimiter::new),
         };
 
         ProcessorBuilder::with_mailboxes(
@@ -79,13 +83,19 @@ impl Processor for TcpInletListenProcessor {
     }
 
     async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        // Connections past the budget stay in the listen backlog until a token is available
+        if let Some(rate_limiter) = &mut self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
         let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
+        let outlet_listener_route = self.outlet_routes.select(peer.ip());
         TcpPortalWorker::start_new_inlet(
             ctx,
             self.registry.clone(),
             stream,
             peer,
-            self.outlet_listener_route.clone(),
+            outlet_listener_route,
             self.access_control.clone(),
         )
         .await?;
@@ -0,0 +1,148 @@
+use core::time::Duration;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use tokio::time::Instant;
+
+/// Longest time an accept is delayed at once, the budget is checked again afterwards
+const MAX_ACCEPT_DELAY: Duration = Duration::from_secs(60);
+
+/// Limit on the rate at which a TCP Portal Inlet accepts new connections
+///
+/// The limit is a token bucket: up to `burst` connections can be accepted at once,
+/// and the bucket is refilled at `connections_per_second`. Connections past the
+/// budget are left in the listen backlog until a token is available.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TcpInletRateLimit {
+    connections_per_second: f64,
+    burst: u32,
+}
+
+impl TcpInletRateLimit {
+    /// Accept at most `connections_per_second` on average, and at most `burst` at once
+    ///
+    /// The rate must be a positive, finite number
+    pub fn new(connections_per_second: f64, burst: u32) -> Result<Self> {
+        if !connections_per_second.is_finite() || connections_per_second <= 0.0 {
+            return Err(Error::new(
+                Origin::Transport,
+                Kind::Invalid,
+                format!(
+                    "invalid inlet rate limit of {} connections per second",
+                    connections_per_second
+                ),
+            ));
+        }
+        Ok(Self {
+            connections_per_second,
+            burst: burst.max(1),
+        })
+    }
+
+    /// Average number of connections accepted per second
+    pub fn connections_per_second(&self) -> f64 {
+        self.connections_per_second
+    }
+
+    /// Maximum number of connections accepted at once
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+}
+
+/// Token bucket state of a [`TcpInletRateLimit`]
+pub(crate) struct AcceptRateLimiter {
+    limit: TcpInletRateLimit,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl AcceptRateLimiter {
+    pub(crate) fn new(limit: TcpInletRateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.connections_per_second)
+            .min(self.limit.burst as f64);
+        self.last_refill = now;
+    }
+
+    /// Take a token if one is available, otherwise return how long to wait for the next one,
+    /// at most [`MAX_ACCEPT_DELAY`]
+    pub(crate) fn try_acquire(&mut self, now: Instant) -> core::result::Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - self.tokens;
+            let delay =
+                (missing / self.limit.connections_per_second).min(MAX_ACCEPT_DELAY.as_secs_f64());
+            Err(Duration::from_secs_f64(delay))
+        }
+    }
+
+    /// Wait until a connection can be accepted
+    pub(crate) async fn acquire(&mut self) {
+        while let Err(delay) = self.try_acquire(Instant::now()) {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AcceptRateLimiter, TcpInletRateLimit, MAX_ACCEPT_DELAY};
+    use core::time::Duration;
+    use tokio::time::Instant;
+
+    #[test]
+    fn burst_is_accepted_then_throttled() {
+        let mut limiter = AcceptRateLimiter::new(TcpInletRateLimit::new(2.0, 3).unwrap());
+        let now = Instant::now();
+        assert!(limiter.try_acquire(now).is_ok());
+        assert!(limiter.try_acquire(now).is_ok());
+        assert!(limiter.try_acquire(now).is_ok());
+
+        let delay = limiter.try_acquire(now).unwrap_err();
+        assert_eq!(delay, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn tokens_are_refilled_over_time() {
+        let mut limiter = AcceptRateLimiter::new(TcpInletRateLimit::new(2.0, 1).unwrap());
+        let now = Instant::now();
+        assert!(limiter.try_acquire(now).is_ok());
+        assert!(limiter.try_acquire(now).is_err());
+        assert!(limiter
+            .try_acquire(now + Duration::from_millis(250))
+            .is_err());
+        assert!(limiter
+            .try_acquire(now + Duration::from_millis(500))
+            .is_ok());
+    }
+
+    #[test]
+    fn invalid_rates_are_rejected() {
+        assert!(TcpInletRateLimit::new(0.0, 1).is_err());
+        assert!(TcpInletRateLimit::new(-1.0, 1).is_err());
+        assert!(TcpInletRateLimit::new(f64::NAN, 1).is_err());
+        assert!(TcpInletRateLimit::new(f64::INFINITY, 1).is_err());
+    }
+
+    #[test]
+    fn delay_is_clamped_for_very_small_rates() {
+        let limit = TcpInletRateLimit::new(f64::MIN_POSITIVE, 1).unwrap();
+        let mut limiter = AcceptRateLimiter::new(limit);
+        let now = Instant::now();
+        assert!(limiter.try_acquire(now).is_ok());
+        assert_eq!(limiter.try_acquire(now).unwrap_err(), MAX_ACCEPT_DELAY);
+    }
+}
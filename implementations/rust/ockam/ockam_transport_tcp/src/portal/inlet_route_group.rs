@@ -0,0 +1,166 @@
+use ockam_core::compat::collections::{HashMap, VecDeque};
+use ockam_core::compat::net::IpAddr;
+use ockam_core::compat::sync::Mutex;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result, Route};
+
+/// Number of client IP addresses a [`TcpInletRouteGroup`] remembers the route of,
+/// the addresses seen the longest ago are forgotten first
+pub const MAX_INLET_AFFINITY_ENTRIES: usize = 4096;
+
+/// How a [`TcpInletRouteGroup`] picks the outlet route serving a new connection
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TcpInletAffinity {
+    /// Spread the connections over the routes of the group in turn
+    #[default]
+    None,
+    /// Send the connections coming from the same client IP address to the same route,
+    /// for the last [`MAX_INLET_AFFINITY_ENTRIES`] addresses
+    ByClientIp,
+}
+
+/// Outlet routes load-balanced by a TCP Portal Inlet
+///
+/// Each new connection is forwarded to one of the routes of the group, picked
+/// according to the [`TcpInletAffinity`] of the group.
+#[derive(Debug)]
+pub struct TcpInletRouteGroup {
+    routes: Vec<Route>,
+    affinity: TcpInletAffinity,
+    state: Mutex<RouteGroupState>,
+}
+
+#[derive(Debug, Default)]
+struct RouteGroupState {
+    next: usize,
+    assigned: HashMap<IpAddr, usize>,
+    /// Client addresses of `assigned`, from the oldest to the most recent
+    assigned_order: VecDeque<IpAddr>,
+}
+
+impl TcpInletRouteGroup {
+    /// Create a group balancing connections over `routes`
+    ///
+    /// Fails if `routes` is empty.
+    pub fn new(routes: Vec<Route>, affinity: TcpInletAffinity) -> Result<Self> {
+        if routes.is_empty() {
+            return Err(Error::new(
+                Origin::Transport,
+                Kind::Invalid,
+                "an inlet route group needs at least one route",
+            ));
+        }
+        Ok(Self::new_unchecked(routes, affinity))
+    }
+
+    fn new_unchecked(routes: Vec<Route>, affinity: TcpInletAffinity) -> Self {
+        Self {
+            routes,
+            affinity,
+            state: Default::default(),
+        }
+    }
+
+    /// Routes of the group
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    /// Affinity mode of the group
+    pub fn affinity(&self) -> TcpInletAffinity {
+        self.affinity
+    }
+
+    /// Pick the route serving a new connection from `client_ip`
+    pub fn select(&self, client_ip: IpAddr) -> Route {
+        let mut state = self.state.lock().unwrap();
+        let index = match self.affinity {
+            TcpInletAffinity::None => state.next_index(self.routes.len()),
+            TcpInletAffinity::ByClientIp => match state.assigned.get(&client_ip) {
+                Some(index) => *index,
+                None => {
+                    let index = state.next_index(self.routes.len());
+                    state.assign(client_ip, index);
+                    index
+                }
+            },
+        };
+
+        self.routes[index].clone()
+    }
+}
+
+impl From<Route> for TcpInletRouteGroup {
+    fn from(route: Route) -> Self {
+        Self::new_unchecked(vec![route], TcpInletAffinity::None)
+    }
+}
+
+impl RouteGroupState {
+    fn next_index(&mut self, len: usize) -> usize {
+        let index = self.next % len;
+        self.next = self.next.wrapping_add(1);
+        index
+    }
+
+    fn assign(&mut self, client_ip: IpAddr, index: usize) {
+        if self.assigned.len() >= MAX_INLET_AFFINITY_ENTRIES {
+            if let Some(oldest) = self.assigned_order.pop_front() {
+                self.assigned.remove(&oldest);
+            }
+        }
+        self.assigned.insert(client_ip, index);
+        self.assigned_order.push_back(client_ip);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TcpInletAffinity, TcpInletRouteGroup, MAX_INLET_AFFINITY_ENTRIES};
+    use ockam_core::compat::net::{IpAddr, Ipv4Addr};
+    use ockam_core::route;
+
+    fn group(affinity: TcpInletAffinity) -> TcpInletRouteGroup {
+        TcpInletRouteGroup::new(vec![route!["outlet_a"], route!["outlet_b"]], affinity).unwrap()
+    }
+
+    const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    const OTHER_CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    #[test]
+    fn a_group_needs_a_route() {
+        assert!(TcpInletRouteGroup::new(vec![], TcpInletAffinity::None).is_err());
+    }
+
+    #[test]
+    fn connections_are_spread_without_affinity() {
+        let group = group(TcpInletAffinity::None);
+        assert_eq!(group.select(CLIENT), route!["outlet_a"]);
+        assert_eq!(group.select(CLIENT), route!["outlet_b"]);
+        assert_eq!(group.select(CLIENT), route!["outlet_a"]);
+    }
+
+    #[test]
+    fn connections_from_the_same_ip_stick_to_one_route() {
+        let group = group(TcpInletAffinity::ByClientIp);
+        assert_eq!(group.select(CLIENT), route!["outlet_a"]);
+        assert_eq!(group.select(OTHER_CLIENT), route!["outlet_b"]);
+        assert_eq!(group.select(CLIENT), route!["outlet_a"]);
+        assert_eq!(group.select(OTHER_CLIENT), route!["outlet_b"]);
+    }
+
+    #[test]
+    fn the_oldest_client_ips_are_forgotten() {
+        let group = group(TcpInletAffinity::ByClientIp);
+        assert_eq!(group.select(CLIENT), route!["outlet_a"]);
+        for i in 0..MAX_INLET_AFFINITY_ENTRIES as u32 {
+            group.select(IpAddr::V4(Ipv4Addr::from(0x0b00_0000 + i)));
+        }
+        assert_eq!(
+            group.state.lock().unwrap().assigned.len(),
+            MAX_INLET_AFFINITY_ENTRIES
+        );
+        assert!(!group.state.lock().unwrap().assigned.contains_key(&CLIENT));
+    }
+}
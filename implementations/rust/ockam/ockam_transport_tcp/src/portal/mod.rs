@@ -1,11 +1,21 @@
 mod inlet_listener;
+mod inlet_rate_limit;
+mod inlet_route_group;
+mod outlet_coalescing;
+mod outlet_framing;
 mod outlet_listener;
+mod outlet_options;
 mod portal_message;
 mod portal_receiver;
 mod portal_worker;
 
 pub(crate) use inlet_listener::*;
+pub use inlet_rate_limit::*;
+pub use inlet_route_group::*;
+pub use outlet_coalescing::*;
+pub use outlet_framing::*;
 pub(crate) use outlet_listener::*;
+pub use outlet_options::*;
 pub use portal_message::*;
 pub(crate) use portal_receiver::*;
 pub(crate) use portal_worker::*;
@@ -0,0 +1,104 @@
+use crate::portal::portal_message::MAX_PAYLOAD_SIZE;
+use core::time::Duration;
+use ockam_core::compat::vec::Vec;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::time::Instant;
+
+/// Coalescing of the data a TCP Portal Outlet reads from its peer before relaying it
+/// to the Inlet
+///
+/// Like Nagle's algorithm, small writes of the peer are held back and relayed
+/// together: once some data is read, the Outlet keeps reading until `max_size` bytes
+/// are buffered or `window` elapses, whichever comes first, and relays everything
+/// buffered in as few payloads as possible. This trades up to `window` of latency
+/// for fewer, larger frames when the peer sends bursts of small writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TcpOutletCoalescing {
+    window: Duration,
+    max_size: usize,
+}
+
+impl TcpOutletCoalescing {
+    /// Hold the data read from the peer for up to `window`, or until `max_size` bytes
+    /// are buffered. `max_size` is capped to the maximum size of a portal payload.
+    pub fn new(window: Duration, max_size: usize) -> Self {
+        Self {
+            window,
+            max_size: max_size.clamp(1, MAX_PAYLOAD_SIZE),
+        }
+    }
+
+    /// Maximum time data is held before being relayed
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Number of buffered bytes relayed without waiting for the end of the window
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+}
+
+/// Keep reading into `buf`, which holds the first data read, until it reaches the
+/// maximum size of `coalescing` or its window elapses. The end of the stream and
+/// read errors stop the coalescing, and are reported by the next read.
+pub(crate) async fn coalesce<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    coalescing: TcpOutletCoalescing,
+) {
+    let deadline = Instant::now() + coalescing.window;
+    while buf.len() < coalescing.max_size {
+        let remaining = (coalescing.max_size - buf.len()) as u64;
+        let mut limited = (&mut *reader).take(remaining);
+        match tokio::time::timeout_at(deadline, limited.read_buf(buf)).await {
+            Ok(Ok(len)) if len > 0 => {}
+            // Window elapsed, end of the stream, or read error
+            _ => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{coalesce, TcpOutletCoalescing};
+    use core::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn bursty_output_is_coalesced_within_the_window() {
+        let (mut backend, mut outlet) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            for chunk in [b"ab", b"cd", b"ef", b"gh"] {
+                backend.write_all(chunk).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            // Written after the window, so relayed separately
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            backend.write_all(b"ij").await.unwrap();
+        });
+
+        let coalescing = TcpOutletCoalescing::new(Duration::from_millis(300), 1024);
+        let mut buf = vec![];
+        outlet.read_buf(&mut buf).await.unwrap();
+        coalesce(&mut outlet, &mut buf, coalescing).await;
+        assert_eq!(buf, b"abcdefgh");
+
+        buf.clear();
+        outlet.read_buf(&mut buf).await.unwrap();
+        coalesce(&mut outlet, &mut buf, coalescing).await;
+        assert_eq!(buf, b"ij");
+    }
+
+    #[tokio::test]
+    async fn coalescing_stops_at_the_maximum_size() {
+        let (mut backend, mut outlet) = tokio::io::duplex(1024);
+        backend.write_all(b"abcdef").await.unwrap();
+
+        let coalescing = TcpOutletCoalescing::new(Duration::from_secs(60), 4);
+        let mut buf = vec![];
+        (&mut outlet).take(1).read_buf(&mut buf).await.unwrap();
+        coalesce(&mut outlet, &mut buf, coalescing).await;
+        assert_eq!(buf, b"abcd");
+    }
+}
@@ -0,0 +1,111 @@
+use ockam_core::compat::vec::Vec;
+use ockam_transport_core::TransportError;
+
+/// Framing of the data relayed by a TCP Portal Outlet to its peer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TcpOutletFraming {
+    /// Relay the bytes received from the Inlet as they are
+    #[default]
+    Raw,
+    /// The peer speaks a protocol made of frames prefixed by their big-endian length.
+    /// Only complete frames are relayed, and the connection is closed as soon as a
+    /// malformed frame is received, before any of it reaches the peer.
+    LengthPrefixed {
+        /// Size of the length prefix, in bytes, between 1 and 8
+        prefix_len: usize,
+        /// Maximum length of a frame, excluding its prefix
+        max_frame_len: usize,
+    },
+}
+
+impl TcpOutletFraming {
+    /// Frames prefixed by a `prefix_len` bytes length, of at most `max_frame_len` bytes
+    pub fn length_prefixed(prefix_len: usize, max_frame_len: usize) -> Self {
+        Self::LengthPrefixed {
+            prefix_len: prefix_len.clamp(1, 8),
+            max_frame_len,
+        }
+    }
+}
+
+/// Reassemble and validate the frames relayed by an Outlet
+pub(crate) struct OutletFrameValidator {
+    prefix_len: usize,
+    max_frame_len: usize,
+    buffer: Vec<u8>,
+}
+
+impl OutletFrameValidator {
+    /// Return a validator for `framing`, or `None` if the data is relayed as is
+    pub(crate) fn new(framing: TcpOutletFraming) -> Option<Self> {
+        match framing {
+            TcpOutletFraming::Raw => None,
+            TcpOutletFraming::LengthPrefixed {
+                prefix_len,
+                max_frame_len,
+            } => Some(Self {
+                prefix_len: prefix_len.clamp(1, 8),
+                max_frame_len,
+                buffer: Vec::new(),
+            }),
+        }
+    }
+
+    /// Append `data` and return the complete frames it terminates, keeping the
+    /// trailing incomplete frame (if any) until more data is received
+    pub(crate) fn push(&mut self, data: &[u8]) -> Result<Vec<u8>, TransportError> {
+        self.buffer.extend_from_slice(data);
+
+        let mut complete = 0;
+        while self.buffer.len() - complete >= self.prefix_len {
+            let prefix = &self.buffer[complete..complete + self.prefix_len];
+            let frame_len = prefix
+                .iter()
+                .fold(0u64, |len, byte| (len << 8) | *byte as u64);
+            if frame_len > self.max_frame_len as u64 {
+                self.buffer.clear();
+                return Err(TransportError::Protocol);
+            }
+
+            let end = complete + self.prefix_len + frame_len as usize;
+            if end > self.buffer.len() {
+                break;
+            }
+            complete = end;
+        }
+
+        let rest = self.buffer.split_off(complete);
+        Ok(core::mem::replace(&mut self.buffer, rest))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{OutletFrameValidator, TcpOutletFraming};
+
+    #[test]
+    fn raw_framing_has_no_validator() {
+        assert!(OutletFrameValidator::new(TcpOutletFraming::Raw).is_none());
+    }
+
+    #[test]
+    fn frames_split_across_payloads_are_reassembled() {
+        let mut validator =
+            OutletFrameValidator::new(TcpOutletFraming::length_prefixed(2, 16)).unwrap();
+
+        assert_eq!(validator.push(&[0, 3, b'a']).unwrap(), Vec::<u8>::new());
+        assert_eq!(
+            validator.push(&[b'b', b'c', 0, 1, b'd', 0]).unwrap(),
+            vec![0, 3, b'a', b'b', b'c', 0, 1, b'd']
+        );
+        assert_eq!(validator.push(&[0]).unwrap(), vec![0, 0]);
+    }
+
+    #[test]
+    fn oversized_frame_is_rejected() {
+        let mut validator =
+            OutletFrameValidator::new(TcpOutletFraming::length_prefixed(2, 16)).unwrap();
+
+        assert!(validator.push(&[0, 17, b'a']).is_err());
+    }
+}
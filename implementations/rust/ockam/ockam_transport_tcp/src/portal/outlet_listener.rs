@@ -1,4 +1,4 @@
-use crate::{PortalMessage, TcpPortalWorker, TcpRegistry};
+use crate::{PortalMessage, TcpOutletOptions, TcpPortalWorker, TcpRegistry};
 use ockam_core::compat::sync::Arc;
 use ockam_core::{
     async_trait, Address, DenyAll, IncomingAccessControl, Mailboxes, Result, Routed, Worker,
@@ -17,6 +17,7 @@ pub(crate) struct TcpOutletListenWorker {
     registry: TcpRegistry,
     peer: SocketAddr,
     access_control: Arc<dyn IncomingAccessControl>,
+    options: TcpOutletOptions,
 }
 
 impl TcpOutletListenWorker {
@@ -25,11 +26,13 @@ impl TcpOutletListenWorker {
         registry: TcpRegistry,
         peer: SocketAddr,
         access_control: Arc<dyn IncomingAccessControl>,
+        options: TcpOutletOptions,
     ) -> Self {
         Self {
             registry,
             peer,
             access_control,
+            options,
         }
     }
 
@@ -39,8 +42,9 @@ impl TcpOutletListenWorker {
         address: Address,
         peer: SocketAddr,
         access_control: Arc<dyn IncomingAccessControl>,
+        options: TcpOutletOptions,
     ) -> Result<()> {
-        let worker = Self::new(registry, peer, access_control.clone());
+        let worker = Self::new(registry, peer, access_control.clone(), options);
         WorkerBuilder::with_mailboxes(
             Mailboxes::main(address, access_control, Arc::new(DenyAll)),
             worker,
@@ -87,8 +91,11 @@ impl Worker for TcpOutletListenWorker {
             self.peer,
             return_route.clone(),
             self.access_control.clone(),
+            self.options,
         )
         .await?;
+        self.registry
+            .add_outlet_connection(&ctx.address(), &address);
 
         debug!("Created Tcp Outlet at {}", &address);
 
@@ -0,0 +1,41 @@
+use crate::{TcpOutletCoalescing, TcpOutletFraming};
+use core::time::Duration;
+
+/// Options of a TCP Portal Outlet, see
+/// [`TcpTransport::create_outlet_with_options`](crate::TcpTransport::create_outlet_with_options)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TcpOutletOptions {
+    pub(crate) close_grace: Option<Duration>,
+    pub(crate) framing: TcpOutletFraming,
+    pub(crate) coalescing: Option<TcpOutletCoalescing>,
+}
+
+impl TcpOutletOptions {
+    /// Relay the data as is, and close the connection to the peer as soon as the
+    /// Inlet side disconnects
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// When the Inlet side disconnects, close the writing side of the connection to
+    /// the peer and let the peer send its remaining data for up to `close_grace`,
+    /// before closing the connection
+    pub fn with_close_grace(mut self, close_grace: Duration) -> Self {
+        self.close_grace = Some(close_grace);
+        self
+    }
+
+    /// Relay the data received from the Inlet according to `framing`.
+    /// See [`TcpOutletFraming`] for the available modes.
+    pub fn with_framing(mut self, framing: TcpOutletFraming) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Coalesce the data read from the peer into fewer, larger payloads for the Inlet.
+    /// See [`TcpOutletCoalescing`] for how the data is held back.
+    pub fn with_coalescing(mut self, coalescing: TcpOutletCoalescing) -> Self {
+        self.coalescing = Some(coalescing);
+        self
+    }
+}
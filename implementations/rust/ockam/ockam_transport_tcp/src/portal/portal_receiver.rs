@@ -1,5 +1,6 @@
+use crate::portal::outlet_coalescing::coalesce;
 use crate::portal::portal_message::MAX_PAYLOAD_SIZE;
-use crate::{PortalInternalMessage, PortalMessage, TcpRegistry};
+use crate::{PortalInternalMessage, PortalMessage, TcpOutletCoalescing, TcpRegistry};
 use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, Encodable, LocalMessage, Route, TransportMessage};
 use ockam_core::{route, Address, Processor, Result};
@@ -18,6 +19,7 @@ pub(crate) struct TcpPortalRecvProcessor {
     read_half: OwnedReadHalf,
     sender_address: Address,
     onward_route: Route,
+    coalescing: Option<TcpOutletCoalescing>,
 }
 
 impl TcpPortalRecvProcessor {
@@ -27,6 +29,7 @@ impl TcpPortalRecvProcessor {
         read_half: OwnedReadHalf,
         sender_address: Address,
         onward_route: Route,
+        coalescing: Option<TcpOutletCoalescing>,
     ) -> Self {
         Self {
             registry,
@@ -34,6 +37,7 @@ impl TcpPortalRecvProcessor {
             read_half,
             sender_address,
             onward_route,
+            coalescing,
         }
     }
 }
@@ -91,6 +95,10 @@ impl Processor for TcpPortalRecvProcessor {
             return Ok(false);
         }
 
+        if let Some(coalescing) = self.coalescing {
+            coalesce(&mut self.read_half, &mut self.buf, coalescing).await;
+        }
+
         // Loop just in case buf was extended (should not happen though)
         for chunk in self.buf.chunks(MAX_PAYLOAD_SIZE) {
             let msg = TransportMessage::v1(
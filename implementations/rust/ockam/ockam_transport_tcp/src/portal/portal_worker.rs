@@ -1,4 +1,7 @@
-use crate::{PortalInternalMessage, PortalMessage, TcpPortalRecvProcessor, TcpRegistry};
+use crate::{
+    OutletFrameValidator, PortalInternalMessage, PortalMessage, TcpOutletCoalescing,
+    TcpOutletOptions, TcpPortalRecvProcessor, TcpRegistry,
+};
 use core::time::Duration;
 use ockam_core::compat::{boxed::Box, net::SocketAddr, sync::Arc};
 use ockam_core::{
@@ -52,6 +55,9 @@ pub(crate) struct TcpPortalWorker {
     remote_route: Option<Route>,
     is_disconnecting: bool,
     type_name: TypeName,
+    close_grace: Option<Duration>,
+    frame_validator: Option<OutletFrameValidator>,
+    coalescing: Option<TcpOutletCoalescing>,
 }
 
 impl TcpPortalWorker {
@@ -72,6 +78,7 @@ impl TcpPortalWorker {
             Some(stream),
             TypeName::Inlet,
             access_control,
+            TcpOutletOptions::new(),
         )
         .await
     }
@@ -83,6 +90,7 @@ impl TcpPortalWorker {
         peer: SocketAddr,
         pong_route: Route,
         access_control: Arc<dyn IncomingAccessControl>,
+        options: TcpOutletOptions,
     ) -> Result<Address> {
         Self::start(
             ctx,
@@ -92,11 +100,13 @@ impl TcpPortalWorker {
             None,
             TypeName::Outlet,
             access_control,
+            options,
         )
         .await
     }
 
     /// Start a new `TcpPortalWorker`
+    #[allow(clippy::too_many_arguments)]
     async fn start(
         ctx: &Context,
         registry: TcpRegistry,
@@ -105,6 +115,7 @@ impl TcpPortalWorker {
         stream: Option<TcpStream>,
         type_name: TypeName,
         access_control: Arc<dyn IncomingAccessControl>,
+        options: TcpOutletOptions,
     ) -> Result<Address> {
         let internal_address = Address::random_tagged("TcpPortalWorker_internal");
         let remote_address = Address::random_tagged("TcpPortalWorker_remote");
@@ -135,6 +146,9 @@ impl TcpPortalWorker {
             receiver_address: receiver_address.clone(),
             is_disconnecting: false,
             type_name,
+            close_grace: options.close_grace,
+            frame_validator: OutletFrameValidator::new(options.framing),
+            coalescing: options.coalescing,
         };
 
         let internal_mailbox = Mailbox::new(
@@ -163,6 +177,8 @@ impl TcpPortalWorker {
 
 enum DisconnectionReason {
     FailedTx,
+    /// Data received from the other side was rejected before reaching the peer
+    Rejected,
     FailedRx,
     Remote,
 }
@@ -181,6 +197,7 @@ impl TcpPortalWorker {
                 rx,
                 self.internal_address.clone(),
                 onward_route,
+                self.coalescing,
             );
 
             let mailbox = Mailbox::new(
@@ -249,6 +266,41 @@ impl TcpPortalWorker {
         Ok(())
     }
 
+    /// Close our writing side of the connection and give the other end up to
+    /// `close_grace` to send its remaining data and close its side
+    async fn wait_for_backend_close(&mut self, ctx: &Context) {
+        let close_grace = match self.close_grace {
+            Some(close_grace) => close_grace,
+            None => return,
+        };
+
+        if let Some(tx) = &mut self.write_half {
+            if let Err(err) = tx.shutdown().await {
+                debug!("Failed to shutdown connection to {}: {}", self.peer, err);
+                return;
+            }
+        }
+
+        let step = Duration::from_millis(100);
+        let mut waited = Duration::ZERO;
+        while waited < close_grace
+            && self
+                .registry
+                .has_portal_receiver_processor(&self.receiver_address)
+        {
+            ctx.sleep(step).await;
+            waited += step;
+        }
+
+        debug!(
+            "{:?} at: {} waited {}ms for the connection to {} to be closed",
+            self.type_name,
+            self.internal_address,
+            waited.as_millis(),
+            self.peer
+        );
+    }
+
     /// Start the portal disconnection process
     async fn start_disconnection(
         &mut self,
@@ -258,7 +310,7 @@ impl TcpPortalWorker {
         self.is_disconnecting = true;
 
         match reason {
-            DisconnectionReason::FailedTx => {
+            DisconnectionReason::FailedTx | DisconnectionReason::Rejected => {
                 self.notify_remote_about_disconnection(ctx).await?;
             }
             DisconnectionReason::FailedRx => {
@@ -266,16 +318,24 @@ impl TcpPortalWorker {
                 self.stop_receiver(ctx).await?;
             }
             DisconnectionReason::Remote => {
+                self.wait_for_backend_close(ctx).await;
                 self.stop_receiver(ctx).await?;
             }
         }
 
         ctx.stop_worker(self.internal_address.clone()).await?;
 
-        info!(
-            "{:?} at: {} stopped due to connection drop",
-            self.type_name, self.internal_address
-        );
+        if let DisconnectionReason::Rejected = reason {
+            info!(
+                "{:?} at: {} stopped after rejecting data for peer {}",
+                self.type_name, self.internal_address, self.peer
+            );
+        } else {
+            info!(
+                "{:?} at: {} stopped due to connection drop",
+                self.type_name, self.internal_address
+            );
+        }
 
         Ok(())
     }
@@ -300,9 +360,10 @@ impl TcpPortalWorker {
         .await?;
 
         if self.write_half.is_none() {
-            let stream = TcpStream::connect(self.peer)
-                .await
-                .map_err(TransportError::from)?;
+            let stream = TcpStream::connect(self.peer).await.map_err(|e| {
+                self.registry.add_connection_error(self.peer, &e);
+                TransportError::from(e)
+            })?;
             let (rx, tx) = stream.into_split();
             self.write_half = Some(tx);
             self.read_half = Some(rx);
@@ -347,9 +408,29 @@ impl Worker for TcpPortalWorker {
         Ok(())
     }
 
-    async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
         self.registry.remove_portal_worker(&self.remote_address);
 
+        // The worker was stopped from the outside, e.g. when its outlet was deleted:
+        // let the other side know and close the connection
+        if !self.is_disconnecting {
+            if let Some(remote_route) = self.remote_route.take() {
+                let _ = ctx
+                    .send_from_address(
+                        remote_route,
+                        PortalMessage::Disconnect,
+                        self.remote_address.clone(),
+                    )
+                    .await;
+            }
+            if self
+                .registry
+                .has_portal_receiver_processor(&self.receiver_address)
+            {
+                let _ = ctx.stop_processor(self.receiver_address.clone()).await;
+            }
+        }
+
         Ok(())
     }
 
@@ -425,6 +506,24 @@ impl Worker for TcpPortalWorker {
 
                     match msg {
                         PortalMessage::Payload(payload) => {
+                            let payload = match &mut self.frame_validator {
+                                None => payload,
+                                Some(validator) => match validator.push(&payload) {
+                                    Ok(frames) => frames,
+                                    Err(_) => {
+                                        warn!(
+                                            "{:?} at: {} rejected a malformed frame for peer {}",
+                                            self.type_name, self.internal_address, self.peer
+                                        );
+                                        self.start_disconnection(
+                                            ctx,
+                                            DisconnectionReason::Rejected,
+                                        )
+                                        .await?;
+                                        return Ok(());
+                                    }
+                                },
+                            };
                             if let Some(tx) = &mut self.write_half {
                                 match tx.write_all(&payload).await {
                                     Ok(()) => {}
@@ -0,0 +1,185 @@
+use crate::workers::{Addresses, ConnectionOptions, TcpRecvProcessor, TcpSendWorker};
+use crate::{TcpConnectionTrustOptions, TcpRegistry};
+use core::time::Duration;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::rand::{self, Rng};
+use ockam_core::{async_trait, Result};
+use ockam_node::Context;
+use ockam_transport_core::TransportError;
+use tokio::net::tcp::OwnedWriteHalf;
+use tracing::{debug, info};
+
+/// Backoff between the attempts to re-establish a connection opened with
+/// [`TcpTransport::connect_reconnecting`](crate::TcpTransport::connect_reconnecting),
+/// once it was lost
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TcpReconnectPolicy {
+    /// Delay before the first attempt, doubled after every failed attempt
+    pub base: Duration,
+    /// Maximum delay between two attempts
+    pub max: Duration,
+    /// Maximum random delay added to every delay, so that the clients of a restarted
+    /// peer don't all reconnect at the same time
+    pub jitter: Duration,
+    /// Number of failed attempts after which the connection is given up
+    pub max_retries: u32,
+}
+
+impl Default for TcpReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(30),
+            jitter: Duration::from_millis(100),
+            max_retries: 10,
+        }
+    }
+}
+
+impl TcpReconnectPolicy {
+    /// Constructor
+    pub fn new(base: Duration, max: Duration, jitter: Duration, max_retries: u32) -> Self {
+        Self {
+            base,
+            max,
+            jitter,
+            max_retries,
+        }
+    }
+
+    /// Delay before the attempt number `attempt`, starting at 0, without its jitter
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        self.base
+            .checked_mul(2u32.saturating_pow(attempt))
+            .map_or(self.max, |delay| delay.min(self.max))
+    }
+
+    /// Delay before the attempt number `attempt`, starting at 0
+    fn delay(&self, attempt: u32) -> Duration {
+        let jitter = self.jitter.mul_f64(rand::thread_rng().gen::<f64>());
+        self.backoff(attempt) + jitter
+    }
+}
+
+/// Re-establishes the connection of a [`TcpSendWorker`] once it was lost
+#[async_trait]
+pub(crate) trait TcpRedial<W>: Send + Sync + 'static {
+    /// Delay before the attempt number `attempt`, starting at 0, or `None` once the
+    /// connection must be given up
+    fn delay(&self, attempt: u32) -> Option<Duration>;
+
+    /// Try once to connect to the peer again, and start the receiver processor of the
+    /// new connection at `addresses`. Return the write half of the new connection
+    async fn redial(&self, ctx: &Context, addresses: &Addresses) -> Result<W>;
+}
+
+/// Reconnects a plain TCP connection according to its [`TcpReconnectPolicy`]
+pub(crate) struct TcpReconnect {
+    registry: TcpRegistry,
+    peer: SocketAddr,
+    trust_options: TcpConnectionTrustOptions,
+    policy: TcpReconnectPolicy,
+}
+
+impl TcpReconnect {
+    /// Constructor
+    pub(crate) fn new(
+        registry: TcpRegistry,
+        peer: SocketAddr,
+        trust_options: TcpConnectionTrustOptions,
+        policy: TcpReconnectPolicy,
+    ) -> Self {
+        Self {
+            registry,
+            peer,
+            trust_options,
+            policy,
+        }
+    }
+}
+
+#[async_trait]
+impl TcpRedial<OwnedWriteHalf> for TcpReconnect {
+    fn delay(&self, attempt: u32) -> Option<Duration> {
+        (attempt < self.policy.max_retries).then(|| self.policy.delay(attempt))
+    }
+
+    async fn redial(&self, ctx: &Context, addresses: &Addresses) -> Result<OwnedWriteHalf> {
+        // The address of the receiver processor of the lost connection can only be
+        // reused once it is stopped
+        if self
+            .registry
+            .has_receiver_processor(addresses.receiver_address())
+        {
+            debug!(addr = %self.peer, "The receiver of the lost connection is still running");
+            return Err(TransportError::PeerBusy.into());
+        }
+
+        let (mut read_half, mut write_half) = TcpSendWorker::connect(
+            self.peer,
+            &self.trust_options.keepalive,
+            self.trust_options.connect_timeout,
+        )
+        .await?;
+        info!(addr = %self.peer, "Reconnected");
+
+        let access_control = self.trust_options.clone().access_control();
+        // In strict ordering mode, the new connection continues the ordered stream of the
+        // lost one, so that the messages buffered on both sides are still delivered in order
+        let ordering = access_control
+            .ordering
+            .initiate(&mut read_half, &mut write_half)
+            .await?;
+
+        TcpRecvProcessor::start(
+            ctx,
+            self.registry.clone(),
+            read_half,
+            addresses,
+            self.peer,
+            ConnectionOptions {
+                access_control,
+                ordering,
+            },
+        )
+        .await?;
+
+        Ok(write_half)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TcpReconnectPolicy;
+    use core::time::Duration;
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let policy = TcpReconnectPolicy::new(
+            Duration::from_millis(100),
+            Duration::from_secs(1),
+            Duration::ZERO,
+            10,
+        );
+        let backoff: Vec<u128> = (0..6)
+            .map(|attempt| policy.backoff(attempt).as_millis())
+            .collect();
+        assert_eq!(backoff, vec![100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_is_added_to_the_backoff() {
+        let policy = TcpReconnectPolicy::new(
+            Duration::from_millis(100),
+            Duration::from_secs(1),
+            Duration::from_millis(50),
+            10,
+        );
+        for attempt in 0..10 {
+            let delay = policy.delay(attempt);
+            assert!(delay >= policy.backoff(attempt));
+            assert!(delay <= policy.backoff(attempt) + policy.jitter);
+        }
+    }
+}
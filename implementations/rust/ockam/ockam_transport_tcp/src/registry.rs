@@ -1,10 +1,76 @@
+use crate::connection_stats::ConnectionCounters;
+use crate::{
+    ConnectionStats, TcpConnectionListener, TcpEvent, TcpListenerTrustOptions, TransportStats,
+    TCP_EVENTS_CAPACITY,
+};
+use core::sync::atomic::{AtomicBool, Ordering};
+use ockam_core::compat::collections::VecDeque;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::string::{String, ToString};
 use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::compat::vec::Vec;
+use ockam_core::sessions::SessionId;
 use ockam_core::Address;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+use tokio_rustls::rustls::ServerConfig;
+
+/// Default number of recent connection errors kept by a [`TcpRegistry`]
+pub const DEFAULT_MAX_CONNECTION_ERRORS: usize = 50;
+
+/// A failure to establish a TCP connection
+#[derive(Clone, Debug)]
+pub struct TcpConnectionError {
+    peer: String,
+    time: u64,
+    reason: String,
+}
+
+impl TcpConnectionError {
+    /// Peer the connection was established with
+    pub fn peer(&self) -> &str {
+        &self.peer
+    }
+    /// Time of the failure, in seconds since the Unix epoch
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+    /// Reason of the failure
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+/// An open TCP connection, see [`TcpRegistry::get_connections`]
+#[derive(Clone, Debug)]
+pub struct TcpConnectionInfo {
+    receiver_address: Address,
+    peer: SocketAddr,
+    session_id: Option<SessionId>,
+}
+
+impl TcpConnectionInfo {
+    /// Address of the receiver processor of the connection
+    pub fn receiver_address(&self) -> &Address {
+        &self.receiver_address
+    }
+    /// Peer the connection was established with
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+    /// Session claimed by the connection, if any
+    pub fn session_id(&self) -> Option<&SessionId> {
+        self.session_id.as_ref()
+    }
+}
 
 /// Registry of all active workers and processors in TCP Transport to ease their lifecycle management
 #[derive(Default, Clone)]
 pub struct TcpRegistry {
     registry: Arc<RwLock<InternalRegistry>>,
+    /// Whether events have subscribers, checked before emitting an event so that
+    /// the connections don't lock the registry when nobody listens
+    has_event_subscribers: Arc<AtomicBool>,
 }
 
 impl TcpRegistry {
@@ -48,11 +114,35 @@ impl TcpRegistry {
             lock.remove_outlet_listener_worker(addr);
         }
     }
-    pub(crate) fn add_listener_processor(&self, addr: &Address) {
+    pub(crate) fn add_outlet_connection(&self, outlet: &Address, portal: &Address) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.add_outlet_connection(outlet, portal);
+        }
+    }
+    pub(crate) fn add_listener_processor(
+        &self,
+        addr: &Address,
+        trust_options: TcpListenerTrustOptions,
+        tls_config: Option<Arc<ServerConfig>>,
+    ) {
         if let Ok(mut lock) = self.registry.write() {
-            lock.add_listener_processor(addr);
+            lock.add_listener_processor(addr, trust_options, tls_config);
         }
     }
+    /// Return the options the listener at `addr` was started with, see
+    /// [`TcpTransport::migrate_listener`](crate::TcpTransport::migrate_listener)
+    pub(crate) fn get_listener_options(
+        &self,
+        addr: &Address,
+    ) -> Option<(TcpListenerTrustOptions, Option<Arc<ServerConfig>>)> {
+        self.registry
+            .read()
+            .unwrap()
+            .listener_processors
+            .iter()
+            .find(|(a, _, _)| a == addr)
+            .map(|(_, trust_options, tls_config)| (trust_options.clone(), tls_config.clone()))
+    }
     pub(crate) fn remove_listener_processor(&self, addr: &Address) {
         if let Ok(mut lock) = self.registry.write() {
             lock.remove_listener_processor(addr);
@@ -63,39 +153,337 @@ impl TcpRegistry {
             lock.add_sender_worker(addr);
         }
     }
+    pub(crate) fn add_listener_connection(&self, listener: &Address, sender: &Address) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.add_listener_connection(listener, sender);
+        }
+    }
+    pub(crate) fn remove_listener_connection(&self, sender: &Address) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.remove_listener_connection(sender);
+        }
+    }
     pub(crate) fn remove_sender_worker(&self, addr: &Address) {
         if let Ok(mut lock) = self.registry.write() {
             lock.remove_sender_worker(addr);
         }
     }
-    pub(crate) fn add_receiver_processor(&self, addr: &Address) {
+    /// Return the traffic counters of the connection whose sender worker is at `sender`,
+    /// shared by its sender worker and receiver processor
+    pub(crate) fn connection_counters(&self, sender: &Address) -> Arc<ConnectionCounters> {
+        match self.registry.write() {
+            Ok(mut lock) => lock.connection_counters(sender),
+            Err(_) => Arc::new(ConnectionCounters::default()),
+        }
+    }
+    pub(crate) fn add_receiver_processor(&self, addr: &Address, peer: SocketAddr) {
+        let listeners = match self.registry.write() {
+            Ok(mut lock) => {
+                lock.add_receiver_processor(addr, peer);
+                lock.connection_listeners.clone()
+            }
+            Err(_) => return,
+        };
+        // Called without holding the lock, so that listeners can use the registry
+        for listener in listeners {
+            listener.on_connect(addr, peer);
+        }
+    }
+    pub(crate) fn remove_receiver_processor(&self, addr: &Address, peer: SocketAddr) {
+        let listeners = match self.registry.write() {
+            Ok(mut lock) => {
+                lock.remove_receiver_processor(addr);
+                lock.connection_listeners.clone()
+            }
+            Err(_) => return,
+        };
+        for listener in listeners {
+            listener.on_disconnect(addr, peer);
+        }
+    }
+    pub(crate) fn add_dropped_message(&self) {
         if let Ok(mut lock) = self.registry.write() {
-            lock.add_receiver_processor(addr);
+            lock.dropped_messages += 1;
         }
     }
-    pub(crate) fn remove_receiver_processor(&self, addr: &Address) {
+    pub(crate) fn add_corrupt_frame(&self) {
         if let Ok(mut lock) = self.registry.write() {
-            lock.remove_receiver_processor(addr);
+            lock.corrupt_frames += 1;
+        }
+    }
+    /// Associate `session_id` with the receiver processor at `addr`, unless another
+    /// connection already uses it. Return whether the session was free
+    pub(crate) fn claim_session(&self, session_id: &SessionId, addr: &Address) -> bool {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.claim_session(session_id, addr)
+        } else {
+            true
+        }
+    }
+    pub(crate) fn release_session(&self, session_id: &SessionId, addr: &Address) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.release_session(session_id, addr);
+        }
+    }
+    pub(crate) fn add_duplicate_session(&self) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.duplicate_sessions += 1;
+        }
+    }
+    pub(crate) fn add_connection_error(&self, peer: impl ToString, reason: impl ToString) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.add_connection_error(peer.to_string(), reason.to_string());
+        }
+    }
+    /// Return true if events have subscribers, see [`TcpRegistry::subscribe_events`]
+    pub(crate) fn has_event_subscribers(&self) -> bool {
+        self.has_event_subscribers.load(Ordering::Relaxed)
+    }
+    /// Send `event` to the current subscribers, if any
+    pub(crate) fn emit_event(&self, event: TcpEvent) {
+        if !self.has_event_subscribers() {
+            return;
+        }
+        let delivered = match self.registry.read() {
+            Ok(lock) => match &lock.events {
+                // Fails only if there are no subscribers left
+                Some(events) => events.send(event).is_ok(),
+                None => false,
+            },
+            Err(_) => return,
+        };
+        if !delivered {
+            // Stop emitting events until somebody subscribes again
+            if let Ok(lock) = self.registry.write() {
+                let subscribed = lock.events.as_ref().map(|e| e.receiver_count() > 0);
+                if subscribed != Some(true) {
+                    self.has_event_subscribers.store(false, Ordering::Relaxed)
+                }
+            }
         }
     }
 }
 
 impl TcpRegistry {
+    pub(crate) fn has_receiver_processor(&self, addr: &Address) -> bool {
+        self.registry
+            .read()
+            .unwrap()
+            .receiver_processors
+            .iter()
+            .any(|(a, _)| a == addr)
+    }
+
+    pub(crate) fn has_portal_receiver_processor(&self, addr: &Address) -> bool {
+        self.registry
+            .read()
+            .unwrap()
+            .portal_receiver_processors
+            .contains(addr)
+    }
+
     /// Return [`Address`]es of all active sender workers
     pub fn get_all_sender_workers(&self) -> Vec<Address> {
         self.registry.read().unwrap().sender_workers.clone()
     }
+
+    /// Return the [`Address`]es of the sender workers of the open connections accepted
+    /// by the listener at `listener`, including when the listener was stopped since,
+    /// see [`TcpTransport::migrate_listener`](crate::TcpTransport::migrate_listener)
+    pub fn get_listener_connections(&self, listener: &Address) -> Vec<Address> {
+        self.registry
+            .read()
+            .unwrap()
+            .listener_connections
+            .iter()
+            .filter(|(l, _)| l == listener)
+            .map(|(_, sender)| sender.clone())
+            .collect()
+    }
+
+    /// Return the [`Address`]es of the portal workers of the open connections created
+    /// by the outlet at `outlet`
+    pub fn get_outlet_connections(&self, outlet: &Address) -> Vec<Address> {
+        self.registry
+            .read()
+            .unwrap()
+            .outlet_connections
+            .iter()
+            .filter(|(o, _)| o == outlet)
+            .map(|(_, portal)| portal.clone())
+            .collect()
+    }
+
+    /// Return the traffic of the open connection whose sender worker is at `sender`,
+    /// see [`ConnectionStats`]
+    pub fn connection_stats(&self, sender: &Address) -> Option<ConnectionStats> {
+        self.registry
+            .read()
+            .unwrap()
+            .connection_counters
+            .iter()
+            .find(|(s, _)| s == sender)
+            .map(|(_, counters)| counters.snapshot())
+    }
+
+    /// Return the traffic of all the open connections, and the number of frames dropped
+    /// by each policy, see [`TransportStats`]
+    pub fn transport_stats(&self) -> TransportStats {
+        let lock = self.registry.read().unwrap();
+        let mut stats = TransportStats {
+            dropped_messages: lock.dropped_messages,
+            corrupt_frames: lock.corrupt_frames,
+            duplicate_sessions: lock.duplicate_sessions,
+            ..Default::default()
+        };
+        for (_, counters) in &lock.connection_counters {
+            stats.add_connection(counters.snapshot());
+        }
+        stats
+    }
+
+    /// Return the open connections, with the session they claimed if any
+    pub fn get_connections(&self) -> Vec<TcpConnectionInfo> {
+        let lock = self.registry.read().unwrap();
+        lock.receiver_processors
+            .iter()
+            .map(|(addr, peer)| TcpConnectionInfo {
+                receiver_address: addr.clone(),
+                peer: *peer,
+                session_id: lock
+                    .session_connections
+                    .iter()
+                    .find(|(_, a)| a == addr)
+                    .map(|(id, _)| id.clone()),
+            })
+            .collect()
+    }
+
+    /// Return the most recent failures to establish a connection, oldest first
+    pub fn get_connection_errors(&self) -> Vec<TcpConnectionError> {
+        self.registry
+            .read()
+            .unwrap()
+            .connection_errors
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Return the number of received messages dropped because the mailbox
+    /// they were forwarded to was full, see [`TcpMailboxFullPolicy`](crate::TcpMailboxFullPolicy)
+    pub fn get_dropped_messages(&self) -> u64 {
+        self.registry.read().unwrap().dropped_messages
+    }
+
+    /// Return the number of received frames rejected because they did not match
+    /// their checksum, see [`TcpConnectionTrustOptions::with_frame_checksum`](crate::TcpConnectionTrustOptions::with_frame_checksum)
+    pub fn get_corrupt_frames(&self) -> u64 {
+        self.registry.read().unwrap().corrupt_frames
+    }
+
+    /// Subscribe to the [`TcpEvent`]s of the connections of this transport
+    ///
+    /// Only the events happening after the subscription are received. A subscriber
+    /// which falls more than [`TCP_EVENTS_CAPACITY`] events behind misses the oldest ones.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<TcpEvent> {
+        let mut lock = self.registry.write().unwrap();
+        self.has_event_subscribers.store(true, Ordering::Relaxed);
+        match &lock.events {
+            Some(events) => events.subscribe(),
+            None => {
+                let (events, receiver) = broadcast::channel(TCP_EVENTS_CAPACITY);
+                lock.events = Some(events);
+                receiver
+            }
+        }
+    }
+
+    /// Notify `listener` of the connections opened and closed from now on, in addition
+    /// to the listeners added before
+    pub fn add_connection_listener(&self, listener: Arc<dyn TcpConnectionListener>) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.connection_listeners.push(listener);
+        }
+    }
+
+    /// Return the [`Address`] of the receiver processor of the connection using `session_id`
+    pub fn get_session_connection(&self, session_id: &SessionId) -> Option<Address> {
+        self.registry
+            .read()
+            .unwrap()
+            .session_connections
+            .iter()
+            .find(|(id, _)| id == session_id)
+            .map(|(_, addr)| addr.clone())
+    }
+
+    /// Return the number of connections whose session was already used by another
+    /// connection, see [`TcpDuplicateSessionPolicy`](crate::TcpDuplicateSessionPolicy)
+    pub fn get_duplicate_sessions(&self) -> u64 {
+        self.registry.read().unwrap().duplicate_sessions
+    }
+
+    /// Return the number of recent connection errors kept
+    pub fn get_max_connection_errors(&self) -> usize {
+        self.registry.read().unwrap().max_connection_errors
+    }
+
+    /// Set the number of recent connection errors to keep,
+    /// [`DEFAULT_MAX_CONNECTION_ERRORS`] by default
+    pub fn set_max_connection_errors(&self, max: usize) {
+        if let Ok(mut lock) = self.registry.write() {
+            lock.max_connection_errors = max;
+            let excess = lock.connection_errors.len().saturating_sub(max);
+            lock.connection_errors.drain(..excess);
+        }
+    }
 }
 
-#[derive(Default)]
 struct InternalRegistry {
     portal_workers: Vec<Address>,
     portal_receiver_processors: Vec<Address>,
     inlet_listener_processors: Vec<Address>,
     outlet_listener_workers: Vec<Address>,
-    listener_processors: Vec<Address>,
+    outlet_connections: Vec<(Address, Address)>,
+    listener_processors: Vec<(Address, TcpListenerTrustOptions, Option<Arc<ServerConfig>>)>,
     sender_workers: Vec<Address>,
-    receiver_processors: Vec<Address>,
+    receiver_processors: Vec<(Address, SocketAddr)>,
+    connection_errors: VecDeque<TcpConnectionError>,
+    max_connection_errors: usize,
+    dropped_messages: u64,
+    corrupt_frames: u64,
+    session_connections: Vec<(SessionId, Address)>,
+    listener_connections: Vec<(Address, Address)>,
+    connection_counters: Vec<(Address, Arc<ConnectionCounters>)>,
+    duplicate_sessions: u64,
+    events: Option<broadcast::Sender<TcpEvent>>,
+    connection_listeners: Vec<Arc<dyn TcpConnectionListener>>,
+}
+
+impl Default for InternalRegistry {
+    fn default() -> Self {
+        Self {
+            portal_workers: Vec::new(),
+            portal_receiver_processors: Vec::new(),
+            inlet_listener_processors: Vec::new(),
+            outlet_listener_workers: Vec::new(),
+            outlet_connections: Vec::new(),
+            listener_processors: Vec::new(),
+            sender_workers: Vec::new(),
+            receiver_processors: Vec::new(),
+            connection_errors: VecDeque::new(),
+            max_connection_errors: DEFAULT_MAX_CONNECTION_ERRORS,
+            dropped_messages: 0,
+            corrupt_frames: 0,
+            session_connections: Vec::new(),
+            listener_connections: Vec::new(),
+            connection_counters: Vec::new(),
+            duplicate_sessions: 0,
+            events: None,
+            connection_listeners: Vec::new(),
+        }
+    }
 }
 
 impl InternalRegistry {
@@ -104,6 +492,7 @@ impl InternalRegistry {
     }
     fn remove_portal_worker(&mut self, addr: &Address) {
         self.portal_workers.retain(|x| x != addr);
+        self.outlet_connections.retain(|(_, p)| p != addr);
     }
     fn add_portal_receiver_processor(&mut self, addr: &Address) {
         self.portal_receiver_processors.push(addr.clone())
@@ -123,22 +512,80 @@ impl InternalRegistry {
     fn remove_outlet_listener_worker(&mut self, addr: &Address) {
         self.outlet_listener_workers.retain(|x| x != addr);
     }
-    fn add_listener_processor(&mut self, addr: &Address) {
-        self.listener_processors.push(addr.clone())
+    fn add_outlet_connection(&mut self, outlet: &Address, portal: &Address) {
+        self.outlet_connections
+            .push((outlet.clone(), portal.clone()))
+    }
+    fn add_listener_processor(
+        &mut self,
+        addr: &Address,
+        trust_options: TcpListenerTrustOptions,
+        tls_config: Option<Arc<ServerConfig>>,
+    ) {
+        self.listener_processors
+            .push((addr.clone(), trust_options, tls_config))
     }
     fn remove_listener_processor(&mut self, addr: &Address) {
-        self.listener_processors.retain(|x| x != addr);
+        self.listener_processors.retain(|(x, _, _)| x != addr);
     }
     fn add_sender_worker(&mut self, addr: &Address) {
         self.sender_workers.push(addr.clone())
     }
     fn remove_sender_worker(&mut self, addr: &Address) {
         self.sender_workers.retain(|x| x != addr);
+        self.remove_listener_connection(addr);
+        self.connection_counters.retain(|(s, _)| s != addr);
+    }
+    fn add_listener_connection(&mut self, listener: &Address, sender: &Address) {
+        self.listener_connections
+            .push((listener.clone(), sender.clone()))
+    }
+    fn remove_listener_connection(&mut self, sender: &Address) {
+        self.listener_connections.retain(|(_, s)| s != sender);
+    }
+    fn connection_counters(&mut self, sender: &Address) -> Arc<ConnectionCounters> {
+        if let Some((_, counters)) = self.connection_counters.iter().find(|(s, _)| s == sender) {
+            return counters.clone();
+        }
+        let counters = Arc::new(ConnectionCounters::default());
+        self.connection_counters
+            .push((sender.clone(), counters.clone()));
+        counters
     }
-    fn add_receiver_processor(&mut self, addr: &Address) {
-        self.receiver_processors.push(addr.clone())
+    fn add_receiver_processor(&mut self, addr: &Address, peer: SocketAddr) {
+        self.receiver_processors.push((addr.clone(), peer))
     }
     fn remove_receiver_processor(&mut self, addr: &Address) {
-        self.receiver_processors.retain(|x| x != addr);
+        self.receiver_processors.retain(|(x, _)| x != addr);
+    }
+    fn claim_session(&mut self, session_id: &SessionId, addr: &Address) -> bool {
+        if self
+            .session_connections
+            .iter()
+            .any(|(id, _)| id == session_id)
+        {
+            return false;
+        }
+        self.session_connections
+            .push((session_id.clone(), addr.clone()));
+        true
+    }
+    fn release_session(&mut self, session_id: &SessionId, addr: &Address) {
+        self.session_connections
+            .retain(|(id, a)| !(id == session_id && a == addr));
+    }
+    fn add_connection_error(&mut self, peer: String, reason: String) {
+        if self.max_connection_errors == 0 {
+            return;
+        }
+        while self.connection_errors.len() >= self.max_connection_errors {
+            self.connection_errors.pop_front();
+        }
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.connection_errors
+            .push_back(TcpConnectionError { peer, time, reason });
     }
 }
@@ -4,13 +4,17 @@ use ockam_core::compat::{boxed::Box, sync::Arc};
 use ockam_core::{Address, AsyncTryClone, Result, Route};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
+use tokio_rustls::rustls::{ClientConfig, ServerConfig, ServerName};
 
 use crate::portal::TcpInletListenProcessor;
 use crate::workers::{
-    Addresses, ConnectionRole, TcpListenProcessor, TcpRecvProcessor, TcpSendWorker,
+    Addresses, ConnectionOptions, ConnectionRole, TcpListenProcessor, TcpReadHalf,
+    TcpRecvProcessor, TcpSendWorker, TcpWriteHalf,
 };
 use crate::{
-    TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpOutletListenWorker, TcpRegistry,
+    TcpConnectionTrustOptions, TcpInletRateLimit, TcpInletRouteGroup, TcpListenerTrustOptions,
+    TcpOutletListenWorker, TcpOutletOptions, TcpReconnect, TcpReconnectPolicy, TcpRedial,
+    TcpRegistry,
 };
 
 pub(crate) const CLUSTER_NAME: &str = "_internals.transport.tcp";
@@ -131,11 +135,164 @@ impl TcpTransport {
         trust_options: TcpConnectionTrustOptions,
     ) -> Result<Address> {
         // Resolve peer address
-        let socket = Self::resolve_peer(peer.into())?;
+        let peer = peer.into();
+        let socket = Self::resolve_peer(peer.clone()).map_err(|e| {
+            self.registry.add_connection_error(&peer, &e);
+            e
+        })?;
 
-        let (read_half, write_half) = TcpSendWorker::connect(socket).await?;
+        let (read_half, write_half) = TcpSendWorker::connect(
+            socket,
+            &trust_options.keepalive,
+            trust_options.connect_timeout,
+        )
+        .await
+        .map_err(|e| {
+            self.registry.add_connection_error(socket, &e);
+            e
+        })?;
+
+        self.start_connection(read_half, write_half, socket, trust_options, None)
+            .await
+    }
+
+    /// Establish an outgoing TCP connection, like [`TcpTransport::connect`], which is
+    /// re-established when it is lost, e.g. because the peer restarted.
+    ///
+    /// The peer is connected to again with the backoff of `reconnect_policy`. The worker
+    /// pair of the new connection keeps the same addresses, so that the routes through
+    /// the returned address keep working, and the messages sent while the connection
+    /// was lost are delivered once it is re-established.
+    /// Only a connection lost to an I/O error is re-established: a connection closed on
+    /// purpose, e.g. after its read timeout, is stopped like any other connection.
+    /// The connection is stopped after [`TcpReconnectPolicy::max_retries`] failed
+    /// attempts in a row, and the failure reported in [`TcpRegistry::get_connection_errors`].
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpReconnectPolicy, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let addr = tcp
+    ///     .connect_reconnecting(
+    ///         "127.0.0.1:5000",
+    ///         TcpConnectionTrustOptions::new(),
+    ///         TcpReconnectPolicy::default(),
+    ///     )
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn connect_reconnecting(
+        &self,
+        peer: impl Into<String>,
+        trust_options: TcpConnectionTrustOptions,
+        reconnect_policy: TcpReconnectPolicy,
+    ) -> Result<Address> {
+        // Resolve peer address
+        let peer = peer.into();
+        let socket = Self::resolve_peer(peer.clone()).map_err(|e| {
+            self.registry.add_connection_error(&peer, &e);
+            e
+        })?;
+
+        let (read_half, write_half) = TcpSendWorker::connect(
+            socket,
+            &trust_options.keepalive,
+            trust_options.connect_timeout,
+        )
+        .await
+        .map_err(|e| {
+            self.registry.add_connection_error(socket, &e);
+            e
+        })?;
+
+        let redial = TcpReconnect::new(
+            self.registry.clone(),
+            socket,
+            trust_options.clone(),
+            reconnect_policy,
+        );
+        self.start_connection(
+            read_half,
+            write_half,
+            socket,
+            trust_options,
+            Some(Arc::new(redial)),
+        )
+        .await
+    }
+
+    /// Establish an outgoing TCP connection wrapped in TLS, for networks requiring
+    /// all their traffic to be encrypted, regardless of the secure channels it carries.
+    ///
+    /// The certificate of the peer is verified according to `tls_config`, and must be
+    /// valid for the host name or IP address of `peer`.
+    ///
+    /// ```rust
+    /// use ockam_core::compat::sync::Arc;
+    /// use ockam_transport_tcp::rustls::ClientConfig;
+    /// use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context, tls_config: ClientConfig) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let addr = tcp
+    ///     .connect_tls("localhost:5000", Arc::new(tls_config), TcpConnectionTrustOptions::new())
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn connect_tls(
+        &self,
+        peer: impl Into<String>,
+        tls_config: Arc<ClientConfig>,
+        trust_options: TcpConnectionTrustOptions,
+    ) -> Result<Address> {
+        // Resolve peer address
+        let peer = peer.into();
+        let (socket, server_name) = Self::resolve_peer(peer.clone())
+            .and_then(|socket| Ok((socket, tls_server_name(&peer)?)))
+            .map_err(|e| {
+                self.registry.add_connection_error(&peer, &e);
+                e
+            })?;
+
+        let (read_half, write_half) = TcpSendWorker::connect_tls(
+            socket,
+            server_name,
+            tls_config,
+            &trust_options.keepalive,
+            trust_options.connect_timeout,
+        )
+        .await
+        .map_err(|e| {
+            self.registry.add_connection_error(socket, &e);
+            e
+        })?;
+
+        self.start_connection(read_half, write_half, socket, trust_options, None)
+            .await
+    }
 
+    /// Start the worker pair of an established connection, and return the address
+    /// of its sender
+    async fn start_connection<W: TcpWriteHalf>(
+        &self,
+        mut read_half: impl TcpReadHalf,
+        mut write_half: W,
+        socket: SocketAddr,
+        trust_options: TcpConnectionTrustOptions,
+        redial: Option<Arc<dyn TcpRedial<W>>>,
+    ) -> Result<Address> {
         let access_control = trust_options.access_control();
+        let ordering = access_control
+            .ordering
+            .initiate(&mut read_half, &mut write_half)
+            .await?;
+        let options = ConnectionOptions {
+            access_control,
+            ordering,
+        };
 
         let addresses = Addresses::generate(ConnectionRole::Initiator);
 
@@ -145,7 +302,8 @@ impl TcpTransport {
             write_half,
             &addresses,
             socket,
-            access_control.sender_incoming_access_control,
+            &options,
+            redial,
         )
         .await?;
 
@@ -155,8 +313,7 @@ impl TcpTransport {
             read_half,
             &addresses,
             socket,
-            access_control.receiver_outgoing_access_control,
-            access_control.session_id,
+            options,
         )
         .await?;
 
@@ -185,13 +342,53 @@ impl TcpTransport {
     ) -> Result<(SocketAddr, Address)> {
         let bind_addr = parse_socket_addr(bind_addr.as_ref())?;
         // Could be different from the bind_addr, e.g., if binding to port 0\
-        let (socket_addr, address) =
-            TcpListenProcessor::start(&self.ctx, self.registry.clone(), bind_addr, trust_options)
-                .await?;
+        let (socket_addr, address) = TcpListenProcessor::start(
+            &self.ctx,
+            self.registry.clone(),
+            bind_addr,
+            trust_options,
+            None,
+        )
+        .await?;
 
         Ok((socket_addr, address))
     }
 
+    /// Start listening to incoming connections wrapped in TLS, see [`Self::connect_tls`]
+    ///
+    /// The TLS handshake of each incoming connection is performed with `tls_config`.
+    /// Connections failing their handshake are closed and reported as connection errors,
+    /// see [`TcpRegistry::get_connection_errors`].
+    ///
+    /// ```rust
+    /// use ockam_core::compat::sync::Arc;
+    /// use ockam_transport_tcp::rustls::ServerConfig;
+    /// use ockam_transport_tcp::{TcpListenerTrustOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::Result;
+    /// # async fn test(ctx: Context, tls_config: ServerConfig) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.listen_tls("127.0.0.1:8000", Arc::new(tls_config), TcpListenerTrustOptions::new())
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn listen_tls(
+        &self,
+        bind_addr: impl AsRef<str>,
+        tls_config: Arc<ServerConfig>,
+        trust_options: TcpListenerTrustOptions,
+    ) -> Result<(SocketAddr, Address)> {
+        let bind_addr = parse_socket_addr(bind_addr.as_ref())?;
+        TcpListenProcessor::start(
+            &self.ctx,
+            self.registry.clone(),
+            bind_addr,
+            trust_options,
+            Some(tls_config),
+        )
+        .await
+    }
+
     /// Interrupt an active TCP connection given its `Address`
     pub async fn disconnect(&self, address: &Address) -> Result<()> {
         self.ctx.stop_worker(address.clone()).await
@@ -201,6 +398,42 @@ impl TcpTransport {
     pub async fn stop_listener(&self, address: &Address) -> Result<()> {
         self.ctx.stop_processor(address.clone()).await
     }
+
+    /// Start listening to incoming connections on `bind_addr` in place of the listener at
+    /// `old_listener`, which stops accepting connections. The new listener is started with
+    /// the trust options and the TLS configuration of the old one.
+    ///
+    /// The connections accepted by the old listener are left open, and it is drained once
+    /// [`TcpRegistry::get_listener_connections`] returns none for it. `bind_addr` must not
+    /// be bound already, e.g. it can be another port of the same interface.
+    ///
+    /// Returns the local address and the [`Address`] of the new listener.
+    pub async fn migrate_listener(
+        &self,
+        old_listener: &Address,
+        bind_addr: impl AsRef<str>,
+    ) -> Result<(SocketAddr, Address)> {
+        let (trust_options, tls_config) = self
+            .registry
+            .get_listener_options(old_listener)
+            .ok_or(TransportError::InvalidAddress)?;
+        let bind_addr = parse_socket_addr(bind_addr.as_ref())?;
+        let (socket_addr, address) = TcpListenProcessor::start(
+            &self.ctx,
+            self.registry.clone(),
+            bind_addr,
+            trust_options,
+            tls_config,
+        )
+        .await?;
+        if let Err(e) = self.stop_listener(old_listener).await {
+            // Keep the old listener as the only one
+            let _ = self.stop_listener(&address).await;
+            return Err(e);
+        }
+
+        Ok((socket_addr, address))
+    }
 }
 
 impl TcpTransport {
@@ -249,9 +482,90 @@ impl TcpTransport {
         TcpInletListenProcessor::start(
             &self.ctx,
             self.registry.clone(),
-            outlet_route,
+            Arc::new(TcpInletRouteGroup::from(outlet_route)),
             socket_addr,
             access_control,
+            None,
+        )
+        .await
+    }
+
+    /// Create Tcp Inlet like [`TcpTransport::create_inlet`], accepting new connections
+    /// at most at the rate given by `rate_limit`. Connections past the budget are
+    /// delayed until the limit allows them.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpInletRateLimit, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{AllowAll, Result, route};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let route_path = route!["outlet"];
+    ///
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.create_inlet_with_rate_limit(
+    ///     "inlet",
+    ///     route_path,
+    ///     AllowAll,
+    ///     TcpInletRateLimit::new(10.0, 20)?,
+    /// )
+    /// .await?;
+    /// # tcp.stop_inlet("inlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_inlet_with_rate_limit(
+        &self,
+        bind_addr: impl Into<String>,
+        outlet_route: impl Into<Route>,
+        access_control: impl IncomingAccessControl,
+        rate_limit: TcpInletRateLimit,
+    ) -> Result<(Address, SocketAddr)> {
+        let socket_addr = parse_socket_addr(&bind_addr.into())?;
+        TcpInletListenProcessor::start(
+            &self.ctx,
+            self.registry.clone(),
+            Arc::new(TcpInletRouteGroup::from(outlet_route.into())),
+            socket_addr,
+            Arc::new(access_control),
+            Some(rate_limit),
+        )
+        .await
+    }
+
+    /// Create Tcp Inlet like [`TcpTransport::create_inlet`], balancing new connections
+    /// over the outlet routes of `route_group`. Each connection is forwarded to a single
+    /// route, picked according to the [`TcpInletAffinity`](crate::TcpInletAffinity)
+    /// of the group.
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{TcpInletAffinity, TcpInletRouteGroup, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{AllowAll, Result, route};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let route_group = TcpInletRouteGroup::new(
+    ///     vec![route!["outlet_1"], route!["outlet_2"]],
+    ///     TcpInletAffinity::ByClientIp,
+    /// )?;
+    ///
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.create_inlet_with_route_group("inlet", route_group, AllowAll)
+    ///     .await?;
+    /// # tcp.stop_inlet("inlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_inlet_with_route_group(
+        &self,
+        bind_addr: impl Into<String>,
+        route_group: TcpInletRouteGroup,
+        access_control: impl IncomingAccessControl,
+    ) -> Result<(Address, SocketAddr)> {
+        let socket_addr = parse_socket_addr(&bind_addr.into())?;
+        TcpInletListenProcessor::start(
+            &self.ctx,
+            self.registry.clone(),
+            Arc::new(route_group),
+            socket_addr,
+            Arc::new(access_control),
+            None,
         )
         .await
     }
@@ -322,6 +636,52 @@ impl TcpTransport {
             address,
             peer_addr,
             access_control,
+            TcpOutletOptions::new(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Create Tcp Outlet Listener at address, like [`TcpTransport::create_outlet`],
+    /// with the given [`TcpOutletOptions`]
+    ///
+    /// ```rust
+    /// use core::time::Duration;
+    /// use ockam_transport_tcp::{TcpOutletCoalescing, TcpOutletFraming, TcpOutletOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{AllowAll, Result};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    ///
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// tcp.create_outlet_with_options(
+    ///     "outlet",
+    ///     "localhost:9000",
+    ///     AllowAll,
+    ///     TcpOutletOptions::new()
+    ///         .with_close_grace(Duration::from_secs(5))
+    ///         .with_framing(TcpOutletFraming::length_prefixed(4, 1024 * 1024))
+    ///         .with_coalescing(TcpOutletCoalescing::new(Duration::from_millis(5), 16 * 1024)),
+    /// )
+    /// .await?;
+    /// # tcp.stop_outlet("outlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_outlet_with_options(
+        &self,
+        address: impl Into<Address>,
+        peer: impl Into<String>,
+        access_control: impl IncomingAccessControl,
+        options: TcpOutletOptions,
+    ) -> Result<()> {
+        let peer_addr = Self::resolve_peer(peer.into())?;
+        TcpOutletListenWorker::start(
+            &self.ctx,
+            self.registry.clone(),
+            address.into(),
+            peer_addr,
+            Arc::new(access_control),
+            options,
         )
         .await?;
 
@@ -345,19 +705,42 @@ impl TcpTransport {
         self.ctx.stop_worker(addr).await?;
         Ok(())
     }
+
+    /// Stop outlet at addr, like [`TcpTransport::stop_outlet`], and close the
+    /// connections it opened to its peer, see [`TcpRegistry::get_outlet_connections`]
+    pub async fn stop_outlet_with_connections(&self, addr: impl Into<Address>) -> Result<()> {
+        let addr = addr.into();
+        let connections = self.registry.get_outlet_connections(&addr);
+        self.ctx.stop_worker(addr).await?;
+        for portal in connections {
+            // The connection may have been closed in the meantime
+            let _ = self.ctx.stop_worker(portal).await;
+        }
+        Ok(())
+    }
 }
 
 fn parse_socket_addr(s: &str) -> Result<SocketAddr> {
     Ok(s.parse().map_err(|_| TransportError::InvalidAddress)?)
 }
 
+/// Return the name the TLS certificate of `peer`, a `host:port` pair, must be valid for
+fn tls_server_name(peer: &str) -> Result<ServerName> {
+    let host = match peer.rsplit_once(':') {
+        Some((host, _port)) => host,
+        None => peer,
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    Ok(ServerName::try_from(host).map_err(|_| TransportError::InvalidAddress)?)
+}
+
 #[cfg(test)]
 mod test {
     use core::fmt::Debug;
     use ockam_core::{Error, Result};
     use ockam_transport_core::TransportError;
 
-    use crate::transport::parse_socket_addr;
+    use crate::transport::{parse_socket_addr, tls_server_name};
 
     fn assert_transport_error<T>(result: Result<T>, error: TransportError)
     where
@@ -406,4 +789,15 @@ mod test {
         let result = parse_socket_addr("127.0.0.1:8080");
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_tls_server_name() {
+        assert!(tls_server_name("localhost:4000").is_ok());
+        assert!(tls_server_name("example.com").is_ok());
+        assert!(tls_server_name("127.0.0.1:4000").is_ok());
+        assert!(tls_server_name("[::1]:4000").is_ok());
+
+        let result = tls_server_name("not a host:4000");
+        assert_transport_error(result, TransportError::InvalidAddress);
+    }
 }
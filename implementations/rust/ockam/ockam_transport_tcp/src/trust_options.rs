@@ -1,23 +1,82 @@
+use crate::{
+    LocalInfoPassthrough, LocalInfoProducers, PeerAddressProducer, TcpConnectionFilter,
+    TcpConnectionFilters, TcpDuplicateSessionPolicy, TcpKeepalive, TcpLocalInfoProducer,
+    TcpMailboxFullPolicy, TcpOrdering,
+};
+use core::time::Duration;
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::Arc;
 use ockam_core::sessions::{SessionId, SessionOutgoingAccessControlBuilder, Sessions};
 use ockam_core::{IncomingAccessControl, LocalOnwardOnly, LocalSourceOnly, OutgoingAccessControl};
 
+/// Default maximum length, in bytes, of the frames received by a connection, which is
+/// the largest length its header can advertise
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = u16::MAX as usize;
+
 pub(crate) struct TcpConnectionAccessControl {
     pub session_id: Option<SessionId>,
     pub sender_incoming_access_control: Arc<dyn IncomingAccessControl>,
     pub receiver_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+    pub local_info_producers: LocalInfoProducers,
+    pub local_info_passthrough: LocalInfoPassthrough,
+    pub heartbeat_reply: bool,
+    pub heartbeat_interval: Option<Duration>,
+    pub ordering: TcpOrdering,
+    pub mailbox_full_policy: TcpMailboxFullPolicy,
+    pub frame_checksum: bool,
+    pub compression: bool,
+    pub duplicate_session_policy: TcpDuplicateSessionPolicy,
+    pub max_message_len: usize,
+    pub read_timeout: Option<Duration>,
+    pub short_read_retries: usize,
 }
 
 /// Trust Options for a TCP connection
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Debug)]
 pub struct TcpConnectionTrustOptions {
     pub(crate) session: Option<(Sessions, SessionId)>,
+    pub(crate) local_info_producers: LocalInfoProducers,
+    pub(crate) local_info_passthrough: LocalInfoPassthrough,
+    pub(crate) heartbeat_reply: bool,
+    pub(crate) heartbeat_interval: Option<Duration>,
+    pub(crate) ordering: TcpOrdering,
+    pub(crate) mailbox_full_policy: TcpMailboxFullPolicy,
+    pub(crate) frame_checksum: bool,
+    pub(crate) compression: bool,
+    pub(crate) duplicate_session_policy: TcpDuplicateSessionPolicy,
+    pub(crate) max_message_len: usize,
+    pub(crate) read_timeout: Option<Duration>,
+    pub(crate) short_read_retries: usize,
+    pub(crate) keepalive: TcpKeepalive,
+    pub(crate) connect_timeout: Option<Duration>,
+}
+
+impl Default for TcpConnectionTrustOptions {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TcpConnectionTrustOptions {
     /// Constructor
     pub fn new() -> Self {
-        Self { session: None }
+        Self {
+            session: None,
+            local_info_producers: LocalInfoProducers::default(),
+            local_info_passthrough: LocalInfoPassthrough::default(),
+            heartbeat_reply: false,
+            heartbeat_interval: None,
+            ordering: TcpOrdering::BestEffort,
+            mailbox_full_policy: TcpMailboxFullPolicy::Block,
+            frame_checksum: false,
+            compression: false,
+            duplicate_session_policy: TcpDuplicateSessionPolicy::Allow,
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+            read_timeout: None,
+            short_read_retries: 0,
+            keepalive: TcpKeepalive::default(),
+            connect_timeout: None,
+        }
     }
 
     /// Set session for this connection, in this case messages from that connection
@@ -31,6 +90,133 @@ impl TcpConnectionTrustOptions {
         self
     }
 
+    /// Add a producer of [`LocalInfo`] run for every message received by that connection.
+    /// Its output is added to the [`LocalInfo`] of the message, next to the [`SessionId`]
+    /// (if a session is set). Producers are run in the order they were added.
+    pub fn with_local_info_producer(mut self, producer: impl TcpLocalInfoProducer) -> Self {
+        self.local_info_producers.push(Arc::new(producer));
+        self
+    }
+
+    /// Attach a [`PeerAddressLocalInfo`](crate::PeerAddressLocalInfo) with the address
+    /// of the peer to every message received by that connection, so that the workers and
+    /// access controls they go through can check it. Run as a [`TcpLocalInfoProducer`]
+    pub fn with_peer_address_local_info(self) -> Self {
+        self.with_local_info_producer(PeerAddressProducer)
+    }
+
+    /// Carry the [`LocalInfo`] of type `type_identifier` attached to the messages sent
+    /// by that connection to the peer, which attaches it to the messages it receives,
+    /// after the [`LocalInfo`] it produces itself. Any other [`LocalInfo`] is stripped.
+    /// Both sides of the connection must use that option, with the types they accept
+    /// from each other. By default no [`LocalInfo`] crosses the connection
+    pub fn with_local_info_passthrough(mut self, type_identifier: impl Into<String>) -> Self {
+        self.local_info_passthrough.push(type_identifier.into());
+        self
+    }
+
+    /// Reply to the heartbeats received by that connection, so that the peer can check
+    /// the liveness of the connection in both directions. By default heartbeats are
+    /// silently dropped
+    pub fn with_heartbeat_reply(mut self) -> Self {
+        self.heartbeat_reply = true;
+        self
+    }
+
+    /// Send a heartbeat over that connection once nothing was sent for `interval`.
+    /// Heartbeats restart the read timeout of the peer, see [`Self::with_read_timeout`],
+    /// and keep the NAT mappings of the connection alive. Unlike keepalive probes, see
+    /// [`Self::with_keepalive`], they go through proxies and are seen by the peer node,
+    /// at the cost of sending a frame per interval. Disabled by default
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    /// Set the ordering guarantees of the messages exchanged over that connection.
+    /// See [`TcpOrdering`] for the available modes, the default is [`TcpOrdering::BestEffort`]
+    pub fn with_ordering(mut self, ordering: TcpOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Set what that connection does with a received message when the mailbox it is
+    /// forwarded to is full. See [`TcpMailboxFullPolicy`] for the available policies,
+    /// the default is [`TcpMailboxFullPolicy::Block`]
+    pub fn with_mailbox_full_policy(mut self, policy: TcpMailboxFullPolicy) -> Self {
+        self.mailbox_full_policy = policy;
+        self
+    }
+
+    /// Append a checksum to the frames sent by that connection and reject the received
+    /// frames which don't match theirs, to detect accidental corruption on transports
+    /// which are not wrapped in a secure channel. Both sides of the connection must use
+    /// that option. Disabled by default, since secure channels already check the
+    /// integrity of the messages they carry
+    pub fn with_frame_checksum(mut self) -> Self {
+        self.frame_checksum = true;
+        self
+    }
+
+    /// Compress the messages sent by that connection with LZ4, and decompress the
+    /// received ones. Each message is prefixed by a flag byte telling if it is compressed,
+    /// so that messages which don't shrink are sent as is. Both sides of the connection
+    /// must use that option. Disabled by default
+    pub fn with_compression(mut self) -> Self {
+        self.compression = true;
+        self
+    }
+
+    /// Set what that connection does if its session, see [`Self::with_session`], is
+    /// already used by another connection of the transport. See [`TcpDuplicateSessionPolicy`]
+    /// for the available policies, the default is [`TcpDuplicateSessionPolicy::Allow`]
+    pub fn with_duplicate_session_policy(mut self, policy: TcpDuplicateSessionPolicy) -> Self {
+        self.duplicate_session_policy = policy;
+        self
+    }
+
+    /// Set the maximum length, in bytes, of the frames received by that connection.
+    /// The connection is closed as soon as the peer announces a longer frame, before
+    /// reading it. The default is [`DEFAULT_MAX_MESSAGE_LEN`]
+    pub fn with_max_message_len(mut self, max_message_len: usize) -> Self {
+        self.max_message_len = max_message_len;
+        self
+    }
+
+    /// Close that connection when nothing is received from the peer for `read_timeout`,
+    /// e.g. when it went away without closing the connection. Every received frame,
+    /// heartbeats included, restarts the timeout. Disabled by default
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = Some(read_timeout);
+        self
+    }
+
+    /// When the body of a received frame stops arriving part way, because the read fails
+    /// or times out, resume it up to `short_read_retries` times before giving up on the
+    /// frame. Nothing is read past the length of the frame, and a frame which can't be
+    /// decoded once received is rejected. Disabled by default
+    pub fn with_short_read_retries(mut self, short_read_retries: usize) -> Self {
+        self.short_read_retries = short_read_retries;
+        self
+    }
+
+    /// Set the keepalive probes sent over that connection while it is idle. The default
+    /// is [`TcpKeepalive::default`]
+    pub fn with_keepalive(mut self, keepalive: TcpKeepalive) -> Self {
+        self.keepalive = keepalive;
+        self
+    }
+
+    /// Fail the connection with [`TransportError::ConnectTimeout`] if the peer doesn't
+    /// accept it within `connect_timeout`, instead of waiting for the OS to give up on
+    /// a peer which doesn't answer. Disabled by default
+    ///
+    /// [`TransportError::ConnectTimeout`]: ockam_transport_core::TransportError::ConnectTimeout
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
     pub(crate) fn access_control(self) -> TcpConnectionAccessControl {
         match self.session {
             Some((sessions, session_id)) => TcpConnectionAccessControl {
@@ -39,26 +225,86 @@ impl TcpConnectionTrustOptions {
                 receiver_outgoing_access_control: Arc::new(
                     SessionOutgoingAccessControlBuilder::new(session_id, sessions).build(),
                 ),
+                local_info_producers: self.local_info_producers,
+                local_info_passthrough: self.local_info_passthrough.clone(),
+                heartbeat_reply: self.heartbeat_reply,
+                heartbeat_interval: self.heartbeat_interval,
+                ordering: self.ordering.clone(),
+                mailbox_full_policy: self.mailbox_full_policy,
+                frame_checksum: self.frame_checksum,
+                compression: self.compression,
+                duplicate_session_policy: self.duplicate_session_policy,
+                max_message_len: self.max_message_len,
+                read_timeout: self.read_timeout,
+                short_read_retries: self.short_read_retries,
             },
             None => TcpConnectionAccessControl {
                 session_id: None,
                 sender_incoming_access_control: Arc::new(LocalSourceOnly),
                 receiver_outgoing_access_control: Arc::new(LocalOnwardOnly),
+                local_info_producers: self.local_info_producers,
+                local_info_passthrough: self.local_info_passthrough.clone(),
+                heartbeat_reply: self.heartbeat_reply,
+                heartbeat_interval: self.heartbeat_interval,
+                ordering: self.ordering.clone(),
+                mailbox_full_policy: self.mailbox_full_policy,
+                frame_checksum: self.frame_checksum,
+                compression: self.compression,
+                duplicate_session_policy: self.duplicate_session_policy,
+                max_message_len: self.max_message_len,
+                read_timeout: self.read_timeout,
+                short_read_retries: self.short_read_retries,
             },
         }
     }
 }
 
 /// Trust Options for a TCP listener
-#[derive(Default, Debug)]
+#[derive(Clone, Debug)]
 pub struct TcpListenerTrustOptions {
     pub(crate) session: Option<(Sessions, SessionId)>,
+    pub(crate) local_info_producers: LocalInfoProducers,
+    pub(crate) local_info_passthrough: LocalInfoPassthrough,
+    pub(crate) heartbeat_reply: bool,
+    pub(crate) heartbeat_interval: Option<Duration>,
+    pub(crate) ordering: TcpOrdering,
+    pub(crate) mailbox_full_policy: TcpMailboxFullPolicy,
+    pub(crate) frame_checksum: bool,
+    pub(crate) compression: bool,
+    pub(crate) max_message_len: usize,
+    pub(crate) read_timeout: Option<Duration>,
+    pub(crate) short_read_retries: usize,
+    pub(crate) keepalive: Option<TcpKeepalive>,
+    pub(crate) bind_interface: Option<String>,
+    pub(crate) connection_filters: TcpConnectionFilters,
+}
+
+impl Default for TcpListenerTrustOptions {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TcpListenerTrustOptions {
     /// Constructor
     pub fn new() -> Self {
-        Self { session: None }
+        Self {
+            session: None,
+            local_info_producers: LocalInfoProducers::default(),
+            local_info_passthrough: LocalInfoPassthrough::default(),
+            heartbeat_reply: false,
+            heartbeat_interval: None,
+            ordering: TcpOrdering::BestEffort,
+            mailbox_full_policy: TcpMailboxFullPolicy::Block,
+            frame_checksum: false,
+            compression: false,
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+            read_timeout: None,
+            short_read_retries: 0,
+            keepalive: None,
+            bind_interface: None,
+            connection_filters: TcpConnectionFilters::default(),
+        }
     }
 
     /// Set session for this listener, in this case messages from connections have following
@@ -76,6 +322,120 @@ impl TcpListenerTrustOptions {
         self
     }
 
+    /// Add a producer of [`LocalInfo`] run for every message received by connections
+    /// spawned by this listener.
+    /// Its output is added to the [`LocalInfo`] of the message, next to the [`SessionId`]
+    /// (if a session is set). Producers are run in the order they were added.
+    pub fn with_local_info_producer(mut self, producer: impl TcpLocalInfoProducer) -> Self {
+        self.local_info_producers.push(Arc::new(producer));
+        self
+    }
+
+    /// Attach the address of the peer to every message received by connections spawned
+    /// by this listener. See [`TcpConnectionTrustOptions::with_peer_address_local_info`]
+    pub fn with_peer_address_local_info(self) -> Self {
+        self.with_local_info_producer(PeerAddressProducer)
+    }
+
+    /// Carry the [`LocalInfo`] of type `type_identifier` across the connections spawned
+    /// by this listener. See [`TcpConnectionTrustOptions::with_local_info_passthrough`]
+    pub fn with_local_info_passthrough(mut self, type_identifier: impl Into<String>) -> Self {
+        self.local_info_passthrough.push(type_identifier.into());
+        self
+    }
+
+    /// Reply to the heartbeats received by connections spawned by this listener, so that
+    /// the peer can check the liveness of the connection in both directions. By default
+    /// heartbeats are silently dropped
+    pub fn with_heartbeat_reply(mut self) -> Self {
+        self.heartbeat_reply = true;
+        self
+    }
+
+    /// Send a heartbeat over the connections spawned by this listener once nothing was
+    /// sent for `interval`. See [`TcpConnectionTrustOptions::with_heartbeat_interval`]
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    /// Set the ordering guarantees of the messages exchanged over connections spawned by
+    /// this listener. See [`TcpOrdering`] for the available modes, the default is
+    /// [`TcpOrdering::BestEffort`]
+    pub fn with_ordering(mut self, ordering: TcpOrdering) -> Self {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Set what connections spawned by this listener do with a received message when
+    /// the mailbox it is forwarded to is full. See [`TcpMailboxFullPolicy`] for the
+    /// available policies, the default is [`TcpMailboxFullPolicy::Block`]
+    pub fn with_mailbox_full_policy(mut self, policy: TcpMailboxFullPolicy) -> Self {
+        self.mailbox_full_policy = policy;
+        self
+    }
+
+    /// Append a checksum to the frames sent by connections spawned by this listener and
+    /// reject the received frames which don't match theirs. See
+    /// [`TcpConnectionTrustOptions::with_frame_checksum`]
+    pub fn with_frame_checksum(mut self) -> Self {
+        self.frame_checksum = true;
+        self
+    }
+
+    /// Compress the messages sent by connections spawned by this listener, and
+    /// decompress the received ones. See [`TcpConnectionTrustOptions::with_compression`]
+    pub fn with_compression(mut self) -> Self {
+        self.compression = true;
+        self
+    }
+
+    /// Set the maximum length, in bytes, of the frames received by connections spawned
+    /// by this listener. See [`TcpConnectionTrustOptions::with_max_message_len`]
+    pub fn with_max_message_len(mut self, max_message_len: usize) -> Self {
+        self.max_message_len = max_message_len;
+        self
+    }
+
+    /// Close the connections spawned by this listener when nothing is received from
+    /// their peer for `read_timeout`. See [`TcpConnectionTrustOptions::with_read_timeout`]
+    pub fn with_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = Some(read_timeout);
+        self
+    }
+
+    /// Resume up to `short_read_retries` times the frames received by connections spawned
+    /// by this listener which stop arriving part way. See
+    /// [`TcpConnectionTrustOptions::with_short_read_retries`]
+    pub fn with_short_read_retries(mut self, short_read_retries: usize) -> Self {
+        self.short_read_retries = short_read_retries;
+        self
+    }
+
+    /// Send keepalive probes over the connections spawned by this listener while they
+    /// are idle. Disabled by default
+    pub fn with_keepalive(mut self, keepalive: TcpKeepalive) -> Self {
+        self.keepalive = Some(keepalive);
+        self
+    }
+
+    /// Bind the listener to the address of the network interface `interface`, e.g. `wg0`,
+    /// instead of the address it is given, whose port is kept. On Linux, the listener is
+    /// also bound to the interface itself, so that it doesn't accept connections received
+    /// by other interfaces
+    pub fn with_bind_interface(mut self, interface: impl Into<String>) -> Self {
+        self.bind_interface = Some(interface.into());
+        self
+    }
+
+    /// Add a filter of the peers which may open a connection to this listener, e.g. a
+    /// [`TcpCidrFilter`](crate::TcpCidrFilter). The connections refused by any filter are
+    /// closed before a worker is spawned for them
+    pub fn with_connection_filter(mut self, filter: impl TcpConnectionFilter) -> Self {
+        self.connection_filters.push(Arc::new(filter));
+        self
+    }
+
     pub(crate) fn access_control(&self) -> TcpConnectionAccessControl {
         match &self.session {
             Some((sessions, listener_session_id)) => {
@@ -88,12 +448,37 @@ impl TcpListenerTrustOptions {
                             .allow_one_message_to_the_listener(listener_session_id.clone())
                             .build(),
                     ),
+                    local_info_producers: self.local_info_producers.clone(),
+                    local_info_passthrough: self.local_info_passthrough.clone(),
+                    heartbeat_reply: self.heartbeat_reply,
+                    heartbeat_interval: self.heartbeat_interval,
+                    ordering: self.ordering.clone(),
+                    mailbox_full_policy: self.mailbox_full_policy,
+                    frame_checksum: self.frame_checksum,
+                    compression: self.compression,
+                    // Spawned connections get a fresh session, which can't be in use
+                    duplicate_session_policy: TcpDuplicateSessionPolicy::Allow,
+                    max_message_len: self.max_message_len,
+                    read_timeout: self.read_timeout,
+                    short_read_retries: self.short_read_retries,
                 }
             }
             None => TcpConnectionAccessControl {
                 session_id: None,
                 sender_incoming_access_control: Arc::new(LocalSourceOnly),
                 receiver_outgoing_access_control: Arc::new(LocalOnwardOnly),
+                local_info_producers: self.local_info_producers.clone(),
+                local_info_passthrough: self.local_info_passthrough.clone(),
+                heartbeat_reply: self.heartbeat_reply,
+                heartbeat_interval: self.heartbeat_interval,
+                ordering: self.ordering.clone(),
+                mailbox_full_policy: self.mailbox_full_policy,
+                frame_checksum: self.frame_checksum,
+                compression: self.compression,
+                duplicate_session_policy: TcpDuplicateSessionPolicy::Allow,
+                max_message_len: self.max_message_len,
+                read_timeout: self.read_timeout,
+                short_read_retries: self.short_read_retries,
             },
         }
     }
@@ -1,21 +1,38 @@
-use crate::workers::{Addresses, ConnectionRole, TcpRecvProcessor};
-use crate::{TcpListenerTrustOptions, TcpRegistry, TcpSendWorker};
+use crate::ordering::HANDSHAKE_TIMEOUT as ORDERING_HANDSHAKE_TIMEOUT;
+use crate::workers::{
+    Addresses, ConnectionOptions, ConnectionRole, TcpReadHalf, TcpRecvProcessor, TcpWriteHalf,
+};
+use crate::{
+    bind_to_interface, TcpConnectionAccessControl, TcpListenerTrustOptions, TcpRegistry,
+    TcpSendWorker,
+};
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, compat::net::SocketAddr, DenyAll};
-use ockam_core::{Address, Processor, Result};
+use ockam_core::{Address, AsyncTryClone, Processor, Result};
 use ockam_node::Context;
 use ockam_transport_core::TransportError;
-use tokio::net::TcpListener;
-use tracing::debug;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, warn};
+
+/// Time given to an incoming connection to complete its TLS handshake, so that a
+/// peer which never completes it doesn't hold on to its connection
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// A TCP Listen processor
 ///
 /// TCP listen processors are created by `TcpTransport`
 /// after a call is made to
-/// [`TcpTransport::listen`](crate::TcpTransport::listen).
+/// [`TcpTransport::listen`](crate::TcpTransport::listen)
+/// or [`TcpTransport::listen_tls`](crate::TcpTransport::listen_tls).
 pub(crate) struct TcpListenProcessor {
     registry: TcpRegistry,
     inner: TcpListener,
     trust_options: TcpListenerTrustOptions,
+    /// Set when the accepted connections are wrapped in TLS
+    tls_config: Option<Arc<ServerConfig>>,
 }
 
 impl TcpListenProcessor {
@@ -24,16 +41,23 @@ impl TcpListenProcessor {
         registry: TcpRegistry,
         addr: SocketAddr,
         trust_options: TcpListenerTrustOptions,
+        tls_config: Option<Arc<ServerConfig>>,
     ) -> Result<(SocketAddr, Address)> {
-        debug!("Binding TcpListener to {}", addr);
-        let inner = TcpListener::bind(addr)
-            .await
-            .map_err(TransportError::from)?;
+        let inner = match &trust_options.bind_interface {
+            Some(interface) => bind_to_interface(addr, interface)?,
+            None => {
+                debug!("Binding TcpListener to {}", addr);
+                TcpListener::bind(addr)
+                    .await
+                    .map_err(TransportError::from)?
+            }
+        };
         let saddr = inner.local_addr().map_err(TransportError::from)?;
         let processor = Self {
             registry,
             inner,
             trust_options,
+            tls_config,
         };
 
         let address = Address::random_tagged("TcpListenProcessor");
@@ -44,6 +68,130 @@ impl TcpListenProcessor {
     }
 }
 
+/// Connection accepted by a [`TcpListenProcessor`], set up out of its accept loop so
+/// that a slow TLS handshake doesn't delay the other incoming connections
+struct AcceptedConnection {
+    registry: TcpRegistry,
+    /// Address of the listener which accepted the connection
+    listener: Address,
+    access_control: TcpConnectionAccessControl,
+    tls: Option<TlsAcceptor>,
+    peer: SocketAddr,
+}
+
+impl AcceptedConnection {
+    /// Complete the TLS handshake if any, and start the worker pair of the connection.
+    /// A failed handshake only drops that connection, the listener keeps going
+    async fn start(mut self, ctx: &Context, stream: TcpStream) -> Result<()> {
+        match self.tls.take() {
+            None => {
+                let (read_half, write_half) = stream.into_split();
+                self.start_connection(ctx, read_half, write_half).await
+            }
+            Some(acceptor) => {
+                let peer = self.peer;
+                let stream = match tokio::time::timeout(
+                    TLS_HANDSHAKE_TIMEOUT,
+                    acceptor.accept(stream),
+                )
+                .await
+                {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(e)) => {
+                        warn!("TLS handshake with peer '{}' failed: {}", peer, e);
+                        self.registry.add_connection_error(peer, &e);
+                        return Ok(());
+                    }
+                    Err(_) => {
+                        warn!("TLS handshake with peer '{}' timed out", peer);
+                        self.registry
+                            .add_connection_error(peer, "TLS handshake timed out");
+                        return Ok(());
+                    }
+                };
+                debug!("TLS handshake completed");
+                let (read_half, write_half) = tokio::io::split(stream);
+                self.start_connection(ctx, read_half, write_half).await
+            }
+        }
+    }
+
+    /// Start the worker pair of the connection
+    async fn start_connection(
+        self,
+        ctx: &Context,
+        mut read_half: impl TcpReadHalf,
+        mut write_half: impl TcpWriteHalf,
+    ) -> Result<()> {
+        let access_control = self.access_control;
+        let peer = self.peer;
+
+        // In strict ordering mode, the messages are kept in order per ordered stream,
+        // which the peer tells before sending any frame
+        let ordering = match tokio::time::timeout(
+            ORDERING_HANDSHAKE_TIMEOUT,
+            access_control
+                .ordering
+                .accept(&mut read_half, &mut write_half),
+        )
+        .await
+        {
+            Ok(Ok(ordering)) => ordering,
+            Ok(Err(e)) => {
+                warn!("Peer '{}' didn't send its ordered stream: {}", peer, e);
+                self.registry.add_connection_error(peer, &e);
+                return Ok(());
+            }
+            Err(_) => {
+                warn!("Ordering handshake with peer '{}' timed out", peer);
+                self.registry
+                    .add_connection_error(peer, "Ordering handshake timed out");
+                return Ok(());
+            }
+        };
+
+        let options = ConnectionOptions {
+            access_control,
+            ordering,
+        };
+        let addresses = Addresses::generate(ConnectionRole::Responder);
+
+        // Tracked before the connection is started, so that a connection closed right
+        // away isn't left behind, see `TcpTransport::migrate_listener`
+        self.registry
+            .add_listener_connection(&self.listener, addresses.sender_address());
+
+        // Worker to receive messages from the Node and send them over the wire
+        if let Err(e) = TcpSendWorker::start(
+            ctx,
+            self.registry.clone(),
+            write_half,
+            &addresses,
+            peer,
+            &options,
+            None,
+        )
+        .await
+        {
+            self.registry
+                .remove_listener_connection(addresses.sender_address());
+            return Err(e);
+        }
+
+        // Processor to receive messages over the wire and forward them to the node.
+        // The session_id of the options (if present) will be added to messages' LocalInfo
+        TcpRecvProcessor::start(
+            ctx,
+            self.registry.clone(),
+            read_half,
+            &addresses,
+            peer,
+            options,
+        )
+        .await
+    }
+}
+
 #[async_trait]
 impl Processor for TcpListenProcessor {
     type Context = Context;
@@ -51,7 +199,12 @@ impl Processor for TcpListenProcessor {
     async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
         ctx.set_cluster(crate::CLUSTER_NAME).await?;
 
-        self.registry.add_listener_processor(&ctx.address());
+        // Kept so that the listener can be moved with the same options
+        self.registry.add_listener_processor(
+            &ctx.address(),
+            self.trust_options.clone(),
+            self.tls_config.clone(),
+        );
 
         Ok(())
     }
@@ -69,35 +222,32 @@ impl Processor for TcpListenProcessor {
         let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
         debug!("TCP connection accepted");
 
-        let access_control = self.trust_options.access_control();
-
-        let addresses = Addresses::generate(ConnectionRole::Responder);
-
-        let (read_half, write_half) = stream.into_split();
-
-        // Worker to receive messages from the Node and send them over the wire
-        TcpSendWorker::start(
-            ctx,
-            self.registry.clone(),
-            write_half,
-            &addresses,
-            peer,
-            access_control.sender_incoming_access_control,
-        )
-        .await?;
-
-        // Processor to receive messages over the wire and forward them to the node
-        TcpRecvProcessor::start(
-            ctx,
-            self.registry.clone(),
-            read_half,
-            &addresses,
+        if !self.trust_options.connection_filters.allow(&peer) {
+            debug!("Refusing the connection from peer '{}'", peer);
+            self.registry
+                .add_connection_error(peer, "connection refused by filter");
+            return Ok(true);
+        }
+
+        if let Some(keepalive) = &self.trust_options.keepalive {
+            if let Err(e) = keepalive.apply(&stream) {
+                warn!("Failed to enable keepalive for peer '{}': {}", peer, e);
+            }
+        }
+
+        let connection = AcceptedConnection {
+            registry: self.registry.clone(),
+            listener: ctx.address(),
+            access_control: self.trust_options.access_control(),
+            tls: self.tls_config.clone().map(TlsAcceptor::from),
             peer,
-            access_control.receiver_outgoing_access_control,
-            // This session_id (if present) will be added to messages' LocalInfo
-            access_control.session_id,
-        )
-        .await?;
+        };
+        let connection_ctx = ctx.async_try_clone().await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.start(&connection_ctx, stream).await {
+                warn!("Failed to start the connection from peer '{}': {}", peer, e);
+            }
+        });
 
         Ok(true)
     }
@@ -7,3 +7,23 @@ pub(crate) use addresses::*;
 pub(crate) use listener::*;
 pub(crate) use receiver::*;
 pub(crate) use sender::*;
+
+use crate::{ConnectionOrdering, TcpConnectionAccessControl};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Options of the worker pair of an established connection
+pub(crate) struct ConnectionOptions {
+    pub(crate) access_control: TcpConnectionAccessControl,
+    /// Ordering of the messages negotiated with the peer, see [`TcpOrdering`](crate::TcpOrdering)
+    pub(crate) ordering: ConnectionOrdering,
+}
+
+/// Read half of a connection, over plain TCP or TLS, read by a [`TcpRecvProcessor`]
+pub(crate) trait TcpReadHalf: AsyncRead + Send + Sync + Unpin + 'static {}
+
+impl<T: AsyncRead + Send + Sync + Unpin + 'static> TcpReadHalf for T {}
+
+/// Write half of a connection, over plain TCP or TLS, written to by a [`TcpSendWorker`]
+pub(crate) trait TcpWriteHalf: AsyncWrite + Send + Sync + Unpin + 'static {}
+
+impl<T: AsyncWrite + Send + Sync + Unpin + 'static> TcpWriteHalf for T {}
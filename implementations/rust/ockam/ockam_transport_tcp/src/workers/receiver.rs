@@ -1,14 +1,26 @@
-use crate::workers::Addresses;
-use crate::{TcpRegistry, TcpSendWorkerMsg};
+use crate::checksum::verify_frame_checksum;
+use crate::compression::decompress_message;
+use crate::connection_stats::ConnectionCounters;
+use crate::workers::{Addresses, ConnectionOptions, TcpReadHalf};
+use crate::{
+    decode_frame_local_info, strip_session_id, ConnectionOrdering, LocalInfoPassthrough,
+    LocalInfoProducers, TcpCloseReason, TcpDuplicateSessionPolicy, TcpEvent, TcpMailboxFullPolicy,
+    TcpRegistry, TcpSendWorkerMsg, HEARTBEAT_REPLY,
+};
+use core::future::Future;
+use core::time::Duration;
+use ockam_core::compat::borrow::Cow;
 use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
 use ockam_core::sessions::{SessionId, SessionIdLocalInfo};
-use ockam_core::{async_trait, DenyAll, Mailbox, Mailboxes, OutgoingAccessControl};
+use ockam_core::{async_trait, DenyAll, Mailbox, Mailboxes};
 use ockam_core::{Decodable, LocalMessage, Processor, Result, TransportMessage};
-use ockam_node::{Context, ProcessorBuilder};
+use ockam_node::{Context, NodeError, ProcessorBuilder};
 use ockam_transport_core::TransportError;
-use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
-use tracing::{error, info, trace};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::net::tcp::OwnedReadHalf;
+use tracing::{debug, error, info, trace, warn};
 
 /// A TCP receiving message processor
 ///
@@ -18,48 +30,89 @@ use tracing::{error, info, trace};
 /// This half of the worker is created when spawning a new connection
 /// worker pair, and listens for incoming TCP packets, to relay into
 /// the node message system.
-pub(crate) struct TcpRecvProcessor {
+pub(crate) struct TcpRecvProcessor<R = OwnedReadHalf> {
     registry: TcpRegistry,
-    read_half: OwnedReadHalf,
+    read_half: R,
     peer: SocketAddr,
     addresses: Addresses,
     session_id: Option<SessionId>,
+    local_info_producers: LocalInfoProducers,
+    /// Types of the [`LocalInfo`](ockam_core::LocalInfo) carried by the received frames
+    /// which are attached to the messages
+    local_info_passthrough: LocalInfoPassthrough,
+    heartbeat_reply: bool,
+    ordering: ConnectionOrdering,
+    mailbox_full_policy: TcpMailboxFullPolicy,
+    frame_checksum: bool,
+    compression: bool,
+    duplicate_session_policy: TcpDuplicateSessionPolicy,
+    /// Frames announcing a longer length close the connection
+    max_message_len: usize,
+    /// The connection is closed when no data is received for that long
+    read_timeout: Option<Duration>,
+    /// Number of times the read of a frame body which stops part way is resumed
+    short_read_retries: usize,
+    /// Traffic of the connection, shared with its sender worker
+    counters: Arc<ConnectionCounters>,
+    /// Set when the session is already used by another connection and the policy
+    /// is to reject the connection
+    rejected: bool,
 }
 
-impl TcpRecvProcessor {
+impl<R: TcpReadHalf> TcpRecvProcessor<R> {
     /// Create a new `TcpRecvProcessor`
     pub fn new(
         registry: TcpRegistry,
-        read_half: OwnedReadHalf,
+        read_half: R,
         peer: SocketAddr,
         addresses: Addresses,
-        session_id: Option<SessionId>,
+        options: ConnectionOptions,
     ) -> Self {
+        let counters = registry.connection_counters(addresses.sender_address());
+        let ConnectionOptions {
+            access_control,
+            ordering,
+        } = options;
         Self {
             registry,
             read_half,
             peer,
             addresses,
-            session_id,
+            session_id: access_control.session_id,
+            local_info_producers: access_control.local_info_producers,
+            local_info_passthrough: access_control.local_info_passthrough,
+            heartbeat_reply: access_control.heartbeat_reply,
+            ordering,
+            mailbox_full_policy: access_control.mailbox_full_policy,
+            frame_checksum: access_control.frame_checksum,
+            compression: access_control.compression,
+            duplicate_session_policy: access_control.duplicate_session_policy,
+            max_message_len: access_control.max_message_len,
+            read_timeout: access_control.read_timeout,
+            short_read_retries: access_control.short_read_retries,
+            counters,
+            rejected: false,
         }
     }
 
     pub async fn start(
         ctx: &Context,
         registry: TcpRegistry,
-        read_half: OwnedReadHalf,
+        read_half: R,
         addresses: &Addresses,
         peer: SocketAddr,
-        receiver_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
-        session_id: Option<SessionId>,
+        options: ConnectionOptions,
     ) -> Result<()> {
-        let receiver =
-            TcpRecvProcessor::new(registry, read_half, peer, addresses.clone(), session_id);
+        let outgoing_access_control = options
+            .access_control
+            .receiver_outgoing_access_control
+            .clone();
+        let receiver = TcpRecvProcessor::new(registry, read_half, peer, addresses.clone(), options);
 
         let mailbox = Mailbox::new(
             addresses.receiver_address().clone(),
             Arc::new(DenyAll),
-            receiver_outgoing_access_control,
+            outgoing_access_control,
         );
         ProcessorBuilder::with_mailboxes(Mailboxes::new(mailbox, vec![]), receiver)
             .start(ctx)
@@ -67,22 +120,135 @@ impl TcpRecvProcessor {
 
         Ok(())
     }
+
+    /// Notify the sender that the connection was closed, and why. The sender may already
+    /// be gone, e.g. if it was stopped at the same time, in which case there is nobody
+    /// left to notify
+    async fn notify_connection_closed(&self, ctx: &Context, reason: TcpCloseReason) {
+        if let Err(e) = ctx
+            .send(
+                self.addresses.sender_internal_addr().clone(),
+                TcpSendWorkerMsg::ConnectionClosed(reason),
+            )
+            .await
+        {
+            debug!(
+                "Sender for peer '{}' is already stopped, not notifying it of the closed connection: {}",
+                self.peer, e
+            );
+        }
+    }
+
+    /// Close the connection, its peer sent nothing within the read timeout
+    async fn close_idle_connection(&self, ctx: &Context) -> Result<bool> {
+        warn!(
+            "Closing the connection to peer '{}', nothing was received within the read timeout",
+            self.peer
+        );
+        self.notify_connection_closed(ctx, TcpCloseReason::ReadTimeout)
+            .await;
+        Ok(false)
+    }
+
+    /// Report a failure to forward a received message to the event subscribers
+    fn forward_error(&self, reason: impl ToString) {
+        self.registry.emit_event(TcpEvent::ForwardError {
+            peer: self.peer,
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Forward a received message, reporting errors to the event subscribers,
+    /// see [`TcpRecvProcessor::try_forward`]
+    async fn forward(&self, ctx: &Context, msg: LocalMessage) -> Result<bool> {
+        self.try_forward(ctx, msg).await.map_err(|e| {
+            self.forward_error(&e);
+            e
+        })
+    }
+
+    /// Forward a received message, applying the [`TcpMailboxFullPolicy`] if the mailbox
+    /// of its next hop is full. Return `false` if the connection must be closed.
+    async fn try_forward(&self, ctx: &Context, msg: LocalMessage) -> Result<bool> {
+        let dropped = match self.mailbox_full_policy {
+            TcpMailboxFullPolicy::Block => {
+                ctx.forward(msg).await?;
+                false
+            }
+            TcpMailboxFullPolicy::BlockWithTimeout(timeout) => {
+                match tokio::time::timeout(timeout, ctx.forward(msg)).await {
+                    Ok(res) => {
+                        res?;
+                        false
+                    }
+                    Err(_) => true,
+                }
+            }
+            TcpMailboxFullPolicy::Drop | TcpMailboxFullPolicy::Close => {
+                match ctx.try_forward(msg).await {
+                    Ok(()) => false,
+                    Err(e) if NodeError::is_mailbox_full(&e) => true,
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+        if !dropped {
+            return Ok(true);
+        }
+
+        self.registry.add_dropped_message();
+        self.forward_error("mailbox full");
+        if self.mailbox_full_policy != TcpMailboxFullPolicy::Close {
+            warn!("Mailbox full, dropped a message from peer '{}'", self.peer);
+            return Ok(true);
+        }
+
+        warn!(
+            "Mailbox full, closing the connection to peer '{}'",
+            self.peer
+        );
+        self.notify_connection_closed(ctx, TcpCloseReason::MailboxFull)
+            .await;
+        Ok(false)
+    }
 }
 
 #[async_trait]
-impl Processor for TcpRecvProcessor {
+impl<R: TcpReadHalf> Processor for TcpRecvProcessor<R> {
     type Context = Context;
 
     async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
         ctx.set_cluster(crate::CLUSTER_NAME).await?;
 
-        self.registry.add_receiver_processor(&ctx.address());
+        self.registry
+            .add_receiver_processor(&ctx.address(), self.peer);
+        self.registry
+            .emit_event(TcpEvent::ConnectionOpened { peer: self.peer });
+
+        if let Some(session_id) = &self.session_id {
+            if !self.registry.claim_session(session_id, &ctx.address())
+                && self.duplicate_session_policy != TcpDuplicateSessionPolicy::Allow
+            {
+                warn!(
+                    "Session {:?} of the connection to peer '{}' is already used by another connection",
+                    session_id, self.peer
+                );
+                self.registry.add_duplicate_session();
+                self.rejected = self.duplicate_session_policy == TcpDuplicateSessionPolicy::Reject;
+            }
+        }
 
         Ok(())
     }
 
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        self.registry.remove_receiver_processor(&ctx.address());
+        self.registry
+            .remove_receiver_processor(&ctx.address(), self.peer);
+        if let Some(session_id) = &self.session_id {
+            self.registry.release_session(session_id, &ctx.address());
+        }
+        self.registry
+            .emit_event(TcpEvent::ConnectionClosed { peer: self.peer });
 
         Ok(())
     }
@@ -99,22 +265,30 @@ impl Processor for TcpRecvProcessor {
     /// 3. We must also stop the TcpReceive loop when the worker gets
     ///    killed by the user or node.
     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
+        if self.rejected {
+            warn!(
+                "Closing the connection to peer '{}', its session is already in use",
+                self.peer
+            );
+            self.notify_connection_closed(ctx, TcpCloseReason::DuplicateSession)
+                .await;
+            return Ok(false);
+        }
+
         // Run in a loop until TcpWorkerPair::stop() is called
         // First read a message length header...
-        let len = match self.read_half.read_u16().await {
-            Ok(len) => len,
-            Err(_e) => {
+        let len = match with_read_timeout(self.read_timeout, self.read_half.read_u16()).await {
+            Some(Ok(len)) => len,
+            None => return self.close_idle_connection(ctx).await,
+            Some(Err(_e)) => {
                 info!(
                     "Connection to peer '{}' was closed; dropping stream",
                     self.peer
                 );
 
                 // Notify sender tx is closed
-                ctx.send(
-                    self.addresses.sender_internal_addr().clone(),
-                    TcpSendWorkerMsg::ConnectionClosed,
-                )
-                .await?;
+                self.notify_connection_closed(ctx, TcpCloseReason::IoError)
+                    .await;
 
                 return Ok(false);
             }
@@ -122,17 +296,82 @@ impl Processor for TcpRecvProcessor {
 
         trace!("Received message header for {} bytes", len);
 
+        if len as usize > self.max_message_len {
+            warn!(
+                "Closing the connection to peer '{}', its message of {} bytes exceeds the maximum of {} bytes",
+                self.peer, len, self.max_message_len
+            );
+            self.notify_connection_closed(ctx, TcpCloseReason::MessageTooLarge)
+                .await;
+            return Ok(false);
+        }
+
         // Allocate a buffer of that size
         let mut buf = vec![0; len as usize];
 
         // Then read into the buffer
-        match self.read_half.read_exact(&mut buf).await {
-            Ok(_) => {}
-            _ => {
+        match read_frame_body(
+            &mut self.read_half,
+            &mut buf,
+            self.short_read_retries,
+            self.read_timeout,
+        )
+        .await
+        {
+            BodyRead::Complete => self.counters.record_in(2 + buf.len()),
+            BodyRead::TimedOut => return self.close_idle_connection(ctx).await,
+            BodyRead::Failed => {
                 error!("Failed to receive message of length: {}", len);
                 return Ok(true);
             }
         }
+        self.registry.emit_event(TcpEvent::FrameReceived {
+            peer: self.peer,
+            size: buf.len(),
+        });
+
+        // With frame checksums, the frame is only accepted if it matches its checksum
+        let buf = if self.frame_checksum {
+            match verify_frame_checksum(&buf) {
+                Some(buf) => buf,
+                None => {
+                    warn!("Rejecting a corrupt frame from peer '{}'", self.peer);
+                    self.registry.add_corrupt_frame();
+                    return Err(TransportError::FrameChecksum.into());
+                }
+            }
+        } else {
+            buf.as_slice()
+        };
+
+        // In strict ordering mode, the message is prefixed by its sequence number
+        let (sequence_number, buf) = match &self.ordering {
+            ConnectionOrdering::BestEffort => (None, buf),
+            ConnectionOrdering::Strict(_) => {
+                if buf.len() < 8 {
+                    return Err(TransportError::RecvBadMessage.into());
+                }
+                let (sequence_number, buf) = buf.split_at(8);
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(sequence_number);
+                (Some(u64::from_be_bytes(bytes)), buf)
+            }
+        };
+
+        // With local info passthrough, the message is prefixed by the local info carried with it
+        let (forwarded_local_info, buf) = if self.local_info_passthrough.is_enabled() {
+            let (local_info, buf) = decode_frame_local_info(buf)?;
+            (self.local_info_passthrough.select(&local_info), buf)
+        } else {
+            (None, buf)
+        };
+
+        // With compression, the message is prefixed by a flag telling if it is compressed
+        let buf = if self.compression {
+            decompress_message(buf, self.max_message_len)?
+        } else {
+            Cow::Borrowed(buf)
+        };
 
         // Deserialize the message now
         let mut msg = TransportMessage::decode(&buf).map_err(|_| TransportError::RecvBadMessage)?;
@@ -140,6 +379,15 @@ impl Processor for TcpRecvProcessor {
         // Heartbeat message
         if msg.onward_route.next().is_err() {
             trace!("Got heartbeat message from: {}", self.peer);
+            self.registry
+                .emit_event(TcpEvent::Heartbeat { peer: self.peer });
+            if self.heartbeat_reply && msg.payload != HEARTBEAT_REPLY {
+                ctx.send(
+                    self.addresses.sender_internal_addr().clone(),
+                    TcpSendWorkerMsg::Heartbeat,
+                )
+                .await?;
+            }
             return Ok(true);
         }
 
@@ -152,14 +400,336 @@ impl Processor for TcpRecvProcessor {
         trace!("Message onward route: {}", msg.onward_route);
         trace!("Message return route: {}", msg.return_route);
 
-        let local_info = match &self.session_id {
+        let mut local_info = match &self.session_id {
             Some(session_id) => vec![SessionIdLocalInfo::new(session_id.clone()).to_local_info()?],
             None => vec![],
         };
+        self.local_info_producers
+            .produce(&self.peer, &msg, &mut local_info);
+        // The session of the message is the one of the connection, the peer can't claim one
+        let mut forwarded_local_info = forwarded_local_info.unwrap_or_default();
+        if strip_session_id(&mut forwarded_local_info) {
+            warn!("Dropped the session id forwarded by peer '{}'", self.peer);
+        }
+        local_info.extend(forwarded_local_info);
+
+        let msg = LocalMessage::new(msg, local_info);
+
+        // Forward the message to the next hop in the route, possibly
+        // along with buffered messages which can now be delivered in order
+        match (&self.ordering, sequence_number) {
+            (ConnectionOrdering::Strict(stream), Some(sequence_number)) => {
+                for msg in stream.reorder(sequence_number, msg) {
+                    if !self.forward(ctx, msg).await? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            _ => self.forward(ctx, msg).await,
+        }
+    }
+}
+
+/// Outcome of the read of a frame body
+#[derive(Debug, PartialEq, Eq)]
+enum BodyRead {
+    Complete,
+    /// No data was received for the read timeout
+    TimedOut,
+    /// The connection failed or was closed before the end of the body
+    Failed,
+}
+
+/// Fill `buf` with the body of a frame. A read which fails or times out after part of
+/// the body was received is resumed where it stopped, at most `retries` times. Nothing
+/// is read past the end of `buf`, so that the next frame is left untouched
+async fn read_frame_body<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buf: &mut [u8],
+    mut retries: usize,
+    read_timeout: Option<Duration>,
+) -> BodyRead {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let failure = match with_read_timeout(read_timeout, reader.read(&mut buf[filled..])).await {
+            // The peer closed the connection
+            Some(Ok(0)) => return BodyRead::Failed,
+            Some(Ok(n)) => {
+                filled += n;
+                continue;
+            }
+            Some(Err(_)) => BodyRead::Failed,
+            None => BodyRead::TimedOut,
+        };
+        if filled == 0 || retries == 0 {
+            return failure;
+        }
+        retries -= 1;
+        debug!(
+            "Resuming the read of a frame after {} of {} bytes",
+            filled,
+            buf.len()
+        );
+    }
+    BodyRead::Complete
+}
+
+/// Run `read`, giving up after `timeout` if there is one
+async fn with_read_timeout<F: Future>(timeout: Option<Duration>, read: F) -> Option<F::Output> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, read).await.ok(),
+        None => Some(read.await),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_frame_body, with_read_timeout, BodyRead, TcpRecvProcessor};
+    use crate::workers::{Addresses, ConnectionOptions, ConnectionRole};
+    use crate::{
+        encode_frame_local_info, ConnectionOrdering, TcpConnectionTrustOptions, TcpRegistry,
+    };
+    use core::time::Duration;
+    use ockam_core::compat::sync::Arc;
+    use ockam_core::sessions::{SessionIdLocalInfo, Sessions, SESSION_ID_IDENTIFIER};
+    use ockam_core::{route, AllowAll, Encodable, Mailboxes, Result, TransportMessage};
+    use ockam_node::Context;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Options of a connection with `trust_options` and no ordering guarantees
+    fn best_effort(trust_options: TcpConnectionTrustOptions) -> ConnectionOptions {
+        ConnectionOptions {
+            access_control: trust_options.access_control(),
+            ordering: ConnectionOrdering::BestEffort,
+        }
+    }
+
+    /// Wait until the receiver processor at `addresses` is registered, or not
+    async fn wait_for_receiver(registry: &TcpRegistry, addresses: &Addresses, running: bool) {
+        for _ in 0..100 {
+            if registry.has_receiver_processor(addresses.receiver_address()) == running {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("the receiver processor running state should be {running}");
+    }
+
+    #[ockam_macros::test]
+    async fn receiver_stops_when_the_sender_is_already_gone(ctx: &mut Context) -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let peer = listener.local_addr().unwrap();
+        let client = TcpStream::connect(peer).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        let (read_half, _write_half) = server.into_split();
+
+        // No sender is started, as if it had already stopped
+        let registry = TcpRegistry::default();
+        let addresses = Addresses::generate(ConnectionRole::Responder);
+        TcpRecvProcessor::start(
+            ctx,
+            registry.clone(),
+            read_half,
+            &addresses,
+            peer,
+            best_effort(TcpConnectionTrustOptions::new()),
+        )
+        .await?;
+        wait_for_receiver(&registry, &addresses, true).await;
+
+        // The connection is closed, the receiver can't notify the sender but stops anyway
+        drop(client);
+        wait_for_receiver(&registry, &addresses, false).await;
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn receiver_reads_frames_from_an_in_memory_stream(ctx: &mut Context) -> Result<()> {
+        let mut collector = ctx
+            .new_detached_with_mailboxes(Mailboxes::main(
+                "collector",
+                Arc::new(AllowAll),
+                Arc::new(AllowAll),
+            ))
+            .await?;
+
+        let (mut peer, read_half) = tokio::io::duplex(1024);
+        let registry = TcpRegistry::default();
+        let addresses = Addresses::generate(ConnectionRole::Responder);
+        TcpRecvProcessor::start(
+            ctx,
+            registry.clone(),
+            read_half,
+            &addresses,
+            "127.0.0.1:4000".parse().unwrap(),
+            best_effort(TcpConnectionTrustOptions::new()),
+        )
+        .await?;
+        wait_for_receiver(&registry, &addresses, true).await;
+
+        // A heartbeat, which isn't forwarded, followed by a message
+        for onward_route in [route![], route!["collector"]] {
+            let msg = TransportMessage::v1(onward_route, route![], "hello".to_string().encode()?);
+            let frame = msg.encode()?;
+            peer.write_u16(frame.len() as u16).await.unwrap();
+            peer.write_all(&frame).await.unwrap();
+        }
+        assert_eq!(collector.receive::<String>().await?.take().body(), "hello");
+        let stats = registry
+            .connection_stats(addresses.sender_address())
+            .unwrap();
+        assert_eq!(stats.messages_in, 2);
+
+        // The receiver stops once the stream is closed
+        drop(peer);
+        wait_for_receiver(&registry, &addresses, false).await;
+
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn receiver_replaces_a_forwarded_session_id(ctx: &mut Context) -> Result<()> {
+        let mut collector = ctx
+            .new_detached_with_mailboxes(Mailboxes::main(
+                "collector",
+                Arc::new(AllowAll),
+                Arc::new(AllowAll),
+            ))
+            .await?;
+
+        let sessions = Sessions::default();
+        let session_id = sessions.generate_session_id();
+        let mut options = best_effort(
+            TcpConnectionTrustOptions::new()
+                .with_session(&sessions, &session_id)
+                .with_local_info_passthrough(SESSION_ID_IDENTIFIER),
+        );
+        // Let the message reach the collector, which isn't part of the session
+        options.access_control.receiver_outgoing_access_control = Arc::new(AllowAll);
+
+        let (mut peer, read_half) = tokio::io::duplex(1024);
+        let registry = TcpRegistry::default();
+        let addresses = Addresses::generate(ConnectionRole::Responder);
+        TcpRecvProcessor::start(
+            ctx,
+            registry.clone(),
+            read_half,
+            &addresses,
+            "127.0.0.1:4000".parse().unwrap(),
+            options,
+        )
+        .await?;
+        wait_for_receiver(&registry, &addresses, true).await;
+
+        // The peer claims another session for its message
+        let forged = SessionIdLocalInfo::new(sessions.generate_session_id()).to_local_info()?;
+        let msg =
+            TransportMessage::v1(route!["collector"], route![], "hello".to_string().encode()?);
+        let mut frame = encode_frame_local_info(&[forged])?;
+        frame.extend(msg.encode()?);
+        peer.write_u16(frame.len() as u16).await.unwrap();
+        peer.write_all(&frame).await.unwrap();
+
+        let msg = collector.receive::<String>().await?.take();
+        let local_msg = msg.local_message();
+        let session_infos = local_msg
+            .local_info()
+            .iter()
+            .filter(|info| info.type_identifier() == SESSION_ID_IDENTIFIER)
+            .count();
+        assert_eq!(session_infos, 1);
+        assert_eq!(
+            SessionIdLocalInfo::find_info(local_msg)?.session_id(),
+            &session_id
+        );
+
+        drop(peer);
+        wait_for_receiver(&registry, &addresses, false).await;
+
+        ctx.stop().await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn read_timeout_restarts_with_every_read() {
+        let (mut peer, mut read_half) = tokio::io::duplex(64);
+        let timeout = Some(Duration::from_secs(30));
+
+        // Frames sent more often than the timeout keep the connection alive
+        for len in [1u16, 2, 3] {
+            tokio::time::sleep(Duration::from_secs(20)).await;
+            peer.write_u16(len).await.unwrap();
+            let read = with_read_timeout(timeout, read_half.read_u16()).await;
+            assert_eq!(read.map(|r| r.unwrap()), Some(len));
+        }
+
+        // A silent peer times out
+        let started = tokio::time::Instant::now();
+        assert!(with_read_timeout(timeout, read_half.read_u16())
+            .await
+            .is_none());
+        assert_eq!(started.elapsed(), Duration::from_secs(30));
+
+        // Without a timeout the read waits for the peer
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            peer.write_u16(4).await.unwrap();
+        });
+        let read = with_read_timeout(None, read_half.read_u16()).await;
+        assert_eq!(read.map(|r| r.unwrap()), Some(4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn short_read_is_resumed_up_to_the_frame_length() {
+        let (mut peer, mut read_half) = tokio::io::duplex(64);
+        let timeout = Some(Duration::from_secs(30));
+
+        // The body stalls past the read timeout after a few bytes, then the rest
+        // of the body arrives, followed by the next frame
+        tokio::spawn(async move {
+            peer.write_all(b"hel").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(45)).await;
+            peer.write_all(b"lo").await.unwrap();
+            peer.write_all(b"next").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+        });
+
+        let mut body = [0u8; 5];
+        let read = read_frame_body(&mut read_half, &mut body, 1, timeout).await;
+        assert_eq!(read, BodyRead::Complete);
+        assert_eq!(&body, b"hello");
+
+        // The next frame wasn't touched
+        let mut next = [0u8; 4];
+        read_half.read_exact(&mut next).await.unwrap();
+        assert_eq!(&next, b"next");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn short_read_is_given_up_past_the_retries() {
+        let (mut peer, mut read_half) = tokio::io::duplex(64);
+        let timeout = Some(Duration::from_secs(30));
+
+        tokio::spawn(async move {
+            peer.write_all(b"hel").await.unwrap();
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+        });
+
+        let mut body = [0u8; 5];
+        let read = read_frame_body(&mut read_half, &mut body, 0, timeout).await;
+        assert_eq!(read, BodyRead::TimedOut);
+    }
 
-        // Forward the message to the next hop in the route
-        ctx.forward(LocalMessage::new(msg, local_info)).await?;
+    #[tokio::test]
+    async fn short_read_is_not_resumed_when_the_peer_is_gone() {
+        let (mut peer, mut read_half) = tokio::io::duplex(64);
+        peer.write_all(b"hel").await.unwrap();
+        drop(peer);
 
-        Ok(true)
+        let mut body = [0u8; 5];
+        let read = read_frame_body(&mut read_half, &mut body, 3, None).await;
+        assert_eq!(read, BodyRead::Failed);
     }
 }
@@ -1,30 +1,70 @@
-use crate::workers::Addresses;
-use crate::TcpRegistry;
-use cfg_if::cfg_if;
+use crate::checksum::frame_checksum;
+use crate::compression::compress_message;
+use crate::connection_stats::ConnectionCounters;
+use crate::workers::{Addresses, ConnectionOptions, TcpWriteHalf};
+use crate::{
+    encode_frame_local_info, ConnectionOrdering, LocalInfoPassthrough, TcpKeepalive, TcpRedial,
+    TcpRegistry, UNORDERED_SEQUENCE_NUMBER,
+};
 use core::time::Duration;
 use ockam_core::{
     async_trait,
-    compat::{net::SocketAddr, sync::Arc},
-    AllowSourceAddress, DenyAll, IncomingAccessControl,
+    compat::{collections::VecDeque, net::SocketAddr, sync::Arc},
+    AllowSourceAddresses, DenyAll,
 };
 use ockam_core::{
-    Any, Decodable, Encodable, Mailbox, Mailboxes, Message, Result, Routed, TransportMessage,
-    Worker,
+    route, Any, Decodable, Encodable, Error, LocalInfo, Mailbox, Mailboxes, Message, Result,
+    Routed, TransportMessage, Worker,
 };
-use ockam_node::{Context, WorkerBuilder};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
 use ockam_transport_core::TransportError;
 use serde::{Deserialize, Serialize};
-use socket2::{SockRef, TcpKeepalive};
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncWriteExt, ReadHalf, WriteHalf};
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
+use tokio_rustls::rustls::{ClientConfig, ServerName};
+use tokio_rustls::{client, TlsConnector};
 use tracing::{debug, info, trace, warn};
 
 #[derive(Serialize, Deserialize, Message, Clone)]
 pub(crate) enum TcpSendWorkerMsg {
-    ConnectionClosed,
+    /// The receiver closed the connection, for the given reason
+    ConnectionClosed(TcpCloseReason),
+    /// A heartbeat was received and must be replied to
+    Heartbeat,
+    /// Nothing was sent for the heartbeat interval, a heartbeat must be sent
+    SendHeartbeat,
+    /// The lost connection must be re-established, see [`TcpRedial`]
+    Redial,
+}
+
+/// Why the receiver closed the connection
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TcpCloseReason {
+    /// The peer closed the connection, or reading from it failed
+    IoError,
+    /// Nothing was received from the peer within the read timeout
+    ReadTimeout,
+    /// The peer sent a message larger than the maximum message length
+    MessageTooLarge,
+    /// A received message couldn't be delivered, its next hop's mailbox is full
+    MailboxFull,
+    /// The session of the connection is already used by another connection
+    DuplicateSession,
 }
 
+impl TcpCloseReason {
+    /// Only a connection lost to an I/O error is re-established. The other closes
+    /// are decided by the receiver, the peer must not be dialed again
+    fn should_redial(&self) -> bool {
+        *self == TcpCloseReason::IoError
+    }
+}
+
+/// Payload of a heartbeat reply. Heartbeat replies are never replied to,
+/// so that two peers replying to heartbeats don't loop forever
+pub(crate) const HEARTBEAT_REPLY: &[u8] = b"heartbeat_reply";
+
 pub(crate) enum ConnectionRole {
     Initiator,
     Responder,
@@ -47,55 +87,282 @@ impl ConnectionRole {
 /// This half of the worker is created when spawning a new connection
 /// worker pair, and listens for messages from the node message system
 /// to dispatch to a remote peer.
-pub(crate) struct TcpSendWorker {
+pub(crate) struct TcpSendWorker<W = OwnedWriteHalf> {
     registry: TcpRegistry,
-    write_half: OwnedWriteHalf,
+    write_half: W,
     peer: SocketAddr,
     addresses: Addresses,
     rx_should_be_stopped: bool,
+    ordering: ConnectionOrdering,
+    frame_checksum: bool,
+    compression: bool,
+    /// Types of the [`LocalInfo`](ockam_core::LocalInfo) carried by the sent frames
+    local_info_passthrough: LocalInfoPassthrough,
+    /// Traffic of the connection, shared with its receiver processor
+    counters: Arc<ConnectionCounters>,
+    heartbeat: DelayedEvent<TcpSendWorkerMsg>,
+    heartbeat_interval: Option<Duration>,
+    /// Set if the connection is re-established once it was lost
+    redial: Option<Arc<dyn TcpRedial<W>>>,
+    /// Next attempt to re-establish the lost connection, set along with `redial`
+    redial_event: Option<DelayedEvent<TcpSendWorkerMsg>>,
+    /// Number of failed attempts to re-establish the lost connection
+    redial_attempts: u32,
+    /// False while the connection is lost and being re-established
+    connected: bool,
+    /// Messages to send once the connection is re-established
+    pending: VecDeque<PendingMessage>,
 }
 
-impl TcpSendWorker {
+/// Message kept to be sent again if the connection is lost
+#[derive(Clone)]
+struct PendingMessage {
+    msg: TransportMessage,
+    local_info: Option<Vec<LocalInfo>>,
+    /// Sequence number given to the message when it was first sent, in strict ordering mode
+    sequence_number: Option<u64>,
+}
+
+impl<W: TcpWriteHalf> TcpSendWorker<W> {
     /// Create a new `TcpSendWorker`
     fn new(
         registry: TcpRegistry,
-        write_half: OwnedWriteHalf,
+        write_half: W,
         peer: SocketAddr,
         addresses: Addresses,
+        options: &ConnectionOptions,
+        heartbeat: DelayedEvent<TcpSendWorkerMsg>,
+        redial: Option<(Arc<dyn TcpRedial<W>>, DelayedEvent<TcpSendWorkerMsg>)>,
     ) -> Self {
+        let (redial, redial_event) = match redial {
+            Some((redial, redial_event)) => (Some(redial), Some(redial_event)),
+            None => (None, None),
+        };
+        let counters = registry.connection_counters(addresses.sender_address());
+        let access_control = &options.access_control;
         Self {
             registry,
             write_half,
             peer,
             addresses,
             rx_should_be_stopped: true,
+            ordering: options.ordering.clone(),
+            frame_checksum: access_control.frame_checksum,
+            compression: access_control.compression,
+            local_info_passthrough: access_control.local_info_passthrough.clone(),
+            counters,
+            heartbeat,
+            heartbeat_interval: access_control.heartbeat_interval,
+            redial,
+            redial_event,
+            redial_attempts: 0,
+            connected: true,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Schedule the next heartbeat, if heartbeats are enabled. Any heartbeat scheduled
+    /// before is cancelled
+    async fn schedule_heartbeat(&mut self) -> Result<()> {
+        match self.heartbeat_interval {
+            Some(interval) => self.heartbeat.schedule(interval).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Write a frame to the connection. The frame is flushed right away, since a TLS
+    /// stream may otherwise keep it buffered
+    async fn write_frame(&mut self, frame: &[u8]) -> std::io::Result<()> {
+        self.write_half.write_all(frame).await?;
+        self.write_half.flush().await?;
+        self.counters.record_out(frame.len());
+        Ok(())
+    }
+
+    /// Send a message to the peer. If the connection is lost and re-established, the
+    /// message is kept to be sent once it is. Return false if the message couldn't be sent
+    async fn send_message(
+        &mut self,
+        msg: TransportMessage,
+        local_info: Option<Vec<LocalInfo>>,
+    ) -> Result<bool> {
+        self.send_pending(PendingMessage {
+            msg,
+            local_info,
+            sequence_number: None,
+        })
+        .await
+    }
+
+    /// Send a message, possibly sent before the connection was lost, see
+    /// [`TcpSendWorker::send_message`]
+    async fn send_pending(&mut self, mut pending: PendingMessage) -> Result<bool> {
+        if !self.connected {
+            self.pending.push_back(pending);
+            return Ok(true);
+        }
+
+        // A message sent again keeps its sequence number, so that the peer doesn't
+        // wait for the one it was given before the connection was lost
+        if pending.sequence_number.is_none() {
+            pending.sequence_number = self.sequence_number(true);
+        }
+        let retry = self.redial.is_some().then(|| pending.clone());
+        // Create a message buffer with prepended length
+        let frame = prepare_message(
+            pending.msg,
+            pending.sequence_number,
+            pending.local_info,
+            self.frame_checksum,
+            self.compression,
+        )?;
+
+        if self.write_frame(&frame).await.is_err() {
+            match retry {
+                // The receiver notices the connection loss, which triggers the reconnection.
+                // The message is sent again before the ones which were held after it
+                Some(retry) => {
+                    debug!("Failed to send message to peer {}, holding it", self.peer);
+                    self.connected = false;
+                    self.pending.push_front(retry);
+                }
+                None => return Ok(false),
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Schedule the next attempt to re-establish the lost connection. The worker stops
+    /// if the peer can't be reached anymore, `last_error` being why the last attempt failed
+    async fn schedule_redial(
+        &mut self,
+        ctx: &Context,
+        redial: &Arc<dyn TcpRedial<W>>,
+        last_error: Option<Error>,
+    ) -> Result<()> {
+        self.connected = false;
+        if let (Some(delay), Some(redial_event)) = (
+            redial.delay(self.redial_attempts),
+            self.redial_event.as_mut(),
+        ) {
+            return redial_event.schedule(delay).await;
+        }
+
+        let reason = match last_error {
+            Some(e) => format!(
+                "gave up reconnecting after {} attempts: {}",
+                self.redial_attempts, e
+            ),
+            None => format!(
+                "gave up reconnecting after {} attempts",
+                self.redial_attempts
+            ),
+        };
+        warn!("Peer {}: {}", self.peer, reason);
+        self.registry.add_connection_error(self.peer, reason);
+        self.rx_should_be_stopped = false;
+        self.stop(ctx).await
+    }
+
+    /// Try to re-establish the lost connection, and send the messages held while it was
+    /// lost. Another attempt is scheduled if this one fails
+    async fn reconnect(&mut self, ctx: &Context, redial: Arc<dyn TcpRedial<W>>) -> Result<()> {
+        match redial.redial(ctx, &self.addresses).await {
+            Ok(write_half) => {
+                self.write_half = write_half;
+                self.connected = true;
+                self.redial_attempts = 0;
+            }
+            Err(e) => {
+                self.redial_attempts += 1;
+                debug!(
+                    addr = %self.peer, attempt = %self.redial_attempts, err = %e,
+                    "Failed to reconnect"
+                );
+                return self.schedule_redial(ctx, &redial, Some(e)).await;
+            }
+        }
+
+        while self.connected {
+            let pending = match self.pending.pop_front() {
+                Some(pending) => pending,
+                None => break,
+            };
+            if !self.send_pending(pending).await? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sequence number to prepend to a frame, in strict ordering mode.
+    /// Only frames which are part of the ordered stream consume a sequence number
+    fn sequence_number(&self, ordered: bool) -> Option<u64> {
+        match &self.ordering {
+            ConnectionOrdering::BestEffort => None,
+            ConnectionOrdering::Strict(_) if !ordered => Some(UNORDERED_SEQUENCE_NUMBER),
+            ConnectionOrdering::Strict(stream) => Some(stream.next_sequence_number()),
         }
     }
 }
 
-impl TcpSendWorker {
+impl<W: TcpWriteHalf> TcpSendWorker<W> {
     /// Create a `(TcpSendWorker, TcpRecvProcessor)` pair that opens and
     /// manages the connection with the given peer
     pub(crate) async fn start(
         ctx: &Context,
         registry: TcpRegistry,
-        write_half: OwnedWriteHalf,
+        write_half: W,
         addresses: &Addresses,
         peer: SocketAddr,
-        sender_incoming_access_control: Arc<dyn IncomingAccessControl>,
+        options: &ConnectionOptions,
+        redial: Option<Arc<dyn TcpRedial<W>>>,
     ) -> Result<()> {
         trace!("Creating new TCP worker pair");
-        let sender_worker = Self::new(registry, write_half, peer, addresses.clone());
+        let heartbeat = DelayedEvent::create(
+            ctx,
+            addresses.sender_internal_addr().clone(),
+            TcpSendWorkerMsg::SendHeartbeat,
+        )
+        .await?;
+        let mut internal_senders = vec![addresses.receiver_address().clone(), heartbeat.address()];
+        let redial = match redial {
+            Some(redial) => {
+                let redial_event = DelayedEvent::create(
+                    ctx,
+                    addresses.sender_internal_addr().clone(),
+                    TcpSendWorkerMsg::Redial,
+                )
+                .await?;
+                internal_senders.push(redial_event.address());
+                Some((redial, redial_event))
+            }
+            None => None,
+        };
+        let sender_worker = Self::new(
+            registry,
+            write_half,
+            peer,
+            addresses.clone(),
+            options,
+            heartbeat,
+            redial,
+        );
 
         let main_mailbox = Mailbox::new(
             addresses.sender_address().clone(),
-            sender_incoming_access_control,
+            options
+                .access_control
+                .sender_incoming_access_control
+                .clone(),
             Arc::new(DenyAll),
         );
 
         let internal_mailbox = Mailbox::new(
             addresses.sender_internal_addr().clone(),
-            Arc::new(AllowSourceAddress(addresses.receiver_address().clone())),
+            Arc::new(AllowSourceAddresses(internal_senders)),
             Arc::new(DenyAll),
         );
 
@@ -115,10 +382,62 @@ impl TcpSendWorker {
 
         Ok(())
     }
+}
 
-    pub(crate) async fn connect(peer: SocketAddr) -> Result<(OwnedReadHalf, OwnedWriteHalf)> {
+impl TcpSendWorker {
+    pub(crate) async fn connect(
+        peer: SocketAddr,
+        keepalive: &TcpKeepalive,
+        connect_timeout: Option<Duration>,
+    ) -> Result<(OwnedReadHalf, OwnedWriteHalf)> {
+        Ok(Self::connect_stream(peer, keepalive, connect_timeout)
+            .await?
+            .into_split())
+    }
+
+    /// Connect to `peer` and perform a TLS handshake with it, expecting its certificate
+    /// to be valid for `server_name`
+    pub(crate) async fn connect_tls(
+        peer: SocketAddr,
+        server_name: ServerName,
+        tls_config: Arc<ClientConfig>,
+        keepalive: &TcpKeepalive,
+        connect_timeout: Option<Duration>,
+    ) -> Result<(
+        ReadHalf<client::TlsStream<TcpStream>>,
+        WriteHalf<client::TlsStream<TcpStream>>,
+    )> {
+        let connection = Self::connect_stream(peer, keepalive, connect_timeout).await?;
+        let stream = TlsConnector::from(tls_config)
+            .connect(server_name, connection)
+            .await
+            .map_err(|e| {
+                debug!(addr = %peer, err = %e, "TLS handshake failed");
+                TransportError::from(e)
+            })?;
+        debug!(addr = %peer, "TLS handshake completed");
+
+        Ok(tokio::io::split(stream))
+    }
+
+    async fn connect_stream(
+        peer: SocketAddr,
+        keepalive: &TcpKeepalive,
+        connect_timeout: Option<Duration>,
+    ) -> Result<TcpStream> {
         debug!(addr = %peer, "Connecting");
-        let connection = match TcpStream::connect(peer).await {
+        let connect = TcpStream::connect(peer);
+        let connected = match connect_timeout {
+            Some(connect_timeout) => match tokio::time::timeout(connect_timeout, connect).await {
+                Ok(connected) => connected,
+                Err(_) => {
+                    debug!(addr = %peer, timeout = ?connect_timeout, "Timed out connecting");
+                    return Err(TransportError::ConnectTimeout.into());
+                }
+            },
+            None => connect.await,
+        };
+        let connection = match connected {
             Ok(c) => {
                 debug!(addr = %peer, "Connected");
                 c
@@ -129,25 +448,14 @@ impl TcpSendWorker {
             }
         };
 
-        let mut keepalive = TcpKeepalive::new()
-            .with_time(Duration::from_secs(300))
-            .with_interval(Duration::from_secs(75));
-
-        cfg_if! {
-            if #[cfg(unix)] {
-               keepalive = keepalive.with_retries(2);
-            }
-        }
-
-        let socket = SockRef::from(&connection);
-        socket.set_tcp_keepalive(&keepalive).unwrap();
+        keepalive.apply(&connection)?;
 
-        Ok(connection.into_split())
+        Ok(connection)
     }
 }
 
 #[async_trait]
-impl Worker for TcpSendWorker {
+impl<W: TcpWriteHalf> Worker for TcpSendWorker<W> {
     type Context = Context;
     type Message = Any;
 
@@ -156,6 +464,7 @@ impl Worker for TcpSendWorker {
 
         self.registry
             .add_sender_worker(self.addresses.sender_address());
+        self.schedule_heartbeat().await?;
 
         Ok(())
     }
@@ -180,13 +489,43 @@ impl Worker for TcpSendWorker {
         ctx: &mut Context,
         msg: Routed<Self::Message>,
     ) -> Result<()> {
+        self.heartbeat.cancel();
+
         let recipient = msg.msg_addr();
         if &recipient == self.addresses.sender_internal_addr() {
             let msg = TcpSendWorkerMsg::decode(msg.payload())?;
 
-            match msg {
-                TcpSendWorkerMsg::ConnectionClosed => {
-                    info!("Stopping sender due to closed connection {}", self.peer);
+            let (payload, kind) = match msg {
+                TcpSendWorkerMsg::ConnectionClosed(reason)
+                    if reason.should_redial() && self.redial.is_some() =>
+                {
+                    info!("Reconnecting due to closed connection {}", self.peer);
+                    let redial = self.redial.clone().unwrap();
+                    self.redial_attempts = 0;
+                    return self.schedule_redial(ctx, &redial, None).await;
+                }
+                TcpSendWorkerMsg::Redial if !self.connected => {
+                    let redial = self.redial.clone().unwrap();
+                    self.reconnect(ctx, redial).await?;
+                    if self.connected {
+                        self.schedule_heartbeat().await?;
+                    }
+
+                    return Ok(());
+                }
+                // The connection was already re-established
+                TcpSendWorkerMsg::Redial => return Ok(()),
+                // Heartbeats are only exchanged over an established connection
+                TcpSendWorkerMsg::Heartbeat | TcpSendWorkerMsg::SendHeartbeat
+                    if !self.connected =>
+                {
+                    return Ok(());
+                }
+                TcpSendWorkerMsg::ConnectionClosed(reason) => {
+                    info!(
+                        "Stopping sender due to closed connection {} ({:?})",
+                        self.peer, reason
+                    );
                     // No need to stop Receiver as it notified us about connection drop and will
                     // stop itself
                     self.rx_should_be_stopped = false;
@@ -194,16 +533,39 @@ impl Worker for TcpSendWorker {
 
                     return Ok(());
                 }
+                TcpSendWorkerMsg::Heartbeat => {
+                    trace!("Replying to heartbeat from {}", self.peer);
+                    (HEARTBEAT_REPLY.to_vec(), "heartbeat reply")
+                }
+                TcpSendWorkerMsg::SendHeartbeat => {
+                    trace!("Sending heartbeat to {}", self.peer);
+                    (vec![], "heartbeat")
+                }
+            };
+            let frame = prepare_message(
+                TransportMessage::v1(route![], route![], payload),
+                self.sequence_number(false),
+                self.local_info_passthrough.select(&[]),
+                self.frame_checksum,
+                self.compression,
+            )?;
+
+            if self.write_frame(&frame).await.is_err() {
+                warn!("Failed to send {} to peer {}", kind, self.peer);
+                self.stop(ctx).await?;
+
+                return Ok(());
             }
         } else {
+            let local_info = self
+                .local_info_passthrough
+                .select(msg.local_message().local_info());
             let mut msg = msg.into_transport_message();
             // Remove our own address from the route so the other end
             // knows what to do with the incoming message
             msg.onward_route.step()?;
-            // Create a message buffer with prepended length
-            let msg = prepare_message(msg)?;
 
-            if self.write_half.write_all(msg.as_slice()).await.is_err() {
+            if !self.send_message(msg, local_info).await? {
                 warn!("Failed to send message to peer {}", self.peer);
                 self.stop(ctx).await?;
 
@@ -211,6 +573,10 @@ impl Worker for TcpSendWorker {
             }
         }
 
+        if self.connected {
+            self.schedule_heartbeat().await?;
+        }
+
         Ok(())
     }
 }
@@ -219,10 +585,49 @@ impl Worker for TcpSendWorker {
 /// `TransportMessage`'s payload
 ///
 /// The length-prefix is encoded as a big-endian 16-bit unsigned
-/// integer.
-fn prepare_message(msg: TransportMessage) -> Result<Vec<u8>> {
+/// integer. In strict ordering mode, the payload is itself prefixed by
+/// its sequence number, encoded as a big-endian 64-bit unsigned integer.
+/// With compression, the serialized message is prefixed by a flag byte and
+/// possibly compressed, see [`compress_message`].
+/// With local info passthrough, the message is prefixed by the local info carried
+/// with it, see [`encode_frame_local_info`].
+/// With frame checksums, the CRC-32 of the payload is appended to it, encoded
+/// as a big-endian 32-bit unsigned integer.
+fn prepare_message(
+    msg: TransportMessage,
+    sequence_number: Option<u64>,
+    local_info: Option<Vec<LocalInfo>>,
+    checksum: bool,
+    compression: bool,
+) -> Result<Vec<u8>> {
     let mut msg_buf = msg.encode().map_err(|_| TransportError::SendBadMessage)?;
 
+    if compression {
+        msg_buf = compress_message(msg_buf);
+    }
+
+    if let Some(local_info) = local_info {
+        let mut buf = encode_frame_local_info(&local_info)?;
+        buf.append(&mut msg_buf);
+        msg_buf = buf;
+    }
+
+    if let Some(sequence_number) = sequence_number {
+        let mut buf = sequence_number.to_be_bytes().to_vec();
+        buf.append(&mut msg_buf);
+        msg_buf = buf;
+    }
+
+    if checksum {
+        let checksum = frame_checksum(&msg_buf);
+        msg_buf.extend_from_slice(&checksum.to_be_bytes());
+    }
+
+    // The frame length is encoded on 16 bits, the peer couldn't read a larger frame
+    if msg_buf.len() > u16::MAX as usize {
+        return Err(TransportError::SendBadMessage.into());
+    }
+
     // Create a buffer that includes the message length in big endian
     let mut len = (msg_buf.len() as u16).to_be_bytes().to_vec();
 
@@ -0,0 +1,76 @@
+use ockam_core::Result;
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpTransport};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn bind_interface__loopback_address__refuses_other_addresses(
+    ctx: &mut Context,
+) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+
+    assert!(transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new()
+        )
+        .await
+        .is_ok());
+
+    // Same port, another address of the host
+    let other_address = SocketAddr::new(
+        IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)),
+        listener_address.port(),
+    );
+    assert!(transport
+        .connect(other_address.to_string(), TcpConnectionTrustOptions::new())
+        .await
+        .is_err());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn bind_interface__interface_name__binds_to_its_address(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "0.0.0.0:0",
+            TcpListenerTrustOptions::new().with_bind_interface("lo"),
+        )
+        .await?;
+    assert!(listener_address.ip().is_loopback());
+
+    assert!(transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new()
+        )
+        .await
+        .is_ok());
+
+    // Unknown interfaces can't be bound to
+    assert!(transport
+        .listen(
+            "0.0.0.0:0",
+            TcpListenerTrustOptions::new().with_bind_interface("not-an-interface"),
+        )
+        .await
+        .is_err());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, Address, AllowAll, Mailboxes, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpTransport};
+
+/// Connect to a new listener, both using `compression`, and return the
+/// address of the connection
+async fn connect(transport: &TcpTransport, compression: bool) -> Result<Address> {
+    let (mut listener_options, mut connection_options) = (
+        TcpListenerTrustOptions::new(),
+        TcpConnectionTrustOptions::new(),
+    );
+    if compression {
+        listener_options = listener_options.with_compression();
+        connection_options = connection_options.with_compression();
+    }
+
+    let (listener_address, _) = transport.listen("127.0.0.1:0", listener_options).await?;
+    transport
+        .connect(listener_address.to_string(), connection_options)
+        .await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn compression__large_payload__is_smaller_on_the_wire(ctx: &mut Context) -> Result<()> {
+    let mut collector = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "collector",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let raw = connect(&transport, false).await?;
+    let compressed = connect(&transport, true).await?;
+
+    let payload = r#"{"host":"db-1","metric":"cpu","value":0.42},"#.repeat(1000);
+    for connection in [&raw, &compressed] {
+        ctx.send(route![connection.clone(), "collector"], payload.clone())
+            .await?;
+        let received = collector.receive::<String>().await?.take().body();
+        assert_eq!(received, payload);
+    }
+
+    let stats = |connection: &Address| {
+        transport
+            .registry()
+            .connection_stats(connection)
+            .unwrap()
+            .bytes_out
+    };
+    assert!(stats(&raw) > payload.len() as u64);
+    assert!(stats(&compressed) < stats(&raw) / 10);
+
+    // Short messages, which don't shrink, still go through
+    ctx.send(route![compressed, "collector"], "hello".to_string())
+        .await?;
+    assert_eq!(collector.receive::<String>().await?.take().body(), "hello");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
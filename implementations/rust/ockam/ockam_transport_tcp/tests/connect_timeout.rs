@@ -0,0 +1,56 @@
+use core::time::Duration;
+use ockam_core::errcode::Kind;
+use ockam_core::Result;
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpTransport};
+use socket2::{Domain, Socket, Type};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Instant;
+
+/// Start a listener which never accepts its connections, and fill its backlog so
+/// that the SYNs of new connections are dropped. Return the listener with its
+/// address and the connections filling the backlog.
+fn syn_dropping_listener() -> (Socket, SocketAddr, Vec<TcpStream>) {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    listener.bind(&addr.into()).unwrap();
+    listener.listen(0).unwrap();
+    let addr = listener.local_addr().unwrap().as_socket().unwrap();
+
+    let mut backlog = Vec::new();
+    while let Ok(stream) = TcpStream::connect_timeout(&addr, Duration::from_millis(200)) {
+        backlog.push(stream);
+        assert!(backlog.len() < 64, "the listener backlog should be full");
+    }
+    (listener, addr, backlog)
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn connect_timeout__peer_dropping_syns__fails_within_the_timeout(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (_listener, addr, _backlog) = syn_dropping_listener();
+    let transport = TcpTransport::create(ctx).await?;
+
+    let connect_timeout = Duration::from_millis(300);
+    let started = Instant::now();
+    let err = transport
+        .connect(
+            addr.to_string(),
+            TcpConnectionTrustOptions::new().with_connect_timeout(connect_timeout),
+        )
+        .await
+        .unwrap_err();
+    let elapsed = started.elapsed();
+
+    assert_eq!(err.code().kind, Kind::Timeout);
+    assert!(elapsed >= connect_timeout);
+    assert!(elapsed < connect_timeout + Duration::from_secs(1));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
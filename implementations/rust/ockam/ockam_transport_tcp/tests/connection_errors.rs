@@ -0,0 +1,44 @@
+use ockam_core::Result;
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpTransport};
+use std::net::TcpListener;
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn connection_errors__connect_failures__are_kept_up_to_the_limit(
+    ctx: &mut Context,
+) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    transport.registry().set_max_connection_errors(3);
+
+    // Find a port nobody listens on
+    let peer = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    for _ in 0..5 {
+        let res = transport
+            .connect(peer.to_string(), TcpConnectionTrustOptions::new())
+            .await;
+        assert!(res.is_err());
+    }
+    assert!(transport
+        .connect("not an address", TcpConnectionTrustOptions::new())
+        .await
+        .is_err());
+
+    let errors = transport.registry().get_connection_errors();
+    assert_eq!(errors.len(), 3);
+    assert!(errors[..2].iter().all(|e| e.peer() == peer.to_string()));
+    assert_eq!(errors[2].peer(), "not an address");
+    assert!(errors
+        .iter()
+        .all(|e| !e.reason().is_empty() && e.time() > 0));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{Address, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TcpCidrFilter, TcpConnectionListener, TcpConnectionTrustOptions, TcpListenerTrustOptions,
+    TcpTransport,
+};
+use std::net::SocketAddr;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+
+/// Counts the receiver processors started for the connections of a transport
+#[derive(Default)]
+struct ConnectCounter(AtomicUsize);
+
+impl TcpConnectionListener for ConnectCounter {
+    fn on_connect(&self, _address: &Address, _peer: SocketAddr) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_disconnect(&self, _address: &Address, _peer: SocketAddr) {}
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn connection_filter__denied_mapped_loopback__no_worker_is_spawned(
+    ctx: &mut Context,
+) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let counter = Arc::new(ConnectCounter::default());
+    transport
+        .registry()
+        .add_connection_listener(counter.clone());
+
+    // A dual-stack listener sees IPv4 peers as IPv4 addresses mapped to IPv6
+    let filter = TcpCidrFilter::new().with_denied("127.0.0.0/8")?;
+    let (listener_address, _) = transport
+        .listen(
+            "[::]:0",
+            TcpListenerTrustOptions::new().with_connection_filter(filter),
+        )
+        .await?;
+
+    let target = SocketAddr::from(([127, 0, 0, 1], listener_address.port()));
+    let mut client = TcpStream::connect(target).await.unwrap();
+
+    // The listener closes the connection right away
+    let mut buf = [0u8; 1];
+    let read = tokio::time::timeout(core::time::Duration::from_secs(5), client.read(&mut buf))
+        .await
+        .expect("the connection should be closed");
+    assert!(matches!(read, Ok(0) | Err(_)));
+
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+    assert!(transport
+        .registry()
+        .get_connection_errors()
+        .iter()
+        .any(|e| e.reason().contains("refused by filter")));
+
+    // Peers which aren't denied are accepted
+    let allowed = TcpCidrFilter::new().with_allowed("127.0.0.1/32")?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_connection_filter(allowed),
+        )
+        .await?;
+    transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use core::time::Duration;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::{Address, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TcpConnectionListener, TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpTransport,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ConnectionEvent {
+    Connected(Address, SocketAddr),
+    Disconnected(Address, SocketAddr),
+}
+
+/// Listener recording the connection events it is notified of
+#[derive(Default)]
+struct RecordingListener {
+    events: Mutex<Vec<ConnectionEvent>>,
+}
+
+impl RecordingListener {
+    fn events(&self) -> Vec<ConnectionEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Wait until `expected` events were recorded, or give up after a few seconds
+    async fn wait_for_events(&self, expected: usize) -> Vec<ConnectionEvent> {
+        for _ in 0..100 {
+            if self.events().len() >= expected {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        self.events()
+    }
+}
+
+impl TcpConnectionListener for RecordingListener {
+    fn on_connect(&self, address: &Address, peer: SocketAddr) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(ConnectionEvent::Connected(address.clone(), peer));
+    }
+
+    fn on_disconnect(&self, address: &Address, peer: SocketAddr) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(ConnectionEvent::Disconnected(address.clone(), peer));
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn connection_listener__open_and_close__are_notified(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let first = Arc::new(RecordingListener::default());
+    let second = Arc::new(RecordingListener::default());
+    transport.registry().add_connection_listener(first.clone());
+    transport.registry().add_connection_listener(second.clone());
+
+    let (listener_address, _) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+    let connection = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+
+    // Both ends of the connection belong to the same transport
+    let opened = first.wait_for_events(2).await;
+    assert_eq!(opened.len(), 2);
+    assert!(opened
+        .iter()
+        .all(|e| matches!(e, ConnectionEvent::Connected(..))));
+    assert!(opened
+        .iter()
+        .any(|e| matches!(e, ConnectionEvent::Connected(_, peer) if *peer == listener_address)));
+
+    transport.disconnect(&connection).await?;
+    let events = first.wait_for_events(4).await;
+    assert_eq!(events.len(), 4);
+    for event in &opened {
+        if let ConnectionEvent::Connected(address, peer) = event {
+            assert!(events[2..].contains(&ConnectionEvent::Disconnected(address.clone(), *peer)));
+        }
+    }
+
+    // Every listener is notified of the same events
+    assert_eq!(second.wait_for_events(4).await, events);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
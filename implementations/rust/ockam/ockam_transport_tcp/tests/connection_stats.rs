@@ -0,0 +1,79 @@
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, Address, AllowAll, Mailboxes, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpTransport};
+
+/// Wait until the listener at `listener` accepted a connection, and return its sender worker
+async fn wait_for_accepted_connection(transport: &TcpTransport, listener: &Address) -> Address {
+    for _ in 0..100 {
+        if let Some(sender) = transport
+            .registry()
+            .get_listener_connections(listener)
+            .pop()
+        {
+            return sender;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("the connection should be accepted")
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn connection_stats__sent_messages__are_counted_on_both_ends(
+    ctx: &mut Context,
+) -> Result<()> {
+    const MESSAGES: u64 = 5;
+
+    let mut collector = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "collector",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (socket_address, listener) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+    let connection = transport
+        .connect(socket_address.to_string(), TcpConnectionTrustOptions::new())
+        .await?;
+    let accepted = wait_for_accepted_connection(&transport, &listener).await;
+
+    for i in 0..MESSAGES {
+        ctx.send(route![connection.clone(), "collector"], i.to_string())
+            .await?;
+        collector.receive::<String>().await?;
+    }
+
+    let sent = transport.registry().connection_stats(&connection).unwrap();
+    assert_eq!(sent.messages_out, MESSAGES);
+    assert_eq!(sent.messages_in, 0);
+    assert!(sent.bytes_out > 0);
+
+    let received = transport.registry().connection_stats(&accepted).unwrap();
+    assert_eq!(received.messages_in, MESSAGES);
+    assert_eq!(received.messages_out, 0);
+    assert_eq!(received.bytes_in, sent.bytes_out);
+
+    // The stats are gone with the connection
+    transport.disconnect(&connection).await?;
+    let mut gone = false;
+    for _ in 0..100 {
+        if transport.registry().connection_stats(&connection).is_none() {
+            gone = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(gone);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
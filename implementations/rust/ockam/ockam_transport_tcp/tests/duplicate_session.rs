@@ -0,0 +1,112 @@
+use core::time::Duration;
+use ockam_core::sessions::Sessions;
+use ockam_core::{Address, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TcpConnectionTrustOptions, TcpDuplicateSessionPolicy, TcpListenerTrustOptions, TcpTransport,
+};
+
+/// Open two connections with the same session, the second one with `policy`,
+/// and return the transport along with the addresses of both connections
+async fn connect_twice(
+    ctx: &Context,
+    policy: TcpDuplicateSessionPolicy,
+) -> Result<(TcpTransport, Address, Address)> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+
+    let sessions = Sessions::default();
+    let session_id = sessions.generate_session_id();
+    let first = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new().with_session(&sessions, &session_id),
+        )
+        .await?;
+    let second = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new()
+                .with_session(&sessions, &session_id)
+                .with_duplicate_session_policy(policy),
+        )
+        .await?;
+    assert!(transport
+        .registry()
+        .get_session_connection(&session_id)
+        .is_some());
+
+    Ok((transport, first, second))
+}
+
+/// Wait until the connection at `address` is closed, or give up after a few seconds
+async fn wait_for_disconnection(transport: &TcpTransport, address: &Address) -> bool {
+    for _ in 0..100 {
+        if !transport
+            .registry()
+            .get_all_sender_workers()
+            .contains(address)
+        {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    false
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn duplicate_session__allow__keeps_both_connections(ctx: &mut Context) -> Result<()> {
+    let (transport, first, second) = connect_twice(ctx, TcpDuplicateSessionPolicy::Allow).await?;
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let senders = transport.registry().get_all_sender_workers();
+    assert!(senders.contains(&first));
+    assert!(senders.contains(&second));
+    assert_eq!(transport.registry().get_duplicate_sessions(), 0);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn duplicate_session__flag__counts_the_second_connection(ctx: &mut Context) -> Result<()> {
+    let (transport, first, second) = connect_twice(ctx, TcpDuplicateSessionPolicy::Flag).await?;
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let senders = transport.registry().get_all_sender_workers();
+    assert!(senders.contains(&first));
+    assert!(senders.contains(&second));
+    assert_eq!(transport.registry().get_duplicate_sessions(), 1);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn duplicate_session__reject__closes_the_second_connection(ctx: &mut Context) -> Result<()> {
+    let (transport, first, second) = connect_twice(ctx, TcpDuplicateSessionPolicy::Reject).await?;
+
+    assert!(wait_for_disconnection(&transport, &second).await);
+    assert!(transport
+        .registry()
+        .get_all_sender_workers()
+        .contains(&first));
+    assert_eq!(transport.registry().get_duplicate_sessions(), 1);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, AllowAll, Mailboxes, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TcpConnectionTrustOptions, TcpEvent, TcpListenerTrustOptions, TcpTransport,
+};
+use tokio::sync::broadcast;
+
+/// Wait for the next event matching `predicate`, or give up after a few seconds
+async fn wait_for_event(
+    events: &mut broadcast::Receiver<TcpEvent>,
+    predicate: impl Fn(&TcpEvent) -> bool,
+) -> TcpEvent {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match events.recv().await {
+                Ok(event) if predicate(&event) => return event,
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => panic!("the events stream is closed"),
+            }
+        }
+    })
+    .await
+    .expect("the event should be received")
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn events__connect_send_disconnect__are_streamed(ctx: &mut Context) -> Result<()> {
+    let mut collector = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "collector",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let mut events = transport.registry().subscribe_events();
+    let (listener_address, _) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+
+    let connection = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+    wait_for_event(
+        &mut events,
+        |e| matches!(e, TcpEvent::ConnectionOpened { peer } if *peer == listener_address),
+    )
+    .await;
+
+    ctx.send(route![connection.clone(), "collector"], "hello".to_string())
+        .await?;
+    collector.receive::<String>().await?;
+    wait_for_event(
+        &mut events,
+        |e| matches!(e, TcpEvent::FrameReceived { size, .. } if *size > 0),
+    )
+    .await;
+
+    transport.disconnect(&connection).await?;
+    wait_for_event(
+        &mut events,
+        |e| matches!(e, TcpEvent::ConnectionClosed { peer } if *peer == listener_address),
+    )
+    .await;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn events__subscribe_after_the_last_subscriber_left__are_streamed(
+    ctx: &mut Context,
+) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+
+    // Events are not emitted anymore once nobody listens to them
+    drop(transport.registry().subscribe_events());
+    let connection = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+    transport.disconnect(&connection).await?;
+
+    // Until somebody subscribes again
+    let mut events = transport.registry().subscribe_events();
+    transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+    wait_for_event(
+        &mut events,
+        |e| matches!(e, TcpEvent::ConnectionOpened { peer } if *peer == listener_address),
+    )
+    .await;
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, AllowAll, Mailboxes, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpTransport};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Relay a single connection to `target`, flipping a bit of the first frame
+async fn start_corrupting_proxy(target: SocketAddr) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy_address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut incoming, _) = listener.accept().await.unwrap();
+        let mut outgoing = TcpStream::connect(target).await.unwrap();
+
+        let len = incoming.read_u16().await.unwrap();
+        let mut frame = vec![0; len as usize];
+        incoming.read_exact(&mut frame).await.unwrap();
+        frame[len as usize / 2] ^= 0x01;
+        outgoing.write_u16(len).await.unwrap();
+        outgoing.write_all(&frame).await.unwrap();
+
+        let _ = tokio::io::copy_bidirectional(&mut incoming, &mut outgoing).await;
+    });
+
+    proxy_address
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn frame_checksum__bit_flipped_frame__is_rejected(ctx: &mut Context) -> Result<()> {
+    let mut collector = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "collector",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_frame_checksum(),
+        )
+        .await?;
+    let proxy_address = start_corrupting_proxy(listener_address).await;
+
+    let connection = transport
+        .connect(
+            proxy_address.to_string(),
+            TcpConnectionTrustOptions::new().with_frame_checksum(),
+        )
+        .await?;
+
+    ctx.send(
+        route![connection.clone(), "collector"],
+        "corrupted".to_string(),
+    )
+    .await?;
+    ctx.send(route![connection, "collector"], "intact".to_string())
+        .await?;
+
+    // Only the frame left intact by the proxy is delivered
+    let msg = collector.receive::<String>().await?.take().body();
+    assert_eq!(msg, "intact");
+    assert_eq!(transport.registry().get_corrupt_frames(), 1);
+    assert!(collector
+        .receive_duration_timeout::<String>(Duration::from_millis(200))
+        .await
+        .is_err());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use core::time::Duration;
+use ockam_core::{route, Decodable, Encodable, Result, TransportMessage};
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpTransport};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{timeout, Instant};
+
+fn heartbeat_frame() -> Vec<u8> {
+    let msg = TransportMessage::v1(route![], route![], vec![])
+        .encode()
+        .unwrap();
+    let mut frame = (msg.len() as u16).to_be_bytes().to_vec();
+    frame.extend(msg);
+    frame
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn heartbeat__reply_enabled__should_send_reply_frame(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_heartbeat_reply(),
+        )
+        .await?;
+
+    let mut stream = TcpStream::connect(listener_address).await.unwrap();
+    stream.write_all(&heartbeat_frame()).await.unwrap();
+
+    let len = timeout(Duration::from_secs(5), stream.read_u16())
+        .await
+        .expect("no heartbeat reply received")
+        .unwrap();
+    let mut buf = vec![0; len as usize];
+    stream.read_exact(&mut buf).await.unwrap();
+
+    let reply = TransportMessage::decode(&buf)?;
+    assert!(reply.onward_route.next().is_err(), "Should be a heartbeat");
+    assert!(!reply.payload.is_empty(), "Should be marked as a reply");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn heartbeat__reply_disabled__should_stay_silent(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+
+    let mut stream = TcpStream::connect(listener_address).await.unwrap();
+    stream.write_all(&heartbeat_frame()).await.unwrap();
+
+    let res = timeout(Duration::from_millis(500), stream.read_u16()).await;
+    assert!(res.is_err(), "Should not receive a heartbeat reply");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// Read the next frame from `stream` and return the message it carries
+async fn read_message(stream: &mut TcpStream) -> TransportMessage {
+    let len = stream.read_u16().await.unwrap();
+    let mut buf = vec![0; len as usize];
+    stream.read_exact(&mut buf).await.unwrap();
+    TransportMessage::decode(&buf).unwrap()
+}
+
+// The node runtime is multi-threaded, so its clock can't be paused:
+// heartbeats are checked with a short interval instead
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn heartbeat__interval_set__should_send_heartbeats_at_that_interval(
+    ctx: &mut Context,
+) -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let interval = Duration::from_millis(300);
+    let transport = TcpTransport::create(ctx).await?;
+    transport
+        .connect(
+            listener.local_addr().unwrap().to_string(),
+            TcpConnectionTrustOptions::new().with_heartbeat_interval(interval),
+        )
+        .await?;
+    let (mut stream, _) = listener.accept().await.unwrap();
+
+    let mut last = Instant::now();
+    for _ in 0..2 {
+        let msg = timeout(Duration::from_secs(5), read_message(&mut stream))
+            .await
+            .expect("no heartbeat received");
+        assert!(msg.onward_route.next().is_err(), "Should be a heartbeat");
+        assert!(msg.payload.is_empty(), "Should not be a heartbeat reply");
+        assert!(last.elapsed() >= interval - Duration::from_millis(50));
+        last = Instant::now();
+    }
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn heartbeat__interval_not_set__should_stay_silent(ctx: &mut Context) -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let transport = TcpTransport::create(ctx).await?;
+    transport
+        .connect(
+            listener.local_addr().unwrap().to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+    let (mut stream, _) = listener.accept().await.unwrap();
+
+    let res = timeout(Duration::from_millis(1000), stream.read_u16()).await;
+    assert!(res.is_err(), "Should not receive a heartbeat");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, Address, AllowAll, Mailboxes, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TcpCidrFilter, TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpTransport,
+};
+
+/// Wait until the listener at `listener` has `expected` open connections, or give up
+/// after a few seconds
+async fn wait_for_listener_connections(
+    transport: &TcpTransport,
+    listener: &Address,
+    expected: usize,
+) -> bool {
+    for _ in 0..100 {
+        if transport
+            .registry()
+            .get_listener_connections(listener)
+            .len()
+            == expected
+        {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    false
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn listener_migration__existing_connections__are_drained(ctx: &mut Context) -> Result<()> {
+    let mut collector = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "collector",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (old_socket_address, old_listener) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+    let old_connection = transport
+        .connect(
+            old_socket_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+    assert!(wait_for_listener_connections(&transport, &old_listener, 1).await);
+
+    let (new_socket_address, new_listener) = transport
+        .migrate_listener(&old_listener, "127.0.0.1:0")
+        .await?;
+    assert_ne!(new_socket_address, old_socket_address);
+
+    // The existing connection survives the migration
+    ctx.send(
+        route![old_connection.clone(), "collector"],
+        "old".to_string(),
+    )
+    .await?;
+    assert_eq!(collector.receive::<String>().await?.take().body(), "old");
+    assert_eq!(
+        transport
+            .registry()
+            .get_listener_connections(&old_listener)
+            .len(),
+        1
+    );
+
+    // New connections go to the new listener
+    let new_connection = transport
+        .connect(
+            new_socket_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+    ctx.send(route![new_connection, "collector"], "new".to_string())
+        .await?;
+    assert_eq!(collector.receive::<String>().await?.take().body(), "new");
+    assert!(wait_for_listener_connections(&transport, &new_listener, 1).await);
+    assert!(transport
+        .connect(
+            old_socket_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await
+        .is_err());
+
+    // The old listener is drained once its last connection is closed
+    transport.disconnect(&old_connection).await?;
+    assert!(wait_for_listener_connections(&transport, &old_listener, 0).await);
+    assert_eq!(
+        transport
+            .registry()
+            .get_listener_connections(&new_listener)
+            .len(),
+        1
+    );
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn listener_migration__new_listener__keeps_the_trust_options(
+    ctx: &mut Context,
+) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let filter = TcpCidrFilter::new().with_denied("127.0.0.0/8")?;
+    let (_, old_listener) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_connection_filter(filter),
+        )
+        .await?;
+
+    let (new_socket_address, _) = transport
+        .migrate_listener(&old_listener, "127.0.0.1:0")
+        .await?;
+
+    // The new listener refuses the peers the old one refused
+    let _stream = tokio::net::TcpStream::connect(new_socket_address)
+        .await
+        .unwrap();
+    let mut refused = false;
+    for _ in 0..100 {
+        refused = transport
+            .registry()
+            .get_connection_errors()
+            .iter()
+            .any(|e| e.reason() == "connection refused by filter");
+        if refused {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(refused);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
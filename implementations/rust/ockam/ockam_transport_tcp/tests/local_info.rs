@@ -0,0 +1,259 @@
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::sessions::{SessionIdLocalInfo, Sessions};
+use ockam_core::{
+    route, AllowAll, Decodable, Encodable, LocalInfo, LocalMessage, Result, Routed,
+    TransportMessage, Worker,
+};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    PeerAddressLocalInfo, TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpLocalInfoProducer,
+    TcpTransport,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const TENANT_IDENTIFIER: &str = "TENANT_IDENTIFIER";
+const TRACE_IDENTIFIER: &str = "TRACE_IDENTIFIER";
+
+struct TenantProducer;
+
+impl TcpLocalInfoProducer for TenantProducer {
+    fn produce(&self, _peer: &SocketAddr, _msg: &TransportMessage) -> Result<Option<LocalInfo>> {
+        Ok(Some(LocalInfo::new(
+            TENANT_IDENTIFIER.into(),
+            b"tenant-1".to_vec(),
+        )))
+    }
+}
+
+/// Replies with the tenant found in the message LocalInfo and whether a session id is present
+struct Checker;
+
+#[ockam_core::worker]
+impl Worker for Checker {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        let local_info = msg.local_message().local_info();
+        let tenant = local_info
+            .iter()
+            .find(|x| x.type_identifier() == TENANT_IDENTIFIER)
+            .map(|x| String::from_utf8(x.data().to_vec()).unwrap())
+            .unwrap_or_default();
+        let has_session = SessionIdLocalInfo::find_info_from_list(local_info).is_ok();
+
+        ctx.send(msg.return_route(), format!("{tenant}/{has_session}"))
+            .await
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn local_info__custom_producer__attached_next_to_session_id(ctx: &mut Context) -> Result<()> {
+    let sessions = Sessions::default();
+    let listener_session_id = sessions.generate_session_id();
+    sessions.set_listener_session_id(&"checker".into(), &listener_session_id);
+    ctx.start_worker("checker", Checker, AllowAll, AllowAll)
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new()
+                .with_session(&sessions, &listener_session_id)
+                .with_local_info_producer(TenantProducer),
+        )
+        .await?;
+
+    let tx_address = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+
+    let reply: String = ctx
+        .send_and_receive(route![tx_address, "checker"], "Hello".to_string())
+        .await?;
+    assert_eq!(reply, "tenant-1/true");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// Replies with the type and data of every LocalInfo of the message
+struct LocalInfoReporter;
+
+#[ockam_core::worker]
+impl Worker for LocalInfoReporter {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        let local_info: Vec<String> = msg
+            .local_message()
+            .local_info()
+            .iter()
+            .map(|x| {
+                format!(
+                    "{}={}",
+                    x.type_identifier(),
+                    String::from_utf8_lossy(x.data())
+                )
+            })
+            .collect();
+
+        ctx.send(msg.return_route(), local_info.join(",")).await
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn local_info__passthrough__only_allowed_types_cross_the_connection(
+    ctx: &mut Context,
+) -> Result<()> {
+    ctx.start_worker("reporter", LocalInfoReporter, AllowAll, AllowAll)
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_local_info_passthrough(TRACE_IDENTIFIER),
+        )
+        .await?;
+
+    let tx_address = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new().with_local_info_passthrough(TRACE_IDENTIFIER),
+        )
+        .await?;
+
+    let msg = TransportMessage::v1(
+        route![tx_address, "reporter"],
+        route![ctx.address()],
+        "Hello".to_string().encode()?,
+    );
+    let local_info = vec![
+        LocalInfo::new(TENANT_IDENTIFIER.into(), b"tenant-1".to_vec()),
+        LocalInfo::new(TRACE_IDENTIFIER.into(), b"trace-1".to_vec()),
+    ];
+    ctx.forward(LocalMessage::new(msg, local_info)).await?;
+
+    let reply = ctx.receive::<String>().await?.take().body();
+    assert_eq!(reply, format!("{TRACE_IDENTIFIER}=trace-1"));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn local_info__passthrough__oversized_frame_is_not_sent(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("reporter", LocalInfoReporter, AllowAll, AllowAll)
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_local_info_passthrough(TRACE_IDENTIFIER),
+        )
+        .await?;
+
+    let tx_address = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new().with_local_info_passthrough(TRACE_IDENTIFIER),
+        )
+        .await?;
+
+    // The local info and the payload fit in a frame on their own, but not together
+    let send = |payload: String, trace: Vec<u8>| {
+        let msg = TransportMessage::v1(
+            route![tx_address.clone(), "reporter"],
+            route![ctx.address()],
+            payload.encode().unwrap(),
+        );
+        LocalMessage::new(msg, vec![LocalInfo::new(TRACE_IDENTIFIER.into(), trace)])
+    };
+    ctx.forward(send("a".repeat(40_000), vec![b'x'; 40_000]))
+        .await?;
+    ctx.forward(send("Hello".to_string(), b"trace-1".to_vec()))
+        .await?;
+
+    // The oversized frame is dropped instead of corrupting the connection
+    let reply = ctx.receive::<String>().await?.take().body();
+    assert_eq!(reply, format!("{TRACE_IDENTIFIER}=trace-1"));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// Replies with the peer address found in the message LocalInfo
+struct PeerAddressReporter;
+
+#[ockam_core::worker]
+impl Worker for PeerAddressReporter {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        let peer = PeerAddressLocalInfo::find_info(msg.local_message())
+            .map(|info| info.peer().to_string())
+            .unwrap_or_default();
+
+        ctx.send(msg.return_route(), peer).await
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn local_info__peer_address__arrives_at_the_terminal_worker(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("peer_reporter", PeerAddressReporter, AllowAll, AllowAll)
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_peer_address_local_info(),
+        )
+        .await?;
+
+    // A raw client, whose address is known
+    let mut client = TcpStream::connect(listener_address).await.unwrap();
+    let msg = TransportMessage::v1(
+        route!["peer_reporter"],
+        route![],
+        "Hello".to_string().encode()?,
+    );
+    let frame = msg.encode()?;
+    client.write_u16(frame.len() as u16).await.unwrap();
+    client.write_all(&frame).await.unwrap();
+
+    let len = client.read_u16().await.unwrap();
+    let mut frame = vec![0; len as usize];
+    client.read_exact(&mut frame).await.unwrap();
+    let reply = TransportMessage::decode(&frame)?;
+    let peer = String::decode(&reply.payload)?;
+    assert_eq!(peer, client.local_addr().unwrap().to_string());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
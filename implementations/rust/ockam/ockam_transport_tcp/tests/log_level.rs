@@ -0,0 +1,106 @@
+use std::sync::Mutex;
+
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, Layer};
+use tracing_subscriber::prelude::*;
+
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, Address, AllowAll, Mailboxes, Result, Routed, Worker};
+use ockam_node::{logging, Context, NodeBuilder, WorkerBuilder};
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpTransport};
+
+const RECEIVER_TARGET: &str = "ockam_transport_tcp::workers::receiver";
+
+/// Layer counting the events emitted by the TCP receiver
+#[derive(Clone, Default)]
+struct ReceiverEvents(Arc<Mutex<usize>>);
+
+impl ReceiverEvents {
+    fn count(&self) -> usize {
+        *self.0.lock().unwrap()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for ReceiverEvents {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        if event.metadata().target() == RECEIVER_TARGET {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+}
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+async fn echo(ctx: &Context, connection: &Address) -> Result<()> {
+    let reply: String = ctx
+        .send_and_receive(route![connection.clone(), "echoer"], "hello".to_string())
+        .await?;
+    assert_eq!(reply, "hello");
+    Ok(())
+}
+
+async fn toggle_receiver_log_level(ctx: &Context, events: ReceiverEvents) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+    WorkerBuilder::with_mailboxes(
+        Mailboxes::main("echoer", Arc::new(AllowAll), Arc::new(AllowAll)),
+        Echoer,
+    )
+    .start(ctx)
+    .await?;
+    let connection = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+
+    // Trace events are filtered out by default
+    echo(ctx, &connection).await?;
+    assert_eq!(events.count(), 0);
+
+    // Raising the level of the receiver takes effect immediately
+    logging::set_log_level(RECEIVER_TARGET, Some(LevelFilter::TRACE))?;
+    echo(ctx, &connection).await?;
+    let traced = events.count();
+    assert!(traced > 0);
+
+    // And so does removing it
+    logging::set_log_level(RECEIVER_TARGET, None)?;
+    echo(ctx, &connection).await?;
+    assert_eq!(events.count(), traced);
+
+    Ok(())
+}
+
+#[test]
+fn receiver_log_level_can_be_changed_at_runtime() {
+    let events = ReceiverEvents::default();
+    tracing_subscriber::registry()
+        .with(logging::reloadable_filter("info"))
+        .with(events.clone())
+        .init();
+
+    let (ctx, mut executor) = NodeBuilder::new().no_logging().build();
+    let res = executor
+        .execute(async move {
+            let res = toggle_receiver_log_level(&ctx, events).await;
+            ctx.stop().await?;
+            res
+        })
+        .unwrap();
+    res.unwrap();
+}
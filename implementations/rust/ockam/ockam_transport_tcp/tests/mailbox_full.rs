@@ -0,0 +1,156 @@
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, AllowAll, Encodable, Mailboxes, Result, TransportMessage};
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpListenerTrustOptions, TcpMailboxFullPolicy, TcpTransport};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Number of messages sent to the downstream worker, more than its mailbox can hold
+const MESSAGES: usize = 40;
+
+fn frame(body: usize) -> Vec<u8> {
+    let msg = TransportMessage::v1(
+        route!["downstream"],
+        route![],
+        body.to_string().encode().unwrap(),
+    )
+    .encode()
+    .unwrap();
+    let mut frame = (msg.len() as u16).to_be_bytes().to_vec();
+    frame.extend(msg);
+    frame
+}
+
+/// Create a "downstream" detached context which never reads its mailbox unless
+/// asked to, and a listener forwarding messages to it with `policy`
+async fn saturated_downstream(
+    ctx: &Context,
+    policy: TcpMailboxFullPolicy,
+) -> Result<(Context, TcpTransport, SocketAddr)> {
+    let downstream = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "downstream",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_mailbox_full_policy(policy),
+        )
+        .await?;
+
+    Ok((downstream, transport, listener_address))
+}
+
+async fn send_messages(addr: SocketAddr) -> TcpStream {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    for i in 0..MESSAGES {
+        stream.write_all(&frame(i)).await.unwrap();
+    }
+    stream
+}
+
+/// Wait until `transport` dropped `expected` messages, or give up after a few seconds
+async fn wait_for_dropped_messages(transport: &TcpTransport, expected: u64) -> u64 {
+    for _ in 0..100 {
+        if transport.registry().get_dropped_messages() >= expected {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    transport.registry().get_dropped_messages()
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn mailbox_full__block__delivers_all_messages(ctx: &mut Context) -> Result<()> {
+    let (mut downstream, transport, addr) =
+        saturated_downstream(ctx, TcpMailboxFullPolicy::Block).await?;
+    let _stream = send_messages(addr).await;
+
+    // Let the receiver fill the mailbox and block before the messages are read
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    for i in 0..MESSAGES {
+        let body = downstream.receive::<String>().await?.take().body();
+        assert_eq!(body, i.to_string());
+    }
+    assert_eq!(transport.registry().get_dropped_messages(), 0);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn mailbox_full__block_with_timeout__drops_messages_after_the_timeout(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (_downstream, transport, addr) = saturated_downstream(
+        ctx,
+        TcpMailboxFullPolicy::BlockWithTimeout(Duration::from_millis(10)),
+    )
+    .await?;
+    let _stream = send_messages(addr).await;
+
+    let dropped = wait_for_dropped_messages(&transport, 1).await;
+    assert!(dropped > 0);
+    assert!(dropped < MESSAGES as u64);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn mailbox_full__drop__counts_dropped_messages(ctx: &mut Context) -> Result<()> {
+    let (mut downstream, transport, addr) =
+        saturated_downstream(ctx, TcpMailboxFullPolicy::Drop).await?;
+    let _stream = send_messages(addr).await;
+
+    let dropped = wait_for_dropped_messages(&transport, 1).await;
+    assert!(dropped > 0);
+
+    // The messages which fit in the mailbox are delivered
+    let first = downstream.receive::<String>().await?.take().body();
+    assert_eq!(first, "0");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn mailbox_full__close__closes_the_connection(ctx: &mut Context) -> Result<()> {
+    let (_downstream, transport, addr) =
+        saturated_downstream(ctx, TcpMailboxFullPolicy::Close).await?;
+    let mut stream = send_messages(addr).await;
+
+    // The peer sees the connection closed
+    let mut buf = [0u8; 64];
+    let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf))
+        .await
+        .expect("the connection should be closed");
+    assert!(matches!(read, Ok(0) | Err(_)));
+    assert_eq!(wait_for_dropped_messages(&transport, 1).await, 1);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
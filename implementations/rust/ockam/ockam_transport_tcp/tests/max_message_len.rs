@@ -0,0 +1,63 @@
+use core::time::Duration;
+use ockam_core::Result;
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpListenerTrustOptions, TcpTransport};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn max_message_len__longer_header__drops_the_connection(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_max_message_len(16),
+        )
+        .await?;
+
+    let mut stream = TcpStream::connect(listener_address).await.unwrap();
+    stream.write_u16(1024).await.unwrap();
+
+    // The connection is closed without waiting for the announced body
+    let mut buf = [0u8; 1];
+    let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf))
+        .await
+        .expect("the connection should be dropped");
+    assert!(matches!(read, Ok(0) | Err(_)));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn max_message_len__shorter_header__keeps_the_connection(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_max_message_len(16),
+        )
+        .await?;
+
+    let mut stream = TcpStream::connect(listener_address).await.unwrap();
+    stream.write_u16(8).await.unwrap();
+
+    // The receiver waits for the body of the frame
+    let mut buf = [0u8; 1];
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), stream.read(&mut buf))
+            .await
+            .is_err()
+    );
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, AllowAll, Encodable, Mailboxes, Result, Routed, TransportMessage, Worker};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpOrdering, TcpTransport,
+};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Frame in the strict ordering format: length, sequence number, message
+fn ordered_frame(sequence_number: u64, body: &str) -> Vec<u8> {
+    let body = body.to_string().encode().unwrap();
+    let msg = TransportMessage::v1(route!["collector"], route![], body)
+        .encode()
+        .unwrap();
+    let mut frame = ((msg.len() + 8) as u16).to_be_bytes().to_vec();
+    frame.extend(sequence_number.to_be_bytes());
+    frame.extend(msg);
+    frame
+}
+
+/// Open a connection belonging to the ordered stream `stream_id`, and send `frame` over it
+async fn send_on_new_connection(addr: SocketAddr, stream_id: u64, frame: Vec<u8>) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(&stream_id.to_be_bytes()).await.unwrap();
+    // No frame was sent on the stream before this test
+    stream.write_u8(0).await.unwrap();
+    // Whether the listener knew the stream
+    stream.read_u8().await.unwrap();
+    stream.write_all(&frame).await.unwrap();
+    stream.shutdown().await.unwrap();
+}
+
+async fn start_collector(ctx: &Context) -> Result<Context> {
+    ctx.new_detached_with_mailboxes(Mailboxes::main(
+        "collector",
+        Arc::new(AllowAll),
+        Arc::new(AllowAll),
+    ))
+    .await
+}
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn ordering__strict__frames_reordered_across_reconnect(ctx: &mut Context) -> Result<()> {
+    let mut collector = start_collector(ctx).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_ordering(TcpOrdering::strict(16)),
+        )
+        .await?;
+
+    // The second frame arrives first, on a new connection, while the first one
+    // was still in flight on the previous connection
+    send_on_new_connection(listener_address, 7, ordered_frame(2, "second")).await;
+    send_on_new_connection(listener_address, 7, ordered_frame(1, "first")).await;
+
+    let first = collector.receive::<String>().await?.take().body();
+    let second = collector.receive::<String>().await?.take().body();
+    assert_eq!(first, "first");
+    assert_eq!(second, "second");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn ordering__strict__peers_have_independent_sequences(ctx: &mut Context) -> Result<()> {
+    let mut collector = start_collector(ctx).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_ordering(TcpOrdering::strict(16)),
+        )
+        .await?;
+
+    // Both peers start their sequence at 1
+    send_on_new_connection(listener_address, 1, ordered_frame(1, "first peer")).await;
+    let first = collector.receive::<String>().await?.take().body();
+    assert_eq!(first, "first peer");
+
+    send_on_new_connection(listener_address, 2, ordered_frame(1, "second peer")).await;
+    let second = collector.receive::<String>().await?.take().body();
+    assert_eq!(second, "second peer");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn ordering__strict__two_clients_on_one_listener(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer, AllowAll, AllowAll)
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_ordering(TcpOrdering::strict(16)),
+        )
+        .await?;
+
+    let client_a = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new().with_ordering(TcpOrdering::strict(16)),
+        )
+        .await?;
+    let client_b = transport
+        .connect(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new().with_ordering(TcpOrdering::strict(16)),
+        )
+        .await?;
+
+    // The replies to each client are numbered independently of the other client
+    for i in 0..3 {
+        for (client, name) in [(&client_a, "a"), (&client_b, "b")] {
+            let msg = format!("{name}{i}");
+            let reply: String = ctx
+                .send_and_receive(route![client.clone(), "echoer"], msg.clone())
+                .await?;
+            assert_eq!(reply, msg);
+        }
+    }
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
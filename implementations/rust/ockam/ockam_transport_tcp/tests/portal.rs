@@ -1,12 +1,16 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 
 use ockam_core::compat::rand::random;
-use ockam_core::{route, LocalSourceOnly, Result};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, AllowAll, LocalSourceOnly, Mailboxes, Result};
 use ockam_node::Context;
-use ockam_transport_tcp::TcpTransport;
+use ockam_transport_tcp::{
+    PortalMessage, TcpInletRateLimit, TcpOutletCoalescing, TcpOutletFraming, TcpOutletOptions,
+    TcpTransport,
+};
 
 const LENGTH: usize = 32;
 
@@ -104,3 +108,267 @@ async fn portal__reverse_flow__should_succeed(ctx: &mut Context) -> Result<()> {
 
     Ok(())
 }
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__outlet_close_grace__should_deliver_buffered_data(ctx: &mut Context) -> Result<()> {
+    let payload1 = generate_binary();
+    let payload2 = generate_binary();
+
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    tcp.create_outlet_with_options(
+        "outlet",
+        listener.local_addr().unwrap().to_string(),
+        LocalSourceOnly,
+        TcpOutletOptions::new().with_close_grace(Duration::from_secs(5)),
+    )
+    .await?;
+
+    // The backend only replies once the client is done sending, and takes longer
+    // than the outlet usually waits before closing the connection
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        let mut request = vec![];
+        stream.read_to_end(&mut request).await.unwrap();
+        assert_eq!(request, payload1);
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        write_binary(&mut stream, payload2).await;
+    });
+
+    // Act as the inlet side of the portal
+    let mut inlet_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "inlet",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    inlet_ctx
+        .send(route!["outlet"], PortalMessage::Ping)
+        .await?;
+    let pong = inlet_ctx.receive::<PortalMessage>().await?.take();
+    let outlet_route = pong.return_route();
+    assert!(matches!(pong.body(), PortalMessage::Pong));
+
+    inlet_ctx
+        .send(
+            outlet_route.clone(),
+            PortalMessage::Payload(payload1.to_vec()),
+        )
+        .await?;
+    inlet_ctx
+        .send(outlet_route, PortalMessage::Disconnect)
+        .await?;
+
+    let mut received = vec![];
+    loop {
+        match inlet_ctx.receive::<PortalMessage>().await?.take().body() {
+            PortalMessage::Payload(payload) => received.extend(payload),
+            PortalMessage::Disconnect => break,
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+    assert_eq!(received, payload2);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__inlet_rate_limit__should_throttle_accepts(ctx: &mut Context) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tcp.create_outlet("outlet", bind_address, LocalSourceOnly)
+        .await?;
+
+    // Burst of 2 connections, then one every 200ms
+    let (_, inlet_saddr) = tcp
+        .create_inlet_with_rate_limit(
+            "127.0.0.1:0",
+            route!["outlet"],
+            LocalSourceOnly,
+            TcpInletRateLimit::new(5.0, 2)?,
+        )
+        .await?;
+
+    let start = Instant::now();
+    let mut clients = vec![];
+    for _ in 0..4 {
+        clients.push(TcpStream::connect(inlet_saddr).await.unwrap());
+    }
+
+    let mut accepted_at = vec![];
+    for _ in 0..4 {
+        let _ = listener.accept().await.unwrap();
+        accepted_at.push(start.elapsed());
+    }
+
+    assert!(
+        accepted_at[3] >= Duration::from_millis(350),
+        "Connections past the burst should be throttled, accepted at {:?}",
+        accepted_at
+    );
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// Send `chunks` through a portal whose outlet uses `framing`, and return what
+/// the backend received before the connection was closed
+async fn relay_chunks(
+    ctx: &Context,
+    framing: TcpOutletFraming,
+    chunks: Vec<Vec<u8>>,
+) -> Result<Vec<u8>> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    tcp.create_outlet_with_options(
+        "outlet",
+        listener.local_addr().unwrap().to_string(),
+        LocalSourceOnly,
+        TcpOutletOptions::new().with_framing(framing),
+    )
+    .await?;
+    let (_, inlet_saddr) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], LocalSourceOnly)
+        .await?;
+
+    let backend = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut received = vec![];
+        let _ = stream.read_to_end(&mut received).await;
+        received
+    });
+
+    // Send every chunk in its own portal payload. The connection may be closed by
+    // the outlet along the way, so write errors are expected
+    let mut stream = TcpStream::connect(inlet_saddr).await.unwrap();
+    for chunk in chunks {
+        let _ = stream.write_all(&chunk).await;
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    drop(stream);
+
+    Ok(backend.await.unwrap())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__outlet_framing__valid_frames_pass_in_raw_mode(ctx: &mut Context) -> Result<()> {
+    let chunks = vec![vec![0, 3, b'a', b'b'], vec![b'c', 0], vec![2, b'o', b'k']];
+    let received = relay_chunks(ctx, TcpOutletFraming::Raw, chunks).await?;
+    assert_eq!(received, vec![0, 3, b'a', b'b', b'c', 0, 2, b'o', b'k']);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__outlet_framing__valid_frames_pass_in_validating_mode(
+    ctx: &mut Context,
+) -> Result<()> {
+    let chunks = vec![vec![0, 3, b'a', b'b'], vec![b'c', 0], vec![2, b'o', b'k']];
+    let framing = TcpOutletFraming::length_prefixed(2, 16);
+    let received = relay_chunks(ctx, framing, chunks).await?;
+    assert_eq!(received, vec![0, 3, b'a', b'b', b'c', 0, 2, b'o', b'k']);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__outlet_framing__malformed_frames_are_rejected(ctx: &mut Context) -> Result<()> {
+    // The second frame announces more than the maximum frame length, so neither
+    // it nor anything sent after it reaches the backend
+    let chunks = vec![
+        vec![0, 3, b'a', b'b', b'c'],
+        vec![0xff, 0xff, b'x'],
+        vec![0, 2, b'o', b'k'],
+    ];
+    let framing = TcpOutletFraming::length_prefixed(2, 16);
+    let received = relay_chunks(ctx, framing, chunks).await?;
+    assert_eq!(received, vec![0, 3, b'a', b'b', b'c']);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 10000)]
+async fn portal__outlet_coalescing__bursty_output_is_relayed_at_once(
+    ctx: &mut Context,
+) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    tcp.create_outlet_with_options(
+        "outlet",
+        listener.local_addr().unwrap().to_string(),
+        LocalSourceOnly,
+        TcpOutletOptions::new()
+            .with_coalescing(TcpOutletCoalescing::new(Duration::from_millis(500), 1024)),
+    )
+    .await?;
+
+    // The backend sends a burst of small writes, well within the coalescing window
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        for chunk in [b"ab", b"cd", b"ef", b"gh"] {
+            stream.write_all(chunk).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+    });
+
+    // Act as the inlet side of the portal
+    let mut inlet_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "inlet",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    inlet_ctx
+        .send(route!["outlet"], PortalMessage::Ping)
+        .await?;
+    let pong = inlet_ctx.receive::<PortalMessage>().await?.take();
+    assert!(matches!(pong.body(), PortalMessage::Pong));
+
+    let mut payloads = vec![];
+    loop {
+        match inlet_ctx.receive::<PortalMessage>().await?.take().body() {
+            PortalMessage::Payload(payload) => payloads.push(payload),
+            PortalMessage::Disconnect => break,
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+    assert_eq!(payloads, vec![b"abcdefgh".to_vec()]);
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use core::time::Duration;
+use ockam_core::Result;
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpListenerTrustOptions, TcpTransport};
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn read_timeout__silent_peer__is_disconnected(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_read_timeout(Duration::from_millis(200)),
+        )
+        .await?;
+
+    // The peer connects but never sends anything, nor closes the connection
+    let mut stream = TcpStream::connect(listener_address).await.unwrap();
+    let mut buf = [0u8; 1];
+    let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf))
+        .await
+        .expect("the connection should be dropped");
+    assert!(matches!(read, Ok(0) | Err(_)));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
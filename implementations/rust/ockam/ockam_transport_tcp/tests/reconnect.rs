@@ -0,0 +1,325 @@
+use core::time::Duration;
+use ockam_core::{route, Address, AllowAll, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_tcp::{
+    TcpConnectionTrustOptions, TcpEvent, TcpListenerTrustOptions, TcpOrdering, TcpReconnectPolicy,
+    TcpTransport,
+};
+use tokio::sync::broadcast;
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+/// Wait for the next event matching `predicate`, or give up after a few seconds
+async fn wait_for_event(
+    events: &mut broadcast::Receiver<TcpEvent>,
+    predicate: impl Fn(&TcpEvent) -> bool,
+) -> TcpEvent {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match events.recv().await {
+                Ok(event) if predicate(&event) => return event,
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => panic!("the events stream is closed"),
+            }
+        }
+    })
+    .await
+    .expect("the event should be received")
+}
+
+/// Stop the listener at `listener`, and close the connections it accepted
+async fn kill_listener(transport: &TcpTransport, listener: &Address) -> Result<()> {
+    let connections = transport.registry().get_listener_connections(listener);
+    transport.stop_listener(listener).await?;
+    for connection in connections {
+        transport.disconnect(&connection).await?;
+    }
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn reconnect__listener_restarted__connection_is_reestablished(
+    ctx: &mut Context,
+) -> Result<()> {
+    ctx.start_worker("echoer", Echoer, AllowAll, AllowAll)
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let mut events = transport.registry().subscribe_events();
+    let (listener_address, listener) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+
+    let policy = TcpReconnectPolicy::new(
+        Duration::from_millis(50),
+        Duration::from_millis(200),
+        Duration::ZERO,
+        50,
+    );
+    let connection = transport
+        .connect_reconnecting(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+            policy,
+        )
+        .await?;
+    let reply: String = ctx
+        .send_and_receive(route![connection.clone(), "echoer"], "hello".to_string())
+        .await?;
+    assert_eq!(reply, "hello");
+
+    kill_listener(&transport, &listener).await?;
+    wait_for_event(
+        &mut events,
+        |e| matches!(e, TcpEvent::ConnectionClosed { peer } if *peer == listener_address),
+    )
+    .await;
+
+    // The peer is back after a few failed attempts
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    transport
+        .listen(listener_address.to_string(), TcpListenerTrustOptions::new())
+        .await?;
+    wait_for_event(
+        &mut events,
+        |e| matches!(e, TcpEvent::ConnectionOpened { peer } if *peer == listener_address),
+    )
+    .await;
+
+    // The same route works over the new connection
+    let reply: String = ctx
+        .send_and_receive(
+            route![connection.clone(), "echoer"],
+            "hello again".to_string(),
+        )
+        .await?;
+    assert_eq!(reply, "hello again");
+    assert!(transport
+        .registry()
+        .get_all_sender_workers()
+        .contains(&connection));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+/// Echo messages over a reconnecting connection in strict ordering mode, while
+/// the listener is restarted with `restarted_ordering`
+async fn echo_across_strict_reconnection(
+    ctx: &mut Context,
+    ordering: TcpOrdering,
+    restarted_ordering: TcpOrdering,
+) -> Result<()> {
+    ctx.start_worker("echoer", Echoer, AllowAll, AllowAll)
+        .await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let mut events = transport.registry().subscribe_events();
+    let (listener_address, listener) = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerTrustOptions::new().with_ordering(ordering),
+        )
+        .await?;
+
+    let policy = TcpReconnectPolicy::new(
+        Duration::from_millis(50),
+        Duration::from_millis(200),
+        Duration::ZERO,
+        50,
+    );
+    let connection = transport
+        .connect_reconnecting(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new().with_ordering(TcpOrdering::strict(16)),
+            policy,
+        )
+        .await?;
+    for i in 0..3 {
+        let msg = format!("before {i}");
+        let reply: String = ctx
+            .send_and_receive(route![connection.clone(), "echoer"], msg.clone())
+            .await?;
+        assert_eq!(reply, msg);
+    }
+
+    kill_listener(&transport, &listener).await?;
+    wait_for_event(
+        &mut events,
+        |e| matches!(e, TcpEvent::ConnectionClosed { peer } if *peer == listener_address),
+    )
+    .await;
+
+    transport
+        .listen(
+            listener_address.to_string(),
+            TcpListenerTrustOptions::new().with_ordering(restarted_ordering),
+        )
+        .await?;
+    wait_for_event(
+        &mut events,
+        |e| matches!(e, TcpEvent::ConnectionOpened { peer } if *peer == listener_address),
+    )
+    .await;
+
+    // Messages and replies are still delivered, in order, over the new connection
+    for i in 0..3 {
+        let msg = format!("after {i}");
+        let reply: String = ctx
+            .send_and_receive(route![connection.clone(), "echoer"], msg.clone())
+            .await?;
+        assert_eq!(reply, msg);
+    }
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn reconnect__strict_ordering__sequence_is_carried_across_reconnection(
+    ctx: &mut Context,
+) -> Result<()> {
+    // The listener is restarted with the same options, so it still knows the stream
+    let ordering = TcpOrdering::strict(16);
+    echo_across_strict_reconnection(ctx, ordering.clone(), ordering).await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn reconnect__strict_ordering__sequence_restarts_with_a_new_listener(
+    ctx: &mut Context,
+) -> Result<()> {
+    // The restarted listener lost the stream, both sides agree to start it over
+    echo_across_strict_reconnection(ctx, TcpOrdering::strict(16), TcpOrdering::strict(16)).await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn reconnect__peer_never_back__gives_up_after_max_retries(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, listener) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+
+    let policy = TcpReconnectPolicy::new(
+        Duration::from_millis(10),
+        Duration::from_millis(20),
+        Duration::ZERO,
+        3,
+    );
+    let connection = transport
+        .connect_reconnecting(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new(),
+            policy,
+        )
+        .await?;
+
+    kill_listener(&transport, &listener).await?;
+
+    let mut stopped = false;
+    for _ in 0..100 {
+        if !transport
+            .registry()
+            .get_all_sender_workers()
+            .contains(&connection)
+        {
+            stopped = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(stopped, "the connection should be given up");
+
+    let errors = transport.registry().get_connection_errors();
+    assert!(errors.iter().any(
+        |e| e.peer() == listener_address.to_string() && e.reason().contains("after 3 attempts")
+    ));
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn reconnect__closed_on_read_timeout__peer_is_not_redialed(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let mut events = transport.registry().subscribe_events();
+    let (listener_address, _listener) = transport
+        .listen("127.0.0.1:0", TcpListenerTrustOptions::new())
+        .await?;
+
+    let policy = TcpReconnectPolicy::new(
+        Duration::from_millis(10),
+        Duration::from_millis(20),
+        Duration::ZERO,
+        50,
+    );
+    // The listener never sends anything, the connection is closed after its read timeout
+    let connection = transport
+        .connect_reconnecting(
+            listener_address.to_string(),
+            TcpConnectionTrustOptions::new().with_read_timeout(Duration::from_millis(200)),
+            policy,
+        )
+        .await?;
+    wait_for_event(
+        &mut events,
+        |e| matches!(e, TcpEvent::ConnectionClosed { peer } if *peer == listener_address),
+    )
+    .await;
+
+    let mut stopped = false;
+    for _ in 0..100 {
+        if !transport
+            .registry()
+            .get_all_sender_workers()
+            .contains(&connection)
+        {
+            stopped = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(stopped, "the connection should be stopped");
+
+    // The connection closed on purpose is not opened again
+    let reopened = tokio::time::timeout(Duration::from_millis(500), async {
+        loop {
+            match events.recv().await {
+                Ok(TcpEvent::ConnectionOpened { peer }) if peer == listener_address => break,
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
+    .await;
+    assert!(reopened.is_err(), "the peer should not be redialed");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use core::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use ockam_core::{route, LocalSourceOnly, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{TcpInletAffinity, TcpInletRouteGroup, TcpTransport};
+
+/// Start a backend answering each connection with `marker`, behind the outlet `outlet`
+async fn start_backend(tcp: &TcpTransport, outlet: &str, marker: u8) -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let bind_address = listener.local_addr().unwrap().to_string();
+    tcp.create_outlet(outlet, bind_address, LocalSourceOnly)
+        .await?;
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                stream.write_all(&[marker]).await.unwrap();
+                // Keep the connection open until the client is done
+                let mut buf = [0u8; 1];
+                let _ = stream.read(&mut buf).await;
+            });
+        }
+    });
+
+    Ok(())
+}
+
+/// Connect to the inlet and return the marker of the backend serving the connection
+async fn connect(inlet_addr: &str) -> (TcpStream, u8) {
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let mut marker = [0u8; 1];
+    stream.read_exact(&mut marker).await.unwrap();
+    (stream, marker[0])
+}
+
+async fn backends_of_two_connections(
+    ctx: &mut Context,
+    affinity: TcpInletAffinity,
+) -> Result<(u8, u8)> {
+    let tcp = TcpTransport::create(ctx).await?;
+    start_backend(&tcp, "outlet_a", b'a').await?;
+    start_backend(&tcp, "outlet_b", b'b').await?;
+
+    let route_group =
+        TcpInletRouteGroup::new(vec![route!["outlet_a"], route!["outlet_b"]], affinity)?;
+    let (_, inlet_saddr) = tcp
+        .create_inlet_with_route_group("127.0.0.1:0", route_group, LocalSourceOnly)
+        .await?;
+
+    // Wait till listener is up
+    tokio::time::sleep(Duration::from_millis(250)).await;
+
+    let inlet_addr = inlet_saddr.to_string();
+    let (_stream1, backend1) = connect(&inlet_addr).await;
+    let (_stream2, backend2) = connect(&inlet_addr).await;
+
+    Ok((backend1, backend2))
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn route_group__without_affinity__should_spread_connections(ctx: &mut Context) -> Result<()> {
+    let (backend1, backend2) = backends_of_two_connections(ctx, TcpInletAffinity::None).await?;
+    assert_ne!(backend1, backend2);
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn route_group__client_ip_affinity__should_keep_connections_on_one_route(
+    ctx: &mut Context,
+) -> Result<()> {
+    let (backend1, backend2) =
+        backends_of_two_connections(ctx, TcpInletAffinity::ByClientIp).await?;
+    assert_eq!(backend1, backend2);
+
+    ctx.stop().await
+}
@@ -0,0 +1,160 @@
+use core::time::Duration;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{route, AllowAll, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_tcp::rustls::{
+    Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig,
+};
+use ockam_transport_tcp::{TcpConnectionTrustOptions, TcpListenerTrustOptions, TcpTransport};
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+/// Return a client config trusting a self-signed certificate for `localhost`,
+/// and a server config presenting it
+fn tls_configs() -> (Arc<ClientConfig>, Arc<ServerConfig>) {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_der = Certificate(cert.serialize_der().unwrap());
+    let key_der = PrivateKey(cert.serialize_private_key_der());
+
+    let mut roots = RootCertStore::empty();
+    roots.add(&cert_der).unwrap();
+    let client = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let server = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert_der], key_der)
+        .unwrap();
+
+    (Arc::new(client), Arc::new(server))
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tls__connect_and_listen__messages_are_exchanged(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer, AllowAll, AllowAll)
+        .await?;
+    let (client_config, server_config) = tls_configs();
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen_tls("127.0.0.1:0", server_config, TcpListenerTrustOptions::new())
+        .await?;
+
+    let connection = transport
+        .connect_tls(
+            format!("localhost:{}", listener_address.port()),
+            client_config,
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+
+    let msg = "Hello over TLS".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![connection, "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tls__untrusted_certificate__fails_to_connect(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer, AllowAll, AllowAll)
+        .await?;
+    let (trusted_config, server_config) = tls_configs();
+    // Trusts another certificate than the one of the server
+    let (untrusted_config, _) = tls_configs();
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen_tls("127.0.0.1:0", server_config, TcpListenerTrustOptions::new())
+        .await?;
+    let peer = format!("localhost:{}", listener_address.port());
+
+    let res = transport
+        .connect_tls(&peer, untrusted_config, TcpConnectionTrustOptions::new())
+        .await;
+    assert!(res.is_err());
+
+    let mut reported = false;
+    for _ in 0..100 {
+        if transport.registry().get_connection_errors().len() == 2 {
+            reported = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(reported, "Both ends should report the failed handshake");
+
+    // The listener keeps accepting connections after a failed handshake
+    let connection = transport
+        .connect_tls(&peer, trusted_config, TcpConnectionTrustOptions::new())
+        .await?;
+    let msg = "Hello again".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![connection, "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn tls__stalled_handshake__other_connections_are_accepted(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", Echoer, AllowAll, AllowAll)
+        .await?;
+    let (client_config, server_config) = tls_configs();
+
+    let transport = TcpTransport::create(ctx).await?;
+    let (listener_address, _) = transport
+        .listen_tls("127.0.0.1:0", server_config, TcpListenerTrustOptions::new())
+        .await?;
+
+    // Opens a socket and never starts the handshake
+    let _stalled = tokio::net::TcpStream::connect(listener_address)
+        .await
+        .unwrap();
+
+    // Served well before the stalled handshake times out
+    let connection = transport
+        .connect_tls(
+            format!("localhost:{}", listener_address.port()),
+            client_config,
+            TcpConnectionTrustOptions::new(),
+        )
+        .await?;
+    let msg = "Hello over TLS".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![connection, "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use ockam_core::{route, Address, AllowAll, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_uds::{UdsTransport, UDS};
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn send_receive__connected_socket__echoes_the_message(ctx: &mut Context) -> Result<()> {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("echoer.sock");
+    let socket = socket.to_str().unwrap();
+
+    ctx.start_worker("echoer", Echoer, AllowAll, AllowAll)
+        .await?;
+
+    let transport = UdsTransport::create(ctx).await?;
+    transport.listen(socket).await?;
+    let connection = transport.connect(socket).await?;
+
+    let msg = "Hello over a Unix domain socket".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![connection, "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn send_receive__socket_path_route__connects_lazily(ctx: &mut Context) -> Result<()> {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("echoer.sock");
+    let socket = socket.to_str().unwrap();
+
+    ctx.start_worker("echoer", Echoer, AllowAll, AllowAll)
+        .await?;
+
+    let transport = UdsTransport::create(ctx).await?;
+    transport.listen(socket).await?;
+
+    let msg = "Hello again".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![Address::new(UDS, socket), "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
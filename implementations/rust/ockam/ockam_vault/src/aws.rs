@@ -224,9 +224,34 @@ enum Error {
     UnsupportedKeyType,
 }
 
+impl Error {
+    /// Whether AWS KMS couldn't be reached, in which case the request may succeed later
+    fn is_unavailable(&self) -> bool {
+        match self {
+            Error::Create(error) => is_transient(error),
+            Error::Sign { error, .. } => is_transient(error),
+            Error::Verify { error, .. } => is_transient(error),
+            Error::Export { error, .. } => is_transient(error),
+            Error::Delete { error, .. } => is_transient(error),
+            Error::MissingKeyId | Error::MissingSignature | Error::UnsupportedKeyType => false,
+        }
+    }
+}
+
+fn is_transient<E, R>(error: &SdkError<E, R>) -> bool {
+    matches!(
+        error,
+        SdkError::TimeoutError(_) | SdkError::DispatchFailure(_)
+    )
+}
+
 impl From<Error> for ockam_core::Error {
     fn from(e: Error) -> Self {
         use ockam_core::errcode::{Kind, Origin};
+        // Reported like `VaultError::Unavailable`, so that callers may retry
+        if e.is_unavailable() {
+            return ockam_core::Error::new(Origin::Vault, Kind::Timeout, e);
+        }
         ockam_core::Error::new(Origin::Other, Kind::Io, e)
     }
 }
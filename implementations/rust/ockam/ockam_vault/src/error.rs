@@ -44,6 +44,9 @@ pub enum VaultError {
     StorageError,
     /// Invalid Storage data
     InvalidStorageData,
+    /// The vault can't be reached for now, e.g. a remote vault, and the operation
+    /// may succeed if retried later
+    Unavailable,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -68,6 +71,7 @@ impl core::fmt::Display for VaultError {
             Self::InvalidSecretAttributes => write!(f, "invalid secret attributes"),
             Self::StorageError => write!(f, "invalid storage"),
             Self::InvalidStorageData => write!(f, "invalid storage data"),
+            Self::Unavailable => write!(f, "vault is unavailable"),
         }
     }
 }
@@ -85,6 +89,7 @@ impl From<VaultError> for Error {
             | InvalidPrivateKeyLen
             | InvalidX25519SecretLength => Kind::Misuse,
             UnknownEcdhKeyType | EntryNotFound(_) | SecretNotFound => Kind::NotFound,
+            Unavailable => Kind::Timeout,
             _ => Kind::Invalid,
         };
 